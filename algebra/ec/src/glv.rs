@@ -0,0 +1,143 @@
+//! Scalar multiplication with an efficient endomorphism, after Gallant, Lambert
+//! and Vanstone.
+//!
+//! On a curve `y^2 = x^3 + b` over a field with a cube root of unity `omega`,
+//! `phi(x, y) = (omega x, y)` is an endomorphism, which acts on a prime-order
+//! group as multiplication by a cube root of unity `lambda` among the scalars.
+//! A scalar `k` splits as `k_1 + k_2 lambda`, with `k_1` and `k_2` about half
+//! as long, so `k P = k_1 P + k_2 phi(P)` takes half the doublings: an MSM over
+//! `n` points becomes one over `2n` points with half the windows.
+use ark_ff::{BigInteger, FpParameters, PrimeField, Zero};
+use ark_std::{vec, vec::Vec};
+
+use crate::{
+    models::SWModelParameters,
+    msm::VariableBaseMSM,
+    short_weierstrass_jacobian::{GroupAffine, GroupProjective},
+    AffineCurve, ProjectiveCurve,
+};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// The parameters of a curve's endomorphism.
+pub trait GLVParameters: SWModelParameters + Sized {
+    /// A cube root of unity among the base field: `phi(x, y) = (OMEGA x, y)`.
+    const OMEGA: Self::BaseField;
+    /// The cube root of unity among the scalars that `phi` multiplies by.
+    const LAMBDA: Self::ScalarField;
+    /// A short basis `(a_1, b_1), (a_2, b_2)` of the lattice of `(a, b)` with
+    /// `a + b LAMBDA = 0`, with determinant the scalar field's modulus `r`.
+    const BASIS: [(Self::ScalarField, Self::ScalarField); 2];
+    /// `b_2 2^w / r` and `-b_1 2^w / r`, rounded, as little-endian limbs, where
+    /// `w` is the bit length of a scalar's representation. They give a
+    /// scalar's coordinates in [Self::BASIS].
+    const ROUNDING: [&'static [u64]; 2];
+
+    /// `phi(p)`, which is `LAMBDA p`.
+    fn endomorphism(p: &GroupAffine<Self>) -> GroupAffine<Self> {
+        GroupAffine::new(p.x * Self::OMEGA, p.y, p.infinity)
+    }
+
+    /// Split `k` into `k_1 + k_2 LAMBDA`, with `k_1` and `k_2` about half as
+    /// long as `k`, each as whether it is negative, and its magnitude.
+    fn scalar_decomposition(
+        k: &Self::ScalarField,
+    ) -> [(bool, <Self::ScalarField as PrimeField>::BigInt); 2] {
+        let repr = k.into_repr();
+        let c_1 = rounded_product::<Self::ScalarField>(repr.as_ref(), Self::ROUNDING[0]);
+        let c_2 = rounded_product::<Self::ScalarField>(repr.as_ref(), Self::ROUNDING[1]);
+        let [(a_1, b_1), (a_2, b_2)] = Self::BASIS;
+        // (k, 0) less the nearby lattice point c_1 (a_1, b_1) + c_2 (a_2, b_2)
+        let k_1 = *k - c_1 * a_1 - c_2 * a_2;
+        let k_2 = -(c_1 * b_1) - c_2 * b_2;
+        [signed(k_1), signed(k_2)]
+    }
+}
+
+/// `k g / 2^w`, rounded down, where `w` is the bit length of `k`: a scalar, if
+/// `g` is below `2^w`.
+fn rounded_product<F: PrimeField>(k: &[u64], g: &[u64]) -> F {
+    assert!(g.len() <= k.len());
+    let mut product = vec![0u64; 2 * k.len()];
+    for (i, k_i) in k.iter().enumerate() {
+        let mut carry = 0u128;
+        for (j, g_j) in g.iter().enumerate() {
+            let t = product[i + j] as u128 + (*k_i as u128) * (*g_j as u128) + carry;
+            product[i + j] = t as u64;
+            carry = t >> 64;
+        }
+        product[i + g.len()] = carry as u64;
+    }
+    let mut repr = F::BigInt::default();
+    repr.as_mut().copy_from_slice(&product[k.len()..]);
+    F::from_repr(repr).unwrap()
+}
+
+/// Whether `s`, read as an integer between `-r/2` and `r/2`, is negative, and
+/// its magnitude.
+fn signed<F: PrimeField>(s: F) -> (bool, F::BigInt) {
+    if s.into_repr() > F::Params::MODULUS_MINUS_ONE_DIV_TWO {
+        (true, (-s).into_repr())
+    } else {
+        (false, s.into_repr())
+    }
+}
+
+/// `p` or `-p`.
+fn with_sign<P: SWModelParameters>(negative: bool, p: GroupAffine<P>) -> GroupAffine<P> {
+    if negative {
+        -p
+    } else {
+        p
+    }
+}
+
+/// `k p`, with the endomorphism: a joint double-and-add over the halves of `k`.
+///
+/// `p` must be in the prime-order subgroup, where `k` may be reduced.
+pub fn glv_mul<P: GLVParameters>(
+    p: &GroupAffine<P>,
+    k: <P::ScalarField as PrimeField>::BigInt,
+) -> GroupProjective<P> {
+    let k = P::ScalarField::from_repr(k)
+        .unwrap_or_else(|| P::ScalarField::from_le_bytes_mod_order(&k.to_bytes_le()));
+    let [(neg_1, k_1), (neg_2, k_2)] = P::scalar_decomposition(&k);
+    let p_1 = with_sign(neg_1, *p);
+    let p_2 = with_sign(neg_2, P::endomorphism(p));
+    let both = (p_1.into_projective() + p_2.into_projective()).into_affine();
+    let bits = k_1.num_bits().max(k_2.num_bits()) as usize;
+    let mut res = GroupProjective::<P>::zero();
+    for i in (0..bits).rev() {
+        res.double_in_place();
+        match (k_1.get_bit(i), k_2.get_bit(i)) {
+            (true, true) => res.add_assign_mixed(&both),
+            (true, false) => res.add_assign_mixed(&p_1),
+            (false, true) => res.add_assign_mixed(&p_2),
+            (false, false) => {},
+        }
+    }
+    res
+}
+
+/// `sum_i s_i P_i`, with the endomorphism: a variable-base MSM over the points
+/// and their images, with the halves of the scalars.
+pub fn glv_msm<P: GLVParameters>(
+    bases: &[GroupAffine<P>],
+    scalars: &[<P::ScalarField as PrimeField>::BigInt],
+) -> GroupProjective<P> {
+    let size = ark_std::cmp::min(bases.len(), scalars.len());
+    let (bases, scalars): (Vec<_>, Vec<_>) = cfg_iter!(bases[..size])
+        .zip(&scalars[..size])
+        .flat_map(|(base, scalar)| {
+            let k = P::ScalarField::from_repr(*scalar).unwrap();
+            let [(neg_1, k_1), (neg_2, k_2)] = P::scalar_decomposition(&k);
+            vec![
+                (with_sign(neg_1, *base), k_1),
+                (with_sign(neg_2, P::endomorphism(base)), k_2),
+            ]
+        })
+        .unzip();
+    let bits = scalars.iter().map(|s| s.num_bits()).max().unwrap_or(0);
+    VariableBaseMSM::multi_scalar_mul_with_bits(&bases, &scalars, bits.max(1) as usize)
+}
@@ -0,0 +1,170 @@
+//! Hashing to short Weierstrass curves, after
+//! [draft-irtf-cfrg-hash-to-curve][h2c]: with `expand_message_xmd`, and the
+//! simplified SWU map to an isogenous curve.
+//!
+//! Simplified SWU needs a curve with `A` and `B` nonzero. So for a curve with
+//! `A = 0` (like the pasta curves), it maps to an isogenous curve with `A`
+//! nonzero ([SWUParameters]), and the isogeny ([IsogenyParameters]) carries the
+//! point over.
+//!
+//! [h2c]: https://datatracker.ietf.org/doc/draft-irtf-cfrg-hash-to-curve/
+use ark_ff::{BigInteger, Field, FpParameters, One, PrimeField, SquareRootField, Zero};
+use ark_std::{vec, vec::Vec};
+use digest::{generic_array::typenum::Unsigned, BlockInput, Digest};
+
+use crate::{
+    models::SWModelParameters,
+    short_weierstrass_jacobian::{GroupAffine, GroupProjective},
+    AffineCurve, ProjectiveCurve,
+};
+
+/// A curve `y^2 = x^3 + A x + B`, with `A` and `B` nonzero, that simplified SWU
+/// maps to.
+pub trait SWUParameters: SWModelParameters {
+    /// A non-square `Z`, chosen as the draft prescribes.
+    const Z: Self::BaseField;
+}
+
+/// A curve that is the image of a curve with [SWUParameters] under an isogeny.
+pub trait IsogenyParameters: SWModelParameters + Sized {
+    /// The curve the isogeny maps from.
+    type Domain: SWUParameters<BaseField = Self::BaseField, ScalarField = Self::ScalarField>;
+    /// The isogeny is `(x, y) -> (X_NUM(x) / X_DEN(x), y Y_NUM(x) / Y_DEN(x))`,
+    /// with the polynomials' coefficients in order of increasing degree.
+    const X_NUM: &'static [Self::BaseField];
+    const X_DEN: &'static [Self::BaseField];
+    const Y_NUM: &'static [Self::BaseField];
+    const Y_DEN: &'static [Self::BaseField];
+
+    /// The image of `p` under the isogeny.
+    fn isogeny_map(p: &GroupAffine<Self::Domain>) -> GroupAffine<Self> {
+        let eval = |coeffs: &[Self::BaseField]| {
+            coeffs
+                .iter()
+                .rev()
+                .fold(Self::BaseField::zero(), |acc, c| acc * p.x + c)
+        };
+        let (x_den, y_den) = (eval(Self::X_DEN), eval(Self::Y_DEN));
+        // The isogeny's kernel maps to infinity.
+        if p.infinity || x_den.is_zero() || y_den.is_zero() {
+            return GroupAffine::zero();
+        }
+        let x = eval(Self::X_NUM) / x_den;
+        let y = p.y * eval(Self::Y_NUM) / y_den;
+        GroupAffine::new(x, y, false)
+    }
+}
+
+/// `len` uniformly random bytes from `msg`, for domain separation tag `dst`
+/// (`expand_message_xmd`, with the hash `D`).
+pub fn expand_message_xmd<D: Digest + BlockInput>(msg: &[u8], dst: &[u8], len: usize) -> Vec<u8> {
+    let b_len = <D as Digest>::output_size();
+    let ell = (len + b_len - 1) / b_len;
+    assert!(
+        ell <= 255 && len <= 0xffff && dst.len() <= 255,
+        "expand_message_xmd: too long"
+    );
+    let dst_prime = [dst, &[dst.len() as u8]].concat();
+    let b_0 = D::new()
+        .chain(vec![0u8; D::BlockSize::to_usize()])
+        .chain(msg)
+        .chain((len as u16).to_be_bytes())
+        .chain([0u8])
+        .chain(&dst_prime)
+        .finalize();
+    let mut b_i = D::new()
+        .chain(&b_0)
+        .chain([1u8])
+        .chain(&dst_prime)
+        .finalize();
+    let mut uniform = b_i.to_vec();
+    for i in 2..=ell {
+        let xored: Vec<u8> = b_0.iter().zip(b_i.iter()).map(|(a, b)| a ^ b).collect();
+        b_i = D::new()
+            .chain(xored)
+            .chain([i as u8])
+            .chain(&dst_prime)
+            .finalize();
+        uniform.extend_from_slice(&b_i);
+    }
+    uniform.truncate(len);
+    uniform
+}
+
+/// `count` field elements from `msg`, for domain separation tag `dst`
+/// (`hash_to_field`, for 128-bit security).
+pub fn hash_to_field<F: PrimeField, D: Digest + BlockInput>(
+    msg: &[u8],
+    dst: &[u8],
+    count: usize,
+) -> Vec<F> {
+    let l = (F::Params::MODULUS_BITS as usize + 128 + 7) / 8;
+    expand_message_xmd::<D>(msg, dst, l * count)
+        .chunks(l)
+        .map(F::from_be_bytes_mod_order)
+        .collect()
+}
+
+/// Whether `a` is "negative": the parity of its canonical representative
+/// (`sgn0`).
+fn sgn0<F: PrimeField>(a: F) -> bool {
+    a.into_repr().is_odd()
+}
+
+/// The simplified SWU map from the field to the curve.
+pub fn map_to_curve<P: SWUParameters>(u: P::BaseField) -> GroupAffine<P>
+where
+    P::BaseField: PrimeField,
+{
+    let (a, b, z) = (P::COEFF_A, P::COEFF_B, P::Z);
+    let g = |x: P::BaseField| x.square() * x + a * x + b;
+    let z_u2 = z * u.square();
+    // 1 / (Z^2 u^4 + Z u^2), or zero
+    let tv1 = (z_u2.square() + z_u2).inverse();
+    let x_1 = match tv1 {
+        Some(tv1) => -b / a * (P::BaseField::one() + tv1),
+        None => b / (z * a),
+    };
+    let x_2 = z_u2 * x_1;
+    let (x, y) = match g(x_1).sqrt() {
+        Some(y) => (x_1, y),
+        None => (
+            x_2,
+            g(x_2)
+                .sqrt()
+                .expect("g(x_2) is a square when g(x_1) is not"),
+        ),
+    };
+    let y = if sgn0(u) == sgn0(y) { y } else { -y };
+    GroupAffine::new(x, y, false)
+}
+
+/// A point on the curve from `msg`, for domain separation tag `dst`, with a
+/// distribution indistinguishable from uniform (`hash_to_curve`).
+pub fn hash_to_curve<P: IsogenyParameters, D: Digest + BlockInput>(
+    msg: &[u8],
+    dst: &[u8],
+) -> GroupAffine<P>
+where
+    P::BaseField: PrimeField,
+{
+    let u = hash_to_field::<P::BaseField, D>(msg, dst, 2);
+    let q_0 = P::isogeny_map(&map_to_curve::<P::Domain>(u[0]));
+    let q_1 = P::isogeny_map(&map_to_curve::<P::Domain>(u[1]));
+    let r: GroupProjective<P> = q_0.into_projective() + q_1.into_projective();
+    r.into_affine().mul_by_cofactor()
+}
+
+/// A point on the curve from `msg`, for domain separation tag `dst`, with a
+/// nonuniform distribution: cheaper than [hash_to_curve], for uses that allow
+/// it (`encode_to_curve`).
+pub fn encode_to_curve<P: IsogenyParameters, D: Digest + BlockInput>(
+    msg: &[u8],
+    dst: &[u8],
+) -> GroupAffine<P>
+where
+    P::BaseField: PrimeField,
+{
+    let u = hash_to_field::<P::BaseField, D>(msg, dst, 1);
+    P::isogeny_map(&map_to_curve::<P::Domain>(u[0])).mul_by_cofactor()
+}
@@ -44,6 +44,10 @@ pub mod group;
 
 pub mod msm;
 
+pub mod glv;
+
+pub mod hashing;
+
 pub trait PairingEngine: Sized + 'static + Copy + Debug + Sync + Send + Eq + PartialEq {
     /// This is the scalar field of the G1/G2 groups.
     type Fr: PrimeField + SquareRootField;
@@ -313,6 +317,16 @@ pub trait AffineCurve:
         end_timer!(msm_timer);
         product
     }
+    /// Perform several multi-scalar multiplications, each with its own bases
+    /// and scalars.
+    ///
+    /// By default, one after another, with [AffineCurve::multi_scalar_mul];
+    /// curves may combine them into one pass.
+    fn batch_multi_scalar_mul(msms: &[(&[Self], &[Self::ScalarField])]) -> Vec<Self::Projective> {
+        msms.iter()
+            .map(|(bases, scalars)| Self::multi_scalar_mul(bases, scalars))
+            .collect()
+    }
     fn scalar_mul<S: Into<Self::ScalarField>>(&self, other: S)
         -> Self::Projective {
         self.mul(other.into().into_repr())
@@ -33,6 +33,34 @@ pub trait SWModelParameters: ModelParameters {
         copy += &Self::COEFF_B;
         copy
     }
+
+    /// Computes `s P` for a point of this curve (see
+    /// [AffineCurve::mul](crate::AffineCurve::mul)).
+    ///
+    /// Curves with an efficient endomorphism override this with [crate::glv::glv_mul].
+    fn mul_affine(
+        base: &short_weierstrass_jacobian::GroupAffine<Self>,
+        scalar: <Self::ScalarField as PrimeField>::BigInt,
+    ) -> short_weierstrass_jacobian::GroupProjective<Self>
+    where
+        Self: Sized,
+    {
+        base.mul_bits(ark_ff::BitIteratorBE::new(scalar))
+    }
+
+    /// Computes `sum_i s_i P_i` over points of this curve (see
+    /// [AffineCurve::multi_scalar_mul](crate::AffineCurve::multi_scalar_mul)).
+    ///
+    /// Curves with an efficient endomorphism override this with [crate::glv::glv_msm].
+    fn multi_scalar_mul(
+        bases: &[short_weierstrass_jacobian::GroupAffine<Self>],
+        scalars: &[<Self::ScalarField as PrimeField>::BigInt],
+    ) -> short_weierstrass_jacobian::GroupProjective<Self>
+    where
+        Self: Sized,
+    {
+        crate::msm::VariableBaseMSM::multi_scalar_mul(bases, scalars)
+    }
 }
 
 pub trait TEModelParameters: ModelParameters {
@@ -198,8 +198,7 @@ impl<P: Parameters> AffineCurve for GroupAffine<P> {
 
     #[inline]
     fn mul<S: Into<<Self::ScalarField as PrimeField>::BigInt>>(&self, by: S) -> GroupProjective<P> {
-        let bits = BitIteratorBE::new(by.into());
-        self.mul_bits(bits)
+        P::mul_affine(self, by.into())
     }
 
     #[inline]
@@ -210,6 +209,36 @@ impl<P: Parameters> AffineCurve for GroupAffine<P> {
     fn mul_by_cofactor_inv(&self) -> Self {
         self.mul(P::COFACTOR_INV).into()
     }
+
+    fn multi_scalar_mul(bases: &[Self], scalars: &[Self::ScalarField]) -> Self::Projective {
+        let msm_timer = start_timer!(|| "Base MSM");
+        let bigint_scalars = cfg_iter!(scalars)
+            .map(|s| s.into_repr())
+            .collect::<Vec<_>>();
+        let product = P::multi_scalar_mul(bases, &bigint_scalars);
+        end_timer!(msm_timer);
+        product
+    }
+
+    /// One pass of [VariableBaseMSM::multi_scalar_mul_batch], which does not
+    /// use any endomorphism the curve's own MSM does.
+    ///
+    /// [VariableBaseMSM::multi_scalar_mul_batch]: crate::msm::VariableBaseMSM::multi_scalar_mul_batch
+    fn batch_multi_scalar_mul(msms: &[(&[Self], &[Self::ScalarField])]) -> Vec<Self::Projective> {
+        let msm_timer = start_timer!(|| format!("Base MSMs: {}", msms.len()));
+        let bigint_scalars: Vec<Vec<_>> = msms
+            .iter()
+            .map(|(_, scalars)| cfg_iter!(scalars).map(|s| s.into_repr()).collect())
+            .collect();
+        let msms: Vec<_> = msms
+            .iter()
+            .zip(&bigint_scalars)
+            .map(|((bases, _), scalars)| (*bases, &scalars[..]))
+            .collect();
+        let products = crate::msm::VariableBaseMSM::multi_scalar_mul_batch(&msms);
+        end_timer!(msm_timer);
+        products
+    }
 }
 
 impl<P: Parameters> Neg for GroupAffine<P> {
@@ -13,95 +13,154 @@ impl VariableBaseMSM {
         bases: &[G],
         scalars: &[<G::ScalarField as PrimeField>::BigInt],
     ) -> G::Projective {
-        let size = ark_std::cmp::min(bases.len(), scalars.len());
-        let scalars = &scalars[..size];
-        let bases = &bases[..size];
-        let scalars_and_bases_iter = scalars.iter().zip(bases).filter(|(s, _)| !s.is_zero());
-
-        let c = if size < 32 {
-            3
-        } else {
-            super::ln_without_floats(size) + 2
-        };
-
         let num_bits = <G::ScalarField as PrimeField>::Params::MODULUS_BITS as usize;
-        let fr_one = G::ScalarField::one().into_repr();
+        Self::multi_scalar_mul_with_bits(bases, scalars, num_bits)
+    }
 
-        let zero = G::Projective::zero();
+    /// As [VariableBaseMSM::multi_scalar_mul], for scalars of at most
+    /// `num_bits` bits: short scalars need fewer windows.
+    pub fn multi_scalar_mul_with_bits<G: AffineCurve>(
+        bases: &[G],
+        scalars: &[<G::ScalarField as PrimeField>::BigInt],
+        num_bits: usize,
+    ) -> G::Projective {
+        let c = window_size(bases.len().min(scalars.len()));
         let window_starts: Vec<_> = (0..num_bits).step_by(c).collect();
 
         // Each window is of size `c`.
         // We divide up the bits 0..num_bits into windows of size `c`, and
         // in parallel process each such window.
         let window_sums: Vec<_> = ark_std::cfg_into_iter!(window_starts)
-            .map(|w_start| {
-                let mut res = zero;
-                // We don't need the "zero" bucket, so we only have 2^c - 1 buckets.
-                let mut buckets = vec![zero; (1 << c) - 1];
-                // This clone is cheap, because the iterator contains just a
-                // pointer and an index into the original vectors.
-                scalars_and_bases_iter.clone().for_each(|(&scalar, base)| {
-                    if scalar == fr_one {
-                        // We only process unit scalars once in the first window.
-                        if w_start == 0 {
-                            res.add_assign_mixed(base);
-                        }
-                    } else {
-                        let mut scalar = scalar;
-
-                        // We right-shift by w_start, thus getting rid of the
-                        // lower bits.
-                        scalar.divn(w_start as u32);
-
-                        // We mod the remaining bits by 2^{window size}, thus taking `c` bits.
-                        let scalar = scalar.as_ref()[0] % (1 << c);
-
-                        // If the scalar is non-zero, we update the corresponding
-                        // bucket.
-                        // (Recall that `buckets` doesn't have a zero bucket.)
-                        if scalar != 0 {
-                            buckets[(scalar - 1) as usize].add_assign_mixed(base);
-                        }
-                    }
-                });
-
-                // Compute sum_{i in 0..num_buckets} (sum_{j in i..num_buckets} bucket[j])
-                // This is computed below for b buckets, using 2b curve additions.
-                //
-                // We could first normalize `buckets` and then use mixed-addition
-                // here, but that's slower for the kinds of groups we care about
-                // (Short Weierstrass curves and Twisted Edwards curves).
-                // In the case of Short Weierstrass curves,
-                // mixed addition saves ~4 field multiplications per addition.
-                // However normalization (with the inversion batched) takes ~6
-                // field multiplications per element,
-                // hence batch normalization is a slowdown.
-
-                // `running_sum` = sum_{j in i..num_buckets} bucket[j],
-                // where we iterate backward from i = num_buckets to 0.
-                let mut running_sum = G::Projective::zero();
-                buckets.into_iter().rev().for_each(|b| {
-                    running_sum += &b;
-                    res += &running_sum;
-                });
-                res
-            })
+            .map(|w_start| window_sum(bases, scalars, c, w_start))
             .collect();
+        combine_windows::<G>(&window_sums, c)
+    }
+
+    /// Several multi-scalar multiplications, each with its own bases and
+    /// scalars, at once.
+    ///
+    /// The windows of all of them are summed in one parallel pass, so a batch
+    /// of small MSMs keeps every thread busy, rather than each MSM waiting
+    /// on its slowest window in turn.
+    #[allow(clippy::type_complexity)]
+    pub fn multi_scalar_mul_batch<G: AffineCurve>(
+        msms: &[(&[G], &[<G::ScalarField as PrimeField>::BigInt])],
+    ) -> Vec<G::Projective> {
+        let num_bits = <G::ScalarField as PrimeField>::Params::MODULUS_BITS as usize;
+        let sizes: Vec<usize> = msms
+            .iter()
+            .map(|(bases, scalars)| window_size(bases.len().min(scalars.len())))
+            .collect();
+        // Every window of every MSM, as (MSM, window size, window start)
+        let windows: Vec<(usize, usize, usize)> = sizes
+            .iter()
+            .enumerate()
+            .flat_map(|(k, &c)| (0..num_bits).step_by(c).map(move |w_start| (k, c, w_start)))
+            .collect();
+        let window_sums: Vec<_> = ark_std::cfg_iter!(windows)
+            .map(|&(k, c, w_start)| window_sum(msms[k].0, msms[k].1, c, w_start))
+            .collect();
+        let mut window_sums = &window_sums[..];
+        sizes
+            .into_iter()
+            .map(|c| {
+                let (sums, rest) = window_sums.split_at(num_bits.div_ceil(c));
+                window_sums = rest;
+                combine_windows::<G>(sums, c)
+            })
+            .collect()
+    }
+}
 
-        // We store the sum for the lowest window.
-        let lowest = *window_sums.first().unwrap();
-
-        // We're traversing windows from high to low.
-        lowest
-            + &window_sums[1..]
-                .iter()
-                .rev()
-                .fold(zero, |mut total, sum_i| {
-                    total += sum_i;
-                    for _ in 0..c {
-                        total.double_in_place();
-                    }
-                    total
-                })
+/// The window size for an MSM of `size` terms.
+fn window_size(size: usize) -> usize {
+    if size < 32 {
+        3
+    } else {
+        super::ln_without_floats(size) + 2
     }
 }
+
+/// The sum of `bases`, each scaled by the `c` bits of its scalar from
+/// `w_start`.
+fn window_sum<G: AffineCurve>(
+    bases: &[G],
+    scalars: &[<G::ScalarField as PrimeField>::BigInt],
+    c: usize,
+    w_start: usize,
+) -> G::Projective {
+    let fr_one = G::ScalarField::one().into_repr();
+    let zero = G::Projective::zero();
+
+    let mut res = zero;
+    // We don't need the "zero" bucket, so we only have 2^c - 1 buckets.
+    let mut buckets = vec![zero; (1 << c) - 1];
+    scalars
+        .iter()
+        .zip(bases)
+        .filter(|(s, _)| !s.is_zero())
+        .for_each(|(&scalar, base)| {
+            if scalar == fr_one {
+                // We only process unit scalars once in the first window.
+                if w_start == 0 {
+                    res.add_assign_mixed(base);
+                }
+            } else {
+                let mut scalar = scalar;
+
+                // We right-shift by w_start, thus getting rid of the
+                // lower bits.
+                scalar.divn(w_start as u32);
+
+                // We mod the remaining bits by 2^{window size}, thus taking `c` bits.
+                let scalar = scalar.as_ref()[0] % (1 << c);
+
+                // If the scalar is non-zero, we update the corresponding
+                // bucket.
+                // (Recall that `buckets` doesn't have a zero bucket.)
+                if scalar != 0 {
+                    buckets[(scalar - 1) as usize].add_assign_mixed(base);
+                }
+            }
+        });
+
+    // Compute sum_{i in 0..num_buckets} (sum_{j in i..num_buckets} bucket[j])
+    // This is computed below for b buckets, using 2b curve additions.
+    //
+    // We could first normalize `buckets` and then use mixed-addition
+    // here, but that's slower for the kinds of groups we care about
+    // (Short Weierstrass curves and Twisted Edwards curves).
+    // In the case of Short Weierstrass curves,
+    // mixed addition saves ~4 field multiplications per addition.
+    // However normalization (with the inversion batched) takes ~6
+    // field multiplications per element,
+    // hence batch normalization is a slowdown.
+
+    // `running_sum` = sum_{j in i..num_buckets} bucket[j],
+    // where we iterate backward from i = num_buckets to 0.
+    let mut running_sum = G::Projective::zero();
+    buckets.into_iter().rev().for_each(|b| {
+        running_sum += &b;
+        res += &running_sum;
+    });
+    res
+}
+
+/// The sum of the window sums of one MSM, in windows of `c` bits, lowest first.
+fn combine_windows<G: AffineCurve>(window_sums: &[G::Projective], c: usize) -> G::Projective {
+    // We store the sum for the lowest window.
+    let lowest = *window_sums.first().unwrap();
+
+    // We're traversing windows from high to low.
+    lowest
+        + &window_sums[1..]
+            .iter()
+            .rev()
+            .fold(G::Projective::zero(), |mut total, sum_i| {
+                total += sum_i;
+                for _ in 0..c {
+                    total.double_in_place();
+                }
+                total
+            })
+}
@@ -152,7 +152,7 @@ macro_rules! impl_field_square_in_place {
                 }
             }
             // Montgomery reduction
-            let mut _carry2 = 0;
+            let mut carry2 = 0;
             for i in 0..$limbs {
                 let k = r[i].wrapping_mul(P::INV);
                 let mut carry = 0;
@@ -160,10 +160,15 @@ macro_rules! impl_field_square_in_place {
                 for j in 1..$limbs {
                     r[j + i] = mac_with_carry!(r[j + i], k, P::MODULUS.0[j], &mut carry);
                 }
-                r[$limbs + i] = adc!(r[$limbs + i], _carry2, &mut carry);
-                _carry2 = carry;
+                r[$limbs + i] = adc!(r[$limbs + i], carry2, &mut carry);
+                carry2 = carry;
             }
             (self.0).0.copy_from_slice(&r[$limbs..]);
+            // The result is below `2 * modulus`, which only overflows when the
+            // modulus uses every bit.
+            if carry2 != 0 {
+                self.0.sub_noborrow(&P::MODULUS);
+            }
             self.reduce();
             self
         }
@@ -173,7 +173,7 @@ macro_rules! impl_Fp {
                     r[$limbs + i] = carry;
                 }
                 // Montgomery reduction
-                let mut _carry2 = 0;
+                let mut carry2 = 0;
                 for i in 0..$limbs {
                     let k = r[i].wrapping_mul(inv);
                     let mut carry = 0;
@@ -181,13 +181,18 @@ macro_rules! impl_Fp {
                     for j in 1..$limbs {
                         r[j + i] = mac_with_carry!(r[j + i], k, modulus.0[j], &mut carry);
                     }
-                    r[$limbs + i] = adc!(r[$limbs + i], _carry2, &mut carry);
-                    _carry2 = carry;
+                    r[$limbs + i] = adc!(r[$limbs + i], carry2, &mut carry);
+                    carry2 = carry;
                 }
 
                 for i in 0..$limbs {
                     (self.0).0[i] = r[$limbs + i];
                 }
+                // The result is below `2 * modulus`, which only overflows when
+                // the modulus uses every bit; then it is reduced here.
+                if carry2 != 0 {
+                    self.0 = Self::sub_noborrow(&self.0, &modulus);
+                }
                 self
             }
 
@@ -296,6 +301,13 @@ macro_rules! impl_Fp {
 
             #[inline]
             fn double_in_place(&mut self) -> &mut Self {
+                if P::MODULUS_BITS as usize == 64 * $limbs {
+                    // The modulus uses every bit, so this may exceed the
+                    // backing capacity; then the modulus is subtracted.
+                    let other = *self;
+                    *self += &other;
+                    return self;
+                }
                 // This cannot exceed the backing capacity.
                 self.0.mul2();
                 // However, it may need to be reduced.
@@ -388,8 +400,12 @@ macro_rules! impl_Fp {
                             if b.0.is_even() {
                                 b.0.div2();
                             } else {
-                                b.0.add_nocarry(&P::MODULUS);
+                                let carry = b.0.add_nocarry(&P::MODULUS);
                                 b.0.div2();
+                                // The carry, if the modulus uses every bit
+                                if carry {
+                                    (b.0).0[$limbs - 1] |= 1 << 63;
+                                }
                             }
                         }
 
@@ -399,8 +415,12 @@ macro_rules! impl_Fp {
                             if c.0.is_even() {
                                 c.0.div2();
                             } else {
-                                c.0.add_nocarry(&P::MODULUS);
+                                let carry = c.0.add_nocarry(&P::MODULUS);
                                 c.0.div2();
+                                // The carry, if the modulus uses every bit
+                                if carry {
+                                    (c.0).0[$limbs - 1] |= 1 << 63;
+                                }
                             }
                         }
 
@@ -663,10 +683,14 @@ macro_rules! impl_Fp {
         impl<'a, P: $FpParameters> AddAssign<&'a Self> for $Fp<P> {
             #[inline]
             fn add_assign(&mut self, other: &Self) {
-                // This cannot exceed the backing capacity.
-                self.0.add_nocarry(&other.0);
-                // However, it may need to be reduced
-                self.reduce();
+                // This exceeds the backing capacity only if the modulus uses
+                // every bit.
+                if self.0.add_nocarry(&other.0) {
+                    self.0.sub_noborrow(&P::MODULUS);
+                } else {
+                    // However, it may need to be reduced
+                    self.reduce();
+                }
             }
         }
 
@@ -246,6 +246,15 @@ pub trait Field:
     )> {
         panic!("No special division algorithm")
     }
+
+    /// An FFT (or, if `inverse`, an inverse FFT) of `xs` over the subgroup of order `xs.len()`
+    /// generated by `group_gen`, for fields that have a faster one than the generic FFT (e.g.,
+    /// fields of secret shares, which can transform each party's shares locally).
+    ///
+    /// Returns whether it did so; if not, `xs` is unchanged.
+    fn batch_fft_in_place(_xs: &mut Vec<Self>, _group_gen: Self, _inverse: bool) -> bool {
+        false
+    }
 }
 
 /// A trait that defines parameters for a field that can be used for FFTs.
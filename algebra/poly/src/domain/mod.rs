@@ -232,6 +232,23 @@ pub trait EvaluationDomain<F: FftField>:
     }
 }
 
+/// An FFT (or, if `inverse`, an inverse FFT) of `xs` over `domain`, by the field's own algorithm
+/// if it has one (see [ark_ff::Field::batch_fft_in_place]).
+pub(crate) fn field_fft_in_place<F: FftField, D: EvaluationDomain<F>>(
+    domain: &D,
+    xs: &mut Vec<F>,
+    inverse: bool,
+) {
+    xs.resize(domain.size(), F::zero());
+    if !F::batch_fft_in_place(xs, domain.element(1), inverse) {
+        if inverse {
+            domain.ifft_in_place(xs);
+        } else {
+            domain.fft_in_place(xs);
+        }
+    }
+}
+
 /// Types that can be FFT-ed must implement this trait.
 pub trait DomainCoeff<F: FftField>:
     Copy
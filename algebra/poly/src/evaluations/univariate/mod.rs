@@ -1,6 +1,7 @@
 //! A univariate polynomial represented in evaluations form.
 
 use crate::univariate::DensePolynomial;
+use crate::domain::field_fft_in_place;
 use crate::{EvaluationDomain, GeneralEvaluationDomain, UVPolynomial};
 use ark_ff::{FftField, Field};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
@@ -39,13 +40,15 @@ impl<F: FftField, D: EvaluationDomain<F>> Evaluations<F, D> {
 
     /// Interpolate a polynomial from a list of evaluations
     pub fn interpolate_by_ref(&self) -> DensePolynomial<F> {
-        DensePolynomial::from_coefficients_vec(self.domain.ifft(&self.evals))
+        let mut evals = self.evals.clone();
+        field_fft_in_place(&self.domain, &mut evals, true);
+        DensePolynomial::from_coefficients_vec(evals)
     }
 
     /// Interpolate a polynomial from a list of evaluations
     pub fn interpolate(self) -> DensePolynomial<F> {
         let Self { mut evals, domain } = self;
-        domain.ifft_in_place(&mut evals);
+        field_fft_in_place(&domain, &mut evals, true);
         DensePolynomial::from_coefficients_vec(evals)
     }
 
@@ -476,7 +476,13 @@ impl<'a, 'b, F: FftField> Mul<&'a DensePolynomial<F>> for &'b DensePolynomial<F>
             let mut self_evals = self.evaluate_over_domain_by_ref(domain);
             let other_evals = other.evaluate_over_domain_by_ref(domain);
             self_evals *= &other_evals;
-            self_evals.interpolate()
+            // The FFT domain may be larger than the product. Drop the excess explicitly: if the
+            // coefficients are secret-shared, their (shared) zeros aren't visible as zeros.
+            let mut result = self_evals.interpolate();
+            result
+                .coeffs
+                .truncate(self.coeffs.len() + other.coeffs.len() - 1);
+            result
         }
     }
 }
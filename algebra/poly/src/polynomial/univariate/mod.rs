@@ -1,5 +1,6 @@
 //! Work with sparse and dense polynomials.
 
+use crate::domain::field_fft_in_place;
 use crate::{EvaluationDomain, Evaluations, Polynomial, UVPolynomial};
 use ark_ff::{poly_stub, FftField, Field, Zero};
 use ark_std::{borrow::Cow, convert::TryInto, vec::Vec};
@@ -195,10 +196,12 @@ impl<'a, F: 'a + FftField> DenseOrSparsePolynomial<'a, F> {
                 Evaluations::from_vec_and_domain(evals, domain)
             }
             DPolynomial(Cow::Borrowed(d)) => {
-                Evaluations::from_vec_and_domain(domain.fft(&d.coeffs), domain)
+                let mut evals = d.coeffs.clone();
+                field_fft_in_place(&domain, &mut evals, false);
+                Evaluations::from_vec_and_domain(evals, domain)
             }
             DPolynomial(Cow::Owned(mut d)) => {
-                domain.fft_in_place(&mut d.coeffs);
+                field_fft_in_place(&domain, &mut d.coeffs, false);
                 Evaluations::from_vec_and_domain(d.coeffs, domain)
             }
         }
@@ -50,13 +50,12 @@ impl Flags for EmptyFlags {
         0
     }
 
+    /// There are no flag bits, so every bit of `value` belongs to the
+    /// serialized object (e.g. to a field element whose modulus uses every
+    /// bit of its last byte).
     #[inline]
-    fn from_u8(value: u8) -> Option<Self> {
-        if (value >> 7) == 0 {
-            Some(EmptyFlags)
-        } else {
-            None
-        }
+    fn from_u8(_value: u8) -> Option<Self> {
+        Some(EmptyFlags)
     }
 }
 
@@ -0,0 +1,60 @@
+use ark_ec::{
+    glv::{glv_msm, glv_mul, GLVParameters},
+    msm::VariableBaseMSM,
+    short_weierstrass_jacobian::{GroupAffine, GroupProjective},
+    AffineCurve, ProjectiveCurve,
+};
+use ark_ff::{BigInteger, PrimeField, UniformRand};
+
+pub fn glv_test<P: GLVParameters>() {
+    const SAMPLES: usize = 1 << 8;
+
+    let mut rng = ark_std::test_rng();
+
+    // Projective multiplication doesn't use the endomorphism, even if affine
+    // multiplication does.
+    let p = GroupProjective::<P>::rand(&mut rng);
+    let g = p.into_affine();
+    assert_eq!(
+        P::endomorphism(&g),
+        p.mul(P::LAMBDA.into_repr()).into_affine()
+    );
+
+    for _ in 0..SAMPLES {
+        let k = P::ScalarField::rand(&mut rng);
+        let [(neg_1, k_1), (neg_2, k_2)] = P::scalar_decomposition(&k);
+        let half = P::ScalarField::size_in_bits() / 2 + 2;
+        assert!(k_1.num_bits() as usize <= half && k_2.num_bits() as usize <= half);
+        let signed = |neg: bool, s| {
+            let s = P::ScalarField::from_repr(s).unwrap();
+            if neg {
+                -s
+            } else {
+                s
+            }
+        };
+        assert_eq!(signed(neg_1, k_1) + signed(neg_2, k_2) * P::LAMBDA, k);
+        assert_eq!(glv_mul(&g, k.into_repr()), p.mul(k.into_repr()));
+        assert_eq!(g.mul(k.into_repr()), p.mul(k.into_repr()));
+    }
+
+    let v = (0..SAMPLES - 1)
+        .map(|_| P::ScalarField::rand(&mut rng).into_repr())
+        .collect::<Vec<_>>();
+    let g = (0..SAMPLES)
+        .map(|_| GroupProjective::<P>::rand(&mut rng))
+        .collect::<Vec<_>>();
+    let g: Vec<GroupAffine<P>> = ProjectiveCurve::batch_normalization_into_affine(&g);
+
+    let plain = VariableBaseMSM::multi_scalar_mul(g.as_slice(), v.as_slice());
+    let fast = glv_msm(g.as_slice(), v.as_slice());
+    assert_eq!(plain.into_affine(), fast.into_affine());
+
+    // The curve's own MSM, which may or may not use the endomorphism
+    let v = v
+        .into_iter()
+        .map(|s| P::ScalarField::from_repr(s).unwrap())
+        .collect::<Vec<_>>();
+    let curve = GroupAffine::<P>::multi_scalar_mul(g.as_slice(), v.as_slice());
+    assert_eq!(plain.into_affine(), curve.into_affine());
+}
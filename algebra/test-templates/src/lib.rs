@@ -1,4 +1,5 @@
 pub mod curves;
 pub mod fields;
+pub mod glv;
 pub mod groups;
 pub mod msm;
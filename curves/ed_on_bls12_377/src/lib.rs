@@ -8,8 +8,6 @@
     rust_2018_idioms
 )]
 #![forbid(unsafe_code)]
-#[cfg(feature = "std")]
-extern crate std;
 
 //! This library implements a twisted Edwards curve whose base field is the scalar field of the
 //! curve BLS12-377.  This allows defining cryptographic primitives that use elliptic curves over
@@ -25,6 +23,9 @@ extern crate std;
 //!    * a = -1
 //!    * d = 3021
 
+#[cfg(feature = "std")]
+extern crate std;
+
 #[cfg(feature = "r1cs")]
 pub mod constraints;
 mod curves;
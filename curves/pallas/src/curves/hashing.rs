@@ -0,0 +1,118 @@
+use crate::{fq::Fq, fr::Fr, PallasParameters};
+use ark_ec::{
+    hashing::{IsogenyParameters, SWUParameters},
+    models::{ModelParameters, SWModelParameters},
+};
+use ark_ff::field_new;
+
+/// Iso-Pallas, `y^2 = x^3 + A x + 1265`: the curve that hashes to Pallas map
+/// to, by simplified SWU, before a 3-isogeny takes them to Pallas. The
+/// constants are those of `pasta_curves`.
+#[derive(Copy, Clone, Default, PartialEq, Eq)]
+pub struct IsoPallasParameters;
+
+impl ModelParameters for IsoPallasParameters {
+    type BaseField = Fq;
+    type ScalarField = Fr;
+}
+
+impl SWModelParameters for IsoPallasParameters {
+    /// COEFF_A
+    const COEFF_A: Fq = field_new!(
+        Fq,
+        "10949663248450308183708987909873589833737836120165333298109615750520499732811"
+    );
+
+    /// COEFF_B = 1265
+    const COEFF_B: Fq = field_new!(Fq, "1265");
+
+    /// COFACTOR = 1
+    const COFACTOR: &'static [u64] = &[0x1];
+
+    /// COFACTOR_INV = 1
+    const COFACTOR_INV: Fr = field_new!(Fr, "1");
+
+    /// AFFINE_GENERATOR_COEFFS = (1, ISO_GENERATOR_Y)
+    const AFFINE_GENERATOR_COEFFS: (Self::BaseField, Self::BaseField) = (
+        field_new!(Fq, "1"),
+        field_new!(
+            Fq,
+            "28766385068276566070424243329217752816143671799772339353216900698357085882990"
+        ),
+    );
+}
+
+impl SWUParameters for IsoPallasParameters {
+    /// Z = -13
+    const Z: Fq = field_new!(Fq, "-13");
+}
+
+impl IsogenyParameters for PallasParameters {
+    type Domain = IsoPallasParameters;
+
+    const X_NUM: &'static [Fq] = &[
+        field_new!(
+            Fq,
+            "12865787693035132824841220556520878650383580658640693651535411895266652280192"
+        ),
+        field_new!(
+            Fq,
+            "10492611921771203378452795982353351666191589197598957448093274638589204800759"
+        ),
+        field_new!(
+            Fq,
+            "23989696149150192365340222745168215001509815558210986772351135915822265203574"
+        ),
+        field_new!(
+            Fq,
+            "6432893846517566412420610278260439325191790329320346825767705947633326140075"
+        ),
+    ];
+
+    const X_DEN: &'static [Fq] = &[
+        field_new!(
+            Fq,
+            "22768321103861051515190775253992702316905399997697804654926324362758820947460"
+        ),
+        field_new!(
+            Fq,
+            "13271109177048389296812780941310096270046944650307955939477485891950613419807"
+        ),
+        field_new!(Fq, "1"),
+    ];
+
+    const Y_NUM: &'static [Fq] = &[
+        field_new!(
+            Fq,
+            "1072148974419594402070101713043406554198631721553391137627950991272221023311"
+        ),
+        field_new!(
+            Fq,
+            "28823569610051396102362669851238297121581474897215657071023781420043761726004"
+        ),
+        field_new!(
+            Fq,
+            "11994848074575096182670111372584107500754907779105493386175567957911132601787"
+        ),
+        field_new!(
+            Fq,
+            "11793638718615538422771118843477472096184948937087302513907460903994431256804"
+        ),
+    ];
+
+    const Y_DEN: &'static [Fq] = &[
+        field_new!(
+            Fq,
+            "28948022309329048855892746252171976963363056481941560715954676764349967629797"
+        ),
+        field_new!(
+            Fq,
+            "10408918692925056833786833257634153023990087029210292532869619559576527581706"
+        ),
+        field_new!(
+            Fq,
+            "5432652610908059517272798285879155923388888734491153551238890455750936314542"
+        ),
+        field_new!(Fq, "1"),
+    ];
+}
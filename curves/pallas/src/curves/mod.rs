@@ -1,10 +1,17 @@
 use crate::{fq::Fq, fr::Fr};
+#[cfg(feature = "glv")]
+use ark_ec::glv::GLVParameters;
 use ark_ec::{
     models::{ModelParameters, SWModelParameters},
     short_weierstrass_jacobian::{GroupAffine, GroupProjective},
 };
+#[cfg(feature = "glv")]
+use ark_ff::PrimeField;
 use ark_ff::{field_new, Zero};
 
+mod hashing;
+pub use hashing::*;
+
 #[cfg(test)]
 mod tests;
 
@@ -40,6 +47,52 @@ impl SWModelParameters for PallasParameters {
     fn mul_by_a(_: &Self::BaseField) -> Self::BaseField {
         Self::BaseField::zero()
     }
+
+    #[cfg(feature = "glv")]
+    fn mul_affine(base: &Affine, scalar: <Fr as PrimeField>::BigInt) -> Projective {
+        ark_ec::glv::glv_mul::<Self>(base, scalar)
+    }
+
+    #[cfg(feature = "glv")]
+    fn multi_scalar_mul(
+        bases: &[Affine],
+        scalars: &[<Fr as PrimeField>::BigInt],
+    ) -> Projective {
+        ark_ec::glv::glv_msm::<Self>(bases, scalars)
+    }
+}
+
+#[cfg(feature = "glv")]
+impl GLVParameters for PallasParameters {
+    /// OMEGA, a cube root of unity in Fq
+    const OMEGA: Fq = field_new!(
+        Fq,
+        "8503465768106391777493614032514048814691664078728891710322960303815233784505"
+    );
+
+    /// LAMBDA, a cube root of unity in Fr
+    const LAMBDA: Fr = field_new!(
+        Fr,
+        "2942865608506852014473558576493638302197734138389222805617480874486368177743"
+    );
+
+    /// BASIS = ((a_1, b_1), (a_2, b_2))
+    const BASIS: [(Fr, Fr); 2] = [
+        (
+            field_new!(Fr, "98231058071186745657228807397848383489"),
+            field_new!(Fr, "-98231058071100081932162823354453065728"),
+        ),
+        (
+            field_new!(Fr, "98231058071100081932162823354453065728"),
+            field_new!(Fr, "196462116142286827589391630752301449217"),
+        ),
+    ];
+
+    /// ROUNDING = (b_2 2^256 / r, -b_1 2^256 / r)
+    const ROUNDING: [&'static [u64]; 2] = [
+        &[0x31f0256800000003, 0x4f34e8b2066389a4, 0x2],
+        &[0x32c49e4bffffffff, 0x279a745902a2654e, 0x1],
+    ];
 }
 
 /// G_GENERATOR_X = -1
@@ -10,12 +10,14 @@ use ark_ec::{models::SWModelParameters, AffineCurve, PairingEngine, ProjectiveCu
 use ark_std::ops::{AddAssign, MulAssign};
 use ark_std::rand::Rng;
 
-use crate::{Affine, PallasParameters, Projective};
+use crate::{Affine, Fq, IsoPallasParameters, PallasParameters, Projective};
 
 use ark_algebra_test_templates::{
     curves::{curve_tests, sw_tests},
     groups::group_test,
 };
+#[cfg(feature = "glv")]
+use ark_algebra_test_templates::glv::glv_test;
 
 #[test]
 fn test_projective_curve() {
@@ -37,3 +39,62 @@ fn test_generator() {
     assert!(generator.is_on_curve());
     assert!(generator.is_in_correct_subgroup_assuming_on_curve());
 }
+
+#[cfg(feature = "glv")]
+#[test]
+fn test_glv() {
+    glv_test::<PallasParameters>();
+}
+
+#[test]
+fn test_isogeny() {
+    use ark_ec::{hashing::IsogenyParameters, short_weierstrass_jacobian::GroupProjective};
+    let mut rng = test_rng();
+    let a: GroupProjective<IsoPallasParameters> = rng.gen();
+    let b: GroupProjective<IsoPallasParameters> = rng.gen();
+    let (a, b) = (a.into_affine(), b.into_affine());
+    let image = |p| PallasParameters::isogeny_map(p);
+    assert!(image(&a).is_on_curve());
+    assert_eq!(image(&(a + b)), image(&a) + image(&b));
+}
+
+#[test]
+fn test_hash_to_curve() {
+    use ark_ec::hashing::{encode_to_curve, expand_message_xmd, hash_to_curve};
+    use sha2::Sha256;
+    use std::str::FromStr;
+
+    let dst = b"QUUX-V01-CS02-with-expander-SHA256-128";
+    assert_eq!(
+        expand_message_xmd::<Sha256>(b"abc", dst, 0x20),
+        [
+            0xd8, 0xcc, 0xab, 0x23, 0xb5, 0x98, 0x5c, 0xce, 0xa8, 0x65, 0xc6, 0xc9, 0x7b, 0x6e,
+            0x5b, 0x83, 0x50, 0xe7, 0x94, 0xe6, 0x03, 0xb4, 0xb9, 0x79, 0x02, 0xf5, 0x3a, 0x8a,
+            0x0d, 0x60, 0x56, 0x15
+        ]
+    );
+
+    let point = |x, y| Affine::new(Fq::from_str(x).unwrap(), Fq::from_str(y).unwrap(), false);
+    let ro = hash_to_curve::<PallasParameters, Sha256>(
+        b"abc",
+        b"QUUX-V01-CS02-with-pallas_XMD:SHA-256_SSWU_RO_",
+    );
+    assert_eq!(
+        ro,
+        point(
+            "24098166527885906846180490619812118413849786001843221727655672376085930846833",
+            "468276367126329304023891759757627524497079410503291193270903742079630873623"
+        )
+    );
+    let nu = encode_to_curve::<PallasParameters, Sha256>(
+        b"abc",
+        b"QUUX-V01-CS02-with-pallas_XMD:SHA-256_SSWU_NU_",
+    );
+    assert_eq!(
+        nu,
+        point(
+            "25356834398185956682133166344548211488882138011273850528330733714098227416450",
+            "15058873900654956698157051653794860465883527567425556546656585448973868057170"
+        )
+    );
+}
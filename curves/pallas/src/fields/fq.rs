@@ -21,6 +21,18 @@ impl FftParameters for FqParameters {
         0xfba6b9ca9dc8448e,
         0x3ec928747b89c6da,
     ]);
+
+    const SMALL_SUBGROUP_BASE: Option<u32> = Some(3);
+    const SMALL_SUBGROUP_BASE_ADICITY: Option<u32> = Some(1);
+
+    // LARGE_SUBGROUP_ROOT_OF_UNITY = GENERATOR^((MODULUS - 1) / (2^32 * 3))
+    // Encoded in Montgomery form.
+    const LARGE_SUBGROUP_ROOT_OF_UNITY: Option<BigInteger> = Some(BigInteger([
+        0x098c5db18c9c8c99,
+        0x2972afb1a7011845,
+        0x109e27bd7c2ace74,
+        0x0ac82e83c0bfa0b2,
+    ]));
 }
 
 impl ark_ff::fields::FpParameters for FqParameters {
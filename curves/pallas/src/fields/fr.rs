@@ -21,6 +21,18 @@ impl FftParameters for FrParameters {
         0xac2e5d27b2efbee2,
         0x0b79fa897f2db056,
     ]);
+
+    const SMALL_SUBGROUP_BASE: Option<u32> = Some(3);
+    const SMALL_SUBGROUP_BASE_ADICITY: Option<u32> = Some(1);
+
+    // LARGE_SUBGROUP_ROOT_OF_UNITY = GENERATOR^((MODULUS - 1) / (2^32 * 3))
+    // Encoded in Montgomery form.
+    const LARGE_SUBGROUP_ROOT_OF_UNITY: Option<BigInteger> = Some(BigInteger([
+        0x5f5ac16c436f468f,
+        0x4e3bb8dacdf79e67,
+        0xd80f3e6be7c3f86e,
+        0x3fe94e44e096dace,
+    ]));
 }
 
 impl FpParameters for FrParameters {
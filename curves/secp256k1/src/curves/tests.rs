@@ -0,0 +1,57 @@
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{PrimeField, Zero};
+use ark_std::{rand::Rng, str::FromStr, test_rng};
+
+use crate::{Affine, Fq, Fr, Projective, Secp256k1Parameters};
+
+use ark_algebra_test_templates::{
+    curves::{curve_tests, sw_tests},
+    groups::group_test,
+};
+
+#[test]
+fn test_projective_curve() {
+    curve_tests::<Projective>();
+    sw_tests::<Secp256k1Parameters>();
+}
+
+#[test]
+fn test_projective_group() {
+    let mut rng = test_rng();
+    let a: Projective = rng.gen();
+    let b: Projective = rng.gen();
+    group_test(a, b);
+}
+
+#[test]
+fn test_generator() {
+    let generator = Affine::prime_subgroup_generator();
+    assert!(generator.is_on_curve());
+    assert!(generator.is_in_correct_subgroup_assuming_on_curve());
+}
+
+#[test]
+fn test_known_multiples() {
+    // 2G, and (r - 1)G = -G
+    let generator = Affine::prime_subgroup_generator();
+    let double = Affine::new(
+        Fq::from_str(
+            "89565891926547004231252920425935692360644145829622209833684329913297188986597",
+        )
+        .unwrap(),
+        Fq::from_str(
+            "12158399299693830322967808612713398636155367887041628176798871954788371653930",
+        )
+        .unwrap(),
+        false,
+    );
+    assert_eq!(
+        generator.mul(Fr::from(2u64).into_repr()).into_affine(),
+        double
+    );
+    assert_eq!(
+        generator.mul((-Fr::from(1u64)).into_repr()).into_affine(),
+        -generator
+    );
+    assert!(generator.mul(Fr::zero().into_repr()).is_zero());
+}
@@ -0,0 +1,90 @@
+use ark_ff::{
+    biginteger::BigInteger256 as BigInteger,
+    fields::{FftParameters, Fp256, Fp256Parameters},
+};
+
+pub type Fq = Fp256<FqParameters>;
+
+pub struct FqParameters;
+
+impl Fp256Parameters for FqParameters {}
+impl FftParameters for FqParameters {
+    type BigInt = BigInteger;
+
+    const TWO_ADICITY: u32 = 1;
+
+    // TWO_ADIC_ROOT_OF_UNITY = GENERATOR^T = -1
+    // Encoded in Montgomery form, so the value here is (3^T)R mod p.
+    const TWO_ADIC_ROOT_OF_UNITY: BigInteger = BigInteger([
+        0xfffffffdfffff85e,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+    ]);
+}
+
+impl ark_ff::fields::FpParameters for FqParameters {
+    // 115792089237316195423570985008687907853269984665640564039457584007908834671663
+    const MODULUS: BigInteger = BigInteger([
+        0xfffffffefffffc2f,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+    ]);
+
+    // R = 2^256 mod p
+    const R: BigInteger = BigInteger([
+        0x00000001000003d1,
+        0x0000000000000000,
+        0x0000000000000000,
+        0x0000000000000000,
+    ]);
+
+    // R2 = (2^256)^2 mod p
+    const R2: BigInteger = BigInteger([
+        0x000007a2000e90a1,
+        0x0000000000000001,
+        0x0000000000000000,
+        0x0000000000000000,
+    ]);
+
+    const MODULUS_MINUS_ONE_DIV_TWO: BigInteger = BigInteger([
+        0xffffffff7ffffe17,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0x7fffffffffffffff,
+    ]);
+
+    // T and T_MINUS_ONE_DIV_TWO, where MODULUS - 1 = 2^S * T
+    const T: BigInteger = BigInteger([
+        0xffffffff7ffffe17,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0x7fffffffffffffff,
+    ]);
+
+    const T_MINUS_ONE_DIV_TWO: BigInteger = BigInteger([
+        0xffffffffbfffff0b,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0x3fffffffffffffff,
+    ]);
+
+    // GENERATOR = 3
+    // Encoded in Montgomery form, so the value here is 3R mod p.
+    const GENERATOR: BigInteger = BigInteger([
+        0x0000000300000b73,
+        0x0000000000000000,
+        0x0000000000000000,
+        0x0000000000000000,
+    ]);
+
+    const MODULUS_BITS: u32 = 256;
+
+    const CAPACITY: u32 = Self::MODULUS_BITS - 1;
+
+    const REPR_SHAVE_BITS: u32 = 0;
+
+    // INV = -p^{-1} (mod 2^64)
+    const INV: u64 = 15580212934572586289;
+}
@@ -0,0 +1,38 @@
+#![no_std]
+#![deny(
+    warnings,
+    unused,
+    future_incompatible,
+    nonstandard_style,
+    rust_2018_idioms
+)]
+#![forbid(unsafe_code)]
+
+//! This library implements the prime-order curve secp256k1, from
+//! [SEC 2](https://www.secg.org/sec2-v2.pdf), which Bitcoin and Ethereum use
+//! for ECDSA signatures. Its fields let a collaborative proof about such
+//! signatures (e.g. in a circuit that verifies ECDSA over an emulated field)
+//! prepare its witness natively.
+//!
+//!
+//! Curve information:
+//! * Base field: q =
+//!   115792089237316195423570985008687907853269984665640564039457584007908834671663
+//! * Scalar field: r =
+//!   115792089237316195423570985008687907852837564279074904382605163141518161494337
+//! * Curve equation: y^2 = x^3 + 7
+//! * Valuation(q - 1, 2) = 1
+//! * Valuation(r - 1, 2) = 6
+//!
+//! Both moduli use all 256 bits of their representation, so a point's flags
+//! take an extra byte: a compressed point serializes to 33 bytes.
+
+#[cfg(feature = "curve")]
+mod curves;
+#[cfg(any(feature = "scalar_field", feature = "base_field"))]
+mod fields;
+
+#[cfg(feature = "curve")]
+pub use curves::*;
+#[cfg(any(feature = "scalar_field", feature = "base_field"))]
+pub use fields::*;
@@ -0,0 +1,173 @@
+//! Accumulation over the Pallas-Vesta cycle.
+//!
+//! Each of the two curves' scalar field is the other's base field. So a Vesta
+//! circuit (one over Vesta's scalar field, proven with a Vesta-based SNARK)
+//! does Pallas group arithmetic natively, and a Pallas circuit does arithmetic
+//! on Pallas scalars natively. Recursion over the cycle splits each check
+//! between them, so that neither emulates a foreign field.
+//!
+//! Here the check is the opening of an accumulated Pedersen commitment over
+//! Pallas. Commitments `C_i = m_i G + r_i H` are folded, with a challenge
+//! `rho`, into `C = C_1 + rho C_2`, which opens to `(m_1 + rho m_2, r_1 + rho
+//! r_2)`:
+//! * the Pallas circuit folds the openings, and
+//! * the Vesta circuit folds the commitments, and checks that `C` opens to the
+//!   folded opening.
+//!
+//! The circuits share the bits of `rho` and of the folded opening as public
+//! inputs: bits are zero or one in either field. (A real scheme would not
+//! reveal the opening, but share a hash of it.)
+//!
+//! Run with `cargo run --example cycle_accumulation --features r1cs`.
+use ark_ec::ProjectiveCurve;
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSystem, ConstraintSystemRef, SynthesisError};
+use ark_std::UniformRand;
+
+/// A Pallas scalar, native to the Pallas circuit.
+type Scalar = ark_pallas::Fr;
+/// A variable for a Pallas scalar, in the Pallas circuit.
+type ScalarVar = ark_vesta::constraints::FBaseVar;
+/// A variable for a Pallas point, in the Vesta circuit.
+type PointVar = ark_pallas::constraints::GVar;
+
+/// The opening of a commitment `m G + r H`.
+#[derive(Clone, Copy)]
+struct Opening {
+    m: Scalar,
+    r: Scalar,
+}
+
+impl Opening {
+    fn commit(
+        &self,
+        g: &ark_pallas::Projective,
+        h: &ark_pallas::Projective,
+    ) -> ark_pallas::Projective {
+        g.mul(self.m.into_repr()) + h.mul(self.r.into_repr())
+    }
+
+    fn fold(&self, other: &Self, rho: Scalar) -> Self {
+        Self {
+            m: self.m + rho * other.m,
+            r: self.r + rho * other.r,
+        }
+    }
+}
+
+/// The little-endian bits of `s`, as many as the scalar field's modulus has.
+fn bits(s: Scalar) -> Vec<bool> {
+    let mut bits = s.into_repr().to_bits_le();
+    bits.truncate(<Scalar as PrimeField>::size_in_bits());
+    bits
+}
+
+/// The public inputs shared by both circuits: the bits of `rho`, then of the
+/// folded opening.
+fn shared_bits(rho: Scalar, folded: &Opening) -> Vec<bool> {
+    [rho, folded.m, folded.r]
+        .iter()
+        .flat_map(|s| bits(*s))
+        .collect()
+}
+
+/// Allocate `values` as public bits, in order.
+fn public_bits<F: PrimeField>(
+    cs: &ConstraintSystemRef<F>,
+    values: &[bool],
+) -> Result<Vec<Boolean<F>>, SynthesisError> {
+    values
+        .iter()
+        .map(|b| Boolean::new_input(cs.clone(), || Ok(*b)))
+        .collect()
+}
+
+/// The Pallas circuit: `shared` is the bits of `rho`, and of `openings` folded
+/// with it.
+fn pallas_circuit(
+    cs: ConstraintSystemRef<Scalar>,
+    openings: &[Opening; 2],
+    rho: Scalar,
+    shared: &[bool],
+) -> Result<(), SynthesisError> {
+    let shared = public_bits(&cs, shared)?;
+    let rho = ScalarVar::new_witness(cs.clone(), || Ok(rho))?;
+    let mut ms = Vec::new();
+    let mut rs = Vec::new();
+    for o in openings {
+        ms.push(ScalarVar::new_witness(cs.clone(), || Ok(o.m))?);
+        rs.push(ScalarVar::new_witness(cs.clone(), || Ok(o.r))?);
+    }
+    let m = &ms[0] + &(&rho * &ms[1]);
+    let r = &rs[0] + &(&rho * &rs[1]);
+    let mut computed = rho.to_bits_le()?;
+    computed.extend(m.to_bits_le()?);
+    computed.extend(r.to_bits_le()?);
+    computed.enforce_equal(&shared)
+}
+
+/// The Vesta circuit: `shared` is the bits of `rho`, and of the opening of
+/// `commitments` folded with it.
+fn vesta_circuit(
+    cs: ConstraintSystemRef<ark_vesta::Fr>,
+    g: ark_pallas::Projective,
+    h: ark_pallas::Projective,
+    commitments: &[ark_pallas::Projective; 2],
+    shared: &[bool],
+) -> Result<(), SynthesisError> {
+    let shared = public_bits(&cs, shared)?;
+    let n = <Scalar as PrimeField>::size_in_bits();
+    let (rho, opening) = shared.split_at(n);
+    let (m, r) = opening.split_at(n);
+    let g = PointVar::new_constant(cs.clone(), g)?;
+    let h = PointVar::new_constant(cs.clone(), h)?;
+    let c1 = PointVar::new_input(cs.clone(), || Ok(commitments[0]))?;
+    let c2 = PointVar::new_input(cs.clone(), || Ok(commitments[1]))?;
+    let c = c1 + c2.scalar_mul_le(rho.iter())?;
+    let opened = g.scalar_mul_le(m.iter())? + h.scalar_mul_le(r.iter())?;
+    opened.enforce_equal(&c)
+}
+
+fn main() -> Result<(), SynthesisError> {
+    // This rng is just for the example; do not use it in real applications.
+    let rng = &mut ark_std::test_rng();
+    let g = ark_pallas::Projective::rand(rng);
+    let h = ark_pallas::Projective::rand(rng);
+    let openings = [
+        Opening {
+            m: Scalar::rand(rng),
+            r: Scalar::rand(rng),
+        },
+        Opening {
+            m: Scalar::rand(rng),
+            r: Scalar::rand(rng),
+        },
+    ];
+    let commitments = [openings[0].commit(&g, &h), openings[1].commit(&g, &h)];
+    let rho = Scalar::rand(rng);
+    let folded = openings[0].fold(&openings[1], rho);
+    assert_eq!(
+        folded.commit(&g, &h),
+        commitments[0] + commitments[1].mul(rho.into_repr())
+    );
+    let shared = shared_bits(rho, &folded);
+
+    let pallas = ConstraintSystem::<Scalar>::new_ref();
+    pallas_circuit(pallas.clone(), &openings, rho, &shared)?;
+    assert!(pallas.is_satisfied()?);
+    println!("Pallas circuit: {} constraints", pallas.num_constraints());
+
+    let vesta = ConstraintSystem::<ark_vesta::Fr>::new_ref();
+    vesta_circuit(vesta.clone(), g, h, &commitments, &shared)?;
+    assert!(vesta.is_satisfied()?);
+    println!("Vesta circuit: {} constraints", vesta.num_constraints());
+
+    // An opening that wasn't folded from the commitments' fails the Vesta circuit.
+    let wrong = openings[0].fold(&openings[1], rho + Scalar::from(1u64));
+    let vesta = ConstraintSystem::<ark_vesta::Fr>::new_ref();
+    vesta_circuit(vesta.clone(), g, h, &commitments, &shared_bits(rho, &wrong))?;
+    assert!(!vesta.is_satisfied()?);
+    println!("A wrong opening is rejected");
+    Ok(())
+}
@@ -5,6 +5,9 @@
 //!
 //! The field underlying these constraints is `crate::Fq`.
 //!
+//! Pallas points, over `crate::Fr`, have constraints in `ark_pallas`: the
+//! `cycle_accumulation` example uses both, to split a check over the cycle.
+//!
 //! # Examples
 //!
 //! One can perform standard algebraic operations on `FBaseVar`:
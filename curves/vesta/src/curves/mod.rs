@@ -1,8 +1,12 @@
 use crate::{fq::Fq, fr::Fr};
+#[cfg(feature = "glv")]
+use ark_ec::glv::GLVParameters;
 use ark_ec::{
     models::{ModelParameters, SWModelParameters},
     short_weierstrass_jacobian::{GroupAffine, GroupProjective},
 };
+#[cfg(feature = "glv")]
+use ark_ff::PrimeField;
 use ark_ff::{field_new, Zero};
 
 #[cfg(test)]
@@ -40,6 +44,52 @@ impl SWModelParameters for VestaParameters {
     fn mul_by_a(_: &Self::BaseField) -> Self::BaseField {
         Self::BaseField::zero()
     }
+
+    #[cfg(feature = "glv")]
+    fn mul_affine(base: &Affine, scalar: <Fr as PrimeField>::BigInt) -> Projective {
+        ark_ec::glv::glv_mul::<Self>(base, scalar)
+    }
+
+    #[cfg(feature = "glv")]
+    fn multi_scalar_mul(
+        bases: &[Affine],
+        scalars: &[<Fr as PrimeField>::BigInt],
+    ) -> Projective {
+        ark_ec::glv::glv_msm::<Self>(bases, scalars)
+    }
+}
+
+#[cfg(feature = "glv")]
+impl GLVParameters for VestaParameters {
+    /// OMEGA, a cube root of unity in Fq
+    const OMEGA: Fq = field_new!(
+        Fq,
+        "26005156700822196841419187675678338661165322343552424574062261873906994770353"
+    );
+
+    /// LAMBDA, a cube root of unity in Fr
+    const LAMBDA: Fr = field_new!(
+        Fr,
+        "20444556541222657078399132219657928148671392403212669005631716460534733845831"
+    );
+
+    /// BASIS = ((a_1, b_1), (a_2, b_2))
+    const BASIS: [(Fr, Fr); 2] = [
+        (
+            field_new!(Fr, "98231058071100081932162823354453065729"),
+            field_new!(Fr, "-98231058071186745657228807397848383488"),
+        ),
+        (
+            field_new!(Fr, "196462116142286827589391630752301449217"),
+            field_new!(Fr, "98231058071100081932162823354453065729"),
+        ),
+    ];
+
+    /// ROUNDING = (b_2 2^256 / r, -b_1 2^256 / r)
+    const ROUNDING: [&'static [u64]; 2] = [
+        &[0x32c49e4c00000003, 0x279a745902a2654e, 0x1],
+        &[0xff2b871bffffffff, 0x279a745903c12455, 0x1],
+    ];
 }
 
 /// G_GENERATOR_X = -1
@@ -16,6 +16,8 @@ use ark_algebra_test_templates::{
     curves::{curve_tests, sw_tests},
     groups::group_test,
 };
+#[cfg(feature = "glv")]
+use ark_algebra_test_templates::glv::glv_test;
 
 #[test]
 fn test_projective_curve() {
@@ -37,3 +39,9 @@ fn test_generator() {
     assert!(generator.is_on_curve());
     assert!(generator.is_in_correct_subgroup_assuming_on_curve());
 }
+
+#[cfg(feature = "glv")]
+#[test]
+fn test_glv() {
+    glv_test::<VestaParameters>();
+}
@@ -1,5 +1,7 @@
 #![allow(missing_docs)]
 use ark_ec::PairingEngine;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_std::io::{Read, Write};
 use mpc_algebra::*;
 
 use super::*;
@@ -11,6 +13,19 @@ impl<E: PairingEngine, S: PairingShare<E>> Reveal
     struct_reveal_simp_impl!(Proof; a, b, c);
 }
 
+impl<E: PairingEngine, S: PairingShare<E>> Proof<MpcPairingEngine<E, S>> {
+    /// Reveal this proof, and write it as a standard Groth16 [Proof], so that existing verifiers
+    /// can read it.
+    pub fn serialize_revealed<W: Write>(self, writer: W) -> Result<(), SerializationError> {
+        self.reveal().serialize(writer)
+    }
+
+    /// Read a standard Groth16 [Proof], as a public value.
+    pub fn deserialize_public<R: Read>(reader: R) -> Result<Self, SerializationError> {
+        Ok(Self::from_public(Proof::<E>::deserialize(reader)?))
+    }
+}
+
 impl<E: PairingEngine, S: PairingShare<E>> Reveal
     for VerifyingKey<MpcPairingEngine<E, S>>
 {
@@ -1,15 +1,16 @@
 use crate::{
     create_random_proof, generate_random_parameters, prepare_verifying_key,
-    rerandomize_proof, verify_proof
+    rerandomize_proof, verify_proof, verify_proofs_batch, Proof
 };
 use ark_ec::PairingEngine;
 use ark_ff::UniformRand;
-use ark_std::test_rng;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{test_rng, vec, vec::Vec};
 
 use core::ops::MulAssign;
 
 
-use ark_ff::{Field, Zero};
+use ark_ff::{Field, One, Zero};
 use ark_relations::{
     lc,
     r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
@@ -156,8 +157,84 @@ where
     assert!(proof2 != proof3);
 }
 
+fn test_batch_verify<E>()
+where
+    E: PairingEngine,
+{
+    let rng = &mut test_rng();
+
+    let params =
+        generate_random_parameters::<E, _, _>(MySillyCircuit { a: None, b: None }, rng).unwrap();
+
+    let pvk = prepare_verifying_key::<E>(&params.vk);
+
+    let mut proofs = Vec::new();
+    let mut inputs = Vec::new();
+    for _ in 0..4 {
+        let a = E::Fr::rand(rng);
+        let b = E::Fr::rand(rng);
+        let proof = create_random_proof(
+            MySillyCircuit {
+                a: Some(a),
+                b: Some(b),
+            },
+            &params,
+            rng,
+        )
+        .unwrap();
+        proofs.push(proof);
+        inputs.push(vec![a * &b]);
+    }
+
+    assert!(verify_proofs_batch(&pvk, &proofs, &inputs, rng).unwrap());
+    assert!(verify_proofs_batch(&pvk, &[], &[], rng).unwrap());
+
+    // One wrong instance fails the whole batch
+    inputs[2][0] += E::Fr::one();
+    assert!(!verify_proofs_batch(&pvk, &proofs, &inputs, rng).unwrap());
+
+    // As do swapped proofs
+    inputs[2][0] -= E::Fr::one();
+    proofs.swap(0, 1);
+    assert!(!verify_proofs_batch(&pvk, &proofs, &inputs, rng).unwrap());
+}
+
+fn test_serialize<E>()
+where
+    E: PairingEngine,
+{
+    let rng = &mut test_rng();
+
+    let params =
+        generate_random_parameters::<E, _, _>(MySillyCircuit { a: None, b: None }, rng).unwrap();
+
+    let a = E::Fr::rand(rng);
+    let b = E::Fr::rand(rng);
+    let proof = create_random_proof(
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &params,
+        rng,
+    )
+    .unwrap();
+
+    // The standard layout: A, B, and C, each compressed
+    let mut bytes = Vec::new();
+    proof.serialize(&mut bytes).unwrap();
+    let mut expected = Vec::new();
+    proof.a.serialize(&mut expected).unwrap();
+    proof.b.serialize(&mut expected).unwrap();
+    proof.c.serialize(&mut expected).unwrap();
+    assert_eq!(bytes, expected);
+    assert_eq!(bytes.len(), proof.serialized_size());
+
+    assert_eq!(Proof::<E>::deserialize(&bytes[..]).unwrap(), proof);
+}
+
 mod bls12_377 {
-    use super::{test_prove_and_verify, test_rerandomize};
+    use super::{test_batch_verify, test_prove_and_verify, test_rerandomize, test_serialize};
     use ark_bls12_377::Bls12_377;
 
     #[test]
@@ -169,6 +246,16 @@ mod bls12_377 {
     fn rerandomize() {
         test_rerandomize::<Bls12_377>();
     }
+
+    #[test]
+    fn batch_verify() {
+        test_batch_verify::<Bls12_377>();
+    }
+
+    #[test]
+    fn serialize() {
+        test_serialize::<Bls12_377>();
+    }
 }
 
 mod cp6_782 {
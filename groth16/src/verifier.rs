@@ -1,5 +1,5 @@
 use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
-use ark_ff::PrimeField;
+use ark_ff::{Field, PrimeField, UniformRand, Zero};
 
 use super::{PreparedVerifyingKey, Proof, VerifyingKey};
 
@@ -7,6 +7,7 @@ use ark_relations::r1cs::{Result as R1CSResult, SynthesisError};
 
 use core::ops::{AddAssign, Neg};
 use ark_std::ops::MulAssign;
+use ark_std::{rand::RngCore, vec::Vec};
 
 /// Prepare the verifying key `vk` for use in proof verification.
 pub fn prepare_verifying_key<E: PairingEngine>(vk: &VerifyingKey<E>) -> PreparedVerifyingKey<E> {
@@ -56,3 +57,43 @@ pub fn verify_proof<E: PairingEngine>(
 //    assert_eq!(test, test2);
     Ok(test2 == pvk.alpha_g1_beta_g2)
 }
+
+/// Verify Groth16 proofs `proofs` against the prepared verification key `pvk`, each with respect
+/// to its instance in `public_inputs`, with a single multi-pairing.
+///
+/// Each proof's equation is raised to a random power from `rng` before they are multiplied
+/// together, so that a batch with an invalid proof fails, except with negligible probability.
+pub fn verify_proofs_batch<E: PairingEngine, R: RngCore>(
+    pvk: &PreparedVerifyingKey<E>,
+    proofs: &[Proof<E>],
+    public_inputs: &[Vec<E::Fr>],
+    rng: &mut R,
+) -> R1CSResult<bool> {
+    assert_eq!(proofs.len(), public_inputs.len());
+    let mut pairs = Vec::with_capacity(proofs.len() + 2);
+    let mut g_ic = E::G1Projective::zero();
+    let mut c = E::G1Projective::zero();
+    let mut r_sum = E::Fr::zero();
+    for (proof, inputs) in proofs.iter().zip(public_inputs) {
+        if (inputs.len() + 1) != pvk.vk.gamma_abc_g1.len() {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+        let r = E::Fr::rand(rng);
+        let mut proof_ic = pvk.vk.gamma_abc_g1[0].into_projective();
+        for (i, b) in inputs.iter().zip(pvk.vk.gamma_abc_g1.iter().skip(1)) {
+            proof_ic.add_assign(&b.mul(i.into_repr()));
+        }
+        proof_ic.mul_assign(r);
+        g_ic.add_assign(&proof_ic);
+        c.add_assign(&proof.c.mul(r.into_repr()));
+        let a = proof.a.mul(r.into_repr()).into_affine();
+        pairs.push((a.into(), proof.b.into()));
+        r_sum += r;
+    }
+    pairs.push((g_ic.into_affine().into(), pvk.gamma_g2_neg_pc.clone()));
+    pairs.push((c.into_affine().into(), pvk.delta_g2_neg_pc.clone()));
+
+    let test = E::final_exponentiation(&E::miller_loop(pairs.iter()))
+        .ok_or(SynthesisError::UnexpectedIdentity)?;
+    Ok(test == pvk.alpha_g1_beta_g2.pow(r_sum.into_repr()))
+}
@@ -0,0 +1,463 @@
+use ark_ec::group::Group;
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{Field, PrimeField, UniformRand};
+use ark_poly::{univariate::DensePolynomial, Polynomial, UVPolynomial};
+use log::debug;
+use rand::Rng;
+use mpc_algebra::bits::STAT_SEC;
+use mpc_algebra::channel::MpcSerNet;
+use mpc_algebra::com::ComField;
+use mpc_algebra::eval::{eval_at_shared, horner_eval};
+use mpc_algebra::preprocessing::*;
+use mpc_algebra::prss::{self, Prss};
+use mpc_algebra::shuffle::{check_permutation, shuffle};
+use mpc_algebra::{
+    msm::NaiveMsm, share::add::*, share::field::FieldShare, share::group::GroupShare,
+    share::pairing::PairingShare, share::shamir::*, share::spdz::*, BeaverSource, MpcField,
+    MpcG1Affine, Reveal,
+};
+use mpc_net::{MpcMultiNet as Net, MpcNet};
+
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "example", about = "An example of StructOpt usage.")]
+struct Opt {
+    /// Id
+    id: usize,
+
+    /// Input file
+    #[structopt(parse(from_os_str))]
+    input: PathBuf,
+}
+
+/// Random triples and inverse pairs, dealt by the king.
+#[derive(Clone)]
+struct KingSource<F, S>(PhantomData<(F, S)>);
+
+impl<F: Field, S: FieldShare<F>> BeaverSource<S, S, S> for KingSource<F, S> {
+    fn triple(&mut self) -> (S, S, S) {
+        let rng = &mut rand::thread_rng();
+        let a = F::rand(rng);
+        let b = F::rand(rng);
+        let mut v = S::king_share_batch(vec![a, b, a * b], rng);
+        let c = v.pop().unwrap();
+        let b = v.pop().unwrap();
+        (v.pop().unwrap(), b, c)
+    }
+    fn inv_pair(&mut self) -> (S, S) {
+        let rng = &mut rand::thread_rng();
+        let a = F::rand(rng);
+        let mut v = S::king_share_batch(vec![a, a.inverse().unwrap()], rng);
+        let a_inv = v.pop().unwrap();
+        (v.pop().unwrap(), a_inv)
+    }
+}
+
+fn test_field<F: Field, S: FieldShare<F>>() {
+    let rng = &mut ark_std::test_rng();
+    let src = &mut KingSource::<F, S>(PhantomData);
+    let size = 20;
+
+    let a_pubs: Vec<F> = (0..size).map(|_| F::rand(rng)).collect();
+    let b_pubs: Vec<F> = (0..size).map(|_| F::rand(rng)).collect();
+    let a: Vec<S> = S::king_share_batch(a_pubs.clone(), rng);
+    let b: Vec<S> = b_pubs.iter().map(|b| S::from_public(*b)).collect();
+    assert_eq!(S::batch_open(a.clone()), a_pubs);
+    assert_eq!(S::batch_open(b.clone()), b_pubs);
+    assert_eq!(S::king_share(a_pubs[0], rng).open(), a_pubs[0]);
+
+    // linear operations
+    let mut t = a[0];
+    t.add(&b[0]).scale(&b_pubs[1]).shift(&a_pubs[1]);
+    assert_eq!(t.open(), (a_pubs[0] + b_pubs[0]) * b_pubs[1] + a_pubs[1]);
+    let mut t = a[0];
+    t.sub(&b[0]).neg();
+    assert_eq!(t.open(), b_pubs[0] - a_pubs[0]);
+
+    // beaver multiplication
+    let c = a[0].mul(b[0], src);
+    assert_eq!(c.open(), a_pubs[0] * b_pubs[0]);
+    let cs = S::batch_mul(a.clone(), b.clone(), src);
+    let c_pubs = S::batch_open(cs);
+    for i in 0..size {
+        assert_eq!(c_pubs[i], a_pubs[i] * b_pubs[i]);
+    }
+
+    // inversion and division
+    assert_eq!(a[0].inv(src).open(), a_pubs[0].inverse().unwrap());
+    let ds = S::batch_div(a.clone(), b.clone(), src);
+    let d_pubs = S::batch_open(ds);
+    for i in 0..size {
+        assert_eq!(d_pubs[i], a_pubs[i] / b_pubs[i]);
+    }
+
+    // partial products
+    let ps = S::batch_open(S::partial_products(a, src));
+    let mut acc = F::one();
+    for i in 0..size {
+        acc *= a_pubs[i];
+        assert_eq!(ps[i], acc);
+    }
+}
+
+fn test_group<G: Group, S: GroupShare<G>>() {
+    let rng = &mut ark_std::test_rng();
+    let size = 5;
+    let g_pubs: Vec<G> = (0..size).map(|_| G::rand(rng)).collect();
+    let s_pubs: Vec<G::ScalarField> = (0..size).map(|_| G::ScalarField::rand(rng)).collect();
+    let gs: Vec<S> = S::king_share_batch(g_pubs.clone(), rng);
+    let ss: Vec<S::FieldShare> = S::FieldShare::king_share_batch(s_pubs.clone(), rng);
+    assert_eq!(S::batch_open(gs.clone()), g_pubs);
+
+    let mut t = gs[0];
+    t.add(&gs[1]).scale_pub_scalar(&s_pubs[0]).shift(&g_pubs[2]);
+    assert_eq!(
+        t.open(),
+        (g_pubs[0] + g_pubs[1]).mul(&s_pubs[0]) + g_pubs[2]
+    );
+
+    let t = S::scale_pub_group(g_pubs[0], &ss[0]);
+    assert_eq!(t.open(), g_pubs[0].mul(&s_pubs[0]));
+
+    let msm = S::multi_scale_pub_group(&g_pubs, &ss);
+    let msm_pub = g_pubs
+        .iter()
+        .zip(&s_pubs)
+        .fold(G::zero(), |acc, (g, s)| acc + g.mul(s));
+    assert_eq!(msm.open(), msm_pub);
+}
+
+fn test_precomputed_msm<E: PairingEngine, PS: PairingShare<E>>() {
+    let rng = &mut ark_std::test_rng();
+    let size = 16;
+    let bases: Vec<E::G1Affine> = (0..size)
+        .map(|_| E::G1Projective::rand(rng).into_affine())
+        .collect();
+    let s_pubs: Vec<E::Fr> = (0..size).map(|_| E::Fr::rand(rng)).collect();
+    let mpc_bases: Vec<MpcG1Affine<E, PS>> =
+        bases.iter().map(|b| MpcG1Affine::from_public(*b)).collect();
+    let ss: Vec<MpcField<E::Fr, PS::FrShare>> =
+        PS::FrShare::king_share_batch(s_pubs.clone(), rng)
+            .into_iter()
+            .map(MpcField::Shared)
+            .collect();
+    // The whole table, a run from its middle, and fewer scalars than bases
+    let runs = [(0, size, size), (3, 9, 6), (5, size, 4)];
+    let msms = || -> Vec<E::G1Projective> {
+        runs.iter()
+            .map(|&(a, b, n)| {
+                MpcG1Affine::multi_scalar_mul(&mpc_bases[a..b], &ss[..n]).reveal()
+            })
+            .collect()
+    };
+    let expected: Vec<E::G1Projective> = runs
+        .iter()
+        .map(|&(a, _, n)| E::G1Affine::multi_scalar_mul(&bases[a..a + n], &s_pubs[..n]))
+        .collect();
+    assert_eq!(msms(), expected);
+    MpcG1Affine::precompute_msm(&mpc_bases);
+    assert_eq!(msms(), expected);
+    mpc_algebra::msm::clear_precomputed();
+}
+
+fn test_com<F: PrimeField, S: FieldShare<F>>() {
+    let rng = &mut ark_std::test_rng();
+    let size = 8;
+    let pubs: Vec<F> = (0..size).map(|_| F::rand(rng)).collect();
+    let vs: Vec<MpcField<F, S>> = S::king_share_batch(pubs.clone(), rng)
+        .into_iter()
+        .map(MpcField::Shared)
+        .collect();
+    let (key, cmt) = MpcField::commit(&vs);
+    assert_eq!(cmt.len(), Net::n_parties());
+    for (i, p) in pubs.iter().enumerate() {
+        let (v, pf) = MpcField::open_at(&vs, &key, i);
+        assert_eq!(v, MpcField::from_public(*p));
+        assert!(MpcField::check_opening(&cmt, pf.clone(), i, v));
+        let (wrong, _) = MpcField::open_at(&vs, &key, (i + 1) % size);
+        assert!(!MpcField::check_opening(&cmt, pf, i, wrong));
+    }
+}
+
+fn test_preprocessed<F: PrimeField, S: FieldShare<F>>() {
+    // Everyone deals the same files (same seed), and keeps their own.
+    let mut files = deal_triples::<F, S, _>(Net::n_parties(), 7, 1, &mut ark_std::test_rng());
+    // Longer than needed: the source shortens them.
+    deal_eda_bits::<F, S, _>(&mut files, 1, 4 + STAT_SEC + 8, &mut ark_std::test_rng());
+    let path = std::env::temp_dir().join(format!(
+        "triples_{}_{}",
+        std::process::id(),
+        Net::party_id()
+    ));
+    files[Net::party_id()].write_to(&path);
+    install_triple_file::<F, S>(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    let rng = &mut ark_std::test_rng();
+    let size = 2;
+    let a_pubs: Vec<F> = (0..size).map(|_| F::rand(rng)).collect();
+    let b_pubs: Vec<F> = (0..size).map(|_| F::rand(rng)).collect();
+    let wrap = |v: Vec<S>| -> Vec<MpcField<F, S>> { v.into_iter().map(MpcField::Shared).collect() };
+    let mut a = wrap(S::king_share_batch(a_pubs.clone(), rng));
+    let b = wrap(S::king_share_batch(b_pubs.clone(), rng));
+    assert_eq!((a[0] * b[0]).reveal(), a_pubs[0] * b_pubs[0]);
+    MpcField::batch_product_in_place(&mut a, &b);
+    for i in 0..size {
+        assert_eq!(a[i].reveal(), a_pubs[i] * b_pubs[i]);
+    }
+    // Inversion uses a triple too, to mask the element.
+    let inv = a[0].inv().unwrap();
+    assert_eq!(inv.reveal(), (a_pubs[0] * b_pubs[0]).inverse().unwrap());
+    // Decomposing 4 bits takes an edaBit, and a triple for each bit after the first.
+    let x = MpcField::<F, S>::king_share(F::from(11u64), rng);
+    let bits: Vec<F> = x.bits(4).into_iter().map(|b| b.reveal()).collect();
+    assert_eq!(bits, vec![F::one(), F::one(), F::zero(), F::one()]);
+
+    let src = uninstall_triple_source::<S>().unwrap();
+    assert_eq!(src.remaining(), Some((0, 0)));
+}
+
+fn test_shuffle<F: PrimeField, S: FieldShare<F>>() {
+    let rng = &mut ark_std::test_rng();
+    let size = 6;
+    let pubs: Vec<F> = (0..size).map(|_| F::rand(rng)).collect();
+    let xs: Vec<MpcField<F, S>> = S::king_share_batch(pubs.clone(), rng)
+        .into_iter()
+        .map(MpcField::Shared)
+        .collect();
+    let ys = shuffle(&xs);
+    let mut shuffled: Vec<F> = ys.iter().map(|y| y.reveal()).collect();
+    let mut sorted = pubs.clone();
+    shuffled.sort();
+    sorted.sort();
+    assert_eq!(shuffled, sorted);
+
+    let mut wrong = ys.clone();
+    wrong[0] += MpcField::from_public(F::one());
+    assert!(!check_permutation(&xs, &wrong));
+    assert!(!check_permutation(&xs, &ys[1..]));
+}
+
+fn test_eval<F: PrimeField, S: FieldShare<F>>() {
+    let rng = &mut ark_std::test_rng();
+    for degree in [0, 1, 5, 8] {
+        let p_pub = DensePolynomial::<F>::rand(degree, rng);
+        let x_pub = F::rand(rng);
+        let p = DensePolynomial::from_coefficients_vec(
+            S::king_share_batch(p_pub.coeffs.clone(), rng)
+                .into_iter()
+                .map(MpcField::Shared)
+                .collect(),
+        );
+        let x = MpcField::<F, S>::king_share(x_pub, rng);
+        let y_pub = p_pub.evaluate(&x_pub);
+        assert_eq!(horner_eval(&p, x).reveal(), y_pub);
+        assert_eq!(eval_at_shared(&p, x).reveal(), y_pub);
+        assert_eq!(eval_at_shared(&p, MpcField::from_public(x_pub)).reveal(), y_pub);
+    }
+}
+
+fn test_bits<F: PrimeField, S: FieldShare<F>>() {
+    let rng = &mut ark_std::test_rng();
+    let n_bits = 16;
+    let size = 6;
+    let mut x_pubs: Vec<u64> = (0..size).map(|_| rng.gen::<u16>() as u64).collect();
+    x_pubs[0] = 0;
+    x_pubs[1] = (1 << n_bits) - 1;
+    let mut y_pubs: Vec<u64> = (0..size).map(|_| rng.gen::<u16>() as u64).collect();
+    y_pubs[2] = x_pubs[2];
+    let share = |v: &[u64]| -> Vec<MpcField<F, S>> {
+        S::king_share_batch(v.iter().map(|x| F::from(*x)).collect(), &mut rand::thread_rng())
+            .into_iter()
+            .map(MpcField::Shared)
+            .collect()
+    };
+    let (xs, ys) = (share(&x_pubs), share(&y_pubs));
+    let publics = |v: &[u64]| -> Vec<MpcField<F, S>> {
+        v.iter().map(|x| MpcField::from_public(F::from(*x))).collect()
+    };
+    let bit = |x: u64, i: usize| F::from((x >> i) & 1);
+
+    let bits = MpcField::batch_bits(&xs, n_bits);
+    let pub_bits = MpcField::batch_bits(&publics(&x_pubs), n_bits);
+    for i in 0..size {
+        let expected: Vec<F> = (0..n_bits).map(|j| bit(x_pubs[i], j)).collect();
+        let got: Vec<F> = bits[i].iter().map(|b| b.reveal()).collect();
+        assert_eq!(got, expected);
+        let got: Vec<F> = pub_bits[i].iter().map(|b| b.reveal()).collect();
+        assert_eq!(got, expected);
+    }
+
+    let lts = MpcField::batch_less_than(&xs, &ys, n_bits);
+    let pub_lts = MpcField::batch_less_than(&publics(&x_pubs), &publics(&y_pubs), n_bits);
+    for i in 0..size {
+        let expected = F::from(x_pubs[i] < y_pubs[i]);
+        assert_eq!(lts[i].reveal(), expected);
+        assert_eq!(pub_lts[i].reveal(), expected);
+    }
+    // Mixed public and shared
+    let lt = xs[3].less_than(MpcField::from_public(F::from(y_pubs[3])), n_bits);
+    assert_eq!(lt.reveal(), F::from(x_pubs[3] < y_pubs[3]));
+
+    for m in [0, 5, n_bits] {
+        let ts = MpcField::batch_trunc(&xs, m, n_bits);
+        let pub_ts = MpcField::batch_trunc(&publics(&x_pubs), m, n_bits);
+        for i in 0..size {
+            let expected = F::from(x_pubs[i] >> m);
+            assert_eq!(ts[i].reveal(), expected);
+            assert_eq!(pub_ts[i].reveal(), expected);
+        }
+    }
+}
+
+fn test_batch_div<F: PrimeField, S: FieldShare<F>>() {
+    let rng = &mut ark_std::test_rng();
+    let size = 8;
+    let a_pubs: Vec<F> = (0..size).map(|_| F::rand(rng)).collect();
+    let b_pubs: Vec<F> = (0..size).map(|_| F::rand(rng)).collect();
+    let share = |v: &[F]| -> Vec<MpcField<F, S>> {
+        S::king_share_batch(v.to_vec(), &mut rand::thread_rng())
+            .into_iter()
+            .map(MpcField::Shared)
+            .collect()
+    };
+    let publics =
+        |v: &[F]| -> Vec<MpcField<F, S>> { v.iter().map(|x| MpcField::from_public(*x)).collect() };
+    for (mut xs, ys) in vec![
+        (share(&a_pubs), share(&b_pubs)),
+        (publics(&a_pubs), share(&b_pubs)),
+        (share(&a_pubs), publics(&b_pubs)),
+        (publics(&a_pubs), publics(&b_pubs)),
+    ] {
+        MpcField::batch_division_in_place(&mut xs, &ys);
+        for i in 0..size {
+            assert_eq!(xs[i].reveal(), a_pubs[i] / b_pubs[i]);
+        }
+    }
+}
+
+fn test_coin<F: Field>() {
+    let c: F = Net::coin();
+    assert!(Net::broadcast(&c).iter().all(|d| *d == c));
+    let cs: Vec<F> = Net::coins(3);
+    assert!(Net::broadcast(&cs).iter().all(|ds| *ds == cs));
+    assert!(cs[0] != cs[1] && cs[0] != c);
+}
+
+fn test_convert<F: PrimeField>() {
+    let rng = &mut ark_std::test_rng();
+    let size = 5;
+    let x_pubs: Vec<F> = (0..size).map(|_| F::rand(rng)).collect();
+    let y_pubs: Vec<F> = (0..size).map(|_| F::rand(rng)).collect();
+    let mut xs: Vec<MpcField<F, AdditiveFieldShare<F>>> =
+        AdditiveFieldShare::king_share_batch(x_pubs.clone(), &mut rand::thread_rng())
+            .into_iter()
+            .map(MpcField::Shared)
+            .collect();
+    xs[size - 1] = MpcField::from_public(x_pubs[size - 1]);
+    let ys: Vec<MpcField<F, ShamirFieldShare<F>>> =
+        ShamirFieldShare::king_share_batch(y_pubs.clone(), &mut rand::thread_rng())
+            .into_iter()
+            .map(MpcField::Shared)
+            .collect();
+
+    let x_shamirs = MpcField::batch_to_shamir(xs.clone());
+    assert!(matches!(x_shamirs[size - 1], MpcField::Public(_)));
+    for i in 0..size {
+        assert_eq!(x_shamirs[i].reveal(), x_pubs[i]);
+        // A product under Shamir sharing, then back to additive
+        let z = (x_shamirs[i] * ys[i]).to_additive();
+        assert_eq!(z.reveal(), x_pubs[i] * y_pubs[i]);
+        assert_eq!(z.to_shamir().reveal(), x_pubs[i] * y_pubs[i]);
+    }
+    let y_adds = MpcField::batch_to_additive(ys);
+    for i in 0..size {
+        assert_eq!(y_adds[i].reveal(), y_pubs[i]);
+        assert_eq!(xs[i].to_shamir().to_additive().reveal(), x_pubs[i]);
+    }
+}
+
+fn test_prss<F: PrimeField>() {
+    let n = Net::n_parties();
+    // Additive shares, against all but one party
+    prss::install(Prss::setup(n - 1));
+    let rs = AdditiveFieldShare::<F>::prss_rand(3).unwrap();
+    assert!(AdditiveFieldShare::batch_open(rs).iter().all(|r| !r.is_zero()));
+    // Dividing now takes triples, but no inverse pairs: test_batch_div takes 24, and an inverse
+    // takes two, one to make the inverse pair.
+    let mut files = deal_triples::<F, AdditiveFieldShare<F>, _>(n, 26, 0, &mut ark_std::test_rng());
+    let file = files.remove(Net::party_id());
+    install_triple_source::<AdditiveFieldShare<F>>(Box::new(FileTripleSource::new(file)));
+    test_batch_div::<F, AdditiveFieldShare<F>>();
+    let x = MpcField::<F, AdditiveFieldShare<F>>::king_share(F::from(3u64), &mut ark_std::test_rng());
+    assert_eq!(x.inv().unwrap().reveal(), F::from(3u64).inverse().unwrap());
+    let src = uninstall_triple_source::<AdditiveFieldShare<F>>().unwrap();
+    assert_eq!(src.remaining(), Some((0, 0)));
+
+    // Shamir shares, of degree t: the first and last t + 1 parties reconstruct the same values.
+    prss::install(Prss::setup(threshold()));
+    let rs = ShamirFieldShare::<F>::batch_rand(3, &mut ark_std::test_rng());
+    let vals = Net::broadcast(&rs.iter().map(|r| r.val).collect::<Vec<F>>());
+    let t = threshold();
+    for (first, last) in (0..3).map(|i| {
+        let open = |parties: Vec<usize>| -> F {
+            lagrange_at_zero::<F>(&parties)
+                .into_iter()
+                .zip(&parties)
+                .map(|(l, &j)| l * vals[j][i])
+                .sum()
+        };
+        (open((0..=t).collect()), open((n - 1 - t..n).collect()))
+    }) {
+        assert_eq!(first, last);
+        assert!(!first.is_zero());
+    }
+    test_batch_div::<F, ShamirFieldShare<F>>();
+    assert_eq!(prss::uninstall().map(|p| p.threshold()), Some(t));
+}
+
+fn main() {
+    env_logger::builder().format_timestamp(None).init();
+    debug!("Start");
+    let opt = Opt::from_args();
+    println!("{:?}", opt);
+    Net::init_from_file(opt.input.to_str().unwrap(), opt.id);
+    debug!("{} parties", Net::n_parties());
+
+    type E = ark_bls12_377::Bls12_377;
+    type F = ark_bls12_377::Fr;
+    type G1 = ark_bls12_377::G1Projective;
+    type G2 = ark_bls12_377::G2Projective;
+
+    test_field::<F, AdditiveFieldShare<F>>();
+    test_field::<F, SpdzFieldShare<F>>();
+    check_accumulated_field_macs::<F>();
+    test_group::<G1, AdditiveGroupShare<G1, NaiveMsm<G1>>>();
+    test_group::<G1, SpdzGroupShare<G1, NaiveMsm<G1>>>();
+    test_group::<G2, SpdzGroupShare<G2, NaiveMsm<G2>>>();
+    check_accumulated_group_macs::<G1>();
+    check_accumulated_group_macs::<G2>();
+    test_precomputed_msm::<E, AdditivePairingShare<E>>();
+    test_precomputed_msm::<E, SpdzPairingShare<E>>();
+    test_com::<F, AdditiveFieldShare<F>>();
+    test_preprocessed::<F, AdditiveFieldShare<F>>();
+    test_preprocessed::<F, SpdzFieldShare<F>>();
+    test_shuffle::<F, AdditiveFieldShare<F>>();
+    test_shuffle::<F, SpdzFieldShare<F>>();
+    test_eval::<F, AdditiveFieldShare<F>>();
+    test_eval::<F, SpdzFieldShare<F>>();
+    test_bits::<F, AdditiveFieldShare<F>>();
+    test_bits::<F, SpdzFieldShare<F>>();
+    test_batch_div::<F, AdditiveFieldShare<F>>();
+    test_batch_div::<F, SpdzFieldShare<F>>();
+    test_batch_div::<F, ShamirFieldShare<F>>();
+    test_convert::<F>();
+    test_coin::<F>();
+    test_prss::<F>();
+
+    debug!("Done");
+    Net::deinit();
+}
@@ -0,0 +1,231 @@
+//! Bit decomposition, comparison and truncation of shared field elements.
+//!
+//! These treat a shared `x` as an integer of at most `n_bits` bits. [batch_bits] masks each `x`
+//! with an edaBit `r` of `n_bits + STAT_SEC` bits (see [crate::preprocessing]), and opens
+//! `c = x + r`. The field is large enough that the sum doesn't wrap, and the extra bits of `r`
+//! hide `x` statistically. Then the bits of `x = c - r mod 2^n_bits` come from a subtraction
+//! circuit, between the public bits of `c` and the shared bits of `r`. It takes one
+//! multiplication per bit, so `n_bits` rounds, however many values are decomposed together.
+//!
+//! Comparison ([batch_less_than]) and truncation ([batch_trunc]) are built on decomposition.
+//! The same operations are methods of [MpcField], for witness generation under MPC.
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_std::{end_timer, start_timer};
+
+use crate::preprocessing::GlobalTripleSource;
+use crate::share::field::FieldShare;
+use crate::share::BeaverSource;
+use crate::wire::field::MpcField;
+
+/// Statistical security: opening `x + r` tells a party about `x` with probability at most
+/// `2^-STAT_SEC`.
+pub const STAT_SEC: usize = 40;
+
+/// The most bits that values can have, for decomposition in field `F`.
+pub fn max_bits<F: PrimeField>() -> usize {
+    // x + r < 2^(n_bits + STAT_SEC + 1) must be less than the modulus, which is at least
+    // 2^(size_in_bits - 1).
+    F::size_in_bits() - STAT_SEC - 2
+}
+
+/// The value whose bits, least significant first, are shared in `bits`.
+pub fn from_bits<F: Field, S: FieldShare<F>>(bits: &[S]) -> S {
+    let two = F::from(2u64);
+    let mut acc = S::from_public(F::zero());
+    for b in bits.iter().rev() {
+        acc.scale(&two).add(b);
+    }
+    acc
+}
+
+/// Shares of the low `n_bits` bits of each `x`, least significant first.
+///
+/// Each `x` must be less than `2^n_bits`: a larger one is decomposed wrongly, and opening
+/// `x + r` may reveal it.
+pub fn batch_bits<F: PrimeField, S: FieldShare<F>, B: BeaverSource<S, S, S>>(
+    xs: Vec<S>,
+    n_bits: usize,
+    src: &mut B,
+) -> Vec<Vec<S>> {
+    assert!(
+        n_bits <= max_bits::<F>(),
+        "Can't decompose values of {} bits in a field of {} bits",
+        n_bits,
+        F::size_in_bits()
+    );
+    let n = xs.len();
+    let timer = start_timer!(|| format!("Bits of {} values ({} bits)", n, n_bits));
+    let masks = src.eda_bits(n, n_bits + STAT_SEC);
+    let cs = S::batch_open(xs.into_iter().zip(&masks).map(|(mut x, (r, _))| {
+        x.add(r);
+        x
+    }));
+    let cs: Vec<Vec<bool>> = cs.iter().map(|c| c.into_repr().to_bits_le()).collect();
+
+    // Subtract the low bits of r from those of c, one position at a time. With the borrow `br`
+    // into a position, and `p = b * br` for r's bit `b`, the difference bit is `c ^ b ^ br`,
+    // where `b ^ br = b + br - 2p`, and the borrow out is `b | br = b + br - p` if c's bit is 0,
+    // and `!b & br = br - p` if it is 1.
+    let (one, two) = (F::one(), F::from(2u64));
+    let zero = S::from_public(F::zero());
+    let mut borrows = vec![zero; n];
+    let mut out: Vec<Vec<S>> = vec![Vec::with_capacity(n_bits); n];
+    for j in 0..n_bits {
+        let bs: Vec<S> = masks.iter().map(|(_, bits)| bits[j]).collect();
+        let ps = if j == 0 {
+            vec![zero; n]
+        } else {
+            S::batch_mul(bs.clone(), borrows.clone(), src)
+        };
+        for i in 0..n {
+            let (b, br, mut p) = (bs[i], borrows[i], ps[i]);
+            let mut borrow = br;
+            borrow.sub(&p);
+            if !cs[i][j] {
+                borrow.add(&b);
+            }
+            let mut d = b;
+            d.add(&br).sub(p.scale(&two));
+            if cs[i][j] {
+                d.neg().shift(&one);
+            }
+            out[i].push(d);
+            borrows[i] = borrow;
+        }
+    }
+    end_timer!(timer);
+    out
+}
+
+/// Shares of whether `x < y`, as 1 or 0, for each pair, given that each `x` and `y` is less
+/// than `2^n_bits`.
+pub fn batch_less_than<F: PrimeField, S: FieldShare<F>, B: BeaverSource<S, S, S>>(
+    xs: Vec<S>,
+    ys: Vec<S>,
+    n_bits: usize,
+    src: &mut B,
+) -> Vec<S> {
+    assert_eq!(xs.len(), ys.len());
+    // x - y + 2^n_bits is in [1, 2^(n_bits + 1)), and its top bit is set iff x >= y.
+    let offset = F::from(2u64).pow([n_bits as u64]);
+    let zs = xs
+        .into_iter()
+        .zip(ys)
+        .map(|(mut x, y)| {
+            x.sub(&y).shift(&offset);
+            x
+        })
+        .collect();
+    batch_bits(zs, n_bits + 1, src)
+        .into_iter()
+        .map(|bits| {
+            let mut lt = bits[n_bits];
+            lt.neg().shift(&F::one());
+            lt
+        })
+        .collect()
+}
+
+/// Shares of `x >> m`, that is, `x / 2^m` rounded down, given that each `x` is less than
+/// `2^n_bits`.
+pub fn batch_trunc<F: PrimeField, S: FieldShare<F>, B: BeaverSource<S, S, S>>(
+    xs: Vec<S>,
+    m: usize,
+    n_bits: usize,
+    src: &mut B,
+) -> Vec<S> {
+    assert!(m <= n_bits);
+    batch_bits(xs, n_bits, src)
+        .iter()
+        .map(|bits| from_bits(&bits[m..]))
+        .collect()
+}
+
+impl<F: PrimeField, S: FieldShare<F>> MpcField<F, S> {
+    /// The values of `xs`, if all are public.
+    fn publics(xs: &[Self]) -> Option<Vec<F>> {
+        xs.iter()
+            .map(|x| match x {
+                MpcField::Public(x) => Some(*x),
+                MpcField::Shared(_) => None,
+            })
+            .collect()
+    }
+
+    /// The low `n_bits` bits of this value, least significant first, which must be less than
+    /// `2^n_bits` (see [batch_bits]).
+    pub fn bits(self, n_bits: usize) -> Vec<Self> {
+        Self::batch_bits(&[self], n_bits).pop().unwrap()
+    }
+    /// The bits of many values at once: see [MpcField::bits].
+    pub fn batch_bits(xs: &[Self], n_bits: usize) -> Vec<Vec<Self>> {
+        match Self::publics(xs) {
+            Some(xs) => xs
+                .iter()
+                .map(|x| {
+                    let bits = x.into_repr().to_bits_le();
+                    bits[..n_bits]
+                        .iter()
+                        .map(|b| MpcField::Public(F::from(*b)))
+                        .collect()
+                })
+                .collect(),
+            None => {
+                let src = &mut GlobalTripleSource::default();
+                batch_bits(Self::shares(xs), n_bits, src)
+                    .into_iter()
+                    .map(|bits| bits.into_iter().map(MpcField::Shared).collect())
+                    .collect()
+            }
+        }
+    }
+
+    /// Whether this value is less than `other`, as 1 or 0. Both must be less than `2^n_bits`.
+    pub fn less_than(self, other: Self, n_bits: usize) -> Self {
+        Self::batch_less_than(&[self], &[other], n_bits)
+            .pop()
+            .unwrap()
+    }
+    /// Many comparisons at once: see [MpcField::less_than].
+    pub fn batch_less_than(xs: &[Self], ys: &[Self], n_bits: usize) -> Vec<Self> {
+        match (Self::publics(xs), Self::publics(ys)) {
+            (Some(xs), Some(ys)) => xs
+                .iter()
+                .zip(&ys)
+                .map(|(x, y)| MpcField::Public(F::from(x.into_repr() < y.into_repr())))
+                .collect(),
+            _ => {
+                let src = &mut GlobalTripleSource::default();
+                batch_less_than(Self::shares(xs), Self::shares(ys), n_bits, src)
+                    .into_iter()
+                    .map(MpcField::Shared)
+                    .collect()
+            }
+        }
+    }
+
+    /// This value shifted right by `m` bits. It must be less than `2^n_bits`.
+    pub fn trunc(self, m: usize, n_bits: usize) -> Self {
+        Self::batch_trunc(&[self], m, n_bits).pop().unwrap()
+    }
+    /// Many truncations at once: see [MpcField::trunc].
+    pub fn batch_trunc(xs: &[Self], m: usize, n_bits: usize) -> Vec<Self> {
+        match Self::publics(xs) {
+            Some(xs) => xs
+                .iter()
+                .map(|x| {
+                    let mut x = x.into_repr();
+                    x.divn(m as u32);
+                    MpcField::Public(F::from_repr(x).unwrap())
+                })
+                .collect(),
+            None => {
+                let src = &mut GlobalTripleSource::default();
+                batch_trunc(Self::shares(xs), m, n_bits, src)
+                    .into_iter()
+                    .map(MpcField::Shared)
+                    .collect()
+            }
+        }
+    }
+}
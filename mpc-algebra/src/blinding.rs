@@ -0,0 +1,99 @@
+//! Randomness for blinds that no party may learn.
+//!
+//! Sampling a shared value (as [UniformRand](ark_ff::UniformRand) does for an
+//! [MpcField](crate::MpcField)) takes each party's contribution from that party's RNG: its
+//! additive share, or the value it deals into a joint sharing. The result is jointly random, and
+//! unknown to every party, only if those contributions are independent and private. A seed the
+//! parties share (like `ark_std::test_rng`) gives each party every contribution, so it knows the
+//! "secret" blind.
+//!
+//! [BlindingRng] is a party-private stream, for sampling the blinds of hiding commitments. It is
+//! either seeded from the operating system, or derived from the party's [BlindingSeed] for one
+//! proof, so that the party can later show (say, to an auditor) how its blinds were drawn without
+//! having stored them.
+use blake2::{Blake2s, Digest};
+use rand::{CryptoRng, Error, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use zeroize::Zeroize;
+
+/// A cryptographic RNG private to this party: seeded from its operating system, or derived from
+/// its [BlindingSeed], and so unknown to the others.
+///
+/// See the [module docs](self).
+pub struct BlindingRng(ChaCha20Rng);
+
+impl BlindingRng {
+    pub fn new() -> Self {
+        Self(ChaCha20Rng::from_entropy())
+    }
+}
+
+impl Default for BlindingRng {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RngCore for BlindingRng {
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest)
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.0.try_fill_bytes(dest)
+    }
+}
+
+impl CryptoRng for BlindingRng {}
+
+/// A party's secret seed, from which it derives the [BlindingRng] of each proof.
+///
+/// Seeds form a tree: each has a child for every label (see [BlindingSeed::child]), which
+/// reveals nothing of its parent or siblings. A proof's blinds come from the master seed's child
+/// for the circuit, and that child's for the session (see [BlindingSeed::for_proof]). So a party
+/// can disclose one circuit's seed, letting an auditor rederive every blind it drew for that
+/// circuit, and no other.
+///
+/// A seed must never serve two proofs: blinds drawn twice could be cancelled out between the
+/// proofs' commitments. Each session must have its own id.
+#[derive(Clone, Zeroize)]
+#[zeroize(drop)]
+pub struct BlindingSeed([u8; 32]);
+
+/// Blake2s personalization of seed derivation
+const SEED_PERSONA: &[u8] = b"mpcblind";
+
+impl BlindingSeed {
+    pub fn new(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+    /// A master seed from the operating system.
+    pub fn from_entropy() -> Self {
+        let mut bytes = [0u8; 32];
+        rand::rngs::OsRng.fill_bytes(&mut bytes);
+        Self(bytes)
+    }
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+    /// The child seed for `label`: this seed's keyed hash of it.
+    pub fn child(&self, label: &[u8]) -> Self {
+        let mut h = Blake2s::with_params(&self.0, &[], SEED_PERSONA);
+        h.update(label);
+        Self(h.finalize().into())
+    }
+    /// The seed of the blinds for one proof, about the circuit with `circuit_digest`, in session
+    /// `session`.
+    pub fn for_proof(&self, circuit_digest: &[u8], session: &[u8]) -> Self {
+        self.child(circuit_digest).child(session)
+    }
+    /// The stream of blinds this seed gives.
+    pub fn rng(&self) -> BlindingRng {
+        BlindingRng(ChaCha20Rng::from_seed(self.0))
+    }
+}
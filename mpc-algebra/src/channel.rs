@@ -1,11 +1,15 @@
+use ark_ff::UniformRand;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use digest::Digest;
-use rand::RngCore;
+use log::debug;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 use sha2::Sha256;
 use std::cell::Cell;
 
 use mpc_net::two as net_two;
 
+use mpc_net::chunked::Progress;
 use mpc_net::MpcNet;
 
 pub trait MpcSerNet: MpcNet {
@@ -20,6 +24,39 @@ pub trait MpcSerNet: MpcNet {
             .collect()
     }
 
+    /// Broadcast `out`, and receive what each party sent us, or `None` from parties that failed,
+    /// so long as at least `quorum` parties respond (see [MpcNet::try_broadcast_bytes_quorum]).
+    #[inline]
+    fn broadcast_quorum<T: CanonicalDeserialize + CanonicalSerialize>(
+        out: &T,
+        quorum: usize,
+    ) -> Vec<Option<T>> {
+        let mut bytes_out = Vec::new();
+        out.serialize(&mut bytes_out).unwrap();
+        Self::try_broadcast_bytes_quorum(&bytes_out, quorum)
+            .unwrap_or_else(|e| e.raise())
+            .into_iter()
+            .map(|b| b.map(|b| T::deserialize(&b[..]).unwrap()))
+            .collect()
+    }
+
+    /// Send `outs[i]` to party `i`, privately, and receive what each party sent us.
+    #[inline]
+    fn send_to_each<T: CanonicalDeserialize + CanonicalSerialize>(outs: &[T]) -> Vec<T> {
+        let bytes_out = outs
+            .iter()
+            .map(|out| {
+                let mut bytes_out = Vec::new();
+                out.serialize(&mut bytes_out).unwrap();
+                bytes_out
+            })
+            .collect();
+        Self::send_bytes_to_each(bytes_out)
+            .into_iter()
+            .map(|b| T::deserialize(&b[..]).unwrap())
+            .collect()
+    }
+
     #[inline]
     fn send_to_king<T: CanonicalDeserialize + CanonicalSerialize>(out: &T) -> Option<Vec<T>> {
         let mut bytes_out = Vec::new();
@@ -74,10 +111,69 @@ pub trait MpcSerNet: MpcNet {
             .collect()
     }
 
+    /// A random seed that all parties agree on, and that no party can bias (alone).
+    ///
+    /// Each party contributes randomness through [MpcSerNet::atomic_broadcast], so no party can
+    /// choose its contribution after seeing the others'. The seed is the hash of all of them.
+    fn joint_seed() -> Vec<u8> {
+        let mut ours = vec![0u8; COMMIT_RAND_BYTES];
+        rand::thread_rng().fill_bytes(&mut ours);
+        let all = Self::atomic_broadcast(&ours);
+        all.iter()
+            .fold(CommitHash::new(), |h, c| h.chain(c))
+            .finalize()
+            .to_vec()
+    }
+
+    /// A public random value that all parties agree on: see [MpcSerNet::coins].
+    fn coin<F: UniformRand>() -> F {
+        Self::coins(1).pop().unwrap()
+    }
+
+    /// `n` public random values that all parties agree on, and that no party can bias or predict
+    /// (alone) before all have contributed.
+    ///
+    /// They come from an RNG seeded with a [MpcSerNet::joint_seed], so any number of them costs
+    /// one commit-then-reveal exchange.
+    fn coins<F: UniformRand>(n: usize) -> Vec<F> {
+        let mut seed = [0u8; 32];
+        seed.copy_from_slice(&Self::joint_seed()[..32]);
+        let rng = &mut ChaCha20Rng::from_seed(seed);
+        (0..n).map(|_| F::rand(rng)).collect()
+    }
+
+    /// Send `x` to the king, who computes `f` of every party's, and sends each party its result.
+    ///
+    /// Every party's `x` must serialize to the same length (see [MpcNet::king_compute]).
     #[inline]
-    fn king_compute<T: CanonicalDeserialize + CanonicalSerialize>(x: &T, f: impl Fn(Vec<T>) -> Vec<T>) -> T {
-        let king_response = Self::send_to_king(x).map(f);
-        Self::recv_from_king(king_response)
+    fn king_compute<T: CanonicalDeserialize + CanonicalSerialize>(
+        x: &T,
+        f: impl Fn(Vec<T>) -> Vec<T>,
+    ) -> T
+    where
+        Self: Sized,
+    {
+        let mut bytes_out = Vec::new();
+        x.serialize(&mut bytes_out).unwrap();
+        let bytes_in = <Self as MpcNet>::king_compute(
+            &bytes_out,
+            |bytes_in| {
+                let xs = bytes_in
+                    .into_iter()
+                    .map(|b| T::deserialize(&b[..]).unwrap())
+                    .collect();
+                f(xs)
+                    .iter()
+                    .map(|out| {
+                        let mut bytes_out = Vec::new();
+                        out.serialize(&mut bytes_out).unwrap();
+                        bytes_out
+                    })
+                    .collect()
+            },
+            |p: Progress| debug!("King compute: {} of {} bytes", p.bytes_done, p.bytes),
+        );
+        T::deserialize(&bytes_in[..]).unwrap()
     }
 }
 
@@ -95,7 +191,7 @@ type CommitHash = Sha256;
 pub fn exchange<F: CanonicalSerialize + CanonicalDeserialize>(f: &F) -> F {
     let mut bytes_out = Vec::new();
     f.serialize(&mut bytes_out).unwrap();
-    let bytes_in = net_two::exchange_bytes(&bytes_out).unwrap();
+    let bytes_in = net_two::exchange_bytes(&bytes_out).unwrap_or_else(|_| net_two::peer_failed());
     F::deserialize(&bytes_in[..]).unwrap()
 }
 
@@ -112,9 +208,11 @@ pub fn atomic_exchange<F: CanonicalSerialize + CanonicalDeserialize>(f: &F) -> F
     rand::thread_rng().fill_bytes(&mut bytes_out[ser_len..]);
     let commitment = CommitHash::new().chain(&bytes_out).finalize();
     // exchange commitments
-    let other_commitment = net_two::exchange_bytes(&commitment[..]).unwrap();
+    let other_commitment =
+        net_two::exchange_bytes(&commitment[..]).unwrap_or_else(|_| net_two::peer_failed());
     // exchange (data || randomness)
-    let other_bytes = net_two::exchange_bytes(&bytes_out).unwrap();
+    let other_bytes =
+        net_two::exchange_bytes(&bytes_out).unwrap_or_else(|_| net_two::peer_failed());
     // check other commitment
     assert_eq!(
         &other_commitment[..],
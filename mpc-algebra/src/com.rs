@@ -6,8 +6,8 @@ use ark_ff::prelude::*;
 use ark_ff::FftField;
 use mpc_trait::MpcWire;
 
-use crate::{channel, Reveal};
-use mpc_net::two as net_two;
+use crate::{channel::MpcSerNet, Reveal};
+use mpc_net::MpcMultiNet as Net;
 use crate::wire::field::MpcField;
 use crate::share::field::FieldShare;
 
@@ -23,13 +23,11 @@ pub trait ComField: FftField + MpcWire {
 }
 
 impl<Fr: PrimeField, S: FieldShare<Fr>>  ComField for MpcField<Fr, S> {
-    type Commitment = (Vec<u8>, Vec<u8>);
+    /// Each party's Merkle root, by party id
+    type Commitment = Vec<Vec<u8>>;
     type Key = Vec<Vec<Vec<u8>>>;
-    type OpeningProof = (
-        Fr,
-        Fr,
-        Vec<(Vec<u8>, Vec<u8>)>,
-    );
+    /// Each party's leaf, and each party's Merkle path, by party id
+    type OpeningProof = (Vec<Fr>, Vec<Vec<Vec<u8>>>);
     fn public_rand<R: Rng>(r: &mut R) -> Self {
         Self::from_public(Fr::rand(r))
     }
@@ -37,13 +35,7 @@ impl<Fr: PrimeField, S: FieldShare<Fr>>  ComField for MpcField<Fr, S> {
         let mut tree = Vec::new();
         let mut hashes: Vec<Vec<u8>> = vs
             .into_iter()
-            .enumerate()
-            .map(|(_i, v)| {
-                let mut bytes_out = Vec::new();
-                v.unwrap_as_public().serialize(&mut bytes_out).unwrap();
-                let o = sha2::Sha256::digest(&bytes_out[..]).as_slice().to_owned();
-                o
-            })
+            .map(|v| leaf_hash(&v.unwrap_as_public()))
             .collect();
         assert!(hashes.len().is_power_of_two());
         while hashes.len() > 1 {
@@ -58,67 +50,55 @@ impl<Fr: PrimeField, S: FieldShare<Fr>>  ComField for MpcField<Fr, S> {
             tree.push(std::mem::replace(&mut hashes, new));
         }
         let slf = hashes.pop().unwrap();
-        let other = net_two::exchange_bytes(&slf).unwrap();
-        if net_two::am_first() {
-            (tree, (other, slf))
-        } else {
-            (tree, (slf, other))
-        }
+        (tree, Net::broadcast(&slf))
     }
     fn open_at(inputs: &[Self], tree: &Self::Key, mut i: usize) -> (Self, Self::OpeningProof) {
         let self_f = inputs[i].unwrap_as_public();
-        let other_f = channel::exchange(&self_f);
+        let all_f = Net::broadcast(&self_f);
         let mut siblings = Vec::new();
         for level in 0..tree.len() {
             siblings.push(tree[level][i ^ 1].clone());
             i /= 2;
         }
         assert_eq!(i / 2, 0);
-        let other: Vec<_> = siblings
-            .iter()
-            .map(|s| net_two::exchange_bytes(s).unwrap())
-            .collect();
-        let p = if net_two::am_first() {
-            siblings.into_iter().zip(other.into_iter()).collect()
-        } else {
-            other.into_iter().zip(siblings.into_iter()).collect()
-        };
+        let all_siblings = Net::broadcast(&siblings);
         (
-            MpcField::from_public(self_f + other_f),
-            if net_two::am_first() {
-                (self_f, other_f, p)
-            } else {
-                (other_f, self_f, p)
-            },
+            MpcField::from_public(all_f.iter().sum()),
+            (all_f, all_siblings),
         )
     }
     fn check_opening(c: &Self::Commitment, p: Self::OpeningProof, i: usize, v: Self) -> bool {
-        if p.0 + p.1 != v.reveal() {
+        let (leaves, paths) = p;
+        if leaves.len() != c.len() || paths.len() != c.len() {
             return false;
         }
-        let mut hash0 = Vec::new();
-        p.0.serialize(&mut hash0).unwrap();
-        hash0 = sha2::Sha256::digest(&hash0).as_slice().to_owned();
-        let mut hash1 = Vec::new();
-        p.1.serialize(&mut hash1).unwrap();
-        hash1 = sha2::Sha256::digest(&hash1).as_slice().to_owned();
-        for (j, (sib0, sib1)) in p.2.into_iter().enumerate() {
-            let mut h0 = sha2::Sha256::default();
-            let mut h1 = sha2::Sha256::default();
-            if (i >> j) & 1 == 0 {
-                h0.update(&hash0);
-                h0.update(&sib0);
-                h1.update(&hash1);
-                h1.update(&sib1);
-            } else {
-                h0.update(&sib0);
-                h0.update(&hash0);
-                h1.update(&sib1);
-                h1.update(&hash1);
-            }
-            hash0 = h0.finalize().as_slice().to_owned();
-            hash1 = h1.finalize().as_slice().to_owned();
+        if leaves.iter().sum::<Fr>() != v.reveal() {
+            return false;
         }
-        &(hash1, hash0) == c
+        leaves
+            .iter()
+            .zip(paths)
+            .zip(c)
+            .all(|((leaf, path), root)| {
+                let mut hash = leaf_hash(leaf);
+                for (j, sib) in path.into_iter().enumerate() {
+                    let mut h = sha2::Sha256::default();
+                    if (i >> j) & 1 == 0 {
+                        h.update(&hash);
+                        h.update(&sib);
+                    } else {
+                        h.update(&sib);
+                        h.update(&hash);
+                    }
+                    hash = h.finalize().as_slice().to_owned();
+                }
+                &hash == root
+            })
     }
 }
+
+fn leaf_hash<F: PrimeField>(f: &F) -> Vec<u8> {
+    let mut bytes_out = Vec::new();
+    f.serialize(&mut bytes_out).unwrap();
+    sha2::Sha256::digest(&bytes_out[..]).as_slice().to_owned()
+}
@@ -0,0 +1,316 @@
+//! Opens that wait for a synchronization point, so that many share a round.
+//!
+//! [enqueue] queues the opens that [MpcWire::publicize] would make, without communicating.
+//! [flush] makes every queued open, in one round per share type (and, for MAC'd shares, one MAC
+//! check). [Deferred::get] then makes the value public from those openings, flushing first if
+//! any are still queued.
+//!
+//! Like all opens, these are collective: every party must enqueue, flush, and get the same
+//! values, in the same order. The queue is per thread.
+use mpc_trait::MpcWire;
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::mem;
+
+use crate::Reveal;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    /// Publicizing opens now
+    Now,
+    /// Publicizing queues opens
+    Collect,
+    /// Publicizing takes queued opens' values
+    Distribute,
+}
+
+/// The opens of one share type.
+trait Lane {
+    fn flush(&mut self);
+    /// Opens, queued or made
+    fn len(&self) -> usize;
+    fn queued(&self) -> usize;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+struct Shares<S: Reveal> {
+    queued: Vec<S>,
+    opened: Vec<S::Base>,
+    batch_reveal: fn(Vec<S>) -> Vec<S::Base>,
+}
+
+impl<S: Reveal + 'static> Lane for Shares<S>
+where
+    S::Base: 'static,
+{
+    fn flush(&mut self) {
+        let queued = mem::take(&mut self.queued);
+        self.opened.extend((self.batch_reveal)(queued));
+    }
+    fn len(&self) -> usize {
+        self.opened.len() + self.queued.len()
+    }
+    fn queued(&self) -> usize {
+        self.queued.len()
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+struct Queue {
+    mode: Mode,
+    /// In order of first use, so that every party flushes them in the same order
+    lanes: Vec<(TypeId, Box<dyn Lane>)>,
+    /// Where each lane's next distributed value is
+    cursors: Vec<usize>,
+    /// Handles not yet taken
+    outstanding: usize,
+}
+
+thread_local! {
+    static QUEUE: RefCell<Queue> = RefCell::new(Queue {
+        mode: Mode::Now,
+        lanes: Vec::new(),
+        cursors: Vec::new(),
+        outstanding: 0,
+    });
+}
+
+/// A value whose opens are queued. See the [module docs](self).
+#[must_use = "the queued opens are made by `get`"]
+pub struct Deferred<T> {
+    value: Option<T>,
+    /// Where its opens start, in each lane
+    starts: Vec<usize>,
+    /// Where they end
+    ends: Vec<usize>,
+}
+
+fn lane_lens() -> Vec<usize> {
+    QUEUE.with(|q| q.borrow().lanes.iter().map(|(_, l)| l.len()).collect())
+}
+
+fn with_mode<R>(mode: Mode, f: impl FnOnce() -> R) -> R {
+    let old = QUEUE.with(|q| mem::replace(&mut q.borrow_mut().mode, mode));
+    let r = f();
+    QUEUE.with(|q| q.borrow_mut().mode = old);
+    r
+}
+
+/// Queue the opens that publicizing `t` makes.
+pub fn enqueue<T: MpcWire>(mut t: T) -> Deferred<T> {
+    let starts = lane_lens();
+    with_mode(Mode::Collect, || t.publicize());
+    QUEUE.with(|q| q.borrow_mut().outstanding += 1);
+    Deferred {
+        value: Some(t),
+        starts,
+        ends: lane_lens(),
+    }
+}
+
+/// Make every queued open.
+pub fn flush() {
+    // Lanes are flushed outside the borrow, since they communicate (and may publicize).
+    let mut lanes = QUEUE.with(|q| mem::take(&mut q.borrow_mut().lanes));
+    for (_, lane) in &mut lanes {
+        if lane.queued() > 0 {
+            lane.flush();
+        }
+    }
+    QUEUE.with(|q| {
+        let mut q = q.borrow_mut();
+        let added = mem::replace(&mut q.lanes, lanes);
+        q.lanes.extend(added);
+    });
+}
+
+impl<T: MpcWire> Deferred<T> {
+    /// The value, made public.
+    pub fn get(mut self) -> T {
+        let mut t = self.value.take().unwrap();
+        let opened = QUEUE.with(|q| {
+            let q = q.borrow();
+            q.lanes
+                .iter()
+                .zip(&self.ends)
+                .all(|((_, l), end)| l.len() - l.queued() >= *end)
+        });
+        if !opened {
+            flush();
+        }
+        let mut starts = self.starts.clone();
+        starts.resize(self.ends.len(), 0);
+        let cursors = QUEUE.with(|q| mem::replace(&mut q.borrow_mut().cursors, starts));
+        with_mode(Mode::Distribute, || t.publicize());
+        QUEUE.with(|q| q.borrow_mut().cursors = cursors);
+        t
+    }
+}
+
+impl<T> Drop for Deferred<T> {
+    fn drop(&mut self) {
+        QUEUE.with(|q| {
+            let mut q = q.borrow_mut();
+            q.outstanding -= 1;
+            if q.outstanding == 0 && q.lanes.iter().all(|(_, l)| l.queued() == 0) {
+                q.lanes.clear();
+            }
+        });
+    }
+}
+
+/// Reveal share `s`, as the queue says: now, or by queueing it (giving `None`), or from its
+/// queued open. `batch_reveal` reveals many shares of its type at once.
+pub(crate) fn reveal<S: Reveal + 'static>(
+    s: S,
+    batch_reveal: fn(Vec<S>) -> Vec<S::Base>,
+) -> Option<S::Base>
+where
+    S::Base: Clone + 'static,
+{
+    let mode = QUEUE.with(|q| q.borrow().mode);
+    if mode == Mode::Now {
+        return Some(s.reveal());
+    }
+    QUEUE.with(|q| {
+        let mut q = q.borrow_mut();
+        let id = TypeId::of::<S>();
+        let i = q.lanes.iter().position(|(t, _)| *t == id);
+        if mode == Mode::Collect {
+            let i = i.unwrap_or_else(|| {
+                let lane = Shares::<S> {
+                    queued: Vec::new(),
+                    opened: Vec::new(),
+                    batch_reveal,
+                };
+                q.lanes.push((id, Box::new(lane)));
+                q.lanes.len() - 1
+            });
+            shares::<S>(&mut *q.lanes[i].1).queued.push(s);
+            None
+        } else {
+            let i = i.expect("Value was not queued");
+            let at = q.cursors[i];
+            q.cursors[i] += 1;
+            Some(shares::<S>(&mut *q.lanes[i].1).opened[at].clone())
+        }
+    })
+}
+
+fn shares<S: Reveal + 'static>(lane: &mut dyn Lane) -> &mut Shares<S>
+where
+    S::Base: 'static,
+{
+    lane.as_any_mut().downcast_mut().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::honest_but_curious::{MpcField, MpcGroup};
+    use crate::share::{add::AdditiveFieldShare, FieldShare};
+    use ark_bls12_377::{Fr, G1Projective};
+    use ark_ff::{UniformRand, Zero};
+    use ark_std::test_rng;
+    use mpc_net::{MpcMultiNet as Net, MpcNet};
+
+    type MF = MpcField<Fr>;
+    type MG = MpcGroup<G1Projective>;
+
+    /// Opens queued, over all lanes.
+    fn queued() -> usize {
+        QUEUE.with(|q| q.borrow().lanes.iter().map(|(_, l)| l.queued()).sum())
+    }
+
+    /// Party 0's shares of some field and group values (the others' are zeros), and the values.
+    #[allow(clippy::type_complexity)]
+    fn shares(id: usize) -> ((Vec<MF>, Vec<MG>), (Vec<Fr>, Vec<G1Projective>)) {
+        let rng = &mut test_rng();
+        let xs: Vec<Fr> = (0..4).map(|_| Fr::rand(rng)).collect();
+        let gs: Vec<G1Projective> = (0..3).map(|_| G1Projective::rand(rng)).collect();
+        let x_shares = xs
+            .iter()
+            .map(|x| MF::from_add_shared(if id == 0 { *x } else { Fr::zero() }))
+            .collect();
+        let g_shares = gs
+            .iter()
+            .map(|g| MG::from_add_shared(if id == 0 { *g } else { G1Projective::zero() }))
+            .collect();
+        ((x_shares, g_shares), (xs, gs))
+    }
+
+    #[test]
+    fn deferred_opens_match_eager_ones() {
+        let results = Net::simulate(2, |id| {
+            let ((xs, gs), values) = shares(id);
+            let eager = (xs.clone().reveal(), gs.clone().reveal());
+            let dx = enqueue(xs);
+            let dg = enqueue(gs);
+            // A public value queues nothing
+            let dp = enqueue(MF::from_public(Fr::from(5u64)));
+            assert_eq!(queued(), 4 + 3);
+            Net::reset_stats();
+            flush();
+            // One round per share type
+            let broadcasts = Net::stats().broadcasts;
+            assert_eq!(queued(), 0);
+            let deferred = (dx.get().reveal(), dg.get().reveal());
+            assert_eq!(dp.get(), MF::from_public(Fr::from(5u64)));
+            (eager, deferred, values, broadcasts)
+        });
+        for (eager, deferred, values, broadcasts) in results {
+            assert_eq!(eager, values);
+            assert_eq!(deferred, values);
+            assert_eq!(broadcasts, 2);
+        }
+    }
+
+    #[test]
+    fn get_flushes_what_is_queued() {
+        let results = Net::simulate(2, |id| {
+            let ((xs, _), (values, _)) = shares(id);
+            let first = enqueue(xs[..2].to_vec());
+            let second = enqueue(xs[2..].to_vec());
+            // Opens the second's values too
+            let first = first.get();
+            assert_eq!(queued(), 0);
+            let second = second.get();
+            // Queued after a flush, a value is opened by the next
+            let third = enqueue(xs[0]).get();
+            ([first, second].concat().reveal(), third.reveal(), values)
+        });
+        for (opened, third, values) in results {
+            assert_eq!(opened, values);
+            assert_eq!(third, values[0]);
+        }
+    }
+
+    #[test]
+    fn dropped_handles_leave_the_queue_consistent() {
+        let results = Net::simulate(2, |id| {
+            let ((xs, _), (values, _)) = shares(id);
+            // Dropped unopened: its opens stay queued, for the next flush
+            drop(enqueue(xs[..2].to_vec()));
+            assert_eq!(queued(), 2);
+            let kept = enqueue(xs[2..].to_vec()).get();
+            // Every handle is gone, and nothing is queued, so the lanes are cleared
+            let lanes = QUEUE.with(|q| q.borrow().lanes.len());
+            (kept.reveal(), values, lanes)
+        });
+        for (kept, values, lanes) in results {
+            assert_eq!(kept, values[2..]);
+            assert_eq!(lanes, 0);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Value was not queued")]
+    fn distributing_an_unqueued_open_panics() {
+        let share = AdditiveFieldShare::from_add_shared(Fr::from(1u64));
+        with_mode(Mode::Distribute, || {
+            reveal(share, AdditiveFieldShare::batch_reveal)
+        });
+    }
+}
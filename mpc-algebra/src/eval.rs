@@ -0,0 +1,80 @@
+//! Evaluating shared polynomials at secret-shared points.
+//!
+//! With a public point, evaluation is local. With a shared point, the obvious approach is Horner's
+//! rule over shares, but that costs one round of communication per degree. [eval_at_shared] does
+//! it in a logarithmic number of rounds, most of which don't depend on the point (and so could be
+//! preprocessed): we mask the point `x` with a random shared `r`, open `d = x - r`, and write
+//! `p(x) = p(r + d) = q(r)`, where `q(y) = p(y + d)` has shared coefficients that are linear in
+//! those of `p`. Given shares of the powers of `r`, evaluating `q(r)` takes one batch of
+//! multiplications.
+use ark_ff::prelude::*;
+use ark_poly::univariate::DensePolynomial;
+use ark_poly::Polynomial;
+use ark_std::{end_timer, start_timer};
+
+use crate::preprocessing::GlobalTripleSource;
+use crate::share::field::FieldShare;
+use crate::wire::field::MpcField;
+
+/// Evaluate `p` at `x` by Horner's rule: one multiplication (round) per degree.
+pub fn horner_eval<F: PrimeField, S: FieldShare<F>>(
+    p: &DensePolynomial<MpcField<F, S>>,
+    x: MpcField<F, S>,
+) -> MpcField<F, S> {
+    p.coeffs
+        .iter()
+        .rev()
+        .fold(MpcField::zero(), |acc, c| acc * x + c)
+}
+
+/// Shares of `1, r, r^2, ..., r^n`, in a logarithmic number of rounds.
+fn powers<F: PrimeField, S: FieldShare<F>>(r: S, n: usize) -> Vec<S> {
+    let src = &mut GlobalTripleSource::default();
+    let mut pows = vec![S::from_public(F::one()), r];
+    while pows.len() <= n {
+        // Multiply r, ..., r^k by r^k, to get r^(k+1), ..., r^(2k).
+        let k = pows.len() - 1;
+        let next = S::batch_mul(pows[1..].to_vec(), vec![pows[k]; k], src);
+        pows.extend(next);
+    }
+    pows.truncate(n + 1);
+    pows
+}
+
+/// Evaluate `p` at `x`, keeping both secret (unless `x` is public).
+///
+/// Takes a logarithmic number of rounds in the degree of `p`; see the module docs.
+pub fn eval_at_shared<F: PrimeField, S: FieldShare<F>>(
+    p: &DensePolynomial<MpcField<F, S>>,
+    x: MpcField<F, S>,
+) -> MpcField<F, S> {
+    let r = match x {
+        MpcField::Public(_) => return p.evaluate(&x),
+        MpcField::Shared(_) if p.coeffs.len() < 2 => return p.evaluate(&x),
+        MpcField::Shared(_) => S::rand(&mut rand::thread_rng()),
+    };
+    let timer = start_timer!(|| format!("Shared eval (degree {})", p.coeffs.len() - 1));
+    let pows = powers::<F, S>(r, p.coeffs.len() - 1);
+    let mut d = x.into_share();
+    d.sub(&r);
+    let d = d.open();
+
+    // Taylor shift: the coefficients of q(y) = p(y + d).
+    let mut q: Vec<S> = p.coeffs.iter().map(|c| c.into_share()).collect();
+    let n = q.len();
+    for i in 0..n {
+        for j in (i..n - 1).rev() {
+            let mut t = q[j + 1];
+            t.scale(&d);
+            q[j].add(&t);
+        }
+    }
+
+    let src = &mut GlobalTripleSource::default();
+    let mut out = q[0];
+    for t in S::batch_mul(q[1..].to_vec(), pows[1..].to_vec(), src) {
+        out.add(&t);
+    }
+    end_timer!(timer);
+    MpcField::Shared(out)
+}
@@ -3,10 +3,21 @@
 
 pub mod reveal;
 pub use reveal::*;
+pub mod bits;
+pub mod blinding;
+pub use blinding::{BlindingRng, BlindingSeed};
 pub mod channel;
 pub mod com;
+pub mod defer;
+pub mod eval;
+pub mod model;
+pub use model::MpcSecurityModel;
+pub mod preprocessing;
+pub mod prss;
 pub mod group;
 pub mod share;
+pub mod shuffle;
+pub mod taint;
 pub use share::*;
 pub mod wire;
 pub use wire::*;
@@ -15,7 +26,7 @@ pub mod honest_but_curious {
     use super::{
         share::add::*,
         share::msm::NaiveMsm,
-        wire::{field, group, pairing},
+        wire::{curve, field, group, pairing},
     };
     pub type MpcField<F> = field::MpcField<F, AdditiveFieldShare<F>>;
     pub type MpcGroup<G> = group::MpcGroup<G, AdditiveGroupShare<G, NaiveMsm<G>>>;
@@ -26,13 +37,15 @@ pub mod honest_but_curious {
     pub type MpcG1Prep<E> = pairing::MpcG1Prep<E, AdditivePairingShare<E>>;
     pub type MpcG2Prep<E> = pairing::MpcG2Prep<E, AdditivePairingShare<E>>;
     pub type MpcPairingEngine<E> = pairing::MpcPairingEngine<E, AdditivePairingShare<E>>;
+    pub type MpcCurveAffine<C> = curve::MpcCurveAffine<C, AdditiveCurveShare<C>>;
+    pub type MpcCurveProjective<C> = curve::MpcCurveProjective<C, AdditiveCurveShare<C>>;
 }
 
 pub mod malicious_majority {
     use super::{
         share::msm::NaiveMsm,
         share::spdz::*,
-        wire::{field, group, pairing},
+        wire::{curve, field, group, pairing},
     };
     pub type MpcField<F> = field::MpcField<F, SpdzFieldShare<F>>;
     pub type MpcGroup<G> = group::MpcGroup<G, SpdzGroupShare<G, NaiveMsm<G>>>;
@@ -43,6 +56,27 @@ pub mod malicious_majority {
     pub type MpcG1Prep<E> = pairing::MpcG1Prep<E, SpdzPairingShare<E>>;
     pub type MpcG2Prep<E> = pairing::MpcG2Prep<E, SpdzPairingShare<E>>;
     pub type MpcPairingEngine<E> = pairing::MpcPairingEngine<E, SpdzPairingShare<E>>;
+    pub type MpcCurveAffine<C> = curve::MpcCurveAffine<C, SpdzCurveShare<C>>;
+    pub type MpcCurveProjective<C> = curve::MpcCurveProjective<C, SpdzCurveShare<C>>;
+}
+
+pub mod threshold {
+    use super::{
+        share::msm::NaiveMsm,
+        share::shamir::*,
+        wire::{curve, field, group, pairing},
+    };
+    pub type MpcField<F> = field::MpcField<F, ShamirFieldShare<F>>;
+    pub type MpcGroup<G> = group::MpcGroup<G, ShamirGroupShare<G, NaiveMsm<G>>>;
+    pub type MpcG1Affine<E> = pairing::MpcG1Affine<E, ShamirPairingShare<E>>;
+    pub type MpcG2Affine<E> = pairing::MpcG2Affine<E, ShamirPairingShare<E>>;
+    pub type MpcG1Projective<E> = pairing::MpcG1Projective<E, ShamirPairingShare<E>>;
+    pub type MpcG2Projective<E> = pairing::MpcG2Projective<E, ShamirPairingShare<E>>;
+    pub type MpcG1Prep<E> = pairing::MpcG1Prep<E, ShamirPairingShare<E>>;
+    pub type MpcG2Prep<E> = pairing::MpcG2Prep<E, ShamirPairingShare<E>>;
+    pub type MpcPairingEngine<E> = pairing::MpcPairingEngine<E, ShamirPairingShare<E>>;
+    pub type MpcCurveAffine<C> = curve::MpcCurveAffine<C, ShamirCurveShare<C>>;
+    pub type MpcCurveProjective<C> = curve::MpcCurveProjective<C, ShamirCurveShare<C>>;
 }
 
 pub mod honest_majority {
@@ -0,0 +1,45 @@
+//! Security models: what the parties are protected against, as a type.
+//!
+//! A model picks the sharing that a computation runs over, so code written against
+//! [MpcSecurityModel] (a prover, say) compiles against either backend, chosen by type parameter:
+//! the cheap [SemiHonest] one, or the MAC-checked [Malicious] one. Each is behind the cargo
+//! feature of the same name (both on by default), so a build can leave one out.
+use ark_ec::PairingEngine;
+
+use crate::share::pairing::PairingShare;
+
+/// A security model, and the sharing that achieves it.
+pub trait MpcSecurityModel {
+    /// A short name, for reports.
+    const NAME: &'static str;
+    /// Whether a party that deviates from the protocol is caught (rather than only a party that
+    /// follows it kept from learning the others' inputs).
+    const MALICIOUS: bool;
+    /// How values over `E` are shared.
+    type Share<E: PairingEngine>: PairingShare<E>;
+}
+
+/// Parties that follow the protocol, but are curious: additive shares, without MACs.
+#[cfg(feature = "semi-honest")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SemiHonest;
+
+#[cfg(feature = "semi-honest")]
+impl MpcSecurityModel for SemiHonest {
+    const NAME: &'static str = "semi-honest";
+    const MALICIOUS: bool = false;
+    type Share<E: PairingEngine> = crate::share::add::AdditivePairingShare<E>;
+}
+
+/// Parties that may deviate, all but one of them: SPDZ shares, whose MACs are checked before
+/// anything opened is used.
+#[cfg(feature = "malicious")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Malicious;
+
+#[cfg(feature = "malicious")]
+impl MpcSecurityModel for Malicious {
+    const NAME: &'static str = "malicious";
+    const MALICIOUS: bool = true;
+    type Share<E: PairingEngine> = crate::share::spdz::SpdzPairingShare<E>;
+}
@@ -0,0 +1,394 @@
+//! Offline preprocessing of Beaver triples.
+//!
+//! Multiplying shared field elements consumes correlated randomness: triples `(a, b, ab)` and
+//! inverse pairs `(r, 1/r)`. Bit decomposition (see [crate::bits]) also consumes edaBits: a
+//! random `r`, shared along with each of its bits. Rather than producing these during the
+//! (online) proving phase, a dealer can generate them ahead of time with [deal_triples] and
+//! [deal_eda_bits], writing one [TripleFile] per party. At the start of the online phase, each
+//! party loads its own file with [install_triple_file]; from then on, [crate::MpcField]
+//! arithmetic draws from it (see [GlobalTripleSource]).
+//!
+//! If no source is installed for a share type, we fall back to
+//! [crate::wire::field::DummyFieldTripleSource].
+//!
+//! With [crate::prss] keys installed, additive and Shamir shares need only triples: random masks
+//! are expanded from the keys, and inverse pairs made from masks and triples.
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use derivative::Derivative;
+use lazy_static::lazy_static;
+use log::debug;
+use rand::Rng;
+
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::marker::PhantomData;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::share::field::FieldShare;
+use crate::share::BeaverSource;
+use crate::wire::field::DummyFieldTripleSource;
+use mpc_net::MpcMultiNet;
+
+/// A runtime supply of multiplication material for shares of type `S`.
+pub trait TripleSource<S>: Send {
+    /// A sharing of a random `(a, b, ab)`.
+    fn triple(&mut self) -> (S, S, S);
+    /// A sharing of a random `(r, 1/r)`.
+    fn inv_pair(&mut self) -> (S, S);
+    /// An edaBit: a sharing of a random `r`, and of each of its bits, least significant first.
+    fn eda_bit(&mut self) -> (S, Vec<S>) {
+        panic!("This triple source has no edaBits")
+    }
+    /// How many triples and inverse pairs are still unused, if the supply is finite.
+    fn remaining(&self) -> Option<(usize, usize)> {
+        None
+    }
+}
+
+/// One party's preprocessed triples and inverse pairs.
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct TripleFile<S: CanonicalSerialize + CanonicalDeserialize> {
+    pub triples: Vec<(S, S, S)>,
+    pub inv_pairs: Vec<(S, S)>,
+    /// See [deal_eda_bits]
+    pub eda_bits: Vec<(S, Vec<S>)>,
+}
+
+impl<S: CanonicalSerialize + CanonicalDeserialize> TripleFile<S> {
+    pub fn write_to(&self, path: &Path) {
+        let f =
+            File::create(path).unwrap_or_else(|e| panic!("triple file {}: {}", path.display(), e));
+        self.serialize(BufWriter::new(f))
+            .unwrap_or_else(|e| panic!("writing triple file {}: {}", path.display(), e));
+    }
+
+    pub fn read_from(path: &Path) -> Self {
+        let f =
+            File::open(path).unwrap_or_else(|e| panic!("triple file {}: {}", path.display(), e));
+        Self::deserialize(BufReader::new(f))
+            .unwrap_or_else(|e| panic!("bad triple file {}: {}", path.display(), e))
+    }
+}
+
+/// Additively split `x` into `n` random shares.
+fn split<F: Field, R: Rng>(x: F, n: usize, rng: &mut R) -> Vec<F> {
+    let mut shares: Vec<F> = (0..n - 1).map(|_| F::rand(rng)).collect();
+    let sum: F = shares.iter().sum();
+    shares.push(x - sum);
+    shares
+}
+
+/// Act as a trusted dealer of a SPDZ MAC key for `F` (see [crate::share::spdz::mac_share]):
+/// sample a secret key, keep it to deal authenticated shares with, and return its additive
+/// shares, one per party.
+///
+/// Each party installs its own share with [crate::share::spdz::install_mac_key_share], and
+/// keeps it private.
+pub fn deal_mac_key<F: Field, R: Rng>(n_parties: usize, rng: &mut R) -> Vec<F> {
+    assert!(n_parties > 0, "no parties to deal to");
+    let key = F::rand(rng);
+    crate::share::spdz::install_dealer_mac_key(key);
+    split(key, n_parties, rng)
+}
+
+/// Act as a trusted dealer: generate `n_triples` triples and `n_inv_pairs` inverse pairs, shared
+/// between `n_parties` parties.
+///
+/// Returns one file per party. The dealer does not need the network, but it does need the MAC
+/// key (for authenticated shares), so it must run while cheating is allowed.
+pub fn deal_triples<F: Field, S: FieldShare<F>, R: Rng>(
+    n_parties: usize,
+    n_triples: usize,
+    n_inv_pairs: usize,
+    rng: &mut R,
+) -> Vec<TripleFile<S>> {
+    assert!(n_parties > 0, "no parties to deal to");
+    let mut files: Vec<TripleFile<S>> = (0..n_parties)
+        .map(|_| TripleFile {
+            triples: Vec::with_capacity(n_triples),
+            inv_pairs: Vec::with_capacity(n_inv_pairs),
+            eda_bits: Vec::new(),
+        })
+        .collect();
+    for _ in 0..n_triples {
+        let a = F::rand(rng);
+        let b = F::rand(rng);
+        let a_s = split(a, n_parties, rng);
+        let b_s = split(b, n_parties, rng);
+        let c_s = split(a * b, n_parties, rng);
+        for (i, f) in files.iter_mut().enumerate() {
+            f.triples.push((
+                S::from_add_shared(a_s[i]),
+                S::from_add_shared(b_s[i]),
+                S::from_add_shared(c_s[i]),
+            ));
+        }
+    }
+    for _ in 0..n_inv_pairs {
+        let r = loop {
+            let r = F::rand(rng);
+            if !r.is_zero() {
+                break r;
+            }
+        };
+        let r_s = split(r, n_parties, rng);
+        let r_inv_s = split(r.inverse().unwrap(), n_parties, rng);
+        for (i, f) in files.iter_mut().enumerate() {
+            f.inv_pairs
+                .push((S::from_add_shared(r_s[i]), S::from_add_shared(r_inv_s[i])));
+        }
+    }
+    files
+}
+
+/// Act as a trusted dealer of edaBits, as [deal_triples] is of triples: add `n_eda_bits` random
+/// values of `n_bits` bits, shared along with their bits, to each party's file.
+///
+/// Bit decomposition of values of up to `l` bits takes edaBits of `l + STAT_SEC` bits (see
+/// [crate::bits::STAT_SEC]); longer ones serve too.
+pub fn deal_eda_bits<F: PrimeField, S: FieldShare<F>, R: Rng>(
+    files: &mut [TripleFile<S>],
+    n_eda_bits: usize,
+    n_bits: usize,
+    rng: &mut R,
+) {
+    assert!(
+        n_bits < F::size_in_bits(),
+        "edaBits must be shorter than the field"
+    );
+    let n_parties = files.len();
+    for _ in 0..n_eda_bits {
+        let bits: Vec<bool> = (0..n_bits).map(|_| rng.gen()).collect();
+        let r = F::from_repr(F::BigInt::from_bits_le(&bits)).unwrap();
+        let r_s = split(r, n_parties, rng);
+        let bit_s: Vec<Vec<F>> = bits
+            .iter()
+            .map(|b| split(F::from(*b as u64), n_parties, rng))
+            .collect();
+        for (i, f) in files.iter_mut().enumerate() {
+            f.eda_bits.push((
+                S::from_add_shared(r_s[i]),
+                bit_s.iter().map(|b| S::from_add_shared(b[i])).collect(),
+            ));
+        }
+    }
+}
+
+/// Serves triples from a [TripleFile], each at most once.
+pub struct FileTripleSource<S: CanonicalSerialize + CanonicalDeserialize> {
+    file: TripleFile<S>,
+    next_triple: usize,
+    next_inv_pair: usize,
+    next_eda_bit: usize,
+}
+
+impl<S: CanonicalSerialize + CanonicalDeserialize> FileTripleSource<S> {
+    pub fn new(file: TripleFile<S>) -> Self {
+        Self {
+            file,
+            next_triple: 0,
+            next_inv_pair: 0,
+            next_eda_bit: 0,
+        }
+    }
+}
+
+impl<S: CanonicalSerialize + CanonicalDeserialize + Copy + Send> TripleSource<S>
+    for FileTripleSource<S>
+{
+    fn triple(&mut self) -> (S, S, S) {
+        let t = *self.file.triples.get(self.next_triple).unwrap_or_else(|| {
+            panic!(
+                "Out of preprocessed triples (all {} used)",
+                self.file.triples.len()
+            )
+        });
+        self.next_triple += 1;
+        t
+    }
+    fn inv_pair(&mut self) -> (S, S) {
+        let p = *self
+            .file
+            .inv_pairs
+            .get(self.next_inv_pair)
+            .unwrap_or_else(|| {
+                panic!(
+                    "Out of preprocessed inverse pairs (all {} used)",
+                    self.file.inv_pairs.len()
+                )
+            });
+        self.next_inv_pair += 1;
+        p
+    }
+    fn eda_bit(&mut self) -> (S, Vec<S>) {
+        let e = self
+            .file
+            .eda_bits
+            .get(self.next_eda_bit)
+            .unwrap_or_else(|| {
+                panic!(
+                    "Out of preprocessed edaBits (all {} used)",
+                    self.file.eda_bits.len()
+                )
+            });
+        self.next_eda_bit += 1;
+        e.clone()
+    }
+    fn remaining(&self) -> Option<(usize, usize)> {
+        Some((
+            self.file.triples.len() - self.next_triple,
+            self.file.inv_pairs.len() - self.next_inv_pair,
+        ))
+    }
+}
+
+type Sources = HashMap<TypeId, Box<dyn Any + Send>>;
+
+lazy_static! {
+    /// Installed triple sources, by share type.
+    static ref SOURCES: Mutex<Sources> = Mutex::new(HashMap::new());
+}
+
+thread_local! {
+    /// The installed triple sources of a party that is a thread (see [MpcMultiNet::simulate]).
+    static LOCAL: RefCell<Sources> = RefCell::new(HashMap::new());
+}
+
+/// Run `f` on the installed sources: this thread's, if it is a simulated party, else the
+/// process's.
+fn with_sources<T>(f: impl FnOnce(&mut Sources) -> T) -> T {
+    if MpcMultiNet::is_simulated() {
+        LOCAL.with(|local| f(&mut local.borrow_mut()))
+    } else {
+        f(&mut SOURCES.lock().unwrap())
+    }
+}
+
+/// Use `src` for all multiplications of `S` shares, returning the previously installed source.
+pub fn install_triple_source<S: 'static>(
+    src: Box<dyn TripleSource<S>>,
+) -> Option<Box<dyn TripleSource<S>>> {
+    debug!(
+        "Installing triple source for {}",
+        std::any::type_name::<S>()
+    );
+    with_sources(|sources| sources.insert(TypeId::of::<S>(), Box::new(src)))
+        .map(|old| *old.downcast::<Box<dyn TripleSource<S>>>().unwrap())
+}
+
+/// Stop using the installed source for `S` shares (if any), and return it.
+pub fn uninstall_triple_source<S: 'static>() -> Option<Box<dyn TripleSource<S>>> {
+    with_sources(|sources| sources.remove(&TypeId::of::<S>()))
+        .map(|old| *old.downcast::<Box<dyn TripleSource<S>>>().unwrap())
+}
+
+/// Load this party's [TripleFile] from `path`, and use it for all multiplications of `S` shares.
+pub fn install_triple_file<F: Field, S: FieldShare<F>>(path: &Path) {
+    let file = TripleFile::<S>::read_from(path);
+    debug!(
+        "Loaded {} triples and {} inverse pairs from {}",
+        file.triples.len(),
+        file.inv_pairs.len(),
+        path.display()
+    );
+    install_triple_source::<S>(Box::new(FileTripleSource::new(file)));
+}
+
+/// The [BeaverSource] used by [crate::MpcField]: the installed [TripleSource] for `S`, if any.
+#[derive(Derivative)]
+#[derivative(Default(bound = ""), Clone(bound = ""), Copy(bound = ""))]
+pub struct GlobalTripleSource<T, S> {
+    _scalar: PhantomData<T>,
+    _share: PhantomData<S>,
+}
+
+impl<T: Field, S: FieldShare<T>> BeaverSource<S, S, S> for GlobalTripleSource<T, S> {
+    fn triple(&mut self) -> (S, S, S) {
+        let (mut a, mut b, mut c) = self.triples(1);
+        (a.pop().unwrap(), b.pop().unwrap(), c.pop().unwrap())
+    }
+    fn triples(&mut self, n: usize) -> (Vec<S>, Vec<S>, Vec<S>) {
+        with_sources(|sources| match sources.get_mut(&TypeId::of::<S>()) {
+            Some(src) => {
+                let src = src.downcast_mut::<Box<dyn TripleSource<S>>>().unwrap();
+                let mut xs = Vec::with_capacity(n);
+                let mut ys = Vec::with_capacity(n);
+                let mut zs = Vec::with_capacity(n);
+                for _ in 0..n {
+                    let (x, y, z) = src.triple();
+                    xs.push(x);
+                    ys.push(y);
+                    zs.push(z);
+                }
+                (xs, ys, zs)
+            }
+            None => DummyFieldTripleSource::<T, S>::default().triples(n),
+        })
+    }
+    fn inv_pair(&mut self) -> (S, S) {
+        let (mut r, mut r_inv) = self.inv_pairs(1);
+        (r.pop().unwrap(), r_inv.pop().unwrap())
+    }
+    /// Made from [crate::prss] masks and triples, if `S` can be expanded from keys: for random
+    /// `r` and `s`, `rs` can be opened, and `1/r = s/(rs)`.
+    fn inv_pairs(&mut self, n: usize) -> (Vec<S>, Vec<S>) {
+        if let Some(mut rs) = S::prss_rand(2 * n) {
+            let ss = rs.split_off(n);
+            let mut ts = S::batch_open(S::batch_mul(rs.clone(), ss.clone(), self));
+            ark_ff::batch_inversion(&mut ts);
+            let r_invs = ss
+                .into_iter()
+                .zip(ts)
+                .map(|(mut s, t)| {
+                    s.scale(&t);
+                    s
+                })
+                .collect();
+            return (rs, r_invs);
+        }
+        with_sources(|sources| match sources.get_mut(&TypeId::of::<S>()) {
+            Some(src) => {
+                let src = src.downcast_mut::<Box<dyn TripleSource<S>>>().unwrap();
+                (0..n).map(|_| src.inv_pair()).unzip()
+            }
+            None => DummyFieldTripleSource::<T, S>::default().inv_pairs(n),
+        })
+    }
+    /// Expanded from the [crate::prss] keys, if `S` can be, rather than preprocessed.
+    fn masks(&mut self, n: usize) -> Vec<S> {
+        S::prss_rand(n).unwrap_or_else(|| self.inv_pairs(n).0)
+    }
+    fn eda_bit(&mut self, n_bits: usize) -> (S, Vec<S>) {
+        self.eda_bits(1, n_bits).pop().unwrap()
+    }
+    fn eda_bits(&mut self, n: usize, n_bits: usize) -> Vec<(S, Vec<S>)> {
+        with_sources(|sources| match sources.get_mut(&TypeId::of::<S>()) {
+            Some(src) => {
+                let src = src.downcast_mut::<Box<dyn TripleSource<S>>>().unwrap();
+                (0..n).map(|_| shorten(src.eda_bit(), n_bits)).collect()
+            }
+            None => DummyFieldTripleSource::<T, S>::default().eda_bits(n, n_bits),
+        })
+    }
+}
+
+/// The edaBit of the low `n_bits` bits of edaBit `e`.
+fn shorten<F: Field, S: FieldShare<F>>(e: (S, Vec<S>), n_bits: usize) -> (S, Vec<S>) {
+    let (r, mut bits) = e;
+    assert!(
+        bits.len() >= n_bits,
+        "Preprocessed edaBits have {} bits, but {} are needed",
+        bits.len(),
+        n_bits
+    );
+    if bits.len() == n_bits {
+        return (r, bits);
+    }
+    bits.truncate(n_bits);
+    (crate::bits::from_bits(&bits), bits)
+}
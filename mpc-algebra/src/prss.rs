@@ -0,0 +1,215 @@
+//! Pseudorandom secret sharing (PRSS): shares of random values, expanded locally from PRG keys.
+//!
+//! With threshold `t`, there is a key for each set `A` of `t` parties, held by every party outside
+//! `A` (Cramer, Damgård and Ishai). The `k`-th random value is the sum, over the sets `A`, of the
+//! `k`-th output `r_A` of `A`'s key. So each party can compute a share of it alone:
+//! * its additive share is the sum of the `r_A` of the sets whose keys it leads (it is the
+//!   first party outside `A`), and
+//! * its Shamir share, of degree `t`, is the sum of `r_A f_A(i)` over the keys it holds, where
+//!   `f_A` has degree `t`, is one at zero, and is zero at the points of `A`.
+//!
+//! Any `t` parties lack the key of the set they form, so together they know nothing of the value.
+//!
+//! The keys are agreed once, in one round (see [Prss::setup]). Once [install]ed, random shares
+//! cost no communication, and no preprocessing: additive and Shamir shares draw the masks of
+//! inversion and division from them (see
+//! [FieldShare::prss_rand](crate::share::field::FieldShare::prss_rand)), and Shamir shares
+//! their blinds, so a dealer only ships triples (see [crate::preprocessing]). SPDZ shares need
+//! MACs, which keys can't give, so they still consume preprocessed masks.
+//!
+//! There are `n` choose `t` keys, so this suits a handful of parties.
+use ark_ff::Field;
+use lazy_static::lazy_static;
+use log::debug;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+use std::cell::RefCell;
+use std::sync::Mutex;
+
+use mpc_net::{MpcMultiNet as Net, MpcNet};
+
+use crate::channel::MpcSerNet;
+
+/// One party's PRSS keys.
+///
+/// Every party must draw the same values in the same order, so that the holders of each key
+/// stay in step.
+pub struct Prss {
+    party: usize,
+    threshold: usize,
+    /// For each set of `threshold` parties that we are not in: the set, whether we lead its key,
+    /// and the key's stream.
+    keys: Vec<(Vec<usize>, bool, ChaCha20Rng)>,
+}
+
+/// Each set of `k` of the parties `0..n`, in order.
+fn subsets(n: usize, k: usize) -> Vec<Vec<usize>> {
+    if k == 0 {
+        return vec![vec![]];
+    }
+    if n < k {
+        return vec![];
+    }
+    let mut sets = subsets(n - 1, k);
+    for mut s in subsets(n - 1, k - 1) {
+        s.push(n - 1);
+        sets.push(s);
+    }
+    sets.sort();
+    sets
+}
+
+/// The first party outside `set`.
+fn leader(set: &[usize]) -> usize {
+    (0..).find(|i| !set.contains(i)).unwrap()
+}
+
+impl Prss {
+    /// Agree on keys with the other parties, secure against `threshold` of them: each key's
+    /// leader samples it, and sends it to the key's other holders.
+    pub fn setup(threshold: usize) -> Self {
+        let (n, party) = (Net::n_parties(), Net::party_id());
+        assert!(
+            threshold < n,
+            "PRSS threshold {} of {} parties",
+            threshold,
+            n
+        );
+        let sets = subsets(n, threshold);
+        // Every party gets a slot for each key we lead, but only the key's holders get the key.
+        // Exchanges are of equal lengths, so there are as many slots as any party leads keys.
+        let mut ours: Vec<Option<[u8; 32]>> = vec![None; sets.len()];
+        let mut outs: Vec<Vec<[u8; 32]>> = vec![Vec::new(); n];
+        for (set, key) in sets.iter().zip(&mut ours) {
+            if leader(set) == party {
+                let mut k = [0u8; 32];
+                rand::rngs::OsRng.fill_bytes(&mut k);
+                for (j, out) in outs.iter_mut().enumerate() {
+                    out.push(if set.contains(&j) { [0u8; 32] } else { k });
+                }
+                *key = Some(k);
+            }
+        }
+        let slots = (0..n)
+            .map(|l| sets.iter().filter(|s| leader(s) == l).count())
+            .max()
+            .unwrap();
+        let outs: Vec<Vec<u8>> = outs
+            .into_iter()
+            .map(|mut o| {
+                o.resize(slots, [0u8; 32]);
+                o.concat()
+            })
+            .collect();
+        let ins = Net::send_to_each(&outs);
+        // The next slot from each leader
+        let mut next = vec![0; n];
+        let mut keys = Vec::new();
+        for (set, key) in sets.into_iter().zip(ours) {
+            let l = leader(&set);
+            let slot = next[l];
+            next[l] += 1;
+            if set.contains(&party) {
+                continue;
+            }
+            let key = key.unwrap_or_else(|| {
+                let mut k = [0u8; 32];
+                k.copy_from_slice(&ins[l][32 * slot..32 * (slot + 1)]);
+                k
+            });
+            keys.push((set, l == party, ChaCha20Rng::from_seed(key)));
+        }
+        debug!(
+            "PRSS: holding {} keys, for threshold {}",
+            keys.len(),
+            threshold
+        );
+        Self {
+            party,
+            threshold,
+            keys,
+        }
+    }
+
+    /// The threshold that the keys were agreed for.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Our additive shares of the next `n` random values.
+    pub fn additive<F: Field>(&mut self, n: usize) -> Vec<F> {
+        (0..n)
+            .map(|_| {
+                self.keys
+                    .iter_mut()
+                    .map(|(_, lead, rng)| (*lead, F::rand(rng)))
+                    .filter(|(lead, _)| *lead)
+                    .map(|(_, r)| r)
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// Our Shamir shares, of degree [Prss::threshold], of the next `n` random values, at
+    /// `point(party)`.
+    pub fn shamir<F: Field>(&mut self, n: usize, point: impl Fn(usize) -> F) -> Vec<F> {
+        let x = point(self.party);
+        // f_A(x): one at zero, and zero at the points of A.
+        let fs: Vec<F> = self
+            .keys
+            .iter()
+            .map(|(set, _, _)| {
+                set.iter().fold(F::one(), |acc, &j| {
+                    let p = point(j);
+                    acc * (p - x) * p.inverse().unwrap()
+                })
+            })
+            .collect();
+        (0..n)
+            .map(|_| {
+                self.keys
+                    .iter_mut()
+                    .zip(&fs)
+                    .map(|((_, _, rng), f)| F::rand(rng) * f)
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+lazy_static! {
+    /// The installed keys, if any.
+    static ref PRSS: Mutex<Option<Prss>> = Mutex::new(None);
+}
+
+thread_local! {
+    /// The installed keys of a party that is a thread (see [Net::simulate]), if any.
+    static LOCAL: RefCell<Option<Prss>> = RefCell::new(None);
+}
+
+/// Run `f` on the installed keys' slot: this thread's, if it is a simulated party, else the
+/// process's.
+fn with_slot<T>(f: impl FnOnce(&mut Option<Prss>) -> T) -> T {
+    if Net::is_simulated() {
+        LOCAL.with(|local| f(&mut local.borrow_mut()))
+    } else {
+        f(&mut PRSS.lock().unwrap())
+    }
+}
+
+/// Draw random shares from `prss` (see the [module docs](self)), returning the keys that were
+/// installed before.
+pub fn install(prss: Prss) -> Option<Prss> {
+    with_slot(|slot| slot.replace(prss))
+}
+
+/// Stop drawing random shares from the installed keys (if any), and return them.
+pub fn uninstall() -> Option<Prss> {
+    with_slot(Option::take)
+}
+
+/// Run `f` on the installed keys, if there are any.
+pub fn with_installed<T>(f: impl FnOnce(&mut Prss) -> T) -> Option<T> {
+    with_slot(|slot| slot.as_mut().map(f))
+}
@@ -42,30 +42,37 @@ pub trait Reveal: Sized {
     fn deinit_protocol() {}
 }
 
-impl Reveal for usize {
-    type Base = usize;
+macro_rules! plain_reveal_impl {
+    ($t:ty) => {
+        impl Reveal for $t {
+            type Base = $t;
 
-    fn reveal(self) -> Self::Base {
-        self
-    }
+            fn reveal(self) -> Self::Base {
+                self
+            }
 
-    fn from_add_shared(b: Self::Base) -> Self {
-        b
-    }
+            fn from_add_shared(b: Self::Base) -> Self {
+                b
+            }
 
-    fn from_public(b: Self::Base) -> Self {
-        b
-    }
+            fn from_public(b: Self::Base) -> Self {
+                b
+            }
 
-    fn unwrap_as_public(self) -> Self::Base {
-        self
-    }
+            fn unwrap_as_public(self) -> Self::Base {
+                self
+            }
 
-    fn king_share<R: Rng>(b: Self::Base, _rng: &mut R) -> Self {
-        b
-    }
+            fn king_share<R: Rng>(b: Self::Base, _rng: &mut R) -> Self {
+                b
+            }
+        }
+    };
 }
 
+plain_reveal_impl!(usize);
+plain_reveal_impl!(u64);
+
 impl<T: Reveal> Reveal for PhantomData<T> {
     type Base = PhantomData<T::Base>;
 
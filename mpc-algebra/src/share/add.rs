@@ -3,7 +3,7 @@ use derivative::Derivative;
 use rand::Rng;
 
 use ark_ec::group::Group;
-use ark_ec::{PairingEngine, ProjectiveCurve};
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
 use ark_ff::bytes::{FromBytes, ToBytes};
 use ark_ff::prelude::*;
 use ark_poly::UVPolynomial;
@@ -26,7 +26,7 @@ use super::field::{
     DenseOrSparsePolynomial, DensePolynomial, ExtFieldShare, FieldShare, SparsePolynomial,
 };
 use super::group::GroupShare;
-use super::pairing::{AffProjShare, PairingShare};
+use super::pairing::{AffProjShare, AffineGroup, CurveShare, PairingShare};
 use super::BeaverSource;
 use crate::msm::*;
 use crate::Reveal;
@@ -123,6 +123,11 @@ impl<F: Field> FieldShare<F> for AdditiveFieldShare<F> {
         let all_vals = Net::broadcast(&self_vec);
         (0..self_vec.len()).map(|i| all_vals.iter().map(|v| &v[i]).sum()).collect()
     }
+    fn prss_rand(n: usize) -> Option<Vec<Self>> {
+        crate::prss::with_installed(|p| {
+            p.additive(n).into_iter().map(Self::from_add_shared).collect()
+        })
+    }
     fn add(&mut self, other: &Self) -> &mut Self {
         self.val += &other.val;
         self
@@ -154,6 +159,12 @@ impl<F: Field> FieldShare<F> for AdditiveFieldShare<F> {
         num.divide_with_q_and_r(&den)
             .map(|(q, r)| (Self::d_poly_unshare(q), Self::d_poly_unshare(r)))
     }
+
+    fn batch_linear_map<Fun: Fn(&mut Vec<F>)>(shares: Vec<Self>, f: Fun) -> Option<Vec<Self>> {
+        let mut vals: Vec<F> = shares.into_iter().map(|s| s.val).collect();
+        f(&mut vals);
+        Some(vals.into_iter().map(Self::from_add_shared).collect())
+    }
 }
 
 #[derive(Derivative)]
@@ -257,6 +268,22 @@ impl<G: Group, M: Msm<G, G::ScalarField>> GroupShare<G> for AdditiveGroupShare<G
         let scalars: Vec<G::ScalarField> = scalars.into_iter().map(|s| s.val.clone()).collect();
         Self::from_add_shared(M::msm(bases, &scalars))
     }
+
+    fn batch_multi_scale_pub_group(msms: &[(&[G], &[Self::FieldShare])]) -> Vec<Self> {
+        let scalars: Vec<Vec<G::ScalarField>> = msms
+            .iter()
+            .map(|(_, s)| s.iter().map(|s| s.val).collect())
+            .collect();
+        let msms: Vec<(&[G], &[G::ScalarField])> = msms
+            .iter()
+            .zip(&scalars)
+            .map(|((bases, _), scalars)| (*bases, &scalars[..]))
+            .collect();
+        M::batch_msm(&msms)
+            .into_iter()
+            .map(Self::from_add_shared)
+            .collect()
+    }
 }
 
 macro_rules! impl_basics {
@@ -282,11 +309,11 @@ macro_rules! impl_basics {
             }
         }
         impl<T: $bound> CanonicalSerialize for $share<T> {
-            fn serialize<W: Write>(&self, _writer: W) -> Result<(), SerializationError> {
-                unimplemented!("serialize")
+            fn serialize<W: Write>(&self, writer: W) -> Result<(), SerializationError> {
+                self.val.serialize(writer)
             }
             fn serialized_size(&self) -> usize {
-                unimplemented!("serialized_size")
+                self.val.serialized_size()
             }
         }
         impl<T: $bound> CanonicalSerializeWithFlags for $share<T> {
@@ -303,8 +330,10 @@ macro_rules! impl_basics {
             }
         }
         impl<T: $bound> CanonicalDeserialize for $share<T> {
-            fn deserialize<R: Read>(_reader: R) -> Result<Self, SerializationError> {
-                unimplemented!("deserialize")
+            fn deserialize<R: Read>(reader: R) -> Result<Self, SerializationError> {
+                Ok(Self {
+                    val: T::deserialize(reader)?,
+                })
             }
         }
         impl<T: $bound> CanonicalDeserializeWithFlags for $share<T> {
@@ -477,6 +506,14 @@ impl<F: Field> FieldShare<F> for MulFieldShare<F> {
     fn batch_inv<S: BeaverSource<Self, Self, Self>>(xs: Vec<Self>, source: &mut S) -> Vec<Self> {
         xs.into_iter().map(|x| x.inv(source)).collect()
     }
+
+    fn batch_div<S: BeaverSource<Self, Self, Self>>(
+        xs: Vec<Self>,
+        ys: Vec<Self>,
+        source: &mut S,
+    ) -> Vec<Self> {
+        xs.into_iter().zip(ys).map(|(x, y)| x.div(y, source)).collect()
+    }
 }
 
 #[derive(Debug, Derivative)]
@@ -541,6 +578,54 @@ macro_rules! groups_share {
 groups_share!(AdditiveG1Share, G1Affine, G1Projective);
 groups_share!(AdditiveG2Share, G2Affine, G2Projective);
 
+/// Additive shares of a curve outside of any pairing engine.
+#[derive(Debug, Derivative)]
+#[derivative(
+    Default(bound = ""),
+    Clone(bound = ""),
+    Copy(bound = ""),
+    PartialEq(bound = ""),
+    Eq(bound = "")
+)]
+pub struct AdditiveCurveShare<C>(pub PhantomData<C>);
+
+impl<C: AffineGroup> AffProjShare<<C as AffineCurve>::ScalarField, C, C::Projective>
+    for AdditiveCurveShare<C>
+{
+    type FrShare = AdditiveFieldShare<<C as AffineCurve>::ScalarField>;
+    type AffineShare = AdditiveGroupShare<C, AffineMsm<C>>;
+    type ProjectiveShare = AdditiveGroupShare<C::Projective, ProjectiveMsm<C::Projective>>;
+
+    fn sh_aff_to_proj(g: Self::AffineShare) -> Self::ProjectiveShare {
+        g.map_homo(|s| s.into())
+    }
+
+    fn sh_proj_to_aff(g: Self::ProjectiveShare) -> Self::AffineShare {
+        g.map_homo(|s| s.into())
+    }
+
+    fn add_sh_proj_sh_aff(
+        mut a: Self::ProjectiveShare,
+        o: &Self::AffineShare,
+    ) -> Self::ProjectiveShare {
+        a.val.add_assign_mixed(&o.val);
+        a
+    }
+    fn add_sh_proj_pub_aff(mut a: Self::ProjectiveShare, o: &C) -> Self::ProjectiveShare {
+        if Net::am_king() {
+            a.val.add_assign_mixed(&o);
+        }
+        a
+    }
+    fn add_pub_proj_sh_aff(a: &C::Projective, o: Self::AffineShare) -> Self::ProjectiveShare {
+        let mut o = Self::sh_aff_to_proj(o);
+        o.shift(a);
+        o
+    }
+}
+
+impl<C: AffineGroup> CurveShare<C> for AdditiveCurveShare<C> {}
+
 #[derive(Debug, Derivative)]
 #[derivative(
     Default(bound = ""),
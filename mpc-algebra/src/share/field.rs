@@ -45,6 +45,18 @@ pub trait FieldShare<F: Field>:
         selfs.into_iter().map(|s| s.open()).collect()
     }
 
+    /// Reveal many shares at once: like [FieldShare::batch_open], but also making any checks
+    /// that [Reveal::reveal] makes.
+    fn batch_reveal(selfs: Vec<Self>) -> Vec<F> {
+        Self::batch_open(selfs)
+    }
+
+    /// Shares of `n` random values, expanded locally from the installed [crate::prss] keys, if
+    /// there are any and this sharing can be expanded from them.
+    fn prss_rand(_n: usize) -> Option<Vec<Self>> {
+        None
+    }
+
     fn add(&mut self, other: &Self) -> &mut Self;
 
     fn sub(&mut self, other: &Self) -> &mut Self {
@@ -127,19 +139,18 @@ pub trait FieldShare<F: Field>:
     }
 
     fn inv<S: BeaverSource<Self, Self, Self>>(self, source: &mut S) -> Self {
-        let (x, mut y) = source.inv_pair();
+        // (x * self)^-1 * x = self^-1
+        let (mut x, _) = source.inv_pair();
         let xa = x.mul(self, source).open().inverse().unwrap();
-        *y.scale(&xa)
+        *x.scale(&xa)
     }
 
     fn batch_inv<S: BeaverSource<Self, Self, Self>>(xs: Vec<Self>, source: &mut S) -> Vec<Self> {
-        let (bs, cs) = source.inv_pairs(xs.len());
-        cs.into_iter()
-            .zip(
-                Self::batch_open(Self::batch_mul(xs, bs, source))
-                    .into_iter()
-                    .map(|i| i.inverse().unwrap()),
-            )
+        let bs = source.masks(xs.len());
+        let mut xbs = Self::batch_open(Self::batch_mul(xs, bs.clone(), source));
+        ark_ff::batch_inversion(&mut xbs);
+        bs.into_iter()
+            .zip(xbs)
             .map(|(mut c, i)| {
                 c.scale(&i);
                 c
@@ -152,12 +163,30 @@ pub trait FieldShare<F: Field>:
         self.mul(o_inv, source)
     }
 
+    /// Divide pointwise, in one multiplication and one opening however many values there are.
     fn batch_div<S: BeaverSource<Self, Self, Self>>(
         xs: Vec<Self>,
         ys: Vec<Self>,
         source: &mut S,
     ) -> Vec<Self> {
-        Self::batch_mul(xs, Self::batch_inv(ys, source), source)
+        // (x * r) / (y * r) = x / y, opening only y * r
+        assert_eq!(xs.len(), ys.len());
+        let n = xs.len();
+        let rs = source.masks(n);
+        let mut xrs = Self::batch_mul(
+            xs.into_iter().chain(ys).collect(),
+            rs.iter().chain(&rs).cloned().collect(),
+            source,
+        );
+        let mut yrs = Self::batch_open(xrs.split_off(n));
+        ark_ff::batch_inversion(&mut yrs);
+        xrs.into_iter()
+            .zip(yrs)
+            .map(|(mut xr, i)| {
+                xr.scale(&i);
+                xr
+            })
+            .collect()
     }
 
     fn partial_products<S: BeaverSource<Self, Self, Self>>(x: Vec<Self>, src: &mut S) -> Vec<Self> {
@@ -191,6 +220,14 @@ pub trait FieldShare<F: Field>:
         todo!("Implement generic poly div")
     }
 
+    /// Apply `f`, a linear map on vectors of values (e.g., an FFT), to a vector of shares,
+    /// locally.
+    ///
+    /// Returns `None` if these shares can't be mapped locally (e.g., multiplicative shares).
+    fn batch_linear_map<Fun: Fn(&mut Vec<F>)>(_shares: Vec<Self>, _f: Fun) -> Option<Vec<Self>> {
+        None
+    }
+
 }
 
 pub type DensePolynomial<T> = Vec<T>;
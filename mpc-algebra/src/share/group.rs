@@ -48,6 +48,12 @@ pub trait GroupShare<G: Group>:
         selfs.into_iter().map(|s| s.open()).collect()
     }
 
+    /// Reveal many shares at once: like [GroupShare::batch_open], but also making any checks
+    /// that [Reveal::reveal] makes.
+    fn batch_reveal(selfs: Vec<Self>) -> Vec<G> {
+        Self::batch_open(selfs)
+    }
+
     fn add(&mut self, other: &Self) -> &mut Self;
 
     fn sub(&mut self, other: &Self) -> &mut Self {
@@ -120,4 +126,11 @@ pub trait GroupShare<G: Group>:
                 acc
             })
     }
+
+    /// [GroupShare::multi_scale_pub_group] for each of `msms`: by default, one after another.
+    fn batch_multi_scale_pub_group(msms: &[(&[G], &[Self::FieldShare])]) -> Vec<Self> {
+        msms.iter()
+            .map(|(bases, scalars)| Self::multi_scale_pub_group(bases, scalars))
+            .collect()
+    }
 }
@@ -36,15 +36,13 @@ use ark_std::{end_timer, start_timer};
 use mpc_net::{MpcMultiNet as Net, MpcNet};
 
 use once_cell::sync::OnceCell;
-use std::any::{Any, TypeId};
+use std::any::Any;
 use std::borrow::Cow;
 use std::cmp::Ord;
-use std::collections::HashMap;
 use std::fmt::{self, Debug, Display, Formatter};
 use std::hash::Hash;
 use std::io::{self, Read, Write};
 use std::marker::PhantomData;
-use std::sync::Mutex;
 
 use derivative::Derivative;
 use lazy_static::lazy_static;
@@ -54,42 +52,15 @@ use rand::Rng;
 use super::field::{
     DenseOrSparsePolynomial, DensePolynomial, ExtFieldShare, FieldShare, SparsePolynomial,
 };
-use super::BeaverSource;
+use super::{add_type, add_types, take_types, BeaverSource};
 use crate::msm::Msm;
 use crate::share::pairing::{AffProjShare, PairingShare};
 use crate::Reveal;
 
 lazy_static! {
-    static ref TYPE_LISTS: Mutex<HashMap<TypeId, Vec<Box<dyn Any + Send>>>> =
-        Mutex::new(HashMap::new());
     static ref SHARE_DOMAIN: OnceCell<Box<dyn Any + Send + Sync>> = OnceCell::new();
 }
 
-fn take_types<T: Any + Send>() -> Vec<T> {
-    let mut lists = TYPE_LISTS.lock().unwrap();
-    let type_id = TypeId::of::<T>();
-    let list = lists.remove(&type_id).unwrap_or_else(|| Vec::new());
-    list.into_iter()
-        .map(|x| *x.downcast::<T>().unwrap())
-        .collect()
-}
-fn add_type<T: Any + Send>(t: T) {
-    let mut lists = TYPE_LISTS.lock().unwrap();
-    let type_id = TypeId::of::<T>();
-    use std::collections::hash_map::Entry;
-    match lists.entry(type_id) {
-        Entry::Occupied(o) => o.into_mut().push(Box::new(t)),
-        Entry::Vacant(v) => {
-            v.insert(vec![Box::new(t)]);
-        }
-    };
-}
-fn add_types<T: Any + Send>(ts: Vec<T>) {
-    for t in ts {
-        add_type(t);
-    }
-}
-
 /// Malicious degree
 pub fn t() -> usize {
     (Net::n_parties() - 1) / 2
@@ -340,6 +311,30 @@ pub mod field {
                 })
                 .collect()
         }
+        fn batch_div<S: BeaverSource<Self, Self, Self>>(
+            xs: Vec<Self>,
+            ys: Vec<Self>,
+            source: &mut S,
+        ) -> Vec<Self> {
+            // (x * r) / (y * r) = x / y, opening only y * r
+            assert_eq!(xs.len(), ys.len());
+            let n = xs.len();
+            let rs: Vec<_> = (0..n).map(|_| rand::<F>()).collect();
+            let mut xrs = Self::batch_mul(
+                xs.into_iter().chain(ys).collect(),
+                rs.iter().chain(&rs).cloned().collect(),
+                source,
+            );
+            let mut yrs = Self::batch_open(xrs.split_off(n));
+            ark_ff::batch_inversion(&mut yrs);
+            xrs.into_iter()
+                .zip(yrs)
+                .map(|(mut xr, i)| {
+                    xr.scale(&i);
+                    xr
+                })
+                .collect()
+        }
         fn partial_products<S: BeaverSource<Self, Self, Self>>(
             x: Vec<Self>,
             src: &mut S,
@@ -373,6 +368,16 @@ pub mod field {
             num.divide_with_q_and_r(&den)
                 .map(|(q, r)| (Self::d_poly_unshare(q, t()), Self::d_poly_unshare(r, t())))
         }
+
+        fn batch_linear_map<Fun: Fn(&mut Vec<F>)>(
+            shares: Vec<Self>,
+            f: Fun,
+        ) -> Option<Vec<Self>> {
+            let degree = shares.iter().map(|s| s.degree).max().unwrap_or_else(t);
+            let mut vals: Vec<F> = shares.into_iter().map(|s| s.val).collect();
+            f(&mut vals);
+            Some(vals.into_iter().map(|val| Self { val, degree }).collect())
+        }
     }
 
     /// Yields a t-share of a random r.
@@ -465,19 +470,18 @@ pub mod field {
     /// 1. Opens the share to King.
     /// 2. King performs the function.
     /// 3. King reshares the result.
-    pub fn king_compute<F: FftField, Func: FnOnce(F) -> F>(
+    pub fn king_compute<F: FftField, Func: Fn(F) -> F>(
         share: &GszFieldShare<F>,
         new_degree: usize,
         f: Func,
     ) -> GszFieldShare<F> {
-        let king_answer = Net::send_to_king(&share.val).map(|shares| {
+        let from_king = <Net as MpcSerNet>::king_compute(&share.val, |shares| {
             let n = shares.len();
             let value = open_degree_vec(shares, share.degree);
             let output = f(value);
             // TODO: randomize
             vec![output; n]
         });
-        let from_king = Net::recv_from_king(king_answer);
         GszFieldShare {
             degree: new_degree,
             val: from_king,
@@ -497,7 +501,7 @@ pub mod field {
         f: Func,
     ) -> Vec<GszFieldShare<F>> {
         let values: Vec<F> = shares.iter().map(|s| s.val).collect();
-        let king_answer = Net::send_to_king(&values).map(|all_shares| {
+        let from_king = <Net as MpcSerNet>::king_compute(&values, |all_shares| {
             let kc_timer = start_timer!(|| format!("King computation"));
             let n = all_shares.len();
             let mut outputs = vec![Vec::new(); n];
@@ -513,7 +517,6 @@ pub mod field {
             end_timer!(kc_timer);
             outputs
         });
-        let from_king = Net::recv_from_king(king_answer);
         from_king
             .into_iter()
             .map(|from_king| GszFieldShare {
@@ -998,6 +1001,38 @@ pub mod group {
                 _phants: Default::default(),
             }
         }
+
+        fn batch_multi_scale_pub_group(msms: &[(&[G], &[Self::FieldShare])]) -> Vec<Self> {
+            let degrees: Vec<usize> = msms
+                .iter()
+                .map(|(_, s)| {
+                    let degree = s.first().map_or(0, |s| s.degree);
+                    assert!(s.iter().all(|s| s.degree == degree));
+                    degree
+                })
+                .collect();
+            let scalars: Vec<Vec<G::ScalarField>> = msms
+                .iter()
+                .map(|(_, s)| s.iter().map(|s| s.val).collect())
+                .collect();
+            let msms: Vec<(&[G], &[G::ScalarField])> = msms
+                .iter()
+                .zip(&scalars)
+                .map(|((bases, _), scalars)| (*bases, &scalars[..]))
+                .collect();
+            let msm_t = start_timer!(|| format!("MSMs: {}", msms.len()));
+            let products = M::batch_msm(&msms);
+            end_timer!(msm_t);
+            products
+                .into_iter()
+                .zip(degrees)
+                .map(|(val, degree)| Self {
+                    val,
+                    degree,
+                    _phants: Default::default(),
+                })
+                .collect()
+        }
     }
 
     /// Yields a t-share of a random r.
@@ -1087,19 +1122,18 @@ pub mod group {
     /// 1. Opens the share to King.
     /// 2. King performs the function.
     /// 3. King reshares the result.
-    pub fn king_compute<G: Group, M, Func: FnOnce(G) -> G>(
+    pub fn king_compute<G: Group, M, Func: Fn(G) -> G>(
         share: &GszGroupShare<G, M>,
         new_degree: usize,
         f: Func,
     ) -> GszGroupShare<G, M> {
-        let king_answer = Net::send_to_king(&share.val).map(|shares| {
+        let from_king = <Net as MpcSerNet>::king_compute(&share.val, |shares| {
             let n = shares.len();
             let value = open_degree_vec(shares, share.degree);
             let output = f(value);
             // TODO: randomize
             vec![output; n]
         });
-        let from_king = Net::recv_from_king(king_answer);
         GszGroupShare {
             degree: new_degree,
             val: from_king,
@@ -1610,6 +1644,13 @@ pub mod mul_field {
         ) -> Vec<Self> {
             xs.into_iter().map(|x| x.inv(source)).collect()
         }
+        fn batch_div<SS: BeaverSource<Self, Self, Self>>(
+            xs: Vec<Self>,
+            ys: Vec<Self>,
+            source: &mut SS,
+        ) -> Vec<Self> {
+            xs.into_iter().zip(ys).map(|(x, y)| x.div(y, source)).collect()
+        }
     }
 
     /// Open a t-share.
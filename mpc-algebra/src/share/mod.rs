@@ -11,9 +11,62 @@ pub mod spdz;
 pub use spdz::*;
 pub mod gsz20;
 pub use gsz20::*;
+pub mod shamir;
+pub use shamir::*;
 
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::marker::PhantomData;
+use std::sync::Mutex;
 use derivative::Derivative;
+use lazy_static::lazy_static;
+use mpc_net::MpcMultiNet;
+
+type TypeLists = HashMap<TypeId, Vec<Box<dyn Any + Send>>>;
+
+lazy_static! {
+    /// Values awaiting a (batched) check, by type.
+    static ref TYPE_LISTS: Mutex<TypeLists> = Mutex::new(HashMap::new());
+}
+
+thread_local! {
+    /// The values awaiting a check of a party that is a thread (see [MpcMultiNet::simulate]).
+    static LOCAL_TYPE_LISTS: RefCell<TypeLists> = RefCell::new(HashMap::new());
+}
+
+/// Run `f` on the values awaiting a check: this thread's, if it is a simulated party, else the
+/// process's.
+fn with_type_lists<T>(f: impl FnOnce(&mut TypeLists) -> T) -> T {
+    if MpcMultiNet::is_simulated() {
+        LOCAL_TYPE_LISTS.with(|local| f(&mut local.borrow_mut()))
+    } else {
+        f(&mut TYPE_LISTS.lock().unwrap())
+    }
+}
+
+pub(crate) fn take_types<T: Any + Send>() -> Vec<T> {
+    let type_id = TypeId::of::<T>();
+    let list = with_type_lists(|lists| lists.remove(&type_id).unwrap_or_else(|| Vec::new()));
+    list.into_iter()
+        .map(|x| *x.downcast::<T>().unwrap())
+        .collect()
+}
+pub(crate) fn add_type<T: Any + Send>(t: T) {
+    let type_id = TypeId::of::<T>();
+    use std::collections::hash_map::Entry;
+    with_type_lists(|lists| match lists.entry(type_id) {
+        Entry::Occupied(o) => o.into_mut().push(Box::new(t)),
+        Entry::Vacant(v) => {
+            v.insert(vec![Box::new(t)]);
+        }
+    });
+}
+pub(crate) fn add_types<T: Any + Send>(ts: Vec<T>) {
+    for t in ts {
+        add_type(t);
+    }
+}
 
 pub trait BeaverSource<A, B, C>: Clone {
     fn triple(&mut self) -> (A, B, C);
@@ -40,6 +93,19 @@ pub trait BeaverSource<A, B, C>: Clone {
         }
         (xs, ys)
     }
+    /// Sharings of `n` random values, to mask others with: by default, the first halves of
+    /// inverse pairs.
+    fn masks(&mut self, n: usize) -> Vec<B> {
+        self.inv_pairs(n).0
+    }
+    /// A sharing of a random `r < 2^n_bits`, and of each of its bits, least significant first:
+    /// an edaBit (see [crate::bits]).
+    fn eda_bit(&mut self, _n_bits: usize) -> (B, Vec<B>) {
+        panic!("This source has no edaBits")
+    }
+    fn eda_bits(&mut self, n: usize, n_bits: usize) -> Vec<(B, Vec<B>)> {
+        (0..n).map(|_| self.eda_bit(n_bits)).collect()
+    }
 }
 
 #[derive(Derivative)]
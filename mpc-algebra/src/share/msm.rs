@@ -1,10 +1,22 @@
 use derivative::Derivative;
 use ark_ec::{AffineCurve, ProjectiveCurve, group::Group};
+use ark_ff::{PrimeField, Zero};
+use ark_std::{end_timer, start_timer};
+use lazy_static::lazy_static;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
 use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
 
 /// Multi-scalar multiplications
 pub trait Msm<G, S>: Send + Sync + 'static {
     fn msm(bases: &[G], scalars: &[S]) -> G;
+    /// Several MSMs, each of its own `(bases, scalars)`: by default, one after another.
+    fn batch_msm(msms: &[(&[G], &[S])]) -> Vec<G> {
+        msms.iter()
+            .map(|(bases, scalars)| Self::msm(bases, scalars))
+            .collect()
+    }
     fn pre_reveal_check() {}
 }
 
@@ -32,7 +44,27 @@ pub struct AffineMsm<G: AffineCurve>(pub PhantomData<G>);
 
 impl<G: AffineCurve> Msm<G, G::ScalarField> for AffineMsm<G> {
     fn msm(bases: &[G], scalars: &[G::ScalarField]) -> G {
-        G::multi_scalar_mul(bases, scalars).into()
+        match precomputed_msm(bases, scalars) {
+            Some(r) => r.into(),
+            None => G::multi_scalar_mul(bases, scalars).into(),
+        }
+    }
+    /// Those MSMs that no precomputed table covers, in one pass (see
+    /// [AffineCurve::batch_multi_scalar_mul]).
+    fn batch_msm(msms: &[(&[G], &[G::ScalarField])]) -> Vec<G> {
+        let precomputed: Vec<Option<G::Projective>> =
+            msms.iter().map(|(b, s)| precomputed_msm(b, s)).collect();
+        let rest: Vec<(&[G], &[G::ScalarField])> = msms
+            .iter()
+            .zip(&precomputed)
+            .filter(|(_, p)| p.is_none())
+            .map(|(m, _)| *m)
+            .collect();
+        let mut rest = G::batch_multi_scalar_mul(&rest).into_iter();
+        precomputed
+            .into_iter()
+            .map(|p| p.unwrap_or_else(|| rest.next().unwrap()).into())
+            .collect()
     }
 }
 
@@ -42,7 +74,141 @@ pub struct ProjectiveMsm<G: ProjectiveCurve>(pub PhantomData<G>);
 
 impl<G: ProjectiveCurve> Msm<G, G::ScalarField> for ProjectiveMsm<G> {
     fn msm(bases: &[G], scalars: &[G::ScalarField]) -> G {
-        let bases: Vec<G::Affine> = bases.iter().map(|s| s.clone().into()).collect();
-        <G::Affine as AffineCurve>::multi_scalar_mul(&bases, scalars)
+        let bases = G::batch_normalization_into_affine(bases);
+        match precomputed_msm(&bases, scalars) {
+            Some(r) => r,
+            None => <G::Affine as AffineCurve>::multi_scalar_mul(&bases, scalars),
+        }
+    }
+}
+
+lazy_static! {
+    /// Fixed-base tables (see [precompute]), by base type.
+    static ref TABLES: Mutex<HashMap<TypeId, Vec<Arc<dyn Any + Send + Sync>>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Precompute a [FixedBaseTable] for `bases`, which [AffineMsm] and [ProjectiveMsm] then use for
+/// any MSM over a contiguous run of them.
+///
+/// Committing to shares of polynomials is an MSM over the same public SRS, by every party, for
+/// every commitment: precompute the SRS once per proof session, and [clear_precomputed] after.
+pub fn precompute<G: AffineCurve>(bases: &[G]) {
+    let table: Arc<dyn Any + Send + Sync> = Arc::new(FixedBaseTable::new(bases));
+    TABLES
+        .lock()
+        .unwrap()
+        .entry(TypeId::of::<G>())
+        .or_default()
+        .push(table);
+}
+
+/// Drop all tables made by [precompute].
+pub fn clear_precomputed() {
+    TABLES.lock().unwrap().clear();
+}
+
+/// `sum_i scalars[i] * bases[i]` from a precomputed table, if some table has the bases.
+fn precomputed_msm<G: AffineCurve>(
+    bases: &[G],
+    scalars: &[G::ScalarField],
+) -> Option<G::Projective> {
+    let tables: Vec<Arc<dyn Any + Send + Sync>> =
+        TABLES.lock().unwrap().get(&TypeId::of::<G>())?.clone();
+    tables.into_iter().find_map(|t| {
+        let t = t.downcast::<FixedBaseTable<G>>().ok()?;
+        let start = t.find(bases)?;
+        Some(t.msm(start, scalars))
+    })
+}
+
+/// Multiples `2^(window * k) * b` of each of a fixed list of bases `b`, for every window `k` of
+/// a scalar.
+///
+/// An MSM with a table needs no doublings, only one mixed addition per nonzero window of each
+/// scalar (and a pass over the buckets). The table takes about `window` times as long as an MSM
+/// to build, and holds `bits / window` points per base.
+pub struct FixedBaseTable<G: AffineCurve> {
+    bases: Vec<G>,
+    /// The first index of each base
+    index: HashMap<G, usize>,
+    window: usize,
+    windows: usize,
+    /// `table[i * windows + k]` is `2^(window * k) * bases[i]`
+    table: Vec<G>,
+}
+
+impl<G: AffineCurve> FixedBaseTable<G> {
+    pub fn new(bases: &[G]) -> Self {
+        let timer = start_timer!(|| format!("Fixed-base table: {}", bases.len()));
+        // Balances the additions, about n * bits / window, against the 2^window buckets
+        let window = (usize::BITS - bases.len().leading_zeros()).clamp(4, 16) as usize;
+        let windows = (G::ScalarField::size_in_bits() + window - 1) / window;
+        let mut table = Vec::with_capacity(bases.len() * windows);
+        for b in bases {
+            let mut p = b.into_projective();
+            for _ in 0..windows {
+                table.push(p);
+                for _ in 0..window {
+                    ProjectiveCurve::double_in_place(&mut p);
+                }
+            }
+        }
+        let table = G::Projective::batch_normalization_into_affine(&table);
+        let mut index = HashMap::new();
+        for (i, b) in bases.iter().enumerate() {
+            index.entry(*b).or_insert(i);
+        }
+        end_timer!(timer);
+        Self {
+            bases: bases.to_vec(),
+            index,
+            window,
+            windows,
+            table,
+        }
+    }
+
+    /// Where `bases` starts in our bases, if it is a contiguous run of them.
+    pub fn find(&self, bases: &[G]) -> Option<usize> {
+        let start = *self.index.get(bases.first()?)?;
+        let ours = self.bases.get(start..start + bases.len())?;
+        (ours == bases).then(|| start)
+    }
+
+    /// `sum_i scalars[i] * bases[start + i]`, over the scalars and the bases after `start`.
+    pub fn msm(&self, start: usize, scalars: &[G::ScalarField]) -> G::Projective {
+        let timer = start_timer!(|| format!("Fixed-base MSM: {}", scalars.len()));
+        let n = scalars.len().min(self.bases.len().saturating_sub(start));
+        let mut buckets = vec![G::Projective::zero(); (1 << self.window) - 1];
+        for (i, s) in scalars[..n].iter().enumerate() {
+            let s = s.into_repr();
+            let row = &self.table[(start + i) * self.windows..][..self.windows];
+            for (k, p) in row.iter().enumerate() {
+                let d = digit(s.as_ref(), k * self.window, self.window);
+                if d != 0 {
+                    buckets[d - 1].add_assign_mixed(p);
+                }
+            }
+        }
+        // sum_d d * buckets[d - 1]
+        let mut running = G::Projective::zero();
+        let mut sum = G::Projective::zero();
+        for b in buckets.into_iter().rev() {
+            running += b;
+            sum += running;
+        }
+        end_timer!(timer);
+        sum
+    }
+}
+
+/// The `len` bits of `limbs` (little-endian) from bit `start`.
+fn digit(limbs: &[u64], start: usize, len: usize) -> usize {
+    let (limb, bit) = (start / 64, start % 64);
+    let mut d = limbs.get(limb).map_or(0, |l| l >> bit);
+    if bit + len > 64 && bit > 0 {
+        d |= limbs.get(limb + 1).map_or(0, |l| l << (64 - bit));
     }
+    (d & ((1 << len) - 1)) as usize
 }
@@ -61,3 +61,22 @@ pub trait PairingShare<E: PairingEngine>:
         ProjectiveShare = Self::G2ProjectiveShare,
     >;
 }
+
+/// An affine curve, viewed as a [Group] over its scalar field.
+pub trait AffineGroup: AffineCurve + Group<ScalarField = <Self as AffineCurve>::ScalarField> {}
+
+impl<C: AffineCurve + Group<ScalarField = <C as AffineCurve>::ScalarField>> AffineGroup for C {}
+
+/// Shares of a single curve, which need not be part of a pairing engine.
+pub trait CurveShare<C: AffineGroup>:
+    AffProjShare<<C as AffineCurve>::ScalarField, C, C::Projective>
+    + Clone
+    + Copy
+    + Debug
+    + 'static
+    + Send
+    + Sync
+    + PartialEq
+    + Eq
+{
+}
@@ -0,0 +1,1336 @@
+//! Threshold (Shamir) secret sharing, for an honest majority.
+//!
+//! A value is shared with a random polynomial of degree [threshold], `t`; party `i` holds its
+//! evaluation at `i + 1`. Any `t + 1` shares determine the value (by Lagrange interpolation at
+//! zero), and any `t` reveal nothing about it, so opening only needs a quorum of `t + 1` parties.
+//!
+//! Products use the degree reduction of Damgård and Nielsen (["Scalable and Unconditionally
+//! Secure Multiparty Computation"](https://ia.cr/2007/082)): parties mask their local products
+//! (shares of degree `2t`) with a random double sharing, the king opens the masked products and
+//! reshares them at degree `t`, and parties remove the mask.
+//!
+//! Security is against `t` honest-but-curious parties. With two parties, `t` would be zero, and
+//! shares not private at all, so [threshold] refuses fewer than three.
+use derivative::Derivative;
+use rand::Rng;
+
+use ark_ec::group::Group;
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::bytes::{FromBytes, ToBytes};
+use ark_ff::prelude::*;
+use ark_poly::UVPolynomial;
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalDeserializeWithFlags, CanonicalSerialize,
+    CanonicalSerializeWithFlags, Flags, SerializationError,
+};
+use ark_std::{end_timer, start_timer};
+
+use std::borrow::Cow;
+use std::cmp::Ord;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::hash::Hash;
+use std::io::{self, Read, Write};
+use std::iter::once;
+use std::marker::PhantomData;
+use std::ops::{Add, MulAssign, Sub};
+
+use crate::channel::MpcSerNet;
+use mpc_net::{MpcMultiNet as Net, MpcNet};
+
+use super::add::AdditiveFieldShare;
+use super::field::{
+    DenseOrSparsePolynomial, DensePolynomial, ExtFieldShare, FieldShare, SparsePolynomial,
+};
+use super::group::GroupShare;
+use super::pairing::{AffProjShare, AffineGroup, CurveShare, PairingShare};
+use super::{BeaverSource, PanicBeaverSource};
+use crate::msm::*;
+use crate::Reveal;
+
+/// The degree of sharings: the most parties that may collude.
+///
+/// Panics with fewer than three parties, whose shares would not be private at all.
+pub fn threshold() -> usize {
+    let n = Net::n_parties();
+    assert!(
+        n >= 3,
+        "Shamir sharing needs at least three parties, not {}",
+        n
+    );
+    (n - 1) / 2
+}
+
+/// The point at which `party`'s share is an evaluation.
+fn point<F: Field>(party: usize) -> F {
+    F::from((party + 1) as u64)
+}
+
+/// Something we can share: a field over itself, or a group over its scalar field.
+pub trait Shareable<F: Field>:
+    Copy
+    + Eq
+    + Debug
+    + Zero
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + MulAssign<F>
+    + UniformRand
+    + CanonicalSerialize
+    + CanonicalDeserialize
+{
+}
+
+impl<F: Field, T> Shareable<F> for T where
+    T: Copy
+        + Eq
+        + Debug
+        + Zero
+        + Add<Output = T>
+        + Sub<Output = T>
+        + MulAssign<F>
+        + UniformRand
+        + CanonicalSerialize
+        + CanonicalDeserialize
+{
+}
+
+/// The coefficients which interpolate the shares of `parties` at zero.
+pub fn lagrange_at_zero<F: Field>(parties: &[usize]) -> Vec<F> {
+    parties
+        .iter()
+        .map(|&i| {
+            let (num, den) = parties
+                .iter()
+                .filter(|&&j| j != i)
+                .fold((F::one(), F::one()), |(num, den), &j| {
+                    (num * point::<F>(j), den * (point::<F>(j) - point::<F>(i)))
+                });
+            num * den.inverse().unwrap()
+        })
+        .collect()
+}
+
+/// Recover a value from the shares of some parties, given as `(party, share)` pairs.
+///
+/// There must be more shares than the sharing degree, but they may come from any parties.
+pub fn reconstruct_from<F: Field, T: Shareable<F>>(shares: &[(usize, T)]) -> T {
+    let parties: Vec<usize> = shares.iter().map(|(i, _)| *i).collect();
+    lagrange_at_zero::<F>(&parties)
+        .into_iter()
+        .zip(shares)
+        .fold(T::zero(), |acc, (l, (_, s))| {
+            let mut s = *s;
+            s *= l;
+            acc + s
+        })
+}
+
+/// Open `shares`, of degree [threshold], over the parties that respond: the first `t + 1` of them
+/// determine the values, so up to `n - t - 1` parties may drop out.
+///
+/// In debug builds, the last `t + 1` responders' shares must agree.
+fn open_quorum<F: Field, T: Shareable<F>>(shares: Vec<T>) -> Vec<T> {
+    let t = threshold();
+    let responses: Vec<(usize, Vec<T>)> = Net::broadcast_quorum(&shares, t + 1)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, s)| s.map(|s| (i, s)))
+        .collect();
+    (0..shares.len())
+        .map(|k| {
+            let shares: Vec<(usize, T)> = responses.iter().map(|(i, s)| (*i, s[k])).collect();
+            let value = reconstruct_from::<F, T>(&shares[..t + 1]);
+            debug_assert_eq!(
+                value,
+                reconstruct_from::<F, T>(&shares[shares.len() - t - 1..]),
+                "Shares are not of degree {}",
+                t
+            );
+            value
+        })
+        .collect()
+}
+
+/// Recover a value of degree `degree` from all parties' shares.
+///
+/// Only the first `degree + 1` shares are used, but (in debug builds) the rest must agree.
+fn reconstruct<F: Field, T: Shareable<F>>(shares: Vec<T>, degree: usize) -> T {
+    let shares: Vec<(usize, T)> = shares.into_iter().enumerate().collect();
+    let value = reconstruct_from::<F, T>(&shares[..degree + 1]);
+    debug_assert_eq!(
+        value,
+        reconstruct_from::<F, T>(&shares[shares.len() - degree - 1..]),
+        "Shares are not of degree {}",
+        degree
+    );
+    value
+}
+
+/// Shares of `secret` at degree `degree`, one per party.
+fn deal<F: Field, T: Shareable<F>, R: Rng + ?Sized>(
+    secret: T,
+    degree: usize,
+    rng: &mut R,
+) -> Vec<T> {
+    let coeffs: Vec<T> = once(secret)
+        .chain((0..degree).map(|_| T::rand(rng)))
+        .collect();
+    (0..Net::n_parties())
+        .map(|i| {
+            let x = point::<F>(i);
+            coeffs.iter().rev().fold(T::zero(), |mut acc, c| {
+                acc *= x;
+                acc + *c
+            })
+        })
+        .collect()
+}
+
+/// Shares of `n` random values, at each of `degrees`.
+///
+/// Each party deals sharings of its own random values, and the parties combine them with a
+/// Vandermonde matrix. Since any `n_parties - t` of its columns are invertible, each `n_parties`
+/// dealt values yield `n_parties - t` random ones that no `t` parties know anything about.
+fn random_sharings<F: Field, T: Shareable<F>, R: Rng + ?Sized>(
+    n: usize,
+    degrees: &[usize],
+    rng: &mut R,
+) -> Vec<Vec<T>> {
+    let timer = start_timer!(|| format!("Random sharings: {}", n));
+    let parties = Net::n_parties();
+    let per_round = parties - threshold();
+    let rounds = n.div_ceil(per_round);
+    // To each party: its shares of each dealt value, at each degree.
+    let mut outs = vec![Vec::with_capacity(rounds * degrees.len()); parties];
+    for _ in 0..rounds {
+        let secret = T::rand(rng);
+        for &d in degrees {
+            for (out, s) in outs.iter_mut().zip(deal::<F, T, R>(secret, d, rng)) {
+                out.push(s);
+            }
+        }
+    }
+    let ins: Vec<Vec<T>> = Net::send_to_each(&outs);
+    let mut out = vec![Vec::with_capacity(rounds * per_round); degrees.len()];
+    for r in 0..rounds {
+        for (k, out) in out.iter_mut().enumerate() {
+            let dealt: Vec<T> = ins.iter().map(|i| i[r * degrees.len() + k]).collect();
+            for j in 0..per_round {
+                out.push(dealt.iter().enumerate().fold(T::zero(), |acc, (i, s)| {
+                    let mut s = *s;
+                    s *= point::<F>(i).pow([j as u64]);
+                    acc + s
+                }));
+            }
+        }
+    }
+    for o in &mut out {
+        o.truncate(n);
+    }
+    end_timer!(timer);
+    out
+}
+
+/// Degree-`t` and degree-`2t` shares of the same `n` random values.
+fn double_random<F: Field, T: Shareable<F>>(n: usize) -> (Vec<T>, Vec<T>) {
+    let t = threshold();
+    let mut rs = random_sharings::<F, T, _>(n, &[t, 2 * t], &mut rand::thread_rng());
+    let r2 = rs.pop().unwrap();
+    (rs.pop().unwrap(), r2)
+}
+
+/// Given degree-`2t` shares, get degree-`t` shares of the same values.
+///
+/// The king learns the values, so they must be masked.
+fn reduce_degree<F: Field, T: Shareable<F>>(shares: Vec<T>) -> Vec<T> {
+    let t = threshold();
+    let n = shares.len();
+    <Net as MpcSerNet>::king_compute(&shares, |all_shares| {
+        let rng = &mut rand::thread_rng();
+        let mut outs = vec![Vec::with_capacity(n); all_shares.len()];
+        for k in 0..n {
+            let value = reconstruct::<F, T>(all_shares.iter().map(|s| s[k]).collect(), 2 * t);
+            for (out, s) in outs.iter_mut().zip(deal::<F, T, _>(value, t, rng)) {
+                out.push(s);
+            }
+        }
+        outs
+    })
+}
+
+/// Multiply shares pointwise, given a sharing of `x * y` for each `x` and `y`.
+fn batch_mul_with<F: Field, X, T: Shareable<F>>(
+    xs: &[X],
+    ys: &[F],
+    mul: impl Fn(&X, &F) -> T,
+) -> Vec<T> {
+    assert_eq!(xs.len(), ys.len());
+    let (r, r2) = double_random::<F, T>(xs.len());
+    let masked: Vec<T> = xs
+        .iter()
+        .zip(ys)
+        .zip(r2)
+        .map(|((x, y), r2)| mul(x, y) + r2)
+        .collect();
+    reduce_degree::<F, T>(masked)
+        .into_iter()
+        .zip(r)
+        .map(|(m, r)| m - r)
+        .collect()
+}
+
+/// A Shamir share of a field element.
+#[derive(Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ShamirFieldShare<T> {
+    pub val: T,
+}
+
+impl<F: Field> ShamirFieldShare<F> {
+    fn poly_share<'a>(
+        p: DenseOrSparsePolynomial<Self>,
+    ) -> ark_poly::univariate::DenseOrSparsePolynomial<'a, F> {
+        match p {
+            Ok(p) => ark_poly::univariate::DenseOrSparsePolynomial::DPolynomial(Cow::Owned(
+                Self::d_poly_share(p),
+            )),
+            Err(p) => ark_poly::univariate::DenseOrSparsePolynomial::SPolynomial(Cow::Owned(
+                Self::s_poly_share(p),
+            )),
+        }
+    }
+    fn d_poly_share(p: DensePolynomial<Self>) -> ark_poly::univariate::DensePolynomial<F> {
+        ark_poly::univariate::DensePolynomial::from_coefficients_vec(
+            p.into_iter().map(|s| s.val).collect(),
+        )
+    }
+    fn s_poly_share(p: SparsePolynomial<Self>) -> ark_poly::univariate::SparsePolynomial<F> {
+        ark_poly::univariate::SparsePolynomial::from_coefficients_vec(
+            p.into_iter().map(|(i, s)| (i, s.val)).collect(),
+        )
+    }
+    fn poly_share2<'a>(
+        p: DenseOrSparsePolynomial<F>,
+    ) -> ark_poly::univariate::DenseOrSparsePolynomial<'a, F> {
+        match p {
+            Ok(p) => ark_poly::univariate::DenseOrSparsePolynomial::DPolynomial(Cow::Owned(
+                ark_poly::univariate::DensePolynomial::from_coefficients_vec(p),
+            )),
+            Err(p) => ark_poly::univariate::DenseOrSparsePolynomial::SPolynomial(Cow::Owned(
+                ark_poly::univariate::SparsePolynomial::from_coefficients_vec(p),
+            )),
+        }
+    }
+    fn d_poly_unshare(p: ark_poly::univariate::DensePolynomial<F>) -> DensePolynomial<Self> {
+        p.coeffs.into_iter().map(Self::from_add_shared).collect()
+    }
+
+    /// Shares of `n` random values: expanded from the [crate::prss] keys, if they are
+    /// installed, and otherwise dealt by every party, from `rng`.
+    pub fn batch_rand<R: Rng + ?Sized>(n: usize, rng: &mut R) -> Vec<Self> {
+        Self::prss_rand(n).unwrap_or_else(|| {
+            random_sharings::<F, F, R>(n, &[threshold()], rng)
+                .pop()
+                .unwrap()
+                .into_iter()
+                .map(Self::from_add_shared)
+                .collect()
+        })
+    }
+
+    /// An additive share of the same value: see [ShamirFieldShare::batch_to_additive].
+    pub fn to_additive(self) -> AdditiveFieldShare<F> {
+        Self::batch_to_additive(vec![self]).pop().unwrap()
+    }
+    /// Additive shares of the same values.
+    ///
+    /// Each party scales its evaluation by its Lagrange coefficient at zero, so this needs no
+    /// communication, but all parties must take part.
+    pub fn batch_to_additive(selfs: Vec<Self>) -> Vec<AdditiveFieldShare<F>> {
+        let parties: Vec<usize> = (0..Net::n_parties()).collect();
+        let l = lagrange_at_zero::<F>(&parties)[Net::party_id()];
+        selfs
+            .into_iter()
+            .map(|s| AdditiveFieldShare::from_add_shared(s.val * l))
+            .collect()
+    }
+}
+
+impl<F: Field> AdditiveFieldShare<F> {
+    /// A Shamir share of the same value: see [AdditiveFieldShare::batch_to_shamir].
+    pub fn to_shamir(self) -> ShamirFieldShare<F> {
+        Self::batch_to_shamir(vec![self]).pop().unwrap()
+    }
+    /// Shamir shares, of degree [threshold], of the same values.
+    ///
+    /// Each party deals a sharing of its additive share, and sums the sharings it gets, in one
+    /// round.
+    pub fn batch_to_shamir(selfs: Vec<Self>) -> Vec<ShamirFieldShare<F>> {
+        let timer = start_timer!(|| format!("Additive to Shamir: {}", selfs.len()));
+        let n = selfs.len();
+        let rng = &mut rand::thread_rng();
+        let mut outs = vec![Vec::with_capacity(n); Net::n_parties()];
+        for s in selfs {
+            let dealt = deal::<F, F, _>(s.val, threshold(), rng);
+            for (out, s) in outs.iter_mut().zip(dealt) {
+                out.push(s);
+            }
+        }
+        let ins: Vec<Vec<F>> = Net::send_to_each(&outs);
+        let out = (0..n)
+            .map(|k| ShamirFieldShare::from_add_shared(ins.iter().map(|i| i[k]).sum()))
+            .collect();
+        end_timer!(timer);
+        out
+    }
+}
+
+impl<F: Field> Reveal for ShamirFieldShare<F> {
+    type Base = F;
+
+    fn reveal(self) -> F {
+        open_quorum::<F, F>(vec![self.val])[0]
+    }
+    fn from_public(f: F) -> Self {
+        Self { val: f }
+    }
+    /// Wrap our share: our evaluation of the sharing polynomial.
+    fn from_add_shared(f: F) -> Self {
+        Self { val: f }
+    }
+    fn unwrap_as_public(self) -> F {
+        self.val
+    }
+    fn king_share<R: Rng>(f: Self::Base, rng: &mut R) -> Self {
+        let shares = if Net::am_king() {
+            Some(deal::<F, F, R>(f, threshold(), rng))
+        } else {
+            None
+        };
+        Self::from_add_shared(Net::recv_from_king(shares))
+    }
+    fn king_share_batch<R: Rng>(f: Vec<Self::Base>, rng: &mut R) -> Vec<Self> {
+        let shares = if Net::am_king() {
+            let mut outs = vec![Vec::with_capacity(f.len()); Net::n_parties()];
+            for f in f {
+                for (out, s) in outs.iter_mut().zip(deal::<F, F, R>(f, threshold(), rng)) {
+                    out.push(s);
+                }
+            }
+            Some(outs)
+        } else {
+            None
+        };
+        Net::recv_from_king(shares)
+            .into_iter()
+            .map(Self::from_add_shared)
+            .collect()
+    }
+}
+
+impl<F: Field> FieldShare<F> for ShamirFieldShare<F> {
+    fn batch_open(selfs: impl IntoIterator<Item = Self>) -> Vec<F> {
+        open_quorum::<F, F>(selfs.into_iter().map(|s| s.val).collect())
+    }
+
+    fn prss_rand(n: usize) -> Option<Vec<Self>> {
+        crate::prss::with_installed(|p| {
+            assert_eq!(
+                p.threshold(),
+                threshold(),
+                "PRSS keys for another threshold"
+            );
+            p.shamir(n, point::<F>)
+                .into_iter()
+                .map(Self::from_add_shared)
+                .collect()
+        })
+    }
+
+    fn add(&mut self, other: &Self) -> &mut Self {
+        self.val += &other.val;
+        self
+    }
+
+    fn sub(&mut self, other: &Self) -> &mut Self {
+        self.val -= &other.val;
+        self
+    }
+
+    fn scale(&mut self, other: &F) -> &mut Self {
+        self.val *= other;
+        self
+    }
+
+    fn shift(&mut self, other: &F) -> &mut Self {
+        self.val += other;
+        self
+    }
+
+    fn mul<S: BeaverSource<Self, Self, Self>>(self, other: Self, source: &mut S) -> Self {
+        Self::batch_mul(vec![self], vec![other], source)[0]
+    }
+
+    fn batch_mul<S: BeaverSource<Self, Self, Self>>(
+        xs: Vec<Self>,
+        ys: Vec<Self>,
+        _source: &mut S,
+    ) -> Vec<Self> {
+        let ys: Vec<F> = ys.into_iter().map(|y| y.val).collect();
+        batch_mul_with::<F, Self, F>(&xs, &ys, |x, y| x.val * y)
+            .into_iter()
+            .map(Self::from_add_shared)
+            .collect()
+    }
+
+    fn inv<S: BeaverSource<Self, Self, Self>>(self, source: &mut S) -> Self {
+        Self::batch_inv(vec![self], source)[0]
+    }
+
+    fn batch_inv<S: BeaverSource<Self, Self, Self>>(xs: Vec<Self>, source: &mut S) -> Vec<Self> {
+        // (x * r)^-1 * r = x^-1
+        let rs = Self::batch_rand(xs.len(), &mut rand::thread_rng());
+        let xrs = Self::batch_open(Self::batch_mul(xs, rs.clone(), source));
+        rs.into_iter()
+            .zip(xrs)
+            .map(|(mut r, xr)| {
+                r.scale(&xr.inverse().unwrap());
+                r
+            })
+            .collect()
+    }
+
+    fn batch_div<S: BeaverSource<Self, Self, Self>>(
+        xs: Vec<Self>,
+        ys: Vec<Self>,
+        source: &mut S,
+    ) -> Vec<Self> {
+        // (x * r) / (y * r) = x / y, opening only y * r
+        assert_eq!(xs.len(), ys.len());
+        let n = xs.len();
+        let rs = Self::batch_rand(n, &mut rand::thread_rng());
+        let mut xrs = Self::batch_mul(
+            xs.into_iter().chain(ys).collect(),
+            rs.iter().chain(&rs).cloned().collect(),
+            source,
+        );
+        let mut yrs = Self::batch_open(xrs.split_off(n));
+        ark_ff::batch_inversion(&mut yrs);
+        xrs.into_iter()
+            .zip(yrs)
+            .map(|(mut xr, i)| {
+                xr.scale(&i);
+                xr
+            })
+            .collect()
+    }
+
+    fn partial_products<S: BeaverSource<Self, Self, Self>>(x: Vec<Self>, src: &mut S) -> Vec<Self> {
+        let n = x.len();
+        let m = Self::batch_rand(n + 1, &mut rand::thread_rng());
+        let m_inv = Self::batch_inv(m.clone(), src);
+        let mx = Self::batch_mul(m[..n].to_vec(), x, src);
+        let mxm = Self::batch_mul(mx, m_inv[1..].to_vec(), src);
+        let mut mxm_pub = Self::batch_open(mxm);
+        for i in 1..mxm_pub.len() {
+            let last = mxm_pub[i - 1];
+            mxm_pub[i] *= &last;
+        }
+        let m0 = vec![m[0]; n];
+        let mms = Self::batch_mul(m0, m_inv[1..].to_vec(), src);
+        let mut mms_inv = Self::batch_inv(mms, src);
+        for i in 0..mxm_pub.len() {
+            mms_inv[i].scale(&mxm_pub[i]);
+        }
+        mms_inv
+    }
+
+    fn univariate_div_qr<'a>(
+        num: DenseOrSparsePolynomial<Self>,
+        den: DenseOrSparsePolynomial<F>,
+    ) -> Option<(DensePolynomial<Self>, DensePolynomial<Self>)> {
+        let num = Self::poly_share(num);
+        let den = Self::poly_share2(den);
+        num.divide_with_q_and_r(&den)
+            .map(|(q, r)| (Self::d_poly_unshare(q), Self::d_poly_unshare(r)))
+    }
+
+    fn batch_linear_map<Fun: Fn(&mut Vec<F>)>(shares: Vec<Self>, f: Fun) -> Option<Vec<Self>> {
+        let mut vals: Vec<F> = shares.into_iter().map(|s| s.val).collect();
+        f(&mut vals);
+        Some(vals.into_iter().map(Self::from_add_shared).collect())
+    }
+}
+
+/// A Shamir share of a group element.
+#[derive(Derivative)]
+#[derivative(
+    Default(bound = "T: Default"),
+    Clone(bound = "T: Clone"),
+    Copy(bound = "T: Copy"),
+    PartialEq(bound = "T: PartialEq"),
+    Eq(bound = "T: Eq"),
+    PartialOrd(bound = "T: PartialOrd"),
+    Ord(bound = "T: Ord"),
+    Hash(bound = "T: Hash")
+)]
+pub struct ShamirGroupShare<T, M> {
+    pub val: T,
+    _phants: PhantomData<M>,
+}
+
+impl<G: Group, M> Reveal for ShamirGroupShare<G, M> {
+    type Base = G;
+
+    fn reveal(self) -> G {
+        open_quorum::<G::ScalarField, G>(vec![self.val])[0]
+    }
+    fn from_public(f: G) -> Self {
+        Self::from_add_shared(f)
+    }
+    /// Wrap our share: our evaluation of the sharing polynomial.
+    fn from_add_shared(f: G) -> Self {
+        Self {
+            val: f,
+            _phants: PhantomData,
+        }
+    }
+    fn unwrap_as_public(self) -> G {
+        self.val
+    }
+    fn king_share<R: Rng>(f: Self::Base, rng: &mut R) -> Self {
+        let shares = if Net::am_king() {
+            Some(deal::<G::ScalarField, G, R>(f, threshold(), rng))
+        } else {
+            None
+        };
+        Self::from_add_shared(Net::recv_from_king(shares))
+    }
+}
+
+impl<G: Group, M: Msm<G, G::ScalarField>> GroupShare<G> for ShamirGroupShare<G, M> {
+    type FieldShare = ShamirFieldShare<G::ScalarField>;
+
+    fn batch_open(selfs: impl IntoIterator<Item = Self>) -> Vec<G> {
+        open_quorum::<G::ScalarField, G>(selfs.into_iter().map(|s| s.val).collect())
+    }
+
+    fn add(&mut self, other: &Self) -> &mut Self {
+        self.val += &other.val;
+        self
+    }
+
+    fn sub(&mut self, other: &Self) -> &mut Self {
+        self.val -= &other.val;
+        self
+    }
+
+    fn scale_pub_scalar(&mut self, scalar: &G::ScalarField) -> &mut Self {
+        self.val *= *scalar;
+        self
+    }
+
+    fn scale_pub_group(mut base: G, scalar: &Self::FieldShare) -> Self {
+        base *= scalar.val;
+        Self::from_add_shared(base)
+    }
+
+    fn shift(&mut self, other: &G) -> &mut Self {
+        self.val += other;
+        self
+    }
+
+    fn scale<S: BeaverSource<Self, Self::FieldShare, Self>>(
+        self,
+        other: Self::FieldShare,
+        _source: &mut S,
+    ) -> Self {
+        let timer = start_timer!(|| "SS scalar multiplication");
+        let out = batch_mul_with::<G::ScalarField, G, G>(&[self.val], &[other.val], |g, s| {
+            let mut g = *g;
+            g *= *s;
+            g
+        });
+        end_timer!(timer);
+        Self::from_add_shared(out[0])
+    }
+
+    fn multi_scale_pub_group(bases: &[G], scalars: &[Self::FieldShare]) -> Self {
+        let scalars: Vec<G::ScalarField> = scalars.iter().map(|s| s.val).collect();
+        Self::from_add_shared(M::msm(bases, &scalars))
+    }
+
+    fn batch_multi_scale_pub_group(msms: &[(&[G], &[Self::FieldShare])]) -> Vec<Self> {
+        let scalars: Vec<Vec<G::ScalarField>> = msms
+            .iter()
+            .map(|(_, s)| s.iter().map(|s| s.val).collect())
+            .collect();
+        let msms: Vec<(&[G], &[G::ScalarField])> = msms
+            .iter()
+            .zip(&scalars)
+            .map(|((bases, _), scalars)| (*bases, &scalars[..]))
+            .collect();
+        M::batch_msm(&msms)
+            .into_iter()
+            .map(Self::from_add_shared)
+            .collect()
+    }
+}
+
+/// A multiplicative Shamir share of a field element: the value is the product of the shares of
+/// a quorum, raised to their Lagrange coefficients (over `S`).
+///
+/// Pairing a shared element with a public one yields such a share, and products, quotients and
+/// scalings by elements of the order-`S` subgroup keep it. Anything else (sums, shifts, scalings
+/// by other elements) first converts it to a Shamir share of the value itself, over `F`, which
+/// behaves like a [ShamirFieldShare] from then on.
+#[derive(Derivative)]
+#[derivative(
+    Clone(bound = "T: Clone"),
+    Copy(bound = "T: Copy"),
+    PartialEq(bound = "T: PartialEq"),
+    Eq(bound = "T: Eq"),
+    PartialOrd(bound = "T: PartialOrd"),
+    Ord(bound = "T: Ord"),
+    Hash(bound = "T: Hash")
+)]
+pub struct ShamirMulFieldShare<T, S> {
+    pub val: T,
+    /// Is `val` a share of the value itself, rather than of its discrete log?
+    pub additive: bool,
+    _phants: PhantomData<S>,
+}
+
+impl<F: Field, S> ShamirMulFieldShare<F, S> {
+    fn from_shamir(s: ShamirFieldShare<F>) -> Self {
+        Self {
+            val: s.val,
+            additive: true,
+            _phants: PhantomData,
+        }
+    }
+    fn to_shamir(self) -> ShamirFieldShare<F> {
+        debug_assert!(self.additive);
+        ShamirFieldShare::from_add_shared(self.val)
+    }
+}
+
+impl<F: Field, S: PrimeField> ShamirMulFieldShare<F, S> {
+    /// Is `f` in the subgroup of order `S`, whose elements a multiplicative share may be scaled
+    /// by?
+    fn in_subgroup(f: &F) -> bool {
+        f.pow(S::characteristic()).is_one()
+    }
+
+    /// Shares of the same values, all of them additive.
+    ///
+    /// Each party of the quorum deals a sharing of its share, raised to its Lagrange coefficient,
+    /// and the parties multiply these together, in `log(t + 1)` rounds of degree reduction. The
+    /// other parties deal sharings of one, so that every party sends alike.
+    pub fn batch_to_additive(mut selfs: Vec<Self>) -> Vec<Self> {
+        let muls: Vec<usize> = (0..selfs.len()).filter(|&i| !selfs[i].additive).collect();
+        if muls.is_empty() {
+            return selfs;
+        }
+        let timer = start_timer!(|| format!("Multiplicative to additive: {}", muls.len()));
+        let quorum: Vec<usize> = (0..threshold() + 1).collect();
+        let l = lagrange_at_zero::<S>(&quorum).get(Net::party_id()).cloned();
+        let rng = &mut rand::thread_rng();
+        let mut outs = vec![Vec::with_capacity(muls.len()); Net::n_parties()];
+        for &i in &muls {
+            let factor = l.map_or_else(F::one, |l| selfs[i].val.pow(l.into_repr()));
+            let dealt = deal::<F, F, _>(factor, threshold(), rng);
+            for (out, s) in outs.iter_mut().zip(dealt) {
+                out.push(s);
+            }
+        }
+        let mut factors: Vec<Vec<F>> = Net::send_to_each(&outs);
+        factors.truncate(quorum.len());
+        while factors.len() > 1 {
+            let odd = if factors.len() % 2 == 1 {
+                factors.pop()
+            } else {
+                None
+            };
+            let ys = factors.split_off(factors.len() / 2).concat();
+            let xs = factors.concat();
+            factors = batch_mul_with::<F, F, F>(&xs, &ys, |x, y| *x * y)
+                .chunks(muls.len())
+                .map(|c| c.to_vec())
+                .collect();
+            factors.extend(odd);
+        }
+        for (&i, v) in muls.iter().zip(factors.pop().unwrap()) {
+            selfs[i] = Self::from_shamir(ShamirFieldShare::from_add_shared(v));
+        }
+        end_timer!(timer);
+        selfs
+    }
+}
+
+impl<F: Field, S: PrimeField> Reveal for ShamirMulFieldShare<F, S> {
+    type Base = F;
+
+    fn reveal(self) -> F {
+        if self.additive {
+            return self.to_shamir().reveal();
+        }
+        // The first `t + 1` responders' shares, as in [open_quorum], but in the exponent
+        let t = threshold();
+        let (quorum, vals): (Vec<usize>, Vec<F>) = Net::broadcast_quorum(&self.val, t + 1)
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, v)| v.map(|v| (i, v)))
+            .take(t + 1)
+            .unzip();
+        lagrange_at_zero::<S>(&quorum)
+            .into_iter()
+            .zip(vals)
+            .map(|(l, v)| v.pow(l.into_repr()))
+            .product()
+    }
+    /// A share, of degree zero, that every party holds alike: an additive one, since `f` need not
+    /// be in the subgroup.
+    fn from_public(f: F) -> Self {
+        Self::from_shamir(ShamirFieldShare::from_public(f))
+    }
+    /// Wrap our share: our evaluation of the sharing polynomial, in the exponent.
+    fn from_add_shared(f: F) -> Self {
+        Self {
+            val: f,
+            additive: false,
+            _phants: PhantomData,
+        }
+    }
+    fn unwrap_as_public(self) -> F {
+        self.val
+    }
+}
+
+impl<F: Field, S: PrimeField> FieldShare<F> for ShamirMulFieldShare<F, S> {
+    fn map_homo<FF: Field, SS: FieldShare<FF>, Fun: Fn(F) -> FF>(self, f: Fun) -> SS {
+        let s = Self::batch_to_additive(vec![self])[0];
+        SS::from_add_shared(f(s.val))
+    }
+
+    fn add(&mut self, other: &Self) -> &mut Self {
+        let both = Self::batch_to_additive(vec![*self, *other]);
+        self.val = both[0].val + both[1].val;
+        self.additive = true;
+        self
+    }
+
+    fn sub(&mut self, other: &Self) -> &mut Self {
+        let both = Self::batch_to_additive(vec![*self, *other]);
+        self.val = both[0].val - both[1].val;
+        self.additive = true;
+        self
+    }
+
+    fn scale(&mut self, other: &F) -> &mut Self {
+        if !self.additive && !Self::in_subgroup(other) {
+            *self = Self::batch_to_additive(vec![*self])[0];
+        }
+        self.val *= other;
+        self
+    }
+
+    fn shift(&mut self, other: &F) -> &mut Self {
+        *self = Self::batch_to_additive(vec![*self])[0];
+        self.val += other;
+        self
+    }
+
+    fn mul<SS: BeaverSource<Self, Self, Self>>(self, other: Self, source: &mut SS) -> Self {
+        Self::batch_mul(vec![self], vec![other], source)[0]
+    }
+
+    fn batch_mul<SS: BeaverSource<Self, Self, Self>>(
+        mut xs: Vec<Self>,
+        ys: Vec<Self>,
+        _source: &mut SS,
+    ) -> Vec<Self> {
+        assert_eq!(xs.len(), ys.len());
+        let n = xs.len();
+        let mixed: Vec<usize> = (0..n)
+            .filter(|&i| xs[i].additive || ys[i].additive)
+            .collect();
+        let mut conv = Self::batch_to_additive(
+            mixed
+                .iter()
+                .map(|&i| xs[i])
+                .chain(mixed.iter().map(|&i| ys[i]))
+                .collect(),
+        )
+        .into_iter()
+        .map(Self::to_shamir)
+        .collect::<Vec<_>>();
+        let conv_ys = conv.split_off(mixed.len());
+        let prods = ShamirFieldShare::batch_mul(conv, conv_ys, &mut PanicBeaverSource::default());
+        for (x, y) in xs.iter_mut().zip(ys.iter()) {
+            if !x.additive && !y.additive {
+                x.val *= y.val;
+            }
+        }
+        for (&i, p) in mixed.iter().zip(prods) {
+            xs[i] = Self::from_shamir(p);
+        }
+        xs
+    }
+
+    fn inv<SS: BeaverSource<Self, Self, Self>>(self, source: &mut SS) -> Self {
+        Self::batch_inv(vec![self], source)[0]
+    }
+
+    fn batch_inv<SS: BeaverSource<Self, Self, Self>>(
+        mut xs: Vec<Self>,
+        _source: &mut SS,
+    ) -> Vec<Self> {
+        let adds: Vec<usize> = (0..xs.len()).filter(|&i| xs[i].additive).collect();
+        let invs = ShamirFieldShare::batch_inv(
+            adds.iter().map(|&i| xs[i].to_shamir()).collect(),
+            &mut PanicBeaverSource::default(),
+        );
+        for x in xs.iter_mut().filter(|x| !x.additive) {
+            x.val = x.val.inverse().unwrap();
+        }
+        for (&i, v) in adds.iter().zip(invs) {
+            xs[i] = Self::from_shamir(v);
+        }
+        xs
+    }
+
+    fn batch_div<SS: BeaverSource<Self, Self, Self>>(
+        xs: Vec<Self>,
+        ys: Vec<Self>,
+        source: &mut SS,
+    ) -> Vec<Self> {
+        let ys = Self::batch_inv(ys, source);
+        Self::batch_mul(xs, ys, source)
+    }
+}
+
+macro_rules! impl_shamir_basics {
+    ($share:ident, $bound:ident) => {
+        impl<T: $bound> Display for $share<T> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.val)
+            }
+        }
+        impl<T: $bound> Debug for $share<T> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                write!(f, "{:?}", self.val)
+            }
+        }
+        impl<T: $bound> ToBytes for $share<T> {
+            fn write<W: Write>(&self, _writer: W) -> io::Result<()> {
+                unimplemented!("write")
+            }
+        }
+        impl<T: $bound> FromBytes for $share<T> {
+            fn read<R: Read>(_reader: R) -> io::Result<Self> {
+                unimplemented!("read")
+            }
+        }
+        impl<T: $bound> CanonicalSerialize for $share<T> {
+            fn serialize<W: Write>(&self, writer: W) -> Result<(), SerializationError> {
+                self.val.serialize(writer)
+            }
+            fn serialized_size(&self) -> usize {
+                self.val.serialized_size()
+            }
+        }
+        impl<T: $bound> CanonicalSerializeWithFlags for $share<T> {
+            fn serialize_with_flags<W: Write, F: Flags>(
+                &self,
+                _writer: W,
+                _flags: F,
+            ) -> Result<(), SerializationError> {
+                unimplemented!("serialize_with_flags")
+            }
+
+            fn serialized_size_with_flags<F: Flags>(&self) -> usize {
+                unimplemented!("serialized_size_with_flags")
+            }
+        }
+        impl<T: $bound> CanonicalDeserialize for $share<T> {
+            fn deserialize<R: Read>(reader: R) -> Result<Self, SerializationError> {
+                Ok(Self {
+                    val: T::deserialize(reader)?,
+                })
+            }
+        }
+        impl<T: $bound> CanonicalDeserializeWithFlags for $share<T> {
+            fn deserialize_with_flags<R: Read, F: Flags>(
+                _reader: R,
+            ) -> Result<(Self, F), SerializationError> {
+                unimplemented!("deserialize_with_flags")
+            }
+        }
+        impl<T: $bound> UniformRand for $share<T> {
+            fn rand<R: Rng + ?Sized>(rng: &mut R) -> Self {
+                Self::batch_rand(1, rng)[0]
+            }
+        }
+    };
+}
+
+macro_rules! impl_shamir_basics_2_param {
+    ($share:ident, $bound:ident, $rand:expr $(, $extra:ident)*) => {
+        impl<T: $bound, M> Display for $share<T, M> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.val)
+            }
+        }
+        impl<T: $bound, M> Debug for $share<T, M> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                write!(f, "{:?}", self.val)
+            }
+        }
+        impl<T: $bound, M> ToBytes for $share<T, M> {
+            fn write<W: Write>(&self, _writer: W) -> io::Result<()> {
+                unimplemented!("write")
+            }
+        }
+        impl<T: $bound, M> FromBytes for $share<T, M> {
+            fn read<R: Read>(_reader: R) -> io::Result<Self> {
+                unimplemented!("read")
+            }
+        }
+        impl<T: $bound, M> CanonicalSerialize for $share<T, M> {
+            fn serialize<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+                self.val.serialize(&mut writer)?;
+                $(self.$extra.serialize(&mut writer)?;)*
+                Ok(())
+            }
+            fn serialized_size(&self) -> usize {
+                self.val.serialized_size() $(+ self.$extra.serialized_size())*
+            }
+        }
+        impl<T: $bound, M> CanonicalSerializeWithFlags for $share<T, M> {
+            fn serialize_with_flags<W: Write, F: Flags>(
+                &self,
+                _writer: W,
+                _flags: F,
+            ) -> Result<(), SerializationError> {
+                unimplemented!("serialize_with_flags")
+            }
+
+            fn serialized_size_with_flags<F: Flags>(&self) -> usize {
+                unimplemented!("serialized_size_with_flags")
+            }
+        }
+        impl<T: $bound, M> CanonicalDeserialize for $share<T, M> {
+            fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+                Ok(Self {
+                    val: T::deserialize(&mut reader)?,
+                    $($extra: CanonicalDeserialize::deserialize(&mut reader)?,)*
+                    _phants: PhantomData,
+                })
+            }
+        }
+        impl<T: $bound, M> CanonicalDeserializeWithFlags for $share<T, M> {
+            fn deserialize_with_flags<R: Read, F: Flags>(
+                _reader: R,
+            ) -> Result<(Self, F), SerializationError> {
+                unimplemented!("deserialize_with_flags")
+            }
+        }
+        impl<T: $bound, M> UniformRand for $share<T, M> {
+            fn rand<R: Rng + ?Sized>(rng: &mut R) -> Self {
+                $rand(rng)
+            }
+        }
+    };
+}
+
+impl_shamir_basics!(ShamirFieldShare, Field);
+impl_shamir_basics_2_param!(ShamirGroupShare, Group, |rng| {
+    Self::from_add_shared(random_sharings::<T::ScalarField, T, _>(1, &[threshold()], rng)[0][0])
+});
+impl_shamir_basics_2_param!(
+    ShamirMulFieldShare,
+    Field,
+    |rng| Self::from_shamir(ShamirFieldShare::batch_rand(1, rng)[0]),
+    additive
+);
+
+#[derive(Debug, Derivative)]
+#[derivative(
+    Default(bound = ""),
+    Clone(bound = ""),
+    Copy(bound = ""),
+    PartialEq(bound = "F: PartialEq"),
+    Eq(bound = "F: Eq"),
+    Hash(bound = "F: Hash")
+)]
+pub struct ShamirExtFieldShare<F: Field>(pub PhantomData<F>);
+
+impl<F: Field> ExtFieldShare<F> for ShamirExtFieldShare<F> {
+    type Ext = ShamirFieldShare<F>;
+    type Base = ShamirFieldShare<F::BasePrimeField>;
+}
+
+#[derive(Debug, Derivative)]
+#[derivative(
+    Default(bound = ""),
+    Clone(bound = ""),
+    Copy(bound = ""),
+    PartialEq(bound = "F: PartialEq"),
+    Eq(bound = "F: Eq"),
+    Hash(bound = "F: Hash")
+)]
+pub struct ShamirMulExtFieldShare<F: Field, S>(pub PhantomData<(F, S)>);
+
+impl<F: Field, S: PrimeField> ExtFieldShare<F> for ShamirMulExtFieldShare<F, S> {
+    type Ext = ShamirMulFieldShare<F, S>;
+    type Base = ShamirMulFieldShare<F::BasePrimeField, S>;
+}
+
+macro_rules! groups_share {
+    ($struct_name:ident, $affine:ident, $proj:ident) => {
+        pub struct $struct_name<E: PairingEngine>(pub PhantomData<E>);
+
+        impl<E: PairingEngine> AffProjShare<E::Fr, E::$affine, E::$proj> for $struct_name<E> {
+            type FrShare = ShamirFieldShare<E::Fr>;
+            type AffineShare = ShamirGroupShare<E::$affine, crate::msm::AffineMsm<E::$affine>>;
+            type ProjectiveShare = ShamirGroupShare<E::$proj, crate::msm::ProjectiveMsm<E::$proj>>;
+
+            fn sh_aff_to_proj(g: Self::AffineShare) -> Self::ProjectiveShare {
+                g.map_homo(|s| s.into())
+            }
+
+            fn sh_proj_to_aff(g: Self::ProjectiveShare) -> Self::AffineShare {
+                g.map_homo(|s| s.into())
+            }
+
+            fn add_sh_proj_sh_aff(
+                mut a: Self::ProjectiveShare,
+                o: &Self::AffineShare,
+            ) -> Self::ProjectiveShare {
+                a.val.add_assign_mixed(&o.val);
+                a
+            }
+            fn add_sh_proj_pub_aff(
+                mut a: Self::ProjectiveShare,
+                o: &E::$affine,
+            ) -> Self::ProjectiveShare {
+                a.val.add_assign_mixed(&o);
+                a
+            }
+            fn add_pub_proj_sh_aff(a: &E::$proj, o: Self::AffineShare) -> Self::ProjectiveShare {
+                let mut o = Self::sh_aff_to_proj(o);
+                o.shift(a);
+                o
+            }
+        }
+    };
+}
+
+groups_share!(ShamirG1Share, G1Affine, G1Projective);
+groups_share!(ShamirG2Share, G2Affine, G2Projective);
+
+/// Shamir shares of a curve outside of any pairing engine.
+#[derive(Debug, Derivative)]
+#[derivative(
+    Default(bound = ""),
+    Clone(bound = ""),
+    Copy(bound = ""),
+    PartialEq(bound = ""),
+    Eq(bound = "")
+)]
+pub struct ShamirCurveShare<C>(pub PhantomData<C>);
+
+impl<C: AffineGroup> AffProjShare<<C as AffineCurve>::ScalarField, C, C::Projective>
+    for ShamirCurveShare<C>
+{
+    type FrShare = ShamirFieldShare<<C as AffineCurve>::ScalarField>;
+    type AffineShare = ShamirGroupShare<C, AffineMsm<C>>;
+    type ProjectiveShare = ShamirGroupShare<C::Projective, ProjectiveMsm<C::Projective>>;
+
+    fn sh_aff_to_proj(g: Self::AffineShare) -> Self::ProjectiveShare {
+        g.map_homo(|s| s.into())
+    }
+
+    fn sh_proj_to_aff(g: Self::ProjectiveShare) -> Self::AffineShare {
+        g.map_homo(|s| s.into())
+    }
+
+    fn add_sh_proj_sh_aff(
+        mut a: Self::ProjectiveShare,
+        o: &Self::AffineShare,
+    ) -> Self::ProjectiveShare {
+        a.val.add_assign_mixed(&o.val);
+        a
+    }
+    fn add_sh_proj_pub_aff(mut a: Self::ProjectiveShare, o: &C) -> Self::ProjectiveShare {
+        a.val.add_assign_mixed(o);
+        a
+    }
+    fn add_pub_proj_sh_aff(a: &C::Projective, o: Self::AffineShare) -> Self::ProjectiveShare {
+        let mut o = Self::sh_aff_to_proj(o);
+        o.shift(a);
+        o
+    }
+}
+
+impl<C: AffineGroup> CurveShare<C> for ShamirCurveShare<C> {}
+
+#[derive(Debug, Derivative)]
+#[derivative(
+    Default(bound = ""),
+    Clone(bound = ""),
+    Copy(bound = ""),
+    PartialEq(bound = "E::G1Affine: PartialEq"),
+    Eq(bound = "E::G1Affine: Eq"),
+    Hash(bound = "E::G1Affine: Hash")
+)]
+pub struct ShamirPairingShare<E: PairingEngine>(pub PhantomData<E>);
+
+impl<E: PairingEngine> PairingShare<E> for ShamirPairingShare<E> {
+    type FrShare = ShamirFieldShare<E::Fr>;
+    type FqShare = ShamirFieldShare<E::Fq>;
+    type FqeShare = ShamirExtFieldShare<E::Fqe>;
+    // Not a typo. We want a multiplicative subgroup.
+    type FqkShare = ShamirMulExtFieldShare<E::Fqk, E::Fr>;
+    type G1AffineShare = ShamirGroupShare<E::G1Affine, crate::msm::AffineMsm<E::G1Affine>>;
+    type G2AffineShare = ShamirGroupShare<E::G2Affine, crate::msm::AffineMsm<E::G2Affine>>;
+    type G1ProjectiveShare =
+        ShamirGroupShare<E::G1Projective, crate::msm::ProjectiveMsm<E::G1Projective>>;
+    type G2ProjectiveShare =
+        ShamirGroupShare<E::G2Projective, crate::msm::ProjectiveMsm<E::G2Projective>>;
+    type G1 = ShamirG1Share<E>;
+    type G2 = ShamirG2Share<E>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::share::PanicFieldTripleSource;
+    use ark_bls12_377::{Bls12_377, Fq12, Fr, G1Affine, G2Affine};
+    use ark_std::test_rng;
+
+    type MulShare = ShamirMulFieldShare<Fq12, Fr>;
+
+    #[test]
+    fn reconstruct_from_any_quorum() {
+        let values: Vec<Fr> = (0..4).map(|_| Fr::rand(&mut test_rng())).collect();
+        let results = Net::simulate(5, |_| {
+            let shares = ShamirFieldShare::king_share_batch(values.clone(), &mut test_rng());
+            (shares.clone(), ShamirFieldShare::batch_open(shares))
+        });
+        for (_, opened) in &results {
+            assert_eq!(opened, &values);
+        }
+        let shares = |parties: &[usize], k: usize| -> Vec<(usize, Fr)> {
+            parties.iter().map(|&i| (i, results[i].0[k].val)).collect()
+        };
+        for (k, v) in values.iter().enumerate() {
+            for quorum in [[0, 1, 2], [2, 3, 4], [0, 2, 4]] {
+                assert_eq!(reconstruct_from::<Fr, Fr>(&shares(&quorum, k)), *v);
+            }
+            // Two shares (the threshold) are not enough
+            assert_ne!(reconstruct_from::<Fr, Fr>(&shares(&[1, 3], k)), *v);
+        }
+    }
+
+    #[test]
+    fn reconstruct_without_dropped_party() {
+        let values: Vec<Fr> = (0..4).map(|_| Fr::rand(&mut test_rng())).collect();
+        // Party 3 drops out once the values are shared, so only the others' shares are left
+        let results = Net::simulate(4, |id| {
+            let shares = ShamirFieldShare::king_share_batch(values.clone(), &mut test_rng());
+            (id != 3).then_some(shares)
+        });
+        let survivors: Vec<(usize, Vec<ShamirFieldShare<Fr>>)> = results
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, s)| s.map(|s| (i, s)))
+            .collect();
+        assert_eq!(survivors.len(), 3);
+        for (k, v) in values.iter().enumerate() {
+            let shares: Vec<(usize, Fr)> = survivors.iter().map(|(i, s)| (*i, s[k].val)).collect();
+            assert_eq!(reconstruct_from::<Fr, Fr>(&shares), *v);
+            assert_eq!(reconstruct_from::<Fr, Fr>(&shares[1..]), *v);
+        }
+    }
+
+    #[test]
+    fn open_without_dropped_party() {
+        let values: Vec<Fr> = (0..4).map(|_| Fr::rand(&mut test_rng())).collect();
+        // Party 3 drops out once the values are shared, and the others open them without it
+        let results = Net::simulate(4, |id| {
+            let shares = ShamirFieldShare::king_share_batch(values.clone(), &mut test_rng());
+            if id == 3 {
+                return None;
+            }
+            let first = shares[0].reveal();
+            Some((first, ShamirFieldShare::batch_open(shares)))
+        });
+        for (first, opened) in results.into_iter().flatten() {
+            assert_eq!(first, values[0]);
+            assert_eq!(opened, values);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "at least three parties")]
+    fn two_parties_refused() {
+        Net::simulate(2, |_| threshold());
+    }
+
+    #[test]
+    fn mul_shares_support_field_ops() {
+        let rng = &mut test_rng();
+        let (x, y) = (Fr::rand(rng), Fr::rand(rng));
+        let g = Bls12_377::pairing(
+            G1Affine::prime_subgroup_generator(),
+            G2Affine::prime_subgroup_generator(),
+        );
+        let (gx, gy) = (g.pow(x.into_repr()), g.pow(y.into_repr()));
+        let c = Fq12::from(3u64);
+        let results = Net::simulate(3, |_| {
+            let src = &mut PanicFieldTripleSource::default();
+            // Shares of x and y, in the exponent: what pairing shares of x * G1 and y * G2 gives
+            let [a, b] = [x, y].map(|v| {
+                let s = ShamirFieldShare::king_share(v, &mut test_rng());
+                MulShare::from_add_shared(g.pow(s.val.into_repr()))
+            });
+            let r = MulShare::rand(&mut test_rng());
+            let mut sum = a;
+            sum.add(&b);
+            let mut shifted = a;
+            shifted.shift(&c);
+            let mut scaled = a;
+            scaled.scale(&c);
+            let mut zero = r;
+            zero.sub(&r);
+            let opened = MulShare::batch_open(vec![
+                a,
+                a.mul(b, src),
+                sum,
+                shifted,
+                scaled,
+                sum.inv(src),
+                r.mul(a, src),
+                zero,
+            ]);
+            assert!(sum.additive && !a.mul(b, src).additive);
+            (opened, r.reveal())
+        });
+        for (opened, r) in results {
+            assert_eq!(
+                opened,
+                vec![
+                    gx,
+                    gx * gy,
+                    gx + gy,
+                    gx + c,
+                    gx * c,
+                    (gx + gy).inverse().unwrap(),
+                    r * gx,
+                    Fq12::zero(),
+                ]
+            );
+        }
+    }
+}
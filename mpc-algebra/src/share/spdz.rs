@@ -1,6 +1,9 @@
 #![macro_use]
 use derivative::Derivative;
-use rand::Rng;
+use lazy_static::lazy_static;
+use log::debug;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaChaRng;
 
 use ark_ec::{group::Group, AffineCurve, PairingEngine, ProjectiveCurve};
 use ark_ff::bytes::{FromBytes, ToBytes};
@@ -10,42 +13,251 @@ use ark_serialize::{
     CanonicalSerializeWithFlags, Flags, SerializationError,
 };
 
+use ark_std::{end_timer, start_timer};
+
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
 use std::cmp::Ord;
+use std::collections::HashMap;
 use std::fmt::{self, Debug, Display, Formatter};
 use std::hash::Hash;
 use std::io::{self, Read, Write};
 use std::marker::PhantomData;
+use std::sync::Mutex;
 
-use mpc_net::{MpcNet, MpcMultiNet as Net};
+use mpc_net::{Abort, AbortCode, MpcNet, MpcMultiNet as Net};
 use crate::channel::{can_cheat, MpcSerNet};
 
 use super::add::{AdditiveFieldShare, AdditiveGroupShare, MulFieldShare};
 use super::field::{DenseOrSparsePolynomial, DensePolynomial, ExtFieldShare, FieldShare};
 use super::group::GroupShare;
 use super::msm::*;
-use super::pairing::{AffProjShare, PairingShare};
-use super::{BeaverSource, PanicBeaverSource};
+use super::pairing::{AffProjShare, AffineGroup, CurveShare, PairingShare};
+use super::{add_types, take_types, BeaverSource, PanicBeaverSource};
 use crate::Reveal;
 
-#[inline]
-pub fn mac_share<F: Field>() -> F {
-    if Net::am_king() {
-        F::one()
+type MacKeys = HashMap<TypeId, Box<dyn Any + Send>>;
+
+lazy_static! {
+    /// This party's share of the MAC key of each field (see [mac_share]).
+    static ref KEY_SHARES: Mutex<MacKeys> = Mutex::new(HashMap::new());
+    /// The dealer's MAC key of each field (see [mac]): process-wide, since simulated parties
+    /// share their dealer.
+    static ref DEALER_KEYS: Mutex<MacKeys> = Mutex::new(HashMap::new());
+}
+
+thread_local! {
+    /// The MAC key shares of a party that is a thread (see [Net::simulate]).
+    static LOCAL_KEY_SHARES: RefCell<MacKeys> = RefCell::new(HashMap::new());
+}
+
+/// Run `f` on the MAC key shares: this thread's, if it is a simulated party, else the process's.
+fn with_key_shares<T>(f: impl FnOnce(&mut MacKeys) -> T) -> T {
+    if Net::is_simulated() {
+        LOCAL_KEY_SHARES.with(|local| f(&mut local.borrow_mut()))
     } else {
-        F::zero()
+        f(&mut KEY_SHARES.lock().unwrap())
     }
 }
 
+/// Our share of the MAC key of `F`.
+#[derive(Clone, Copy)]
+struct KeyShare<F> {
+    share: F,
+    /// The number of parties and our id, if this is the dummy dealer's share (see [dummy_key]).
+    dummy_for: Option<(usize, usize)>,
+}
+
+/// The dummy dealer's MAC key for `F`, and its additive shares for `n_parties` parties.
+///
+/// Like [crate::wire::field::DummyFieldTripleSource]'s triples, the key is fixed, so anyone can
+/// compute it: it stands in for preprocessing when none is done. Deal a secret one with
+/// [crate::preprocessing::deal_mac_key].
+fn dummy_key<F: Field>(n_parties: usize) -> (F, Vec<F>) {
+    let rng = &mut ChaChaRng::from_seed(*b"SPDZ dummy dealer's MAC key seed");
+    let key = F::rand(rng);
+    let mut shares: Vec<F> = (1..n_parties).map(|_| F::rand(rng)).collect();
+    let sum: F = shares.iter().sum();
+    shares.push(key - sum);
+    (key, shares)
+}
+
+/// Use `share` as our share of the MAC key of `F`. The shares of all parties must sum to the
+/// dealer's key.
+pub fn install_mac_key_share<F: Field>(share: F) {
+    with_key_shares(|keys| {
+        keys.insert(
+            TypeId::of::<F>(),
+            Box::new(KeyShare {
+                share,
+                dummy_for: None,
+            }),
+        )
+    });
+}
+
+/// Make `key` the dealer's MAC key of `F`, which [mac] gives: for dealing authenticated shares.
+pub fn install_dealer_mac_key<F: Field>(key: F) {
+    DEALER_KEYS
+        .lock()
+        .unwrap()
+        .insert(TypeId::of::<F>(), Box::new(key));
+}
+
+/// Our share of the secret MAC key, `alpha`, of `F`: every SPDZ share's MAC is a sharing of
+/// `alpha` times its value.
+///
+/// Ours stays private: without `alpha`, a party cannot shift a value and its MAC consistently.
+/// Unless one was installed (see [install_mac_key_share]), it is the dummy dealer's.
+#[inline]
+pub fn mac_share<F: Field>() -> F {
+    let parties = (Net::n_parties(), Net::party_id());
+    with_key_shares(|keys| {
+        let installed = keys
+            .get(&TypeId::of::<F>())
+            .and_then(|k| k.downcast_ref::<KeyShare<F>>());
+        match installed {
+            Some(k) if k.dummy_for.is_none_or(|p| p == parties) => k.share,
+            _ => {
+                let share = dummy_key::<F>(parties.0).1[parties.1];
+                let dummy_for = Some(parties);
+                keys.insert(TypeId::of::<F>(), Box::new(KeyShare { share, dummy_for }));
+                share
+            }
+        }
+    })
+}
+
 #[inline]
-/// A huge cheat. Useful for importing shares.
+/// A huge cheat. Useful for importing shares: the dealer's MAC key itself (see
+/// [install_dealer_mac_key]), or the dummy dealer's if none was installed.
 pub fn mac<F: Field>() -> F {
     if can_cheat() {
-        F::one()
+        let dealt = DEALER_KEYS
+            .lock()
+            .unwrap()
+            .get(&TypeId::of::<F>())
+            .map(|k| *k.downcast_ref::<F>().unwrap());
+        dealt.unwrap_or_else(|| dummy_key::<F>(1).0)
     } else {
         panic!("Attempted to grab the MAC secret while cheating was not allowed")
     }
 }
 
+/// An opened value, and our share of its MAC, awaiting a MAC check.
+struct SpdzFieldOpening<F>(F, F);
+
+/// An opened value, and our share of its MAC, awaiting a MAC check.
+struct SpdzGroupOpening<G>(G, G);
+
+/// Agree on a random challenge that no party can bias.
+pub(crate) fn joint_challenge<F: Field>() -> F {
+    Net::atomic_broadcast(&F::rand(&mut rand::thread_rng()))
+        .into_iter()
+        .sum()
+}
+
+/// Open values (without checking their MACs), and queue the MAC check.
+fn open_field<F: Field>(s_vals: Vec<F>, macs: Vec<F>) -> Vec<F> {
+    let n = s_vals.len();
+    let all_vals = Net::broadcast(&s_vals);
+    let vals: Vec<F> = (0..n).map(|i| all_vals.iter().map(|v| &v[i]).sum()).collect();
+    add_types(
+        vals.iter()
+            .zip(macs)
+            .map(|(val, mac)| SpdzFieldOpening(*val, mac))
+            .collect(),
+    );
+    vals
+}
+
+/// Open values (without checking their MACs), and queue the MAC check.
+fn open_group<G: Group>(s_vals: Vec<G>, macs: Vec<G>) -> Vec<G> {
+    let n = s_vals.len();
+    let all_vals = Net::broadcast(&s_vals);
+    let vals: Vec<G> = (0..n).map(|i| all_vals.iter().map(|v| &v[i]).sum()).collect();
+    add_types(
+        vals.iter()
+            .zip(macs)
+            .map(|(val, mac)| SpdzGroupOpening(*val, mac))
+            .collect(),
+    );
+    vals
+}
+
+/// Check the MACs of all field values opened since the last check.
+///
+/// This is a single check (_Pragmatic MPC_ 6.6.2) on the random linear combination
+/// `sum_i chi^i x_i` of the opened values, where `chi` is chosen jointly after they are opened.
+/// Raises an [AbortCode::MacCheckFailed] abort if the check fails.
+pub fn check_accumulated_field_macs<F: Field>() {
+    let to_check = take_types::<SpdzFieldOpening<F>>();
+    if !to_check.is_empty() {
+        let timer = start_timer!(|| format!("MAC check: {}", to_check.len()));
+        debug!("Field MAC check: {} openings", to_check.len());
+        let chi = joint_challenge::<F>();
+        let mut coeff = F::one();
+        let mut x = F::zero();
+        let mut x_mac = F::zero();
+        for SpdzFieldOpening(val, mac) in to_check {
+            x += coeff * val;
+            x_mac += coeff * mac;
+            coeff *= chi;
+        }
+        let dx_t: F = mac_share::<F>() * x - x_mac;
+        let all_dx_ts: Vec<F> = Net::atomic_broadcast(&dx_t);
+        let sum: F = all_dx_ts.iter().sum();
+        if !sum.is_zero() {
+            Abort::new(AbortCode::MacCheckFailed, "MAC check failed").raise();
+        }
+        end_timer!(timer);
+    }
+}
+
+/// Check the MACs of all group values opened since the last check.
+///
+/// See [check_accumulated_field_macs].
+pub fn check_accumulated_group_macs<G: Group>() {
+    let to_check = take_types::<SpdzGroupOpening<G>>();
+    if !to_check.is_empty() {
+        let timer = start_timer!(|| format!("MAC check: {}", to_check.len()));
+        debug!("Group MAC check: {} openings", to_check.len());
+        let chi = joint_challenge::<G::ScalarField>();
+        let mut coeff = G::ScalarField::one();
+        let mut x = G::zero();
+        let mut x_mac = G::zero();
+        for SpdzGroupOpening(val, mac) in to_check {
+            x += val.mul(&coeff);
+            x_mac += mac.mul(&coeff);
+            coeff *= chi;
+        }
+        let dx_t: G = x.mul(&mac_share::<G::ScalarField>()) - x_mac;
+        let all_dx_ts: Vec<G> = Net::atomic_broadcast(&dx_t);
+        let sum: G = all_dx_ts.iter().sum();
+        if !sum.is_zero() {
+            Abort::new(AbortCode::MacCheckFailed, "MAC check failed").raise();
+        }
+        end_timer!(timer);
+    }
+}
+
+/// Pair value and MAC shares of polynomial coefficients, the shorter padded with zeros.
+///
+/// Dividing our shares drops their leading zeros, which a value share and its MAC share needn't
+/// have alike: a coefficient that is public has a value share of zero at all but the king.
+fn zip_padded<F: Field>(
+    mut sh: Vec<AdditiveFieldShare<F>>,
+    mut mac: Vec<AdditiveFieldShare<F>>,
+) -> Vec<SpdzFieldShare<F>> {
+    let len = sh.len().max(mac.len());
+    sh.resize(len, AdditiveFieldShare::from_add_shared(F::zero()));
+    mac.resize(len, AdditiveFieldShare::from_add_shared(F::zero()));
+    sh.into_iter()
+        .zip(mac)
+        .map(|(sh, mac)| SpdzFieldShare { sh, mac })
+        .collect()
+}
+
 #[derive(Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct SpdzFieldShare<T> {
     sh: AdditiveFieldShare<T>,
@@ -75,11 +287,12 @@ macro_rules! impl_basics_spdz {
             }
         }
         impl<T: $bound> CanonicalSerialize for $share<T> {
-            fn serialize<W: Write>(&self, _writer: W) -> Result<(), SerializationError> {
-                unimplemented!("serialize")
+            fn serialize<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+                self.sh.serialize(&mut writer)?;
+                self.mac.serialize(&mut writer)
             }
             fn serialized_size(&self) -> usize {
-                unimplemented!("serialized_size")
+                self.sh.serialized_size() + self.mac.serialized_size()
             }
         }
         impl<T: $bound> CanonicalSerializeWithFlags for $share<T> {
@@ -96,8 +309,10 @@ macro_rules! impl_basics_spdz {
             }
         }
         impl<T: $bound> CanonicalDeserialize for $share<T> {
-            fn deserialize<R: Read>(_reader: R) -> Result<Self, SerializationError> {
-                unimplemented!("deserialize")
+            fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+                let sh = CanonicalDeserialize::deserialize(&mut reader)?;
+                let mac = CanonicalDeserialize::deserialize(&mut reader)?;
+                Ok(Self { sh, mac })
             }
         }
         impl<T: $bound> CanonicalDeserializeWithFlags for $share<T> {
@@ -119,14 +334,10 @@ impl_basics_spdz!(SpdzFieldShare, Field);
 impl<F: Field> Reveal for SpdzFieldShare<F> {
     type Base = F;
 
+    /// Opens, and then checks the MACs of everything opened so far.
     fn reveal(self) -> F {
-        let vals: Vec<F> = Net::broadcast(&self.sh.val);
-        // _Pragmatic MPC_ 6.6.2
-        let x: F = vals.iter().sum();
-        let dx_t: F = mac_share::<F>() * x - self.mac.val;
-        let all_dx_ts: Vec<F> = Net::atomic_broadcast(&dx_t);
-        let sum: F = all_dx_ts.iter().sum();
-        assert!(sum.is_zero());
+        let x = open_field(vec![self.sh.val], vec![self.mac.val]).pop().unwrap();
+        check_accumulated_field_macs::<F>();
         x
     }
     fn from_public(f: F) -> Self {
@@ -163,24 +374,19 @@ impl<F: Field> Reveal for SpdzFieldShare<F> {
 }
 
 impl<F: Field> FieldShare<F> for SpdzFieldShare<F> {
+    /// Opens, deferring the MAC check to the next [Reveal::reveal].
+    fn open(&self) -> F {
+        open_field(vec![self.sh.val], vec![self.mac.val]).pop().unwrap()
+    }
+    /// Opens, deferring the MAC check to the next [Reveal::reveal].
     fn batch_open(selfs: impl IntoIterator<Item = Self>) -> Vec<F> {
         let (s_vals, macs): (Vec<F>, Vec<F>) =
             selfs.into_iter().map(|s| (s.sh.val, s.mac.val)).unzip();
-        let n = s_vals.len();
-        let all_vals = Net::broadcast(&s_vals);
-        let vals: Vec<F> =
-            (0..n).map(|i| all_vals.iter().map(|v| &v[i]).sum()).collect();
-        let dx_ts: Vec<F> =
-            macs
-            .iter()
-            .zip(vals.iter())
-            .map(|(mac, val)| mac_share::<F>() * val - mac)
-            .collect();
-        let all_dx_ts: Vec<Vec<F>> = Net::atomic_broadcast(&dx_ts);
-        for i in 0..n {
-            let sum: F = all_dx_ts.iter().map(|dx_ts| &dx_ts[i]).sum();
-            assert!(sum.is_zero());
-        }
+        open_field(s_vals, macs)
+    }
+    fn batch_reveal(selfs: Vec<Self>) -> Vec<F> {
+        let vals = Self::batch_open(selfs);
+        check_accumulated_field_macs::<F>();
         vals
     }
     fn add(&mut self, other: &Self) -> &mut Self {
@@ -227,16 +433,20 @@ impl<F: Field> FieldShare<F> for SpdzFieldShare<F> {
         };
         let (q_sh, r_sh) = AdditiveFieldShare::univariate_div_qr(num_sh, den.clone()).unwrap();
         let (q_mac, r_mac) = AdditiveFieldShare::univariate_div_qr(num_mac, den).unwrap();
-        Some((
-            q_sh.into_iter()
-                .zip(q_mac)
-                .map(|(sh, mac)| Self { sh, mac })
-                .collect(),
-            r_sh.into_iter()
-                .zip(r_mac)
+        Some((zip_padded(q_sh, q_mac), zip_padded(r_sh, r_mac)))
+    }
+
+    /// Maps the value and MAC shares alike, so the MACs still hold.
+    fn batch_linear_map<Fun: Fn(&mut Vec<F>)>(shares: Vec<Self>, f: Fun) -> Option<Vec<Self>> {
+        let (sh, mac): (Vec<_>, Vec<_>) = shares.into_iter().map(|s| (s.sh, s.mac)).unzip();
+        let sh = AdditiveFieldShare::batch_linear_map(sh, &f)?;
+        let mac = AdditiveFieldShare::batch_linear_map(mac, &f)?;
+        Some(
+            sh.into_iter()
+                .zip(mac)
                 .map(|(sh, mac)| Self { sh, mac })
                 .collect(),
-        ))
+        )
     }
 }
 
@@ -259,18 +469,11 @@ pub struct SpdzGroupShare<T, M> {
 impl<G: Group, M> Reveal for SpdzGroupShare<G, M> {
     type Base = G;
 
+    /// Opens, and then checks the MACs of everything opened so far (including scalars).
     fn reveal(self) -> G {
-        let vals: Vec<G> = Net::broadcast(&self.sh.val);
-        // _Pragmatic MPC_ 6.6.2
-        let x: G = vals.iter().sum();
-        let dx_t: G = {
-            let mut t = x.clone();
-            t *= mac_share::<G::ScalarField>();
-            t - self.mac.val
-        };
-        let all_dx_ts: Vec<G> = Net::atomic_broadcast(&dx_t);
-        let sum: G = all_dx_ts.iter().sum();
-        assert!(sum.is_zero());
+        let x = open_group(vec![self.sh.val], vec![self.mac.val]).pop().unwrap();
+        check_accumulated_field_macs::<G::ScalarField>();
+        check_accumulated_group_macs::<G>();
         x
     }
     fn from_public(f: G) -> Self {
@@ -382,24 +585,20 @@ impl_spdz_basics_2_param!(SpdzGroupShare, Group);
 impl<G: Group, M: Msm<G, G::ScalarField>> GroupShare<G> for SpdzGroupShare<G, M> {
     type FieldShare = SpdzFieldShare<G::ScalarField>;
 
+    /// Opens, deferring the MAC check to the next [Reveal::reveal].
+    fn open(&self) -> G {
+        open_group(vec![self.sh.val], vec![self.mac.val]).pop().unwrap()
+    }
+    /// Opens, deferring the MAC check to the next [Reveal::reveal].
     fn batch_open(selfs: impl IntoIterator<Item = Self>) -> Vec<G> {
         let (s_vals, macs): (Vec<G>, Vec<G>) =
             selfs.into_iter().map(|s| (s.sh.val, s.mac.val)).unzip();
-        let n = s_vals.len();
-        let all_vals = Net::broadcast(&s_vals);
-        let vals: Vec<G> =
-            (0..n).map(|i| all_vals.iter().map(|v| &v[i]).sum()).collect();
-        let dx_ts: Vec<G> =
-            macs
-            .iter()
-            .zip(vals.iter())
-            .map(|(mac, val)| val.mul(&mac_share::<G::ScalarField>()) - mac)
-            .collect();
-        let all_dx_ts: Vec<Vec<G>> = Net::atomic_broadcast(&dx_ts);
-        for i in 0..n {
-            let sum: G = all_dx_ts.iter().map(|dx_ts| &dx_ts[i]).sum();
-            assert!(sum.is_zero());
-        }
+        open_group(s_vals, macs)
+    }
+    fn batch_reveal(selfs: Vec<Self>) -> Vec<G> {
+        let vals = Self::batch_open(selfs);
+        check_accumulated_field_macs::<G::ScalarField>();
+        check_accumulated_group_macs::<G>();
         vals
     }
 
@@ -439,11 +638,41 @@ impl<G: Group, M: Msm<G, G::ScalarField>> GroupShare<G> for SpdzGroupShare<G, M>
 
     fn multi_scale_pub_group(bases: &[G], scalars: &[Self::FieldShare]) -> Self {
         let shares: Vec<G::ScalarField> = scalars.into_iter().map(|s| s.sh.val.clone()).collect();
-        let macs: Vec<G::ScalarField> = scalars.into_iter().map(|s| s.sh.val.clone()).collect();
+        let macs: Vec<G::ScalarField> = scalars.into_iter().map(|s| s.mac.val.clone()).collect();
         let sh = AdditiveGroupShare::from_add_shared(M::msm(bases, &shares));
         let mac = AdditiveGroupShare::from_add_shared(M::msm(bases, &macs));
         Self { sh, mac }
     }
+
+    /// The shares' MSMs and the MACs', all in one batch.
+    fn batch_multi_scale_pub_group(msms: &[(&[G], &[Self::FieldShare])]) -> Vec<Self> {
+        // Shares, then MACs
+        let scalars: Vec<Vec<G::ScalarField>> = (0..2)
+            .flat_map(|half| {
+                msms.iter().map(move |(_, s)| {
+                    s.iter()
+                        .map(|s| if half == 0 { s.sh.val } else { s.mac.val })
+                        .collect()
+                })
+            })
+            .collect();
+        let msms: Vec<(&[G], &[G::ScalarField])> = msms
+            .iter()
+            .chain(msms)
+            .zip(&scalars)
+            .map(|((bases, _), scalars)| (*bases, &scalars[..]))
+            .collect();
+        let mut products = M::batch_msm(&msms);
+        let macs = products.split_off(msms.len() / 2);
+        products
+            .into_iter()
+            .zip(macs)
+            .map(|(sh, mac)| Self {
+                sh: AdditiveGroupShare::from_add_shared(sh),
+                mac: AdditiveGroupShare::from_add_shared(mac),
+            })
+            .collect()
+    }
 }
 
 #[derive(Derivative)]
@@ -510,9 +739,11 @@ impl<F: Field, S: PrimeField> FieldShare<F> for SpdzMulFieldShare<F, S> {
     }
 
     fn mul<S2: BeaverSource<Self, Self, Self>>(self, other: Self, _source: &mut S2) -> Self {
-        self.sh.mul(other.sh, &mut PanicBeaverSource::default());
-        self.mac.mul(other.mac, &mut PanicBeaverSource::default());
-        self
+        Self {
+            sh: self.sh.mul(other.sh, &mut PanicBeaverSource::default()),
+            mac: self.mac.mul(other.mac, &mut PanicBeaverSource::default()),
+            _phants: PhantomData::default(),
+        }
     }
 
     fn batch_mul<S2: BeaverSource<Self, Self, Self>>(
@@ -521,8 +752,8 @@ impl<F: Field, S: PrimeField> FieldShare<F> for SpdzMulFieldShare<F, S> {
         _source: &mut S2,
     ) -> Vec<Self> {
         for (x, y) in xs.iter_mut().zip(ys.iter()) {
-            x.sh.mul(y.sh, &mut PanicBeaverSource::default());
-            x.mac.mul(y.mac, &mut PanicBeaverSource::default());
+            x.sh = x.sh.mul(y.sh, &mut PanicBeaverSource::default());
+            x.mac = x.mac.mul(y.mac, &mut PanicBeaverSource::default());
         }
         xs
     }
@@ -538,6 +769,14 @@ impl<F: Field, S: PrimeField> FieldShare<F> for SpdzMulFieldShare<F, S> {
     fn batch_inv<S2: BeaverSource<Self, Self, Self>>(xs: Vec<Self>, source: &mut S2) -> Vec<Self> {
         xs.into_iter().map(|x| x.inv(source)).collect()
     }
+
+    fn batch_div<S2: BeaverSource<Self, Self, Self>>(
+        xs: Vec<Self>,
+        ys: Vec<Self>,
+        source: &mut S2,
+    ) -> Vec<Self> {
+        xs.into_iter().zip(ys).map(|(x, y)| x.div(y, source)).collect()
+    }
 }
 
 #[derive(Debug, Derivative)]
@@ -623,6 +862,62 @@ macro_rules! groups_share {
 groups_share!(SpdzG1Share, G1Affine, G1Projective);
 groups_share!(SpdzG2Share, G2Affine, G2Projective);
 
+/// SPDZ shares of a curve outside of any pairing engine.
+#[derive(Debug, Derivative)]
+#[derivative(
+    Default(bound = ""),
+    Clone(bound = ""),
+    Copy(bound = ""),
+    PartialEq(bound = ""),
+    Eq(bound = "")
+)]
+pub struct SpdzCurveShare<C>(pub PhantomData<C>);
+
+impl<C: AffineGroup> AffProjShare<<C as AffineCurve>::ScalarField, C, C::Projective>
+    for SpdzCurveShare<C>
+{
+    type FrShare = SpdzFieldShare<<C as AffineCurve>::ScalarField>;
+    type AffineShare = SpdzGroupShare<C, AffineMsm<C>>;
+    type ProjectiveShare = SpdzGroupShare<C::Projective, ProjectiveMsm<C::Projective>>;
+
+    fn sh_aff_to_proj(g: Self::AffineShare) -> Self::ProjectiveShare {
+        SpdzGroupShare {
+            sh: g.sh.map_homo(|s| s.into()),
+            mac: g.mac.map_homo(|s| s.into()),
+        }
+    }
+
+    fn sh_proj_to_aff(g: Self::ProjectiveShare) -> Self::AffineShare {
+        SpdzGroupShare {
+            sh: g.sh.map_homo(|s| s.into()),
+            mac: g.mac.map_homo(|s| s.into()),
+        }
+    }
+
+    fn add_sh_proj_sh_aff(
+        mut a: Self::ProjectiveShare,
+        o: &Self::AffineShare,
+    ) -> Self::ProjectiveShare {
+        a.sh.val.add_assign_mixed(&o.sh.val);
+        a.mac.val.add_assign_mixed(&o.mac.val);
+        a
+    }
+    fn add_sh_proj_pub_aff(mut a: Self::ProjectiveShare, o: &C) -> Self::ProjectiveShare {
+        if Net::am_king() {
+            a.sh.val.add_assign_mixed(&o);
+        }
+        a.mac.val += &o.scalar_mul(mac_share::<<C as AffineCurve>::ScalarField>());
+        a
+    }
+    fn add_pub_proj_sh_aff(a: &C::Projective, o: Self::AffineShare) -> Self::ProjectiveShare {
+        let mut o = Self::sh_aff_to_proj(o);
+        o.shift(a);
+        o
+    }
+}
+
+impl<C: AffineGroup> CurveShare<C> for SpdzCurveShare<C> {}
+
 #[derive(Debug, Derivative)]
 #[derivative(
     Default(bound = ""),
@@ -649,3 +944,90 @@ impl<E: PairingEngine> PairingShare<E> for SpdzPairingShare<E> {
     type G1 = SpdzG1Share<E>;
     type G2 = SpdzG2Share<E>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preprocessing::deal_mac_key;
+    use ark_bls12_377::{Fq, Fr};
+    use ark_std::test_rng;
+    use mpc_net::catch_abort;
+
+    /// Share `x` among three parties, let `tamper` change party 1's share, then open it.
+    fn open_tampered<F: Field>(
+        x: F,
+        tamper: impl Fn(&mut SpdzFieldShare<F>) + Sync,
+    ) -> Vec<Result<F, Abort>> {
+        Net::simulate(3, |id| {
+            let mut s = SpdzFieldShare::king_share(x, &mut test_rng());
+            if id == 1 {
+                tamper(&mut s);
+            }
+            catch_abort(|| s.reveal())
+        })
+    }
+
+    fn assert_mac_check_failed<F: Field>(results: Vec<Result<F, Abort>>) {
+        for r in results {
+            assert_eq!(r.unwrap_err().code, AbortCode::MacCheckFailed);
+        }
+    }
+
+    #[test]
+    fn honest_shares_pass() {
+        let x = Fr::rand(&mut test_rng());
+        let results = Net::simulate(3, |_| {
+            let mut s = SpdzFieldShare::king_share(x, &mut test_rng());
+            s.scale(&Fr::from(3u64)).shift(&Fr::one());
+            let mut t = s;
+            t.add(&s);
+            catch_abort(|| t.reveal())
+        });
+        for r in results {
+            assert_eq!(r.unwrap(), (x * Fr::from(3u64) + Fr::one()).double());
+        }
+    }
+
+    #[test]
+    fn shifting_a_share_and_its_mac_alike_is_caught() {
+        // With a public key of 1, this shift would pass
+        let delta = Fr::from(7u64);
+        assert_mac_check_failed(open_tampered(Fr::rand(&mut test_rng()), |s| {
+            s.sh.val += delta;
+            s.mac.val += delta;
+        }));
+    }
+
+    #[test]
+    fn tampered_mac_is_caught() {
+        assert_mac_check_failed(open_tampered(Fr::rand(&mut test_rng()), |s| {
+            s.mac.val += Fr::one();
+        }));
+    }
+
+    #[test]
+    fn dealt_key() {
+        // Over a field that no other test uses, since the dealer's key is process-wide
+        let rng = &mut test_rng();
+        let shares = deal_mac_key::<Fq, _>(3, rng);
+        let x = Fq::rand(rng);
+        let results = Net::simulate(3, |id| {
+            install_mac_key_share(shares[id]);
+            assert_eq!(mac_share::<Fq>(), shares[id]);
+            let s = SpdzFieldShare::king_share(x, &mut test_rng());
+            let mut tampered = s;
+            if id == 1 {
+                tampered.sh.val += Fq::one();
+                tampered.mac.val += Fq::one();
+            }
+            (
+                catch_abort(|| s.reveal()),
+                catch_abort(|| tampered.reveal()),
+            )
+        });
+        for (honest, tampered) in results {
+            assert_eq!(honest.unwrap(), x);
+            assert_eq!(tampered.unwrap_err().code, AbortCode::MacCheckFailed);
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! Secret-shared shuffles.
+//!
+//! [shuffle] permutes a shared vector so that no single party knows the permutation: each party,
+//! in turn, applies a random permutation of its own choosing (input as a shared permutation
+//! matrix). As long as one party is honest, the overall permutation is hidden.
+//!
+//! A dishonest party could input something other than a permutation matrix, so the result is
+//! checked with the (grand-product) permutation argument: see [check_permutation].
+use log::debug;
+use rand::seq::SliceRandom;
+
+use ark_ff::prelude::*;
+use ark_std::{end_timer, start_timer};
+
+use crate::preprocessing::GlobalTripleSource;
+use crate::share::field::FieldShare;
+use crate::share::spdz::joint_challenge;
+use crate::wire::field::MpcField;
+use mpc_net::{MpcMultiNet as Net, MpcNet};
+
+fn to_shares<F: Field, S: FieldShare<F>>(xs: &[MpcField<F, S>]) -> Vec<S> {
+    xs.iter().map(|x| x.into_share()).collect()
+}
+
+/// Apply a permutation known only to party `dealer`.
+fn permute_by<F: Field, S: FieldShare<F>>(dealer: usize, xs: Vec<S>) -> Vec<S> {
+    let n = xs.len();
+    let am_dealer = Net::party_id() == dealer;
+    // Row k of the matrix selects the entry that moves to position k.
+    let mut perm: Vec<usize> = (0..n).collect();
+    if am_dealer {
+        perm.shuffle(&mut rand::thread_rng());
+    }
+    let matrix: Vec<S> = (0..n * n)
+        .map(|i| {
+            let one = am_dealer && perm[i / n] == i % n;
+            S::from_add_shared(if one { F::one() } else { F::zero() })
+        })
+        .collect();
+    let cols: Vec<S> = (0..n * n).map(|i| xs[i % n]).collect();
+    let prods = S::batch_mul(matrix, cols, &mut GlobalTripleSource::default());
+    prods
+        .chunks(n)
+        .map(|row| {
+            let mut acc = row[0];
+            for p in &row[1..] {
+                acc.add(p);
+            }
+            acc
+        })
+        .collect()
+}
+
+/// Shuffle `xs`, by a permutation that no single party knows.
+///
+/// Panics if the permutation argument rejects the result.
+pub fn shuffle<F: Field, S: FieldShare<F>>(xs: &[MpcField<F, S>]) -> Vec<MpcField<F, S>> {
+    if xs.is_empty() {
+        return Vec::new();
+    }
+    let timer = start_timer!(|| format!("Shuffle {}", xs.len()));
+    let mut ys = to_shares(xs);
+    for dealer in 0..Net::n_parties() {
+        ys = permute_by(dealer, ys);
+    }
+    let ys: Vec<MpcField<F, S>> = ys.into_iter().map(MpcField::Shared).collect();
+    assert!(check_permutation(xs, &ys), "Shuffle is not a permutation");
+    end_timer!(timer);
+    ys
+}
+
+/// Check that `ys` is a permutation of `xs`, revealing nothing else (with high probability).
+///
+/// This is the permutation argument: for a random challenge `g`, the products of `x + g` and of
+/// `y + g` agree iff the multisets agree (up to a soundness error of `len / |F|`). We open only
+/// their ratio, which is one for a permutation, and random otherwise.
+pub fn check_permutation<F: Field, S: FieldShare<F>>(
+    xs: &[MpcField<F, S>],
+    ys: &[MpcField<F, S>],
+) -> bool {
+    if xs.len() != ys.len() {
+        return false;
+    }
+    if xs.is_empty() {
+        return true;
+    }
+    let g = joint_challenge::<F>();
+    let shift = |v: Vec<S>| -> Vec<S> {
+        v.into_iter()
+            .map(|mut s| {
+                s.shift(&g);
+                s
+            })
+            .collect()
+    };
+    let src = &mut GlobalTripleSource::default();
+    let ratios = S::batch_div(shift(to_shares(xs)), shift(to_shares(ys)), src);
+    let ratio = S::partial_products(ratios, src).pop().unwrap().reveal();
+    debug!("Permutation argument ratio: {}", ratio);
+    ratio.is_one()
+}
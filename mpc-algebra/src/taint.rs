@@ -0,0 +1,164 @@
+//! Witness taint tracking: a safety net against provers that leak their witness.
+//!
+//! A shared value may depend on the witness, so being shared is the taint. Within a [sensitive]
+//! region (e.g., a prover), making a shared value public is a leak, unless it happens in an
+//! [approved] sink (e.g., committing to a polynomial, or evaluating a committed one). Leaks are
+//! handled according to the [TaintMode], which starts as the `MPC_TAINT` environment variable
+//! (`off`, `log`, or `panic`), or `log` if that is unset.
+//!
+//! Regions are per thread.
+use log::warn;
+use once_cell::sync::Lazy;
+use std::cell::Cell;
+use std::panic::Location;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::thread::LocalKey;
+
+/// What to do when a shared value is made public outside an approved sink.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum TaintMode {
+    /// Nothing
+    Off,
+    /// Log a warning
+    Log,
+    /// Panic
+    Panic,
+}
+
+static MODE: Lazy<AtomicU8> = Lazy::new(|| {
+    let mode = match std::env::var("MPC_TAINT").as_deref() {
+        Ok("off") => TaintMode::Off,
+        Ok("panic") => TaintMode::Panic,
+        _ => TaintMode::Log,
+    };
+    AtomicU8::new(mode as u8)
+});
+
+thread_local! {
+    /// How many sensitive regions we are in
+    static SENSITIVE: Cell<usize> = const { Cell::new(0) };
+    /// How many approved sinks we are in
+    static APPROVED: Cell<usize> = const { Cell::new(0) };
+}
+
+pub fn set_taint_mode(mode: TaintMode) {
+    MODE.store(mode as u8, Ordering::Relaxed);
+}
+
+pub fn taint_mode() -> TaintMode {
+    match MODE.load(Ordering::Relaxed) {
+        0 => TaintMode::Off,
+        1 => TaintMode::Log,
+        _ => TaintMode::Panic,
+    }
+}
+
+/// Leaves a region when dropped, even by a panic.
+struct Exit(&'static LocalKey<Cell<usize>>);
+
+impl Drop for Exit {
+    fn drop(&mut self) {
+        self.0.with(|d| d.set(d.get() - 1));
+    }
+}
+
+fn enter<T>(region: &'static LocalKey<Cell<usize>>, f: impl FnOnce() -> T) -> T {
+    region.with(|d| d.set(d.get() + 1));
+    let _exit = Exit(region);
+    f()
+}
+
+/// Run `f`, checking that it makes no shared value public outside an [approved] sink.
+pub fn sensitive<T>(f: impl FnOnce() -> T) -> T {
+    enter(&SENSITIVE, f)
+}
+
+/// Run `f`, in which making shared values public is fine (e.g., committing, or opening a value
+/// that is masked).
+pub fn approved<T>(f: impl FnOnce() -> T) -> T {
+    enter(&APPROVED, f)
+}
+
+/// Record that a shared `what` is being made public.
+#[track_caller]
+pub fn sink(what: &'static str) {
+    let leak = SENSITIVE.with(|d| d.get() > 0) && APPROVED.with(|d| d.get() == 0);
+    if !leak {
+        return;
+    }
+    match taint_mode() {
+        TaintMode::Off => {}
+        TaintMode::Log => warn!(
+            "Shared {} made public outside an approved sink, at {}",
+            what,
+            Location::caller()
+        ),
+        TaintMode::Panic => panic!(
+            "Shared {} made public outside an approved sink, at {}",
+            what,
+            Location::caller()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::honest_but_curious::MpcField;
+    use crate::Reveal;
+    use ark_bls12_377::Fr;
+    use mpc_net::MpcMultiNet as Net;
+    use mpc_trait::MpcWire;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    type MF = MpcField<Fr>;
+
+    /// Whether `f` is flagged as a leak: under [TaintMode::Panic], whether it panics.
+    fn flagged<T>(f: impl FnOnce() -> T) -> bool {
+        set_taint_mode(TaintMode::Panic);
+        catch_unwind(AssertUnwindSafe(f)).is_err()
+    }
+
+    #[test]
+    fn sinks_outside_sensitive_regions_pass() {
+        assert!(!flagged(|| sink("value")));
+        assert!(!flagged(|| sensitive(|| approved(|| sink("value")))));
+        // An approved sink covers the sensitive regions within it
+        assert!(!flagged(|| approved(|| sensitive(|| sink("value")))));
+        assert!(flagged(|| sensitive(|| sink("value"))));
+        // Leaving the region by a panic leaves it
+        assert!(!flagged(|| sink("value")));
+    }
+
+    #[test]
+    fn shared_values_made_public_are_flagged() {
+        let results = Net::simulate(2, |id| {
+            let x = MF::from_add_shared(Fr::from(id as u64 + 1));
+            // Flagged before anything is sent, so neither party waits on the other
+            let revealed = flagged(|| sensitive(|| x.reveal()));
+            let published = flagged(|| {
+                sensitive(|| {
+                    let mut y = x;
+                    y.publicize();
+                })
+            });
+            (revealed, published, SENSITIVE.with(Cell::get))
+        });
+        assert_eq!(results, vec![(true, true, 0); 2]);
+    }
+
+    #[test]
+    fn clean_values_pass() {
+        set_taint_mode(TaintMode::Panic);
+        let results = Net::simulate(2, |id| {
+            let x = MF::from_add_shared(Fr::from(id as u64 + 1));
+            sensitive(|| {
+                let public = MF::from_public(Fr::from(7u64)).reveal();
+                let approved = approved(|| x.reveal());
+                (public, approved)
+            })
+        });
+        assert_eq!(results, vec![(Fr::from(7u64), Fr::from(3u64)); 2]);
+    }
+}
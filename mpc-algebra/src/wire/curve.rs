@@ -0,0 +1,263 @@
+//! MPC wrappers for a single curve, outside of any pairing engine.
+//!
+//! These mirror the G1/G2 wrappers in [super::pairing], but for curves that have no pairing (e.g.,
+//! the pasta curves), so that pairing-free schemes (e.g., inner-product arguments) can run over
+//! shared data.
+use ark_ec::group::Group;
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::bytes::{FromBytes, ToBytes};
+use ark_ff::prelude::*;
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalDeserializeWithFlags, CanonicalSerialize,
+    CanonicalSerializeWithFlags, Flags, SerializationError,
+};
+use ark_std::io::{self, Read, Write};
+use ark_std::{end_timer, start_timer};
+use core::ops::*;
+use derivative::Derivative;
+use rand::Rng;
+use std::default::Default;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::hash::Hash;
+use std::iter::Sum;
+use zeroize::Zeroize;
+
+use mpc_trait::MpcWire;
+
+use super::super::share::group::GroupShare;
+use super::super::share::pairing::{AffProjShare, AffineGroup, CurveShare};
+use super::field::MpcField;
+use super::group::MpcGroup;
+use crate::Reveal;
+
+#[derive(Debug, Derivative)]
+#[derivative(
+    Clone(bound = ""),
+    Copy(bound = ""),
+    PartialEq(bound = "C: PartialEq"),
+    Eq(bound = "C: Eq"),
+    Hash(bound = "C: Hash")
+)]
+pub struct MpcCurveAffine<C: AffineGroup, S: CurveShare<C>> {
+    pub val: MpcGroup<C, S::AffineShare>,
+}
+
+#[derive(Debug, Derivative)]
+#[derivative(
+    Clone(bound = ""),
+    Copy(bound = ""),
+    PartialEq(bound = "C: PartialEq"),
+    Eq(bound = "C: Eq"),
+    Hash(bound = "C: Hash")
+)]
+pub struct MpcCurveProjective<C: AffineGroup, S: CurveShare<C>> {
+    pub val: MpcGroup<C::Projective, S::ProjectiveShare>,
+}
+
+type Scalar<C, S> =
+    MpcField<<C as AffineCurve>::ScalarField, <S as AffProjShare<
+        <C as AffineCurve>::ScalarField,
+        C,
+        <C as AffineCurve>::Projective,
+    >>::FrShare>;
+
+macro_rules! impl_curve_wrapper {
+    ($wrap:ident, $base:ty, $share:ident) => {
+        impl<C: AffineGroup, S: CurveShare<C>> $wrap<C, S> {
+            #[inline]
+            pub fn new(t: $base, shared: bool) -> Self {
+                Self {
+                    val: MpcGroup::new(t, shared),
+                }
+            }
+            #[inline]
+            pub fn from_public(t: $base) -> Self {
+                Self {
+                    val: MpcGroup::from_public(t),
+                }
+            }
+        }
+        impl<C: AffineGroup, S: CurveShare<C>> Reveal for $wrap<C, S> {
+            type Base = $base;
+            #[inline]
+            fn reveal(self) -> Self::Base {
+                self.val.reveal()
+            }
+            #[inline]
+            fn from_public(t: Self::Base) -> Self {
+                Self {
+                    val: MpcGroup::from_public(t),
+                }
+            }
+            #[inline]
+            fn from_add_shared(t: Self::Base) -> Self {
+                Self {
+                    val: MpcGroup::from_add_shared(t),
+                }
+            }
+            #[inline]
+            fn unwrap_as_public(self) -> Self::Base {
+                self.val.unwrap_as_public()
+            }
+            #[inline]
+            fn king_share<R: Rng>(f: Self::Base, rng: &mut R) -> Self {
+                Self {
+                    val: MpcGroup::king_share(f, rng),
+                }
+            }
+            #[inline]
+            fn king_share_batch<R: Rng>(f: Vec<Self::Base>, rng: &mut R) -> Vec<Self> {
+                MpcGroup::king_share_batch(f, rng)
+                    .into_iter()
+                    .map(|val| Self { val })
+                    .collect()
+            }
+        }
+        impl_pairing_mpc_wrapper!(MpcGroup, AffineGroup, CurveShare, $share, $share, $wrap);
+        impl<C: AffineGroup, S: CurveShare<C>> Mul<Scalar<C, S>> for $wrap<C, S> {
+            type Output = Self;
+            #[inline]
+            fn mul(self, other: Scalar<C, S>) -> Self::Output {
+                Self {
+                    val: self.val.mul(other),
+                }
+            }
+        }
+        impl<'a, C: AffineGroup, S: CurveShare<C>> Mul<&'a Scalar<C, S>> for $wrap<C, S> {
+            type Output = Self;
+            #[inline]
+            fn mul(self, other: &'a Scalar<C, S>) -> Self::Output {
+                Self {
+                    val: self.val.mul(other),
+                }
+            }
+        }
+        impl<C: AffineGroup, S: CurveShare<C>> MulAssign<Scalar<C, S>> for $wrap<C, S> {
+            #[inline]
+            fn mul_assign(&mut self, other: Scalar<C, S>) {
+                self.val.mul_assign(other);
+            }
+        }
+        impl<'a, C: AffineGroup, S: CurveShare<C>> MulAssign<&'a Scalar<C, S>> for $wrap<C, S> {
+            #[inline]
+            fn mul_assign(&mut self, other: &'a Scalar<C, S>) {
+                self.val.mul_assign(other);
+            }
+        }
+    };
+}
+
+impl_curve_wrapper!(MpcCurveAffine, C, AffineShare);
+impl_curve_wrapper!(MpcCurveProjective, C::Projective, ProjectiveShare);
+
+impl<C: AffineGroup, S: CurveShare<C>> Group for MpcCurveAffine<C, S> {
+    type ScalarField = Scalar<C, S>;
+}
+
+impl<C: AffineGroup, S: CurveShare<C>> From<MpcCurveProjective<C, S>> for MpcCurveAffine<C, S> {
+    #[inline]
+    fn from(o: MpcCurveProjective<C, S>) -> Self {
+        Self {
+            val: o.val.map(|s| s.into(), S::sh_proj_to_aff),
+        }
+    }
+}
+
+impl<C: AffineGroup, S: CurveShare<C>> From<MpcCurveAffine<C, S>> for MpcCurveProjective<C, S> {
+    #[inline]
+    fn from(o: MpcCurveAffine<C, S>) -> Self {
+        Self {
+            val: o.val.map(|s| s.into(), S::sh_aff_to_proj),
+        }
+    }
+}
+
+impl<C: AffineGroup, S: CurveShare<C>> AffineCurve for MpcCurveAffine<C, S> {
+    type ScalarField = Scalar<C, S>;
+    const COFACTOR: &'static [u64] = C::COFACTOR;
+    // Coordinates are never exposed, so they needn't be shared.
+    type BaseField = C::BaseField;
+    type Projective = MpcCurveProjective<C, S>;
+    #[inline]
+    fn prime_subgroup_generator() -> Self {
+        Self::from_public(C::prime_subgroup_generator())
+    }
+    fn from_random_bytes(bytes: &[u8]) -> Option<Self> {
+        C::from_random_bytes(bytes).map(Self::from_public)
+    }
+    #[inline]
+    fn mul<T: Into<<Self::ScalarField as PrimeField>::BigInt>>(
+        &self,
+        _s: T,
+    ) -> <Self as AffineCurve>::Projective {
+        unimplemented!("mul by bigint")
+    }
+    fn mul_by_cofactor_to_projective(&self) -> <Self as AffineCurve>::Projective {
+        Self::Projective {
+            val: match self.val {
+                MpcGroup::Public(g) => MpcGroup::Public(g.mul_by_cofactor_to_projective()),
+                MpcGroup::Shared(_) => unimplemented!("mul_by_cofactor on a shared point"),
+            },
+        }
+    }
+    fn mul_by_cofactor_inv(&self) -> Self {
+        unimplemented!("AffineCurve::mul_by_cofactor_inv")
+    }
+    fn multi_scalar_mul(bases: &[Self], scalars: &[Self::ScalarField]) -> Self::Projective {
+        let t = start_timer!(|| "MSM inner");
+        let bases = MpcGroup::all_public_or_shared(bases.iter().map(|b| b.val))
+            .expect("MSM bases must be public");
+        let val = if scalars.iter().any(|s| s.is_shared()) {
+            // Some coefficients (e.g., padding) may be public; share those too.
+            let priv_scalars: Vec<_> = scalars.iter().map(|s| s.into_share()).collect();
+            MpcGroup::Shared(S::sh_aff_to_proj(S::AffineShare::multi_scale_pub_group(
+                &bases,
+                &priv_scalars,
+            )))
+        } else {
+            let pub_scalars: Vec<_> = scalars.iter().map(|s| s.unwrap_as_public()).collect();
+            MpcGroup::Public(C::multi_scalar_mul(&bases, &pub_scalars))
+        };
+        end_timer!(t);
+        MpcCurveProjective { val }
+    }
+    fn scalar_mul<T: Into<Self::ScalarField>>(&self, other: T) -> Self::Projective {
+        (*self * other.into()).into()
+    }
+}
+
+impl<C: AffineGroup, S: CurveShare<C>> ProjectiveCurve for MpcCurveProjective<C, S> {
+    type ScalarField = Scalar<C, S>;
+    const COFACTOR: &'static [u64] = C::COFACTOR;
+    type BaseField = C::BaseField;
+    type Affine = MpcCurveAffine<C, S>;
+    #[inline]
+    fn prime_subgroup_generator() -> Self {
+        Self::from_public(C::Projective::prime_subgroup_generator())
+    }
+    fn batch_normalization(_elems: &mut [Self]) {}
+    fn is_normalized(&self) -> bool {
+        unimplemented!("ProjectiveCurve::is_normalized")
+    }
+    fn double_in_place(&mut self) -> &mut Self {
+        self.val.double_in_place();
+        self
+    }
+    fn add_assign_mixed(&mut self, o: &<Self as ProjectiveCurve>::Affine) {
+        self.val = match (&self.val, &o.val) {
+            (MpcGroup::Shared(a), MpcGroup::Shared(b)) => {
+                MpcGroup::Shared(S::add_sh_proj_sh_aff(*a, b))
+            }
+            (MpcGroup::Shared(a), MpcGroup::Public(b)) => {
+                MpcGroup::Shared(S::add_sh_proj_pub_aff(*a, b))
+            }
+            (MpcGroup::Public(a), MpcGroup::Shared(b)) => {
+                MpcGroup::Shared(S::add_pub_proj_sh_aff(a, *b))
+            }
+            (MpcGroup::Public(a), MpcGroup::Public(b)) => MpcGroup::Public(a.add_mixed(b)),
+        };
+    }
+    fn mul<T: AsRef<[u64]>>(self, _scalar_words: T) -> Self {
+        unimplemented!("mul by words")
+    }
+}
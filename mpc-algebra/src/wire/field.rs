@@ -6,6 +6,9 @@ use zeroize::Zeroize;
 use ark_ff::bytes::{FromBytes, ToBytes};
 use ark_ff::prelude::*;
 use ark_ff::{poly_stub, FftField};
+use ark_poly::domain::{
+    EvaluationDomain, GeneralEvaluationDomain, MixedRadixEvaluationDomain, Radix2EvaluationDomain,
+};
 use ark_serialize::{
     CanonicalDeserialize, CanonicalDeserializeWithFlags, CanonicalSerialize,
     CanonicalSerializeWithFlags, Flags, SerializationError,
@@ -18,8 +21,11 @@ use std::iter::{Product, Sum};
 use std::marker::PhantomData;
 use std::ops::*;
 
+use super::super::share::add::AdditiveFieldShare;
 use super::super::share::field::FieldShare;
+use super::super::share::shamir::ShamirFieldShare;
 use super::super::share::BeaverSource;
+use crate::preprocessing::GlobalTripleSource;
 use crate::Reveal;
 use mpc_net::{MpcNet, MpcMultiNet as Net};
 
@@ -74,6 +80,11 @@ impl<T: Field, S: FieldShare<T>> BeaverSource<S, S, S> for DummyFieldTripleSourc
             }),
         )
     }
+    #[inline]
+    fn eda_bit(&mut self, n_bits: usize) -> (S, Vec<S>) {
+        let zero = S::from_add_shared(T::zero());
+        (zero, vec![zero; n_bits])
+    }
 }
 
 impl<T: Field, S: FieldShare<T>> MpcField<T, S> {
@@ -82,10 +93,22 @@ impl<T: Field, S: FieldShare<T>> MpcField<T, S> {
         match self {
             Self::Public(x) => x.inverse().map(MpcField::Public),
             Self::Shared(x) => Some(MpcField::Shared(
-                x.inv(&mut DummyFieldTripleSource::default()),
+                x.inv(&mut GlobalTripleSource::default()),
             )),
         }
     }
+    /// Our share of this value, sharing it first if it is public.
+    #[inline]
+    pub fn into_share(self) -> S {
+        match self {
+            Self::Public(x) => S::from_public(x),
+            Self::Shared(x) => x,
+        }
+    }
+    /// Our shares of these values: see [MpcField::into_share].
+    pub(crate) fn shares(xs: &[Self]) -> Vec<S> {
+        xs.iter().map(|x| x.into_share()).collect()
+    }
     pub fn all_public_or_shared(v: impl IntoIterator<Item = Self>) -> Result<Vec<T>, Vec<S>> {
         let mut out_a = Vec::new();
         let mut out_b = Vec::new();
@@ -104,6 +127,42 @@ impl<T: Field, S: FieldShare<T>> MpcField<T, S> {
         }
     }
 }
+impl<F: Field> MpcField<F, AdditiveFieldShare<F>> {
+    /// This value under Shamir sharing; public values stay public.
+    pub fn to_shamir(self) -> MpcField<F, ShamirFieldShare<F>> {
+        Self::batch_to_shamir(vec![self]).pop().unwrap()
+    }
+    /// Many values under Shamir sharing, in one round: see [AdditiveFieldShare::batch_to_shamir].
+    pub fn batch_to_shamir(xs: Vec<Self>) -> Vec<MpcField<F, ShamirFieldShare<F>>> {
+        let shared: Vec<AdditiveFieldShare<F>> = xs
+            .iter()
+            .filter_map(|x| match x {
+                MpcField::Shared(s) => Some(*s),
+                MpcField::Public(_) => None,
+            })
+            .collect();
+        let mut shared = AdditiveFieldShare::batch_to_shamir(shared).into_iter();
+        xs.into_iter()
+            .map(|x| match x {
+                MpcField::Public(x) => MpcField::Public(x),
+                MpcField::Shared(_) => MpcField::Shared(shared.next().unwrap()),
+            })
+            .collect()
+    }
+}
+impl<F: Field> MpcField<F, ShamirFieldShare<F>> {
+    /// This value under additive sharing, without communication; public values stay public.
+    pub fn to_additive(self) -> MpcField<F, AdditiveFieldShare<F>> {
+        match self {
+            MpcField::Public(x) => MpcField::Public(x),
+            MpcField::Shared(s) => MpcField::Shared(s.to_additive()),
+        }
+    }
+    /// Many values under additive sharing: see [MpcField::to_additive].
+    pub fn batch_to_additive(xs: Vec<Self>) -> Vec<MpcField<F, AdditiveFieldShare<F>>> {
+        xs.into_iter().map(Self::to_additive).collect()
+    }
+}
 impl<'a, T: Field, S: FieldShare<T>> MulAssign<&'a MpcField<T, S>> for MpcField<T, S> {
     #[inline]
     fn mul_assign(&mut self, other: &Self) {
@@ -125,7 +184,7 @@ impl<'a, T: Field, S: FieldShare<T>> MulAssign<&'a MpcField<T, S>> for MpcField<
                     x.scale(y);
                 }
                 MpcField::Shared(y) => {
-                    let t = x.mul(*y, &mut DummyFieldTripleSource::default());
+                    let t = x.mul(*y, &mut GlobalTripleSource::default());
                     *self = MpcField::Shared(t);
                 }
             },
@@ -162,7 +221,7 @@ impl<'a, T: Field, S: FieldShare<T>> DivAssign<&'a MpcField<T, S>> for MpcField<
                     *x /= y;
                 }
                 MpcField::Shared(y) => {
-                    let mut t = y.inv(&mut DummyFieldTripleSource::default());
+                    let mut t = y.inv(&mut GlobalTripleSource::default());
                     t.scale(&x);
                     *self = MpcField::Shared(t);
                 }
@@ -172,7 +231,7 @@ impl<'a, T: Field, S: FieldShare<T>> DivAssign<&'a MpcField<T, S>> for MpcField<
                     x.scale(&y.inverse().unwrap());
                 }
                 MpcField::Shared(y) => {
-                    let src = &mut DummyFieldTripleSource::default();
+                    let src = &mut GlobalTripleSource::default();
                     *x = x.div(*y, src);
                 }
             },
@@ -219,10 +278,16 @@ impl_ref_ops!(
 
 impl<T: Field, S: FieldShare<T>> MpcWire for MpcField<T, S> {
     #[inline]
+    #[track_caller]
     fn publicize(&mut self) {
         match self {
             MpcField::Shared(s) => {
-                *self = MpcField::Public(s.open());
+                crate::taint::sink("field element");
+                match crate::defer::reveal(*s, S::batch_reveal) {
+                    Some(x) => *self = MpcField::Public(x),
+                    // Queued (see `defer`)
+                    None => return,
+                }
             }
             _ => {}
         }
@@ -248,9 +313,13 @@ impl<T: Field, S: FieldShare<T>> MpcWire for MpcField<T, S> {
 impl<T: Field, S: FieldShare<T>> Reveal for MpcField<T, S> {
     type Base = T;
     #[inline]
+    #[track_caller]
     fn reveal(self) -> Self::Base {
         let result = match self {
-            Self::Shared(s) => s.reveal(),
+            Self::Shared(s) => {
+                crate::taint::sink("field element");
+                s.reveal()
+            }
             Self::Public(s) => s,
         };
         super::macros::check_eq(result.clone());
@@ -381,7 +450,7 @@ impl<F: PrimeField, S: FieldShare<F>> Field for MpcField<F, S> {
                     Self::Public(_) => unreachable!(),
                 })
                 .collect();
-            let nshares = S::batch_mul(sshares, oshares, &mut DummyFieldTripleSource::default());
+            let nshares = S::batch_mul(sshares, oshares, &mut GlobalTripleSource::default());
             for (self_, new) in selfs.iter_mut().zip(nshares.into_iter()) {
                 *self_ = Self::Shared(new);
             }
@@ -391,39 +460,34 @@ impl<F: PrimeField, S: FieldShare<F>> Field for MpcField<F, S> {
             }
         }
     }
+    /// Divides in one batch: shared by shared takes one multiplication and one opening (see
+    /// [FieldShare::batch_div]), a public by a shared value one inversion (see
+    /// [FieldShare::batch_inv]), and a shared by a public one is local.
     fn batch_division_in_place(selfs: &mut [Self], others: &[Self]) {
-        let selfs_shared = selfs[0].is_shared();
-        let others_shared = others[0].is_shared();
-        assert!(
-            selfs.iter().all(|s| s.is_shared() == selfs_shared),
-            "Selfs heterogenously shared!"
-        );
-        assert!(
-            others.iter().all(|s| s.is_shared() == others_shared),
-            "others heterogenously shared!"
-        );
-        if selfs_shared && others_shared {
-            let sshares = selfs
-                .iter()
-                .map(|s| match s {
-                    Self::Shared(s) => s.clone(),
-                    Self::Public(_) => unreachable!(),
-                })
-                .collect();
-            let oshares = others
-                .iter()
-                .map(|s| match s {
-                    Self::Shared(s) => s.clone(),
-                    Self::Public(_) => unreachable!(),
-                })
-                .collect();
-            let nshares = S::batch_div(sshares, oshares, &mut DummyFieldTripleSource::default());
-            for (self_, new) in selfs.iter_mut().zip(nshares.into_iter()) {
+        assert_eq!(selfs.len(), others.len());
+        if others.iter().any(|o| o.is_shared()) {
+            let src = &mut GlobalTripleSource::default();
+            let oshares = Self::shares(others);
+            let nshares = if selfs.iter().any(|s| s.is_shared()) {
+                S::batch_div(Self::shares(selfs), oshares, src)
+            } else {
+                S::batch_inv(oshares, src)
+                    .into_iter()
+                    .zip(selfs.iter())
+                    .map(|(mut i, s)| {
+                        i.scale(&s.unwrap_as_public());
+                        i
+                    })
+                    .collect()
+            };
+            for (self_, new) in selfs.iter_mut().zip(nshares) {
                 *self_ = Self::Shared(new);
             }
         } else {
-            for (a, b) in ark_std::cfg_iter_mut!(selfs).zip(others.iter()) {
-                *a *= b;
+            let mut inverses: Vec<F> = others.iter().map(|o| o.unwrap_as_public()).collect();
+            ark_ff::batch_inversion(&mut inverses);
+            for (a, b) in ark_std::cfg_iter_mut!(selfs).zip(inverses) {
+                *a *= Self::Public(b);
             }
         }
     }
@@ -442,7 +506,7 @@ impl<F: PrimeField, S: FieldShare<F>> Field for MpcField<F, S> {
                 })
                 .collect();
             for (self_, new) in selfs.iter_mut().zip(
-                S::partial_products(sshares, &mut DummyFieldTripleSource::default()).into_iter(),
+                S::partial_products(sshares, &mut GlobalTripleSource::default()).into_iter(),
             ) {
                 *self_ = Self::Shared(new);
             }
@@ -495,6 +559,52 @@ impl<F: PrimeField, S: FieldShare<F>> Field for MpcField<F, S> {
             )
         })
     }
+    /// Runs the FFT on each party's shares, locally, rather than element by element.
+    fn batch_fft_in_place(xs: &mut Vec<Self>, group_gen: Self, inverse: bool) -> bool {
+        // Public values take the generic FFT
+        if !xs.iter().any(|x| x.is_shared()) {
+            return false;
+        }
+        let domain = match group_gen {
+            Self::Public(g) => fft_domain(xs.len(), g),
+            Self::Shared(_) => None,
+        };
+        let domain = match domain {
+            Some(d) => d,
+            None => return false,
+        };
+        let shares = xs
+            .iter()
+            .map(|x| match x {
+                Self::Shared(s) => *s,
+                Self::Public(x) => S::from_public(*x),
+            })
+            .collect();
+        let mapped = S::batch_linear_map(shares, |v| {
+            if inverse {
+                domain.ifft_in_place(v)
+            } else {
+                domain.fft_in_place(v)
+            }
+        });
+        match mapped {
+            Some(mapped) => {
+                *xs = mapped.into_iter().map(Self::Shared).collect();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// The FFT domain over `F` of order `n` generated by `group_gen`, if there is one.
+fn fft_domain<F: FftField>(n: usize, group_gen: F) -> Option<GeneralEvaluationDomain<F>> {
+    let radix2 = Radix2EvaluationDomain::new(n).map(GeneralEvaluationDomain::Radix2);
+    let mixed = MixedRadixEvaluationDomain::new(n).map(GeneralEvaluationDomain::MixedRadix);
+    radix2
+        .into_iter()
+        .chain(mixed)
+        .find(|d| d.size() == n && d.element(1) == group_gen)
 }
 
 impl<F: PrimeField, S: FieldShare<F>> FftField for MpcField<F, S> {
@@ -584,3 +694,65 @@ mod poly_impl {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::share::add::AdditiveFieldShare;
+    use ark_bls12_377::Fr;
+    use ark_poly::domain::{EvaluationDomain, Radix2EvaluationDomain};
+    use ark_poly::evaluations::univariate::Evaluations;
+    use ark_poly::univariate::DensePolynomial;
+    use ark_poly::UVPolynomial;
+    use ark_std::test_rng;
+
+    type MF = MpcField<Fr, AdditiveFieldShare<Fr>>;
+
+    #[test]
+    fn batch_fft_matches_per_polynomial_fft() {
+        let n = 16;
+        let domain = Radix2EvaluationDomain::<Fr>::new(n).unwrap();
+        let mpc_domain = Radix2EvaluationDomain::<MF>::new(n).unwrap();
+        let mut rng = test_rng();
+        let polys: Vec<Vec<Fr>> = (0..3)
+            .map(|_| (0..n).map(|_| Fr::rand(&mut rng)).collect())
+            .collect();
+        let results = Net::simulate(2, |id| {
+            polys
+                .iter()
+                .map(|p| {
+                    // Party 0 holds the value, party 1 a zero share
+                    let shared: Vec<MF> = p
+                        .iter()
+                        .map(|x| MF::from_add_shared(if id == 0 { *x } else { Fr::zero() }))
+                        .collect();
+                    // The batch FFT, through the polynomial API
+                    let evals = DensePolynomial::from_coefficients_vec(shared.clone())
+                        .evaluate_over_domain(mpc_domain)
+                        .evals;
+                    let coeffs = Evaluations::from_vec_and_domain(shared.clone(), mpc_domain)
+                        .interpolate()
+                        .coeffs;
+                    // The generic FFT, element by element
+                    let generic_evals = mpc_domain.fft(&shared);
+                    let generic_coeffs = mpc_domain.ifft(&shared);
+                    assert!(evals.iter().chain(&coeffs).all(|x| x.is_shared()));
+                    (
+                        evals.reveal(),
+                        coeffs.reveal(),
+                        generic_evals.reveal(),
+                        generic_coeffs.reveal(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        });
+        for party in results {
+            for (p, (evals, coeffs, generic_evals, generic_coeffs)) in polys.iter().zip(party) {
+                assert_eq!(evals, domain.fft(p));
+                assert_eq!(coeffs, domain.ifft(p));
+                assert_eq!(evals, generic_evals);
+                assert_eq!(coeffs, generic_coeffs);
+            }
+        }
+    }
+}
@@ -76,10 +76,16 @@ impl_ref_ops!(Sub, SubAssign, sub, sub_assign, Group, GroupShare, MpcGroup);
 
 impl<T: Group, S: GroupShare<T>> MpcWire for MpcGroup<T, S> {
     #[inline]
+    #[track_caller]
     fn publicize(&mut self) {
         match self {
             MpcGroup::Shared(s) => {
-                *self = MpcGroup::Public(s.reveal());
+                crate::taint::sink("group element");
+                match crate::defer::reveal(*s, S::batch_reveal) {
+                    Some(x) => *self = MpcGroup::Public(x),
+                    // Queued (see `defer`)
+                    None => return,
+                }
             }
             _ => {}
         }
@@ -105,9 +111,13 @@ impl<T: Group, S: GroupShare<T>> MpcWire for MpcGroup<T, S> {
 impl<T: Group, S: GroupShare<T>> Reveal for MpcGroup<T, S> {
     type Base = T;
     #[inline]
+    #[track_caller]
     fn reveal(self) -> Self::Base {
         let result = match self {
-            Self::Shared(s) => s.reveal(),
+            Self::Shared(s) => {
+                crate::taint::sink("group element");
+                s.reveal()
+            }
             Self::Public(s) => s,
         };
         super::macros::check_eq(result.clone());
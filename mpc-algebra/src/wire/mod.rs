@@ -3,5 +3,8 @@ pub mod field;
 pub use field::*;
 pub mod group;
 pub use group::*;
+#[macro_use]
 pub mod pairing;
 pub use pairing::*;
+pub mod curve;
+pub use curve::*;
@@ -204,10 +204,8 @@ impl<E: PairingEngine, PS: PairingShare<E>> PairingEngine for MpcPairingEngine<E
             let source = &mut DummyPairingTripleSource::default();
             // x * y = z
             let (x, y, z) = source.triple();
-            // x + a
-            let xa = (a + x).reveal();
-            // y + b
-            let yb = (b + y).reveal();
+            // x + a and y + b, which are masked
+            let (xa, yb) = crate::taint::approved(|| ((a + x).reveal(), (b + y).reveal()));
             let xayb: MpcExtField<E::Fqk, PS::FqkShare> =
                 MpcExtField::wrap(MpcField::Public(E::pairing(xa, yb)));
             let xay: MpcExtField<E::Fqk, PS::FqkShare> = MpcExtField::wrap(MpcField::Shared(
@@ -670,6 +668,20 @@ macro_rules! impl_aff_proj {
         impl<E: PairingEngine, PS: PairingShare<E>> Group for $w_aff<E, PS> {
             type ScalarField = MpcField<E::Fr, PS::FrShare>;
         }
+        impl<E: PairingEngine, PS: PairingShare<E>> $w_aff<E, PS> {
+            /// Precompute a fixed-base table for MSMs with shared scalars over `bases` (or any
+            /// contiguous run of them), which must be public: see [crate::msm::precompute].
+            pub fn precompute_msm(bases: &[Self]) {
+                let bases: Vec<E::$aff> = bases
+                    .iter()
+                    .map(|b| match b.val {
+                        MpcGroup::Public(b) => b,
+                        MpcGroup::Shared(_) => panic!("Can't precompute shared bases"),
+                    })
+                    .collect();
+                crate::msm::precompute(&bases);
+            }
+        }
         impl<E: PairingEngine, PS: PairingShare<E>> From<$w_pro<E, PS>> for $w_aff<E, PS> {
             #[inline]
             fn from(o: $w_pro<E, PS>) -> Self {
@@ -807,6 +819,43 @@ macro_rules! impl_aff_proj {
                 // }
                 b
             }
+            /// The MSMs with shared scalars go to the shares' batch, so each party runs them in one
+            /// pass; any others run one by one, as [AffineCurve::multi_scalar_mul] runs them.
+            fn batch_multi_scalar_mul(
+                msms: &[(&[Self], &[Self::ScalarField])],
+            ) -> Vec<Self::Projective> {
+                let mut shared = Vec::new();
+                let mut jobs = Vec::new();
+                for (k, (bases, scalars)) in msms.iter().enumerate() {
+                    if let Err(priv_scalars) =
+                        MpcField::all_public_or_shared(scalars.iter().cloned())
+                    {
+                        let bases = MpcGroup::all_public_or_shared(bases.iter().map(|i| i.val))
+                            .expect("MSM bases must be public");
+                        shared.push(k);
+                        jobs.push((bases, priv_scalars));
+                    }
+                }
+                let jobs: Vec<(&[E::$aff], &[PS::FrShare])> =
+                    jobs.iter().map(|(b, s)| (&b[..], &s[..])).collect();
+                let mut products =
+                    <PS::$share_aff as GroupShare<E::$aff>>::batch_multi_scale_pub_group(&jobs)
+                        .into_iter();
+                msms.iter()
+                    .enumerate()
+                    .map(|(k, (bases, scalars))| {
+                        if shared.contains(&k) {
+                            $w_pro {
+                                val: MpcGroup::Shared(PS::$g_name::sh_aff_to_proj(
+                                    products.next().unwrap(),
+                                )),
+                            }
+                        } else {
+                            Self::multi_scalar_mul(bases, scalars)
+                        }
+                    })
+                    .collect()
+            }
             fn scalar_mul<S: Into<Self::ScalarField>>(&self, other: S) -> Self::Projective {
                 (*self * other.into()).into()
             }
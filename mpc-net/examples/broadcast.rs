@@ -1,6 +1,5 @@
 use log::debug;
-use mpc_net::multi as net;
-use mpc_algebra::share::gs20;
+use mpc_net::{MpcMultiNet as Net, MpcNet};
 
 use std::path::PathBuf;
 use structopt::StructOpt;
@@ -14,6 +13,11 @@ struct Opt {
     /// Input file
     #[structopt(parse(from_os_str))]
     input: PathBuf,
+
+    /// Private key; if given, connect over TLS
+    #[cfg(feature = "tls")]
+    #[structopt(long, parse(from_os_str))]
+    tls_key: Option<PathBuf>,
 }
 
 fn main() {
@@ -21,11 +25,21 @@ fn main() {
     debug!("Start");
     let opt = Opt::from_args();
     println!("{:?}", opt);
-    multi::init_from_path(opt.input.to_str().unwrap(), opt.id);
-    let all = multi::broadcast(&[opt.id as u8]);
+    #[cfg(feature = "tls")]
+    match &opt.tls_key {
+        Some(key) => Net::init_from_file_with_tls(
+            opt.input.to_str().unwrap(),
+            opt.id,
+            key.to_str().unwrap(),
+        ),
+        None => Net::init_from_file(opt.input.to_str().unwrap(), opt.id),
+    }
+    #[cfg(not(feature = "tls"))]
+    Net::init_from_file(opt.input.to_str().unwrap(), opt.id);
+    let all = Net::broadcast_bytes(&[opt.id as u8]);
     println!("{:?}", all);
-    let r = multi::send_to_king(&[opt.id as u8]);
-    let all = multi::recv_from_king(r);
+    let r = Net::send_bytes_to_king(&[opt.id as u8]);
+    let all = Net::recv_bytes_from_king(r.map(|v| v.into_iter().rev().collect()));
     println!("{:?}", all);
-    multi::uninit();
+    Net::deinit();
 }
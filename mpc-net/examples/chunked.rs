@@ -0,0 +1,46 @@
+use mpc_net::{MpcMultiNet as Net, MpcNet};
+
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "chunked",
+    about = "Send a large message to and from the king, in chunks."
+)]
+struct Opt {
+    /// Id
+    id: usize,
+
+    /// Input file
+    #[structopt(parse(from_os_str))]
+    input: PathBuf,
+
+    /// Message size, in MB
+    #[structopt(long, default_value = "20")]
+    mb: usize,
+}
+
+fn main() {
+    env_logger::builder()
+        .format_timestamp(None)
+        .format_module_path(false)
+        .init();
+    let opt = Opt::from_args();
+    println!("{:?}", opt);
+    Net::init_from_file(opt.input.to_str().unwrap(), opt.id);
+    let bytes: Vec<u8> = (0..opt.mb << 20).map(|i| (i + opt.id) as u8).collect();
+    // The king sends each party the next party's message.
+    let next = Net::king_compute(
+        &bytes,
+        |mut all| {
+            all.rotate_left(1);
+            all
+        },
+        |p| println!("{} of {} bytes", p.bytes_done, p.bytes),
+    );
+    let from = (opt.id + 1) % Net::n_parties();
+    assert!(next.iter().enumerate().all(|(i, b)| *b == (i + from) as u8));
+    println!("Got party {}'s message", from);
+    Net::deinit();
+}
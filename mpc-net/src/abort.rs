@@ -0,0 +1,118 @@
+//! Why a party gave up on a collaborative computation, as a stable code.
+//!
+//! Like [MpcNetError], an [Abort] unwinds (see [Abort::raise]), since the arithmetic that detects
+//! most failures is infallible; [catch_abort] turns either back into an error. Binaries exit with
+//! [AbortCode::exit_code], so that whatever runs them can branch on the cause.
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::MpcNetError;
+
+/// The cause of an [Abort]. Codes, their names, and their exit codes are stable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AbortCode {
+    /// A party crashed, went silent for longer than the timeout, or was reported failed.
+    PeerTimeout,
+    /// Opened values failed a MAC check: a party deviated from the protocol, or corrupted its
+    /// shares.
+    MacCheckFailed,
+    /// The witness does not satisfy the circuit.
+    ConstraintUnsatisfied,
+    /// The keys were made for a different circuit or reference string.
+    SrsMismatch,
+    /// The parties' transcripts differ, so their challenges do too.
+    TranscriptDivergence,
+    /// A peer speaks another protocol version, or expects another number of parties, field or
+    /// curve: caught when connecting.
+    ConfigMismatch,
+}
+
+impl AbortCode {
+    pub const ALL: [AbortCode; 6] = [
+        AbortCode::PeerTimeout,
+        AbortCode::MacCheckFailed,
+        AbortCode::ConstraintUnsatisfied,
+        AbortCode::SrsMismatch,
+        AbortCode::TranscriptDivergence,
+        AbortCode::ConfigMismatch,
+    ];
+    /// The code's name, in kebab case.
+    pub fn name(self) -> &'static str {
+        match self {
+            AbortCode::PeerTimeout => "peer-timeout",
+            AbortCode::MacCheckFailed => "mac-check-failed",
+            AbortCode::ConstraintUnsatisfied => "constraint-unsatisfied",
+            AbortCode::SrsMismatch => "srs-mismatch",
+            AbortCode::TranscriptDivergence => "transcript-divergence",
+            AbortCode::ConfigMismatch => "config-mismatch",
+        }
+    }
+    /// The process exit code for the cause. 1 is left for other failures, and 101 for panics.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            AbortCode::PeerTimeout => 2,
+            AbortCode::MacCheckFailed => 3,
+            AbortCode::ConstraintUnsatisfied => 4,
+            AbortCode::SrsMismatch => 5,
+            AbortCode::TranscriptDivergence => 6,
+            AbortCode::ConfigMismatch => 7,
+        }
+    }
+}
+
+impl fmt::Display for AbortCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A party giving up, with its cause.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Abort {
+    pub code: AbortCode,
+    pub detail: String,
+}
+
+impl Abort {
+    pub fn new(code: AbortCode, detail: impl Into<String>) -> Self {
+        Self {
+            code,
+            detail: detail.into(),
+        }
+    }
+    /// Unwind, with this abort as the panic payload.
+    pub fn raise(self) -> ! {
+        panic::panic_any(self)
+    }
+}
+
+impl fmt::Display for Abort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code, self.detail)
+    }
+}
+
+impl std::error::Error for Abort {}
+
+impl From<MpcNetError> for Abort {
+    fn from(e: MpcNetError) -> Self {
+        Abort::new(AbortCode::PeerTimeout, e.to_string())
+    }
+}
+
+/// Run `f`, returning the [Abort] (or [MpcNetError], as a [AbortCode::PeerTimeout]) that it
+/// raised, if any.
+///
+/// Other panics are resumed. After an abort, the network should be deinitialized.
+pub fn catch_abort<T>(f: impl FnOnce() -> T) -> Result<T, Abort> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        let payload = match payload.downcast::<Abort>() {
+            Ok(a) => return *a,
+            Err(payload) => payload,
+        };
+        match payload.downcast::<MpcNetError>() {
+            Ok(e) => Abort::from(*e),
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    })
+}
@@ -0,0 +1,208 @@
+//! Chunked transfers of large messages to and from the king.
+//!
+//! [MpcNet::send_bytes_to_king] and [MpcNet::recv_bytes_from_king] send each message as one
+//! frame, which the receiver allocates whole before reading it, and which holds the connection
+//! until it's through. For messages of hundreds of MB (e.g., the share vectors of a resharing), a
+//! [Transfer] sends a chunk per round instead:
+//!
+//! * Receivers acknowledge each window of chunks, so a sender is never more than a window ahead
+//!   of the slowest receiver.
+//! * The caller hears of each acknowledged window, as [Progress].
+//! * A [Transfer] keeps what was acknowledged, so after a network failure (see
+//!   [crate::catch_failure]) and reconnection, running it again, with the same message, sends
+//!   only the rest.
+use std::convert::TryInto;
+use std::mem;
+use std::ops::Range;
+
+use crate::MpcNet;
+
+/// The chunk size of [Transfer::default]: a MB.
+pub const DEFAULT_CHUNK_SIZE: usize = 1 << 20;
+/// The number of chunks that [Transfer::default] sends per acknowledgement.
+pub const DEFAULT_WINDOW: usize = 8;
+
+/// How far a [Transfer] has got.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Progress {
+    /// Bytes of each message that every receiver has acknowledged
+    pub bytes_done: usize,
+    /// Bytes in each message
+    pub bytes: usize,
+}
+
+/// A chunked transfer to or from the king (see the [module docs](self)).
+#[derive(Clone, Debug)]
+pub struct Transfer {
+    chunk_size: usize,
+    window: usize,
+    /// Chunks that every receiver has acknowledged
+    acked: usize,
+    /// What we've received so far, by sender
+    received: Vec<Vec<u8>>,
+}
+
+impl Default for Transfer {
+    fn default() -> Self {
+        Self::new(DEFAULT_CHUNK_SIZE, DEFAULT_WINDOW)
+    }
+}
+
+fn encode(a: usize, b: usize) -> Vec<u8> {
+    let mut bytes = (a as u64).to_le_bytes().to_vec();
+    bytes.extend_from_slice(&(b as u64).to_le_bytes());
+    bytes
+}
+
+fn decode(bytes: &[u8]) -> (usize, usize) {
+    let a = u64::from_le_bytes(bytes[..8].try_into().unwrap());
+    let b = u64::from_le_bytes(bytes[8..16].try_into().unwrap());
+    (a as usize, b as usize)
+}
+
+impl Transfer {
+    /// A transfer in chunks of `chunk_size` bytes, acknowledged every `window` chunks.
+    ///
+    /// Every party must use the same chunk size and window.
+    pub fn new(chunk_size: usize, window: usize) -> Self {
+        assert!(chunk_size > 0 && window > 0);
+        Self {
+            chunk_size,
+            window,
+            acked: 0,
+            received: Vec::new(),
+        }
+    }
+
+    /// How far we've got with messages of `bytes` bytes.
+    fn progress(&self, bytes: usize) -> Progress {
+        Progress {
+            bytes_done: (self.acked * self.chunk_size).min(bytes),
+            bytes,
+        }
+    }
+
+    /// Agree with the other parties on where to (re)start: the chunks that everyone has
+    /// acknowledged. Produces the number of those, and the length of the messages: `len`, of
+    /// the king's.
+    ///
+    /// If `all_send`, every party's `len` must be the same.
+    fn start<N: MpcNet>(&mut self, len: usize, all_send: bool) -> (usize, usize) {
+        let king_answer = N::send_bytes_to_king(&encode(self.acked, len)).map(|from_all| {
+            let from_all: Vec<_> = from_all.iter().map(|b| decode(b)).collect();
+            if all_send {
+                assert!(
+                    from_all.iter().all(|(_, l)| *l == len),
+                    "Messages to the king differ in length"
+                );
+            }
+            let acked = from_all.iter().map(|(a, _)| *a).min().unwrap();
+            vec![encode(acked, len); from_all.len()]
+        });
+        let (acked, len) = decode(&N::recv_bytes_from_king(king_answer));
+        self.acked = acked;
+        (acked, len)
+    }
+
+    /// Keep what `senders` parties have sent us, up to what everyone has acknowledged.
+    fn keep(&mut self, senders: usize) {
+        let kept = self.acked * self.chunk_size;
+        self.received.resize(senders, Vec::new());
+        for r in &mut self.received {
+            r.truncate(kept);
+        }
+    }
+
+    /// The bytes of chunk `k` of a message of `len` bytes.
+    fn chunk(&self, k: usize, len: usize) -> Range<usize> {
+        k * self.chunk_size..((k + 1) * self.chunk_size).min(len)
+    }
+
+    /// Does the window end with chunk `k`, of `chunks`?
+    fn window_ends(&self, k: usize, chunks: usize) -> bool {
+        (k + 1).is_multiple_of(self.window) || k + 1 == chunks
+    }
+
+    /// Done: forget everything, so that this transfer can be reused.
+    fn finish(&mut self) -> Vec<Vec<u8>> {
+        self.acked = 0;
+        mem::take(&mut self.received)
+    }
+
+    /// Send `bytes` to the king, as [MpcNet::send_bytes_to_king] does: the king gets every
+    /// party's message, which must all be the same length.
+    ///
+    /// Calls `progress` after each acknowledged window.
+    pub fn send_to_king<N: MpcNet>(
+        &mut self,
+        bytes: &[u8],
+        mut progress: impl FnMut(Progress),
+    ) -> Option<Vec<Vec<u8>>> {
+        let (acked, len) = self.start::<N>(bytes.len(), true);
+        let chunks = len.div_ceil(self.chunk_size);
+        self.keep(if N::am_king() { N::n_parties() } else { 0 });
+        for k in acked..chunks {
+            if let Some(from_all) = N::send_bytes_to_king(&bytes[self.chunk(k, len)]) {
+                for (r, chunk) in self.received.iter_mut().zip(from_all) {
+                    r.extend_from_slice(&chunk);
+                }
+            }
+            if self.window_ends(k, chunks) {
+                let acks = if N::am_king() {
+                    Some(vec![Vec::new(); N::n_parties()])
+                } else {
+                    None
+                };
+                N::recv_bytes_from_king(acks);
+                self.acked = k + 1;
+                progress(self.progress(len));
+            }
+        }
+        let received = self.finish();
+        if N::am_king() {
+            Some(received)
+        } else {
+            None
+        }
+    }
+
+    /// Receive our message from the king, as [MpcNet::recv_bytes_from_king] does: the king
+    /// provides every party's message, which must all be the same length.
+    ///
+    /// Calls `progress` after each acknowledged window.
+    pub fn recv_from_king<N: MpcNet>(
+        &mut self,
+        bytes: Option<&[Vec<u8>]>,
+        mut progress: impl FnMut(Progress),
+    ) -> Vec<u8> {
+        assert_eq!(
+            bytes.is_some(),
+            N::am_king(),
+            "Exactly the king provides messages"
+        );
+        let len = match bytes {
+            Some(bytes) => {
+                assert_eq!(bytes.len(), N::n_parties(), "No message for some party");
+                let len = bytes[0].len();
+                assert!(bytes.iter().all(|b| b.len() == len));
+                len
+            }
+            None => 0,
+        };
+        let (acked, len) = self.start::<N>(len, false);
+        let chunks = len.div_ceil(self.chunk_size);
+        self.keep(1);
+        for k in acked..chunks {
+            let range = self.chunk(k, len);
+            let out = bytes.map(|b| b.iter().map(|b| b[range.clone()].to_vec()).collect());
+            let chunk = N::recv_bytes_from_king(out);
+            self.received[0].extend_from_slice(&chunk);
+            if self.window_ends(k, chunks) {
+                N::send_bytes_to_king(&[]);
+                self.acked = k + 1;
+                progress(self.progress(len));
+            }
+        }
+        self.finish().pop().unwrap()
+    }
+}
@@ -0,0 +1,330 @@
+//! The wire protocol of [MpcMultiNet](crate::MpcMultiNet): frames, and the hello that starts a
+//! connection.
+//!
+//! A frame is a tag byte, then, for every tag but [HEARTBEAT], the length of its body (a
+//! little-endian `u64`) and the body. Once its channels are open, each party sends every other a
+//! [Hello], and checks the one it gets back, so that a misconfigured peer (another protocol
+//! version, party count, field, curve or king rotation) is caught at connect time, before any shares are
+//! exchanged, rather than corrupting them mid-proof.
+//!
+//! If the parties resume broken connections (see
+//! [MpcMultiNet::set_resumable](crate::MpcMultiNet::set_resumable)), each hello carries a random
+//! session token. A party that reconnects opens with a [Resumption], which presents the token of
+//! the party it reconnects to, and says how many data frames it has received, so that the other
+//! can resend the rest.
+use std::convert::TryInto;
+use std::io::{self, Read, Write};
+
+use crate::KingRotation;
+
+/// A frame of data for the computation.
+pub const DATA: u8 = 0;
+/// A frame that only says that its sender is alive. It has no length or body.
+pub const HEARTBEAT: u8 = 1;
+/// A frame saying that a party failed: its body is the party's id, a little-endian `u64`.
+pub const ABORT: u8 = 2;
+/// A [Hello].
+pub const HELLO: u8 = 3;
+/// A [Resumption].
+pub const RESUME: u8 = 4;
+
+/// The version of the protocol that we speak. Parties that speak different versions can't talk.
+pub const PROTOCOL_VERSION: u32 = 3;
+/// The longest [Hello] that we read: its names should be short.
+const MAX_HELLO: usize = 4096;
+
+/// Write a frame: `tag`, then the length of `parts`, together, and the parts.
+pub fn write_frame(w: &mut impl Write, tag: u8, parts: &[&[u8]]) -> io::Result<()> {
+    let len: usize = parts.iter().map(|p| p.len()).sum();
+    w.write_all(&[tag])?;
+    w.write_all(&(len as u64).to_le_bytes())?;
+    parts.iter().try_for_each(|p| w.write_all(p))
+}
+
+/// Read the tag of the next frame, and, unless it's a heartbeat, the length of its body, which
+/// is left to read.
+pub fn read_header(r: &mut impl Read) -> io::Result<(u8, usize)> {
+    let mut tag = [0u8];
+    r.read_exact(&mut tag)?;
+    if tag[0] == HEARTBEAT {
+        return Ok((HEARTBEAT, 0));
+    }
+    let mut len = [0u8; 8];
+    r.read_exact(&mut len)?;
+    Ok((tag[0], u64::from_le_bytes(len) as usize))
+}
+
+/// What a party says about itself when it connects.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hello {
+    pub version: u32,
+    pub n_parties: usize,
+    pub party_id: usize,
+    /// The field that the party computes over, as named by
+    /// [MpcMultiNet::set_field_and_curve](crate::MpcMultiNet::set_field_and_curve)
+    pub field: String,
+    /// The curve, likewise
+    pub curve: String,
+    /// How the party passes the crown on (see
+    /// [MpcMultiNet::set_king_rotation](crate::MpcMultiNet::set_king_rotation))
+    pub king_rotation: KingRotation,
+    /// If the party resumes broken connections, the token that a peer presents to resume one
+    pub session: Option<u64>,
+}
+
+impl Hello {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.version.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&(self.n_parties as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.party_id as u64).to_le_bytes());
+        for name in &[&self.field, &self.curve] {
+            bytes.extend_from_slice(&(name.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(name.as_bytes());
+        }
+        bytes.push(match self.king_rotation {
+            KingRotation::Fixed => 0,
+            KingRotation::RoundRobin => 1,
+        });
+        if let Some(token) = self.session {
+            bytes.extend_from_slice(&token.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// The hello in `bytes`, or why there isn't one. The version comes first, so that a hello in
+    /// another version of the protocol is reported as such.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let malformed = || "sent a malformed hello".to_owned();
+        let mut rest = bytes;
+        let mut take = |n: usize| {
+            if rest.len() < n {
+                return Err(malformed());
+            }
+            let (taken, r) = rest.split_at(n);
+            rest = r;
+            Ok(taken)
+        };
+        let version = u32::from_le_bytes(take(4)?.try_into().unwrap());
+        if version != PROTOCOL_VERSION {
+            return Err(format!(
+                "speaks protocol version {}, and we speak {}",
+                version, PROTOCOL_VERSION
+            ));
+        }
+        let mut int = || take(8).map(|b| u64::from_le_bytes(b.try_into().unwrap()) as usize);
+        let n_parties = int()?;
+        let party_id = int()?;
+        let mut name = || {
+            let len = u64::from_le_bytes(take(8)?.try_into().unwrap()) as usize;
+            String::from_utf8(take(len)?.to_vec()).map_err(|_| malformed())
+        };
+        let field = name()?;
+        let curve = name()?;
+        let king_rotation = match take(1)?[0] {
+            0 => KingRotation::Fixed,
+            1 => KingRotation::RoundRobin,
+            _ => return Err(malformed()),
+        };
+        let session = match rest.len() {
+            0 => None,
+            8 => Some(u64::from_le_bytes(rest.try_into().unwrap())),
+            _ => return Err(malformed()),
+        };
+        Ok(Self {
+            version,
+            n_parties,
+            party_id,
+            field,
+            curve,
+            king_rotation,
+            session,
+        })
+    }
+
+    /// Read a hello frame: `Ok(Err(why))` if it isn't a good hello.
+    pub fn read(r: &mut impl Read) -> io::Result<Result<Self, String>> {
+        let (tag, len) = read_header(r)?;
+        if tag != HELLO {
+            return Ok(Err(
+                "did not say hello first: it speaks another protocol".to_owned()
+            ));
+        }
+        if len > MAX_HELLO {
+            return Ok(Err(format!("sent a hello of {} bytes", len)));
+        }
+        let mut bytes = vec![0u8; len];
+        r.read_exact(&mut bytes)?;
+        Ok(Self::from_bytes(&bytes))
+    }
+
+    /// Why party `peer`, which said `theirs`, can't compute with us, who said `self`, if it
+    /// can't.
+    pub fn mismatch(&self, theirs: &Hello, peer: usize) -> Option<String> {
+        let why = if theirs.version != self.version {
+            format!(
+                "speaks protocol version {}, and we speak {}",
+                theirs.version, self.version
+            )
+        } else if theirs.n_parties != self.n_parties {
+            format!(
+                "expects {} parties, and we expect {}",
+                theirs.n_parties, self.n_parties
+            )
+        } else if theirs.party_id != peer {
+            format!("claims to be party {}", theirs.party_id)
+        } else if theirs.field != self.field {
+            format!(
+                "computes over field {:?}, and we compute over {:?}",
+                theirs.field, self.field
+            )
+        } else if theirs.curve != self.curve {
+            format!(
+                "computes over curve {:?}, and we compute over {:?}",
+                theirs.curve, self.curve
+            )
+        } else if theirs.king_rotation != self.king_rotation {
+            format!(
+                "uses king rotation {:?}, and we use {:?}",
+                theirs.king_rotation, self.king_rotation
+            )
+        } else if theirs.session.is_some() && self.session.is_none() {
+            "resumes broken connections, and we don't".to_owned()
+        } else if theirs.session.is_none() && self.session.is_some() {
+            "doesn't resume broken connections, and we do".to_owned()
+        } else {
+            return None;
+        };
+        Some(format!("party {} {}", peer, why))
+    }
+}
+
+/// What a party says when it reconnects to a peer, and what the peer answers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Resumption {
+    /// The session token of the party spoken to (see [Hello::session])
+    pub token: u64,
+    /// The speaker
+    pub party_id: usize,
+    /// How many data frames the speaker has received from the party spoken to
+    pub received: u64,
+}
+
+impl Resumption {
+    pub fn write(&self, w: &mut impl Write) -> io::Result<()> {
+        let ints = [self.token, self.party_id as u64, self.received];
+        let bytes: Vec<u8> = ints.iter().flat_map(|i| i.to_le_bytes()).collect();
+        write_frame(w, RESUME, &[&bytes])
+    }
+
+    /// Read a resumption frame: `None` if the next frame is something else.
+    pub fn read(r: &mut impl Read) -> io::Result<Option<Self>> {
+        if read_header(r)? != (RESUME, 24) {
+            return Ok(None);
+        }
+        let mut int = || {
+            let mut b = [0u8; 8];
+            r.read_exact(&mut b).map(|()| u64::from_le_bytes(b))
+        };
+        Ok(Some(Self {
+            token: int()?,
+            party_id: int()? as usize,
+            received: int()?,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hello(n_parties: usize, party_id: usize, curve: &str) -> Hello {
+        Hello {
+            version: PROTOCOL_VERSION,
+            n_parties,
+            party_id,
+            field: "Fr".to_owned(),
+            curve: curve.to_owned(),
+            king_rotation: KingRotation::Fixed,
+            session: None,
+        }
+    }
+
+    #[test]
+    fn hello_bytes() {
+        let h = hello(3, 1, "bls12-377");
+        assert_eq!(Hello::from_bytes(&h.to_bytes()), Ok(h.clone()));
+        let mut frame = Vec::new();
+        write_frame(&mut frame, HELLO, &[&h.to_bytes()]).unwrap();
+        assert_eq!(Hello::read(&mut &frame[..]).unwrap(), Ok(h.clone()));
+        let resuming = Hello {
+            session: Some(7),
+            ..h.clone()
+        };
+        assert_eq!(Hello::from_bytes(&resuming.to_bytes()), Ok(resuming));
+        let rotating = Hello {
+            king_rotation: KingRotation::RoundRobin,
+            ..h.clone()
+        };
+        assert_eq!(Hello::from_bytes(&rotating.to_bytes()), Ok(rotating));
+        let bytes = h.to_bytes();
+        assert!(Hello::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let mut other = h;
+        other.version += 1;
+        let why = Hello::from_bytes(&other.to_bytes()).unwrap_err();
+        assert!(why.contains("protocol version"), "{}", why);
+        // A data frame, where a hello should be
+        let mut frame = Vec::new();
+        write_frame(&mut frame, DATA, &[&[0u8; 4]]).unwrap();
+        assert!(Hello::read(&mut &frame[..]).unwrap().is_err());
+    }
+
+    #[test]
+    fn mismatches() {
+        let ours = hello(3, 0, "bls12-377");
+        assert_eq!(ours.mismatch(&hello(3, 1, "bls12-377"), 1), None);
+        let why = |theirs: Hello| ours.mismatch(&theirs, 1).unwrap();
+        assert_eq!(
+            why(hello(4, 1, "bls12-377")),
+            "party 1 expects 4 parties, and we expect 3"
+        );
+        assert_eq!(
+            why(hello(3, 2, "bls12-377")),
+            "party 1 claims to be party 2"
+        );
+        assert_eq!(
+            why(hello(3, 1, "bn254")),
+            "party 1 computes over curve \"bn254\", and we compute over \"bls12-377\""
+        );
+        let rotating = Hello {
+            king_rotation: KingRotation::RoundRobin,
+            ..hello(3, 1, "bls12-377")
+        };
+        assert_eq!(
+            why(rotating),
+            "party 1 uses king rotation RoundRobin, and we use Fixed"
+        );
+        let resuming = Hello {
+            session: Some(7),
+            ..hello(3, 1, "bls12-377")
+        };
+        assert_eq!(
+            why(resuming),
+            "party 1 resumes broken connections, and we don't"
+        );
+    }
+
+    #[test]
+    fn resumption() {
+        let r = Resumption {
+            token: 0xdead_beef,
+            party_id: 2,
+            received: 5,
+        };
+        let mut frame = Vec::new();
+        r.write(&mut frame).unwrap();
+        assert_eq!(Resumption::read(&mut &frame[..]).unwrap(), Some(r));
+        let mut frame = Vec::new();
+        write_frame(&mut frame, DATA, &[&[0u8; 24]]).unwrap();
+        assert_eq!(Resumption::read(&mut &frame[..]).unwrap(), None);
+    }
+}
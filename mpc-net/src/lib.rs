@@ -1,16 +1,84 @@
+pub mod abort;
+pub mod chunked;
+pub mod frame;
 pub mod multi;
+#[cfg(feature = "tls")]
+pub mod tls;
+pub mod transport;
 pub mod two;
 
 pub use two::MpcTwoNet;
-pub use multi::MpcMultiNet;
+pub use multi::{KingRotation, MpcMultiNet};
+pub use abort::{catch_abort, Abort, AbortCode};
+pub use frame::Hello;
+pub use transport::{MemTransport, Transport, UnixTransport};
 
-#[derive(Clone, Debug)]
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+
+/// Why the network gave up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MpcNetError {
+    /// Party `id` crashed, went silent for longer than the timeout, or was reported failed by
+    /// another party.
+    PartyFailure(usize),
+}
+
+impl fmt::Display for MpcNetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MpcNetError::PartyFailure(id) => write!(f, "party {} failed", id),
+        }
+    }
+}
+
+impl std::error::Error for MpcNetError {}
+
+impl MpcNetError {
+    /// Unwind, with this error as the panic payload.
+    ///
+    /// The network returns its errors (see, e.g., [MpcNet::try_broadcast_bytes]), but the
+    /// arithmetic built on it is infallible, so its infallible methods give up this way.
+    /// [catch_failure] turns the unwinding back into an error.
+    pub fn raise(self) -> ! {
+        panic::panic_any(self)
+    }
+}
+
+/// Run `f`, returning the [MpcNetError] that it raised, if any.
+///
+/// Other panics are resumed. After a failure, the network should be deinitialized.
+pub fn catch_failure<T>(f: impl FnOnce() -> T) -> Result<T, MpcNetError> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| match payload.downcast() {
+        Ok(e) => *e,
+        Err(payload) => panic::resume_unwind(payload),
+    })
+}
+
+/// What the network has exchanged since it was initialized, or since the stats were last reset
+/// (see [MpcNet::reset_stats]): to profile one proof, reset them before it, or take the
+/// difference of snapshots (see [Stats::since]).
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Stats {
     pub bytes_sent: usize,
     pub bytes_recv: usize,
     pub broadcasts: usize,
     pub to_king: usize,
     pub from_king: usize,
+    /// Time spent in exchanges: sending, and waiting to receive
+    pub wait: std::time::Duration,
+    /// What we exchanged with each peer, by party id; our own entry stays zero. Payload bytes
+    /// only, like the totals.
+    pub peers: Vec<PeerStats>,
+}
+
+/// What we exchanged with one peer.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PeerStats {
+    pub bytes_sent: usize,
+    pub bytes_recv: usize,
+    /// Exchanges in which we sent the peer something, or heard from it
+    pub rounds: usize,
 }
 
 impl std::default::Default for Stats {
@@ -21,16 +89,70 @@ impl std::default::Default for Stats {
             broadcasts: 0,
             to_king: 0,
             from_king: 0,
+            wait: std::time::Duration::ZERO,
+            peers: Vec::new(),
+        }
+    }
+}
+
+impl Stats {
+    /// Rounds of communication: broadcasts, and exchanges with the king.
+    pub fn rounds(&self) -> usize {
+        self.broadcasts + self.to_king + self.from_king
+    }
+    /// What was exchanged after `earlier`, an earlier snapshot of the same stats.
+    pub fn since(&self, earlier: &Stats) -> Stats {
+        let zero = PeerStats::default();
+        Stats {
+            bytes_sent: self.bytes_sent.saturating_sub(earlier.bytes_sent),
+            bytes_recv: self.bytes_recv.saturating_sub(earlier.bytes_recv),
+            broadcasts: self.broadcasts.saturating_sub(earlier.broadcasts),
+            to_king: self.to_king.saturating_sub(earlier.to_king),
+            from_king: self.from_king.saturating_sub(earlier.from_king),
+            wait: self.wait.saturating_sub(earlier.wait),
+            peers: self
+                .peers
+                .iter()
+                .enumerate()
+                .map(|(id, p)| {
+                    let e = earlier.peers.get(id).unwrap_or(&zero);
+                    PeerStats {
+                        bytes_sent: p.bytes_sent.saturating_sub(e.bytes_sent),
+                        bytes_recv: p.bytes_recv.saturating_sub(e.bytes_recv),
+                        rounds: p.rounds.saturating_sub(e.rounds),
+                    }
+                })
+                .collect(),
         }
     }
+    /// Count an exchange with party `peer`, whose entry must exist: `sent` bytes to it, and
+    /// `recv` from it.
+    pub(crate) fn record(&mut self, peer: usize, sent: usize, recv: usize) {
+        let p = &mut self.peers[peer];
+        p.bytes_sent += sent;
+        p.bytes_recv += recv;
+        p.rounds += 1;
+        self.bytes_sent += sent;
+        self.bytes_recv += recv;
+    }
 }
 
 pub trait MpcNet {
-    /// Am I the first party?
+    /// Am I the king?
     #[inline]
     fn am_king() -> bool {
-        Self::party_id() == 0
+        Self::party_id() == Self::king()
+    }
+    /// Which party is the king, who gets everyone's bytes in [MpcNet::send_bytes_to_king]?
+    /// The first, unless kings rotate (see [MpcNet::next_king]).
+    #[inline]
+    fn king() -> usize {
+        0
     }
+    /// Hand the crown on, if kings rotate, after a [MpcNet::king_compute]. Every party calls
+    /// this at the same point, so they agree on the king.
+    #[inline]
+    fn next_king() {}
     /// How many parties are there?
     fn n_parties() -> usize;
     /// What is my party number (0 to n-1)?
@@ -39,7 +161,8 @@ pub trait MpcNet {
     /// The file should contain one HOST:PORT setting per line, corresponding to the addresses of
     /// the parties in increasing order.
     ///
-    /// Parties are zero-indexed.
+    /// Parties are zero-indexed. Raises an [AbortCode::ConfigMismatch] abort if the parties
+    /// disagree on how many they are, or on what they compute over.
     fn init_from_file(path: &str, party_id: usize);
     /// Is the network layer initalized?
     fn is_init() -> bool;
@@ -47,24 +170,82 @@ pub trait MpcNet {
     fn deinit();
     /// Set statistics to zero.
     fn reset_stats();
-    /// Get statistics.
+    /// Get statistics: totals, and for each peer.
     fn stats() -> Stats;
     /// All parties send bytes to each other.
-    fn broadcast_bytes(bytes: &[u8]) -> Vec<Vec<u8>>;
+    fn try_broadcast_bytes(bytes: &[u8]) -> Result<Vec<Vec<u8>>, MpcNetError>;
+    /// All parties send bytes to each other, privately: party `i` gets `bytes[i]`.
+    /// All of one party's messages must have the same length.
+    fn try_send_bytes_to_each(bytes: Vec<Vec<u8>>) -> Result<Vec<Vec<u8>>, MpcNetError>;
     /// All parties send bytes to the king.
-    fn send_bytes_to_king(bytes: &[u8]) -> Option<Vec<Vec<u8>>>;
+    fn try_send_bytes_to_king(bytes: &[u8]) -> Result<Option<Vec<Vec<u8>>>, MpcNetError>;
     /// All parties recv bytes from the king.
     /// Provide bytes iff you're the king!
-    fn recv_bytes_from_king(bytes: Option<Vec<Vec<u8>>>) -> Vec<u8>;
+    fn try_recv_bytes_from_king(bytes: Option<Vec<Vec<u8>>>) -> Result<Vec<u8>, MpcNetError>;
+
+    /// All parties send bytes to each other, as in [MpcNet::try_broadcast_bytes], but parties
+    /// that fail are left out (as `None`) rather than failing us, so long as at least `quorum`
+    /// parties, us included, respond.
+    ///
+    /// By default, every party must respond.
+    fn try_broadcast_bytes_quorum(
+        bytes: &[u8],
+        _quorum: usize,
+    ) -> Result<Vec<Option<Vec<u8>>>, MpcNetError> {
+        Self::try_broadcast_bytes(bytes).map(|all| all.into_iter().map(Some).collect())
+    }
+
+    /// Like [MpcNet::try_broadcast_bytes], but raises its error (see [MpcNetError::raise]).
+    #[inline]
+    fn broadcast_bytes(bytes: &[u8]) -> Vec<Vec<u8>> {
+        Self::try_broadcast_bytes(bytes).unwrap_or_else(|e| e.raise())
+    }
+    /// Like [MpcNet::try_send_bytes_to_each], but raises its error.
+    #[inline]
+    fn send_bytes_to_each(bytes: Vec<Vec<u8>>) -> Vec<Vec<u8>> {
+        Self::try_send_bytes_to_each(bytes).unwrap_or_else(|e| e.raise())
+    }
+    /// Like [MpcNet::try_send_bytes_to_king], but raises its error.
+    #[inline]
+    fn send_bytes_to_king(bytes: &[u8]) -> Option<Vec<Vec<u8>>> {
+        Self::try_send_bytes_to_king(bytes).unwrap_or_else(|e| e.raise())
+    }
+    /// Like [MpcNet::try_recv_bytes_from_king], but raises its error.
+    #[inline]
+    fn recv_bytes_from_king(bytes: Option<Vec<Vec<u8>>>) -> Vec<u8> {
+        Self::try_recv_bytes_from_king(bytes).unwrap_or_else(|e| e.raise())
+    }
 
     /// Everyone sends bytes to the king, who recieves those bytes, runs a computation on them, and
     /// redistributes the resulting bytes.
     ///
     /// The king's computation is given by a function, `f`
     /// proceeds.
+    ///
+    /// Every party's bytes must be the same length. If that's more than a chunk, both ways are
+    /// [chunked](chunked::Transfer), and `progress` hears how they're going.
     #[inline]
-    fn king_compute(bytes: &[u8], f: impl Fn(Vec<Vec<u8>>) -> Vec<Vec<u8>>) -> Vec<u8> {
-        let king_response = Self::send_bytes_to_king(bytes).map(f);
-        Self::recv_bytes_from_king(king_response)
+    fn king_compute(
+        bytes: &[u8],
+        f: impl Fn(Vec<Vec<u8>>) -> Vec<Vec<u8>>,
+        mut progress: impl FnMut(chunked::Progress),
+    ) -> Vec<u8>
+    where
+        Self: Sized,
+    {
+        let r = if bytes.len() <= chunked::DEFAULT_CHUNK_SIZE {
+            let king_response = Self::send_bytes_to_king(bytes).map(f);
+            Self::recv_bytes_from_king(king_response)
+        } else {
+            let mut transfer = chunked::Transfer::default();
+            let king_response = match transfer.send_to_king::<Self>(bytes, &mut progress) {
+                Some(from_all) => Some(f(from_all)),
+                None if Self::am_king() => panic!("The king received no messages"),
+                None => None,
+            };
+            transfer.recv_from_king::<Self>(king_response.as_deref(), progress)
+        };
+        Self::next_king();
+        r
     }
 }
@@ -1,32 +1,423 @@
 use lazy_static::lazy_static;
 use log::debug;
 use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::cell::RefCell;
+use std::collections::hash_map::RandomState;
+use std::collections::VecDeque;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Read, Write};
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, BufRead, BufReader, Read, Write};
 use std::net::{SocketAddr, TcpListener, TcpStream};
-use std::sync::Mutex;
+use std::panic;
+#[cfg(feature = "tls")]
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
 use ark_std::{end_timer, start_timer};
 
-use super::{MpcNet, Stats};
+use super::{Abort, AbortCode, MpcNet, MpcNetError, PeerStats, Stats};
+use crate::frame::{self, Hello, Resumption, ABORT, DATA, HEARTBEAT, HELLO, PROTOCOL_VERSION};
+#[cfg(feature = "tls")]
+use crate::tls;
+use crate::transport::{self, Channel, MemTransport, Transport, CONNECT_TIMEOUT};
 
 #[macro_use]
 lazy_static! {
     static ref CONNECTIONS: Mutex<Connections> = Mutex::new(Connections::default());
 }
 
-/// Macro for locking the FieldChannel singleton in the current scope.
-macro_rules! get_ch {
-    () => {
-        CONNECTIONS.lock().expect("Poisoned FieldChannel")
-    };
+thread_local! {
+    /// The connections of a party that is a thread (see [MpcMultiNet::simulate]), if we are one.
+    static LOCAL: RefCell<Option<Connections>> = RefCell::new(None);
+}
+
+/// Run `f` on the connections: this thread's, if it is a simulated party, else the process's.
+fn with_ch<T>(f: impl FnOnce(&mut Connections) -> T) -> T {
+    LOCAL.with(|local| match local.borrow_mut().as_mut() {
+        Some(ch) => f(ch),
+        None => f(&mut CONNECTIONS.lock().expect("Poisoned FieldChannel")),
+    })
+}
+
+/// Run exchange `f` on the connections, counting its time in their stats.
+fn exchange<T: Send>(
+    f: impl FnOnce(&mut Connections) -> Result<T, MpcNetError> + Send,
+) -> Result<T, MpcNetError> {
+    with_ch(|ch| {
+        ch.in_pool(|ch| {
+            let start = Instant::now();
+            let r = f(ch);
+            ch.stats.wait += start.elapsed();
+            r
+        })
+    })
+}
+
+/// How long to wait on a silent peer, and how often to tell our peers that we're alive.
+///
+/// We send heartbeats while we compute, so the heartbeat interval should be well under the
+/// timeout: otherwise a slow party looks like a failed one.
+#[derive(Clone, Copy, Debug)]
+pub struct Timeouts {
+    pub peer: Duration,
+    pub heartbeat: Duration,
+}
+
+/// Which party is king for each [MpcNet::king_compute].
+///
+/// The king gets every party's values and sends each its result, so it moves `n` times as many
+/// bytes as the others. Rotating the crown spreads that load, say over the many degree
+/// reductions of a Shamir-shared computation, without changing any result.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KingRotation {
+    /// Party 0 is always king.
+    Fixed,
+    /// The next party is king after each king computation.
+    RoundRobin,
+}
+
+impl Default for KingRotation {
+    fn default() -> Self {
+        KingRotation::Fixed
+    }
+}
+
+/// A connection to a peer: plain TCP, TLS over TCP, or a [Transport]'s channel.
+#[derive(Debug)]
+enum Stream {
+    Tcp(TcpStream),
+    #[cfg(feature = "tls")]
+    Tls(Box<tls::TlsStream>),
+    Channel(Box<dyn Channel>),
+}
+
+impl Read for Stream {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(s) => s.read(buf),
+            #[cfg(feature = "tls")]
+            Stream::Tls(s) => s.read(buf),
+            Stream::Channel(s) => s.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(s) => s.write(buf),
+            #[cfg(feature = "tls")]
+            Stream::Tls(s) => s.write(buf),
+            Stream::Channel(s) => s.write(buf),
+        }
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Stream::Tcp(s) => s.flush(),
+            #[cfg(feature = "tls")]
+            Stream::Tls(s) => s.flush(),
+            Stream::Channel(s) => s.flush(),
+        }
+    }
+}
+
+impl Stream {
+    /// Give up on reads and writes that take longer than `timeout`.
+    fn set_timeout(&mut self, timeout: Duration) -> io::Result<()> {
+        match self {
+            Stream::Tcp(s) => s.set_timeout(Some(timeout)),
+            #[cfg(feature = "tls")]
+            Stream::Tls(s) => {
+                s.sock().set_read_timeout(Some(timeout))?;
+                s.sock().set_write_timeout(Some(timeout))
+            }
+            Stream::Channel(s) => s.set_timeout(Some(timeout)),
+        }
+    }
+}
+
+/// Why an exchange with a peer stopped.
+enum Fault {
+    /// Our connection to it broke: we may be able to resume it
+    Broken,
+    /// It failed, or told us that a party did
+    Failed(MpcNetError),
+}
+
+/// Read the header of the next data frame from party `peer`, skipping heartbeats, and return
+/// the length of its body.
+fn read_data_header(s: &mut Stream, peer: usize) -> Result<usize, Fault> {
+    let broken = |_: io::Error| Fault::Broken;
+    loop {
+        match frame::read_header(s).map_err(broken)? {
+            (DATA, len) => return Ok(len),
+            (HEARTBEAT, _) => {}
+            (ABORT, 8) => {
+                let mut id = [0u8; 8];
+                s.read_exact(&mut id).map_err(broken)?;
+                let id = u64::from_le_bytes(id) as usize;
+                return Err(Fault::Failed(MpcNetError::PartyFailure(id)));
+            }
+            _ => return Err(Fault::Failed(MpcNetError::PartyFailure(peer))),
+        }
+    }
+}
+
+/// A random token, to name a session.
+fn random_token() -> u64 {
+    let mut h = RandomState::new().build_hasher();
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH);
+    h.write_u128(now.map_or(0, |d| d.as_nanos()));
+    h.finish()
+}
+
+/// How many data frames we keep, for each peer, to resend once we resume a broken connection. A
+/// peer that is further behind than this can't resume.
+const REPLAY_WINDOW: usize = 16;
+/// How long we try to resume a broken connection before we give up on the peer.
+const RESUME_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// What we need to resume our connection to a peer if it breaks (see
+/// [MpcMultiNet::set_resumable]).
+#[derive(Debug)]
+struct Session {
+    own_id: usize,
+    /// The peer's token, which we present when we reconnect to it, and ours, which it presents
+    theirs: u64,
+    ours: u64,
+    /// The peer timeout, if any, for the new connection
+    timeout: Option<Duration>,
+    /// Where our listener puts the peer's reconnections, if it reconnects to us
+    incoming: Arc<Incoming>,
+    log: Mutex<Log>,
+}
+
+/// The data frames that we have exchanged with a peer.
+#[derive(Debug, Default)]
+struct Log {
+    sent: u64,
+    received: u64,
+    /// The last frames that we sent, oldest first: the last is frame `sent - 1`
+    replay: VecDeque<Vec<u8>>,
+}
+
+/// Reconnections that our listener has accepted: for each peer, the stream, and what the peer
+/// said when it reconnected.
+#[derive(Debug, Default)]
+struct Incoming {
+    streams: Mutex<Vec<Option<(TcpStream, Resumption)>>>,
+    ready: Condvar,
+}
+
+impl Incoming {
+    fn put(&self, peer: usize, stream: TcpStream, said: Resumption) {
+        let mut streams = self.streams.lock().expect("Poisoned incoming");
+        if streams.len() <= peer {
+            streams.resize_with(peer + 1, || None);
+        }
+        streams[peer] = Some((stream, said));
+        self.ready.notify_all();
+    }
+    /// Wait for party `peer` to reconnect, until `deadline`.
+    fn take(&self, peer: usize, deadline: Instant) -> Option<(TcpStream, Resumption)> {
+        let mut streams = self.streams.lock().expect("Poisoned incoming");
+        loop {
+            if let Some(s) = streams.get_mut(peer).and_then(|s| s.take()) {
+                return Some(s);
+            }
+            let left = deadline.checked_duration_since(Instant::now())?;
+            streams = self
+                .ready
+                .wait_timeout(streams, left)
+                .expect("Poisoned incoming")
+                .0;
+        }
+    }
 }
 
 #[derive(Debug)]
 struct Peer {
     id: usize,
     addr: SocketAddr,
-    stream: Option<TcpStream>,
+    /// Path to the peer's TLS certificate, if the hosts file gives one.
+    #[cfg(feature = "tls")]
+    cert: Option<PathBuf>,
+    /// Locked, so that heartbeats can be sent while we compute
+    stream: Option<Arc<Mutex<Stream>>>,
+    /// If we resume our connection to the peer when it breaks, what we need to
+    session: Option<Session>,
+}
+
+impl Peer {
+    fn stream(&self) -> MutexGuard<'_, Stream> {
+        self.stream
+            .as_ref()
+            .unwrap()
+            .lock()
+            .expect("Poisoned stream")
+    }
+    /// Send one frame, made of `parts`.
+    fn send_frame(&self, tag: u8, parts: &[&[u8]]) -> Result<(), MpcNetError> {
+        frame::write_frame(&mut *self.stream(), tag, parts)
+            .map_err(|_| MpcNetError::PartyFailure(self.id))
+    }
+    /// Send one data frame, made of `parts`.
+    fn send(&self, parts: &[&[u8]]) -> Result<(), MpcNetError> {
+        let session = match &self.session {
+            Some(session) => session,
+            None => return self.send_frame(DATA, parts),
+        };
+        let mut s = self.stream();
+        session
+            .log
+            .lock()
+            .expect("Poisoned log")
+            .push(parts.concat());
+        match frame::write_frame(&mut *s, DATA, parts) {
+            Ok(()) => Ok(()),
+            // Resuming resends the frame.
+            Err(_) => self.resume(&mut s, session),
+        }
+    }
+    /// Receive one data frame, of `buf.len()` bytes: a frame of any other length fails.
+    fn recv(&self, buf: &mut [u8]) -> Result<(), MpcNetError> {
+        self.recv_with(|s| {
+            if read_data_header(s, self.id)? != buf.len() {
+                return Err(Fault::Failed(MpcNetError::PartyFailure(self.id)));
+            }
+            s.read_exact(buf).map_err(|_| Fault::Broken)
+        })
+    }
+    /// Receive one data frame, of any length.
+    fn recv_sized(&self) -> Result<Vec<u8>, MpcNetError> {
+        self.recv_with(|s| {
+            let mut bytes_in = vec![0u8; read_data_header(s, self.id)?];
+            s.read_exact(&mut bytes_in).map_err(|_| Fault::Broken)?;
+            Ok(bytes_in)
+        })
+    }
+    /// Receive one data frame with `read`, resuming the connection and reading again if it
+    /// breaks and we can.
+    fn recv_with<T>(
+        &self,
+        mut read: impl FnMut(&mut Stream) -> Result<T, Fault>,
+    ) -> Result<T, MpcNetError> {
+        let mut s = self.stream();
+        loop {
+            match (read(&mut s), &self.session) {
+                (Ok(t), session) => {
+                    if let Some(session) = session {
+                        session.log.lock().expect("Poisoned log").received += 1;
+                    }
+                    return Ok(t);
+                }
+                (Err(Fault::Broken), Some(session)) => self.resume(&mut s, session)?,
+                (Err(Fault::Broken), None) => return Err(MpcNetError::PartyFailure(self.id)),
+                (Err(Fault::Failed(e)), _) => return Err(e),
+            }
+        }
+    }
+    /// Replace our broken connection, `s`, with a new one, and resend the frames that the peer
+    /// missed.
+    ///
+    /// The party with the lower id reconnects, as it connected at first; the other waits for it.
+    fn resume(&self, s: &mut Stream, session: &Session) -> Result<(), MpcNetError> {
+        let fail = MpcNetError::PartyFailure(self.id);
+        let deadline = Instant::now() + RESUME_TIMEOUT;
+        debug!("Resuming our connection to {}", self.id);
+        loop {
+            let left = deadline
+                .checked_duration_since(Instant::now())
+                .ok_or(fail)?;
+            let ours = Resumption {
+                token: session.theirs,
+                party_id: session.own_id,
+                received: session.log.lock().expect("Poisoned log").received,
+            };
+            let (mut stream, theirs) = if session.own_id < self.id {
+                let stream = transport::with_backoff(left, || TcpStream::connect(self.addr));
+                let mut stream = match stream {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        debug!("Could not reach {}: {}", self.id, e);
+                        return Err(fail);
+                    }
+                };
+                stream.set_read_timeout(Some(left)).map_err(|_| fail)?;
+                match ours
+                    .write(&mut stream)
+                    .and_then(|()| Resumption::read(&mut stream))
+                {
+                    Ok(Some(theirs)) => (stream, theirs),
+                    // The new connection broke too, or the peer refused it: try again.
+                    Ok(None) | Err(_) => continue,
+                }
+            } else {
+                let (mut stream, theirs) = session.incoming.take(self.id, deadline).ok_or(fail)?;
+                if ours.write(&mut stream).is_err() {
+                    continue;
+                }
+                (stream, theirs)
+            };
+            if theirs.token != session.ours || theirs.party_id != self.id {
+                return Err(fail);
+            }
+            stream.set_nodelay(true).map_err(|_| fail)?;
+            stream.set_timeout(session.timeout).map_err(|_| fail)?;
+            let missed = session
+                .log
+                .lock()
+                .expect("Poisoned log")
+                .since(theirs.received)
+                .ok_or(fail)?;
+            if missed
+                .iter()
+                .try_for_each(|f| frame::write_frame(&mut stream, DATA, &[f]))
+                .is_ok()
+            {
+                debug!("Resumed, resending {} frames", missed.len());
+                *s = Stream::Tcp(stream);
+                return Ok(());
+            }
+        }
+    }
+    /// Receive the peer's hello: `Ok(Err(why))` if it isn't one.
+    fn recv_hello(&self) -> Result<Result<Hello, String>, MpcNetError> {
+        Hello::read(&mut *self.stream()).map_err(|_| MpcNetError::PartyFailure(self.id))
+    }
+}
+
+impl Log {
+    /// Note that we sent `frame`.
+    fn push(&mut self, frame: Vec<u8>) {
+        self.sent += 1;
+        self.replay.push_back(frame);
+        if self.replay.len() > REPLAY_WINDOW {
+            self.replay.pop_front();
+        }
+    }
+    /// The frames that we sent after the first `received`, if we still have them.
+    fn since(&self, received: u64) -> Option<Vec<Vec<u8>>> {
+        let missed = self.sent.checked_sub(received)? as usize;
+        if missed > self.replay.len() {
+            return None;
+        }
+        Some(
+            self.replay
+                .iter()
+                .skip(self.replay.len() - missed)
+                .cloned()
+                .collect(),
+        )
+    }
 }
 
 #[derive(Default, Debug)]
@@ -34,6 +425,30 @@ struct Connections {
     id: usize,
     peers: Vec<Peer>,
     stats: Stats,
+    timeouts: Option<Timeouts>,
+    /// The failure that stopped us, if any. Once we fail, every operation fails.
+    failed: Option<MpcNetError>,
+    /// The field and curve we compute over, as we tell our peers (see [Hello])
+    field: String,
+    curve: String,
+    /// Whether to resume connections that break (see [MpcMultiNet::set_resumable])
+    resumable: bool,
+    /// How kings succeed each other (see [MpcMultiNet::set_king_rotation])
+    king_rotation: KingRotation,
+    /// The current king
+    king: usize,
+    /// If we resume connections, our session token, and where our listener puts reconnections
+    session: Option<(u64, Arc<Incoming>)>,
+    /// Stop flag and handle for the thread that accepts reconnections
+    acceptor: Option<(Arc<AtomicBool>, JoinHandle<()>)>,
+    /// Stop flag and handle for the heartbeat thread
+    heartbeat: Option<(Arc<AtomicBool>, JoinHandle<()>)>,
+    /// The threads our exchanges run on, if not rayon's global pool. Exchanges block on peers, so
+    /// parties that share a process each need their own.
+    pool: Option<Arc<ThreadPool>>,
+    /// Our TLS identity, if we're using TLS.
+    #[cfg(feature = "tls")]
+    tls: Option<tls::Identity>,
 }
 
 impl std::default::Default for Peer {
@@ -41,27 +456,40 @@ impl std::default::Default for Peer {
         Self {
             id: 0,
             addr: "127.0.0.1:8000".parse().unwrap(),
+            #[cfg(feature = "tls")]
+            cert: None,
             stream: None,
+            session: None,
         }
     }
 }
 
 impl Connections {
     /// Given a path and the `id` of oneself, initialize the structure
+    ///
+    /// Each line is `HOST:PORT`, optionally followed by the path to that party's certificate
+    /// (relative to the hosts file).
     fn init_from_path(&mut self, path: &str, id: usize) {
         let f = BufReader::new(File::open(path).expect("host configuration path"));
+        #[cfg(feature = "tls")]
+        let dir = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
+        // From any earlier connection
+        self.peers.clear();
         let mut peer_id = 0;
         for line in f.lines() {
             let line = line.unwrap();
-            let trimmed = line.trim();
-            if trimmed.len() > 0 {
-                let addr: SocketAddr = trimmed
+            let mut fields = line.split_whitespace();
+            if let Some(addr) = fields.next() {
+                let addr: SocketAddr = addr
                     .parse()
-                    .unwrap_or_else(|e| panic!("bad socket address: {}:\n{}", trimmed, e));
+                    .unwrap_or_else(|e| panic!("bad socket address: {}:\n{}", addr, e));
                 let peer = Peer {
                     id: peer_id,
                     addr,
+                    #[cfg(feature = "tls")]
+                    cert: fields.next().map(|c| dir.join(c)),
                     stream: None,
+                    session: None,
                 };
                 self.peers.push(peer);
                 peer_id += 1;
@@ -70,239 +498,843 @@ impl Connections {
         assert!(id < self.peers.len());
         self.id = id;
     }
-    fn connect_to_all(&mut self) {
+    fn connect_to_all(&mut self) -> Result<(), Abort> {
         let timer = start_timer!(|| "Connecting");
         let n = self.peers.len();
+        // We listen on one socket for the whole connection phase. Re-binding for each peer would
+        // drop any connection that was queued on the old socket before we accepted it.
+        let listener = if self.id > 0 {
+            Some(TcpListener::bind(self.peers[self.id].addr).unwrap())
+        } else {
+            None
+        };
         for from_id in 0..n {
             for to_id in (from_id + 1)..n {
                 debug!("{} to {}", from_id, to_id);
                 if self.id == from_id {
                     let to_addr = self.peers[to_id].addr;
                     debug!("Contacting {}", to_id);
-                    let stream = loop {
-                        let mut ms_waited = 0;
-                        match TcpStream::connect(to_addr) {
-                            Ok(s) => break s,
-                            Err(e) => match e.kind() {
-                                std::io::ErrorKind::ConnectionRefused
-                                | std::io::ErrorKind::ConnectionReset => {
-                                    ms_waited += 10;
-                                    std::thread::sleep(std::time::Duration::from_millis(10));
-                                    if ms_waited % 3_000 == 0 {
-                                        debug!("Still waiting");
-                                    } else if ms_waited > 30_000 {
-                                        panic!("Could not find peer in 30s");
-                                    }
-                                }
-                                _ => {
-                                    panic!("Error during FieldChannel::new: {}", e);
-                                }
-                            },
-                        }
-                    };
-                    stream.set_nodelay(true).unwrap();
-                    self.peers[to_id].stream = Some(stream);
+                    let stream =
+                        transport::with_backoff(CONNECT_TIMEOUT, || TcpStream::connect(to_addr))
+                            .unwrap_or_else(|e| panic!("Could not reach party {}: {}", to_id, e));
+                    let stream = self.wrap(stream, to_id, true);
+                    self.peers[to_id].stream = Some(Arc::new(Mutex::new(stream)));
                 } else if self.id == to_id {
                     debug!("Awaiting {}", from_id);
-                    let listener = TcpListener::bind(self.peers[self.id].addr).unwrap();
-                    let (stream, _addr) = listener.accept().unwrap();
-                    stream.set_nodelay(true).unwrap();
-                    self.peers[from_id].stream = Some(stream);
+                    let (stream, _addr) = listener.as_ref().unwrap().accept().unwrap();
+                    let stream = self.wrap(stream, from_id, false);
+                    self.peers[from_id].stream = Some(Arc::new(Mutex::new(stream)));
                 }
             }
             // Sender for next round waits for note from this sender to prevent race on receipt.
             if from_id + 1 < n {
                 if self.id == from_id {
-                    self.peers[self.id + 1]
-                        .stream
-                        .as_mut()
-                        .unwrap()
-                        .write_all(&[0u8])
-                        .unwrap();
+                    self.peers[self.id + 1].stream().write_all(&[0u8]).unwrap();
                 } else if self.id == from_id + 1 {
                     self.peers[self.id - 1]
-                        .stream
-                        .as_mut()
-                        .unwrap()
+                        .stream()
                         .read_exact(&mut [0u8])
                         .unwrap();
                 }
             }
         }
+        #[cfg(feature = "tls")]
+        let resumable = self.resumable && self.tls.is_none();
+        #[cfg(not(feature = "tls"))]
+        let resumable = self.resumable;
+        if resumable {
+            self.session = Some((random_token(), Arc::default()));
+        }
+        let r = self.in_pool(|ch| ch.finish_connecting());
+        if let (Ok(()), Some(listener)) = (&r, listener) {
+            if self.session.is_some() {
+                self.start_acceptor(listener);
+            }
+        }
+        end_timer!(timer);
+        r
+    }
+    /// Set up our connections to `transport`'s channels, for `id`, and we are party `id`.
+    fn connect_with(&mut self, transport: impl Transport, id: usize) -> Result<(), Abort> {
+        let timer = start_timer!(|| "Connecting");
+        let n = transport.n_parties();
+        assert!(id < n);
+        self.id = id;
+        let channels = transport
+            .connect(id)
+            .unwrap_or_else(|e| panic!("Could not connect: {}", e));
+        assert_eq!(channels.len(), n);
+        self.peers = channels
+            .into_iter()
+            .enumerate()
+            .map(|(peer_id, c)| Peer {
+                id: peer_id,
+                stream: c.map(|c| Arc::new(Mutex::new(Stream::Channel(c)))),
+                ..Peer::default()
+            })
+            .collect();
+        let r = self.in_pool(|ch| ch.finish_connecting());
+        end_timer!(timer);
+        r
+    }
+    /// Run `f` in our thread pool, if we have one, so that its parallel iterators do too.
+    fn in_pool<T: Send>(&mut self, f: impl FnOnce(&mut Self) -> T + Send) -> T {
+        match self.pool.clone() {
+            Some(pool) => pool.install(|| f(self)),
+            None => f(self),
+        }
+    }
+    /// Once every channel is open: exchange hellos, check that everyone is ready, and start
+    /// heartbeats.
+    ///
+    /// A peer whose hello doesn't match ours is a [AbortCode::ConfigMismatch].
+    fn finish_connecting(&mut self) -> Result<(), Abort> {
+        let n = self.peers.len();
+        let hello = Hello {
+            version: PROTOCOL_VERSION,
+            n_parties: n,
+            party_id: self.id,
+            field: self.field.clone(),
+            curve: self.curve.clone(),
+            king_rotation: self.king_rotation,
+            session: self.session.as_ref().map(|(token, _)| *token),
+        }
+        .to_bytes();
+        let peers = self.peers.iter().filter(|p| p.stream.is_some());
+        peers
+            .clone()
+            .try_for_each(|p| p.send_frame(HELLO, &[&hello]))
+            .unwrap_or_else(|e| e.raise());
+        let ours = Hello::from_bytes(&hello).unwrap();
+        let mut sessions = Vec::new();
+        for p in peers {
+            let why = match p.recv_hello().unwrap_or_else(|e| e.raise()) {
+                Ok(theirs) => {
+                    sessions.push((p.id, theirs.session));
+                    ours.mismatch(&theirs, p.id)
+                }
+                Err(why) => Some(format!("party {} {}", p.id, why)),
+            };
+            if let Some(why) = why {
+                return Err(Abort::new(AbortCode::ConfigMismatch, why));
+            }
+        }
+        if let Some((token, incoming)) = &self.session {
+            for (id, theirs) in sessions {
+                self.peers[id].session = Some(Session {
+                    own_id: self.id,
+                    theirs: theirs.unwrap(),
+                    ours: *token,
+                    timeout: self.timeouts.map(|t| t.peer),
+                    incoming: incoming.clone(),
+                    log: Mutex::default(),
+                });
+            }
+        }
         // Do a round with the king, to be sure everyone is ready
+        self.king = 0;
         let from_all = self.send_to_king(&[self.id as u8]);
-        self.recv_from_king(from_all);
+        from_all
+            .and_then(|from_all| self.recv_from_king(from_all))
+            .unwrap_or_else(|e| e.raise());
+        // That round is setup, not computation
+        self.stats = Stats::default();
         for id in 0..n {
             if id != self.id {
                 assert!(self.peers[id].stream.is_some());
             }
         }
-        end_timer!(timer);
+        // Only time out once everyone is up: connecting can take a while.
+        if let Some(timeouts) = self.timeouts {
+            self.start_heartbeat(timeouts);
+        }
+        Ok(())
+    }
+    /// Apply `timeouts` to every connection, and start sending heartbeats.
+    fn start_heartbeat(&mut self, timeouts: Timeouts) {
+        let streams: Vec<_> = self.peers.iter().filter_map(|p| p.stream.clone()).collect();
+        for s in &streams {
+            let mut s = s.lock().expect("Poisoned stream");
+            s.set_timeout(timeouts.peer).unwrap();
+        }
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        let handle = thread::spawn(move || {
+            while !stopped.load(Ordering::Relaxed) {
+                thread::sleep(timeouts.heartbeat);
+                for s in &streams {
+                    // A stream in use is either carrying our data, or waiting on the peer, which
+                    // then isn't waiting on us.
+                    if let Ok(mut s) = s.try_lock() {
+                        let _ = s.write_all(&[HEARTBEAT]).and_then(|()| s.flush());
+                    }
+                }
+            }
+        });
+        self.heartbeat = Some((stop, handle));
+    }
+    /// Accept reconnections on `listener`, from peers that present our session token, until we
+    /// uninitialize.
+    fn start_acceptor(&mut self, listener: TcpListener) {
+        let (token, incoming) = self.session.clone().unwrap();
+        let own_id = self.id;
+        listener.set_nonblocking(true).unwrap();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        let handle = thread::spawn(move || {
+            while !stopped.load(Ordering::Relaxed) {
+                let mut stream = match listener.accept() {
+                    Ok((stream, _addr)) => stream,
+                    Err(e) => {
+                        if e.kind() != io::ErrorKind::WouldBlock {
+                            debug!("Could not accept a reconnection: {}", e);
+                        }
+                        thread::sleep(Duration::from_millis(10));
+                        continue;
+                    }
+                };
+                let said = stream
+                    .set_nonblocking(false)
+                    .and_then(|()| stream.set_read_timeout(Some(Duration::from_secs(1))))
+                    .and_then(|()| Resumption::read(&mut stream));
+                match said {
+                    Ok(Some(said)) if said.token == token && said.party_id < own_id => {
+                        debug!("{} reconnected", said.party_id);
+                        incoming.put(said.party_id, stream, said);
+                    }
+                    _ => debug!("Refused a reconnection"),
+                }
+            }
+        });
+        self.acceptor = Some((stop, handle));
+    }
+    fn stop_heartbeat(&mut self) {
+        if let Some((stop, handle)) = self.heartbeat.take() {
+            stop.store(true, Ordering::Relaxed);
+            handle.join().expect("Heartbeat thread panicked");
+        }
+    }
+    /// Fail if we already have.
+    fn check(&self) -> Result<(), MpcNetError> {
+        self.failed.map_or(Ok(()), Err)
+    }
+    /// Record the failure `e`, and tell every peer to give up too.
+    ///
+    /// Peers forward the abort in turn, so parties that aren't talking to us hear of it too.
+    fn fail(&mut self, e: MpcNetError) -> MpcNetError {
+        if self.failed.is_some() {
+            return e;
+        }
+        self.failed = Some(e);
+        let MpcNetError::PartyFailure(failed_id) = e;
+        if let Some((stop, _)) = &self.heartbeat {
+            stop.store(true, Ordering::Relaxed);
+        }
+        for p in &self.peers {
+            if p.id != failed_id && p.stream.is_some() {
+                let mut s = p.stream();
+                let _ = frame::write_frame(&mut *s, ABORT, &[&(failed_id as u64).to_le_bytes()])
+                    .and_then(|()| s.flush());
+            }
+        }
+        e
+    }
+    /// Set up a freshly-opened TCP connection to `peer_id`.
+    ///
+    /// If we have a TLS identity, this runs the TLS handshake, with us as the client iff
+    /// `connecting`.
+    #[cfg_attr(not(feature = "tls"), allow(unused_variables))]
+    fn wrap(&self, stream: TcpStream, peer_id: usize, connecting: bool) -> Stream {
+        stream.set_nodelay(true).unwrap();
+        #[cfg(feature = "tls")]
+        if let Some(me) = &self.tls {
+            let peer_cert = tls::load_cert(
+                self.peers[peer_id]
+                    .cert
+                    .as_ref()
+                    .unwrap_or_else(|| panic!("No certificate for party {}", peer_id)),
+            );
+            let s = if connecting {
+                tls::TlsStream::connect(stream, me, self.id, &peer_cert, peer_id)
+            } else {
+                tls::TlsStream::accept(stream, me, self.id, &peer_cert, peer_id)
+            };
+            let s = s.unwrap_or_else(|e| panic!("TLS with party {} failed: {}", peer_id, e));
+            return Stream::Tls(Box::new(s));
+        }
+        Stream::Tcp(stream)
     }
     fn am_king(&self) -> bool {
-        self.id == 0
+        self.id == self.king
     }
-    fn broadcast(&mut self, bytes_out: &[u8]) -> Vec<Vec<u8>> {
+    fn next_king(&mut self) {
+        if self.king_rotation == KingRotation::RoundRobin {
+            self.king = (self.king + 1) % self.peers.len();
+        }
+    }
+    /// Count an exchange with party `peer` in our stats.
+    fn record(&mut self, peer: usize, sent: usize, recv: usize) {
+        let n = self.peers.len();
+        if self.stats.peers.len() < n {
+            self.stats.peers.resize(n, PeerStats::default());
+        }
+        self.stats.record(peer, sent, recv);
+    }
+    /// Count an exchange with every other party in our stats.
+    fn record_all(&mut self, sent: usize, recv: usize) {
+        let id = self.id;
+        for peer in (0..self.peers.len()).filter(|p| *p != id) {
+            self.record(peer, sent, recv);
+        }
+    }
+    fn broadcast(&mut self, bytes_out: &[u8]) -> Result<Vec<Vec<u8>>, MpcNetError> {
+        self.check()?;
         let timer = start_timer!(|| format!("Broadcast {}", bytes_out.len()));
         let m = bytes_out.len();
         let own_id = self.id;
-        self.stats.bytes_sent += (self.peers.len() - 1) * m;
-        self.stats.bytes_recv += (self.peers.len() - 1) * m;
+        self.record_all(m, m);
         self.stats.broadcasts += 1;
         let r = self
             .peers
-            .par_iter_mut()
+            .par_iter()
             .enumerate()
             .map(|(id, peer)| {
                 let mut bytes_in = vec![0u8; m];
                 if id < own_id {
-                    let stream = peer.stream.as_mut().unwrap();
-                    stream.read_exact(&mut bytes_in[..]).unwrap();
-                    stream.write_all(bytes_out).unwrap();
+                    peer.recv(&mut bytes_in[..])?;
+                    peer.send(&[bytes_out])?;
                 } else if id == own_id {
                     bytes_in.copy_from_slice(bytes_out);
                 } else {
-                    let stream = peer.stream.as_mut().unwrap();
-                    stream.write_all(bytes_out).unwrap();
-                    stream.read_exact(&mut bytes_in[..]).unwrap();
+                    peer.send(&[bytes_out])?;
+                    peer.recv(&mut bytes_in[..])?;
                 };
-                bytes_in
+                Ok(bytes_in)
+            })
+            .collect::<Result<_, _>>();
+        end_timer!(timer);
+        r.map_err(|e| self.fail(e))
+    }
+    /// Like [Connections::broadcast], but without the parties that fail, if at least `quorum`
+    /// parties are left.
+    ///
+    /// We don't give up on the others, or tell them of the failures: they'll hear of them at
+    /// their next exchange with the failed parties.
+    fn broadcast_quorum(
+        &mut self,
+        bytes_out: &[u8],
+        quorum: usize,
+    ) -> Result<Vec<Option<Vec<u8>>>, MpcNetError> {
+        self.check()?;
+        let timer = start_timer!(|| format!("Broadcast to a quorum {}", bytes_out.len()));
+        let m = bytes_out.len();
+        let own_id = self.id;
+        self.record_all(m, m);
+        self.stats.broadcasts += 1;
+        let r: Vec<Result<Vec<u8>, MpcNetError>> = self
+            .peers
+            .par_iter()
+            .enumerate()
+            .map(|(id, peer)| {
+                let mut bytes_in = vec![0u8; m];
+                if id < own_id {
+                    peer.recv(&mut bytes_in[..])?;
+                    peer.send(&[bytes_out])?;
+                } else if id == own_id {
+                    bytes_in.copy_from_slice(bytes_out);
+                } else {
+                    peer.send(&[bytes_out])?;
+                    peer.recv(&mut bytes_in[..])?;
+                };
+                Ok(bytes_in)
             })
             .collect();
         end_timer!(timer);
-        r
+        if r.iter().filter(|r| r.is_ok()).count() < quorum {
+            let e = r.into_iter().find_map(|r| r.err()).unwrap();
+            return Err(self.fail(e));
+        }
+        Ok(r.into_iter().map(|r| r.ok()).collect())
     }
-    fn send_to_king(&mut self, bytes_out: &[u8]) -> Option<Vec<Vec<u8>>> {
+    fn send_to_each(&mut self, bytes_out: Vec<Vec<u8>>) -> Result<Vec<Vec<u8>>, MpcNetError> {
+        self.check()?;
+        assert_eq!(bytes_out.len(), self.peers.len());
+        let m = bytes_out[0].len();
+        assert!(bytes_out.iter().all(|b| b.len() == m));
+        let timer = start_timer!(|| format!("To each {}", m));
+        let own_id = self.id;
+        self.record_all(m, m);
+        self.stats.broadcasts += 1;
+        let r = self
+            .peers
+            .par_iter()
+            .zip(bytes_out.par_iter())
+            .enumerate()
+            .map(|(id, (peer, bytes_out))| {
+                let mut bytes_in = vec![0u8; m];
+                if id < own_id {
+                    peer.recv(&mut bytes_in[..])?;
+                    peer.send(&[bytes_out])?;
+                } else if id == own_id {
+                    bytes_in.copy_from_slice(bytes_out);
+                } else {
+                    peer.send(&[bytes_out])?;
+                    peer.recv(&mut bytes_in[..])?;
+                };
+                Ok(bytes_in)
+            })
+            .collect::<Result<_, _>>();
+        end_timer!(timer);
+        r.map_err(|e| self.fail(e))
+    }
+    fn send_to_king(&mut self, bytes_out: &[u8]) -> Result<Option<Vec<Vec<u8>>>, MpcNetError> {
+        self.check()?;
         let timer = start_timer!(|| format!("To king {}", bytes_out.len()));
         let m = bytes_out.len();
         let own_id = self.id;
         self.stats.to_king += 1;
         let r = if self.am_king() {
-            self.stats.bytes_recv += (self.peers.len() - 1) * m;
-            Some(
-                self.peers
-                    .par_iter_mut()
-                    .enumerate()
-                    .map(|(id, peer)| {
-                        let mut bytes_in = vec![0u8; m];
-                        if id == own_id {
-                            bytes_in.copy_from_slice(bytes_out);
-                        } else {
-                            let stream = peer.stream.as_mut().unwrap();
-                            stream.read_exact(&mut bytes_in[..]).unwrap();
-                        };
-                        bytes_in
-                    })
-                    .collect(),
-            )
+            self.record_all(0, m);
+            self.peers
+                .par_iter()
+                .enumerate()
+                .map(|(id, peer)| {
+                    let mut bytes_in = vec![0u8; m];
+                    if id == own_id {
+                        bytes_in.copy_from_slice(bytes_out);
+                    } else {
+                        peer.recv(&mut bytes_in[..])?;
+                    };
+                    Ok(bytes_in)
+                })
+                .collect::<Result<_, _>>()
+                .map(Some)
         } else {
-            self.stats.bytes_sent += m;
-            self.peers[0]
-                .stream
-                .as_mut()
-                .unwrap()
-                .write_all(bytes_out)
-                .unwrap();
-            None
+            let king = self.king;
+            self.record(king, m, 0);
+            self.peers[king].send(&[bytes_out]).map(|()| None)
         };
         end_timer!(timer);
-        r
+        r.map_err(|e| self.fail(e))
     }
-    fn recv_from_king(&mut self, bytes_out: Option<Vec<Vec<u8>>>) -> Vec<u8> {
+    fn recv_from_king(&mut self, bytes_out: Option<Vec<Vec<u8>>>) -> Result<Vec<u8>, MpcNetError> {
+        self.check()?;
         let own_id = self.id;
         self.stats.from_king += 1;
-        if self.am_king() {
+        let r = if self.am_king() {
             let bytes_out = bytes_out.unwrap();
             let m = bytes_out[0].len();
             let timer = start_timer!(|| format!("From king {}", m));
-            let bytes_size = (m as u64).to_le_bytes();
-            self.stats.bytes_sent += (self.peers.len() - 1) * (m + 8);
-            self.peers
-                .par_iter_mut()
+            self.record_all(m, 0);
+            let r = self
+                .peers
+                .par_iter()
                 .enumerate()
                 .filter(|p| p.0 != own_id)
-                .for_each(|(id, peer)| {
-                    let stream = peer.stream.as_mut().unwrap();
+                .try_for_each(|(id, peer)| {
                     assert_eq!(bytes_out[id].len(), m);
-                    stream.write_all(&bytes_size).unwrap();
-                    stream.write_all(&bytes_out[id]).unwrap();
+                    peer.send(&[&bytes_out[id]])
                 });
             end_timer!(timer);
-            bytes_out[own_id].clone()
+            r.map(|()| bytes_out[own_id].clone())
         } else {
-            let stream = self.peers[0].stream.as_mut().unwrap();
-            let mut bytes_size = [0u8; 8];
-            stream.read_exact(&mut bytes_size).unwrap();
-            let m = u64::from_le_bytes(bytes_size) as usize;
-            self.stats.bytes_recv += m;
-            let mut bytes_in = vec![0u8; m];
-            stream.read_exact(&mut bytes_in).unwrap();
-            bytes_in
-        }
+            let king = self.king;
+            let r = self.peers[king].recv_sized();
+            if let Ok(bytes_in) = &r {
+                self.record(king, 0, bytes_in.len());
+            }
+            r
+        };
+        r.map_err(|e| self.fail(e))
     }
     fn uninit(&mut self) {
+        self.stop_heartbeat();
+        if let Some((stop, handle)) = self.acceptor.take() {
+            stop.store(true, Ordering::Relaxed);
+            handle.join().expect("Acceptor thread panicked");
+        }
+        self.session = None;
         for p in &mut self.peers {
             p.stream = None;
+            p.session = None;
         }
+        self.failed = None;
+        self.king = 0;
     }
 }
 
 pub struct MpcMultiNet;
 
+impl MpcMultiNet {
+    /// Give up on peers that are silent for `timeouts.peer`, and send heartbeats every
+    /// `timeouts.heartbeat`. Call this before initializing the network.
+    ///
+    /// Without timeouts, we notice peers that crash (their connections close) or that report a
+    /// failure, but not peers that hang.
+    ///
+    /// Set in the main thread, the timeouts also apply to [MpcMultiNet::simulate]d parties.
+    pub fn set_timeouts(timeouts: Timeouts) {
+        with_ch(|ch| ch.timeouts = Some(timeouts));
+    }
+
+    /// Resume connections that break, rather than give up on the peer, if `resumable`. Call this
+    /// before initializing the network; every party must agree (see [Hello]).
+    ///
+    /// When a connection breaks, the party with the lower id reconnects, with backoff, and
+    /// presents the other's session token. Each then resends the data that the other missed, so
+    /// that a blip in the network doesn't fail a long preprocessing phase. We keep only the last
+    /// few frames for each peer, so a peer that has fallen further behind still fails. Only plain
+    /// TCP connections, from [MpcNet::init_from_file], resume.
+    pub fn set_resumable(resumable: bool) {
+        with_ch(|ch| ch.resumable = resumable);
+    }
+
+    /// Pass the crown on after each king computation as `rotation` says. Call this before
+    /// initializing the network; every party must agree (see [Hello]).
+    ///
+    /// Like the timeouts, it also applies to [MpcMultiNet::simulate]d parties.
+    pub fn set_king_rotation(rotation: KingRotation) {
+        with_ch(|ch| ch.king_rotation = rotation);
+    }
+
+    /// Name the field and curve that we compute over, say by their types' names. Call this
+    /// before initializing the network.
+    ///
+    /// We tell our peers, in our [Hello], and a peer that names others is caught at connect time
+    /// (see [AbortCode::ConfigMismatch]), rather than corrupting our shares. If no party names
+    /// them, they all agree. Like the timeouts, they also apply to [MpcMultiNet::simulate]d
+    /// parties.
+    pub fn set_field_and_curve(field: &str, curve: &str) {
+        with_ch(|ch| {
+            ch.field = field.to_owned();
+            ch.curve = curve.to_owned();
+        });
+    }
+
+    /// Is this thread a party of its own (see [MpcMultiNet::simulate])? If so, whatever a party
+    /// keeps for the length of a computation should be kept per thread, not per process.
+    pub fn is_simulated() -> bool {
+        LOCAL.with(|local| local.borrow().is_some())
+    }
+
+    /// Initialize the network layer over `transport`'s channels, as party `party_id`.
+    ///
+    /// Raises an [AbortCode::ConfigMismatch] abort if a peer is misconfigured (see [Hello]).
+    pub fn init_with_transport(transport: impl Transport, party_id: usize) {
+        Self::try_init_with_transport(transport, party_id).unwrap_or_else(|e| e.raise())
+    }
+
+    /// Like [MpcMultiNet::init_with_transport], but returns the abort.
+    pub fn try_init_with_transport(
+        transport: impl Transport,
+        party_id: usize,
+    ) -> Result<(), Abort> {
+        with_ch(|ch| ch.connect_with(transport, party_id))
+    }
+
+    /// Run `f` as each of `n_parties` parties, each in a thread of its own, connected in memory
+    /// (see [MemTransport]), and return what each returned, in party order.
+    ///
+    /// Each party's thread has a network of its own, initialized before `f` and deinitialized
+    /// after, so a party must only use the network from that thread. Other per-party state (e.g.,
+    /// mpc-algebra's preprocessing) must be the thread's own too: see
+    /// [MpcMultiNet::is_simulated]. If a party panics, its
+    /// channels close, so the others fail (see [MpcNetError]) rather than wait for it; the first
+    /// panic, in party order, is resumed once every party has stopped.
+    pub fn simulate<T: Send>(n_parties: usize, f: impl Fn(usize) -> T + Sync) -> Vec<T> {
+        let f = &f;
+        let (timeouts, field, curve, king_rotation) = {
+            let ch = CONNECTIONS.lock().expect("Poisoned FieldChannel");
+            (
+                ch.timeouts,
+                ch.field.clone(),
+                ch.curve.clone(),
+                ch.king_rotation,
+            )
+        };
+        let (field, curve) = (&field, &curve);
+        let pool = || {
+            let pool = ThreadPoolBuilder::new().num_threads(n_parties).build();
+            Some(Arc::new(pool.expect("Could not start a thread pool")))
+        };
+        thread::scope(|scope| {
+            let parties: Vec<_> = MemTransport::new(n_parties)
+                .into_iter()
+                .enumerate()
+                .map(|(id, transport)| {
+                    scope.spawn(move || {
+                        let ch = Connections {
+                            timeouts,
+                            field: field.clone(),
+                            curve: curve.clone(),
+                            king_rotation,
+                            pool: pool(),
+                            ..Connections::default()
+                        };
+                        LOCAL.with(|local| *local.borrow_mut() = Some(ch));
+                        Self::init_with_transport(transport, id);
+                        let r = f(id);
+                        Self::deinit();
+                        LOCAL.with(|local| *local.borrow_mut() = None);
+                        r
+                    })
+                })
+                .collect();
+            parties
+                .into_iter()
+                .map(|p| p.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+                .collect()
+        })
+    }
+}
+
+#[cfg(feature = "tls")]
+impl MpcMultiNet {
+    /// Initialize the network layer from a file, using TLS for all channels.
+    ///
+    /// Like [MpcNet::init_from_file], but every line of the hosts file must also name the party's
+    /// certificate. `key_path` is our private key, matching our own certificate.
+    pub fn init_from_file_with_tls(path: &str, party_id: usize, key_path: &str) {
+        with_ch(|ch| {
+            ch.init_from_path(path, party_id);
+            let cert = ch.peers[party_id]
+                .cert
+                .clone()
+                .unwrap_or_else(|| panic!("No certificate for party {} in {}", party_id, path));
+            ch.tls = Some(tls::Identity::from_files(&cert, Path::new(key_path)));
+            ch.connect_to_all()
+        })
+        .unwrap_or_else(|e| e.raise())
+    }
+}
+
 impl MpcNet for MpcMultiNet {
     #[inline]
     fn party_id() -> usize {
-        get_ch!().id
+        with_ch(|ch| ch.id)
     }
 
     #[inline]
     fn n_parties() -> usize {
-        get_ch!().peers.len()
+        with_ch(|ch| ch.peers.len())
+    }
+
+    #[inline]
+    fn king() -> usize {
+        with_ch(|ch| ch.king)
+    }
+
+    #[inline]
+    fn next_king() {
+        with_ch(|ch| ch.next_king())
     }
 
     #[inline]
     fn init_from_file(path: &str, party_id: usize) {
-        let mut ch = get_ch!();
-        ch.init_from_path(path, party_id);
-        ch.connect_to_all();
+        with_ch(|ch| {
+            ch.init_from_path(path, party_id);
+            ch.connect_to_all()
+        })
+        .unwrap_or_else(|e| e.raise())
     }
 
     #[inline]
     fn is_init() -> bool {
-        get_ch!()
-            .peers
-            .first()
-            .map(|p| p.stream.is_some())
-            .unwrap_or(false)
+        // Our own entry never has a stream, so check them all.
+        with_ch(|ch| ch.peers.iter().any(|p| p.stream.is_some()))
     }
 
     #[inline]
     fn deinit() {
-        get_ch!().uninit()
+        with_ch(|ch| ch.uninit())
     }
 
     #[inline]
     fn reset_stats() {
-        get_ch!().stats = Stats::default();
+        with_ch(|ch| ch.stats = Stats::default());
     }
 
     #[inline]
     fn stats() -> crate::Stats {
-        get_ch!().stats.clone()
+        with_ch(|ch| ch.stats.clone())
+    }
+
+    #[inline]
+    fn try_broadcast_bytes(bytes: &[u8]) -> Result<Vec<Vec<u8>>, MpcNetError> {
+        exchange(|ch| ch.broadcast(bytes))
     }
 
     #[inline]
-    fn broadcast_bytes(bytes: &[u8]) -> Vec<Vec<u8>> {
-        get_ch!().broadcast(bytes)
+    fn try_broadcast_bytes_quorum(
+        bytes: &[u8],
+        quorum: usize,
+    ) -> Result<Vec<Option<Vec<u8>>>, MpcNetError> {
+        exchange(|ch| ch.broadcast_quorum(bytes, quorum))
     }
 
     #[inline]
-    fn send_bytes_to_king(bytes: &[u8]) -> Option<Vec<Vec<u8>>> {
-        get_ch!().send_to_king(bytes)
+    fn try_send_bytes_to_each(bytes: Vec<Vec<u8>>) -> Result<Vec<Vec<u8>>, MpcNetError> {
+        exchange(|ch| ch.send_to_each(bytes))
     }
 
     #[inline]
-    fn recv_bytes_from_king(bytes: Option<Vec<Vec<u8>>>) -> Vec<u8> {
-        get_ch!().recv_from_king(bytes)
+    fn try_send_bytes_to_king(bytes: &[u8]) -> Result<Option<Vec<Vec<u8>>>, MpcNetError> {
+        exchange(|ch| ch.send_to_king(bytes))
+    }
+
+    #[inline]
+    fn try_recv_bytes_from_king(bytes: Option<Vec<Vec<u8>>>) -> Result<Vec<u8>, MpcNetError> {
+        exchange(|ch| ch.recv_from_king(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunked;
+    use std::net::Shutdown;
+
+    /// Run `f` as each of `n_parties` parties, in threads of their own, over TCP on localhost,
+    /// resuming broken connections.
+    fn over_tcp<T: Send>(n_parties: usize, f: impl Fn(usize) -> T + Sync) -> Vec<T> {
+        let dir = std::env::temp_dir().join(format!("mpc-net-tcp-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let hosts = dir.join("hosts");
+        let ports: Vec<_> = (0..n_parties)
+            .map(|_| TcpListener::bind("127.0.0.1:0").unwrap())
+            .map(|l| l.local_addr().unwrap().to_string())
+            .collect();
+        std::fs::write(&hosts, ports.join("\n")).unwrap();
+        let hosts = hosts.to_str().unwrap();
+        let f = &f;
+        let r = thread::scope(|scope| {
+            let parties: Vec<_> = (0..n_parties)
+                .map(|id| {
+                    scope.spawn(move || {
+                        let pool = ThreadPoolBuilder::new().num_threads(n_parties).build();
+                        let ch = Connections {
+                            resumable: true,
+                            pool: Some(Arc::new(pool.unwrap())),
+                            ..Connections::default()
+                        };
+                        LOCAL.with(|local| *local.borrow_mut() = Some(ch));
+                        MpcMultiNet::init_from_file(hosts, id);
+                        let r = f(id);
+                        MpcMultiNet::deinit();
+                        r
+                    })
+                })
+                .collect();
+            parties.into_iter().map(|p| p.join().unwrap()).collect()
+        });
+        std::fs::remove_dir_all(&dir).unwrap();
+        r
+    }
+
+    /// Break our connection to `peer`.
+    fn cut(peer: usize) {
+        with_ch(|ch| match &*ch.peers[peer].stream() {
+            Stream::Tcp(s) => s.shutdown(Shutdown::Both).unwrap(),
+            s => panic!("Not TCP: {:?}", s),
+        })
+    }
+
+    #[test]
+    fn resumed() {
+        let results = over_tcp(3, |id| {
+            let first = MpcMultiNet::broadcast_bytes(&[id as u8]);
+            // Party 0 reconnects to 1, and 1 to 2.
+            match id {
+                0 => cut(1),
+                2 => cut(1),
+                _ => {}
+            }
+            let second = MpcMultiNet::broadcast_bytes(&[id as u8 + 10; 3]);
+            let to_king = MpcMultiNet::send_bytes_to_king(&[id as u8]);
+            let from_king = MpcMultiNet::recv_bytes_from_king(to_king);
+            (first, second, from_king, MpcMultiNet::stats())
+        });
+        for (id, (first, second, from_king, stats)) in results.into_iter().enumerate() {
+            assert_eq!(first, vec![vec![0], vec![1], vec![2]]);
+            assert_eq!(second, vec![vec![10; 3], vec![11; 3], vec![12; 3]]);
+            assert_eq!(from_king, vec![id as u8]);
+            assert_eq!(stats.rounds(), 4);
+        }
+    }
+
+    #[test]
+    fn king_rotation() {
+        let run = |rotation| {
+            MpcMultiNet::simulate(3, |id| {
+                // Only this party's setting: the simulated parties share no global state.
+                MpcMultiNet::set_king_rotation(rotation);
+                (0..5u8)
+                    .map(|i| {
+                        let king = MpcMultiNet::king();
+                        let sum = MpcMultiNet::king_compute(
+                            &[id as u8 + i],
+                            |all| vec![vec![all.iter().map(|b| b[0]).sum()]; all.len()],
+                            |_| {},
+                        );
+                        (king, sum)
+                    })
+                    .collect::<Vec<_>>()
+            })
+        };
+        let fixed = run(KingRotation::Fixed);
+        let rotating = run(KingRotation::RoundRobin);
+        let results = |r: &Vec<Vec<(usize, Vec<u8>)>>| {
+            r.iter()
+                .map(|p| p.iter().map(|(_, sum)| sum.clone()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(results(&fixed), results(&rotating));
+        for party in &rotating {
+            let kings: Vec<_> = party.iter().map(|(king, _)| *king).collect();
+            assert_eq!(kings, vec![0, 1, 2, 0, 1]);
+            assert_eq!(party[4].1, vec![3 + 3 * 4]);
+        }
+        assert!(fixed.iter().flatten().all(|(king, _)| *king == 0));
+    }
+
+    #[test]
+    fn chunked_transfer() {
+        let transfer = || chunked::Transfer::new(4, 2);
+        let results = MpcMultiNet::simulate(3, |id| {
+            let mine: Vec<u8> = (0..10).map(|i| id as u8 * 10 + i).collect();
+            let king_response = transfer()
+                .send_to_king::<MpcMultiNet>(&mine, |_| {})
+                .map(|all| all.into_iter().rev().collect::<Vec<_>>());
+            transfer().recv_from_king::<MpcMultiNet>(king_response.as_deref(), |_| {})
+        });
+        for (id, r) in results.iter().enumerate() {
+            let from = 2 - id as u8;
+            assert_eq!(r, &(0..10).map(|i| from * 10 + i).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "No message for some party")]
+    fn chunked_transfer_missing_message() {
+        MpcMultiNet::simulate(3, |_| {
+            // The king answers only two of the three parties.
+            let king_response = MpcMultiNet::am_king().then(|| vec![vec![0u8; 10]; 2]);
+            chunked::Transfer::new(4, 2)
+                .recv_from_king::<MpcMultiNet>(king_response.as_deref(), |_| {})
+        });
+    }
+
+    #[test]
+    fn replay_window() {
+        let mut log = Log::default();
+        for i in 0..(REPLAY_WINDOW as u8 + 2) {
+            log.push(vec![i]);
+        }
+        let sent = log.sent;
+        assert_eq!(log.since(sent), Some(vec![]));
+        assert_eq!(log.since(sent - 2), Some(vec![vec![16], vec![17]]));
+        assert_eq!(log.since(2).map(|m| m.len()), Some(REPLAY_WINDOW));
+        assert_eq!(log.since(1), None);
+        assert_eq!(log.since(sent + 1), None);
     }
 }
@@ -0,0 +1,270 @@
+//! TLS transport for the multi-party network layer.
+//!
+//! Every party has a (typically self-signed) certificate, listed next to its address in the hosts
+//! file. Connections are mutually authenticated by *pinning*: when talking to party `i`, we only
+//! accept the exact certificate configured for party `i`. Once the handshake completes, both ends
+//! announce their party ids inside the tunnel, so a party can't be confused about who is on the
+//! other end of a channel.
+use log::debug;
+use rustls::client::{ServerCertVerified, ServerCertVerifier};
+use rustls::server::{ClientCertVerified, ClientCertVerifier};
+use rustls::{
+    Certificate, ClientConfig, ClientConnection, DistinguishedName, Error, PrivateKey,
+    ServerConfig, ServerConnection, ServerName, StreamOwned,
+};
+
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// The name we ask for during the handshake.
+///
+/// We don't check names, only pinned certificates, so this is arbitrary.
+const SERVER_NAME: &str = "mpc-party";
+
+/// Read the first certificate from a PEM file.
+pub fn load_cert(path: &Path) -> Certificate {
+    let mut r = BufReader::new(
+        File::open(path).unwrap_or_else(|e| panic!("certificate {}: {}", path.display(), e)),
+    );
+    let mut certs = rustls_pemfile::certs(&mut r)
+        .unwrap_or_else(|e| panic!("bad certificate {}: {}", path.display(), e));
+    assert!(
+        !certs.is_empty(),
+        "no certificate found in {}",
+        path.display()
+    );
+    Certificate(certs.swap_remove(0))
+}
+
+/// Read the first (PKCS#8, RSA, or SEC1) private key from a PEM file.
+pub fn load_key(path: &Path) -> PrivateKey {
+    let mut r = BufReader::new(
+        File::open(path).unwrap_or_else(|e| panic!("private key {}: {}", path.display(), e)),
+    );
+    loop {
+        match rustls_pemfile::read_one(&mut r)
+            .unwrap_or_else(|e| panic!("bad private key {}: {}", path.display(), e))
+        {
+            Some(rustls_pemfile::Item::PKCS8Key(k))
+            | Some(rustls_pemfile::Item::RSAKey(k))
+            | Some(rustls_pemfile::Item::ECKey(k)) => break PrivateKey(k),
+            Some(_) => {}
+            None => panic!("no private key found in {}", path.display()),
+        }
+    }
+}
+
+/// Our own TLS identity
+#[derive(Clone, Debug)]
+pub struct Identity {
+    pub cert: Certificate,
+    pub key: PrivateKey,
+}
+
+impl Identity {
+    pub fn from_files(cert: &Path, key: &Path) -> Self {
+        Self {
+            cert: load_cert(cert),
+            key: load_key(key),
+        }
+    }
+}
+
+/// Accepts exactly one certificate, in either the client or the server role.
+struct PinnedCert(Certificate);
+
+impl PinnedCert {
+    fn check(&self, presented: &Certificate) -> Result<(), Error> {
+        if presented == &self.0 {
+            Ok(())
+        } else {
+            Err(Error::InvalidCertificate(
+                rustls::CertificateError::ApplicationVerificationFailure,
+            ))
+        }
+    }
+}
+
+impl ServerCertVerifier for PinnedCert {
+    fn verify_server_cert(
+        &self,
+        end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<ServerCertVerified, Error> {
+        self.check(end_entity)
+            .map(|()| ServerCertVerified::assertion())
+    }
+
+    fn request_scts(&self) -> bool {
+        false
+    }
+}
+
+impl ClientCertVerifier for PinnedCert {
+    fn client_auth_root_subjects(&self) -> &[DistinguishedName] {
+        &[]
+    }
+
+    fn verify_client_cert(
+        &self,
+        end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _now: SystemTime,
+    ) -> Result<ClientCertVerified, Error> {
+        self.check(end_entity)
+            .map(|()| ClientCertVerified::assertion())
+    }
+}
+
+/// A TLS-wrapped TCP stream, in either role.
+pub enum TlsStream {
+    Client(StreamOwned<ClientConnection, TcpStream>),
+    Server(StreamOwned<ServerConnection, TcpStream>),
+}
+
+impl Read for TlsStream {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            TlsStream::Client(s) => s.read(buf),
+            TlsStream::Server(s) => s.read(buf),
+        }
+    }
+}
+
+impl Write for TlsStream {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            TlsStream::Client(s) => s.write(buf),
+            TlsStream::Server(s) => s.write(buf),
+        }
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            TlsStream::Client(s) => s.flush(),
+            TlsStream::Server(s) => s.flush(),
+        }
+    }
+}
+
+impl std::fmt::Debug for TlsStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TlsStream::Client(s) => write!(f, "TlsStream::Client({:?})", s.sock),
+            TlsStream::Server(s) => write!(f, "TlsStream::Server({:?})", s.sock),
+        }
+    }
+}
+
+impl TlsStream {
+    /// The TCP connection underneath
+    pub fn sock(&self) -> &TcpStream {
+        match self {
+            TlsStream::Client(s) => &s.sock,
+            TlsStream::Server(s) => &s.sock,
+        }
+    }
+
+    /// Run the handshake to completion.
+    ///
+    /// We do this eagerly, rather than on first use, so that both ends finish connecting in the
+    /// same step of the connection schedule.
+    fn handshake(&mut self) -> io::Result<()> {
+        match self {
+            TlsStream::Client(s) => {
+                while s.conn.is_handshaking() {
+                    s.conn.complete_io(&mut s.sock)?;
+                }
+            }
+            TlsStream::Server(s) => {
+                while s.conn.is_handshaking() {
+                    s.conn.complete_io(&mut s.sock)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Tell the other end who we are, and check that they are who we expect.
+    ///
+    /// The client speaks first.
+    fn bind_party_ids(&mut self, own_id: usize, peer_id: usize) -> io::Result<()> {
+        let own = (own_id as u64).to_le_bytes();
+        let mut theirs = [0u8; 8];
+        if let TlsStream::Client(_) = self {
+            self.write_all(&own)?;
+            self.read_exact(&mut theirs)?;
+        } else {
+            self.read_exact(&mut theirs)?;
+            self.write_all(&own)?;
+        }
+        let theirs = u64::from_le_bytes(theirs) as usize;
+        if theirs != peer_id {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!(
+                    "expected party {}, but peer claims to be {}",
+                    peer_id, theirs
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Connect to party `peer_id` (as the TLS client), over an established TCP stream.
+    pub fn connect(
+        sock: TcpStream,
+        me: &Identity,
+        own_id: usize,
+        peer_cert: &Certificate,
+        peer_id: usize,
+    ) -> io::Result<Self> {
+        let config = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_custom_certificate_verifier(Arc::new(PinnedCert(peer_cert.clone())))
+            .with_client_auth_cert(vec![me.cert.clone()], me.key.clone())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let conn =
+            ClientConnection::new(Arc::new(config), ServerName::try_from(SERVER_NAME).unwrap())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let mut s = TlsStream::Client(StreamOwned::new(conn, sock));
+        s.handshake()?;
+        s.bind_party_ids(own_id, peer_id)?;
+        debug!("TLS channel to {} established", peer_id);
+        Ok(s)
+    }
+
+    /// Accept party `peer_id` (as the TLS server), over an established TCP stream.
+    pub fn accept(
+        sock: TcpStream,
+        me: &Identity,
+        own_id: usize,
+        peer_cert: &Certificate,
+        peer_id: usize,
+    ) -> io::Result<Self> {
+        let config = ServerConfig::builder()
+            .with_safe_defaults()
+            .with_client_cert_verifier(Arc::new(PinnedCert(peer_cert.clone())))
+            .with_single_cert(vec![me.cert.clone()], me.key.clone())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let conn = ServerConnection::new(Arc::new(config))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let mut s = TlsStream::Server(StreamOwned::new(conn, sock));
+        s.handshake()?;
+        s.bind_party_ids(own_id, peer_id)?;
+        debug!("TLS channel from {} established", peer_id);
+        Ok(s)
+    }
+}
@@ -0,0 +1,415 @@
+//! Ways for the parties to reach each other, besides TCP (see [MpcNet::init_from_file]).
+//!
+//! A [Transport] opens a [Channel] to every other party; [MpcMultiNet::init_with_transport] runs
+//! the network over them. There are two:
+//!
+//! * [UnixTransport]: UNIX domain sockets, for parties in separate processes on one machine.
+//! * [MemTransport]: in-memory channels, for parties that are threads of one process. See
+//!   [MpcMultiNet::simulate], which sets them up.
+//!
+//! [MpcNet::init_from_file]: crate::MpcNet::init_from_file
+//! [MpcMultiNet::init_with_transport]: crate::MpcMultiNet::init_with_transport
+//! [MpcMultiNet::simulate]: crate::MpcMultiNet::simulate
+use log::debug;
+use std::fmt::Debug;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long to keep trying to reach a peer that isn't listening yet.
+pub(crate) const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// The longest that we wait between tries to reach a peer.
+const MAX_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Call `connect` until it succeeds, waiting twice as long after each try, up to [MAX_BACKOFF].
+/// We give up on errors that don't look like the peer just isn't reachable yet, and once
+/// `timeout` has passed.
+pub(crate) fn with_backoff<S>(
+    timeout: Duration,
+    mut connect: impl FnMut() -> io::Result<S>,
+) -> io::Result<S> {
+    let start = Instant::now();
+    let mut delay = Duration::from_millis(10);
+    loop {
+        match connect() {
+            Ok(s) => return Ok(s),
+            Err(e) if not_yet(&e) && start.elapsed() + delay < timeout => {
+                if delay == MAX_BACKOFF {
+                    debug!("Still waiting: {}", e);
+                }
+                thread::sleep(delay);
+                delay = (delay * 2).min(MAX_BACKOFF);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Whether a failure to connect may pass if we try again.
+fn not_yet(e: &io::Error) -> bool {
+    use io::ErrorKind::*;
+    matches!(
+        e.kind(),
+        NotFound
+            | ConnectionRefused
+            | ConnectionReset
+            | ConnectionAborted
+            | TimedOut
+            | HostUnreachable
+            | NetworkUnreachable
+    )
+}
+
+/// A connection to one peer.
+pub trait Channel: Read + Write + Send + Debug {
+    /// Give up on reads and writes that take longer than `timeout`, or never if `None`.
+    fn set_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()>;
+}
+
+impl Channel for TcpStream {
+    fn set_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        self.set_read_timeout(timeout)?;
+        self.set_write_timeout(timeout)
+    }
+}
+
+impl Channel for UnixStream {
+    fn set_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        self.set_read_timeout(timeout)?;
+        self.set_write_timeout(timeout)
+    }
+}
+
+/// A way to open a [Channel] to every other party.
+pub trait Transport {
+    /// How many parties there are, ourselves included.
+    fn n_parties(&self) -> usize;
+    /// Open a channel to each other party, in party order, with `None` for ourselves (party
+    /// `own_id`).
+    ///
+    /// Every party connects at once, so this may block until the others do.
+    fn connect(self, own_id: usize) -> io::Result<Vec<Option<Box<dyn Channel>>>>;
+}
+
+/// UNIX domain sockets: party `i` listens at the `i`th path, and connects to the parties before
+/// it, telling each its id. The parties after it connect to it in turn.
+#[derive(Clone, Debug)]
+pub struct UnixTransport {
+    pub paths: Vec<PathBuf>,
+}
+
+impl UnixTransport {
+    /// Sockets `0.sock`, `1.sock`, ... in `dir`, for `n_parties` parties.
+    pub fn in_dir(dir: &Path, n_parties: usize) -> Self {
+        Self {
+            paths: (0..n_parties)
+                .map(|i| dir.join(format!("{}.sock", i)))
+                .collect(),
+        }
+    }
+}
+
+impl Transport for UnixTransport {
+    fn n_parties(&self) -> usize {
+        self.paths.len()
+    }
+
+    fn connect(self, own_id: usize) -> io::Result<Vec<Option<Box<dyn Channel>>>> {
+        let n = self.paths.len();
+        assert!(own_id < n);
+        // From an earlier run
+        let _ = std::fs::remove_file(&self.paths[own_id]);
+        let listener = UnixListener::bind(&self.paths[own_id])?;
+        let mut channels: Vec<Option<Box<dyn Channel>>> = (0..n).map(|_| None).collect();
+        for (peer_id, path) in self.paths.iter().enumerate().take(own_id) {
+            debug!("Contacting {}", peer_id);
+            let mut stream = with_backoff(CONNECT_TIMEOUT, || UnixStream::connect(path))?;
+            stream.write_all(&(own_id as u64).to_le_bytes())?;
+            channels[peer_id] = Some(Box::new(stream));
+        }
+        for _ in (own_id + 1)..n {
+            let (mut stream, _addr) = listener.accept()?;
+            let mut peer_id = [0u8; 8];
+            stream.read_exact(&mut peer_id)?;
+            let peer_id = u64::from_le_bytes(peer_id) as usize;
+            if peer_id <= own_id || peer_id >= n || channels[peer_id].is_some() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unexpected connection from party {}", peer_id),
+                ));
+            }
+            debug!("Accepted {}", peer_id);
+            channels[peer_id] = Some(Box::new(stream));
+        }
+        std::fs::remove_file(&self.paths[own_id])?;
+        Ok(channels)
+    }
+}
+
+/// One end of an in-memory channel: each write is sent as a message to the other end, which
+/// reads the messages back as a stream of bytes.
+///
+/// When one end is dropped, reads at the other end see the end of the stream, as they would if a
+/// peer's socket closed.
+#[derive(Debug)]
+pub struct MemChannel {
+    tx: Sender<Vec<u8>>,
+    rx: Receiver<Vec<u8>>,
+    /// The unread rest of the last message
+    buf: Vec<u8>,
+    pos: usize,
+    timeout: Option<Duration>,
+}
+
+impl MemChannel {
+    /// The two ends of a new channel.
+    pub fn pair() -> (Self, Self) {
+        let (tx_a, rx_a) = mpsc::channel();
+        let (tx_b, rx_b) = mpsc::channel();
+        let end = |tx, rx| Self {
+            tx,
+            rx,
+            buf: Vec::new(),
+            pos: 0,
+            timeout: None,
+        };
+        (end(tx_a, rx_b), end(tx_b, rx_a))
+    }
+}
+
+impl Read for MemChannel {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.buf.len() {
+            let msg = match self.timeout {
+                Some(t) => self.rx.recv_timeout(t).map_err(|e| match e {
+                    RecvTimeoutError::Timeout => io::ErrorKind::TimedOut,
+                    RecvTimeoutError::Disconnected => io::ErrorKind::UnexpectedEof,
+                }),
+                None => self.rx.recv().map_err(|_| io::ErrorKind::UnexpectedEof),
+            };
+            match msg {
+                Ok(msg) => {
+                    self.buf = msg;
+                    self.pos = 0;
+                }
+                Err(io::ErrorKind::UnexpectedEof) => return Ok(0),
+                Err(kind) => return Err(kind.into()),
+            }
+        }
+        let n = buf.len().min(self.buf.len() - self.pos);
+        buf[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+impl Write for MemChannel {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.tx
+            .send(buf.to_vec())
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Channel for MemChannel {
+    fn set_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        self.timeout = timeout;
+        Ok(())
+    }
+}
+
+/// In-memory channels, for parties that are threads of one process. Make one transport per
+/// party with [MemTransport::new], and hand each to its party's thread.
+#[derive(Debug)]
+pub struct MemTransport {
+    id: usize,
+    channels: Vec<Option<MemChannel>>,
+}
+
+impl MemTransport {
+    /// A transport for each of `n_parties` parties, in party order, connected to each other.
+    pub fn new(n_parties: usize) -> Vec<Self> {
+        let mut transports: Vec<Self> = (0..n_parties)
+            .map(|id| Self {
+                id,
+                channels: (0..n_parties).map(|_| None).collect(),
+            })
+            .collect();
+        for i in 0..n_parties {
+            for j in (i + 1)..n_parties {
+                let (a, b) = MemChannel::pair();
+                transports[i].channels[j] = Some(a);
+                transports[j].channels[i] = Some(b);
+            }
+        }
+        transports
+    }
+}
+
+impl Transport for MemTransport {
+    fn n_parties(&self) -> usize {
+        self.channels.len()
+    }
+
+    fn connect(self, own_id: usize) -> io::Result<Vec<Option<Box<dyn Channel>>>> {
+        assert_eq!(own_id, self.id, "This transport is party {}'s", self.id);
+        Ok(self
+            .channels
+            .into_iter()
+            .map(|c| c.map(|c| Box::new(c) as Box<dyn Channel>))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{catch_failure, MpcMultiNet, MpcNet, MpcNetError, PeerStats, Stats};
+
+    #[test]
+    fn simulated() {
+        let results = MpcMultiNet::simulate(3, |id| {
+            assert_eq!(MpcMultiNet::party_id(), id);
+            assert_eq!(MpcMultiNet::n_parties(), 3);
+            let all = MpcMultiNet::broadcast_bytes(&[id as u8; 4]);
+            let to_king = MpcMultiNet::send_bytes_to_king(&[id as u8 * 2]);
+            let from_king =
+                MpcMultiNet::recv_bytes_from_king(to_king.map(|v| v.into_iter().rev().collect()));
+            (all, from_king)
+        });
+        for (id, (all, from_king)) in results.into_iter().enumerate() {
+            assert_eq!(all, vec![vec![0; 4], vec![1; 4], vec![2; 4]]);
+            assert_eq!(from_king, vec![2 * (2 - id as u8)]);
+        }
+        // Not a simulated party, so not initialized
+        assert!(!MpcMultiNet::is_init());
+    }
+
+    #[test]
+    fn simulated_stats() {
+        let results = MpcMultiNet::simulate(3, |id| {
+            MpcMultiNet::broadcast_bytes(&[id as u8; 4]);
+            let before = MpcMultiNet::stats();
+            MpcMultiNet::send_bytes_to_king(&[id as u8; 2]);
+            let since = MpcMultiNet::stats().since(&before);
+            MpcMultiNet::reset_stats();
+            (MpcMultiNet::stats(), before, since)
+        });
+        for (id, (reset, before, since)) in results.into_iter().enumerate() {
+            assert_eq!(reset, Stats::default());
+            assert_eq!(
+                (before.rounds(), before.bytes_sent, before.bytes_recv),
+                (1, 8, 8)
+            );
+            let sent = |p: &PeerStats| p.bytes_sent;
+            let expected: Vec<usize> = (0..3).map(|p| if p == id { 0 } else { 4 }).collect();
+            assert_eq!(before.peers.iter().map(sent).collect::<Vec<_>>(), expected);
+            assert_eq!(since.rounds(), 1);
+            if id == 0 {
+                assert_eq!((since.bytes_sent, since.bytes_recv), (0, 4));
+                assert_eq!(since.peers[1].bytes_recv, 2);
+            } else {
+                assert_eq!((since.bytes_sent, since.bytes_recv), (2, 0));
+                assert_eq!(since.peers[0].bytes_sent, 2);
+                assert_eq!(since.peers[0].rounds, 1);
+                assert_eq!(since.peers[3 - id].rounds, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn simulated_failure() {
+        let results = MpcMultiNet::simulate(3, |id| {
+            catch_failure(|| {
+                if id == 2 {
+                    MpcNetError::PartyFailure(2).raise();
+                }
+                MpcMultiNet::broadcast_bytes(&[id as u8]);
+            })
+        });
+        assert!(results.iter().all(|r| r.is_err()));
+    }
+
+    #[test]
+    fn simulated_dropout() {
+        let results = MpcMultiNet::simulate(3, |id| {
+            if id == 2 {
+                // Killed: its channels close without a word.
+                return None;
+            }
+            let first = MpcMultiNet::try_broadcast_bytes(&[id as u8]);
+            let then = MpcMultiNet::try_send_bytes_to_king(&[id as u8]);
+            Some((first, then))
+        });
+        for r in &results[..2] {
+            let (first, then) = r.as_ref().unwrap();
+            assert_eq!(first, &Err(MpcNetError::PartyFailure(2)));
+            assert_eq!(then, &Err(MpcNetError::PartyFailure(2)));
+        }
+    }
+
+    #[test]
+    fn simulated_dropout_quorum() {
+        // Three of four is a quorum
+        let results = MpcMultiNet::simulate(4, |id| {
+            if id == 2 {
+                return None;
+            }
+            Some(MpcMultiNet::try_broadcast_bytes_quorum(&[id as u8], 3))
+        });
+        for r in results.iter().filter_map(|r| r.as_ref()) {
+            let got = r.as_ref().unwrap();
+            assert_eq!(got, &vec![Some(vec![0]), Some(vec![1]), None, Some(vec![3])]);
+        }
+        // Two of three isn't
+        let results = MpcMultiNet::simulate(3, |id| {
+            if id == 2 {
+                return None;
+            }
+            Some(MpcMultiNet::try_broadcast_bytes_quorum(&[id as u8], 3))
+        });
+        for r in &results[..2] {
+            assert_eq!(r.as_ref().unwrap(), &Err(MpcNetError::PartyFailure(2)));
+        }
+    }
+
+    #[test]
+    fn unix() {
+        let dir = std::env::temp_dir().join(format!("mpc-net-unix-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let transport = UnixTransport::in_dir(&dir, 3);
+        let parties: Vec<_> = (0..3)
+            .map(|id| {
+                let transport = transport.clone();
+                thread::spawn(move || {
+                    let mut channels = transport.connect(id).unwrap();
+                    let mut got = Vec::new();
+                    for (peer, c) in channels.iter_mut().enumerate() {
+                        if let Some(c) = c {
+                            c.write_all(&[id as u8]).unwrap();
+                            let mut b = [0u8];
+                            c.read_exact(&mut b).unwrap();
+                            got.push((peer, b[0] as usize));
+                        }
+                    }
+                    got
+                })
+            })
+            .collect();
+        for (id, p) in parties.into_iter().enumerate() {
+            let got = p.join().unwrap();
+            assert_eq!(got.len(), 2);
+            assert!(got.iter().all(|(peer, said)| peer == said && *peer != id));
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,13 +1,14 @@
 use lazy_static::lazy_static;
 use log::debug;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Read, Write};
+use std::io::{self, BufRead, BufReader, Read, Write};
 use std::net::{SocketAddr, TcpListener, TcpStream};
 use std::sync::Mutex;
+use std::time::Instant;
 
 use ark_std::{end_timer, start_timer};
 
-use super::{MpcNet, Stats};
+use super::{MpcNet, MpcNetError, PeerStats, Stats};
 
 #[macro_use]
 lazy_static! {
@@ -106,32 +107,38 @@ impl FieldChannel {
     }
 
     #[inline]
-    pub fn send_slice(&mut self, v: &[u8]) {
+    pub fn send_slice(&mut self, v: &[u8]) -> io::Result<()> {
+        let start = Instant::now();
         let s = self.stream();
-        s.set_nonblocking(false).unwrap();
+        s.set_nonblocking(false)?;
         let bytes = (v.len() as u64).to_ne_bytes();
-        s.write_all(&bytes[..]).unwrap();
-        s.write_all(v).unwrap();
-        s.set_nonblocking(true).unwrap();
-        self.stats.bytes_sent += bytes.len() + v.len();
+        s.write_all(&bytes[..])?;
+        s.write_all(v)?;
+        s.set_nonblocking(true)?;
+        self.record(bytes.len() + v.len(), 0);
+        self.stats.wait += start.elapsed();
+        Ok(())
     }
 
     #[inline]
-    pub fn recv_vec(&mut self) -> Vec<u8> {
+    pub fn recv_vec(&mut self) -> io::Result<Vec<u8>> {
+        let start = Instant::now();
         let s = self.stream();
         let mut len = [0u8; 8];
-        s.set_nonblocking(false).unwrap();
-        s.read_exact(&mut len[..]).unwrap();
+        s.set_nonblocking(false)?;
+        s.read_exact(&mut len[..])?;
         let mut bytes = vec![0u8; u64::from_ne_bytes(len) as usize];
-        s.read_exact(&mut bytes[..]).unwrap();
-        s.set_nonblocking(true).unwrap();
-        self.stats.bytes_recv += bytes.len() + len.len();
-        bytes
+        s.read_exact(&mut bytes[..])?;
+        s.set_nonblocking(true)?;
+        self.record(0, bytes.len() + len.len());
+        self.stats.wait += start.elapsed();
+        Ok(bytes)
     }
 
     #[inline]
     pub fn exchange_bytes(&mut self, bytes_out: &[u8]) -> std::io::Result<Vec<u8>> {
         let timer = start_timer!(|| format!("Exchanging {}", bytes_out.len()));
+        let start = Instant::now();
         let s = self.stream();
         let n = bytes_out.len();
         let mut bytes_in = vec![0u8; n];
@@ -140,6 +147,7 @@ impl FieldChannel {
         while bytes_out_offset < n || bytes_in_offset < n {
             if bytes_out_offset < n {
                 match s.write(&bytes_out[bytes_out_offset..]) {
+                    Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
                     Ok(written) => {
                         bytes_out_offset += written;
                         let _e = s.flush();
@@ -155,6 +163,8 @@ impl FieldChannel {
             }
             if bytes_in_offset < n {
                 match s.read(&mut bytes_in[bytes_in_offset..]) {
+                    // The peer hung up
+                    Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
                     Ok(read) => {
                         bytes_in_offset += read;
                     }
@@ -169,12 +179,19 @@ impl FieldChannel {
             }
         }
         self.stats.broadcasts += 1;
-        self.stats.bytes_sent += n;
-        self.stats.bytes_recv += n;
+        self.record(n, n);
+        self.stats.wait += start.elapsed();
         end_timer!(timer);
         Ok(bytes_in)
     }
 
+    /// Count an exchange with the other party in our stats.
+    fn record(&mut self, sent: usize, recv: usize) {
+        self.stats.peers.resize(2, PeerStats::default());
+        let other = if self.talk_first { 1 } else { 0 };
+        self.stats.record(other, sent, recv);
+    }
+
     #[inline]
     pub fn stats(&self) -> Stats {
         self.stats.clone()
@@ -228,6 +245,16 @@ pub fn reset_stats() {
     ch.reset_stats();
 }
 
+/// The other party's failure, after a failed exchange with it.
+pub fn peer_failure() -> MpcNetError {
+    MpcNetError::PartyFailure(1 - MpcTwoNet::party_id())
+}
+
+/// Give up on the other party, after a failed exchange with it (see [MpcNetError::raise]).
+pub fn peer_failed() -> ! {
+    peer_failure().raise()
+}
+
 /// Are you the first party in the MPC?
 #[inline]
 pub fn am_first() -> bool {
@@ -280,40 +307,61 @@ impl MpcNet for MpcTwoNet {
     }
 
     #[inline]
-    fn broadcast_bytes(bytes: &[u8]) -> Vec<Vec<u8>> {
-        let other = get_ch!().exchange_bytes(bytes).unwrap();
+    fn try_broadcast_bytes(bytes: &[u8]) -> Result<Vec<Vec<u8>>, MpcNetError> {
+        let other = get_ch!().exchange_bytes(bytes);
+        let other = other.map_err(|_| peer_failure())?;
         if Self::am_king() {
-            vec![bytes.to_vec(), other]
+            Ok(vec![bytes.to_vec(), other])
         } else {
-            vec![other, bytes.to_vec()]
+            Ok(vec![other, bytes.to_vec()])
         }
     }
 
     #[inline]
-    fn send_bytes_to_king(bytes: &[u8]) -> Option<Vec<Vec<u8>>> {
-        let mut ch = get_ch!();
-        ch.stats.to_king += 1;
-        if ch.talk_first {
-            let other = ch.recv_vec();
-            debug_assert_eq!(bytes.len(), other.len());
-            Some(vec![bytes.to_vec(), other])
+    fn try_send_bytes_to_each(mut bytes: Vec<Vec<u8>>) -> Result<Vec<Vec<u8>>, MpcNetError> {
+        assert_eq!(bytes.len(), 2);
+        if Self::am_king() {
+            let other = get_ch!().exchange_bytes(&bytes[1]);
+            let other = other.map_err(|_| peer_failure())?;
+            Ok(vec![bytes.swap_remove(0), other])
         } else {
-            ch.send_slice(bytes);
-            None
+            let other = get_ch!().exchange_bytes(&bytes[0]);
+            let other = other.map_err(|_| peer_failure())?;
+            Ok(vec![other, bytes.pop().unwrap()])
         }
     }
 
     #[inline]
-    fn recv_bytes_from_king(bytes: Option<Vec<Vec<u8>>>) -> Vec<u8> {
-        let mut ch = get_ch!();
-        ch.stats.from_king += 1;
-        if ch.talk_first {
-            let mut bytes = bytes.expect("king needs bytes");
-            assert_eq!(bytes.len(), 2);
-            ch.send_slice(&bytes.pop().unwrap());
-            bytes.pop().unwrap()
-        } else {
-            ch.recv_vec()
-        }
+    fn try_send_bytes_to_king(bytes: &[u8]) -> Result<Option<Vec<Vec<u8>>>, MpcNetError> {
+        let r = {
+            let mut ch = get_ch!();
+            ch.stats.to_king += 1;
+            if ch.talk_first {
+                ch.recv_vec().map(|other| {
+                    debug_assert_eq!(bytes.len(), other.len());
+                    Some(vec![bytes.to_vec(), other])
+                })
+            } else {
+                ch.send_slice(bytes).map(|()| None)
+            }
+        };
+        r.map_err(|_| peer_failure())
+    }
+
+    #[inline]
+    fn try_recv_bytes_from_king(bytes: Option<Vec<Vec<u8>>>) -> Result<Vec<u8>, MpcNetError> {
+        let r = {
+            let mut ch = get_ch!();
+            ch.stats.from_king += 1;
+            if ch.talk_first {
+                let mut bytes = bytes.expect("king needs bytes");
+                assert_eq!(bytes.len(), 2);
+                ch.send_slice(&bytes.pop().unwrap())
+                    .map(|()| bytes.pop().unwrap())
+            } else {
+                ch.recv_vec()
+            }
+        };
+        r.map_err(|_| peer_failure())
     }
 }
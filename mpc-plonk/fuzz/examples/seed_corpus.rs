@@ -0,0 +1,32 @@
+//! Seed the fuzzers' corpora with honest proofs and keys, so that they start from bytes that read.
+//!
+//! Run from this directory: `cargo run --example seed_corpus`.
+use mpc_plonk::write_versioned;
+use mpc_plonk_fuzz::FIXTURE;
+use std::fs;
+
+fn main() {
+    let f = &*FIXTURE;
+    let mut vk = Vec::new();
+    write_versioned(&f.vk, &mut vk).unwrap();
+    let mut circ = Vec::new();
+    write_versioned(&f.circ, &mut circ).unwrap();
+    let seeds: [(&str, &[(&str, &[u8])]); 3] = [
+        (
+            "read_proof",
+            &[("proof", &f.proof), ("small_proof", &f.small_proof)],
+        ),
+        ("read_keys", &[("vk", &vk), ("circuit", &circ)]),
+        (
+            "verify",
+            &[("proof", &f.proof), ("small_proof", &f.small_proof)],
+        ),
+    ];
+    for (target, files) in seeds.iter() {
+        let dir = format!("corpus/{}", target);
+        fs::create_dir_all(&dir).unwrap();
+        for (name, bytes) in files.iter() {
+            fs::write(format!("{}/{}", dir, name), bytes).unwrap();
+        }
+    }
+}
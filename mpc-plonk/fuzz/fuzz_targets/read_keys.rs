@@ -0,0 +1,13 @@
+//! Reading keys, circuit layouts, and universal parameters from untrusted bytes.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use mpc_plonk::read_versioned;
+use mpc_plonk::relations::flat::Instance;
+use mpc_plonk_fuzz::{Params, Pk, Vk, F};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = read_versioned::<Vk, _>(data);
+    let _ = read_versioned::<Pk, _>(data);
+    let _ = read_versioned::<Instance<F>, _>(data);
+    let _ = read_versioned::<Params, _>(data);
+});
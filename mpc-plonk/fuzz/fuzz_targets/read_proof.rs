@@ -0,0 +1,10 @@
+//! Reading a proof, of either kind, from untrusted bytes.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use mpc_plonk::read_versioned;
+use mpc_plonk_fuzz::{Pf, SmallPf};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = read_versioned::<Pf, _>(data);
+    let _ = read_versioned::<SmallPf, _>(data);
+});
@@ -0,0 +1,16 @@
+//! Verifying untrusted proof bytes, of either kind: any that read must be rejected (or accepted)
+//! without a panic.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use mpc_plonk::read_versioned;
+use mpc_plonk_fuzz::{Pf, Pl, SmallPf, FIXTURE};
+
+fuzz_target!(|data: &[u8]| {
+    let f = &*FIXTURE;
+    if let Ok(pf) = read_versioned::<Pf, _>(data) {
+        let _ = Pl::verify_with_report(&f.vk, &f.circ, pf.value, &f.public, None);
+    }
+    if let Ok(pf) = read_versioned::<SmallPf, _>(data) {
+        let _ = Pl::verify_small_with_report(&f.vk, &f.circ, pf.value, &f.public);
+    }
+});
@@ -0,0 +1,68 @@
+//! What the fuzz targets share: the types they read, and a circuit with honest keys and proofs.
+use ark_poly::univariate::DensePolynomial;
+use ark_poly_commit::{marlin_pc::MarlinKZG10, PolynomialCommitment};
+use ark_std::test_rng;
+use lazy_static::lazy_static;
+use mpc_plonk::relations::{
+    flat::{Instance, Witness},
+    structured::PlonkCircuit,
+};
+use mpc_plonk::{write_versioned, Plonk};
+use std::collections::HashMap;
+
+pub type E = ark_bls12_377::Bls12_377;
+pub type F = ark_bls12_377::Fr;
+pub type P = DensePolynomial<F>;
+pub type PC = MarlinKZG10<E, P>;
+pub type Pl = Plonk<F, PC>;
+pub type Cmt = <PC as PolynomialCommitment<F, P>>::Commitment;
+pub type Params = <PC as PolynomialCommitment<F, P>>::UniversalParams;
+pub type Pk = mpc_plonk::ProverKey<F, Cmt, <PC as PolynomialCommitment<F, P>>::CommitterKey>;
+pub type Vk = mpc_plonk::VerifierKey<Cmt, <PC as PolynomialCommitment<F, P>>::VerifierKey>;
+pub type PcPf = <PC as PolynomialCommitment<F, P>>::Proof;
+pub type Pf = mpc_plonk::Proof<F, Cmt, PcPf>;
+pub type SmallPf = mpc_plonk::SmallProof<F, Cmt, PcPf>;
+
+/// A circuit's instance, its verifier key and public inputs, and an honest proof of
+/// each kind, serialized (see [write_versioned]), for seeding the fuzzers' corpora.
+pub struct Fixture {
+    pub vk: Vk,
+    pub circ: Instance<F>,
+    pub public: HashMap<String, F>,
+    pub proof: Vec<u8>,
+    pub small_proof: Vec<u8>,
+}
+
+const STEPS: usize = 4;
+
+lazy_static! {
+    pub static ref FIXTURE: Fixture = {
+        let start = F::from(2u64);
+        let c = PlonkCircuit::<F>::new_squaring_circuit(STEPS, Some(start));
+        let out = (0..STEPS).fold(start, |a, _| a * a);
+        let public = vec![("out".to_owned(), out)].into_iter().collect();
+        let circ = Instance::from_circuit(&c);
+        let witness = Witness::from_circuit(&circ, &c);
+        let srs = Pl::universal_setup(STEPS.next_power_of_two(), &mut test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, &circ);
+        let mut proof = Vec::new();
+        write_versioned(
+            &Pl::prove(&pk, &circ, &witness, &mut test_rng()),
+            &mut proof,
+        )
+        .unwrap();
+        let mut small_proof = Vec::new();
+        write_versioned(
+            &Pl::prove_small(&pk, &circ, &witness, &mut test_rng()),
+            &mut small_proof,
+        )
+        .unwrap();
+        Fixture {
+            vk,
+            circ,
+            public,
+            proof,
+            small_proof,
+        }
+    };
+}
@@ -0,0 +1,285 @@
+//! Aggregation of many (KZG-based) Plonk proofs of one circuit.
+//!
+//! A proof opens its polynomials at a handful of points, with one opening proof per (polynomial,
+//! point), but for the gate sub-proof's batched opening at its point (see
+//! [GateProof](crate::GateProof)). [Plonk::aggregate] folds the opening proofs at each point into one, with powers of a
+//! Fiat-Shamir challenge, and [Plonk::verify_aggregated] checks the folded openings of all the
+//! proofs with a single product of pairings, weighting each with another challenge.
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{One, PrimeField, Zero};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly_commit::{kzg10, marlin_pc, LabeledCommitment, PCCommitment};
+use ark_serialize::CanonicalSerialize;
+use ark_std::rand::SeedableRng;
+use rand_chacha::ChaChaRng;
+use std::collections::HashMap;
+
+use crate::{
+    AggregatedProof, Failure, Opening, Plonk, Proof, Query, Transcript, VerificationReport,
+    Verifier, VerifierKey,
+};
+
+type Kzg<E> = marlin_pc::MarlinKZG10<E, DensePolynomial<<E as PairingEngine>::Fr>>;
+type KzgProof<E> = Proof<<E as PairingEngine>::Fr, marlin_pc::Commitment<E>, kzg10::Proof<E>>;
+type KzgAggregatedProof<E> =
+    AggregatedProof<<E as PairingEngine>::Fr, marlin_pc::Commitment<E>, kzg10::Proof<E>>;
+
+/// The opening proofs of `pf`, grouped by point, in the order that the [Verifier] checks them.
+fn opening_groups<F, C, O>(pf: &Proof<F, C, O>) -> Vec<Vec<&O>> {
+    let (public, wiring) = (&pf.public, &pf.wiring);
+    let prod = &wiring.l1_prod_pf;
+    let mut public_group: Vec<&O> = public.p_opens.iter().map(|(_, o)| o).collect();
+    public_group.push(&public.q_open.1);
+    let mut wiring_group = vec![&wiring.l2_q_x_open.1];
+    wiring_group.extend(wiring.w_x_opens.iter().map(|(_, o)| o));
+    wiring_group.push(&wiring.l1_x_open.1);
+    wiring_group.extend(wiring.p_x_opens.iter().map(|(_, o)| o));
+    vec![
+        public_group,
+        vec![&pf.gates.open],
+        vec![&prod.f_wr_open.1, &prod.t_wr_open.1],
+        vec![&prod.q_r_open.1, &prod.t_r_open.1],
+        vec![&prod.t_wk_open.1],
+        wiring_group,
+    ]
+}
+
+/// `openings`, grouped by point, in order of first appearance.
+fn group_by_point<F: Copy + PartialEq, C: PCCommitment>(
+    openings: Vec<Opening<F, C>>,
+) -> Vec<(F, Vec<Opening<F, C>>)> {
+    let mut groups: Vec<(F, Vec<Opening<F, C>>)> = Vec::new();
+    for o in openings {
+        let point = o.0[0].1;
+        match groups.iter_mut().find(|(x, _)| *x == point) {
+            Some((_, group)) => group.push(o),
+            None => groups.push((point, vec![o])),
+        }
+    }
+    groups
+}
+
+/// The commitment to the linear combination in `query`.
+///
+/// As in [PolynomialCommitment::open_combinations], only a lone term, with coefficient one, may be
+/// degree-bounded.
+fn combine<E: PairingEngine>(
+    query: &Query<E::Fr, marlin_pc::Commitment<E>>,
+) -> LabeledCommitment<marlin_pc::Commitment<E>> {
+    match &query.0[..] {
+        [(coeff, cmt)] if coeff.is_one() => cmt.clone(),
+        terms => {
+            let mut comm = E::G1Projective::zero();
+            for (coeff, cmt) in terms {
+                assert!(cmt.degree_bound().is_none(), "Degree-bounded combination");
+                comm += &cmt.commitment().comm.0.scalar_mul(*coeff);
+            }
+            let comm = marlin_pc::Commitment {
+                comm: kzg10::Commitment(comm.into_affine()),
+                shifted_comm: None,
+            };
+            LabeledCommitment::new("combination".to_owned(), comm, None)
+        }
+    }
+}
+
+/// `sum_i rho^i * proofs_i`: an opening proof of the same combination of polynomials.
+fn fold<'a, E: PairingEngine>(
+    rho: E::Fr,
+    proofs: impl IntoIterator<Item = &'a kzg10::Proof<E>>,
+) -> kzg10::Proof<E> {
+    let mut w = E::G1Projective::zero();
+    let mut random_v = None;
+    let mut coeff = E::Fr::one();
+    for pf in proofs {
+        w += &pf.w.scalar_mul(coeff);
+        if let Some(v) = pf.random_v {
+            *random_v.get_or_insert_with(E::Fr::zero) += coeff * v;
+        }
+        coeff *= rho;
+    }
+    kzg10::Proof {
+        w: w.into_affine(),
+        random_v,
+    }
+}
+
+impl<E: PairingEngine, T: Transcript> Plonk<E::Fr, Kzg<E>, T>
+where
+    marlin_pc::Commitment<E>: mpc_trait::MpcWire,
+{
+    /// A transcript that has absorbed `proofs`, for the challenge that their openings are folded
+    /// with.
+    fn aggregation_transcript(proofs: &[Proof<E::Fr, marlin_pc::Commitment<E>, ()>]) -> T {
+        let mut transcript = T::new(b"plonk_aggregate");
+        for pf in proofs {
+            let mut bytes = Vec::new();
+            pf.serialize(&mut bytes).expect("failed serialization");
+            transcript.absorb_bytes(b"proof", &bytes);
+        }
+        transcript
+    }
+
+    /// Aggregate proofs of one circuit, for [Plonk::verify_aggregated].
+    ///
+    /// Each proof keeps its commitments and evaluations, but its opening proofs are folded into
+    /// one per point.
+    pub fn aggregate(proofs: &[KzgProof<E>]) -> KzgAggregatedProof<E> {
+        let stripped: Vec<_> = proofs
+            .iter()
+            .map(|pf| pf.clone().map_openings(|_| ()))
+            .collect();
+        let rho = Self::aggregation_transcript(&stripped).challenge(b"aggregate_rho");
+        let openings = proofs
+            .iter()
+            .map(|pf| {
+                opening_groups(pf)
+                    .into_iter()
+                    .map(|group| fold(rho, group))
+                    .collect()
+            })
+            .collect();
+        AggregatedProof {
+            proofs: stripped,
+            openings,
+        }
+    }
+
+    /// Verify proofs aggregated by [Plonk::aggregate], each against its own public inputs.
+    ///
+    /// Proofs made with a beacon are not supported.
+    pub fn verify_aggregated(
+        vk: &VerifierKey<marlin_pc::Commitment<E>, marlin_pc::VerifierKey<E>>,
+        circ: &crate::relations::flat::Instance<E::Fr>,
+        pf: KzgAggregatedProof<E>,
+        publics: &[HashMap<String, E::Fr>],
+    ) {
+        if let Err(report) = Self::verify_aggregated_with_report(vk, circ, pf, publics) {
+            panic!("Aggregated verification failed: {}", report);
+        }
+    }
+
+    /// Like [Plonk::verify_aggregated], but returning every check that failed rather than
+    /// panicking, malformed proofs included (see [Plonk::verify_with_report]).
+    ///
+    /// A proof that fails a check of its own has its openings left out of the final check.
+    pub fn verify_aggregated_with_report(
+        vk: &VerifierKey<marlin_pc::Commitment<E>, marlin_pc::VerifierKey<E>>,
+        circ: &crate::relations::flat::Instance<E::Fr>,
+        pf: KzgAggregatedProof<E>,
+        publics: &[HashMap<String, E::Fr>],
+    ) -> Result<(), VerificationReport<E::Fr>> {
+        let mut failures = Vec::new();
+        if pf.proofs.len() != publics.len() {
+            failures.push(Failure::Aggregated {
+                what: "proofs",
+                expected: publics.len(),
+                found: pf.proofs.len(),
+            });
+        }
+        if pf.openings.len() != pf.proofs.len() {
+            failures.push(Failure::Aggregated {
+                what: "proofs' openings",
+                expected: pf.proofs.len(),
+                found: pf.openings.len(),
+            });
+        }
+        if !failures.is_empty() {
+            return Err(VerificationReport { failures });
+        }
+        let mut opening_bytes = Vec::new();
+        pf.openings
+            .serialize(&mut opening_bytes)
+            .expect("failed serialization");
+        let mut transcript = Self::aggregation_transcript(&pf.proofs);
+        let rho: E::Fr = transcript.challenge(b"aggregate_rho");
+        let (mut comms, mut points, mut values, mut opens) =
+            (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        'proofs: for ((proof, openings), public) in
+            pf.proofs.into_iter().zip(pf.openings).zip(publics)
+        {
+            // A deferred verifier never looks at the opening proofs.
+            let proof = proof.map_openings(|()| kzg10::Proof::default());
+            let ver = Verifier::<E::Fr, Kzg<E>, T>::new_deferred_reporting(vk);
+            ver.verify(circ, proof, public);
+            let own = ver.take_failures();
+            if !own.is_empty() {
+                failures.extend(own);
+                continue;
+            }
+            let groups = group_by_point(ver.take_openings());
+            if groups.len() != openings.len() {
+                failures.push(Failure::Aggregated {
+                    what: "openings",
+                    expected: groups.len(),
+                    found: openings.len(),
+                });
+                continue;
+            }
+            let mut claims = Vec::with_capacity(groups.len());
+            for ((x, group), open) in groups.iter().zip(openings) {
+                // The opening proofs are folded with powers of rho, and each batches its claims
+                // with powers of its own challenge. Marlin folds in a degree-bounded
+                // commitment's shift with the next power.
+                let mut comm = E::G1Projective::zero();
+                let mut value = E::Fr::zero();
+                let mut coeff = E::Fr::one();
+                for (queries, challenge) in group {
+                    let mut power = coeff;
+                    for query in queries {
+                        let cmt = combine(query);
+                        comm += &cmt.commitment().comm.0.scalar_mul(power);
+                        value += power * query.2;
+                        power *= challenge;
+                        if let Some(bound) = cmt.degree_bound() {
+                            let shift = vk.pc_vk.get_shift_power(bound);
+                            let shifted = cmt.commitment().shifted_comm.as_ref();
+                            let (shift, shifted) = match (shift, shifted) {
+                                (Some(shift), Some(shifted)) => (shift, shifted.0),
+                                _ => {
+                                    failures.push(Failure::DegreeBound {
+                                        commitment: cmt.label().clone(),
+                                        bound,
+                                    });
+                                    continue 'proofs;
+                                }
+                            };
+                            comm += &(shifted.into_projective() - shift.scalar_mul(query.2))
+                                .mul(power.into_repr());
+                            power *= challenge;
+                        }
+                    }
+                    coeff *= rho;
+                }
+                claims.push((kzg10::Commitment(comm.into_affine()), *x, value, open));
+            }
+            for (comm, x, value, open) in claims {
+                comms.push(comm);
+                points.push(x);
+                values.push(value);
+                opens.push(open);
+            }
+        }
+        // One pairing product for every point of every proof, each claim weighted by a
+        // challenge bound to all the opening proofs.
+        transcript.absorb_bytes(b"openings", &opening_bytes);
+        let mut seed = [0u8; 32];
+        transcript.challenge_bytes(b"batch_check", &mut seed);
+        let checked = kzg10::KZG10::<E, DensePolynomial<E::Fr>>::batch_check(
+            &vk.pc_vk.vk,
+            &comms,
+            &points,
+            &values,
+            &opens,
+            &mut ChaChaRng::from_seed(seed),
+        );
+        if !checked.unwrap_or(false) {
+            failures.push(Failure::BatchedOpenings { count: comms.len() });
+        }
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(VerificationReport { failures })
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//! Where a prover's FFTs and MSMs are computed.
+//!
+//! The coset FFTs of the quotients (see [relations::flat::Domains::quotient]) and the MSMs of
+//! commitments dominate proving time. An [ArithmeticBackend] may compute them in process
+//! ([CpuBackend]), or offload them to an accelerator. The prover runs its FFTs on its backend
+//! (see [Prover::arithmetic_backend]); commitments' MSMs are inside [PolynomialCommitment::commit],
+//! so an [ArithmeticCommitter] computes KZG commitments with a backend's MSM instead.
+//!
+//! In MPC, each party has its own backend, which sees that party's shares (never the witness).
+//!
+//! [relations::flat::Domains::quotient]: crate::relations::flat::Domains::quotient
+//! [Prover::arithmetic_backend]: crate::Prover::arithmetic_backend
+//! [PolynomialCommitment::commit]: ark_poly_commit::PolynomialCommitment::commit
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{FftField, Zero};
+use ark_poly::{
+    univariate::DensePolynomial, EvaluationDomain, Evaluations, Radix2EvaluationDomain,
+};
+use ark_poly_commit::{
+    kzg10, marlin_pc, optional_rng::OptionalRng, BatchLCProof, LabeledCommitment,
+    LabeledPolynomial, LinearCombination, QuerySet,
+};
+use ark_std::rand::RngCore;
+
+use crate::{CommitterBackend, Error, LocalCommitter};
+
+type Kzg<E> = marlin_pc::MarlinKZG10<E, DensePolynomial<<E as PairingEngine>::Fr>>;
+
+/// Computes FFTs over radix-2 domains of `F`, and MSMs with scalars in `F`.
+///
+/// Each method's default computes in process, as [CpuBackend] does. Results must match it
+/// exactly: the prover uses whatever is returned, so a faulty backend yields a proof that does
+/// not verify.
+pub trait ArithmeticBackend<F: FftField>: Sync {
+    /// Replace `coeffs` (padded with zeros to `domain`'s size) by their evaluations over
+    /// `domain`, as [DensePolynomial::evaluate_over_domain] does (so that MPC fields may batch
+    /// it).
+    fn fft_in_place(&self, domain: Radix2EvaluationDomain<F>, coeffs: &mut Vec<F>) {
+        let p = DensePolynomial {
+            coeffs: std::mem::take(coeffs),
+        };
+        *coeffs = p.evaluate_over_domain(domain).evals;
+    }
+
+    /// Replace `evals`, over `domain`, by their polynomial's coefficients (without leading
+    /// zeros), as [Evaluations::interpolate] does.
+    fn ifft_in_place(&self, domain: Radix2EvaluationDomain<F>, evals: &mut Vec<F>) {
+        let e = Evaluations::from_vec_and_domain(std::mem::take(evals), domain);
+        *evals = e.interpolate().coeffs;
+    }
+
+    /// Replace `coeffs` (padded with zeros to `domain`'s size) by their evaluations over
+    /// `domain`'s coset, shifted by the multiplicative generator.
+    fn coset_fft_in_place(&self, domain: Radix2EvaluationDomain<F>, coeffs: &mut Vec<F>) {
+        domain.coset_fft_in_place(coeffs)
+    }
+
+    /// Replace `evals`, over `domain`'s coset, by their polynomial's coefficients.
+    fn coset_ifft_in_place(&self, domain: Radix2EvaluationDomain<F>, evals: &mut Vec<F>) {
+        domain.coset_ifft_in_place(evals)
+    }
+
+    /// `sum_i scalars[i] * bases[i]`, over the shorter of the two.
+    fn msm<G: AffineCurve<ScalarField = F>>(&self, bases: &[G], scalars: &[F]) -> G::Projective
+    where
+        Self: Sized,
+    {
+        G::multi_scalar_mul(bases, scalars)
+    }
+}
+
+/// Computes in process (on a rayon thread pool, with feature `parallel`). The default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CpuBackend;
+
+impl<F: FftField> ArithmeticBackend<F> for CpuBackend {}
+
+/// Commits with KZG, computing commitments' MSMs on an [ArithmeticBackend]; computes everything
+/// else in process.
+///
+/// Blinds are still committed to in process, as their MSMs are only as long as the hiding bound.
+/// Opening proofs are too.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ArithmeticCommitter<B> {
+    backend: B,
+}
+
+impl<B> ArithmeticCommitter<B> {
+    pub fn new(backend: B) -> Self {
+        Self { backend }
+    }
+
+    /// The backend computing commitments' MSMs.
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Whether `p` may be committed to on the backend under `ck`: its powers cover it, and any
+    /// degree bound is one that `ck` supports. Otherwise [PolynomialCommitment::commit] handles
+    /// it (and reports any error).
+    ///
+    /// [PolynomialCommitment::commit]: ark_poly_commit::PolynomialCommitment::commit
+    fn supported<E: PairingEngine>(
+        ck: &marlin_pc::CommitterKey<E>,
+        p: &LabeledPolynomial<E::Fr, DensePolynomial<E::Fr>>,
+    ) -> bool {
+        let len = p.polynomial().coeffs.len();
+        len <= ck.powers.len()
+            && match p.degree_bound() {
+                None => true,
+                Some(d) => {
+                    len <= d + 1
+                        && matches!(&ck.enforced_degree_bounds, Some(bounds) if bounds.contains(&d))
+                        && ck.shifted_powers(d).unwrap().powers_of_g.len() >= len
+                }
+            }
+    }
+}
+
+impl<E: PairingEngine, B: ArithmeticBackend<E::Fr>> CommitterBackend<E::Fr, Kzg<E>>
+    for ArithmeticCommitter<B>
+{
+    fn commit(
+        &self,
+        ck: &marlin_pc::CommitterKey<E>,
+        p: &LabeledPolynomial<E::Fr, DensePolynomial<E::Fr>>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<
+        (
+            LabeledCommitment<marlin_pc::Commitment<E>>,
+            marlin_pc::Randomness<E::Fr, DensePolynomial<E::Fr>>,
+        ),
+        Error<ark_poly_commit::Error>,
+    > {
+        if !Self::supported(ck, p) {
+            return CommitterBackend::<E::Fr, Kzg<E>>::commit(&LocalCommitter, ck, p, rng);
+        }
+        let coeffs = &p.polynomial().coeffs;
+        // The blinds are drawn as PolynomialCommitment::commit does.
+        let rng = &mut OptionalRng(rng);
+        let zero = DensePolynomial::zero();
+        let commit = |powers: &kzg10::Powers<E>, rng: &mut OptionalRng<_>| {
+            let comm = self
+                .backend
+                .msm(&powers.powers_of_g[..coeffs.len()], coeffs);
+            let (blind, rand) = kzg10::KZG10::commit(powers, &zero, p.hiding_bound(), Some(rng))?;
+            let comm = kzg10::Commitment(comm.add_mixed(&blind.0).into_affine());
+            Ok::<_, ark_poly_commit::Error>((comm, rand))
+        };
+        let (comm, rand) = commit(&ck.powers(), rng)?;
+        let (shifted_comm, shifted_rand) = match p.degree_bound() {
+            Some(d) => {
+                let (comm, rand) = commit(&ck.shifted_powers(d).unwrap(), rng)?;
+                (Some(comm), Some(rand))
+            }
+            None => (None, None),
+        };
+        Ok((
+            LabeledCommitment::new(
+                p.label().clone(),
+                marlin_pc::Commitment { comm, shifted_comm },
+                p.degree_bound(),
+            ),
+            marlin_pc::Randomness { rand, shifted_rand },
+        ))
+    }
+
+    fn open(
+        &self,
+        ck: &marlin_pc::CommitterKey<E>,
+        p: &LabeledPolynomial<E::Fr, DensePolynomial<E::Fr>>,
+        p_c: &LabeledCommitment<marlin_pc::Commitment<E>>,
+        x: E::Fr,
+        p_r: &marlin_pc::Randomness<E::Fr, DensePolynomial<E::Fr>>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<kzg10::Proof<E>, Error<ark_poly_commit::Error>> {
+        CommitterBackend::<E::Fr, Kzg<E>>::open(&LocalCommitter, ck, p, p_c, x, p_r, rng)
+    }
+
+    fn open_combinations(
+        &self,
+        ck: &marlin_pc::CommitterKey<E>,
+        lcs: &[LinearCombination<E::Fr>],
+        ps: &[&LabeledPolynomial<E::Fr, DensePolynomial<E::Fr>>],
+        p_cs: &[&LabeledCommitment<marlin_pc::Commitment<E>>],
+        query_set: &QuerySet<E::Fr>,
+        opening_challenge: E::Fr,
+        p_rs: &[&marlin_pc::Randomness<E::Fr, DensePolynomial<E::Fr>>],
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<BatchLCProof<E::Fr, DensePolynomial<E::Fr>, Kzg<E>>, Error<ark_poly_commit::Error>>
+    {
+        CommitterBackend::<E::Fr, Kzg<E>>::open_combinations(
+            &LocalCommitter,
+            ck,
+            lcs,
+            ps,
+            p_cs,
+            query_set,
+            opening_challenge,
+            p_rs,
+            rng,
+        )
+    }
+}
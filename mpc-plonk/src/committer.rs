@@ -0,0 +1,157 @@
+//! Where a prover's commitments (and opening proofs) are computed.
+//!
+//! Both are multi-scalar multiplications against the SRS, and dominate proving time. A
+//! [CommitterBackend] may compute them in process ([LocalCommitter]), or delegate them to another
+//! process, an HSM, or an accelerator holding the SRS. In MPC, each party has its own backend,
+//! which sees that party's shares (never the witness).
+use ark_ff::FftField;
+use ark_poly::univariate::DensePolynomial;
+use ark_poly_commit::{
+    BatchLCProof, LabeledCommitment, LabeledPolynomial, LinearCombination, PolynomialCommitment,
+    QuerySet,
+};
+use ark_std::rand::RngCore;
+use std::iter::once;
+
+use crate::Error;
+
+/// Computes commitments and opening proofs for a prover, under polynomial commitment `PC`.
+///
+/// The prover checks that a backend committed to the polynomials it was given, in order (or
+/// fails with [Error::Backend]), but otherwise uses whatever commitments and proofs are returned,
+/// so a faulty backend yields a proof that does not verify.
+pub trait CommitterBackend<F: FftField, PC: PolynomialCommitment<F, DensePolynomial<F>>> {
+    /// Commit to `p`, as [PolynomialCommitment::commit] does.
+    #[allow(clippy::type_complexity)]
+    fn commit(
+        &self,
+        ck: &PC::CommitterKey,
+        p: &LabeledPolynomial<F, DensePolynomial<F>>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<(LabeledCommitment<PC::Commitment>, PC::Randomness), Error<PC::Error>>;
+
+    /// Commit to each of `ps`, as [PolynomialCommitment::commit] does given them all at once.
+    ///
+    /// The prover commits to polynomials whose commitments are revealed in the same round (see
+    /// [crate::schedule]) in one call, so a backend may combine their MSMs. By default, each is
+    /// committed to in turn, with [CommitterBackend::commit].
+    #[allow(clippy::type_complexity)]
+    fn commit_many(
+        &self,
+        ck: &PC::CommitterKey,
+        ps: &[&LabeledPolynomial<F, DensePolynomial<F>>],
+        mut rng: Option<&mut dyn RngCore>,
+    ) -> Result<Vec<(LabeledCommitment<PC::Commitment>, PC::Randomness)>, Error<PC::Error>> {
+        ps.iter()
+            .map(|p| self.commit(ck, p, rng.as_mut().map(|r| &mut **r as &mut dyn RngCore)))
+            .collect()
+    }
+
+    /// Prove the evaluation of `p` (committed in `p_c`, under randomness `p_r`) at `x`, as
+    /// [PolynomialCommitment::open] does.
+    fn open(
+        &self,
+        ck: &PC::CommitterKey,
+        p: &LabeledPolynomial<F, DensePolynomial<F>>,
+        p_c: &LabeledCommitment<PC::Commitment>,
+        x: F,
+        p_r: &PC::Randomness,
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<PC::Proof, Error<PC::Error>>;
+
+    /// Prove the evaluations of the linear combinations `lcs` of `ps` (committed in `p_cs`, under
+    /// randomness `p_rs`) at the points of `query_set`, as
+    /// [PolynomialCommitment::open_combinations] does.
+    #[allow(clippy::too_many_arguments)]
+    fn open_combinations(
+        &self,
+        ck: &PC::CommitterKey,
+        lcs: &[LinearCombination<F>],
+        ps: &[&LabeledPolynomial<F, DensePolynomial<F>>],
+        p_cs: &[&LabeledCommitment<PC::Commitment>],
+        query_set: &QuerySet<F>,
+        opening_challenge: F,
+        p_rs: &[&PC::Randomness],
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<BatchLCProof<F, DensePolynomial<F>, PC>, Error<PC::Error>>;
+}
+
+/// Computes commitments in process, with the prover key's committer key. The default.
+///
+/// Its [CommitterBackend::commit_many] hands the whole batch to [PolynomialCommitment::commit]:
+/// for `MarlinKZG10`, that is one combined MSM, so each party computes its shares of every
+/// commitment in one pass.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LocalCommitter;
+
+impl<F: FftField, PC: PolynomialCommitment<F, DensePolynomial<F>>> CommitterBackend<F, PC>
+    for LocalCommitter
+where
+    PC::Error: 'static,
+{
+    fn commit(
+        &self,
+        ck: &PC::CommitterKey,
+        p: &LabeledPolynomial<F, DensePolynomial<F>>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<(LabeledCommitment<PC::Commitment>, PC::Randomness), Error<PC::Error>> {
+        let (mut cs, mut rs) = PC::commit(ck, once(p), rng)?;
+        assert_eq!(cs.len(), 1);
+        assert_eq!(rs.len(), 1);
+        Ok((cs.pop().unwrap(), rs.pop().unwrap()))
+    }
+
+    fn commit_many(
+        &self,
+        ck: &PC::CommitterKey,
+        ps: &[&LabeledPolynomial<F, DensePolynomial<F>>],
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<Vec<(LabeledCommitment<PC::Commitment>, PC::Randomness)>, Error<PC::Error>> {
+        let (cs, rs) = PC::commit(ck, ps.iter().copied(), rng)?;
+        assert_eq!(cs.len(), ps.len());
+        assert_eq!(rs.len(), ps.len());
+        Ok(cs.into_iter().zip(rs).collect())
+    }
+
+    fn open(
+        &self,
+        ck: &PC::CommitterKey,
+        p: &LabeledPolynomial<F, DensePolynomial<F>>,
+        p_c: &LabeledCommitment<PC::Commitment>,
+        x: F,
+        p_r: &PC::Randomness,
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<PC::Proof, Error<PC::Error>> {
+        Ok(PC::open(
+            ck,
+            once(p),
+            once(p_c),
+            &x,
+            F::one(), // acceptable b/c this is just one commitment.
+            once(p_r),
+            rng,
+        )?)
+    }
+    fn open_combinations(
+        &self,
+        ck: &PC::CommitterKey,
+        lcs: &[LinearCombination<F>],
+        ps: &[&LabeledPolynomial<F, DensePolynomial<F>>],
+        p_cs: &[&LabeledCommitment<PC::Commitment>],
+        query_set: &QuerySet<F>,
+        opening_challenge: F,
+        p_rs: &[&PC::Randomness],
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<BatchLCProof<F, DensePolynomial<F>, PC>, Error<PC::Error>> {
+        Ok(PC::open_combinations(
+            ck,
+            lcs,
+            ps.iter().copied(),
+            p_cs.iter().copied(),
+            query_set,
+            opening_challenge,
+            p_rs.iter().copied(),
+            rng,
+        )?)
+    }
+}
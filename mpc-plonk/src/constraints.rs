@@ -0,0 +1,366 @@
+//! The verifier's checks as R1CS gadgets, for verifying a proof inside another SNARK whose
+//! constraints are over the proof's scalar field (say, Groth16 over the same curve).
+//!
+//! [PlonkVerifierGadget] enforces every polynomial identity that [Verifier::verify] checks
+//! (public, unit product, and wiring), given the proof's challenges and opened values (see
+//! [Plonk::verifier_inputs]). Two checks are deferred to whoever verifies the outer proof,
+//! natively:
+//! * the transcript: the challenges are drawn from the proof's commitments, which are points over
+//!   another field;
+//! * the openings of those commitments at the challenges (for KZG, pairings), including the
+//!   gates' linearized opening, which holds the gates' identity. They may be accumulated across
+//!   proofs (as [crate::aggregate] does).
+//!
+//! So that the two halves agree, [PlonkVerifierCircuit] makes the challenges and opened values
+//! public inputs of the outer proof.
+//!
+//! [Verifier::verify]: crate::Verifier::verify
+use ark_ff::PrimeField;
+use ark_poly::{univariate::DensePolynomial, EvaluationDomain};
+use ark_poly_commit::PolynomialCommitment;
+use ark_r1cs_std::{fields::fp::FpVar, prelude::*};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, Namespace, SynthesisError};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+
+use crate::relations::flat::Instance;
+use crate::{Plonk, Proof, SubProof, Transcript, Verifier, VerifierKey};
+
+/// What [PlonkVerifierGadget] checks a proof with: its challenges, and the values its openings
+/// claim.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifierInputs<F> {
+    /// The public check's combining challenge, and its point
+    pub public_beta: F,
+    pub public_x: F,
+    /// Each public column's P_j, and the public quotient, at `public_x`
+    pub public_p: Vec<F>,
+    pub public_q: F,
+    /// The unit product proof's point `r`, and its partial products t, the wiring fraction f, and
+    /// quotient q: t(r), t(wr), t(w^{k-1}), f(wr), and q(r)
+    pub product_r: F,
+    pub t_r: F,
+    pub t_wr: F,
+    pub t_wk: F,
+    pub f_wr: F,
+    pub q_r: F,
+    /// The wiring challenges, and the wiring check's point
+    pub wiring_y: F,
+    pub wiring_z: F,
+    pub wiring_x: F,
+    /// Each column's P_j and W_j, L_1, and the wiring quotient, at `wiring_x`
+    pub p_x: Vec<F>,
+    pub w_x: Vec<F>,
+    pub l1_x: F,
+    pub l2_q_x: F,
+}
+
+impl<F: Clone> VerifierInputs<F> {
+    /// Every input, in the order [VerifierInputsVar] allocates them.
+    pub fn to_vec(&self) -> Vec<F> {
+        let mut all = vec![self.public_beta.clone(), self.public_x.clone()];
+        all.extend(self.public_p.iter().cloned());
+        all.extend(vec![
+            self.public_q.clone(),
+            self.product_r.clone(),
+            self.t_r.clone(),
+            self.t_wr.clone(),
+            self.t_wk.clone(),
+            self.f_wr.clone(),
+            self.q_r.clone(),
+            self.wiring_y.clone(),
+            self.wiring_z.clone(),
+            self.wiring_x.clone(),
+        ]);
+        all.extend(self.p_x.iter().cloned());
+        all.extend(self.w_x.iter().cloned());
+        all.push(self.l1_x.clone());
+        all.push(self.l2_q_x.clone());
+        all
+    }
+
+    fn from_vec(all: Vec<F>, public_columns: usize, width: usize) -> Self {
+        let mut all = all.into_iter();
+        let mut next = || all.next().unwrap();
+        Self {
+            public_beta: next(),
+            public_x: next(),
+            public_p: (0..public_columns).map(|_| next()).collect(),
+            public_q: next(),
+            product_r: next(),
+            t_r: next(),
+            t_wr: next(),
+            t_wk: next(),
+            f_wr: next(),
+            q_r: next(),
+            wiring_y: next(),
+            wiring_z: next(),
+            wiring_x: next(),
+            p_x: (0..width).map(|_| next()).collect(),
+            w_x: (0..width).map(|_| next()).collect(),
+            l1_x: next(),
+            l2_q_x: next(),
+        }
+    }
+}
+
+/// [VerifierInputs], allocated.
+pub type VerifierInputsVar<F> = VerifierInputs<FpVar<F>>;
+
+impl<F: PrimeField> AllocVar<VerifierInputs<F>, F> for VerifierInputsVar<F> {
+    fn new_variable<T: Borrow<VerifierInputs<F>>>(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let cs = cs.into().cs();
+        let inputs = f()?;
+        let inputs = inputs.borrow();
+        let vars = inputs
+            .to_vec()
+            .into_iter()
+            .map(|v| FpVar::new_variable(cs.clone(), || Ok(v), mode))
+            .collect::<Result<_, _>>()?;
+        Ok(Self::from_vec(
+            vars,
+            inputs.public_p.len(),
+            inputs.p_x.len(),
+        ))
+    }
+}
+
+impl<F: PrimeField, PC: PolynomialCommitment<F, DensePolynomial<F>>, T: Transcript> Plonk<F, PC, T>
+where
+    PC::Commitment: mpc_trait::MpcWire,
+    PC::Error: 'static,
+{
+    /// The challenges of `pf`, drawn as [Plonk::verify] draws them, and its opened values: what
+    /// [PlonkVerifierGadget] checks it with.
+    ///
+    /// Neither the circuit's digest nor the openings are checked: these stay native (see the
+    /// [module docs](self)).
+    ///
+    /// Panics if `pf` has the wrong number of columns.
+    pub fn verifier_inputs(
+        vk: &VerifierKey<PC::Commitment, PC::VerifierKey>,
+        circ: &Instance<F>,
+        pf: &Proof<F, PC::Commitment, PC::Proof>,
+    ) -> VerifierInputs<F> {
+        let width = circ.domains.width;
+        assert_eq!(pf.p_cmts.len(), width, "Proof is for a different circuit");
+        let ver = Verifier::<F, PC, T>::new(vk);
+        let r = ver.receive(circ, pf);
+        let product = &pf.wiring.l1_prod_pf;
+        VerifierInputs {
+            public_beta: r.beta,
+            public_x: r.points.get(SubProof::Public),
+            public_p: pf.public.p_opens.iter().map(|o| o.0).collect(),
+            public_q: pf.public.q_open.0,
+            product_r: r.points.get(SubProof::UnitProduct),
+            t_r: product.t_r_open.0,
+            t_wr: product.t_wr_open.0,
+            t_wk: product.t_wk_open.0,
+            f_wr: product.f_wr_open.0,
+            q_r: product.q_r_open.0,
+            wiring_y: r.wiring.y,
+            wiring_z: r.wiring.z,
+            wiring_x: r.points.get(SubProof::Wiring),
+            p_x: pf.wiring.p_x_opens.iter().map(|o| o.0).collect(),
+            w_x: pf.wiring.w_x_opens.iter().map(|o| o.0).collect(),
+            l1_x: pf.wiring.l1_x_open.0,
+            l2_q_x: pf.wiring.l2_q_x_open.0,
+        }
+    }
+}
+
+/// The verifier's identities, as constraints (see the [module docs](self)).
+pub struct PlonkVerifierGadget;
+
+impl PlonkVerifierGadget {
+    /// Whether `inputs` satisfy every identity of a proof about `circ`, with `public` inputs.
+    ///
+    /// Panics unless `public` has every public input of `circ`.
+    pub fn verify<F: PrimeField>(
+        circ: &Instance<F>,
+        inputs: &VerifierInputsVar<F>,
+        public: &HashMap<String, FpVar<F>>,
+    ) -> Result<Boolean<F>, SynthesisError> {
+        let width = circ.domains.width;
+        let columns = circ.public_columns();
+        if inputs.public_p.len() != columns.len()
+            || inputs.p_x.len() != width
+            || inputs.w_x.len() != width
+        {
+            return Ok(Boolean::FALSE);
+        }
+        let n = circ.domains.gates.size() as u64;
+        let vanishing = |x: &FpVar<F>| Ok::<_, SynthesisError>(x.pow_by_constant([n])? - F::one());
+
+        // Public: sum_i beta^i (P_j(x) - V_j(x))/Z_j(x) = Q(x), as a fraction
+        let x = &inputs.public_x;
+        let mut power = FpVar::one();
+        let (mut num, mut den) = (FpVar::zero(), FpVar::one());
+        for (j, p) in columns.into_iter().zip(&inputs.public_p) {
+            let mut z = FpVar::one();
+            let mut sum = FpVar::zero();
+            for (var, (xi, wi)) in circ.column_inputs(j) {
+                let yi = public
+                    .get(var)
+                    .unwrap_or_else(|| panic!("Missing public input {}", var));
+                let d = x - *xi;
+                sum += yi * *wi * d.inverse()?;
+                z *= d;
+            }
+            let c = p - &z * sum;
+            num = &num * &z + &power * c * &den;
+            den *= z;
+            power *= &inputs.public_beta;
+        }
+        let public_ok = num.is_eq(&(&inputs.public_q * den))?;
+
+        // Unit product of L_1
+        let partial_ok = (&inputs.t_wr - &inputs.t_r * &inputs.f_wr)
+            .is_eq(&(vanishing(&inputs.product_r)? * &inputs.q_r))?;
+        let total_ok = inputs.t_wk.is_one()?;
+
+        // Wiring: the products over the columns of P_j(x) + yW_j(x) + z and P_j(x) + yk_jx + z
+        let (x, y, z) = (&inputs.wiring_x, &inputs.wiring_y, &inputs.wiring_z);
+        let (mut num, mut den) = (FpVar::one(), FpVar::one());
+        for (j, (p, w)) in inputs.p_x.iter().zip(&inputs.w_x).enumerate() {
+            num *= p + y * w + z;
+            den *= p + y * x * circ.domains.shift(j) + z;
+        }
+        let wiring_ok = (den * &inputs.l1_x - num).is_eq(&(&inputs.l2_q_x * vanishing(x)?))?;
+
+        Boolean::kary_and(&[public_ok, partial_ok, total_ok, wiring_ok])
+    }
+}
+
+/// The constraints that a proof about `circ`, with `inputs`, satisfies the verifier's identities
+/// for the circuit's `public` inputs.
+///
+/// Its public inputs are `public`'s values, by name, then `inputs` (see
+/// [PlonkVerifierCircuit::public_inputs]).
+pub struct PlonkVerifierCircuit<'a, F: PrimeField> {
+    pub circ: &'a Instance<F>,
+    pub inputs: VerifierInputs<F>,
+    pub public: HashMap<String, F>,
+}
+
+impl<'a, F: PrimeField> PlonkVerifierCircuit<'a, F> {
+    fn sorted_public(&self) -> Vec<(&String, &F)> {
+        let mut public: Vec<_> = self.public.iter().collect();
+        public.sort();
+        public
+    }
+
+    /// The public inputs of the constraints, in order.
+    pub fn public_inputs(&self) -> Vec<F> {
+        let mut all: Vec<F> = self.sorted_public().into_iter().map(|(_, v)| *v).collect();
+        all.extend(self.inputs.to_vec());
+        all
+    }
+}
+
+impl<'a, F: PrimeField> ConstraintSynthesizer<F> for PlonkVerifierCircuit<'a, F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let public = self
+            .sorted_public()
+            .into_iter()
+            .map(|(var, v)| Ok((var.clone(), FpVar::new_input(cs.clone(), || Ok(*v))?)))
+            .collect::<Result<_, SynthesisError>>()?;
+        let inputs = VerifierInputsVar::new_input(cs, || Ok(&self.inputs))?;
+        PlonkVerifierGadget::verify(self.circ, &inputs, &public)?.enforce_equal(&Boolean::TRUE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relations::{flat::Witness, structured::PlonkCircuit};
+    use ark_bls12_377::{Bls12_377, Fr};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    type PC = ark_poly_commit::marlin::marlin_pc::MarlinKZG10<Bls12_377, DensePolynomial<Fr>>;
+    type Pl = Plonk<Fr, PC>;
+
+    /// A proof's verifier circuit, for the squaring circuit.
+    fn verifier_circuit(circ: &Instance<Fr>) -> PlonkVerifierCircuit<'_, Fr> {
+        let start = Fr::from(3u64);
+        let steps = 4;
+        let c = PlonkCircuit::<Fr>::new_squaring_circuit(steps, Some(start));
+        let witness = Witness::from_circuit(circ, &c);
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, circ);
+        let pf = Pl::prove(&pk, circ, &witness, &mut ark_std::test_rng());
+        let out = (0..steps).fold(start, |a, _| a * a);
+        let public: HashMap<String, Fr> = vec![("out".to_owned(), out)].into_iter().collect();
+        Pl::verify(&vk, circ, pf.clone(), &public);
+        PlonkVerifierCircuit {
+            circ,
+            inputs: Pl::verifier_inputs(&vk, circ, &pf),
+            public,
+        }
+    }
+
+    fn satisfied(verifier: PlonkVerifierCircuit<'_, Fr>) -> bool {
+        let cs = ConstraintSystem::new_ref();
+        verifier.generate_constraints(cs.clone()).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn verifier_gadget_test() {
+        let circ = Instance::from_circuit(&PlonkCircuit::<Fr>::new_squaring_circuit(4, None));
+        let verifier = verifier_circuit(&circ);
+        let inputs = verifier.inputs.clone();
+        let public = verifier.public.clone();
+        assert!(satisfied(verifier));
+
+        let mut wrong = inputs.clone();
+        wrong.l1_x += Fr::from(1u64);
+        assert!(!satisfied(PlonkVerifierCircuit {
+            circ: &circ,
+            inputs: wrong,
+            public: public.clone(),
+        }));
+        wrong = inputs.clone();
+        wrong.t_wk = Fr::from(2u64);
+        assert!(!satisfied(PlonkVerifierCircuit {
+            circ: &circ,
+            inputs: wrong,
+            public: public.clone(),
+        }));
+        let wrong_public = vec![("out".to_owned(), Fr::from(1u64))]
+            .into_iter()
+            .collect();
+        assert!(!satisfied(PlonkVerifierCircuit {
+            circ: &circ,
+            inputs,
+            public: wrong_public,
+        }));
+    }
+
+    #[test]
+    fn verifier_gadget_groth16_test() {
+        use ark_groth16::{
+            create_random_proof, generate_random_parameters, prepare_verifying_key, verify_proof,
+        };
+        let circ = Instance::from_circuit(&PlonkCircuit::<Fr>::new_squaring_circuit(4, None));
+        let verifier = verifier_circuit(&circ);
+        let public_inputs = verifier.public_inputs();
+        let rng = &mut ark_std::test_rng();
+        let params = generate_random_parameters::<Bls12_377, _, _>(
+            PlonkVerifierCircuit {
+                circ: &circ,
+                inputs: verifier.inputs.clone(),
+                public: verifier.public.clone(),
+            },
+            rng,
+        )
+        .unwrap();
+        let pf = create_random_proof(verifier, &params, rng).unwrap();
+        let pvk = prepare_verifying_key(&params.vk);
+        assert!(verify_proof(&pvk, &pf, &public_inputs).unwrap());
+    }
+}
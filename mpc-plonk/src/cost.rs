@@ -0,0 +1,327 @@
+//! What a collaborative proof will cost, estimated from a circuit's layout alone (see
+//! [Instance::mpc_cost_estimate]), so that a committee can budget preprocessing material
+//! and network time per job before running anything.
+//!
+//! The estimate follows [Prover::prove] step by step, counting what each step asks of the
+//! sharing scheme: products of shared values (each a Beaver triple, with schemes that use them),
+//! inversions (each an inverse pair), and opens. FFTs and MSMs of shares are local. It leaves out
+//! revealing the finished proof.
+//!
+//! Not every product is batched: the wiring fraction's numerator and denominator, and the wiring
+//! quotient, are multiplied one at a time over the quotient domain's coset, each product its own
+//! exchanges, so rounds grow with the circuit. The fraction itself, over the gate domain, is one
+//! batched division.
+//!
+//! [Prover::prove]: crate::Prover::prove
+use ark_ff::FftField;
+use ark_poly::EvaluationDomain;
+
+use crate::prove_schedule;
+use crate::relations::flat::{Instance, GATE_WIDTH};
+
+/// How parties share values, as far as cost goes (see [mpc_algebra::share]).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SharingScheme {
+    /// Additive shares with MACs: Beaver triples, and a MAC check whenever shares are revealed
+    Spdz,
+    /// Additive shares (honest but curious): Beaver triples
+    Additive,
+    /// Shamir shares: products by degree reduction through the king, with no preprocessing
+    Shamir,
+}
+
+/// The parties to a collaborative proof, and how they share values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MpcBackend {
+    pub scheme: SharingScheme,
+    pub parties: usize,
+}
+
+impl MpcBackend {
+    pub fn new(scheme: SharingScheme, parties: usize) -> Self {
+        assert!(
+            parties >= 2,
+            "A collaborative proof has at least two parties"
+        );
+        Self { scheme, parties }
+    }
+
+    /// Shamir's threshold, as [mpc_algebra::share::shamir::threshold] sets it
+    fn threshold(&self) -> usize {
+        (self.parties - 1) / 2
+    }
+}
+
+/// The estimated cost of one collaborative proof, for each party.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MpcCost {
+    /// Products of two shared values, including those inside inversions
+    pub multiplications: usize,
+    /// Beaver triples consumed (see [mpc_algebra::preprocessing]): none with Shamir shares
+    pub triples: usize,
+    /// Random values with their inverses consumed, both shared: none with Shamir shares
+    pub inverse_pairs: usize,
+    /// Exchanges, one after another: broadcasts, and messages to and from the king, as
+    /// [mpc_net::Stats] counts them
+    pub rounds: usize,
+    /// Field elements sent to each other party
+    pub field_elements: usize,
+    /// Group elements sent to each other party: shares of commitments
+    pub group_elements: usize,
+    /// Butterflies in the local FFTs, each a multiplication and two additions of shares
+    pub fft_butterflies: usize,
+}
+
+impl MpcCost {
+    /// Bytes each party sends, with `parties` in all, given the size of a field and a group
+    /// element.
+    pub fn bytes_sent(&self, parties: usize, field_bytes: usize, group_bytes: usize) -> usize {
+        (parties - 1) * (self.field_elements * field_bytes + self.group_elements * group_bytes)
+    }
+}
+
+/// Counts the cost of the sharing scheme's operations, as [mpc_algebra::share::FieldShare]
+/// implements them.
+struct Tally {
+    backend: MpcBackend,
+    cost: MpcCost,
+    /// Whether field values were opened since the last MAC check
+    unchecked: bool,
+}
+
+impl Tally {
+    fn beaver(&self) -> bool {
+        self.backend.scheme != SharingScheme::Shamir
+    }
+
+    /// Open `k` values at once.
+    fn open(&mut self, k: usize) {
+        self.cost.rounds += 1;
+        self.cost.field_elements += k;
+        self.unchecked = true;
+    }
+
+    /// Share `k` random values, each at `degrees` degrees (Shamir only).
+    fn random_sharings(&mut self, k: usize, degrees: usize) {
+        let per_deal = self.backend.parties - self.backend.threshold();
+        self.cost.rounds += 1;
+        self.cost.field_elements += k.div_ceil(per_deal) * degrees;
+    }
+
+    /// Multiply `k` pairs of values at once.
+    fn batch_mul(&mut self, k: usize) {
+        self.cost.multiplications += k;
+        if self.beaver() {
+            self.cost.triples += k;
+            self.open(k);
+            self.open(k);
+        } else {
+            // Masked by double random sharings, then reduced by the king
+            self.random_sharings(k, 2);
+            self.cost.rounds += 2;
+            self.cost.field_elements += k;
+        }
+    }
+
+    /// Multiply `k` pairs of values one at a time.
+    fn mul_each(&mut self, k: usize) {
+        for _ in 0..k {
+            self.batch_mul(1);
+        }
+    }
+
+    /// Invert `k` values at once: mask each by a random value, open, and unmask.
+    fn batch_inv(&mut self, k: usize) {
+        if self.beaver() {
+            self.cost.inverse_pairs += k;
+        } else {
+            self.random_sharings(k, 1);
+        }
+        self.batch_mul(k);
+        self.open(k);
+    }
+
+    /// Divide `k` pairs of values at once: mask each pair by one random value, multiply, and
+    /// open only the masked denominators.
+    fn batch_div(&mut self, k: usize) {
+        if self.beaver() {
+            self.cost.inverse_pairs += k;
+        } else {
+            self.random_sharings(k, 1);
+        }
+        self.batch_mul(2 * k);
+        self.open(k);
+    }
+
+    /// The partial products of `k` values: mask them by random values and their inverses, open,
+    /// multiply in public, and unmask.
+    fn partial_products(&mut self, k: usize) {
+        if self.beaver() {
+            self.cost.inverse_pairs += k + 1;
+        } else {
+            self.random_sharings(k + 1, 1);
+            self.batch_inv(k + 1);
+        }
+        self.batch_mul(k);
+        self.batch_mul(k);
+        self.open(k);
+        self.batch_mul(k);
+        self.batch_inv(k);
+    }
+
+    /// Reveal queued values: `fields` field and `groups` group elements, each kind in its own
+    /// exchange. With SPDZ, every value opened since the last reveal then has its MACs checked.
+    fn flush(&mut self, fields: usize, groups: usize) {
+        if fields > 0 {
+            self.open(fields);
+        }
+        if groups > 0 {
+            self.cost.rounds += 1;
+            self.cost.group_elements += groups;
+        }
+        if self.backend.scheme == SharingScheme::Spdz {
+            // A joint challenge, then the MACs' combination, each committed to and then opened
+            if self.unchecked {
+                self.cost.rounds += 4;
+                self.cost.field_elements += 6;
+                self.unchecked = false;
+            }
+            if groups > 0 {
+                self.cost.rounds += 4;
+                self.cost.field_elements += 5;
+                self.cost.group_elements += 1;
+            }
+        }
+    }
+
+    /// `count` FFTs over a domain of `size`.
+    fn ffts(&mut self, count: usize, size: usize) {
+        self.cost.fft_butterflies += count * size / 2 * size.trailing_zeros() as usize;
+    }
+}
+
+impl<F: FftField> Instance<F> {
+    /// Estimate what proving this circuit with [Prover::prove] costs each party, with
+    /// `backend`'s sharing scheme and parties.
+    ///
+    /// [Prover::prove]: crate::Prover::prove
+    pub fn mpc_cost_estimate(&self, backend: MpcBackend) -> MpcCost {
+        let n = self.domains.gates.size();
+        let width = self.domains.width;
+        let quotient = self.domains.quotient().size();
+        let mut tally = Tally {
+            backend,
+            cost: MpcCost::default(),
+            unchecked: false,
+        };
+
+        // Warmup: each W_j over the gate domain, and its coset
+        tally.ffts(width, n);
+        tally.ffts(width, quotient);
+        // Gates quotient: A(X)B(X), then S(X) and 1 - S(X) times the terms
+        tally.ffts(6, 2 * n);
+        tally.ffts(3, 4 * n);
+        tally.batch_mul(2 * n);
+        // Wiring fraction: the first column's factors are public times shared
+        tally.ffts(width, n);
+        tally.ffts(width, quotient);
+        for _ in 1..width {
+            tally.batch_mul(n);
+            tally.batch_mul(n);
+        }
+        tally.mul_each(2 * (width - 1) * quotient);
+        // L_1 = num / den
+        tally.batch_div(n);
+        tally.ffts(1, n);
+        // Unit product proof of L_1
+        tally.ffts(2, n);
+        tally.partial_products(n);
+        tally.ffts(4, n);
+        tally.mul_each(n);
+        // Wiring quotient
+        tally.ffts(2, quotient);
+        tally.mul_each(quotient);
+
+        // Each commitment is degree-bounded: its KZG commitment, and the shifted one
+        for round in prove_schedule(width).rounds() {
+            tally.flush(0, 2 * round.len());
+        }
+        // The gates' values are published before their opening; then the rest
+        tally.flush(GATE_WIDTH + 1, 0);
+        let product_values = 5;
+        tally.flush(
+            product_values + width + 2 + 1 + self.public_columns().len(),
+            0,
+        );
+        tally.cost
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relations::structured::PlonkCircuit;
+    use ark_bls12_377::Fr;
+
+    fn layout(steps: usize) -> Instance<Fr> {
+        Instance::from_circuit(&PlonkCircuit::<Fr>::new_squaring_circuit(steps, None))
+    }
+
+    #[test]
+    fn cost_scales_with_circuit_test() {
+        let spdz = MpcBackend::new(SharingScheme::Spdz, 3);
+        let small = layout(8).mpc_cost_estimate(spdz);
+        let large = layout(16).mpc_cost_estimate(spdz);
+        assert_eq!(small.triples, small.multiplications);
+        assert_eq!(large.triples, 2 * small.triples);
+        assert_eq!(large.inverse_pairs - 1, 2 * (small.inverse_pairs - 1));
+        assert!(large.rounds > small.rounds);
+        assert!(large.fft_butterflies > 2 * small.fft_butterflies);
+    }
+
+    #[test]
+    fn cost_by_scheme_test() {
+        let c = layout(8);
+        let spdz = c.mpc_cost_estimate(MpcBackend::new(SharingScheme::Spdz, 3));
+        let additive = c.mpc_cost_estimate(MpcBackend::new(SharingScheme::Additive, 3));
+        let shamir = c.mpc_cost_estimate(MpcBackend::new(SharingScheme::Shamir, 3));
+        // MAC checks cost SPDZ rounds, not triples
+        assert_eq!(spdz.triples, additive.triples);
+        assert!(spdz.rounds > additive.rounds);
+        // Shamir needs no preprocessing, but exchanges more
+        assert_eq!((shamir.triples, shamir.inverse_pairs), (0, 0));
+        assert!(shamir.multiplications > additive.multiplications);
+        assert!(shamir.rounds > additive.rounds);
+        assert_eq!(shamir.fft_butterflies, additive.fft_butterflies);
+        assert_eq!(
+            spdz.bytes_sent(3, 32, 48),
+            2 * (spdz.field_elements * 32 + spdz.group_elements * 48)
+        );
+    }
+
+    #[test]
+    fn cost_matches_measured_test() {
+        use ark_ff::Zero;
+        use ark_serialize::CanonicalSerialize;
+        let parties = 3;
+        // The estimate counts inverse pairs as preprocessed, not made from PRSS masks
+        let (_, circ, measured, _) =
+            crate::tests::simulated_proof(parties, false, |_, stats| stats);
+        let estimate = circ.mpc_cost_estimate(MpcBackend::new(SharingScheme::Additive, parties));
+        let field_bytes = Fr::zero().serialized_size();
+        let group_bytes = ark_bls12_377::G1Affine::zero().serialized_size();
+        let elements = estimate.bytes_sent(parties, field_bytes, group_bytes);
+        for stats in measured {
+            // Each exchange of several elements also sends their count
+            let framing = 8 * stats.rounds() * (parties - 1);
+            if cfg!(debug_assertions) {
+                // Debug builds check each unbatched product, revealing it and its factors
+                assert!(estimate.rounds < stats.rounds());
+                assert!(elements < stats.bytes_sent);
+            } else {
+                assert_eq!(estimate.rounds, stats.rounds());
+                assert!((elements..=elements + framing).contains(&stats.bytes_sent));
+            }
+        }
+    }
+}
@@ -4,41 +4,48 @@
 use ark_ff::Field;
 use ark_poly::univariate::DensePolynomial;
 use ark_poly_commit::{LabeledCommitment, LabeledPolynomial, PCCommitment};
-use mpc_trait::{struct_mpc_wire_impl, MpcWire};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_std::io::{Read, Write};
+use blake2::{Blake2s, Digest};
+use mpc_trait::{MpcWire, Reveal};
+use std::collections::HashMap;
 
-/// Check that S(X)*(P(X) + P(wX)) + (1-S(X))*P(X)*P(WX) - P(WWX) = Q(X)*Z(X)
+use crate::relations::flat::CircuitDigest;
+
+/// Check that S(X)*(P_0(X) + P_1(X)) + (1-S(X))*P_0(X)*P_1(X) - P_2(X) = Q(X)*Z(X)
 /// where Z vanishes on the gate domain, and Q is existential
-#[derive(Clone)]
-pub struct GateProof<C, O> {
+///
+/// Linearized: with a = P_0(x), b = P_1(x), c = P_2(x), and Q(x) sent, the identity at x is that
+/// the linearization polynomial R(X) = (a + b - ab)*S(X) takes value c - ab + Z(x)*Q(x) at x. R,
+/// Q, and the P_j are opened together by `open`, so S(x) is not sent. Q is opened alone, since
+/// its commitment is degree-bounded.
+#[derive(Clone, MpcWire, Reveal)]
+pub struct GateProof<F, C, O> {
     /// Q commitment
     pub q_cmt: C,
-    /// S(x) proof
-    pub s_open: O,
-    /// Q(x) proof
-    pub q_open: O,
-    /// P(x) proof
-    pub p_open: O,
-    /// P(w*x) proof
-    pub p_w_open: O,
-    /// P(w*w*x) proof
-    pub p_w2_open: O,
+    /// P_j(x), for each column the gates read
+    pub p_values: Vec<F>,
+    /// Q(x)
+    pub q_value: F,
+    /// Proof of R(x), Q(x), and the P_j(x)
+    pub open: O,
 }
 
-/// Check that P(X) agree with v(X) for the public wires
-/// via P(X) - v(X) = Q(X)*Z(X)
-/// where Z vanishes on the public wires
-#[derive(Clone)]
+/// Check that each public column's P_j(X) agrees with v_j(X) on its public wires
+/// via sum_i beta^i (P_j(X) - v_j(X))/Z_j(X) = Q(X), over the public columns j
+/// where Z_j vanishes on column j's public wires
+#[derive(Clone, MpcWire, Reveal)]
 pub struct PublicProof<C, O> {
     /// Q commitment
     pub q_cmt: C,
     /// Q(x) proof
     pub q_open: O,
-    /// P(x) proof
-    pub p_open: O,
+    /// P_j(x) proof, for each public column
+    pub p_opens: Vec<O>,
 }
 
 /// Proof that some polynomial f has a product pi over a domain
-#[derive(Clone)]
+#[derive(Clone, MpcWire, Reveal)]
 pub struct ProductProof<C, O> {
     /// t (partial products) commitment
     pub t_cmt: C,
@@ -56,22 +63,22 @@ pub struct ProductProof<C, O> {
     pub q_r_open: O,
 }
 
-/// Check that P(X) = P(W(X)) on the wires
-/// via P(X) - v(X) = Q(X)*Z(X)
-/// where Z vanishes on the public wires
-#[derive(Clone)]
+/// Check that each wire's value is the next wire's in its cycle
+/// via L_1(X) * prod_j (P_j(X) + y*k_j*X + z) - prod_j (P_j(X) + y*W_j(X) + z) = Q(X)*Z(X)
+/// where k_j is column j's coset shift, Z vanishes on the gate domain, and L_1 multiplies to 1
+#[derive(Clone, MpcWire, Reveal)]
 pub struct WiringProof<C, O> {
     /// commitment to L_1
     pub l1_cmt: C,
     /// proof that L_1 multiplies to 1
-    /// over the wire wire domain
+    /// over the gate domain
     pub l1_prod_pf: ProductProof<C, O>,
-    /// commitment to L_2's quotient over the wire domain
+    /// commitment to L_2's quotient over the gate domain
     pub l2_q_cmt: C,
-    /// p(x) openning
-    pub p_x_open: O,
-    /// w(x) openning
-    pub w_x_open: O,
+    /// p_j(x) openning, for each column
+    pub p_x_opens: Vec<O>,
+    /// w_j(x) openning, for each column
+    pub w_x_opens: Vec<O>,
     /// L_1(x) openning
     pub l1_x_open: O,
     /// L_2(x) openning
@@ -79,78 +86,429 @@ pub struct WiringProof<C, O> {
 }
 
 /// Plonk proof
-#[derive(Clone)]
+#[derive(Clone, MpcWire, Reveal)]
 pub struct Proof<F, C, O> {
-    /// Commitment to P
-    pub p_cmt: C,
+    /// Commitment to each column's P_j
+    pub p_cmts: Vec<C>,
     /// Proof of wiring
     pub wiring: WiringProof<C, (F, O)>,
     /// Proof of gates
-    pub gates: GateProof<C, (F, O)>,
+    pub gates: GateProof<F, C, O>,
     /// Proof of gates
     pub public: PublicProof<C, (F, O)>,
+    /// Round of the randomness beacon mixed into the Fiat-Shamir seed, if any
+    pub beacon_round: Option<u64>,
+}
+
+impl<F, C, O> GateProof<F, C, O> {
+    fn values_mut(&mut self) -> Vec<&mut F> {
+        let mut values: Vec<&mut F> = self.p_values.iter_mut().collect();
+        values.push(&mut self.q_value);
+        values
+    }
+}
+
+impl<F, C, O> PublicProof<C, (F, O)> {
+    fn values_mut(&mut self) -> Vec<&mut F> {
+        let mut values = vec![&mut self.q_open.0];
+        values.extend(self.p_opens.iter_mut().map(|o| &mut o.0));
+        values
+    }
+}
+
+impl<F, C, O> ProductProof<C, (F, O)> {
+    fn values_mut(&mut self) -> Vec<&mut F> {
+        vec![
+            &mut self.t_wk_open.0,
+            &mut self.t_r_open.0,
+            &mut self.t_wr_open.0,
+            &mut self.f_wr_open.0,
+            &mut self.q_r_open.0,
+        ]
+    }
+}
+
+impl<F, C, O> WiringProof<C, (F, O)> {
+    fn values_mut(&mut self) -> Vec<&mut F> {
+        let mut values = self.l1_prod_pf.values_mut();
+        values.extend(self.p_x_opens.iter_mut().map(|o| &mut o.0));
+        values.extend(self.w_x_opens.iter_mut().map(|o| &mut o.0));
+        values.extend(vec![&mut self.l1_x_open.0, &mut self.l2_q_x_open.0]);
+        values
+    }
+}
+
+impl<F, C, O> Proof<F, C, O> {
+    /// The opened values.
+    pub(crate) fn values_mut(&mut self) -> Vec<&mut F> {
+        let mut values = self.wiring.values_mut();
+        values.extend(self.gates.values_mut());
+        values.extend(self.public.values_mut());
+        values
+    }
+}
+
+/// Plonk proof with one quotient for every check (see [crate::Plonk::prove_small])
+///
+/// Checks that Q(X) = sum_i alpha^i C_i(X)/Z_i(X), for the constraints C_i of the public, gate,
+/// partial product, and wiring checks (each over its own vanishing polynomial Z_i), with all
+/// openings at x or w*x, but for t's at w^{k-1}.
+#[derive(Clone, MpcWire, Reveal)]
+pub struct SmallProof<F, C, O> {
+    /// Commitment to each column's P_j
+    pub p_cmts: Vec<C>,
+    /// Commitment to L_1
+    pub l1_cmt: C,
+    /// Commitment to t (partial products of L_1)
+    pub t_cmt: C,
+    /// Commitments to Q, in a chunk of the gate domain's size per column
+    pub q_cmts: Vec<C>,
+    /// P_j(x) proof, for each column
+    pub p_opens: Vec<(F, O)>,
+    /// S(x) proof
+    pub s_open: (F, O),
+    /// W_j(x) proof, for each column
+    pub w_opens: Vec<(F, O)>,
+    /// L_1(x) proof
+    pub l1_open: (F, O),
+    /// L_1(w*x) proof
+    pub l1_w_open: (F, O),
+    /// t(x) proof
+    pub t_open: (F, O),
+    /// t(w*x) proof
+    pub t_w_open: (F, O),
+    /// t(w^{k-1}) proof
+    pub t_wk_open: (F, O),
+    /// Proof of each chunk of Q at x
+    pub q_opens: Vec<(F, O)>,
+    /// Round of the randomness beacon mixed into the Fiat-Shamir seed, if any
+    pub beacon_round: Option<u64>,
+}
+
+impl<F, C, O> SmallProof<F, C, O> {
+    /// The opened values.
+    pub(crate) fn values_mut(&mut self) -> Vec<&mut F> {
+        let mut values: Vec<&mut F> = self.p_opens.iter_mut().map(|o| &mut o.0).collect();
+        values.push(&mut self.s_open.0);
+        values.extend(self.w_opens.iter_mut().map(|o| &mut o.0));
+        values.extend(vec![
+            &mut self.l1_open.0,
+            &mut self.l1_w_open.0,
+            &mut self.t_open.0,
+            &mut self.t_w_open.0,
+            &mut self.t_wk_open.0,
+        ]);
+        values.extend(self.q_opens.iter_mut().map(|o| &mut o.0));
+        values
+    }
+}
+
+/// An output of a public randomness beacon (e.g., a drand round).
+///
+/// Mixing it into the Fiat-Shamir seed makes the challenges unpredictable, even to a fully
+/// colluding set of provers, until the beacon publishes that round.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Beacon {
+    /// Beacon round
+    pub round: u64,
+    /// Beacon output for that round
+    pub value: Vec<u8>,
+}
+
+/// What a proof is bound to besides its circuit: its transcript begins with the session, so
+/// the verifier must rebuild the same one, or every challenge differs.
+///
+/// A nonce fresh to each session (say, one the verifier chose) keeps a proof from being
+/// replayed in another, and naming the enclosing protocol in it keeps a proof made for one
+/// protocol from counting in another. Binding the public inputs too fixes them before any
+/// challenge is drawn.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Session {
+    pub nonce: Vec<u8>,
+    /// The public inputs' digest (see [Session::public_digest]), if they are bound
+    pub public: Option<[u8; 32]>,
+}
+
+impl Session {
+    pub fn new(nonce: impl Into<Vec<u8>>) -> Self {
+        Self {
+            nonce: nonce.into(),
+            public: None,
+        }
+    }
+
+    /// This session, with `public` bound too.
+    pub fn with_public<F: Field>(self, public: &HashMap<String, F>) -> Self {
+        Self {
+            public: Some(Self::public_digest(public)),
+            ..self
+        }
+    }
+
+    /// A Blake2s hash of `public`, by name, in name order. The values must be public: under
+    /// MPC, reveal them first (see
+    /// [PlonkCircuit::reveal_public_outputs](crate::relations::structured::PlonkCircuit::reveal_public_outputs)).
+    pub fn public_digest<F: Field>(public: &HashMap<String, F>) -> [u8; 32] {
+        let mut names: Vec<&String> = public.keys().collect();
+        names.sort_unstable();
+        let mut hasher = Blake2s::new();
+        for name in names {
+            let value = ark_ff::to_bytes![public[name]].expect("failed serialization");
+            for bytes in [name.as_bytes(), &value[..]] {
+                hasher.update((bytes.len() as u64).to_le_bytes());
+                hasher.update(bytes);
+            }
+        }
+        hasher.finalize().into()
+    }
+}
+
+/// Plonk proof of a circuit split into chunks
+#[derive(Clone, MpcWire, Reveal)]
+pub struct ChunkedProof<F, C, O> {
+    /// Proof of each chunk
+    pub chunks: Vec<Proof<F, C, O>>,
+    /// Commitments to the links between chunks
+    pub links: Vec<F>,
+}
+
+/// Plonk proofs of one circuit, with the opening proofs of each aggregated (see
+/// [crate::Plonk::aggregate])
+#[derive(Clone)]
+pub struct AggregatedProof<F, C, O> {
+    /// Each proof, with evaluations but without opening proofs
+    pub proofs: Vec<Proof<F, C, ()>>,
+    /// For each proof, one opening proof per evaluation point
+    pub openings: Vec<Vec<O>>,
+}
+
+impl<F, C, O> GateProof<F, C, O> {
+    /// Apply `f` to the opening.
+    pub fn map_openings<O2>(self, mut f: impl FnMut(O) -> O2) -> GateProof<F, C, O2> {
+        GateProof {
+            q_cmt: self.q_cmt,
+            p_values: self.p_values,
+            q_value: self.q_value,
+            open: f(self.open),
+        }
+    }
+}
+
+impl<C, O> PublicProof<C, O> {
+    /// Apply `f` to each opening.
+    pub fn map_openings<O2>(self, mut f: impl FnMut(O) -> O2) -> PublicProof<C, O2> {
+        PublicProof {
+            q_cmt: self.q_cmt,
+            q_open: f(self.q_open),
+            p_opens: self.p_opens.into_iter().map(&mut f).collect(),
+        }
+    }
+}
+
+impl<C, O> ProductProof<C, O> {
+    /// Apply `f` to each opening.
+    pub fn map_openings<O2>(self, mut f: impl FnMut(O) -> O2) -> ProductProof<C, O2> {
+        ProductProof {
+            t_cmt: self.t_cmt,
+            q_cmt: self.q_cmt,
+            t_wk_open: f(self.t_wk_open),
+            t_r_open: f(self.t_r_open),
+            t_wr_open: f(self.t_wr_open),
+            f_wr_open: f(self.f_wr_open),
+            q_r_open: f(self.q_r_open),
+        }
+    }
+}
+
+impl<C, O> WiringProof<C, O> {
+    /// Apply `f` to each opening.
+    pub fn map_openings<O2>(self, mut f: impl FnMut(O) -> O2) -> WiringProof<C, O2> {
+        WiringProof {
+            l1_cmt: self.l1_cmt,
+            l1_prod_pf: self.l1_prod_pf.map_openings(&mut f),
+            l2_q_cmt: self.l2_q_cmt,
+            p_x_opens: self.p_x_opens.into_iter().map(&mut f).collect(),
+            w_x_opens: self.w_x_opens.into_iter().map(&mut f).collect(),
+            l1_x_open: f(self.l1_x_open),
+            l2_q_x_open: f(self.l2_q_x_open),
+        }
+    }
+}
+
+impl<F, C, O> Proof<F, C, O> {
+    /// Apply `f` to each opening proof, keeping the evaluations.
+    pub fn map_openings<O2>(self, mut f: impl FnMut(O) -> O2) -> Proof<F, C, O2> {
+        Proof {
+            p_cmts: self.p_cmts,
+            wiring: self.wiring.map_openings(|(v, o)| (v, f(o))),
+            gates: self.gates.map_openings(&mut f),
+            public: self.public.map_openings(|(v, o)| (v, f(o))),
+            beacon_round: self.beacon_round,
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct ProverKey<F: Field, C: PCCommitment, PcCk> {
-    pub w: LabeledPolynomial<F, DensePolynomial<F>>,
-    pub w_cmt: LabeledCommitment<C>,
+    /// Each column's wiring permutation polynomial
+    pub w: Vec<LabeledPolynomial<F, DensePolynomial<F>>>,
+    pub w_cmts: Vec<LabeledCommitment<C>>,
     pub s: LabeledPolynomial<F, DensePolynomial<F>>,
     pub s_cmt: LabeledCommitment<C>,
+    /// The digest of the circuit's layout (see [Instance::digest])
+    ///
+    /// [Instance::digest]: crate::relations::flat::Instance::digest
+    pub circuit_digest: CircuitDigest,
     pub pc_ck: PcCk,
 }
 
 #[derive(Clone)]
 pub struct VerifierKey<C: PCCommitment, PcVk> {
-    pub w_cmt: LabeledCommitment<C>,
+    /// Commitment to each column's wiring permutation polynomial
+    pub w_cmts: Vec<LabeledCommitment<C>>,
     pub s_cmt: LabeledCommitment<C>,
+    /// The digest of the circuit's layout (see [Instance::digest])
+    ///
+    /// [Instance::digest]: crate::relations::flat::Instance::digest
+    pub circuit_digest: CircuitDigest,
     pub pc_vk: PcVk,
 }
 
-impl<C: MpcWire, O: MpcWire> MpcWire for GateProof<C, O> {
-    struct_mpc_wire_impl!(GateProof<C, O>;
-        (C, q_cmt), (O, s_open), (O, q_open), (O, p_open), (O, p_w_open), (O, p_w2_open));
+/// Serialize each field, in order.
+macro_rules! struct_serialize_impl {
+    ($name:ident<$($g:ident),*>; $($field:ident),*) => {
+        impl<$($g: CanonicalSerialize),*> CanonicalSerialize for $name<$($g),*> {
+            fn serialize<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+                $(self.$field.serialize(&mut writer)?;)*
+                Ok(())
+            }
+            fn serialized_size(&self) -> usize {
+                0 $(+ self.$field.serialized_size())*
+            }
+        }
+        impl<$($g: CanonicalDeserialize),*> CanonicalDeserialize for $name<$($g),*> {
+            fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+                Ok(Self {
+                    $($field: CanonicalDeserialize::deserialize(&mut reader)?,)*
+                })
+            }
+        }
+    };
 }
 
-impl<C: MpcWire, O: MpcWire> MpcWire for PublicProof<C, O> {
-    struct_mpc_wire_impl!(PublicProof<C, O>;
-        (C, q_cmt), (O, q_open), (O, p_open));
-}
-impl<C: MpcWire, O: MpcWire> MpcWire for ProductProof<C, O> {
-    struct_mpc_wire_impl!(ProductProof<C, O>;
-        (C, q_cmt), (C, t_cmt), (O, t_wk_open), (O, t_r_open), (O, t_wr_open), (O, f_wr_open), (O, q_r_open));
-}
+struct_serialize_impl!(GateProof<F, C, O>; q_cmt, p_values, q_value, open);
+struct_serialize_impl!(PublicProof<C, O>; q_cmt, q_open, p_opens);
+struct_serialize_impl!(ProductProof<C, O>; t_cmt, q_cmt, t_wk_open, t_r_open, t_wr_open, f_wr_open, q_r_open);
+struct_serialize_impl!(WiringProof<C, O>; l1_cmt, l1_prod_pf, l2_q_cmt, p_x_opens, w_x_opens, l1_x_open, l2_q_x_open);
+struct_serialize_impl!(Proof<F, C, O>; p_cmts, wiring, gates, public, beacon_round);
+struct_serialize_impl!(AggregatedProof<F, C, O>; proofs, openings);
+struct_serialize_impl!(SmallProof<F, C, O>; p_cmts, l1_cmt, t_cmt, q_cmts, p_opens, s_open,
+    w_opens, l1_open, l1_w_open, t_open, t_w_open, t_wk_open, q_opens, beacon_round);
 
-impl<C: MpcWire, O: MpcWire> MpcWire for WiringProof<C, O> {
-    struct_mpc_wire_impl!(WiringProof<C, O>;
-        (C, l1_cmt), (ProductProof<C, O>, l1_prod_pf), (C, l2_q_cmt), (O, p_x_open), (O, w_x_open), (O, l1_x_open), (O, l2_q_x_open));
+// As for [VerifierKey], only the polynomials and commitments are written, not their labels.
+impl<F: Field, C: PCCommitment, PcCk: CanonicalSerialize> CanonicalSerialize
+    for ProverKey<F, C, PcCk>
+{
+    fn serialize<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        (self.w.len() as u64).serialize(&mut writer)?;
+        for (w, w_cmt) in self.w.iter().zip(&self.w_cmts) {
+            w.polynomial().serialize(&mut writer)?;
+            w_cmt.commitment().serialize(&mut writer)?;
+        }
+        self.s.polynomial().serialize(&mut writer)?;
+        self.s_cmt.commitment().serialize(&mut writer)?;
+        self.circuit_digest.serialize(&mut writer)?;
+        self.pc_ck.serialize(&mut writer)
+    }
+    fn serialized_size(&self) -> usize {
+        (self.w.len() as u64).serialized_size()
+            + self
+                .w
+                .iter()
+                .zip(&self.w_cmts)
+                .map(|(w, w_cmt)| {
+                    w.polynomial().serialized_size() + w_cmt.commitment().serialized_size()
+                })
+                .sum::<usize>()
+            + self.s.polynomial().serialized_size()
+            + self.s_cmt.commitment().serialized_size()
+            + self.circuit_digest.serialized_size()
+            + self.pc_ck.serialized_size()
+    }
 }
 
-impl<F: MpcWire, C: MpcWire, O: MpcWire> MpcWire for Proof<F, C, O> {
-    struct_mpc_wire_impl!(Proof<F, PC>;
-        (C, p_cmt),
-        (WiringProof<C, (F, O)>, wiring),
-        (GateProof<C, (F, O)>, gates),
-        (PublicProof<C, (F, O)>, public)
-    );
+impl<F: Field, C: PCCommitment, PcCk: CanonicalDeserialize> CanonicalDeserialize
+    for ProverKey<F, C, PcCk>
+{
+    fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        let columns = u64::deserialize(&mut reader)?;
+        let mut w = Vec::new();
+        let mut w_cmts = Vec::new();
+        for j in 0..columns as usize {
+            let label = crate::column_label("w", j);
+            let poly = DensePolynomial::deserialize(&mut reader)?;
+            w.push(LabeledPolynomial::new(label.clone(), poly, None, None));
+            w_cmts.push(LabeledCommitment::new(
+                label,
+                C::deserialize(&mut reader)?,
+                None,
+            ));
+        }
+        let s = DensePolynomial::deserialize(&mut reader)?;
+        let s_cmt = C::deserialize(&mut reader)?;
+        Ok(Self {
+            w,
+            w_cmts,
+            s: LabeledPolynomial::new("s".into(), s, None, None),
+            s_cmt: LabeledCommitment::new("s".into(), s_cmt, None),
+            circuit_digest: CircuitDigest::deserialize(&mut reader)?,
+            pc_ck: PcCk::deserialize(&mut reader)?,
+        })
+    }
 }
 
-// impl<F: MpcWire, C: MpcWire, PcCk: MpcWire> MpcWire for ProverKey<F, C, PcCk> {
-//     struct_mpc_wire_impl!(ProverKey<F, C, PcCk>;
-//         (C, p_cmt),
-//         (WiringProof<C, (F, O)>, wiring),
-//         (GateProof<C, (F, O)>, gates),
-//         (PublicProof<C, (F, O)>, public)
-//     );
-// }
-// 
-// impl<F: Reveal, C: Reveal, O: Reveal> Reveal for Proof<F, C, O> {
-//     type Base = Proof<F::Base, C::Base, O::Base>;
-//     struct_reveal_impl!(Proof<F, PC>, Proof;
-//         (C, p_cmt),
-//         (WiringProof<C, (F, O)>, wiring),
-//         (GateProof<C, (F, O)>, gates),
-//         (PublicProof<C, (F, O)>, public)
-//     );
-// }
+// Labels are fixed (see [crate::setup]), so only the commitments are written.
+impl<C: PCCommitment, PcVk: CanonicalSerialize> CanonicalSerialize for VerifierKey<C, PcVk> {
+    fn serialize<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        (self.w_cmts.len() as u64).serialize(&mut writer)?;
+        for w_cmt in &self.w_cmts {
+            w_cmt.commitment().serialize(&mut writer)?;
+        }
+        self.s_cmt.commitment().serialize(&mut writer)?;
+        self.circuit_digest.serialize(&mut writer)?;
+        self.pc_vk.serialize(&mut writer)
+    }
+    fn serialized_size(&self) -> usize {
+        (self.w_cmts.len() as u64).serialized_size()
+            + self
+                .w_cmts
+                .iter()
+                .map(|w_cmt| w_cmt.commitment().serialized_size())
+                .sum::<usize>()
+            + self.s_cmt.commitment().serialized_size()
+            + self.circuit_digest.serialized_size()
+            + self.pc_vk.serialized_size()
+    }
+}
 
+impl<C: PCCommitment, PcVk: CanonicalDeserialize> CanonicalDeserialize for VerifierKey<C, PcVk> {
+    fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        let columns = u64::deserialize(&mut reader)?;
+        let mut w_cmts = Vec::new();
+        for j in 0..columns as usize {
+            let w_cmt = C::deserialize(&mut reader)?;
+            w_cmts.push(LabeledCommitment::new(
+                crate::column_label("w", j),
+                w_cmt,
+                None,
+            ));
+        }
+        let s_cmt = C::deserialize(&mut reader)?;
+        Ok(Self {
+            w_cmts,
+            s_cmt: LabeledCommitment::new("s".into(), s_cmt, None),
+            circuit_digest: CircuitDigest::deserialize(&mut reader)?,
+            pc_vk: PcVk::deserialize(&mut reader)?,
+        })
+    }
+}
@@ -0,0 +1,74 @@
+//! Serialized proofs, keys, and circuit layouts, tagged with a format version.
+//!
+//! * Version 1 proofs used a Blake2s Fiat-Shamir transcript without labels.
+//! * Version 2 proofs used a labeled [crate::Transcript], as later versions do.
+//! * Version 3 proofs open the gate sub-proof's linearization (see [crate::GateProof]), so their
+//!   gate proofs are laid out differently.
+//! * Version 4 lays the wires out in columns (see [crate::relations::flat::Instance]), with
+//!   a polynomial, and commitment, per column, so keys and circuit layouts changed too.
+//! * Version 5 keys hold their circuit's digest (see
+//!   [crate::relations::flat::Instance::digest]), which proofs' transcripts begin with.
+//! * Version 6 proofs commit to every polynomial with a degree bound (for KZG, with a shifted
+//!   commitment too), and send the gates quotient's value (see [crate::GateProof]); keys are
+//!   trimmed for those bounds.
+//! * Version 7 proofs' transcripts begin with the format version and a [crate::Session], which
+//!   binds them to a nonce and, optionally, their public inputs. Keys are as in version 6.
+//!
+//! Keys were the same in versions 1 to 3: their wiring permutation is over a single interleaved
+//! wire polynomial, which version 4 has no use for. Version 4 keys lack the digest that version
+//! 5 proofs are bound to, and version 5 keys the degree bounds that version 6 proofs enforce.
+//!
+//! With the `compat` feature, version 6 is still accepted, so that verifiers can be upgraded
+//! before the provers are: its keys and proofs are laid out as in version 7, and its proofs are
+//! verified with their own transcript (see [crate::V6Transcript]). Earlier versions are laid out
+//! differently, and rejected.
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_std::io::{Read, Write};
+use thiserror::Error;
+
+/// The format version we write.
+pub const FORMAT_VERSION: u8 = 7;
+
+/// The oldest format version we read.
+#[cfg(feature = "compat")]
+pub const OLDEST_FORMAT_VERSION: u8 = 6;
+/// The oldest format version we read.
+#[cfg(not(feature = "compat"))]
+pub const OLDEST_FORMAT_VERSION: u8 = FORMAT_VERSION;
+
+#[derive(Error, Debug)]
+pub enum FormatError {
+    #[error("Unsupported format version {0}")]
+    UnsupportedVersion(u8),
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] SerializationError),
+}
+
+/// A value, with the format version it was written in.
+#[derive(Clone)]
+pub struct Versioned<T> {
+    pub version: u8,
+    pub value: T,
+}
+
+/// Write `t` (e.g., a proof or verifier key) in the current format version.
+pub fn write_versioned<T: CanonicalSerialize, W: Write>(
+    t: &T,
+    mut writer: W,
+) -> Result<(), FormatError> {
+    FORMAT_VERSION.serialize(&mut writer)?;
+    t.serialize(&mut writer)?;
+    Ok(())
+}
+
+/// Read a `T` (e.g., a proof or verifier key) in any supported format version.
+pub fn read_versioned<T: CanonicalDeserialize, R: Read>(
+    mut reader: R,
+) -> Result<Versioned<T>, FormatError> {
+    let version = u8::deserialize(&mut reader)?;
+    if !(OLDEST_FORMAT_VERSION..=FORMAT_VERSION).contains(&version) {
+        return Err(FormatError::UnsupportedVersion(version));
+    }
+    let value = T::deserialize(&mut reader)?;
+    Ok(Versioned { version, value })
+}
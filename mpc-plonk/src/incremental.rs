@@ -0,0 +1,297 @@
+//! Incremental (KZG) commitments, for proving successive statements about a mostly-unchanged
+//! witness.
+//!
+//! A KZG commitment is linear in its polynomial's evaluations: for `P(X) = sum_i P(w^i) L_i(X)`
+//! over the gate domain, its commitment is `sum_i P(w^i) [L_i(tau)]`. [IncrementalCommitter]
+//! keeps each wire column's evaluations and commitment from the last proof, and commits to the
+//! next proof's column by adding `(P'(w^i) - P(w^i)) [L_i(tau)]` for just the evaluations `i`
+//! that changed, so the MSM costs as much as the changed wires.
+//!
+//! In MPC, each party compares its own shares, so wires whose shares are reused from the last
+//! proof (say, rolling state kept shared between proofs) count as unchanged.
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{Field, One, Zero};
+use ark_poly::{univariate::DensePolynomial, EvaluationDomain, Radix2EvaluationDomain};
+use ark_poly_commit::{
+    kzg10, marlin_pc, optional_rng::OptionalRng, BatchLCProof, LabeledCommitment,
+    LabeledPolynomial, LinearCombination, QuerySet,
+};
+use ark_std::rand::RngCore;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::{column_label, relations::flat::Instance, CommitterBackend, Error, LocalCommitter};
+
+type Kzg<E> = marlin_pc::MarlinKZG10<E, DensePolynomial<<E as PairingEngine>::Fr>>;
+
+/// A cached column: its evaluations over the gate domain, and its unblinded commitment (and
+/// shifted commitment, under its degree bound).
+struct Column<E: PairingEngine> {
+    evals: Vec<E::Fr>,
+    degree_bound: Option<usize>,
+    comm: E::G1Projective,
+    shifted_comm: Option<E::G1Projective>,
+}
+
+/// Commits to chosen polynomials (by default, the wire columns) incrementally, from their
+/// commitments in the last proof; computes everything else in process.
+///
+/// Keeps a copy of each chosen column's evaluations, and the commitments to the gate domain's
+/// Lagrange basis (one set per degree bound), built on first use. Only commitments are
+/// incremental: opening proofs still cost an MSM over each opened polynomial.
+pub struct IncrementalCommitter<E: PairingEngine> {
+    domain: Radix2EvaluationDomain<E::Fr>,
+    labels: Vec<String>,
+    /// Commitments to the Lagrange basis, by degree bound
+    lagrange: RefCell<HashMap<Option<usize>, Vec<E::G1Affine>>>,
+    columns: RefCell<HashMap<String, Column<E>>>,
+    /// How many evaluations changed at each label's last commitment
+    changed: RefCell<HashMap<String, usize>>,
+}
+
+impl<E: PairingEngine> IncrementalCommitter<E> {
+    /// Commit to the polynomials labeled `labels`, of degree below `domain`'s size,
+    /// incrementally.
+    pub fn new(
+        domain: Radix2EvaluationDomain<E::Fr>,
+        labels: impl IntoIterator<Item = String>,
+    ) -> Self {
+        Self {
+            domain,
+            labels: labels.into_iter().collect(),
+            lagrange: RefCell::new(HashMap::new()),
+            columns: RefCell::new(HashMap::new()),
+            changed: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Commit to `circ`'s wire columns incrementally.
+    pub fn for_wires(circ: &Instance<E::Fr>) -> Self {
+        let labels = (0..circ.domains.width).map(|j| column_label("p", j));
+        Self::new(circ.domains.gates, labels)
+    }
+
+    /// How many of `label`'s evaluations changed at its last commitment (all of them, at its
+    /// first), if it has been committed.
+    pub fn changed(&self, label: &str) -> Option<usize> {
+        self.changed.borrow().get(label).cloned()
+    }
+
+    /// Forget every cached column, so the next commitments are computed in full.
+    pub fn clear(&self) {
+        self.columns.borrow_mut().clear();
+        self.changed.borrow_mut().clear();
+    }
+
+    /// Whether `p` may be committed incrementally under `ck`: it is chosen, fits the domain, and
+    /// any degree bound is one that `ck` supports. Otherwise [PolynomialCommitment::commit]
+    /// handles it (and reports any error).
+    fn incremental(
+        &self,
+        ck: &marlin_pc::CommitterKey<E>,
+        p: &LabeledPolynomial<E::Fr, DensePolynomial<E::Fr>>,
+    ) -> bool {
+        let n = self.domain.size();
+        let len = p.polynomial().coeffs.len();
+        self.labels.contains(p.label())
+            && len <= n
+            && ck.powers.len() >= n
+            && match p.degree_bound() {
+                None => true,
+                Some(d) => {
+                    len <= d + 1
+                        && matches!(&ck.enforced_degree_bounds, Some(bounds) if bounds.contains(&d))
+                        && ck.shifted_powers(d).unwrap().powers_of_g.len() >= n
+                }
+            }
+    }
+
+    /// The commitments to the Lagrange basis under `ck`'s powers for `degree_bound`.
+    fn lagrange(
+        &self,
+        ck: &marlin_pc::CommitterKey<E>,
+        degree_bound: Option<usize>,
+    ) -> std::cell::Ref<'_, [E::G1Affine]> {
+        if !self.lagrange.borrow().contains_key(&degree_bound) {
+            let basis = match degree_bound {
+                None => lagrange_basis::<E>(&ck.powers, self.domain),
+                Some(d) => {
+                    lagrange_basis::<E>(&ck.shifted_powers(d).unwrap().powers_of_g, self.domain)
+                }
+            };
+            self.lagrange.borrow_mut().insert(degree_bound, basis);
+        }
+        std::cell::Ref::map(self.lagrange.borrow(), |l| &l[&degree_bound][..])
+    }
+}
+
+/// `[L_i(tau)]`, for the Lagrange basis `L_i` of `domain`, from `powers` (`[tau^j]`): the
+/// inverse FFT of the powers, in the group.
+fn lagrange_basis<E: PairingEngine>(
+    powers: &[E::G1Affine],
+    domain: Radix2EvaluationDomain<E::Fr>,
+) -> Vec<E::G1Affine> {
+    let n = domain.size();
+    let mut a: Vec<E::G1Projective> = powers[..n].iter().map(|g| g.into_projective()).collect();
+    if n > 1 {
+        let log_n = domain.log_size_of_group;
+        for i in 0..n {
+            let r = ((i as u64).reverse_bits() >> (64 - log_n)) as usize;
+            if i < r {
+                a.swap(i, r);
+            }
+        }
+    }
+    let mut m = 1;
+    while m < n {
+        let w_m = domain.group_gen_inv.pow([(n / (2 * m)) as u64]);
+        for k in (0..n).step_by(2 * m) {
+            let mut w = E::Fr::one();
+            for j in 0..m {
+                let mut t = a[k + j + m];
+                t *= w;
+                let u = a[k + j];
+                a[k + j] = u + t;
+                a[k + j + m] = u - t;
+                w *= w_m;
+            }
+        }
+        m *= 2;
+    }
+    a.into_iter()
+        .map(|mut g| {
+            g *= domain.size_inv;
+            g.into_affine()
+        })
+        .collect()
+}
+
+/// `comm + sum_i delta_i * basis_i`, over the `(i, delta_i)` in `deltas`.
+fn update<E: PairingEngine>(
+    comm: E::G1Projective,
+    basis: &[E::G1Affine],
+    deltas: &[(usize, E::Fr)],
+) -> E::G1Projective {
+    let bases: Vec<E::G1Affine> = deltas.iter().map(|(i, _)| basis[*i]).collect();
+    let scalars: Vec<E::Fr> = deltas.iter().map(|(_, d)| *d).collect();
+    comm + <E::G1Affine as AffineCurve>::multi_scalar_mul(&bases, &scalars)
+}
+
+impl<E: PairingEngine> CommitterBackend<E::Fr, Kzg<E>> for IncrementalCommitter<E> {
+    fn commit(
+        &self,
+        ck: &marlin_pc::CommitterKey<E>,
+        p: &LabeledPolynomial<E::Fr, DensePolynomial<E::Fr>>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<
+        (
+            LabeledCommitment<marlin_pc::Commitment<E>>,
+            marlin_pc::Randomness<E::Fr, DensePolynomial<E::Fr>>,
+        ),
+        Error<ark_poly_commit::Error>,
+    > {
+        if !self.incremental(ck, p) {
+            return CommitterBackend::<E::Fr, Kzg<E>>::commit(&LocalCommitter, ck, p, rng);
+        }
+        let label = p.label();
+        let degree_bound = p.degree_bound();
+        let evals = self.domain.fft(&p.polynomial().coeffs);
+        let mut columns = self.columns.borrow_mut();
+        let last = columns
+            .remove(label)
+            .filter(|c| c.degree_bound == degree_bound);
+        let deltas: Vec<(usize, E::Fr)> = match &last {
+            Some(last) => evals
+                .iter()
+                .zip(&last.evals)
+                .enumerate()
+                .filter(|(_, (e, last))| e != last)
+                .map(|(i, (e, last))| (i, *e - last))
+                .collect(),
+            None => evals.iter().cloned().enumerate().collect(),
+        };
+        let (comm, shifted_comm) = last.map_or_else(
+            || {
+                (
+                    E::G1Projective::zero(),
+                    degree_bound.map(|_| E::G1Projective::zero()),
+                )
+            },
+            |c| (c.comm, c.shifted_comm),
+        );
+        let comm = update::<E>(comm, &self.lagrange(ck, None), &deltas);
+        let shifted_comm = shifted_comm
+            .map(|shifted| update::<E>(shifted, &self.lagrange(ck, degree_bound), &deltas));
+        self.changed
+            .borrow_mut()
+            .insert(label.clone(), deltas.len());
+        // The blinds are fresh for every commitment, drawn as PolynomialCommitment::commit does.
+        let rng = &mut OptionalRng(rng);
+        let zero = DensePolynomial::zero();
+        let (blind, rand) = kzg10::KZG10::commit(&ck.powers(), &zero, p.hiding_bound(), Some(rng))?;
+        let (shifted, shifted_rand) = match degree_bound {
+            Some(d) => {
+                let powers = ck.shifted_powers(d).unwrap();
+                let (blind, rand) =
+                    kzg10::KZG10::commit(&powers, &zero, p.hiding_bound(), Some(rng))?;
+                let shifted = shifted_comm.unwrap().add_mixed(&blind.0);
+                (Some(kzg10::Commitment(shifted.into_affine())), Some(rand))
+            }
+            None => (None, None),
+        };
+        let cmt = marlin_pc::Commitment {
+            comm: kzg10::Commitment(comm.add_mixed(&blind.0).into_affine()),
+            shifted_comm: shifted,
+        };
+        columns.insert(
+            label.clone(),
+            Column {
+                evals,
+                degree_bound,
+                comm,
+                shifted_comm,
+            },
+        );
+        Ok((
+            LabeledCommitment::new(label.clone(), cmt, degree_bound),
+            marlin_pc::Randomness { rand, shifted_rand },
+        ))
+    }
+
+    fn open(
+        &self,
+        ck: &marlin_pc::CommitterKey<E>,
+        p: &LabeledPolynomial<E::Fr, DensePolynomial<E::Fr>>,
+        p_c: &LabeledCommitment<marlin_pc::Commitment<E>>,
+        x: E::Fr,
+        p_r: &marlin_pc::Randomness<E::Fr, DensePolynomial<E::Fr>>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<kzg10::Proof<E>, Error<ark_poly_commit::Error>> {
+        CommitterBackend::<E::Fr, Kzg<E>>::open(&LocalCommitter, ck, p, p_c, x, p_r, rng)
+    }
+
+    fn open_combinations(
+        &self,
+        ck: &marlin_pc::CommitterKey<E>,
+        lcs: &[LinearCombination<E::Fr>],
+        ps: &[&LabeledPolynomial<E::Fr, DensePolynomial<E::Fr>>],
+        p_cs: &[&LabeledCommitment<marlin_pc::Commitment<E>>],
+        query_set: &QuerySet<E::Fr>,
+        opening_challenge: E::Fr,
+        p_rs: &[&marlin_pc::Randomness<E::Fr, DensePolynomial<E::Fr>>],
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<BatchLCProof<E::Fr, DensePolynomial<E::Fr>, Kzg<E>>, Error<ark_poly_commit::Error>>
+    {
+        CommitterBackend::<E::Fr, Kzg<E>>::open_combinations(
+            &LocalCommitter,
+            ck,
+            lcs,
+            ps,
+            p_cs,
+            query_set,
+            opening_challenge,
+            p_rs,
+            rng,
+        )
+    }
+}
@@ -6,127 +6,796 @@
 //! 17](https://cs251.stanford.edu/lectures/lecture17.pdf) for CS 251 (Spring 20) at Stanford.
 //!
 //! You should look at those notes for the notation used here.
+//!
+//! ```
+//! use ark_bls12_377::{Bls12_377, Fr};
+//! use ark_poly::univariate::DensePolynomial;
+//! use ark_poly_commit::marlin::marlin_pc::MarlinKZG10;
+//! use std::collections::HashMap;
+//! use mpc_plonk::relations::{
+//!     flat::{Instance, Witness},
+//!     structured::assign,
+//! };
+//! use mpc_plonk::{circuit, Plonk};
+//!
+//! type Pl = Plonk<Fr, MarlinKZG10<Bls12_377, DensePolynomial<Fr>>>;
+//!
+//! // The verifier knows the circuit, but not its inputs.
+//! let c = circuit! { pub out = (a + b) * c; };
+//! let v_circ = Instance::from_circuit(&c);
+//!
+//! let rng = &mut ark_std::test_rng();
+//! let srs = Pl::universal_setup(c.n_gates(), rng);
+//! let (pk, vk) = Pl::circuit_setup(&srs, &v_circ);
+//!
+//! // The prover assigns them.
+//! let inputs: HashMap<_, _> = vec![("a", 1u64), ("b", 2), ("c", 3)]
+//!     .into_iter()
+//!     .map(|(name, x)| (name.to_owned(), Fr::from(x)))
+//!     .collect();
+//! let assignment = assign(&c, &inputs).unwrap();
+//! let witness = Witness::from_circuit(&v_circ, &c.with_assignment(assignment));
+//! let pf = Pl::prove(&pk, &v_circ, &witness, rng);
+//!
+//! let public = vec![("out".to_owned(), Fr::from(9u64))].into_iter().collect();
+//! Pl::verify(&vk, &v_circ, pf, &public);
+//! ```
 
+pub mod aggregate;
+pub mod arithmetic;
+pub use arithmetic::*;
+pub mod committer;
+pub use committer::*;
+#[cfg(feature = "r1cs")]
+pub mod constraints;
+pub mod cost;
+pub use cost::*;
 pub mod data_structures;
 pub use data_structures::*;
+pub mod format;
+pub use format::*;
+pub mod incremental;
+pub mod metrics;
+pub use metrics::Phase;
+#[cfg(feature = "metrics")]
+pub use metrics::{PhaseMetrics, ProverMetrics};
+pub mod opening;
+pub use opening::*;
+pub mod pool;
+pub use pool::*;
+pub mod ptau;
 pub mod relations;
 pub use relations::*;
+pub mod report;
+pub use report::*;
 pub mod reveal;
+pub mod schedule;
+pub use schedule::*;
+pub mod selftest;
+mod sections;
+pub mod transcript;
+pub use transcript::*;
 mod util;
 
 use log::debug;
 
-use blake2::Blake2s;
+use ark_ff::{batch_inversion, FftField, Field, One, Zero};
 
-use ark_ff::{FftField, Field};
-
-use ark_poly_commit::{LabeledCommitment, LabeledPolynomial, PCRandomness, PolynomialCommitment};
+use ark_poly_commit::{
+    BatchLCProof, LabeledCommitment, LabeledPolynomial, LinearCombination, PCCommitment,
+    PCRandomness, PolynomialCommitment, QuerySet,
+};
 
 use ark_poly::{
-    domain::EvaluationDomain,
+    domain::{EvaluationDomain, Radix2EvaluationDomain},
     univariate::{DenseOrSparsePolynomial, DensePolynomial},
-    Polynomial, UVPolynomial,
+    Evaluations, Polynomial, UVPolynomial,
 };
 
-use ark_std::{end_timer, rand::RngCore, start_timer};
+use ark_relations::r1cs::{ConstraintSynthesizer, SynthesisError};
+use ark_serialize::CanonicalSerialize;
+
+use ark_std::{
+    end_timer,
+    rand::{RngCore, SeedableRng},
+    start_timer,
+};
+use rand_chacha::ChaChaRng;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use std::borrow::Cow;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::iter::once;
 use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::sync::Arc;
 use thiserror::Error;
 
+use mpc_algebra::{defer, taint, Reveal};
+use mpc_net::{catch_abort, Abort, AbortCode, MpcMultiNet, MpcNet};
 use mpc_trait::MpcWire;
-pub use util::FiatShamirRng;
+use relations::flat::{CircuitDigest, LocalRows, GATE_WIDTH};
+pub use util::{poseidon_hash, FiatShamirRng, PoseidonParameters, PoseidonSponge};
+
+/// The initial transcript for proofs about the circuit with digest `circuit`, in `session`:
+/// the format version, the circuit, optionally a beacon output, then the session. An enclosing
+/// protocol that runs the sub-verifiers (see [Verifier::with_transcript]) starts from it.
+///
+/// Transcripts that predate sessions (see [Transcript::BINDS_SESSION]) begin with the circuit,
+/// and can only be in the default session.
+pub fn transcript_init<T: Transcript>(
+    circuit: &CircuitDigest,
+    beacon: Option<&Beacon>,
+    session: &Session,
+) -> T {
+    let mut transcript = T::new(b"plonk");
+    if T::BINDS_SESSION {
+        transcript.absorb_bytes(b"version", &[FORMAT_VERSION]);
+    } else {
+        assert!(
+            *session == Session::default(),
+            "This transcript can't be bound to a session"
+        );
+    }
+    transcript.absorb_bytes(b"circuit", &circuit.0);
+    if let Some(b) = beacon {
+        let bytes = ark_ff::to_bytes![b.round, b.value].expect("failed serialization");
+        transcript.absorb(b"beacon", &bytes);
+    }
+    if T::BINDS_SESSION {
+        transcript.absorb_bytes(b"session", &session.nonce);
+        if let Some(public) = &session.public {
+            transcript.absorb_bytes(b"public", public);
+        }
+    }
+    transcript
+}
+
+/// The label of column `j`'s polynomial, of those labeled `label` (e.g., `p_0` for `p`).
+///
+/// Each column's commitment is absorbed into the transcript under `label` itself, as the
+/// transcript's labels are static.
+pub(crate) fn column_label(label: &str, j: usize) -> String {
+    format!("{}_{}", label, j)
+}
+
+/// The commitments of [Prover::prove] for a circuit laid out in `width` columns, and the
+/// challenges they depend on.
+///
+/// The opening points are left out: they are drawn once the sub-proofs opening there have
+/// committed, and no commitment depends on them, so they don't move any commitment's round.
+pub fn prove_schedule(width: usize) -> RoundSchedule {
+    let mut schedule = RoundSchedule::new();
+    for j in 0..width {
+        schedule.commit(column_label("p", j), &[]);
+    }
+    schedule.challenge("public_beta");
+    schedule.commit("pub_q", &["public_beta"]);
+    schedule.commit("gates_q", &[]);
+    schedule.challenge("wiring_y");
+    schedule.challenge("wiring_z");
+    for label in &["l1", "t", "q", "l2_q"] {
+        schedule.commit(*label, &["wiring_y", "wiring_z"]);
+    }
+    schedule
+}
+
+/// The most times a proof opens any one of its polynomials: each `P_j`, at the public, gate, and
+/// wiring sub-proofs' points, and `t`, at the unit product's three. A commitment hides its
+/// polynomial if its hiding bound is at least its openings, so this bound keeps every commitment
+/// hiding.
+pub const HIDING_BOUND: usize = 3;
+
+/// The linear combinations that a [GateProof] opens at the gate sub-proof's point `x`, with where
+/// each is queried and its value: the linearization polynomial, the quotient, and each `P_j` the
+/// gates read.
+///
+/// Given those `P_j`'s values and the quotient's value at `x`, and the gate domain's vanishing
+/// polynomial at `x`. The quotient is degree-bounded, so it is opened alone, rather than in the
+/// linearization.
+fn gates_linearization<F: Field>(
+    x: F,
+    z_x: F,
+    p_values: &[F],
+    q_value: F,
+) -> (
+    Vec<LinearCombination<F>>,
+    QuerySet<F>,
+    ark_poly_commit::Evaluations<F, F>,
+) {
+    let (a, b, c) = (p_values[0], p_values[1], p_values[2]);
+    let mut lcs = vec![
+        LinearCombination::new("gates_r", vec![(a + b - a * b, "s")]),
+        LinearCombination::new("gates_q", vec![(F::one(), "gates_q")]),
+    ];
+    let mut evals = vec![
+        (("gates_r".to_owned(), x), c - a * b + z_x * q_value),
+        (("gates_q".to_owned(), x), q_value),
+    ];
+    for (j, p) in p_values.iter().enumerate() {
+        let label = column_label("p", j);
+        lcs.push(LinearCombination::new(
+            label.clone(),
+            vec![(F::one(), label.clone())],
+        ));
+        evals.push(((label, x), *p));
+    }
+    let query_set = lcs
+        .iter()
+        .map(|lc| (lc.label.clone(), ("gates_x".to_owned(), x)))
+        .collect();
+    (lcs, query_set, evals.into_iter().collect())
+}
+
+/// The challenge that a [GateProof]'s openings at `x` are batched with.
+///
+/// The `P_j`'s values and the quotient's (see [gates_linearization]) fix the linearization, so
+/// it's drawn after them. It has a transcript of its own, bound to everything before by `x`, so
+/// that the proof's transcript (which sub-verifiers may share, see [Verifier::with_transcript])
+/// holds only commitments.
+fn gates_challenge<F: FftField, T: Transcript>(x: F, p_values: &[F], q_value: F) -> F {
+    let mut transcript = T::new(b"plonk_gates");
+    transcript.absorb(b"gates_x", &x);
+    for y in p_values {
+        transcript.absorb(b"gates_p", y);
+    }
+    transcript.absorb(b"gates_q", &q_value);
+    transcript.challenge(b"gates_v")
+}
+
+/// One [PolynomialCommitment::trim] that serves several circuits.
+///
+/// Trimming a large SRS takes seconds, so a service with many circuits should [register] them
+/// all, trim once, and then [index] each circuit under the shared keys.
+///
+/// [register]: TrimPlan::register
+#[derive(Clone, Debug, Default)]
+pub struct TrimPlan {
+    supported_degree: usize,
+    degree_bounds: BTreeSet<usize>,
+    hiding_bound: usize,
+}
+
+impl TrimPlan {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Make the trimmed keys serve `circ` too.
+    pub fn register<F: FftField>(&mut self, circ: &relations::flat::Instance<F>) {
+        self.supported_degree = self.supported_degree.max(circ.degree_bound());
+        self.degree_bounds.extend(circ.domains.degree_bounds());
+    }
+    /// Make the trimmed keys support hiding commitments (see [Prover::hiding_commitments]) opened
+    /// up to `bound` times. [HIDING_BOUND] serves every proof.
+    pub fn hiding_bound(&mut self, bound: usize) {
+        self.hiding_bound = self.hiding_bound.max(bound);
+    }
+    /// Trim `srs` for every registered circuit.
+    pub fn trim<F: FftField, PC: PolynomialCommitment<F, DensePolynomial<F>>>(
+        &self,
+        srs: &PC::UniversalParams,
+    ) -> (PC::CommitterKey, PC::VerifierKey) {
+        assert!(!self.degree_bounds.is_empty(), "No circuits registered");
+        let degree_bounds: Vec<usize> = self.degree_bounds.iter().cloned().collect();
+        PC::trim(
+            srs,
+            self.supported_degree,
+            self.hiding_bound,
+            Some(&degree_bounds),
+        )
+        .unwrap()
+    }
+}
 
 pub fn setup<'r, F: FftField, PC: PolynomialCommitment<F, DensePolynomial<F>>>(
     srs: &PC::UniversalParams,
-    circ: &relations::flat::CircuitLayout<F>,
+    circ: &relations::flat::Instance<F>,
 ) -> (
     ProverKey<F, PC::Commitment, PC::CommitterKey>,
     VerifierKey<PC::Commitment, PC::VerifierKey>,
 ) {
-    let (ck, vk) = PC::trim(
-        &srs,
-        circ.degree_bound(),
-        0,
-        Some(&[circ.domains.wires.size() - 1]),
-    )
-    .unwrap();
-    let w = LabeledPolynomial::new("w".into(), circ.w.clone(), None, None);
-    let (mut cs, rs) = PC::commit(&ck, once(&w), None).unwrap();
-    assert_eq!(cs.len(), 1);
-    assert_eq!(rs.len(), 1);
-    let w_cmt = cs.pop().unwrap();
+    let (ck, vk) = trim_for_circuit::<F, PC>(srs, circ);
+    index::<F, PC>(ck, vk, circ)
+}
+
+/// Trim `srs` (e.g., from [ptau::load_ptau]) for `circ` alone.
+pub fn trim_for_circuit<F: FftField, PC: PolynomialCommitment<F, DensePolynomial<F>>>(
+    srs: &PC::UniversalParams,
+    circ: &relations::flat::Instance<F>,
+) -> (PC::CommitterKey, PC::VerifierKey) {
+    let mut plan = TrimPlan::new();
+    plan.register(circ);
+    plan.trim::<F, PC>(srs)
+}
+
+/// A circuit's prover and verifier keys, under polynomial commitment `PC`.
+pub type CircuitKeys<F, PC> = (
+    ProverKey<
+        F,
+        <PC as PolynomialCommitment<F, DensePolynomial<F>>>::Commitment,
+        <PC as PolynomialCommitment<F, DensePolynomial<F>>>::CommitterKey,
+    >,
+    VerifierKey<
+        <PC as PolynomialCommitment<F, DensePolynomial<F>>>::Commitment,
+        <PC as PolynomialCommitment<F, DensePolynomial<F>>>::VerifierKey,
+    >,
+);
+
+/// Commit to the circuit's polynomials, under keys trimmed for it (see [TrimPlan]).
+///
+/// The keys, like the circuit's layout, serialize (see [write_versioned]), so they can be made
+/// once and distributed to provers and verifiers.
+pub fn index<F: FftField, PC: PolynomialCommitment<F, DensePolynomial<F>>>(
+    ck: PC::CommitterKey,
+    vk: PC::VerifierKey,
+    circ: &relations::flat::Instance<F>,
+) -> CircuitKeys<F, PC> {
+    let w: Vec<_> = circ
+        .w
+        .iter()
+        .enumerate()
+        .map(|(j, w)| LabeledPolynomial::new(column_label("w", j), w.clone(), None, None))
+        .collect();
+    let (w_cmts, rs) = PC::commit(&ck, &w, None).unwrap();
+    assert_eq!(w_cmts.len(), w.len());
+    assert_eq!(rs.len(), w.len());
     let s = LabeledPolynomial::new("s".into(), circ.s.clone(), None, None);
     let (mut cs, rs) = PC::commit(&ck, once(&s), None).unwrap();
     assert_eq!(cs.len(), 1);
     assert_eq!(rs.len(), 1);
     let s_cmt = cs.pop().unwrap();
+    let circuit_digest = circ.digest();
     (
         ProverKey {
             pc_ck: ck,
             s_cmt: s_cmt.clone(),
-            w_cmt: w_cmt.clone(),
+            w_cmts: w_cmts.clone(),
             s,
             w,
+            circuit_digest,
         },
         VerifierKey {
             pc_vk: vk,
             s_cmt,
-            w_cmt,
+            w_cmts,
+            circuit_digest,
         },
     )
 }
 
+/// How a verifier key differs from the one [index] makes for its circuit (see
+/// [VerifierKey::validate]).
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum KeyError {
+    #[error("Verifier key is for another circuit: digest {found}, not {expected}")]
+    Circuit {
+        expected: CircuitDigest,
+        found: CircuitDigest,
+    },
+    #[error("Verifier key has {found} wiring commitments, not {expected}")]
+    Columns { expected: usize, found: usize },
+    /// The commitment to this polynomial (`s`, or a column's `w_j`) is not the circuit's.
+    #[error("Verifier key's commitment to {0} is not the circuit's")]
+    Commitment(String),
+    #[error("Verifier key's commitment parameters are not the trusted ones")]
+    PcVerifierKey,
+}
+
+impl<C: PCCommitment, PcVk: CanonicalSerialize + Clone> VerifierKey<C, PcVk> {
+    /// Check that this key is the one [index] makes for `circ` under `ck` and `pc_vk`, trimmed
+    /// from a trusted SRS (e.g., by [trim_for_circuit]), by committing to `circ`'s polynomials
+    /// afresh.
+    ///
+    /// A verifier that takes keys from an untrusted indexer should validate them once: the
+    /// circuit digest binds proofs to the layout, but not the commitments that the verifier
+    /// checks openings against, so a key with tampered commitments can accept false proofs.
+    pub fn validate<F, PC>(
+        &self,
+        ck: &PC::CommitterKey,
+        pc_vk: &PcVk,
+        circ: &relations::flat::Instance<F>,
+    ) -> std::result::Result<(), KeyError>
+    where
+        F: FftField,
+        PC: PolynomialCommitment<F, DensePolynomial<F>, Commitment = C, VerifierKey = PcVk>,
+    {
+        let found = circ.digest();
+        if found != self.circuit_digest {
+            return Err(KeyError::Circuit {
+                expected: found,
+                found: self.circuit_digest,
+            });
+        }
+        if self.w_cmts.len() != circ.domains.width {
+            return Err(KeyError::Columns {
+                expected: circ.domains.width,
+                found: self.w_cmts.len(),
+            });
+        }
+        let (_, fresh) = index::<F, PC>(ck.clone(), pc_vk.clone(), circ);
+        let ours = self.w_cmts.iter().chain(once(&self.s_cmt));
+        let theirs = fresh.w_cmts.iter().chain(once(&fresh.s_cmt));
+        for (ours, theirs) in ours.zip(theirs) {
+            if !same_bytes(ours.commitment(), theirs.commitment()) {
+                return Err(KeyError::Commitment(theirs.label().clone()));
+            }
+        }
+        if !same_bytes(&self.pc_vk, pc_vk) {
+            return Err(KeyError::PcVerifierKey);
+        }
+        Ok(())
+    }
+}
+
+/// Whether `a` and `b` serialize alike.
+fn same_bytes<T: CanonicalSerialize>(a: &T, b: &T) -> bool {
+    let (mut a_bytes, mut b_bytes) = (Vec::new(), Vec::new());
+    a.serialize(&mut a_bytes).unwrap();
+    b.serialize(&mut b_bytes).unwrap();
+    a_bytes == b_bytes
+}
+
+/// Opened values and their proofs, by polynomial label and point.
+type Openings<F, P> = HashMap<(String, F), (F, P)>;
+
 #[allow(dead_code)]
-pub struct Prover<'r, F: FftField, PC: PolynomialCommitment<F, DensePolynomial<F>>> {
+pub struct Prover<
+    'r,
+    F: FftField,
+    PC: PolynomialCommitment<F, DensePolynomial<F>>,
+    T: Transcript = Blake2sTranscript,
+> {
     _pc: PhantomData<PC>,
     pk: &'r ProverKey<F, PC::Commitment, PC::CommitterKey>,
     zk_rng: RefCell<&'r mut dyn RngCore>,
-    fs_rng: RefCell<FiatShamirRng<Blake2s>>,
+    committer: &'r dyn CommitterBackend<F, PC>,
+    arithmetic: &'r dyn ArithmeticBackend<F>,
+    transcript: RefCell<T>,
+    beacon_round: Option<u64>,
+    warmup: Option<Warmup<F>>,
+    memory: MemoryMode,
+    policy: &'r dyn OpeningPolicy,
+    /// The hiding bound of every commitment, if they hide
+    hiding: Option<usize>,
+    /// Free buffers, for coset evaluations
+    scratch: RefCell<Vec<Vec<F>>>,
+    /// This proof's openings so far, by polynomial label and point
+    openings: RefCell<Openings<F, PC::Proof>>,
+    /// Commitments queued to be revealed in the next round
+    queued: RefCell<Vec<String>>,
+    /// The commitments revealed in each of this proof's rounds so far
+    rounds: RefCell<Vec<Vec<String>>>,
+    /// This proof's costs so far, by phase
+    recorder: metrics::Recorder,
+    /// The rows of L_1 the king computes natively, if any
+    local: Option<&'r dyn LocalWiring<F>>,
+}
+
+/// Rows of the wiring proof's L_1 that the king computes natively, for [Prover::local_rows].
+pub trait LocalWiring<F> {
+    /// The rows, in order
+    fn rows(&self) -> &[usize];
+    /// Shares of L_1 on the rows: `prod_j (P_j(X) + yW_j(X) + z)/(P_j(X) + yk_jX + z)`, from each
+    /// `W_j(X)` and `X` over the whole gate domain, and each column's shift `k_j`.
+    fn l1(
+        &self,
+        w_evals: &[&[F]],
+        x_evals: &[F],
+        shifts: &[F],
+        y: F,
+        z: F,
+        rng: &mut dyn RngCore,
+    ) -> Vec<F>;
+}
+
+impl<F: Reveal + Copy> LocalWiring<F> for LocalRows<F::Base>
+where
+    F::Base: Field,
+{
+    fn rows(&self) -> &[usize] {
+        &self.rows
+    }
+
+    fn l1(
+        &self,
+        w_evals: &[&[F]],
+        x_evals: &[F],
+        shifts: &[F],
+        y: F,
+        z: F,
+        mut rng: &mut dyn RngCore,
+    ) -> Vec<F> {
+        // The challenges and tables are public
+        let (y, z) = (y.unwrap_as_public(), z.unwrap_as_public());
+        let mut num = vec![F::Base::one(); self.rows.len()];
+        let mut den = num.clone();
+        for (j, values) in self.values.iter().enumerate() {
+            let yk = y * shifts[j].unwrap_as_public();
+            for (i, (&g, &v)) in self.rows.iter().zip(values).enumerate() {
+                num[i] *= v + y * w_evals[j][g].unwrap_as_public() + z;
+                den[i] *= v + yk * x_evals[g].unwrap_as_public() + z;
+            }
+        }
+        batch_inversion(&mut den);
+        let l1 = num.into_iter().zip(den).map(|(n, d)| n * d).collect();
+        F::king_share_batch(l1, &mut rng)
+    }
+}
+
+/// Where the prover keeps large tables that sit idle through a sub-proof.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum MemoryMode {
+    /// In memory
+    #[default]
+    InMemory,
+    /// In files in this directory, which are deleted once read back.
+    ///
+    /// The files hold the tables in plaintext, and the tables derive from the witness, so the
+    /// directory must be as private as the witness. Shares are never written out (serializing
+    /// one panics), so only a local prover may spill.
+    Spill(PathBuf),
+}
+
+/// What the prover computes from the circuit and key alone: everything not depending on the
+/// witness.
+#[derive(Clone)]
+struct Warmup<F: FftField> {
+    /// 1 - S(X)
+    one_minus_s: DensePolynomial<F>,
+    /// The circuit's own precomputation: vanishing polynomials and domain tables
+    precomputed: Arc<relations::flat::Precomputed<F>>,
+    /// Each W_j(X) over the gate domain
+    w_evals: Vec<Evaluations<F, Radix2EvaluationDomain<F>>>,
+    /// Each W_j(X) over the quotient domain's coset (see [relations::flat::Domains::quotient])
+    w_coset_evals: Vec<Vec<F>>,
+}
+
+impl<F: FftField> Warmup<F> {
+    fn new(
+        w: &[LabeledPolynomial<F, DensePolynomial<F>>],
+        circ: &relations::flat::Instance<F>,
+        arithmetic: &dyn ArithmeticBackend<F>,
+    ) -> Self {
+        let timer = start_timer!(|| "warmup");
+        let dom = circ.domains.gates;
+        let quotient = circ.domains.quotient();
+        let warmup = Self {
+            one_minus_s: &(&circ.s * &-F::one()) + &F::one(),
+            precomputed: circ.precomputed.clone(),
+            w_evals: w
+                .iter()
+                .map(|w| {
+                    let mut evals = w.polynomial().coeffs.clone();
+                    arithmetic.fft_in_place(dom, &mut evals);
+                    Evaluations::from_vec_and_domain(evals, dom)
+                })
+                .collect(),
+            w_coset_evals: w
+                .iter()
+                .map(|w| {
+                    let mut evals = w.polynomial().coeffs.clone();
+                    arithmetic.coset_fft_in_place(quotient, &mut evals);
+                    evals
+                })
+                .collect(),
+        };
+        end_timer!(timer);
+        warmup
+    }
+
+    /// The public check's quotient: over the public columns, with powers of `beta`, the quotient
+    /// of each `P_j` (less its interpolant on the column's public wires) by their vanishing
+    /// polynomial.
+    fn public_quotient(&self, p: &[&DensePolynomial<F>], beta: F) -> DensePolynomial<F> {
+        let mut q = DensePolynomial::zero();
+        let mut power = F::one();
+        for column in &self.precomputed.public {
+            let (p_j, vanishing) = (p[column.column], &column.vanishing);
+            let points: Vec<(F, F)> = column
+                .inputs
+                .iter()
+                .map(|(_, (x, _))| (*x, p_j.evaluate(x)))
+                .collect();
+            let v = util::interpolate(&points);
+            let (q_j, _r) = DenseOrSparsePolynomial::DPolynomial(Cow::Owned(p_j - &v))
+                .divide_with_q_and_r(&DenseOrSparsePolynomial::DPolynomial(Cow::Borrowed(
+                    vanishing,
+                )))
+                .unwrap();
+            let q_j = pad_quotient(q_j, p_j.coeffs.len() - vanishing.degree(), &p_j.coeffs[0]);
+            q += (power, &q_j);
+            power *= beta;
+        }
+        q
+    }
+
+    /// The quotient of the gate constraint on the `P_j` by the gate domain's vanishing
+    /// polynomial.
+    fn gates_quotient(
+        &self,
+        p: &[&DensePolynomial<F>],
+        circ: &relations::flat::Instance<F>,
+    ) -> DensePolynomial<F> {
+        let (a, b, c) = (p[0], p[1], p[2]);
+        let d = &circ.s * &(a + b) + &self.one_minus_s * &(a * b);
+        let d = &d - c;
+        let (q, _r) = DenseOrSparsePolynomial::DPolynomial(Cow::Owned(d))
+            .divide_with_q_and_r(&DenseOrSparsePolynomial::SPolynomial(Cow::Borrowed(
+                &self.precomputed.gates_vanishing,
+            )))
+            .unwrap();
+        // debug_assert!(r.is_zero());
+        // The constraint has degree below 2n - 1, for n gates
+        pad_quotient(q, a.coeffs.len() - 1, &a.coeffs[0])
+    }
+}
+
+/// `q`, a quotient of up to `len` coefficients, padded to them with zeros shared as `like` is.
+///
+/// Under MPC, division works on each party's share of the dividend, dropping its leading zeros,
+/// so parties' shares of the quotient may differ in length, or even be empty (and so public).
+fn pad_quotient<F: Field>(q: DensePolynomial<F>, len: usize, like: &F) -> DensePolynomial<F> {
+    let zero = *like - like;
+    let mut coeffs = q.coeffs;
+    coeffs.resize(len.max(coeffs.len()), zero);
+    DensePolynomial::from_coefficients_vec(coeffs)
+}
+
+/// A polynomial the prover has committed to.
+struct Committed<F: FftField, PC: PolynomialCommitment<F, DensePolynomial<F>>> {
+    cmt: LabeledCommitment<PC::Commitment>,
+    poly: LabeledPolynomial<F, DensePolynomial<F>>,
+    rand: PC::Randomness,
+}
+
+/// A polynomial the prover has committed to, with its commitment queued to be revealed (see
+/// [Prover::absorb]).
+struct Pending<F: FftField, PC: PolynomialCommitment<F, DensePolynomial<F>>> {
+    /// Label the commitment is absorbed under
+    label: &'static str,
+    cmt: defer::Deferred<LabeledCommitment<PC::Commitment>>,
+    poly: LabeledPolynomial<F, DensePolynomial<F>>,
+    rand: PC::Randomness,
+}
+
+/// The wiring proof's commitments, including its unit product proof's.
+struct WiringCommits<F: FftField, PC: PolynomialCommitment<F, DensePolynomial<F>>> {
+    l1: Committed<F, PC>,
+    t: Committed<F, PC>,
+    q: Committed<F, PC>,
+    l2_q: Committed<F, PC>,
 }
 
-impl<'r, F: FftField, PC: PolynomialCommitment<F, DensePolynomial<F>>> Prover<'r, F, PC> {
+impl<'r, F: FftField, PC: PolynomialCommitment<F, DensePolynomial<F>>, T: Transcript>
+    Prover<'r, F, PC, T>
+where
+    PC::Error: 'static,
+{
     pub fn new(
         pk: &'r ProverKey<F, PC::Commitment, PC::CommitterKey>,
         zk_rng: &'r mut dyn RngCore,
+    ) -> Self {
+        Self::new_with_beacon(pk, zk_rng, None)
+    }
+    /// A prover whose challenges also depend on `beacon`.
+    pub fn new_with_beacon(
+        pk: &'r ProverKey<F, PC::Commitment, PC::CommitterKey>,
+        zk_rng: &'r mut dyn RngCore,
+        beacon: Option<&Beacon>,
+    ) -> Self {
+        Self::new_in_session(pk, zk_rng, beacon, &Session::default())
+    }
+    /// A prover whose challenges also depend on `beacon`, and on `session`.
+    pub fn new_in_session(
+        pk: &'r ProverKey<F, PC::Commitment, PC::CommitterKey>,
+        zk_rng: &'r mut dyn RngCore,
+        beacon: Option<&Beacon>,
+        session: &Session,
     ) -> Self {
         Self {
             _pc: PhantomData::default(),
             pk,
             zk_rng: RefCell::new(zk_rng),
-            fs_rng: RefCell::new(FiatShamirRng::from_seed(&0u64)),
+            committer: &LocalCommitter,
+            arithmetic: &CpuBackend,
+            transcript: RefCell::new(transcript_init(&pk.circuit_digest, beacon, session)),
+            beacon_round: beacon.map(|b| b.round),
+            warmup: None,
+            memory: MemoryMode::default(),
+            policy: &PerSubProof,
+            hiding: None,
+            scratch: RefCell::new(Vec::new()),
+            openings: RefCell::new(HashMap::new()),
+            queued: RefCell::new(Vec::new()),
+            rounds: RefCell::new(Vec::new()),
+            recorder: metrics::Recorder::new(),
+            local: None,
         }
     }
+    /// Set where large idle tables are kept; spilling them lets larger circuits fit in memory.
+    pub fn memory_mode(&mut self, memory: MemoryMode) {
+        self.memory = memory;
+    }
+    /// Set where the committed polynomials are opened (by default, [PerSubProof]). The verifier
+    /// must use the same policy.
+    pub fn opening_policy(&mut self, policy: &'r dyn OpeningPolicy) {
+        self.policy = policy;
+    }
+    /// Blind every commitment, so that it reveals nothing about its polynomial through `bound`
+    /// openings (see [HIDING_BOUND]). The keys must support it (see [TrimPlan::hiding_bound]).
+    ///
+    /// The blinds are drawn from `zk_rng`. In MPC, each party's share of a blind comes from its
+    /// own `zk_rng`, which must be private to it (like a [BlindingRng]) for the blind to be
+    /// secret. Deriving it from the party's [BlindingSeed] (see [BlindingSeed::for_proof]) with
+    /// [ProverKey::circuit_digest] and a session id lets the party reproduce its blinds later.
+    ///
+    /// [BlindingRng]: mpc_algebra::BlindingRng
+    /// [BlindingSeed]: mpc_algebra::BlindingSeed
+    /// [BlindingSeed::for_proof]: mpc_algebra::BlindingSeed::for_proof
+    pub fn hiding_commitments(&mut self, bound: usize) {
+        self.hiding = Some(bound);
+    }
+    /// Compute L_1 on `local`'s rows natively, on the king, and share it in one round, rather than
+    /// multiply and divide shares there: for a witness mostly known to the king (see
+    /// [relations::structured::assign_hybrid]), most rows are local. `local` must be the same
+    /// rows on every party.
+    pub fn local_rows(&mut self, local: &'r dyn LocalWiring<F>) {
+        self.local = Some(local);
+    }
+    /// Set where commitments and opening proofs are computed (by default, in process).
+    pub fn committer_backend(&mut self, committer: &'r dyn CommitterBackend<F, PC>) {
+        self.committer = committer;
+    }
+    /// Set where FFTs are computed (by default, in process). Commitments' MSMs are computed by
+    /// the committer backend (see [ArithmeticCommitter]).
+    pub fn arithmetic_backend(&mut self, arithmetic: &'r dyn ArithmeticBackend<F>) {
+        self.arithmetic = arithmetic;
+    }
+    /// The arithmetic backend, counting its time (with feature `metrics`).
+    fn arithmetic(&self) -> metrics::Metered<'_, F> {
+        self.recorder.arithmetic(self.arithmetic)
+    }
+    /// Precompute everything that does not depend on the witness (so `circ` may lack it), to cut
+    /// the time from witness to proof.
+    pub fn warmup(&mut self, circ: &relations::flat::Instance<F>) {
+        self.warmup = Some(Warmup::new(&self.pk.w, circ, &self.arithmetic()));
+    }
 }
 
 #[allow(dead_code)]
-impl<'r, F: FftField, PC: PolynomialCommitment<F, DensePolynomial<F>>> Prover<'r, F, PC>
+impl<'r, F: FftField, PC: PolynomialCommitment<F, DensePolynomial<F>>, T: Transcript>
+    Prover<'r, F, PC, T>
 where
     PC::Commitment: mpc_trait::MpcWire,
     PC::Error: 'static,
 {
-    fn prove_unit_product<D: EvaluationDomain<F>>(
+    /// The partial products of `f` over `domain`, interpolated.
+    fn partial_products(
         &self,
-        f: &LabeledPolynomial<F, DensePolynomial<F>>,
-        f_cmt: &LabeledCommitment<PC::Commitment>,
-        f_rand: &PC::Randomness,
-        domain: D,
-    ) -> ProductProof<PC::Commitment, (F, PC::Proof)> {
-        let timer = start_timer!(|| "prove_unit_product");
-        let t_evals = {
-            let mut t = f.evaluate_over_domain_by_ref(domain);
-            F::partial_products_in_place(&mut t.evals);
-            t
-        };
+        f: &DensePolynomial<F>,
+        domain: Radix2EvaluationDomain<F>,
+    ) -> DensePolynomial<F> {
+        let mut t_evals = f.coeffs.clone();
+        self.arithmetic().fft_in_place(domain, &mut t_evals);
+        F::partial_products_in_place(&mut t_evals);
         // debug_assert!(t_evals.evals[f.coeffs.len() - 1], F::one());
         //        debug_assert_eq!(
         //            t_evals.evals[f.coeffs.len() - 1] * t_evals.evals[0],
         //            t_evals[0]
         //        );
-        let t = t_evals.interpolate();
-        let (t_cmt, t, t_rand) = self.commit("t", t.clone(), None, None).unwrap();
+        self.arithmetic().ifft_in_place(domain, &mut t_evals);
+        DensePolynomial::from_coefficients_vec(t_evals)
+    }
+
+    /// The quotient of t(wX) - f(wX)t(X) by `domain`'s vanishing polynomial, where `t` holds the
+    /// partial products of `f`.
+    fn unit_product_quotient(
+        &self,
+        f: &DensePolynomial<F>,
+        t: &DensePolynomial<F>,
+        domain: Radix2EvaluationDomain<F>,
+    ) -> DensePolynomial<F> {
         let w = domain.element(1);
         // let q = {
         //     let d = &shift(t.clone(), w) - &t.naive_mul(&shift(f.clone(), w));
@@ -135,49 +804,72 @@ where
         //     q
         // };
         let q_timer = start_timer!(|| "q");
-        let q = {
-            // get f(wX) over coset
-            let mut f_evals = f.coeffs.clone();
-            D::distribute_powers(&mut f_evals, w);
-            domain.coset_fft_in_place(&mut f_evals);
-            // get t(X) over coset
-            let mut t_evals = t.coeffs.clone();
-            domain.coset_fft_in_place(&mut t_evals);
-            // get f(wX)t(X) over coset
-            let fwt_evals = domain.mul_polynomials_in_evaluation_domain(&f_evals, &t_evals);
-            // get t(wX) over coset
-            let mut tw_evals = t.coeffs.clone();
-            D::distribute_powers(&mut tw_evals, w);
-            domain.coset_fft_in_place(&mut tw_evals);
-            // get t(wX) - f(wX)t(X) over coset
-            ark_std::cfg_iter_mut!(tw_evals)
-                .zip(fwt_evals)
-                .for_each(|(a, b)| *a -= b);
-            domain.divide_by_vanishing_poly_on_coset_in_place(&mut tw_evals);
-            domain.coset_ifft_in_place(&mut tw_evals);
-            DensePolynomial::from_coefficients_vec(tw_evals)
-        };
+        // get f(wX) and t(X) over coset
+        let mut fwt_evals = self.scratch_copy(&f.coeffs);
+        let mut t_evals = self.scratch_copy(&t.coeffs);
+        let arithmetic = self.arithmetic();
+        util::join(
+            || {
+                Radix2EvaluationDomain::distribute_powers(&mut fwt_evals, w);
+                arithmetic.coset_fft_in_place(domain, &mut fwt_evals)
+            },
+            || arithmetic.coset_fft_in_place(domain, &mut t_evals),
+        );
+        // get f(wX)t(X) over coset
+        ark_std::cfg_iter_mut!(fwt_evals)
+            .zip(&t_evals)
+            .for_each(|(a, b)| *a *= b);
+        self.scratch_free(t_evals);
+        // get t(wX) over coset
+        let mut tw_evals = t.coeffs.clone();
+        Radix2EvaluationDomain::distribute_powers(&mut tw_evals, w);
+        self.arithmetic().coset_fft_in_place(domain, &mut tw_evals);
+        // get t(wX) - f(wX)t(X) over coset
+        ark_std::cfg_iter_mut!(tw_evals)
+            .zip(&fwt_evals)
+            .for_each(|(a, b)| *a -= b);
+        self.scratch_free(fwt_evals);
+        domain.divide_by_vanishing_poly_on_coset_in_place(&mut tw_evals);
+        self.arithmetic().coset_ifft_in_place(domain, &mut tw_evals);
         end_timer!(q_timer);
-        // assert_eq!(q, qq);
-        let (q_cmt, q, q_rand) = self.commit("q", q.clone(), None, None).unwrap();
-        let k = domain.size();
+        DensePolynomial::from_coefficients_vec(tw_evals)
+    }
+
+    /// The partial products `t` of `f` over `domain`, and the quotient `q` that checks them.
+    fn unit_product(
+        &self,
+        f: &DensePolynomial<F>,
+        domain: Radix2EvaluationDomain<F>,
+    ) -> (DensePolynomial<F>, DensePolynomial<F>) {
+        let timer = start_timer!(|| "unit_product");
+        let t = self.partial_products(f, domain);
+        let q = self.unit_product_quotient(f, &t, domain);
         //        debug_assert_eq!(t.evaluate(&domain.element(k - 1)), F::one());
         //        for i in 0..k {
         //            let r = domain.element(i);
         //            debug_assert_eq!(t.evaluate(&(w * r)), t.evaluate(&r) * f.evaluate(&(w * r)));
         //        }
-        let r = self.fs_rng.borrow_mut().gen::<F>();
-        //        debug_assert_eq!(
-        //            t.evaluate(&(w * r)) - t.evaluate(&r) * f.evaluate(&(w * r)),
-        //            domain.evaluate_vanishing_polynomial(r) * q.evaluate(&r)
-        //        );
-        let t_wr_open = self.eval(&t, &t_rand, &t_cmt, w * r).unwrap();
-        let t_r_open = self.eval(&t, &t_rand, &t_cmt, r).unwrap();
-        let t_wk_open = self
-            .eval(&t, &t_rand, &t_cmt, domain.element(k - 1))
-            .unwrap();
-        let f_wr_open = self.eval(&f, &f_rand, &f_cmt, w * r).unwrap();
-        let q_r_open = self.eval(&q, &q_rand, &q_cmt, r).unwrap();
+        end_timer!(timer);
+        (t, q)
+    }
+
+    /// Open the unit product proof of `f` (with partial products `t` and quotient `q`) at `r`.
+    fn open_unit_product<D: EvaluationDomain<F>>(
+        &self,
+        f: &Committed<F, PC>,
+        t: &Committed<F, PC>,
+        q: &Committed<F, PC>,
+        domain: D,
+        r: F,
+    ) -> ProductProof<PC::Commitment, (F, PC::Proof)> {
+        let timer = start_timer!(|| "open_unit_product");
+        let k = domain.size();
+        let w = domain.element(1);
+        let t_wr_open = self.open(t, w * r);
+        let t_r_open = self.open(t, r);
+        let t_wk_open = self.open(t, domain.element(k - 1));
+        let f_wr_open = self.open(f, w * r);
+        let q_r_open = self.open(q, r);
         end_timer!(timer);
         //        debug_assert_eq!(
         //            t_wr_open.0 - t_r_open.0 * f_wr_open.0,
@@ -185,8 +877,8 @@ where
         //        );
         //        debug_assert_eq!(t_wk_open.0, F::one());
         ProductProof {
-            t_cmt: t_cmt.commitment,
-            q_cmt: q_cmt.commitment,
+            t_cmt: t.cmt.commitment.clone(),
+            q_cmt: q.cmt.commitment.clone(),
             t_wk_open,
             t_r_open,
             t_wr_open,
@@ -195,151 +887,302 @@ where
         }
     }
 
-    /// Prove that p(X) = p(w(X)) on the domain.
-    fn prove_wiring<D: EvaluationDomain<F>>(
+    /// L_1 = prod_j (P_j(X) + yW_j(X) + z)/(P_j(X) + yk_jX + z) over the gate domain, and its
+    /// numerator and denominator over the quotient domain's coset (held until
+    /// [Prover::wiring_quotient]).
+    ///
+    /// On the local rows (see [Prover::local_rows]), L_1 is the king's; only the rest are
+    /// multiplied and divided in MPC.
+    fn wiring_fraction(
         &self,
-        p: &LabeledPolynomial<F, DensePolynomial<F>>,
-        p_cmt: &LabeledCommitment<PC::Commitment>,
-        p_rand: &PC::Randomness,
+        p: &[&DensePolynomial<F>],
+        warmup: &Warmup<F>,
+        quotient: Radix2EvaluationDomain<F>,
+        y: F,
+        z: F,
+    ) -> (DensePolynomial<F>, util::Table<F>, util::Table<F>) {
+        let tables = &warmup.precomputed;
+        let dom = tables.x_evals.domain();
+        let local = self.local.map_or(&[][..], |local| local.rows());
+        let mut is_local = vec![false; dom.size()];
+        local.iter().for_each(|g| is_local[*g] = true);
+        let shared: Vec<usize> = (0..dom.size()).filter(|g| !is_local[*g]).collect();
+        let mut num_evals = vec![F::one(); shared.len()];
+        let mut den_evals = vec![F::one(); shared.len()];
+        let mut num_v = vec![F::one(); quotient.size()];
+        let mut den_v = vec![F::one(); quotient.size()];
+        for (j, p_j) in p.iter().enumerate() {
+            let yk = y * tables.shifts[j];
+            let mut p_evals = p_j.coeffs.clone();
+            self.arithmetic().fft_in_place(dom, &mut p_evals);
+            if !shared.is_empty() {
+                let w_evals = &warmup.w_evals[j].evals;
+                let num: Vec<F> = shared
+                    .iter()
+                    .map(|g| p_evals[*g] + y * w_evals[*g] + z)
+                    .collect();
+                let den: Vec<F> = shared
+                    .iter()
+                    .map(|g| p_evals[*g] + yk * tables.x_evals[*g] + z)
+                    .collect();
+                F::batch_product_in_place(&mut num_evals, &num);
+                F::batch_product_in_place(&mut den_evals, &den);
+            }
+            let mut p_v = self.scratch_copy(&p_j.coeffs);
+            self.arithmetic().coset_fft_in_place(quotient, &mut p_v);
+            ark_std::cfg_iter_mut!(num_v)
+                .zip(&p_v)
+                .zip(&warmup.w_coset_evals[j])
+                .for_each(|((a, p), w)| *a *= *p + y * w + z);
+            ark_std::cfg_iter_mut!(den_v)
+                .zip(&p_v)
+                .zip(&tables.x_coset_evals)
+                .for_each(|((a, p), x)| *a *= *p + yk * x + z);
+            self.scratch_free(p_v);
+        }
+        // One batch: in MPC, a constant number of openings however large the domain
+        if !shared.is_empty() {
+            F::batch_division_in_place(&mut num_evals, &den_evals);
+        }
+        let mut l1 = vec![F::zero(); dom.size()];
+        for (g, v) in shared.iter().zip(num_evals) {
+            l1[*g] = v;
+        }
+        if let Some(local) = self.local {
+            let w_evals: Vec<&[F]> = warmup.w_evals.iter().map(|w| &w.evals[..]).collect();
+            let rng = &mut **self.zk_rng.borrow_mut();
+            let local_l1 = local.l1(&w_evals, &tables.x_evals.evals, &tables.shifts, y, z, rng);
+            for (g, v) in local.rows().iter().zip(local_l1) {
+                l1[*g] = v;
+            }
+        }
+        self.arithmetic().ifft_in_place(dom, &mut l1);
+        let l1 = DensePolynomial::from_coefficients_vec(l1);
+        // Idle until the unit product proof is done
+        let num = self.table(num_v);
+        let den = self.table(den_v);
+        (l1, num, den)
+    }
+
+    /// The quotient of L_1 * den - num by the gate domain's vanishing polynomial, computed over
+    /// the quotient domain's coset.
+    fn wiring_quotient(
+        &self,
+        l1: &DensePolynomial<F>,
+        num: util::Table<F>,
+        den: util::Table<F>,
+        domains: &relations::flat::Domains<F>,
+    ) -> DensePolynomial<F> {
+        let quotient = domains.quotient();
+        let mut l1_den_v = self.scratch_copy(&l1.coeffs);
+        self.arithmetic()
+            .coset_fft_in_place(quotient, &mut l1_den_v);
+        let den_v = den.load();
+        ark_std::cfg_iter_mut!(l1_den_v)
+            .zip(&den_v)
+            .for_each(|(a, b)| *a *= b);
+        self.scratch_free(den_v);
+        let num_v = num.load();
+        ark_std::cfg_iter_mut!(l1_den_v)
+            .zip(&num_v)
+            .for_each(|(a, b)| *a -= b);
+        self.scratch_free(num_v);
+        util::divide_by_vanishing_on_coset(&mut l1_den_v, domains.gates, quotient);
+        self.arithmetic()
+            .coset_ifft_in_place(quotient, &mut l1_den_v);
+        // The quotient's degree is below the wires', so the rest are zeros (or, under MPC, shares
+        // of zero).
+        l1_den_v.truncate(domains.n_wires());
+        DensePolynomial::from_coefficients_vec(l1_den_v)
+    }
+
+    /// Commit to L_1 (with its unit product proof) and to L_2's quotient, the polynomials
+    /// showing that each wire's value is the next's in its cycle.
+    fn commit_wiring(
+        &self,
+        p: &[&DensePolynomial<F>],
+        warmup: &Warmup<F>,
+        domains: &relations::flat::Domains<F>,
+        points: &mut Points<F>,
+    ) -> WiringCommits<F, PC> {
+        let timer = start_timer!(|| "commit_wiring");
+        let y = self.transcript.borrow_mut().challenge::<F>(b"wiring_y");
+        let z = self.transcript.borrow_mut().challenge::<F>(b"wiring_z");
+        let (l1, num, den) = self.wiring_fraction(p, warmup, domains.quotient(), y, z);
+        // Nothing here depends on a later challenge, so every polynomial is committed to at once,
+        // and every commitment revealed in one round.
+        let (t, q) = self.unit_product(&l1, domains.gates);
+        let l2_q = self.wiring_quotient(&l1, num, den, domains);
+        let d = domains.gate_degree();
+        let polys = vec![
+            self.bounded("l1", l1, d),
+            self.bounded("t", t, d),
+            self.bounded("q", q, d),
+            self.bounded("l2_q", l2_q, domains.wiring_quotient_degree()),
+        ];
+        let mut pending = self.queue_many(polys).unwrap().into_iter();
+        let mut absorb_next = || self.absorb(pending.next().unwrap());
+        let (l1, t, q) = (absorb_next(), absorb_next(), absorb_next());
+        points.committed(SubProof::UnitProduct, &mut *self.transcript.borrow_mut());
+        let l2_q = absorb_next();
+        points.committed(SubProof::Wiring, &mut *self.transcript.borrow_mut());
+        end_timer!(timer);
+        WiringCommits { l1, t, q, l2_q }
+    }
+
+    /// Open the wiring proof at `x`, and its unit product proof at `r`.
+    fn open_wiring<D: EvaluationDomain<F>>(
+        &self,
+        p: &[Committed<F, PC>],
+        c: &WiringCommits<F, PC>,
         dom: D,
+        x: F,
+        r: F,
     ) -> WiringProof<PC::Commitment, (F, PC::Proof)> {
-        let timer = start_timer!(|| "prove_wiring");
-        let y = self.fs_rng.borrow_mut().gen::<F>();
-        let z = self.fs_rng.borrow_mut().gen::<F>();
-        let p_evals = p.evaluate_over_domain_by_ref(dom);
-        let w_evals = self.pk.w.evaluate_over_domain_by_ref(dom);
-        let yx_z_evals =
-            DensePolynomial::from_coefficients_vec(vec![z, y]).evaluate_over_domain_by_ref(dom);
-        let num_evals = &(&p_evals + &(&w_evals * &y)) + &z;
-        let den_evals = &(&p_evals + &yx_z_evals);
-        //TODO: batch!
-        let l1_evals = &num_evals / &den_evals;
-        let l1 = l1_evals.clone().interpolate();
-        let (l1_cmt, l1, l1_rand) = self.commit("l1", l1, None, None).unwrap();
-        let l1_prod_pf = self.prove_unit_product(&l1, &l1_cmt, &l1_rand, dom);
-        let l2_q_coeffs = {
-            let mut l1_v = l1.coeffs.clone();
-            let mut num_v = num_evals.interpolate().coeffs;
-            let mut den_v = den_evals.clone().interpolate().coeffs;
-            dom.coset_fft_in_place(&mut l1_v);
-            dom.coset_fft_in_place(&mut num_v);
-            dom.coset_fft_in_place(&mut den_v);
-            let mut l1_den_v = dom.mul_polynomials_in_evaluation_domain(&l1_v, &den_v);
-            ark_std::cfg_iter_mut!(l1_den_v)
-                .zip(num_v)
-                .for_each(|(a, b)| *a -= b);
-            dom.divide_by_vanishing_poly_on_coset_in_place(&mut l1_den_v);
-            dom.coset_ifft_in_place(&mut l1_den_v);
-            l1_den_v
-        };
-        let l2_q = DensePolynomial::from_coefficients_vec(l2_q_coeffs);
-        let (l2_q_cmt, l2_q, l2_q_rand) = self.commit("l2_q", l2_q, None, None).unwrap();
-        let x = self.fs_rng.borrow_mut().gen::<F>();
-        let l2_q_x_open = self.eval(&l2_q, &l2_q_rand, &l2_q_cmt, x).unwrap();
-        let w_x_open = self
-            .eval(&self.pk.w, &PC::Randomness::empty(), &self.pk.w_cmt, x)
-            .unwrap();
-        let l1_x_open = self.eval(&l1, &l1_rand, &l1_cmt, x).unwrap();
-        let p_x_open = self.eval(&p, &p_rand, &p_cmt, x).unwrap();
-        //        debug_assert_eq!(
-        //            (p_x_open.0 + y * x + z) * l1_x_open.0 - (p_x_open.0 + y * w_x_open.0 + z),
-        //            l2_q_x_open.0 * dom.evaluate_vanishing_polynomial(x)
-        //        );
+        let timer = start_timer!(|| "open_wiring");
+        let l1_prod_pf = self.open_unit_product(&c.l1, &c.t, &c.q, dom, r);
+        let l2_q_x_open = self.open(&c.l2_q, x);
+        let empty = PC::Randomness::empty();
+        let w_x_opens = self
+            .pk
+            .w
+            .iter()
+            .zip(&self.pk.w_cmts)
+            .map(|(w, w_cmt)| self.eval(w, &empty, w_cmt, x).unwrap())
+            .collect();
+        let l1_x_open = self.open(&c.l1, x);
+        let p_x_opens = p.iter().map(|p| self.open(p, x)).collect();
         end_timer!(timer);
         WiringProof {
             l1_prod_pf,
             l2_q_x_open,
             l1_x_open,
-            p_x_open,
-            w_x_open,
-            l1_cmt: l1_cmt.commitment,
-            l2_q_cmt: l2_q_cmt.commitment,
+            p_x_opens,
+            w_x_opens,
+            l1_cmt: c.l1.cmt.commitment.clone(),
+            l2_q_cmt: c.l2_q.cmt.commitment.clone(),
         }
     }
 
-    fn prove_public(
+    fn commit_public(
         &self,
-        p: &LabeledPolynomial<F, DensePolynomial<F>>,
-        p_cmt: &LabeledCommitment<PC::Commitment>,
-        p_rand: &PC::Randomness,
-        circ: &relations::flat::CircuitLayout<F>,
+        q: DensePolynomial<F>,
+        domains: &relations::flat::Domains<F>,
+        points: &mut Points<F>,
+    ) -> Committed<F, PC> {
+        let q = self.commit_bounded("pub_q", q, domains.gate_degree());
+        points.committed(SubProof::Public, &mut *self.transcript.borrow_mut());
+        q
+    }
+
+    /// Open the public proof at `x`: its quotient, and the `P_j` of the public `columns`.
+    fn open_public(
+        &self,
+        p: &[Committed<F, PC>],
+        q: &Committed<F, PC>,
+        columns: &[usize],
+        x: F,
     ) -> PublicProof<PC::Commitment, (F, PC::Proof)> {
-        let timer = start_timer!(|| "prove_public");
-        let points: Vec<(F, F)> = circ
-            .public_indices
-            .iter()
-            .map(|(_, i)| {
-                let x = circ.domains.wires.element(*i);
-                let y = p.evaluate(&x);
-                (x, y)
-            })
-            .collect();
-        let v = util::interpolate(&points);
-        let z = circ.vanishing_poly_on_inputs();
-        let (q, _r) = DenseOrSparsePolynomial::DPolynomial(Cow::Owned(p.polynomial() - &v))
-            .divide_with_q_and_r(&DenseOrSparsePolynomial::DPolynomial(Cow::Borrowed(&z)))
-            .unwrap();
-        let (q_cmt, q, q_rand) = self.commit("pub_q", q, None, None).unwrap();
-        let x = self.fs_rng.borrow_mut().gen::<F>();
-        let q_open = self.eval(&q, &q_rand, &q_cmt, x).unwrap();
-        let p_open = self.eval(&p, &p_rand, &p_cmt, x).unwrap();
-        //debug_assert!( p_open.0 - v.evaluate(&x), q_open.0 * z.evaluate(&x));
+        let timer = start_timer!(|| "open_public");
+        let q_open = self.open(q, x);
+        let p_opens = columns.iter().map(|j| self.open(&p[*j], x)).collect();
         end_timer!(timer);
         PublicProof {
             q_open,
-            q_cmt: q_cmt.commitment,
-            p_open,
+            q_cmt: q.cmt.commitment.clone(),
+            p_opens,
         }
     }
 
-    fn prove_gates(
+    /// Absorb the gates quotient's commitment, queued by [Prover::queue_many].
+    fn commit_gates(&self, q: Pending<F, PC>, points: &mut Points<F>) -> Committed<F, PC> {
+        let q = self.absorb(q);
+        points.committed(SubProof::Gates, &mut *self.transcript.borrow_mut());
+        q
+    }
+
+    fn open_gates(
         &self,
-        p: &LabeledPolynomial<F, DensePolynomial<F>>,
-        p_cmt: &LabeledCommitment<PC::Commitment>,
-        p_rand: &PC::Randomness,
-        circ: &relations::flat::CircuitLayout<F>,
-    ) -> GateProof<PC::Commitment, (F, PC::Proof)> {
-        let timer = start_timer!(|| "prove_gates");
-        let w = circ.domains.wires.group_gen;
-        let pw = util::shift(p.polynomial().clone(), w);
-        let pww = util::shift(p.polynomial().clone(), w * w);
-        let d = &(&circ.s * &(p.polynomial() + &pw)
-            + &(&(&circ.s * &-F::one()) + &F::one()) * &(p.polynomial() * &pw))
-            - &pww;
-        let (q, _r) = DenseOrSparsePolynomial::DPolynomial(Cow::Owned(d))
-            .divide_with_q_and_r(&DenseOrSparsePolynomial::SPolynomial(Cow::Owned(
-                circ.domains.gates.vanishing_polynomial(),
-            )))
-            .unwrap();
-        // debug_assert!(r.is_zero());
-        let (q_cmt, q, q_rand) = self.commit("gates_q", q, None, None).unwrap();
-        let x = self.fs_rng.borrow_mut().gen::<F>();
-        let s_open = self
-            .eval(&self.pk.s, &PC::Randomness::empty(), &self.pk.s_cmt, x)
+        p: &[Committed<F, PC>],
+        q: &Committed<F, PC>,
+        circ: &relations::flat::Instance<F>,
+        x: F,
+    ) -> GateProof<F, PC::Commitment, PC::Proof> {
+        let timer = start_timer!(|| "open_gates");
+        let p = &p[..GATE_WIDTH];
+        // The P_j's values are the linearization's coefficients, so they're public before it's
+        // opened, as is the quotient's value, which the linearization's depends on.
+        let mut p_values: Vec<F> = p.iter().map(|p| p.poly.polynomial().evaluate(&x)).collect();
+        let mut q_value = q.poly.polynomial().evaluate(&x);
+        let mut values: Vec<&mut F> = p_values.iter_mut().collect();
+        values.push(&mut q_value);
+        self.publish(values);
+        let v = gates_challenge::<F, T>(x, &p_values, q_value);
+        let z_x = circ.domains.gates.evaluate_vanishing_polynomial(x);
+        let (lcs, query_set, _) = gates_linearization(x, z_x, &p_values, q_value);
+        let empty = PC::Randomness::empty();
+        let mut polys = vec![&self.pk.s, &q.poly];
+        polys.extend(p.iter().map(|p| &p.poly));
+        let mut cmts = vec![&self.pk.s_cmt, &q.cmt];
+        cmts.extend(p.iter().map(|p| &p.cmt));
+        let mut rands = vec![&empty, &q.rand];
+        rands.extend(p.iter().map(|p| &p.rand));
+        let batch = self
+            .recorder
+            .open(|| {
+                taint::approved(|| {
+                    self.committer.open_combinations(
+                        &self.pk.pc_ck,
+                        &lcs,
+                        &polys,
+                        &cmts,
+                        &query_set,
+                        v,
+                        &rands,
+                        Some(&mut *self.zk_rng.borrow_mut()),
+                    )
+                })
+            })
             .unwrap();
-        let p_open = self.eval(p, p_rand, p_cmt, x).unwrap();
-        let q_open = self.eval(&q, &q_rand, &q_cmt, x).unwrap();
-        let p_w_open = self.eval(p, p_rand, p_cmt, w * x).unwrap();
-        let p_w2_open = self.eval(p, p_rand, p_cmt, w * w * x).unwrap();
-        //        debug_assert_eq!(
-        //            s_open.0 * (p_open.0 + p_w_open.0) + (F::one() - s_open.0) * p_open.0 * p_w_open.0
-        //                - p_w2_open.0,
-        //            q_open.0 * circ.domains.gates.evaluate_vanishing_polynomial(x)
-        //        );
+        let mut proofs: Vec<PC::Proof> = batch.proof.into();
+        assert_eq!(proofs.len(), 1, "One point, so one opening proof");
         end_timer!(timer);
         GateProof {
-            q_cmt: q_cmt.commitment,
-            s_open,
-            p_open,
-            q_open,
-            p_w_open,
-            p_w2_open,
+            q_cmt: q.cmt.commitment.clone(),
+            p_values,
+            q_value,
+            open: proofs.pop().unwrap(),
+        }
+    }
+
+    /// A copy of `coeffs`, in a free scratch buffer if there is one.
+    fn scratch_copy(&self, coeffs: &[F]) -> Vec<F> {
+        let mut buf = self.scratch.borrow_mut().pop().unwrap_or_default();
+        buf.clear();
+        buf.extend_from_slice(coeffs);
+        buf
+    }
+
+    /// Return `buf` to the scratch buffers.
+    fn scratch_free(&self, buf: Vec<F>) {
+        self.scratch.borrow_mut().push(buf);
+    }
+
+    /// Hold `elems` where the memory mode says.
+    fn table(&self, elems: Vec<F>) -> util::Table<F> {
+        match &self.memory {
+            MemoryMode::InMemory => util::Table::new(elems, None),
+            MemoryMode::Spill(dir) => util::Table::new(elems, Some(dir)),
         }
     }
 
     /// Evaluate polynomial `p` at `x`, producing a proof of the evaluation as well.
     ///
-    /// With respect to a commitment `p_c` under randomness `p_r`.
+    /// With respect to a commitment `p_c` under randomness `p_r`. An opening this proof has
+    /// already made is reused.
+    ///
+    /// Both are shared: the value until the proof is published (see [Prover::publish]), and the
+    /// proof until the caller reveals it.
     fn eval(
         &self,
         p: &LabeledPolynomial<F, DensePolynomial<F>>,
@@ -347,241 +1190,1269 @@ where
         p_c: &LabeledCommitment<PC::Commitment>,
         x: F,
     ) -> Result<(F, PC::Proof), Error<PC::Error>> {
+        let key = (p.label().clone(), x);
+        if let Some(open) = self.openings.borrow().get(&key) {
+            return Ok(open.clone());
+        }
         let timer = start_timer!(|| format!("open: {}", p.label()));
-        let pf_p = PC::open(
-            &self.pk.pc_ck,
-            once(p),
-            once(p_c),
-            &x,
-            F::one(), // acceptable b/c this is just one commitment.
-            once(p_r),
-            Some(&mut *self.zk_rng.borrow_mut()),
-        )?;
-        let mut y = p.polynomial().evaluate(&x);
-        let p_timer = start_timer!(|| "publicize");
-        y.publicize();
-        end_timer!(p_timer);
+        let pf_p = self.recorder.open(|| {
+            taint::approved(|| {
+                self.committer.open(
+                    &self.pk.pc_ck,
+                    p,
+                    p_c,
+                    x,
+                    p_r,
+                    Some(&mut *self.zk_rng.borrow_mut()),
+                )
+            })
+        })?;
+        // Shared until the proof is published
+        let y = p.polynomial().evaluate(&x);
         end_timer!(timer);
+        self.openings.borrow_mut().insert(key, (y, pf_p.clone()));
         Ok((y, pf_p))
     }
 
-    /// Commit to a polynomial `p`.
-    ///
-    /// Produces a (commitment, labeled_poly, randomness) triple.
-    fn commit(
+    /// Evaluate committed polynomial `c` at `x`, with a proof.
+    fn open(&self, c: &Committed<F, PC>, x: F) -> (F, PC::Proof) {
+        self.eval(&c.poly, &c.rand, &c.cmt, x).unwrap()
+    }
+
+    /// `p`, named `name`, with degree bound `degree`, hidden if the prover hides commitments.
+    fn labeled(
         &self,
-        label: impl ark_std::fmt::Display,
+        name: String,
         p: DensePolynomial<F>,
         degree: Option<usize>,
-        hiding_bound: Option<usize>,
-    ) -> Result<
-        (
-            LabeledCommitment<PC::Commitment>,
-            LabeledPolynomial<F, DensePolynomial<F>>,
-            PC::Randomness,
-        ),
-        Error<PC::Error>,
-    > {
-        debug!("commit: {}", label);
-        let timer = start_timer!(|| format!("commit: {}", label));
-        let label_p = LabeledPolynomial::new(format!("{}", label), p, degree, hiding_bound);
-        let (mut cs, mut rs) = PC::commit(
-            &self.pk.pc_ck,
-            once(&label_p),
-            Some(&mut *self.zk_rng.borrow_mut()),
-        )?;
-        assert_eq!(cs.len(), 1);
-        assert_eq!(rs.len(), 1);
-        let mut c = cs.pop().unwrap();
-        c.commitment.publicize();
-        self.fs_rng
-            .borrow_mut()
-            .absorb(&ark_ff::to_bytes![c].expect("failed serialization"));
-        end_timer!(timer);
-        Ok((c, label_p, rs.pop().unwrap()))
+    ) -> LabeledPolynomial<F, DensePolynomial<F>> {
+        LabeledPolynomial::new(name, p, degree, self.hiding)
     }
 
-    pub fn prove(
+    /// Commit to each polynomial in `polys` (see [Prover::labeled]), in one call to the
+    /// committer backend, and queue the commitments to be revealed. [Prover::absorb] absorbs each
+    /// under its label.
+    fn queue_many(
         &self,
-        circ: &relations::flat::CircuitLayout<F>,
-    ) -> Proof<F, PC::Commitment, PC::Proof> {
-        assert!(circ.p.is_some());
-        let n_gates = circ.domains.gates.size();
-        let n_wires = n_gates * 3;
-        let (p_cmt, p, p_rand) = self
-            .commit(
-                "p".to_owned(),
-                circ.p.clone().unwrap(),
-                Some(n_wires - 1),
-                None,
-            )
-            .unwrap();
-        let public = self.prove_public(&p, &p_cmt, &p_rand, circ);
-        let gates = self.prove_gates(&p, &p_cmt, &p_rand, circ);
-        let wiring = self.prove_wiring(&p, &p_cmt, &p_rand, circ.domains.wires);
-        Proof {
-            p_cmt: p_cmt.commitment,
-            wiring,
-            gates,
-            public,
+        polys: Vec<(&'static str, LabeledPolynomial<F, DensePolynomial<F>>)>,
+    ) -> Result<Vec<Pending<F, PC>>, Error<PC::Error>> {
+        let names: Vec<&str> = polys.iter().map(|(_, p)| p.label().as_str()).collect();
+        debug!("commit: {}", names.join(", "));
+        let timer = start_timer!(|| format!("commit: {}", names.join(", ")));
+        let ps: Vec<_> = polys.iter().map(|(_, p)| p).collect();
+        let cmts = self.recorder.commit(|| {
+            taint::approved(|| {
+                self.committer.commit_many(
+                    &self.pk.pc_ck,
+                    &ps,
+                    Some(&mut *self.zk_rng.borrow_mut()),
+                )
+            })
+        })?;
+        let found: Vec<&str> = cmts.iter().map(|(c, _)| c.label().as_str()).collect();
+        if found != names {
+            let detail = format!("committed to {:?}, not {:?}", found, names);
+            return Err(Error::Backend(detail));
         }
+        // Queued together, so every party's shares of them go in one exchange
+        let pending = polys
+            .into_iter()
+            .zip(cmts)
+            .map(|((label, poly), (c, rand))| {
+                let cmt = taint::approved(|| defer::enqueue(c));
+                self.queued.borrow_mut().push(poly.label().clone());
+                Pending {
+                    label,
+                    cmt,
+                    poly,
+                    rand,
+                }
+            })
+            .collect();
+        end_timer!(timer);
+        Ok(pending)
     }
-}
 
-pub struct Verifier<'r, F: FftField, PC: PolynomialCommitment<F, DensePolynomial<F>>> {
-    _field: PhantomData<F>,
-    _pc: PhantomData<PC>,
-    vk: &'r VerifierKey<PC::Commitment, PC::VerifierKey>,
-    fs_rng: RefCell<FiatShamirRng<Blake2s>>,
-}
-#[allow(dead_code)]
-impl<'r, F: FftField, PC: PolynomialCommitment<F, DensePolynomial<F>>> Verifier<'r, F, PC>
-where
-    PC::Commitment: mpc_trait::MpcWire,
-    PC::Error: 'static,
-{
-    pub fn new(vk: &'r VerifierKey<PC::Commitment, PC::VerifierKey>) -> Self {
-        Self {
-            _field: PhantomData::default(),
-            _pc: PhantomData::default(),
-            vk,
-            fs_rng: RefCell::new(FiatShamirRng::from_seed(&0u64)),
+    /// Reveal a queued commitment, and absorb it. The first of a round's commitments to be
+    /// absorbed reveals them all.
+    fn absorb(&self, pending: Pending<F, PC>) -> Committed<F, PC> {
+        {
+            let mut queued = self.queued.borrow_mut();
+            if queued.contains(pending.poly.label()) {
+                self.rounds.borrow_mut().push(std::mem::take(&mut *queued));
+            }
+        }
+        // The transcript needs it now, so flush the open queue; anything already queued shares
+        // the round. With MAC'd shares (SPDZ), the flush runs a batched MAC check on every value
+        // opened so far.
+        let Pending {
+            label,
+            cmt,
+            poly,
+            rand,
+        } = pending;
+        let cmt = taint::approved(|| cmt.get());
+        self.transcript.borrow_mut().absorb(label.as_bytes(), &cmt);
+        Committed { cmt, poly, rand }
+    }
+
+    /// Make a proof's opened `values` public, all in one flush of the open queue, rather than a
+    /// round each.
+    fn publish(&self, values: Vec<&mut F>) {
+        let timer = start_timer!(|| "publish");
+        let shared: Vec<F> = values.iter().map(|y| **y).collect();
+        // With MAC'd shares (SPDZ), the flush runs a batched MAC check on every value opened so
+        // far, so a corrupted opening can't reach the proof.
+        let public = taint::approved(|| defer::enqueue(shared).get());
+        for (y, x) in values.into_iter().zip(public) {
+            *y = x;
         }
+        end_timer!(timer);
     }
-    fn verify_unit_product<D: EvaluationDomain<F>>(
+
+    /// `p`, labeled `label`, with degree bound `degree`, to be committed to by
+    /// [Prover::queue_many].
+    fn bounded(
         &self,
-        f_cmt: &LabeledCommitment<PC::Commitment>,
-        pf: ProductProof<PC::Commitment, (F, PC::Proof)>,
-        domain: D,
-    ) {
-        let k = domain.size();
-        let w = domain.element(1);
-        let t_cmt = self.recv_commit("t", pf.t_cmt, None);
-        let q_cmt = self.recv_commit("q", pf.q_cmt, None);
-        let r = self.fs_rng.borrow_mut().gen::<F>();
-        // Check commitments
-        let f_wr = self.check(f_cmt, w * r, &pf.f_wr_open);
-        let q_r = self.check(&q_cmt, r, &pf.q_r_open);
-        let t_r = self.check(&t_cmt, r, &pf.t_r_open);
-        let t_wr = self.check(&t_cmt, w * r, &pf.t_wr_open);
-        let t_wk = self.check(&t_cmt, domain.element(k - 1), &pf.t_wk_open);
-        // Check partial product
-        let l = t_wr - t_r * f_wr;
-        let r = domain.evaluate_vanishing_polynomial(r) * q_r;
-        assert_eq!(l, r, "Partial product failure: \n{}\nnot equal to\n{}", l, r);
-        // Check total product is 1
-        assert_eq!(t_wk, F::one());
+        label: &'static str,
+        p: DensePolynomial<F>,
+        degree: usize,
+    ) -> (&'static str, LabeledPolynomial<F, DensePolynomial<F>>) {
+        (label, self.labeled(label.to_owned(), p, Some(degree)))
     }
-    /// Receive a commitment
-    ///
-    /// Produces a (commitment, labeled_poly, randomness) triple.
-    fn recv_commit(
+
+    /// Commit to `p`, with degree bound `degree`.
+    fn commit_bounded(
         &self,
-        label: impl ark_std::fmt::Display,
-        c: PC::Commitment,
+        label: &'static str,
+        p: DensePolynomial<F>,
+        degree: usize,
+    ) -> Committed<F, PC> {
+        let mut pending = self
+            .queue_many(vec![self.bounded(label, p, degree)])
+            .unwrap();
+        self.absorb(pending.pop().unwrap())
+    }
+
+    /// Each column's polynomial in `ps`, named by `label` and its column (see [column_label]),
+    /// with degree bound `degree`, to be committed to by [Prover::queue_many].
+    fn columns(
+        &self,
+        label: &'static str,
+        ps: Vec<DensePolynomial<F>>,
+        degree: Option<usize>,
+    ) -> Vec<(&'static str, LabeledPolynomial<F, DensePolynomial<F>>)> {
+        ps.into_iter()
+            .enumerate()
+            .map(|(j, p)| (label, self.labeled(column_label(label, j), p, degree)))
+            .collect()
+    }
+
+    /// Commit to each column's polynomial in `ps` (see [Prover::columns]) at once, revealing the
+    /// commitments in one round.
+    fn commit_columns(
+        &self,
+        label: &'static str,
+        ps: Vec<DensePolynomial<F>>,
+        degree: Option<usize>,
+    ) -> Vec<Committed<F, PC>> {
+        let pending = self.queue_many(self.columns(label, ps, degree)).unwrap();
+        pending.into_iter().map(|p| self.absorb(p)).collect()
+    }
+
+    /// The labels of the commitments revealed in each round of the last proof: for
+    /// [Prover::prove], as its [prove_schedule] plans.
+    pub fn commit_rounds(&self) -> Vec<Vec<String>> {
+        self.rounds.borrow().clone()
+    }
+
+    /// What each phase of the last proof cost (see [metrics]).
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> ProverMetrics {
+        self.recorder.metrics()
+    }
+
+    pub fn prove(
+        &self,
+        circ: &relations::flat::Instance<F>,
+        witness: &relations::flat::Witness<F>,
+    ) -> Proof<F, PC::Commitment, PC::Proof> {
+        // Only commitments and evaluations may make witness-derived values public.
+        taint::sensitive(|| self.prove_inner(circ, witness))
+    }
+
+    fn prove_inner(
+        &self,
+        circ: &relations::flat::Instance<F>,
+        witness: &relations::flat::Witness<F>,
+    ) -> Proof<F, PC::Commitment, PC::Proof> {
+        self.check_circuit(circ);
+        self.clear();
+        let p = witness.polys(circ);
+        self.recorder.enter(Phase::Warmup);
+        let warmup = self.warmup_for(circ);
+        // The gates quotient depends on no challenge, so its commitment is revealed with the
+        // P_j's (see [prove_schedule]).
+        self.recorder.enter(Phase::Gates);
+        let gates_q = {
+            let p_polys: Vec<&DensePolynomial<F>> = p.iter().collect();
+            warmup.gates_quotient(&p_polys, circ)
+        };
+        self.recorder.enter(Phase::Columns);
+        let mut polys = self.columns("p", p, Some(circ.domains.gate_degree()));
+        let gates_q_degree = circ.domains.gates_quotient_degree();
+        polys.push(self.bounded("gates_q", gates_q, gates_q_degree));
+        let mut p = self.queue_many(polys).unwrap();
+        let gates_q = p.pop().unwrap();
+        let p: Vec<_> = p.into_iter().map(|p| self.absorb(p)).collect();
+        self.recorder.enter(Phase::Public);
+        let beta = self.transcript.borrow_mut().challenge::<F>(b"public_beta");
+        let p_polys: Vec<&DensePolynomial<F>> = p.iter().map(|p| p.poly.polynomial()).collect();
+        let public_q = warmup.public_quotient(&p_polys, beta);
+        // Commit to everything, then open where the policy says.
+        let mut points = Points::new(self.policy, circ.domains.gates.group_gen);
+        let public_q = self.commit_public(public_q, &circ.domains, &mut points);
+        self.recorder.enter(Phase::Gates);
+        let gates_q = self.commit_gates(gates_q, &mut points);
+        self.recorder.enter(Phase::Wiring);
+        let wiring = self.commit_wiring(&p_polys, &warmup, &circ.domains, &mut points);
+        self.recorder.enter(Phase::Public);
+        let public = self.open_public(
+            &p,
+            &public_q,
+            &circ.public_columns(),
+            points.get(SubProof::Public),
+        );
+        self.recorder.enter(Phase::Gates);
+        let gates = self.open_gates(&p, &gates_q, circ, points.get(SubProof::Gates));
+        self.recorder.enter(Phase::Wiring);
+        let wiring = self.open_wiring(
+            &p,
+            &wiring,
+            circ.domains.gates,
+            points.get(SubProof::Wiring),
+            points.get(SubProof::UnitProduct),
+        );
+        let mut pf = Proof {
+            p_cmts: p.into_iter().map(|p| p.cmt.commitment).collect(),
+            wiring,
+            gates,
+            public,
+            beacon_round: self.beacon_round,
+        };
+        self.recorder.enter(Phase::Publish);
+        self.publish(pf.values_mut());
+        self.recorder.finish();
+        pf
+    }
+
+    /// Forget the last proof's openings and rounds.
+    fn clear(&self) {
+        self.openings.borrow_mut().clear();
+        self.queued.borrow_mut().clear();
+        self.rounds.borrow_mut().clear();
+        self.recorder.clear();
+    }
+
+    /// Check that `circ` is laid out as the prover key's circuit is (see
+    /// [relations::flat::Instance::digest]).
+    fn check_circuit(&self, circ: &relations::flat::Instance<F>) {
+        let found = circ.digest();
+        if found != self.pk.circuit_digest {
+            let detail = format!(
+                "Circuit layout differs from the prover key's: digest {}, not {}",
+                found, self.pk.circuit_digest
+            );
+            Abort::new(AbortCode::SrsMismatch, detail).raise();
+        }
+    }
+
+    /// Check, in one round over `N`, that every party's transcript agrees with ours: otherwise,
+    /// so do their challenges, and their shares make no proof. Draws a challenge, so call it
+    /// after proving.
+    ///
+    /// `N` should be the network the parties' shares are exchanged on, for the round to reach
+    /// the parties that proved.
+    fn check_transcript<N: MpcNet>(&self) {
+        let mut digest = [0u8; 32];
+        self.transcript
+            .borrow_mut()
+            .challenge_bytes(b"transcript_check", &mut digest);
+        let all = N::broadcast_bytes(&digest);
+        if let Some(i) = all.iter().position(|d| d[..] != digest[..]) {
+            let detail = format!("party {}'s transcript differs from ours", i);
+            Abort::new(AbortCode::TranscriptDivergence, detail).raise();
+        }
+    }
+
+    /// The warmup for `circ`: the one precomputed by [Prover::warmup], if any.
+    fn warmup_for(&self, circ: &relations::flat::Instance<F>) -> Cow<'_, Warmup<F>> {
+        match &self.warmup {
+            Some(warmup) => {
+                let tables = &warmup.precomputed;
+                assert_eq!(
+                    (tables.x_evals.domain().size(), tables.shifts.len()),
+                    (circ.domains.gates.size(), circ.domains.width),
+                    "Prover was warmed up for a different circuit"
+                );
+                Cow::Borrowed(warmup)
+            }
+            None => Cow::Owned(Warmup::new(&self.pk.w, circ, &self.arithmetic())),
+        }
+    }
+
+    /// Prove, with one quotient for every check (see [SmallProof]).
+    pub fn prove_small(
+        &self,
+        circ: &relations::flat::Instance<F>,
+        witness: &relations::flat::Witness<F>,
+    ) -> SmallProof<F, PC::Commitment, PC::Proof> {
+        taint::sensitive(|| self.prove_small_inner(circ, witness))
+    }
+
+    fn prove_small_inner(
+        &self,
+        circ: &relations::flat::Instance<F>,
+        witness: &relations::flat::Witness<F>,
+    ) -> SmallProof<F, PC::Commitment, PC::Proof> {
+        self.check_circuit(circ);
+        self.clear();
+        let p = witness.polys(circ);
+        let dom = circ.domains.gates;
+        let k = dom.size();
+        let d = circ.domains.gate_degree();
+        self.recorder.enter(Phase::Columns);
+        let p = self.commit_columns("p", p, Some(d));
+        self.recorder.enter(Phase::Warmup);
+        let warmup = self.warmup_for(circ);
+        // The public and gate quotients, computed together
+        self.recorder.enter(Phase::Gates);
+        let beta = self.transcript.borrow_mut().challenge::<F>(b"public_beta");
+        let p_polys: Vec<&DensePolynomial<F>> = p.iter().map(|p| p.poly.polynomial()).collect();
+        let (public_q, gates_q) = util::join(
+            || warmup.public_quotient(&p_polys, beta),
+            || warmup.gates_quotient(&p_polys, circ),
+        );
+        self.recorder.enter(Phase::Wiring);
+        let y = self.transcript.borrow_mut().challenge::<F>(b"wiring_y");
+        let z = self.transcript.borrow_mut().challenge::<F>(b"wiring_z");
+        let (l1, num, den) = self.wiring_fraction(&p_polys, &warmup, circ.domains.quotient(), y, z);
+        // L_1 and its partial products are committed to, and revealed, together.
+        let t = self.partial_products(&l1, dom);
+        let mut pending = self
+            .queue_many(vec![self.bounded("l1", l1, d), self.bounded("t", t, d)])
+            .unwrap()
+            .into_iter();
+        let l1 = self.absorb(pending.next().unwrap());
+        let t = self.absorb(pending.next().unwrap());
+        let product_q = self.unit_product_quotient(l1.poly.polynomial(), t.poly.polynomial(), dom);
+        let wiring_q = self.wiring_quotient(l1.poly.polynomial(), num, den, &circ.domains);
+        self.recorder.enter(Phase::Quotient);
+        let alpha = self.transcript.borrow_mut().challenge::<F>(b"small_alpha");
+        // Q = public_q + alpha * gates_q + alpha^2 * product_q + alpha^3 * wiring_q
+        let q = [wiring_q, product_q, gates_q, public_q]
+            .iter()
+            .fold(DensePolynomial::zero(), |acc, q| &(&acc * &alpha) + q);
+        // Split as Q(X) = sum_j X^{j*k} Q_j(X), so each chunk has degree below k. There are
+        // always as many chunks as columns.
+        let width = circ.domains.width;
+        assert!(q.coeffs.len() <= k * width, "Quotient too large");
+        // Under MPC, parties' shares of Q may differ in length (each drops its leading zero
+        // shares), so pad with a zero that is shared whenever Q is, keeping every chunk, and its
+        // evaluation, shared at every party.
+        let zero = q.coeffs.first().map_or_else(F::zero, |c| *c - c);
+        let qs = (0..width)
+            .map(|j| {
+                let q_j = (j * k..(j + 1) * k)
+                    .map(|i| q.coeffs.get(i).cloned().unwrap_or(zero))
+                    .collect();
+                DensePolynomial::from_coefficients_vec(q_j)
+            })
+            .collect();
+        let qs = self.commit_columns("q", qs, Some(d));
+        let x = self.transcript.borrow_mut().challenge::<F>(b"small_x");
+        let w = dom.element(1);
+        let q_opens = qs.iter().map(|q| self.open(q, x)).collect();
+        let empty = PC::Randomness::empty();
+        let mut pf = SmallProof {
+            p_opens: p.iter().map(|p| self.open(p, x)).collect(),
+            s_open: self.eval(&self.pk.s, &empty, &self.pk.s_cmt, x).unwrap(),
+            w_opens: self
+                .pk
+                .w
+                .iter()
+                .zip(&self.pk.w_cmts)
+                .map(|(w, w_cmt)| self.eval(w, &empty, w_cmt, x).unwrap())
+                .collect(),
+            l1_open: self.open(&l1, x),
+            l1_w_open: self.open(&l1, w * x),
+            t_open: self.open(&t, x),
+            t_w_open: self.open(&t, w * x),
+            t_wk_open: self.open(&t, dom.element(k - 1)),
+            q_opens,
+            p_cmts: p.into_iter().map(|p| p.cmt.commitment).collect(),
+            l1_cmt: l1.cmt.commitment,
+            t_cmt: t.cmt.commitment,
+            q_cmts: qs.into_iter().map(|q| q.cmt.commitment).collect(),
+            beacon_round: self.beacon_round,
+        };
+        self.recorder.enter(Phase::Publish);
+        self.publish(pf.values_mut());
+        self.recorder.finish();
+        pf
+    }
+}
+
+pub struct Verifier<
+    'r,
+    F: FftField,
+    PC: PolynomialCommitment<F, DensePolynomial<F>>,
+    T: Transcript = Blake2sTranscript,
+> {
+    _field: PhantomData<F>,
+    _pc: PhantomData<PC>,
+    vk: &'r VerifierKey<PC::Commitment, PC::VerifierKey>,
+    transcript: RefCell<T>,
+    beacon_round: Option<u64>,
+    policy: &'r dyn OpeningPolicy,
+    /// If set, openings are recorded here, rather than checked.
+    deferred: Option<RefCell<DeferredOpenings<F, PC::Commitment>>>,
+    /// If set, openings are queued here, to be checked together (see
+    /// [Verifier::batch_openings]).
+    batched: Option<OpeningQueue<F, PC::Commitment, PC::Proof>>,
+    /// If set, failed checks are recorded here, rather than panicking.
+    failures: Option<RefCell<Vec<Failure<F>>>>,
+}
+
+/// A claim that a linear combination of committed polynomials takes a value at a point:
+/// ((coefficient, commitment) terms, point, value).
+type Query<F, C> = (Vec<(F, LabeledCommitment<C>)>, F, F);
+/// The claims that one opening proof makes, at one point, batched with powers of a challenge:
+/// (claims, challenge).
+type Opening<F, C> = (Vec<Query<F, C>>, F);
+type DeferredOpenings<F, C> = Vec<Opening<F, C>>;
+/// An opening of one commitment, not yet checked: (commitment, point, value, proof).
+type QueuedOpening<F, C, P> = (LabeledCommitment<C>, F, F, P);
+type OpeningQueue<F, C, P> = RefCell<Vec<QueuedOpening<F, C, P>>>;
+
+#[allow(dead_code)]
+impl<'r, F: FftField, PC: PolynomialCommitment<F, DensePolynomial<F>>, T: Transcript>
+    Verifier<'r, F, PC, T>
+where
+    PC::Commitment: mpc_trait::MpcWire,
+    PC::Error: 'static,
+{
+    pub fn new(vk: &'r VerifierKey<PC::Commitment, PC::VerifierKey>) -> Self {
+        Self::new_with_beacon(vk, None)
+    }
+    /// A verifier for proofs whose challenges also depend on `beacon`.
+    pub fn new_with_beacon(
+        vk: &'r VerifierKey<PC::Commitment, PC::VerifierKey>,
+        beacon: Option<&Beacon>,
+    ) -> Self {
+        Self::new_in_session(vk, beacon, &Session::default())
+    }
+    /// A verifier for proofs whose challenges also depend on `beacon`, and on `session`, which
+    /// the verifier must build itself: from its own nonce and public inputs, not the prover's.
+    pub fn new_in_session(
+        vk: &'r VerifierKey<PC::Commitment, PC::VerifierKey>,
+        beacon: Option<&Beacon>,
+        session: &Session,
+    ) -> Self {
+        Self {
+            _field: PhantomData::default(),
+            _pc: PhantomData::default(),
+            vk,
+            transcript: RefCell::new(transcript_init(&vk.circuit_digest, beacon, session)),
+            beacon_round: beacon.map(|b| b.round),
+            policy: &PerSubProof,
+            deferred: None,
+            batched: None,
+            failures: None,
+        }
+    }
+    /// Set where the proof's polynomials are opened (by default, [PerSubProof]), as the prover
+    /// did.
+    pub fn opening_policy(&mut self, policy: &'r dyn OpeningPolicy) {
+        self.policy = policy;
+    }
+    /// Check the openings of single commitments together, with one
+    /// [PolynomialCommitment::batch_check] at the end of [Verifier::verify] or
+    /// [Verifier::verify_small], rather than one at a time: with KZG, one pairing check rather
+    /// than one an opening. The checks are combined with
+    /// randomness from the transcript, so a failure names no opening (see
+    /// [Failure::BatchedOpenings]).
+    ///
+    /// With the sub-verifiers, call [Verifier::check_batched_openings] once they are done.
+    pub fn batch_openings(&mut self) {
+        self.batched = Some(RefCell::new(Vec::new()));
+    }
+    /// Check every queued opening (see [Verifier::batch_openings]), in one batch, and empty the
+    /// queue.
+    pub fn check_batched_openings(&self) {
+        let queued = match &self.batched {
+            Some(queued) => queued.take(),
+            None => return,
+        };
+        if queued.is_empty() {
+            return;
+        }
+        // Each opening is its own commitment, at its own point, so that none is merged with
+        // another, and the proofs are in point label order.
+        let mut cmts = Vec::with_capacity(queued.len());
+        let mut query_set = QuerySet::new();
+        let mut evals = ark_poly_commit::Evaluations::new();
+        let mut proofs = Vec::with_capacity(queued.len());
+        for (i, (cmt, x, value, proof)) in queued.iter().enumerate() {
+            let label = format!("{:08}", i);
+            cmts.push(LabeledCommitment::new(
+                label.clone(),
+                cmt.commitment().clone(),
+                cmt.degree_bound(),
+            ));
+            query_set.insert((label.clone(), (label.clone(), *x)));
+            evals.insert((label, *x), *value);
+            proofs.push(proof.clone());
+        }
+        let mut seed = [0u8; 32];
+        self.transcript
+            .borrow_mut()
+            .challenge_bytes(b"batch_check", &mut seed);
+        let valid = PC::batch_check(
+            &self.vk.pc_vk,
+            &cmts,
+            &query_set,
+            &evals,
+            &proofs.into(),
+            F::one(), // Okay b/c a single commit at each point
+            &mut ChaChaRng::from_seed(seed),
+        );
+        if !matches!(valid, Ok(true)) {
+            self.fail(Failure::BatchedOpenings {
+                count: queued.len(),
+            });
+        }
+    }
+    /// A verifier that continues `transcript` (e.g., that of an enclosing protocol), for use with
+    /// the sub-verifiers ([Verifier::verify_public], [Verifier::verify_gates], and
+    /// [Verifier::verify_wiring]).
+    pub fn with_transcript(
+        vk: &'r VerifierKey<PC::Commitment, PC::VerifierKey>,
+        transcript: T,
+    ) -> Self {
+        Self {
+            transcript: RefCell::new(transcript),
+            ..Self::new(vk)
+        }
+    }
+    /// The transcript, with everything received and challenged so far.
+    pub fn into_transcript(self) -> T {
+        self.transcript.into_inner()
+    }
+    /// A verifier that records openings instead of checking them (see [Verifier::take_openings]).
+    fn new_deferred(vk: &'r VerifierKey<PC::Commitment, PC::VerifierKey>) -> Self {
+        Self {
+            deferred: Some(RefCell::new(Vec::new())),
+            ..Self::new(vk)
+        }
+    }
+    /// The openings recorded so far, in the order they were made.
+    fn take_openings(&self) -> DeferredOpenings<F, PC::Commitment> {
+        self.deferred
+            .as_ref()
+            .expect("Verifier does not defer openings")
+            .take()
+    }
+    /// A verifier that records failed checks instead of panicking (see
+    /// [Verifier::take_failures]).
+    fn new_reporting(
+        vk: &'r VerifierKey<PC::Commitment, PC::VerifierKey>,
+        beacon: Option<&Beacon>,
+    ) -> Self {
+        Self {
+            failures: Some(RefCell::new(Vec::new())),
+            ..Self::new_with_beacon(vk, beacon)
+        }
+    }
+    /// A verifier that records openings instead of checking them, and failed checks instead of
+    /// panicking (see [Verifier::new_deferred] and [Verifier::new_reporting]).
+    fn new_deferred_reporting(vk: &'r VerifierKey<PC::Commitment, PC::VerifierKey>) -> Self {
+        Self {
+            failures: Some(RefCell::new(Vec::new())),
+            ..Self::new_deferred(vk)
+        }
+    }
+    /// The failed checks recorded so far, in the order they were made.
+    fn take_failures(&self) -> Vec<Failure<F>> {
+        self.failures
+            .as_ref()
+            .expect("Verifier does not report failures")
+            .take()
+    }
+    /// Record `failure`, or panic with it if not reporting.
+    #[track_caller]
+    fn fail(&self, failure: Failure<F>) {
+        match &self.failures {
+            Some(failures) => failures.borrow_mut().push(failure),
+            None => panic!("{}", failure),
+        }
+    }
+    /// Check that `identity` holds: that its sides, evaluated with `challenges`, are equal.
+    #[track_caller]
+    fn identity(
+        &self,
+        identity: &'static str,
+        left: F,
+        right: F,
+        challenges: &[(&'static str, F)],
+    ) {
+        if left != right {
+            self.fail(Failure::Identity {
+                identity,
+                left,
+                right,
+                challenges: challenges.to_vec(),
+            });
+        }
+    }
+    /// Check the unit product proof of `f`, whose partial products and quotient are committed in
+    /// `t_cmt` and `q_cmt`.
+    fn check_unit_product<D: EvaluationDomain<F>>(
+        &self,
+        f_cmt: &LabeledCommitment<PC::Commitment>,
+        t_cmt: &LabeledCommitment<PC::Commitment>,
+        q_cmt: &LabeledCommitment<PC::Commitment>,
+        pf: &ProductProof<PC::Commitment, (F, PC::Proof)>,
+        domain: D,
+        points: &Points<F>,
+    ) {
+        let k = domain.size();
+        let w = domain.element(1);
+        let r = points.get(SubProof::UnitProduct);
+        // Check commitments
+        let f_wr = self.check(f_cmt, w * r, &pf.f_wr_open);
+        let q_r = self.check(q_cmt, r, &pf.q_r_open);
+        let t_r = self.check(t_cmt, r, &pf.t_r_open);
+        let t_wr = self.check(t_cmt, w * r, &pf.t_wr_open);
+        let t_wk = self.check(t_cmt, domain.element(k - 1), &pf.t_wk_open);
+        // Check partial product
+        self.identity(
+            "partial product",
+            t_wr - t_r * f_wr,
+            domain.evaluate_vanishing_polynomial(r) * q_r,
+            &[points.challenge(SubProof::UnitProduct)],
+        );
+        // Check total product is 1
+        self.identity("total product", t_wk, F::one(), &[]);
+        self.check_batched_openings();
+    }
+    /// Receive a commitment, absorbing it into the transcript.
+    ///
+    /// Produces a labeled commitment, with degree bound `degree` (which its openings then
+    /// enforce).
+    pub fn recv_commit(
+        &self,
+        label: &'static str,
+        c: PC::Commitment,
         degree: Option<usize>,
     ) -> LabeledCommitment<PC::Commitment> {
+        let degree = self.check_degree_bound(label.to_owned(), &c, degree);
         let label_c = LabeledCommitment::new(format!("{}", label), c, degree);
-        self.fs_rng
+        self.transcript
             .borrow_mut()
-            .absorb(&ark_ff::to_bytes![label_c].expect("failed serialization"));
+            .absorb(label.as_bytes(), &label_c);
         label_c
     }
 
+    /// Receive a commitment to each column's polynomial, named by `label` and its column (see
+    /// [column_label]), absorbing them into the transcript.
+    pub fn recv_columns(
+        &self,
+        label: &'static str,
+        cs: Vec<PC::Commitment>,
+        degree: Option<usize>,
+    ) -> Vec<LabeledCommitment<PC::Commitment>> {
+        cs.into_iter()
+            .enumerate()
+            .map(|(j, c)| {
+                let degree = self.check_degree_bound(column_label(label, j), &c, degree);
+                let label_c = LabeledCommitment::new(column_label(label, j), c, degree);
+                self.transcript
+                    .borrow_mut()
+                    .absorb(label.as_bytes(), &label_c);
+                label_c
+            })
+            .collect()
+    }
+
+    /// Check that commitment `c`, labeled `label`, carries what enforcing degree bound `degree`
+    /// takes (for KZG, a shifted commitment).
+    ///
+    /// Produces the degree bound to label it with: none, if it lacks one, so that its openings
+    /// are still checkable.
+    fn check_degree_bound(
+        &self,
+        label: String,
+        c: &PC::Commitment,
+        degree: Option<usize>,
+    ) -> Option<usize> {
+        match degree {
+            Some(bound) if !c.has_degree_bound() => {
+                self.fail(Failure::DegreeBound {
+                    commitment: label,
+                    bound,
+                });
+                None
+            }
+            degree => degree,
+        }
+    }
+
+    /// Check that a proof has as many of each thing as the circuit's columns call for: `counts`
+    /// are (what, expected, found).
+    ///
+    /// Produces whether it does; if not, nothing depending on the counts is checkable.
+    #[track_caller]
+    fn check_columns(&self, counts: &[(&'static str, usize, usize)]) -> bool {
+        let mut valid = true;
+        for &(what, expected, found) in counts {
+            if expected != found {
+                self.fail(Failure::Columns {
+                    what,
+                    expected,
+                    found,
+                });
+                valid = false;
+            }
+        }
+        valid
+    }
+
+    /// Check that `public` has every public input of `circ`.
+    ///
+    /// Produces whether it does; if not, the public check can't be made.
+    fn check_inputs(
+        &self,
+        circ: &relations::flat::Instance<F>,
+        public: &HashMap<String, F>,
+    ) -> bool {
+        let mut complete = true;
+        for j in circ.public_columns() {
+            for (name, _) in circ.column_inputs(j) {
+                if !public.contains_key(name) {
+                    let name = name.clone();
+                    self.fail(Failure::MissingInput { name });
+                    complete = false;
+                }
+            }
+        }
+        complete
+    }
+
     #[track_caller]
     fn check(&self, cmt: &LabeledCommitment<PC::Commitment>, x: F, open: &(F, PC::Proof)) -> F {
-        assert!(
-            PC::check(
-                &self.vk.pc_vk,
-                once(cmt),
-                &x,
-                once(open.0),
-                &open.1,
-                F::one(), // Okay b/c a single commit
-                None,
-            )
-            .unwrap(),
-            "Verification failed: {} at {}",
-            cmt.label(),
-            x
+        if let Some(openings) = &self.deferred {
+            let query = (vec![(F::one(), cmt.clone())], x, open.0);
+            openings.borrow_mut().push((vec![query], F::one()));
+            return open.0;
+        }
+        if let Some(queued) = &self.batched {
+            queued
+                .borrow_mut()
+                .push((cmt.clone(), x, open.0, open.1.clone()));
+            return open.0;
+        }
+        let valid = PC::check(
+            &self.vk.pc_vk,
+            once(cmt),
+            &x,
+            once(open.0),
+            &open.1,
+            F::one(), // Okay b/c a single commit
+            None,
         );
+        if !matches!(valid, Ok(true)) {
+            self.fail(Failure::Opening {
+                commitment: cmt.label().clone(),
+                point: x,
+                value: open.0,
+            });
+        }
         open.0
     }
 
+    /// Check `proof`, an opening of the linear combinations `lcs` of the polynomials committed in
+    /// `cmts`, at the points of `query_set`, to `evals`, batched with powers of `challenge`.
+    #[track_caller]
+    fn check_combinations(
+        &self,
+        lcs: &[LinearCombination<F>],
+        cmts: &[&LabeledCommitment<PC::Commitment>],
+        query_set: &QuerySet<F>,
+        evals: &ark_poly_commit::Evaluations<F, F>,
+        proof: &PC::Proof,
+        challenge: F,
+    ) {
+        if let Some(openings) = &self.deferred {
+            // In the order that the claims are batched: that of the query set.
+            let queries = query_set
+                .iter()
+                .map(|(label, (_, x))| {
+                    let lc = lcs.iter().find(|lc| &lc.label == label).unwrap();
+                    let terms = lc
+                        .iter()
+                        .map(|(coeff, term)| {
+                            let cmt = cmts.iter().find(|c| term == c.label()).unwrap();
+                            (*coeff, (*cmt).clone())
+                        })
+                        .collect();
+                    (terms, *x, evals[&(label.clone(), *x)])
+                })
+                .collect();
+            openings.borrow_mut().push((queries, challenge));
+            return;
+        }
+        let proof = BatchLCProof {
+            proof: vec![proof.clone()].into(),
+            evals: None,
+        };
+        // The rng randomizes the checks at different points against each other; there's one.
+        let rng = &mut ChaChaRng::from_seed([0; 32]);
+        let valid = PC::check_combinations(
+            &self.vk.pc_vk,
+            lcs,
+            cmts.iter().copied(),
+            query_set,
+            evals,
+            &proof,
+            challenge,
+            rng,
+        );
+        if !matches!(valid, Ok(true)) {
+            for ((label, point), value) in evals {
+                self.fail(Failure::Opening {
+                    commitment: label.clone(),
+                    point: *point,
+                    value: *value,
+                });
+            }
+        }
+    }
+
     pub fn verify(
         &self,
-        circ: &relations::flat::CircuitLayout<F>,
+        circ: &relations::flat::Instance<F>,
         pf: Proof<F, PC::Commitment, PC::Proof>,
         public: &HashMap<String, F>,
     ) {
-        assert!(circ.p.is_none());
-        let n_gates = circ.domains.gates.size();
-        let n_wires = n_gates * 3;
-        let p = self.recv_commit("p", pf.p_cmt, Some(n_wires - 1));
-        self.verify_public(&circ, &p, pf.public, public);
-        self.verify_gates(&p, &circ, pf.gates);
-        self.verify_wiring(&p, circ.domains.wires, pf.wiring);
+        if !self.check_circuit(circ) {
+            return;
+        }
+        if pf.beacon_round != self.beacon_round {
+            // Every challenge differs, so nothing else is worth checking.
+            return self.fail(Failure::BeaconRound {
+                expected: self.beacon_round,
+                found: pf.beacon_round,
+            });
+        }
+        // The transcript depends on every column's commitment.
+        let width = circ.domains.width;
+        if !self.check_columns(&[
+            ("W commitments", width, self.vk.w_cmts.len()),
+            ("P commitments", width, pf.p_cmts.len()),
+        ]) {
+            return;
+        }
+        let r = self.receive(circ, &pf);
+        self.check_public(
+            &r.p,
+            &r.public_q,
+            r.beta,
+            circ,
+            &pf.public,
+            public,
+            &r.points,
+        );
+        self.check_gates(&r.p, &r.gates_q, circ, &pf.gates, &r.points);
+        self.check_wiring(&r.p, &r.wiring, circ, &pf.wiring, &r.points);
+        self.check_batched_openings();
+    }
+
+    /// Receive `pf`'s commitments, drawing every challenge, as [Verifier::verify] does.
+    fn receive(
+        &self,
+        circ: &relations::flat::Instance<F>,
+        pf: &Proof<F, PC::Commitment, PC::Proof>,
+    ) -> ReceivedProof<'r, F, PC::Commitment> {
+        let d = circ.domains.gate_degree();
+        let p = self.recv_columns("p", pf.p_cmts.clone(), Some(d));
+        let mut points = Points::new(self.policy, circ.domains.gates.group_gen);
+        let beta = self.transcript.borrow_mut().challenge::<F>(b"public_beta");
+        let public_q = self.recv_commit("pub_q", pf.public.q_cmt.clone(), Some(d));
+        self.committed(&mut points, SubProof::Public);
+        let gates_q_degree = circ.domains.gates_quotient_degree();
+        let gates_q = self.recv_commit("gates_q", pf.gates.q_cmt.clone(), Some(gates_q_degree));
+        self.committed(&mut points, SubProof::Gates);
+        let wiring = self.recv_wiring(&pf.wiring, &circ.domains, &mut points);
+        ReceivedProof {
+            p,
+            beta,
+            public_q,
+            gates_q,
+            wiring,
+            points,
+        }
+    }
+
+    /// Check that `circ` is laid out as the verifier key's circuit is (see
+    /// [relations::flat::Instance::digest]); the transcript depends on it, so if not,
+    /// nothing else is worth checking.
+    fn check_circuit(&self, circ: &relations::flat::Instance<F>) -> bool {
+        let found = circ.digest();
+        let same = found == self.vk.circuit_digest;
+        if !same {
+            self.fail(Failure::Circuit {
+                expected: self.vk.circuit_digest,
+                found,
+            });
+        }
+        same
+    }
+
+    /// Record that `sub_proof`'s commitments are received, drawing challenges as `points` says.
+    fn committed(&self, points: &mut Points<F>, sub_proof: SubProof) {
+        points.committed(sub_proof, &mut *self.transcript.borrow_mut());
+    }
+
+    /// Verify a [SmallProof].
+    pub fn verify_small(
+        &self,
+        circ: &relations::flat::Instance<F>,
+        pf: SmallProof<F, PC::Commitment, PC::Proof>,
+        public: &HashMap<String, F>,
+    ) {
+        if !self.check_circuit(circ) {
+            return;
+        }
+        if pf.beacon_round != self.beacon_round {
+            return self.fail(Failure::BeaconRound {
+                expected: self.beacon_round,
+                found: pf.beacon_round,
+            });
+        }
+        let width = circ.domains.width;
+        if pf.q_cmts.len() != width || pf.q_opens.len() != width {
+            // The transcript depends on every chunk, so nothing else is checkable.
+            return self.fail(Failure::QuotientChunks {
+                expected: width,
+                commitments: pf.q_cmts.len(),
+                openings: pf.q_opens.len(),
+            });
+        }
+        if !self.check_columns(&[
+            ("W commitments", width, self.vk.w_cmts.len()),
+            ("P commitments", width, pf.p_cmts.len()),
+            ("P openings", width, pf.p_opens.len()),
+            ("W openings", width, pf.w_opens.len()),
+        ]) {
+            return;
+        }
+        if !self.check_inputs(circ, public) {
+            return;
+        }
+        let dom = circ.domains.gates;
+        let k = dom.size();
+        let d = circ.domains.gate_degree();
+        let p = self.recv_columns("p", pf.p_cmts, Some(d));
+        let beta = self.transcript.borrow_mut().challenge::<F>(b"public_beta");
+        let y = self.transcript.borrow_mut().challenge::<F>(b"wiring_y");
+        let z = self.transcript.borrow_mut().challenge::<F>(b"wiring_z");
+        let l1 = self.recv_commit("l1", pf.l1_cmt, Some(d));
+        let t = self.recv_commit("t", pf.t_cmt, Some(d));
+        let alpha = self.transcript.borrow_mut().challenge::<F>(b"small_alpha");
+        let q_cmts = self.recv_columns("q", pf.q_cmts, Some(d));
+        let x = self.transcript.borrow_mut().challenge::<F>(b"small_x");
+        let w = dom.group_gen;
+        // Check commitments
+        let p_x: Vec<F> = p
+            .iter()
+            .zip(&pf.p_opens)
+            .map(|(p, open)| self.check(p, x, open))
+            .collect();
+        let s_x = self.check(&self.vk.s_cmt, x, &pf.s_open);
+        let w_x: Vec<F> = self
+            .vk
+            .w_cmts
+            .iter()
+            .zip(&pf.w_opens)
+            .map(|(w_cmt, open)| self.check(w_cmt, x, open))
+            .collect();
+        let l1_x = self.check(&l1, x, &pf.l1_open);
+        let l1_wx = self.check(&l1, w * x, &pf.l1_w_open);
+        let t_x = self.check(&t, x, &pf.t_open);
+        let t_wx = self.check(&t, w * x, &pf.t_w_open);
+        let t_wk = self.check(&t, dom.element(k - 1), &pf.t_wk_open);
+        let x_k = x.pow(&[k as u64]);
+        let q_x = q_cmts
+            .iter()
+            .zip(&pf.q_opens)
+            .rev()
+            .fold(F::zero(), |acc, (q_cmt, open)| {
+                acc * x_k + self.check(q_cmt, x, open)
+            });
+        // The public check's constraint as a fraction; the others' constraints, all vanishing on
+        // the gate domain
+        let public_p: Vec<F> = circ.public_columns().iter().map(|j| p_x[*j]).collect();
+        let (public_c, public_z) = circ.eval_public_fraction(public, beta, &public_p, x);
+        let gates_c = s_x * (p_x[0] + p_x[1]) + (F::one() - s_x) * p_x[0] * p_x[1] - p_x[2];
+        let product_c = t_wx - t_x * l1_wx;
+        let (num, den) = wiring_products(&circ.domains, &p_x, &w_x, x, y, z);
+        let wiring_c = den * l1_x - num;
+        let gates_z = dom.evaluate_vanishing_polynomial(x);
+        // Q(x) = public_c/public_z + sum_i alpha^i C_i(x)/Z(x), times public_z * Z(x)
+        self.identity(
+            "quotient",
+            public_c * gates_z
+                + alpha * (gates_c + alpha * (product_c + alpha * wiring_c)) * public_z,
+            q_x * public_z * gates_z,
+            &[
+                ("public_beta", beta),
+                ("wiring_y", y),
+                ("wiring_z", z),
+                ("small_alpha", alpha),
+                ("small_x", x),
+            ],
+        );
+        // Check total product is 1
+        self.identity("total product", t_wk, F::one(), &[]);
     }
 
-    fn verify_public(
+    /// Verify that the polynomials committed in `p_cmts` agree with `public` on the public wires
+    /// of `circ`.
+    ///
+    /// Panics if not.
+    pub fn verify_public(
         &self,
-        circ: &relations::flat::CircuitLayout<F>,
-        p_cmt: &LabeledCommitment<PC::Commitment>,
+        p_cmts: &[LabeledCommitment<PC::Commitment>],
+        circ: &relations::flat::Instance<F>,
         pf: PublicProof<PC::Commitment, (F, PC::Proof)>,
         public: &HashMap<String, F>,
     ) {
-        let q_cmt = self.recv_commit("pub_q", pf.q_cmt, None);
-        let x = self.fs_rng.borrow_mut().gen::<F>();
-        let p_val = self.check(p_cmt, x, &pf.p_open);
-        let q_val = self.check(&q_cmt, x, &pf.q_open);
-        let z = circ.vanishing_poly_on_inputs();
-        let v = circ.inputs_poly(public);
-        assert_eq!(p_val - v.evaluate(&x), q_val * z.evaluate(&x));
+        let mut points = Points::new(&PerSubProof, circ.domains.gates.group_gen);
+        let beta = self.transcript.borrow_mut().challenge::<F>(b"public_beta");
+        let q_cmt = self.recv_commit("pub_q", pf.q_cmt.clone(), Some(circ.domains.gate_degree()));
+        self.committed(&mut points, SubProof::Public);
+        self.check_public(p_cmts, &q_cmt, beta, circ, &pf, public, &points);
     }
 
-    fn verify_gates(
+    #[allow(clippy::too_many_arguments)]
+    fn check_public(
         &self,
-        p_cmt: &LabeledCommitment<PC::Commitment>,
-        circ: &relations::flat::CircuitLayout<F>,
-        pf: GateProof<PC::Commitment, (F, PC::Proof)>,
+        p_cmts: &[LabeledCommitment<PC::Commitment>],
+        q_cmt: &LabeledCommitment<PC::Commitment>,
+        beta: F,
+        circ: &relations::flat::Instance<F>,
+        pf: &PublicProof<PC::Commitment, (F, PC::Proof)>,
+        public: &HashMap<String, F>,
+        points: &Points<F>,
     ) {
-        let q_cmt = self.recv_commit("gates_q", pf.q_cmt, None);
-        let x = self.fs_rng.borrow_mut().gen::<F>();
-        let w = circ.domains.wires.group_gen;
-        let s = self.check(&self.vk.s_cmt, x, &pf.s_open);
-        let q = self.check(&q_cmt, x, &pf.q_open);
-        let p = self.check(p_cmt, x, &pf.p_open);
-        let pw = self.check(p_cmt, x * w, &pf.p_w_open);
-        let pww = self.check(p_cmt, x * w * w, &pf.p_w2_open);
-        assert_eq!(
-            s * (p + pw) + (F::one() - s) * p * pw - pww,
-            q * circ.domains.gates.evaluate_vanishing_polynomial(x)
+        let columns = circ.public_columns();
+        if !self.check_columns(&[
+            ("P commitments", circ.domains.width, p_cmts.len()),
+            ("public P openings", columns.len(), pf.p_opens.len()),
+        ]) {
+            return;
+        }
+        if !self.check_inputs(circ, public) {
+            return;
+        }
+        let x = points.get(SubProof::Public);
+        let p_values: Vec<F> = columns
+            .iter()
+            .zip(&pf.p_opens)
+            .map(|(j, open)| self.check(&p_cmts[*j], x, open))
+            .collect();
+        let q_val = self.check(q_cmt, x, &pf.q_open);
+        let (num, den) = circ.eval_public_fraction(public, beta, &p_values, x);
+        self.identity(
+            "public",
+            num,
+            q_val * den,
+            &[points.challenge(SubProof::Public), ("public_beta", beta)],
         );
     }
-    fn verify_wiring<D: EvaluationDomain<F>>(
+
+    /// Verify that the polynomials committed in `p_cmts` satisfy the gates of `circ`.
+    ///
+    /// Panics if not.
+    pub fn verify_gates(
+        &self,
+        p_cmts: &[LabeledCommitment<PC::Commitment>],
+        circ: &relations::flat::Instance<F>,
+        pf: GateProof<F, PC::Commitment, PC::Proof>,
+    ) {
+        let mut points = Points::new(&PerSubProof, circ.domains.gates.group_gen);
+        let degree = circ.domains.gates_quotient_degree();
+        let q_cmt = self.recv_commit("gates_q", pf.q_cmt.clone(), Some(degree));
+        self.committed(&mut points, SubProof::Gates);
+        self.check_gates(p_cmts, &q_cmt, circ, &pf, &points);
+    }
+
+    fn check_gates(
         &self,
-        p_cmt: &LabeledCommitment<PC::Commitment>,
-        dom: D,
+        p_cmts: &[LabeledCommitment<PC::Commitment>],
+        q_cmt: &LabeledCommitment<PC::Commitment>,
+        circ: &relations::flat::Instance<F>,
+        pf: &GateProof<F, PC::Commitment, PC::Proof>,
+        points: &Points<F>,
+    ) {
+        if !self.check_columns(&[
+            ("P commitments", circ.domains.width, p_cmts.len()),
+            ("gate P values", GATE_WIDTH, pf.p_values.len()),
+        ]) {
+            return;
+        }
+        let x = points.get(SubProof::Gates);
+        let v = gates_challenge::<F, T>(x, &pf.p_values, pf.q_value);
+        let z_x = circ.domains.gates.evaluate_vanishing_polynomial(x);
+        let (lcs, query_set, evals) = gates_linearization(x, z_x, &pf.p_values, pf.q_value);
+        let mut cmts = vec![&self.vk.s_cmt, q_cmt];
+        cmts.extend(&p_cmts[..GATE_WIDTH]);
+        self.check_combinations(&lcs, &cmts, &query_set, &evals, &pf.open, v);
+    }
+    /// Verify that the polynomials committed in `p_cmts` respect the wiring permutation (from
+    /// the verifier key) of `circ`.
+    ///
+    /// Panics if not.
+    pub fn verify_wiring(
+        &self,
+        p_cmts: &[LabeledCommitment<PC::Commitment>],
+        circ: &relations::flat::Instance<F>,
         pf: WiringProof<PC::Commitment, (F, PC::Proof)>,
     ) {
-        let y = self.fs_rng.borrow_mut().gen::<F>();
-        let z = self.fs_rng.borrow_mut().gen::<F>();
-        let l1 = self.recv_commit("l1", pf.l1_cmt, None);
-        self.verify_unit_product(&l1, pf.l1_prod_pf, dom);
-        let l2_q = self.recv_commit("l2_q", pf.l2_q_cmt, None);
-        let x = self.fs_rng.borrow_mut().gen::<F>();
-
-        let l2_q_x = self.check(&l2_q, x, &pf.l2_q_x_open);
-        let w_x = self.check(&self.vk.w_cmt, x, &pf.w_x_open);
-        let l1_x = self.check(&l1, x, &pf.l1_x_open);
-        let p_x = self.check(&p_cmt, x, &pf.p_x_open);
-        assert_eq!(
-            (p_x + y * x + z) * l1_x - (p_x + y * w_x + z),
-            l2_q_x * dom.evaluate_vanishing_polynomial(x)
+        let mut points = Points::new(&PerSubProof, circ.domains.gates.group_gen);
+        let c = self.recv_wiring(&pf, &circ.domains, &mut points);
+        self.check_wiring(p_cmts, &c, circ, &pf, &points);
+    }
+
+    /// Receive the wiring proof's commitments, drawing its challenges.
+    fn recv_wiring(
+        &self,
+        pf: &WiringProof<PC::Commitment, (F, PC::Proof)>,
+        domains: &relations::flat::Domains<F>,
+        points: &mut Points<F>,
+    ) -> WiringCommitments<F, PC::Commitment> {
+        let y = self.transcript.borrow_mut().challenge::<F>(b"wiring_y");
+        let z = self.transcript.borrow_mut().challenge::<F>(b"wiring_z");
+        let d = Some(domains.gate_degree());
+        let l1 = self.recv_commit("l1", pf.l1_cmt.clone(), d);
+        let t = self.recv_commit("t", pf.l1_prod_pf.t_cmt.clone(), d);
+        let q = self.recv_commit("q", pf.l1_prod_pf.q_cmt.clone(), d);
+        self.committed(points, SubProof::UnitProduct);
+        let l2_q_degree = Some(domains.wiring_quotient_degree());
+        let l2_q = self.recv_commit("l2_q", pf.l2_q_cmt.clone(), l2_q_degree);
+        self.committed(points, SubProof::Wiring);
+        WiringCommitments {
+            y,
+            z,
+            l1,
+            t,
+            q,
+            l2_q,
+        }
+    }
+
+    fn check_wiring(
+        &self,
+        p_cmts: &[LabeledCommitment<PC::Commitment>],
+        c: &WiringCommitments<F, PC::Commitment>,
+        circ: &relations::flat::Instance<F>,
+        pf: &WiringProof<PC::Commitment, (F, PC::Proof)>,
+        points: &Points<F>,
+    ) {
+        let width = circ.domains.width;
+        if !self.check_columns(&[
+            ("W commitments", width, self.vk.w_cmts.len()),
+            ("P commitments", width, p_cmts.len()),
+            ("wiring P openings", width, pf.p_x_opens.len()),
+            ("wiring W openings", width, pf.w_x_opens.len()),
+        ]) {
+            return;
+        }
+        let dom = circ.domains.gates;
+        self.check_unit_product(&c.l1, &c.t, &c.q, &pf.l1_prod_pf, dom, points);
+        let x = points.get(SubProof::Wiring);
+        let (y, z) = (c.y, c.z);
+        let l2_q_x = self.check(&c.l2_q, x, &pf.l2_q_x_open);
+        let w_x: Vec<F> = self
+            .vk
+            .w_cmts
+            .iter()
+            .zip(&pf.w_x_opens)
+            .map(|(w_cmt, open)| self.check(w_cmt, x, open))
+            .collect();
+        let l1_x = self.check(&c.l1, x, &pf.l1_x_open);
+        let p_x: Vec<F> = p_cmts
+            .iter()
+            .zip(&pf.p_x_opens)
+            .map(|(p_cmt, open)| self.check(p_cmt, x, open))
+            .collect();
+        let (num, den) = wiring_products(&circ.domains, &p_x, &w_x, x, y, z);
+        self.identity(
+            "wiring",
+            den * l1_x - num,
+            l2_q_x * dom.evaluate_vanishing_polynomial(x),
+            &[
+                ("wiring_y", y),
+                ("wiring_z", z),
+                points.challenge(SubProof::Wiring),
+            ],
         );
     }
 }
 
+/// The wiring identity's numerator and denominator at `x`: the products, over the columns, of
+/// `P_j(x) + yW_j(x) + z` and of `P_j(x) + yk_jx + z`.
+fn wiring_products<F: FftField>(
+    domains: &relations::flat::Domains<F>,
+    p_x: &[F],
+    w_x: &[F],
+    x: F,
+    y: F,
+    z: F,
+) -> (F, F) {
+    p_x.iter()
+        .zip(w_x)
+        .enumerate()
+        .fold((F::one(), F::one()), |(num, den), (j, (p, w))| {
+            (
+                num * (*p + y * w + z),
+                den * (*p + y * domains.shift(j) * x + z),
+            )
+        })
+}
+
+/// A [Proof]'s commitments, as the verifier received them, and the challenges drawn from them.
+struct ReceivedProof<'a, F: Field, C: PCCommitment> {
+    p: Vec<LabeledCommitment<C>>,
+    beta: F,
+    public_q: LabeledCommitment<C>,
+    gates_q: LabeledCommitment<C>,
+    wiring: WiringCommitments<F, C>,
+    points: Points<'a, F>,
+}
+
+/// The wiring proof's commitments (including its unit product proof's), and its challenges `y`
+/// and `z`, as the verifier received them.
+struct WiringCommitments<F, C: PCCommitment> {
+    y: F,
+    z: F,
+    l1: LabeledCommitment<C>,
+    t: LabeledCommitment<C>,
+    q: LabeledCommitment<C>,
+    l2_q: LabeledCommitment<C>,
+}
+
 #[derive(Error, Debug)]
 pub enum Error<PCE: 'static + std::error::Error> {
     #[error("Sub error: {0}")]
@@ -592,81 +2463,2356 @@ pub enum Error<PCE: 'static + std::error::Error> {
     DomainDivisionFailed,
     #[error("Eq check for domain division failed")]
     DomainCheckFailed,
+    #[error("Committer backend failed: {0}")]
+    Backend(String),
 }
 
 pub type Result<T, PCE> = std::result::Result<T, PCE>;
 
-pub struct Plonk<F: Field, PC: PolynomialCommitment<F, DensePolynomial<F>>>(PhantomData<(F, PC)>);
+/// The Plonk proof system, over polynomial commitment `PC`, with Fiat-Shamir transcript `T`.
+pub struct Plonk<
+    F: Field,
+    PC: PolynomialCommitment<F, DensePolynomial<F>>,
+    T: Transcript = Blake2sTranscript,
+>(PhantomData<(F, PC, T)>);
 
-impl<F: FftField, PC: PolynomialCommitment<F, DensePolynomial<F>>> Plonk<F, PC>
+impl<F: FftField, PC: PolynomialCommitment<F, DensePolynomial<F>>, T: Transcript> Plonk<F, PC, T>
 where
     PC::Commitment: mpc_trait::MpcWire,
     PC::Error: 'static,
 {
+    /// Parameters for circuits of up to `n_gates` gates, laid out in [GATE_WIDTH] columns.
     pub fn universal_setup<R: RngCore>(n_gates: usize, setup_rng: &mut R) -> PC::UniversalParams {
-        PC::setup(n_gates * 6 - 1, Some(1), setup_rng).unwrap()
+        Self::universal_setup_with_width(n_gates, GATE_WIDTH, setup_rng)
+    }
+    /// Parameters for circuits of up to `n_gates` gates, laid out in `width` columns (see
+    /// [relations::flat::Instance::from_circuit_with_width]).
+    pub fn universal_setup_with_width<R: RngCore>(
+        n_gates: usize,
+        width: usize,
+        setup_rng: &mut R,
+    ) -> PC::UniversalParams {
+        // Gates are padded to a power of two
+        PC::setup(n_gates.next_power_of_two() * width - 1, Some(1), setup_rng).unwrap()
     }
     pub fn circuit_setup(
         srs: &PC::UniversalParams,
-        circ: &relations::flat::CircuitLayout<F>,
+        circ: &relations::flat::Instance<F>,
     ) -> (
         ProverKey<F, PC::Commitment, PC::CommitterKey>,
         VerifierKey<PC::Commitment, PC::VerifierKey>,
     ) {
         setup::<F, PC>(srs, circ)
     }
+    /// Setup for several circuits, trimming the SRS only once (see [TrimPlan]).
+    pub fn circuit_setup_many(
+        srs: &PC::UniversalParams,
+        circs: &[relations::flat::Instance<F>],
+    ) -> Vec<CircuitKeys<F, PC>> {
+        let mut plan = TrimPlan::new();
+        circs.iter().for_each(|circ| plan.register(circ));
+        let (ck, vk) = plan.trim::<F, PC>(srs);
+        circs
+            .iter()
+            .map(|circ| index::<F, PC>(ck.clone(), vk.clone(), circ))
+            .collect()
+    }
+    /// Setup whose keys support hiding commitments, through [HIDING_BOUND] openings.
+    pub fn circuit_setup_hiding(
+        srs: &PC::UniversalParams,
+        circ: &relations::flat::Instance<F>,
+    ) -> CircuitKeys<F, PC> {
+        let mut plan = TrimPlan::new();
+        plan.register(circ);
+        plan.hiding_bound(HIDING_BOUND);
+        let (ck, vk) = plan.trim::<F, PC>(srs);
+        index::<F, PC>(ck, vk, circ)
+    }
     pub fn prove(
         pk: &ProverKey<F, PC::Commitment, PC::CommitterKey>,
-        circ: &relations::flat::CircuitLayout<F>,
+        circ: &relations::flat::Instance<F>,
+        witness: &relations::flat::Witness<F>,
+        zk_rng: &mut dyn RngCore,
+    ) -> Proof<F, PC::Commitment, PC::Proof> {
+        let prv = Prover::<F, PC, T>::new(pk, zk_rng);
+        prv.prove(circ, witness)
+    }
+    /// Prove, as [Plonk::prove] does, returning why the proof was abandoned (see [AbortCode])
+    /// rather than unwinding.
+    ///
+    /// In MPC (when [MpcMultiNet] is initialized), the parties also check that their transcripts
+    /// agree, in one more round.
+    pub fn try_prove(
+        pk: &ProverKey<F, PC::Commitment, PC::CommitterKey>,
+        circ: &relations::flat::Instance<F>,
+        witness: &relations::flat::Witness<F>,
+        zk_rng: &mut dyn RngCore,
+    ) -> std::result::Result<Proof<F, PC::Commitment, PC::Proof>, Abort> {
+        catch_abort(|| {
+            let prv = Prover::<F, PC, T>::new(pk, zk_rng);
+            let pf = prv.prove(circ, witness);
+            if MpcMultiNet::is_init() {
+                prv.check_transcript::<MpcMultiNet>();
+            }
+            pf
+        })
+    }
+    /// Which commitments [Plonk::prove] reveals together, in MPC, for a proof about `circ`.
+    pub fn round_schedule(circ: &relations::flat::Instance<F>) -> RoundSchedule {
+        prove_schedule(circ.domains.width)
+    }
+    /// Prove, mixing `beacon` into the Fiat-Shamir seed.
+    ///
+    /// The beacon round is recorded in the proof.
+    pub fn prove_with_beacon(
+        pk: &ProverKey<F, PC::Commitment, PC::CommitterKey>,
+        circ: &relations::flat::Instance<F>,
+        witness: &relations::flat::Witness<F>,
         zk_rng: &mut dyn RngCore,
+        beacon: &Beacon,
     ) -> Proof<F, PC::Commitment, PC::Proof> {
-        let prv = Prover::<F, PC>::new(pk, zk_rng);
-        prv.prove(circ)
+        let prv = Prover::<F, PC, T>::new_with_beacon(pk, zk_rng, Some(beacon));
+        prv.prove(circ, witness)
     }
     pub fn verify(
         vk: &VerifierKey<PC::Commitment, PC::VerifierKey>,
-        circ: &relations::flat::CircuitLayout<F>,
+        circ: &relations::flat::Instance<F>,
         pf: Proof<F, PC::Commitment, PC::Proof>,
         public: &HashMap<String, F>,
     ) {
-        let ver = Verifier::<F, PC>::new(vk);
+        let ver = Verifier::<F, PC, T>::new(vk);
         ver.verify(circ, pf, public)
     }
-}
+    /// Prove with hiding commitments (see [Prover::hiding_commitments]), under keys from
+    /// [Plonk::circuit_setup_hiding].
+    pub fn prove_hiding(
+        pk: &ProverKey<F, PC::Commitment, PC::CommitterKey>,
+        circ: &relations::flat::Instance<F>,
+        witness: &relations::flat::Witness<F>,
+        zk_rng: &mut dyn RngCore,
+    ) -> Proof<F, PC::Commitment, PC::Proof> {
+        let mut prv = Prover::<F, PC, T>::new(pk, zk_rng);
+        prv.hiding_commitments(HIDING_BOUND);
+        prv.prove(circ, witness)
+    }
+    /// Prove, opening where `policy` says.
+    pub fn prove_with_policy(
+        pk: &ProverKey<F, PC::Commitment, PC::CommitterKey>,
+        circ: &relations::flat::Instance<F>,
+        witness: &relations::flat::Witness<F>,
+        zk_rng: &mut dyn RngCore,
+        policy: &dyn OpeningPolicy,
+    ) -> Proof<F, PC::Commitment, PC::Proof> {
+        let mut prv = Prover::<F, PC, T>::new(pk, zk_rng);
+        prv.opening_policy(policy);
+        prv.prove(circ, witness)
+    }
+    /// Verify a proof made under opening `policy`.
+    pub fn verify_with_policy(
+        vk: &VerifierKey<PC::Commitment, PC::VerifierKey>,
+        circ: &relations::flat::Instance<F>,
+        pf: Proof<F, PC::Commitment, PC::Proof>,
+        public: &HashMap<String, F>,
+        policy: &dyn OpeningPolicy,
+    ) {
+        let mut ver = Verifier::<F, PC, T>::new(vk);
+        ver.opening_policy(policy);
+        ver.verify(circ, pf, public)
+    }
+    /// Prove, with a [SmallProof] rather than a [Proof].
+    pub fn prove_small(
+        pk: &ProverKey<F, PC::Commitment, PC::CommitterKey>,
+        circ: &relations::flat::Instance<F>,
+        witness: &relations::flat::Witness<F>,
+        zk_rng: &mut dyn RngCore,
+    ) -> SmallProof<F, PC::Commitment, PC::Proof> {
+        let prv = Prover::<F, PC, T>::new(pk, zk_rng);
+        prv.prove_small(circ, witness)
+    }
+    pub fn verify_small(
+        vk: &VerifierKey<PC::Commitment, PC::VerifierKey>,
+        circ: &relations::flat::Instance<F>,
+        pf: SmallProof<F, PC::Commitment, PC::Proof>,
+        public: &HashMap<String, F>,
+    ) {
+        let ver = Verifier::<F, PC, T>::new(vk);
+        ver.verify_small(circ, pf, public)
+    }
+    /// Verify, returning every check that failed rather than panicking at the first.
+    ///
+    /// `beacon` is as in [Plonk::verify_with_beacon], for proofs made with one.
+    pub fn verify_with_report(
+        vk: &VerifierKey<PC::Commitment, PC::VerifierKey>,
+        circ: &relations::flat::Instance<F>,
+        pf: Proof<F, PC::Commitment, PC::Proof>,
+        public: &HashMap<String, F>,
+        beacon: Option<&Beacon>,
+    ) -> std::result::Result<(), VerificationReport<F>> {
+        let ver = Verifier::<F, PC, T>::new_reporting(vk, beacon);
+        ver.verify(circ, pf, public);
+        let failures = ver.take_failures();
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(VerificationReport { failures })
+        }
+    }
+    /// Like [Plonk::verify_with_report], for a proof read by [read_versioned], whatever its
+    /// format version (see [Plonk::verify_versioned]). A version that isn't verified is reported,
+    /// not panicked at.
+    pub fn verify_versioned_with_report(
+        vk: &VerifierKey<PC::Commitment, PC::VerifierKey>,
+        circ: &relations::flat::Instance<F>,
+        pf: Versioned<Proof<F, PC::Commitment, PC::Proof>>,
+        public: &HashMap<String, F>,
+        beacon: Option<&Beacon>,
+    ) -> std::result::Result<(), VerificationReport<F>> {
+        match pf.version {
+            FORMAT_VERSION => Self::verify_with_report(vk, circ, pf.value, public, beacon),
+            #[cfg(feature = "compat")]
+            6 => Plonk::<F, PC, V6Transcript<T>>::verify_with_report(
+                vk, circ, pf.value, public, beacon,
+            ),
+            version => Err(VerificationReport {
+                failures: vec![Failure::Version { version }],
+            }),
+        }
+    }
+    /// Like [Plonk::verify_with_report], for a [SmallProof].
+    pub fn verify_small_with_report(
+        vk: &VerifierKey<PC::Commitment, PC::VerifierKey>,
+        circ: &relations::flat::Instance<F>,
+        pf: SmallProof<F, PC::Commitment, PC::Proof>,
+        public: &HashMap<String, F>,
+    ) -> std::result::Result<(), VerificationReport<F>> {
+        let ver = Verifier::<F, PC, T>::new_reporting(vk, None);
+        ver.verify_small(circ, pf, public);
+        let failures = ver.take_failures();
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(VerificationReport { failures })
+        }
+    }
+    /// Verify a proof read by [read_versioned], whatever its (supported) format version: with
+    /// the `compat` feature, version 6 proofs are verified with their transcript.
+    pub fn verify_versioned(
+        vk: &VerifierKey<PC::Commitment, PC::VerifierKey>,
+        circ: &relations::flat::Instance<F>,
+        pf: Versioned<Proof<F, PC::Commitment, PC::Proof>>,
+        public: &HashMap<String, F>,
+    ) {
+        match pf.version {
+            FORMAT_VERSION => Self::verify(vk, circ, pf.value, public),
+            #[cfg(feature = "compat")]
+            6 => Plonk::<F, PC, V6Transcript<T>>::verify(vk, circ, pf.value, public),
+            v => panic!("Unsupported proof format version {}", v),
+        }
+    }
+    /// Verify a proof made with [Plonk::prove_with_beacon].
+    ///
+    /// The verifier should obtain `beacon` from the beacon itself, for round `pf.beacon_round`,
+    /// rather than from the prover.
+    pub fn verify_with_beacon(
+        vk: &VerifierKey<PC::Commitment, PC::VerifierKey>,
+        circ: &relations::flat::Instance<F>,
+        pf: Proof<F, PC::Commitment, PC::Proof>,
+        public: &HashMap<String, F>,
+        beacon: &Beacon,
+    ) {
+        let ver = Verifier::<F, PC, T>::new_with_beacon(vk, Some(beacon));
+        ver.verify(circ, pf, public)
+    }
+    /// Prove, in `session` (see [Session]).
+    pub fn prove_in_session(
+        pk: &ProverKey<F, PC::Commitment, PC::CommitterKey>,
+        circ: &relations::flat::Instance<F>,
+        witness: &relations::flat::Witness<F>,
+        zk_rng: &mut dyn RngCore,
+        session: &Session,
+    ) -> Proof<F, PC::Commitment, PC::Proof> {
+        let prv = Prover::<F, PC, T>::new_in_session(pk, zk_rng, None, session);
+        prv.prove(circ, witness)
+    }
+    /// Verify, checking the openings in one batch (see [Verifier::batch_openings]).
+    pub fn verify_batched(
+        vk: &VerifierKey<PC::Commitment, PC::VerifierKey>,
+        circ: &relations::flat::Instance<F>,
+        pf: Proof<F, PC::Commitment, PC::Proof>,
+        public: &HashMap<String, F>,
+    ) {
+        let mut ver = Verifier::<F, PC, T>::new(vk);
+        ver.batch_openings();
+        ver.verify(circ, pf, public)
+    }
+    /// Verify a proof made with [Plonk::prove_in_session], in the same session: with the nonce
+    /// the verifier expects, and, if the prover bound them, `public` bound too (see
+    /// [Session::with_public]).
+    pub fn verify_in_session(
+        vk: &VerifierKey<PC::Commitment, PC::VerifierKey>,
+        circ: &relations::flat::Instance<F>,
+        pf: Proof<F, PC::Commitment, PC::Proof>,
+        public: &HashMap<String, F>,
+        session: &Session,
+    ) {
+        let ver = Verifier::<F, PC, T>::new_in_session(vk, None, session);
+        ver.verify(circ, pf, public)
+    }
+    /// Prove each chunk of a split circuit (see [relations::structured::PlonkCircuit::split]).
+    ///
+    /// `links` are the split circuit's link commitments. They are revealed in the proof; the
+    /// links' values stay secret.
+    pub fn prove_chunked(
+        pks: &[ProverKey<F, PC::Commitment, PC::CommitterKey>],
+        circs: &[relations::flat::Instance<F>],
+        witnesses: &[relations::flat::Witness<F>],
+        links: Vec<F>,
+        zk_rng: &mut dyn RngCore,
+    ) -> ChunkedProof<F, PC::Commitment, PC::Proof> {
+        assert_eq!(pks.len(), circs.len());
+        assert_eq!(witnesses.len(), circs.len());
+        let chunks = pks
+            .iter()
+            .zip(circs)
+            .zip(witnesses)
+            .map(|((pk, circ), witness)| Self::prove(pk, circ, witness, zk_rng))
+            .collect();
+        // In one round, rather than one each
+        let links = defer::enqueue(links).get();
+        ChunkedProof { chunks, links }
+    }
+    /// Verify each chunk of a split circuit, against the public inputs and the revealed link
+    /// commitments. The public inputs must include the split circuit's
+    /// [ChunkedCircuit::public](relations::structured::ChunkedCircuit::public).
+    pub fn verify_chunked(
+        vks: &[VerifierKey<PC::Commitment, PC::VerifierKey>],
+        circs: &[relations::flat::Instance<F>],
+        pf: ChunkedProof<F, PC::Commitment, PC::Proof>,
+        public: &HashMap<String, F>,
+    ) {
+        assert_eq!(vks.len(), circs.len());
+        assert_eq!(pf.chunks.len(), circs.len(), "Wrong number of chunk proofs");
+        let mut public = public.clone();
+        for (i, l) in pf.links.iter().enumerate() {
+            public.insert(format!("{}{}", relations::structured::LINK_PREFIX, i), *l);
+        }
+        for ((vk, circ), chunk_pf) in vks.iter().zip(circs).zip(pf.chunks) {
+            let chunk_public: HashMap<String, F> = circ
+                .public_indices
+                .keys()
+                .map(|name| {
+                    let v = public
+                        .get(name)
+                        .unwrap_or_else(|| panic!("Missing public input {}", name));
+                    (name.clone(), *v)
+                })
+                .collect();
+            Self::verify(vk, circ, chunk_pf, &chunk_public);
+        }
+    }
+    /// Synthesize `c` with values, lower it to Plonk (see [relations::r1cs::R1csPlonk]), and
+    /// prove it.
+    ///
+    /// Returns the proof and the R1CS instance, which is revealed.
+    #[allow(clippy::type_complexity)]
+    pub fn prove_synthesizer<C: ConstraintSynthesizer<F>>(
+        pk: &ProverKey<F, PC::Commitment, PC::CommitterKey>,
+        c: C,
+        zk_rng: &mut dyn RngCore,
+    ) -> std::result::Result<(Proof<F, PC::Commitment, PC::Proof>, Vec<F>), SynthesisError> {
+        let r1cs = relations::r1cs::R1csPlonk::from_synthesizer(c, true)?;
+        let circ = relations::flat::Instance::from_circuit(&r1cs.circuit);
+        let witness = relations::flat::Witness::from_circuit(&circ, &r1cs.circuit);
+        let pf = Self::prove(pk, &circ, &witness, zk_rng);
+        let mut inputs = r1cs.inputs.unwrap();
+        inputs.publicize();
+        Ok((pf, inputs))
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use blake2::Blake2s;
+    use relations::{flat::*, structured::*};
+    // Not the trait [structured::Witness]
+    use relations::flat::Witness;
 
     type E = ark_bls12_377::Bls12_377;
     type F = ark_bls12_377::Fr;
     type P = DensePolynomial<F>;
     type PC = ark_poly_commit::marlin::marlin_pc::MarlinKZG10<E, P>;
     type Pl = Plonk<F, PC>;
+    type Cmt = <PC as PolynomialCommitment<F, P>>::Commitment;
+    type Vk = VerifierKey<Cmt, <PC as PolynomialCommitment<F, P>>::VerifierKey>;
+    type PcPf = <PC as PolynomialCommitment<F, P>>::Proof;
+    type Pf = Proof<F, Cmt, PcPf>;
+    type SmallPf = SmallProof<F, Cmt, PcPf>;
+    type Public = HashMap<String, F>;
 
-    #[test]
-    fn plonk_test() {
-        use relations::{flat::*, structured::*};
-        use std::collections::HashMap;
+    fn squaring_instance() -> (Instance<F>, Witness<F>, HashMap<String, F>) {
         let steps = 4;
         let start = F::from(2u64);
         let c = PlonkCircuit::<F>::new_squaring_circuit(steps, Some(start));
         let res = (0..steps).fold(start, |a, _| a * a);
         let public: HashMap<String, F> = vec![("out".to_owned(), res)].into_iter().collect();
-        let circ = CircuitLayout::from_circuit(&c);
+        let circ = Instance::from_circuit(&c);
+        let witness = Witness::from_circuit(&circ, &c);
+        (circ, witness, public)
+    }
 
-        let setup_rng = &mut ark_std::test_rng();
-        let zk_rng = &mut ark_std::test_rng();
+    fn beacon_proof(beacon: &Beacon) -> (Vk, Instance<F>, Pf, HashMap<String, F>) {
+        let (circ, witness, public) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, &circ);
+        let pf = Pl::prove_with_beacon(&pk, &circ, &witness, &mut ark_std::test_rng(), beacon);
+        assert_eq!(pf.beacon_round, Some(beacon.round));
+        (vk, circ, pf, public)
+    }
 
-        let v_circ = {
-            let mut t = circ.clone();
-            t.p = None;
-            t
+    #[test]
+    fn plonk_beacon_test() {
+        let beacon = Beacon {
+            round: 7,
+            value: vec![1, 2, 3],
         };
+        let (vk, v_circ, pf, public) = beacon_proof(&beacon);
+        Pl::verify_with_beacon(&vk, &v_circ, pf, &public, &beacon);
+    }
 
-        let srs = Pl::universal_setup(steps, setup_rng);
+    #[test]
+    #[should_panic]
+    fn plonk_wrong_beacon_test() {
+        let beacon = Beacon {
+            round: 7,
+            value: vec![1, 2, 3],
+        };
+        let (vk, v_circ, pf, public) = beacon_proof(&beacon);
+        let wrong = Beacon {
+            round: 7,
+            value: vec![1, 2, 4],
+        };
+        Pl::verify_with_beacon(&vk, &v_circ, pf, &public, &wrong);
+    }
+
+    fn session_proof(session: &Session) -> (Vk, Instance<F>, Pf, HashMap<String, F>) {
+        let (circ, witness, public) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, &circ);
+        let session = session.clone().with_public(&public);
+        let pf = Pl::prove_in_session(&pk, &circ, &witness, &mut ark_std::test_rng(), &session);
+        (vk, circ, pf, public)
+    }
+
+    #[test]
+    fn plonk_session_test() {
+        let (vk, v_circ, pf, public) = session_proof(&Session::new(&b"auction 17"[..]));
+        let session = Session::new(&b"auction 17"[..]).with_public(&public);
+        Pl::verify_in_session(&vk, &v_circ, pf, &public, &session);
+    }
+
+    #[test]
+    #[should_panic]
+    fn plonk_replayed_session_test() {
+        let (vk, v_circ, pf, public) = session_proof(&Session::new(&b"auction 17"[..]));
+        let session = Session::new(&b"auction 18"[..]).with_public(&public);
+        Pl::verify_in_session(&vk, &v_circ, pf, &public, &session);
+    }
+
+    #[test]
+    #[should_panic]
+    fn plonk_session_public_test() {
+        let (vk, v_circ, pf, public) = session_proof(&Session::new(&b"auction 17"[..]));
+        let mut other = public.clone();
+        other.insert("extra".to_owned(), F::from(1u64));
+        let session = Session::new(&b"auction 17"[..]).with_public(&other);
+        Pl::verify_in_session(&vk, &v_circ, pf, &public, &session);
+    }
+
+    #[test]
+    fn plonk_assigned_witness_test() {
+        let v_circ = Instance::from_circuit(&PlonkCircuit::<F>::squaring_circuit(4));
+        let c = PlonkCircuit::<F>::squaring_circuit(4);
+        assert_eq!(
+            assign(&c, &Public::new()),
+            Err(AssignError::MissingInput("start".to_owned()))
+        );
+        let inputs: Public = vec![("start".to_owned(), F::from(2u64))].into_iter().collect();
+        let assignment = assign(&c, &inputs).unwrap();
+        let c = c.with_assignment(assignment);
+        let witness = Witness::from_circuit(&v_circ, &c);
+        let (_, _, public) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, &v_circ);
+        let pf = Pl::prove(&pk, &v_circ, &witness, &mut ark_std::test_rng());
+        Pl::verify(&vk, &v_circ, pf, &public);
+    }
+
+    #[test]
+    fn plonk_circuit_macro_test() {
+        let c: PlonkCircuit<F> = crate::circuit! {
+            s = a + b;
+            t = s * s;
+            pub out = (t + a) * (b * c);
+            pub b;
+        };
+        assert_eq!(c.n_gates(), 8);
+        let mut names: Vec<_> = c.pub_vars.values().cloned().collect();
+        names.sort();
+        assert_eq!(names, vec!["b", "out"]);
+        let v_circ = Instance::from_circuit(&c);
+        let inputs = |name: &str| match name {
+            "a" => Some(F::from(1u64)),
+            "b" => Some(F::from(2u64)),
+            "c" => Some(F::from(3u64)),
+            _ => None,
+        };
+        let assignment = assign(&c, &inputs).unwrap();
+        let witness = Witness::from_circuit(&v_circ, &c.with_assignment(assignment));
+        let public: Public = vec![
+            ("out".to_owned(), F::from(60u64)),
+            ("b".to_owned(), F::from(2u64)),
+        ]
+        .into_iter()
+        .collect();
+        let srs = Pl::universal_setup(8, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, &v_circ);
+        let pf = Pl::prove(&pk, &v_circ, &witness, &mut ark_std::test_rng());
+        Pl::verify(&vk, &v_circ, pf, &public);
+    }
+
+    #[test]
+    fn plonk_optimize_test() {
+        let circuit = || -> PlonkCircuit<F> {
+            crate::circuit! {
+                pub one;
+                pub three;
+                nine = three * three;
+                s = (a * one) + (b * nine);
+                t = (nine * b) + a;
+                dead = s * t;
+                pub out = s * t + (a * b);
+            }
+        };
+        let constants: Public = vec![
+            ("one".to_owned(), F::from(1u64)),
+            ("three".to_owned(), F::from(3u64)),
+        ]
+        .into_iter()
+        .collect();
+        // The prover and verifier each optimize their own copy
+        let mut c = circuit();
+        let opt = c.optimize(&constants);
+        let mut v_c = circuit();
+        assert_eq!(v_c.optimize(&constants), opt);
+        // `nine` is folded, `a * one` is `a`, `t` is `s`, and `dead` and the padding go
+        assert_eq!((opt.gates_before, opt.gates_after), (16, 5));
+        assert_eq!(opt.saved(), 11);
+        assert_eq!(
+            opt.folded,
+            vec![("folded_0".to_owned(), F::from(9u64))]
+                .into_iter()
+                .collect()
+        );
+
+        let mut inputs = constants.clone();
+        inputs.extend(opt.folded.clone());
+        inputs.insert("a".to_owned(), F::from(2u64));
+        inputs.insert("b".to_owned(), F::from(5u64));
+        let assignment = assign(&c, &inputs).unwrap();
+        let c = c.with_assignment(assignment);
+        let v_circ = Instance::from_circuit(&v_c);
+        let witness = Witness::from_circuit(&v_circ, &c);
+        let mut public = constants;
+        public.extend(opt.folded);
+        public.insert("out".to_owned(), F::from(47u64 * 47 + 10));
+        let srs = Pl::universal_setup(8, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, &v_circ);
+        let pf = Pl::prove(&pk, &v_circ, &witness, &mut ark_std::test_rng());
+        Pl::verify(&vk, &v_circ, pf, &public);
+    }
+
+    #[test]
+    fn plonk_public_outputs_test() {
+        let c: PlonkCircuit<F> = crate::circuit! {
+            pub k;
+            t = a * b;
+            pub out = (t + k) * a;
+        };
+        let inputs: Public = vec![("a", 3u64), ("b", 4), ("k", 5)]
+            .into_iter()
+            .map(|(name, v)| (name.to_owned(), F::from(v)))
+            .collect();
+        let assignment = assign(&c, &inputs).unwrap();
+        let outputs = c.eval_public_outputs(&assignment);
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs["k"], F::from(5u64));
+        assert_eq!(outputs["out"], F::from(51u64));
+        // Outside MPC, opening changes nothing
+        let public = c.reveal_public_outputs(&assignment);
+        assert_eq!(public, outputs);
+
+        // The prover proves against the values it computed
+        let circ = Instance::from_circuit(&c);
+        let witness = Witness::from_circuit(&circ, &c.with_assignment(assignment));
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, &circ);
+        let pf = Pl::prove(&pk, &circ, &witness, &mut ark_std::test_rng());
+        Pl::verify(&vk, &circ, pf, &public);
+    }
+
+    #[test]
+    fn plonk_shared_output_test() {
+        let c: PlonkCircuit<F> = crate::circuit! {
+            shared t = a * b;
+            pub out = t + a;
+        };
+        assert!(c.pub_vars.values().all(|name| name != "t"));
+        let v_circ = Instance::from_circuit(&c);
+        let inputs = |name: &str| match name {
+            "a" => Some(F::from(2u64)),
+            "b" => Some(F::from(3u64)),
+            _ => None,
+        };
+        let assignment = assign(&c, &inputs).unwrap();
+        let c = c.with_assignment(assignment);
+        let shared = c.shared_output_values().unwrap();
+        assert_eq!(
+            shared,
+            vec![("t".to_owned(), F::from(6u64))].into_iter().collect()
+        );
+        let witness = Witness::from_circuit(&v_circ, &c);
+        let public: Public = vec![("out".to_owned(), F::from(8u64))]
+            .into_iter()
+            .collect();
+        let srs = Pl::universal_setup(2, &mut ark_std::test_rng());
         let (pk, vk) = Pl::circuit_setup(&srs, &v_circ);
-        let pf = Pl::prove(&pk, &circ, zk_rng);
+        let pf = Pl::prove(&pk, &v_circ, &witness, &mut ark_std::test_rng());
+        Pl::verify(&vk, &v_circ, pf, &public);
+    }
+
+    #[test]
+    #[should_panic(expected = "is a shared output")]
+    fn plonk_shared_output_public_test() {
+        let _: PlonkCircuit<F> = crate::circuit! {
+            shared t = a * b;
+            pub t;
+        };
+    }
+
+    #[test]
+    fn plonk_round_schedule_test() {
+        let (circ, witness, _) = squaring_instance();
+        let rounds = Pl::round_schedule(&circ).rounds();
+        assert_eq!(
+            rounds,
+            vec![
+                vec!["p_0", "p_1", "p_2", "gates_q"],
+                vec!["pub_q"],
+                vec!["l1", "t", "q", "l2_q"],
+            ]
+        );
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, _) = Pl::circuit_setup(&srs, &circ);
+        let policies: Vec<&dyn OpeningPolicy> = vec![&PerSubProof, &SinglePoint, &Rotated];
+        for policy in policies {
+            let rng = &mut ark_std::test_rng();
+            let mut prv = Prover::<F, PC>::new(&pk, rng);
+            prv.opening_policy(policy);
+            prv.prove(&circ, &witness);
+            assert_eq!(prv.commit_rounds(), rounds);
+        }
+    }
+
+    fn policy_proof(policy: &dyn OpeningPolicy) -> (Vk, Instance<F>, Pf, HashMap<String, F>) {
+        let (circ, witness, public) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, &circ);
+        let pf = Pl::prove_with_policy(&pk, &circ, &witness, &mut ark_std::test_rng(), policy);
+        (vk, circ, pf, public)
+    }
+
+    #[test]
+    fn plonk_single_point_test() {
+        let (vk, v_circ, pf, public) = policy_proof(&SinglePoint);
+        // One opening of a public column at the point serves the public, gates, and wiring
+        // proofs.
+        let col = v_circ.public_columns()[0];
+        assert_eq!(pf.public.p_opens[0].0, pf.gates.p_values[col]);
+        assert_eq!(pf.public.p_opens[0].0, pf.wiring.p_x_opens[col].0);
+        Pl::verify_with_policy(&vk, &v_circ, pf, &public, &SinglePoint);
+    }
+
+    #[test]
+    fn plonk_rotated_test() {
+        let (vk, v_circ, pf, public) = policy_proof(&Rotated);
+        // The unit product opens L_1 where the wiring proof does.
+        assert_eq!(pf.wiring.l1_prod_pf.f_wr_open.0, pf.wiring.l1_x_open.0);
+        Pl::verify_with_policy(&vk, &v_circ, pf, &public, &Rotated);
+    }
+
+    #[test]
+    #[should_panic]
+    fn plonk_wrong_policy_test() {
+        let (vk, v_circ, pf, public) = policy_proof(&SinglePoint);
+        Pl::verify_with_policy(&vk, &v_circ, pf, &public, &PerSubProof);
+    }
+
+    #[test]
+    fn plonk_hiding_test() {
+        use ark_std::rand::{rngs::StdRng, SeedableRng};
+        let (circ, witness, public) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup_hiding(&srs, &circ);
+        let pf = Pl::prove_hiding(&pk, &circ, &witness, &mut StdRng::seed_from_u64(1));
+        let other = Pl::prove_hiding(&pk, &circ, &witness, &mut StdRng::seed_from_u64(2));
+        // Different blinds, so the same witness commits differently.
+        assert_ne!(pf.p_cmts, other.p_cmts);
+        Pl::verify(&vk, &circ, pf, &public);
+    }
+
+    #[test]
+    fn plonk_hiding_derived_blinds_test() {
+        use mpc_algebra::BlindingSeed;
+        let (circ, witness, public) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup_hiding(&srs, &circ);
+        let seed = BlindingSeed::new([7; 32]);
+        let digest = &pk.circuit_digest.0;
+        let prove = |session: &[u8]| {
+            let mut rng = seed.for_proof(digest, session).rng();
+            Pl::prove_hiding(&pk, &circ, &witness, &mut rng)
+        };
+        let pf = prove(b"session 1");
+        // The blinds can be rederived from the seed: the proof is reproduced exactly.
+        let (mut bytes, mut again) = (Vec::new(), Vec::new());
+        pf.serialize(&mut bytes).unwrap();
+        prove(b"session 1").serialize(&mut again).unwrap();
+        assert_eq!(bytes, again);
+        assert_ne!(pf.p_cmts, prove(b"session 2").p_cmts);
+        // An auditor given just the circuit's seed rederives the session's.
+        let circuit_seed = seed.child(digest);
+        assert_eq!(
+            circuit_seed.child(b"session 1").as_bytes(),
+            seed.for_proof(digest, b"session 1").as_bytes()
+        );
+        Pl::verify(&vk, &circ, pf, &public);
+    }
+
+    #[test]
+    fn plonk_report_test() {
+        let (vk, v_circ, pf, public) = transcript_proof::<Blake2sTranscript>();
+        assert_eq!(
+            Pl::verify_with_report(&vk, &v_circ, pf, &public, None),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn plonk_report_wrong_public_test() {
+        let (vk, v_circ, pf, mut public) = transcript_proof::<Blake2sTranscript>();
+        public.insert("out".to_owned(), F::from(1u64));
+        let report = Pl::verify_with_report(&vk, &v_circ, pf, &public, None).unwrap_err();
+        match &report.failures[..] {
+            [Failure::Identity {
+                identity: "public",
+                left,
+                right,
+                challenges,
+            }] => {
+                assert_ne!(left, right);
+                assert_eq!(challenges[0].0, "public_x");
+            }
+            failures => panic!("Unexpected failures: {:?}", failures),
+        }
+        assert_eq!(report.abort().code, AbortCode::ConstraintUnsatisfied);
+    }
+
+    #[test]
+    fn plonk_report_missing_input_test() {
+        let (vk, v_circ, pf, mut public) = transcript_proof::<Blake2sTranscript>();
+        public.remove("out");
+        let report = Pl::verify_with_report(&vk, &v_circ, pf, &public, None).unwrap_err();
+        assert_eq!(
+            report.failures,
+            vec![Failure::MissingInput {
+                name: "out".to_owned()
+            }]
+        );
+    }
+
+    #[test]
+    fn plonk_small_proof_missing_input_test() {
+        let (circ, witness, mut public) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, &circ);
+        let pf = Pl::prove_small(&pk, &circ, &witness, &mut ark_std::test_rng());
+        public.remove("out");
+        let report = Pl::verify_small_with_report(&vk, &circ, pf, &public).unwrap_err();
+        assert_eq!(report.abort().code, AbortCode::SrsMismatch);
+        assert!(matches!(&report.failures[..], [Failure::MissingInput { .. }]));
+    }
+
+    #[test]
+    fn plonk_report_key_columns_test() {
+        let (mut vk, v_circ, pf, public) = transcript_proof::<Blake2sTranscript>();
+        vk.w_cmts.pop();
+        let report = Pl::verify_with_report(&vk, &v_circ, pf, &public, None).unwrap_err();
+        assert_eq!(
+            report.failures,
+            vec![Failure::Columns {
+                what: "W commitments",
+                expected: 3,
+                found: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn plonk_report_bad_opening_test() {
+        let (vk, v_circ, mut pf, public) = transcript_proof::<Blake2sTranscript>();
+        pf.gates.p_values[1] += F::from(1u64);
+        let report = Pl::verify_with_report(&vk, &v_circ, pf, &public, None).unwrap_err();
+        let labels: Vec<_> = report
+            .failures
+            .iter()
+            .map(|failure| match failure {
+                Failure::Opening { commitment, .. } => commitment.as_str(),
+                failure => panic!("Unexpected failure: {:?}", failure),
+            })
+            .collect();
+        // Every claim of the batched opening, which uses the P_j's values
+        assert_eq!(labels, ["gates_q", "gates_r", "p_0", "p_1", "p_2"]);
+    }
+
+    #[test]
+    fn plonk_batched_openings_test() {
+        let (vk, v_circ, pf, public) = transcript_proof::<Blake2sTranscript>();
+        Pl::verify_batched(&vk, &v_circ, pf, &public);
+        let (circ, witness, public) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, &circ);
+        let pf = Pl::prove_small(&pk, &circ, &witness, &mut ark_std::test_rng());
+        let mut ver = Verifier::<F, PC>::new(&vk);
+        ver.batch_openings();
+        ver.verify_small(&circ, pf, &public);
+    }
+
+    #[test]
+    fn plonk_report_batched_opening_test() {
+        let (vk, v_circ, mut pf, public) = transcript_proof::<Blake2sTranscript>();
+        // The right values, with each other's opening proofs
+        let (a, b) = (pf.wiring.w_x_opens[0].1, pf.wiring.w_x_opens[1].1);
+        pf.wiring.w_x_opens[0].1 = b;
+        pf.wiring.w_x_opens[1].1 = a;
+        let mut ver = Verifier::<F, PC>::new_reporting(&vk, None);
+        ver.batch_openings();
+        ver.verify(&v_circ, pf, &public);
+        match &ver.take_failures()[..] {
+            [Failure::BatchedOpenings { count }] => assert!(*count > 2),
+            failures => panic!("Unexpected failures: {:?}", failures),
+        }
+    }
+
+    /// A proof whose gates and wiring quotients are replaced by commitments to `q`, with the
+    /// honest commitments' shifted parts (or, if `shifted` is false, without any).
+    fn forge_quotients(q: &P, shifted: bool) -> (Vk, Instance<F>, Pf, HashMap<String, F>) {
+        let (circ, witness, public) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, &circ);
+        let mut pf = Pl::prove(&pk, &circ, &witness, &mut ark_std::test_rng());
+        let q = LabeledPolynomial::new("q".to_owned(), q.clone(), None, None);
+        let (cmts, _) = PC::commit(&pk.pc_ck, once(&q), None).unwrap();
+        let comm = cmts[0].commitment().comm;
+        for cmt in [&mut pf.gates.q_cmt, &mut pf.wiring.l2_q_cmt] {
+            cmt.comm = comm;
+            if !shifted {
+                cmt.shifted_comm = None;
+            }
+        }
+        (vk, circ, pf, public)
+    }
+
+    #[test]
+    fn plonk_unbounded_quotient_test() {
+        let q = P::from_coefficients_vec(vec![F::from(1u64); 3]);
+        let (vk, v_circ, pf, public) = forge_quotients(&q, false);
+        let report = Pl::verify_with_report(&vk, &v_circ, pf, &public, None).unwrap_err();
+        let unbounded: Vec<_> = report
+            .failures
+            .iter()
+            .filter_map(|failure| match failure {
+                Failure::DegreeBound { commitment, .. } => Some(commitment.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(unbounded, ["gates_q", "l2_q"]);
+        assert_eq!(report.abort().code, AbortCode::SrsMismatch);
+    }
+
+    #[test]
+    fn plonk_high_degree_quotient_test() {
+        let (circ, _, _) = squaring_instance();
+        // Above the gates quotient's bound, but within the keys' supported degree
+        let degree = circ.domains.gates_quotient_degree() + 1;
+        let q = P::from_coefficients_vec(vec![F::from(1u64); degree + 1]);
+        let (vk, circ, pf, public) = forge_quotients(&q, true);
+        let report = Pl::verify_with_report(&vk, &circ, pf, &public, None).unwrap_err();
+        // Each quotient's opening fails, as its shifted commitment is to another polynomial
+        let forged: Vec<_> = report
+            .failures
+            .iter()
+            .filter_map(|failure| match failure {
+                Failure::Opening { commitment, .. } => Some(commitment.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert!(forged.contains(&"gates_q"), "{}", report);
+        assert!(forged.contains(&"l2_q"), "{}", report);
+    }
+
+    #[test]
+    fn plonk_report_beacon_test() {
+        let beacon = Beacon {
+            round: 7,
+            value: vec![1, 2, 3],
+        };
+        let (vk, v_circ, pf, public) = beacon_proof(&beacon);
+        let report = Pl::verify_with_report(&vk, &v_circ, pf, &public, None).unwrap_err();
+        assert_eq!(
+            report.failures,
+            vec![Failure::BeaconRound {
+                expected: None,
+                found: Some(7)
+            }]
+        );
+        assert_eq!(report.abort().code, AbortCode::TranscriptDivergence);
+    }
+
+    #[test]
+    #[should_panic(expected = "different beacon round")]
+    fn plonk_missing_beacon_test() {
+        let beacon = Beacon {
+            round: 7,
+            value: vec![1, 2, 3],
+        };
+        let (vk, v_circ, pf, public) = beacon_proof(&beacon);
         Pl::verify(&vk, &v_circ, pf, &public);
     }
+
+    /// Chunks of `max_gates` gates, of unpadded circuits of `steps` squarings: the prover's, and
+    /// the verifier's, split without values.
+    #[allow(clippy::type_complexity)]
+    fn chunked_proof<
+        G: ark_ec::AffineCurve,
+        PC: PolynomialCommitment<G::ScalarField, DensePolynomial<G::ScalarField>>,
+    >(
+        max_gates: usize,
+        steps: usize,
+    ) -> (
+        Vec<VerifierKey<PC::Commitment, PC::VerifierKey>>,
+        Vec<Instance<G::ScalarField>>,
+        ChunkedProof<G::ScalarField, PC::Commitment, PC::Proof>,
+        HashMap<String, G::ScalarField>,
+    )
+    where
+        PC::Commitment: mpc_trait::MpcWire,
+        PC::Error: 'static,
+    {
+        let start = G::ScalarField::from(2u64);
+        let v_c = PlonkCircuit::unpadded_squaring_circuit(steps);
+        let assignment = assign(&v_c, &|_: &str| Some(start)).unwrap();
+        let c = PlonkCircuit::unpadded_squaring_circuit(steps).with_assignment(assignment);
+        let res = (0..steps).fold(start, |a, _| a * a);
+        let params = util::PoseidonParameters::new();
+        let rng = &mut ark_std::test_rng();
+        let split = c.split(max_gates, &params, rng);
+        let v_split = v_c.split(max_gates, &params, rng);
+        let mut public: HashMap<String, _> = vec![("out".to_owned(), res)].into_iter().collect();
+        public.extend(v_split.public);
+        let circs: Vec<Instance<_>> = split.chunks.iter().map(Instance::from_circuit).collect();
+        let v_circs: Vec<Instance<_>> = v_split.chunks.iter().map(Instance::from_circuit).collect();
+        let witnesses: Vec<Witness<_>> = circs
+            .iter()
+            .zip(&split.chunks)
+            .map(|(circ, c)| Witness::from_circuit(circ, c))
+            .collect();
+        let srs = Plonk::<_, PC>::universal_setup(max_gates, rng);
+        let pks: Vec<_> = circs
+            .iter()
+            .map(|c| Plonk::<_, PC>::circuit_setup(&srs, c).0)
+            .collect();
+        let vks: Vec<_> = v_circs
+            .iter()
+            .map(|c| Plonk::<_, PC>::circuit_setup(&srs, c).1)
+            .collect();
+        let pf = Plonk::<_, PC>::prove_chunked(&pks, &circs, &witnesses, split.links.unwrap(), rng);
+        (vks, v_circs, pf, public)
+    }
+
+    #[test]
+    fn plonk_chunked_test() {
+        // A commitment is too many gates for a chunk to have two links
+        let (vks, v_circs, pf, public) = chunked_proof::<ark_bls12_377::G1Affine, PC>(4096, 4500);
+        assert_eq!(pf.chunks.len(), 2);
+        assert_eq!(pf.links.len(), 1);
+        let mut bad = pf.clone();
+        bad.links[0] += F::from(1u64);
+        Pl::verify_chunked(&vks, &v_circs, pf, &public);
+        let verify_bad = || Pl::verify_chunked(&vks, &v_circs, bad, &public);
+        assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(verify_bad)).is_err());
+    }
+
+    #[test]
+    fn split_hides_links_test() {
+        let steps = 4500;
+        let start = F::from(2u64);
+        let c = PlonkCircuit::<F>::unpadded_squaring_circuit(steps);
+        let values = assign(&c, &|_: &str| Some(start)).unwrap().0;
+        let c = c.with_assignment(Assignment(values.clone()));
+        let params = util::PoseidonParameters::new();
+        let split = c.split(4096, &params, &mut ark_std::test_rng());
+        assert_eq!(split.chunks.len(), 2);
+        let commitments = split.links.clone().unwrap();
+        assert_eq!(commitments.len(), 1);
+        // The squaring that both chunks have is the link
+        let shared: Vec<&F> = values
+            .iter()
+            .filter(|v| {
+                let in_chunk = |c: &PlonkCircuit<F>| c.values.as_ref().unwrap().contains(v);
+                split.chunks.iter().all(in_chunk)
+            })
+            .collect();
+        assert_eq!(shared.len(), 1);
+        let link = *shared[0];
+        for chunk in &split.chunks {
+            let values = chunk.values.as_ref().unwrap();
+            let public: HashMap<&String, F> = chunk
+                .pub_vars
+                .iter()
+                .map(|(v, name)| (name, values[*v as usize]))
+                .collect();
+            assert_eq!(public[&format!("{}0", LINK_PREFIX)], commitments[0]);
+            assert!(!public.values().any(|v| *v == link));
+            for (name, v) in &split.public {
+                assert_eq!(public[name], *v);
+            }
+        }
+    }
+
+    /// In a field of low two-adicity, a circuit of as many gates as fit is proven in one chunk.
+    #[test]
+    fn plonk_split_to_fit_test() {
+        type G = ark_secp256k1::Affine;
+        type Fs = ark_secp256k1::Fr;
+        let max_gates = PlonkCircuit::<Fs>::max_gates();
+        // 2^6 points, and the wiring quotient takes four a gate
+        assert_eq!(max_gates, 16);
+        assert!(!Domains::<Fs>::fit(2 * max_gates, GATE_WIDTH));
+        let c = PlonkCircuit::<Fs>::squaring_circuit(max_gates);
+        assert_eq!(c.n_gates(), max_gates);
+        let split = c.split_to_fit(&util::PoseidonParameters::new(), &mut ark_std::test_rng());
+        assert_eq!(split.chunks.len(), 1);
+        assert!(split.public.is_empty());
+        let (vks, v_circs, pf, public) = chunked_proof::<G, IpaPC<G>>(max_gates, max_gates);
+        assert!(pf.links.is_empty());
+        Plonk::<Fs, IpaPC<G>>::verify_chunked(&vks, &v_circs, pf, &public);
+    }
+
+    type IpaPC<G> = ark_poly_commit::ipa_pc::InnerProductArgPC<
+        G,
+        Blake2s,
+        DensePolynomial<<G as ark_ec::AffineCurve>::ScalarField>,
+    >;
+
+    /// Plonk over a curve with no pairing, and no trusted setup.
+    fn ipa_roundtrip<G: ark_ec::AffineCurve>() {
+        let steps = 4;
+        let start = G::ScalarField::from(2u64);
+        let c = PlonkCircuit::new_squaring_circuit(steps, Some(start));
+        let res = (0..steps).fold(start, |a, _| a * a);
+        let public: HashMap<String, _> = vec![("out".to_owned(), res)].into_iter().collect();
+        let circ = Instance::from_circuit(&c);
+        let witness = Witness::from_circuit(&circ, &c);
+
+        let srs = Plonk::<_, IpaPC<G>>::universal_setup(steps, &mut ark_std::test_rng());
+        let (pk, vk) = Plonk::<_, IpaPC<G>>::circuit_setup(&srs, &circ);
+        let pf = Plonk::<_, IpaPC<G>>::prove(&pk, &circ, &witness, &mut ark_std::test_rng());
+        Plonk::<_, IpaPC<G>>::verify(&vk, &circ, pf, &public);
+    }
+
+    #[test]
+    fn plonk_pallas_ipa_test() {
+        ipa_roundtrip::<ark_pallas::Affine>();
+    }
+
+    #[test]
+    fn plonk_vesta_ipa_test() {
+        ipa_roundtrip::<ark_vesta::Affine>();
+    }
+
+    fn transcript_proof<T: Transcript>() -> (Vk, Instance<F>, Pf, HashMap<String, F>) {
+        let (circ, witness, public) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, &circ);
+        let pf = Plonk::<F, PC, T>::prove(&pk, &circ, &witness, &mut ark_std::test_rng());
+        (vk, circ, pf, public)
+    }
+
+    #[test]
+    fn plonk_sha3_transcript_test() {
+        let (vk, v_circ, pf, public) = transcript_proof::<Sha3Transcript>();
+        Plonk::<F, PC, Sha3Transcript>::verify(&vk, &v_circ, pf, &public);
+    }
+
+    #[test]
+    fn plonk_poseidon_transcript_test() {
+        let (vk, v_circ, pf, public) = transcript_proof::<PoseidonTranscript<F>>();
+        Plonk::<F, PC, PoseidonTranscript<F>>::verify(&vk, &v_circ, pf, &public);
+    }
+
+    #[test]
+    fn plonk_merlin_transcript_test() {
+        let (vk, v_circ, pf, public) = transcript_proof::<merlin::Transcript>();
+        Plonk::<F, PC, merlin::Transcript>::verify(&vk, &v_circ, pf, &public);
+    }
+
+    #[test]
+    #[should_panic]
+    fn plonk_wrong_transcript_test() {
+        let (vk, v_circ, pf, public) = transcript_proof::<merlin::Transcript>();
+        Pl::verify(&vk, &v_circ, pf, &public);
+    }
+
+    /// `t`, serialized in format version `version`.
+    fn versioned_bytes<T: CanonicalSerialize>(version: u8, t: &T) -> Vec<u8> {
+        let mut bytes = vec![version];
+        t.serialize(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn versioned_roundtrip_test() {
+        let (vk, v_circ, pf, public) = transcript_proof::<Blake2sTranscript>();
+        let mut vk_bytes = Vec::new();
+        write_versioned(&vk, &mut vk_bytes).unwrap();
+        let mut pf_bytes = Vec::new();
+        write_versioned(&pf, &mut pf_bytes).unwrap();
+        assert_eq!(pf_bytes, versioned_bytes(FORMAT_VERSION, &pf));
+
+        let vk: Versioned<Vk> = read_versioned(&vk_bytes[..]).unwrap();
+        let pf: Versioned<Pf> = read_versioned(&pf_bytes[..]).unwrap();
+        assert_eq!(pf.version, FORMAT_VERSION);
+        Pl::verify_versioned(&vk.value, &v_circ, pf, &public);
+    }
+
+    /// Write then read `t`, checking that it reads back to the same bytes.
+    fn versioned_roundtrip<T: CanonicalSerialize + CanonicalDeserialize>(t: &T) -> T {
+        let mut bytes = Vec::new();
+        write_versioned(t, &mut bytes).unwrap();
+        assert_eq!(bytes.len(), 1 + t.serialized_size());
+        let t: Versioned<T> = read_versioned(&bytes[..]).unwrap();
+        let mut again = Vec::new();
+        write_versioned(&t.value, &mut again).unwrap();
+        assert_eq!(bytes, again);
+        t.value
+    }
+
+    #[test]
+    fn index_roundtrip_test() {
+        let (circ, witness, public) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, &circ);
+        let pk = versioned_roundtrip(&pk);
+        let pf = Pl::prove(
+            &pk,
+            &versioned_roundtrip(&circ),
+            &witness,
+            &mut ark_std::test_rng(),
+        );
+        Pl::verify(
+            &versioned_roundtrip(&vk),
+            &versioned_roundtrip(&circ),
+            pf,
+            &public,
+        );
+    }
+
+    #[test]
+    fn future_format_rejected_test() {
+        let (_, _, pf, _) = transcript_proof::<Blake2sTranscript>();
+        let bytes = versioned_bytes(FORMAT_VERSION + 1, &pf);
+        assert!(matches!(
+            read_versioned::<Pf, _>(&bytes[..]),
+            Err(FormatError::UnsupportedVersion(v)) if v == FORMAT_VERSION + 1
+        ));
+    }
+
+    #[cfg(not(feature = "compat"))]
+    #[test]
+    fn previous_format_rejected_test() {
+        let (vk, _, pf, _) = transcript_proof::<Blake2sTranscript>();
+        let bytes = versioned_bytes(FORMAT_VERSION - 1, &pf);
+        assert!(matches!(
+            read_versioned::<Pf, _>(&bytes[..]),
+            Err(FormatError::UnsupportedVersion(v)) if v == FORMAT_VERSION - 1
+        ));
+        let bytes = versioned_bytes(FORMAT_VERSION - 1, &vk);
+        assert!(read_versioned::<Vk, _>(&bytes[..]).is_err());
+    }
+
+    // Fingerprints of proofs made with the version 1 transcript.
+    #[cfg(feature = "compat")]
+    #[test]
+    fn legacy_transcript_test() {
+        let (_, _, pf, _) = transcript_proof::<LegacyTranscript>();
+        let fingerprint = |f: &F| f.to_string();
+        assert_eq!(
+            fingerprint(&pf.gates.p_values[0]),
+            "Fp256 \"(0DFD9E14545548839BEE1D6D313651CA1280C4A10D5C5DBF3DF9A643D7C22910)\""
+        );
+        assert_eq!(
+            fingerprint(&pf.wiring.p_x_opens[0].0),
+            "Fp256 \"(08D1EEDABE7E12339D1ECCF192727D5D32729E542FAD7D7AFEEE5B09BD4C84F1)\""
+        );
+        assert_eq!(
+            fingerprint(&pf.public.p_opens[0].0),
+            "Fp256 \"(0C695F6FB80E96DCB8948D848F594153F5C3E3767C0D087F18FE61F3EFB48C56)\""
+        );
+
+        let (circ, witness, _) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, _) = Pl::circuit_setup(&srs, &circ);
+        let beacon = Beacon {
+            round: 7,
+            value: vec![1, 2, 3],
+        };
+        let pf = Plonk::<F, PC, LegacyTranscript>::prove_with_beacon(
+            &pk,
+            &circ,
+            &witness,
+            &mut ark_std::test_rng(),
+            &beacon,
+        );
+        assert_eq!(
+            fingerprint(&pf.gates.p_values[0]),
+            "Fp256 \"(105D8093914CE388C139B9CFD10C56C706CB5ECEC86DCA296A8B2CBE70BD7B2E)\""
+        );
+    }
+
+    /// A version 6 proof of [squaring_instance], and its key, as written before version 7.
+    #[cfg(feature = "compat")]
+    const V6_PROOF: &[u8] = include_bytes!("testdata/v6_proof.bin");
+    #[cfg(feature = "compat")]
+    const V6_KEY: &[u8] = include_bytes!("testdata/v6_key.bin");
+
+    #[cfg(feature = "compat")]
+    #[test]
+    fn v6_proof_accepted_test() {
+        let (circ, _, public) = squaring_instance();
+        let vk: Versioned<Vk> = read_versioned(V6_KEY).unwrap();
+        let pf: Versioned<Pf> = read_versioned(V6_PROOF).unwrap();
+        assert_eq!((vk.version, pf.version), (6, 6));
+        Pl::verify_versioned(&vk.value, &circ, pf.clone(), &public);
+        assert!(
+            Pl::verify_versioned_with_report(&vk.value, &circ, pf.clone(), &public, None).is_ok()
+        );
+        // Read as the current version, its transcript differs.
+        assert!(Pl::verify_with_report(&vk.value, &circ, pf.value, &public, None).is_err());
+    }
+
+    #[cfg(feature = "compat")]
+    #[test]
+    fn older_formats_rejected_test() {
+        let (vk, _, pf, _) = transcript_proof::<Blake2sTranscript>();
+        for version in 1..OLDEST_FORMAT_VERSION {
+            let bytes = versioned_bytes(version, &pf);
+            assert!(matches!(
+                read_versioned::<Pf, _>(&bytes[..]),
+                Err(FormatError::UnsupportedVersion(v)) if v == version
+            ));
+            let bytes = versioned_bytes(version, &vk);
+            assert!(matches!(
+                read_versioned::<Vk, _>(&bytes[..]),
+                Err(FormatError::UnsupportedVersion(v)) if v == version
+            ));
+        }
+    }
+
+    #[test]
+    fn plonk_warmup_test() {
+        let (circ, witness, public) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, &circ);
+        let cold = Pl::prove(&pk, &circ, &witness, &mut ark_std::test_rng());
+        let rng = &mut ark_std::test_rng();
+        let mut prv = Prover::<F, PC>::new(&pk, rng);
+        prv.warmup(&circ);
+        let warm = prv.prove(&circ, &witness);
+        let bytes = |pf: &Pf| {
+            let mut bytes = Vec::new();
+            pf.serialize(&mut bytes).unwrap();
+            bytes
+        };
+        assert_eq!(bytes(&cold), bytes(&warm));
+        Pl::verify(&vk, &circ, warm, &public);
+    }
+
+    /// Runs `f` on a pool of `threads` threads; with one, every phase runs serially.
+    #[cfg(feature = "parallel")]
+    fn on_threads<T: Send>(threads: usize, f: impl FnOnce() -> T + Send) -> T {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap()
+            .install(f)
+    }
+
+    /// Parallel proving must not reorder anything the proof depends on (e.g., transcript absorbs).
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn plonk_parallel_determinism_test() {
+        let (circ, witness, public) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, &circ);
+        // The keys share polynomials through `Rc`s, so each pool gets its own copy.
+        let mut pk_bytes = Vec::new();
+        pk.serialize(&mut pk_bytes).unwrap();
+        let prove_bytes = |threads| {
+            on_threads(threads, || {
+                let pk = ProverKey::<F, Cmt, Ck>::deserialize(&pk_bytes[..]).unwrap();
+                let pf = Pl::prove(&pk, &circ, &witness, &mut ark_std::test_rng());
+                let mut bytes = Vec::new();
+                pf.serialize(&mut bytes).unwrap();
+                bytes
+            })
+        };
+        let serial = prove_bytes(1);
+        for threads in &[2, 4, 8] {
+            assert_eq!(serial, prove_bytes(*threads), "{} threads", threads);
+        }
+        let pf = Pf::deserialize(&serial[..]).unwrap();
+        Pl::verify(&vk, &circ, pf, &public);
+    }
+
+    /// Every phase of a proof is recorded, and a local proof exchanges nothing.
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn plonk_metrics_test() {
+        let (circ, witness, public) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, &circ);
+        let rng = &mut ark_std::test_rng();
+        let prv = Prover::<F, PC>::new(&pk, rng);
+        let start = std::time::Instant::now();
+        let pf = prv.prove(&circ, &witness);
+        let elapsed = start.elapsed();
+        let metrics = prv.metrics();
+        let phases: Vec<Phase> = metrics.phases.iter().map(|(p, _)| *p).collect();
+        assert_eq!(
+            phases,
+            vec![
+                Phase::Warmup,
+                Phase::Gates,
+                Phase::Columns,
+                Phase::Public,
+                Phase::Wiring,
+                Phase::Publish
+            ]
+        );
+        let total = metrics.total();
+        assert!(total.wall <= elapsed);
+        let zero = std::time::Duration::ZERO;
+        assert!(total.fft > zero && total.commit > zero && total.open > zero);
+        assert!(metrics.phase(Phase::Wiring).unwrap().fft > zero);
+        assert_eq!((total.bytes_sent, total.rounds, total.wait), (0, 0, zero));
+        // The next proof's metrics replace these
+        prv.prove(&circ, &witness);
+        assert_eq!(prv.metrics().phases.len(), phases.len());
+        Pl::verify(&vk, &circ, pf, &public);
+    }
+
+    #[test]
+    fn plonk_spill_test() {
+        let (circ, witness, public) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, &circ);
+        let in_memory = Pl::prove(&pk, &circ, &witness, &mut ark_std::test_rng());
+        let dir = std::env::temp_dir().join(format!("mpc-plonk-spill-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let rng = &mut ark_std::test_rng();
+        let mut prv = Prover::<F, PC>::new(&pk, rng);
+        prv.memory_mode(MemoryMode::Spill(dir.clone()));
+        let spilled = prv.prove(&circ, &witness);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir(&dir).unwrap();
+        let bytes = |pf: &Pf| {
+            let mut bytes = Vec::new();
+            pf.serialize(&mut bytes).unwrap();
+            bytes
+        };
+        assert_eq!(bytes(&in_memory), bytes(&spilled));
+        Pl::verify(&vk, &circ, spilled, &public);
+    }
+
+    #[test]
+    fn plonk_witness_file_test() {
+        let (circ, witness, public) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, &circ);
+        let in_memory = Pl::prove(&pk, &circ, &witness, &mut ark_std::test_rng());
+        let path = std::env::temp_dir().join(format!("mpc-plonk-witness-{}", std::process::id()));
+        let values = PlonkCircuit::<F>::new_squaring_circuit(4, Some(F::from(2u64)))
+            .values
+            .unwrap();
+        WitnessFile::write_values(&values, &path).unwrap();
+        let c = PlonkCircuit::<F>::new_squaring_circuit(4, None);
+        let streamed = Witness::from_file(&c, &path).unwrap();
+        assert!(matches!(streamed, Witness::File(_)));
+        assert_eq!(streamed.polys(&circ), witness.polys(&circ));
+        let pf = Pl::prove(&pk, &circ, &streamed, &mut ark_std::test_rng());
+        // A file of the wrong size is rejected.
+        WitnessFile::write_values(&values[1..], &path).unwrap();
+        assert!(Witness::<F>::from_file(&c, &path).is_err());
+        std::fs::remove_file(&path).unwrap();
+        let bytes = |pf: &Pf| {
+            let mut bytes = Vec::new();
+            pf.serialize(&mut bytes).unwrap();
+            bytes
+        };
+        assert_eq!(bytes(&in_memory), bytes(&pf));
+        Pl::verify(&vk, &circ, pf, &public);
+    }
+
+    type Ck = <PC as PolynomialCommitment<F, P>>::CommitterKey;
+    type Rand = <PC as PolynomialCommitment<F, P>>::Randomness;
+    type PcError = <PC as PolynomialCommitment<F, P>>::Error;
+
+    /// Computes in process, recording what it was asked for.
+    #[derive(Default)]
+    struct RecordingCommitter {
+        labels: RefCell<Vec<String>>,
+        /// How many polynomials each call committed to
+        batches: RefCell<Vec<usize>>,
+        opens: RefCell<usize>,
+        /// Drop each call's last commitment, as a faulty backend might
+        drop_last: bool,
+    }
+
+    impl CommitterBackend<F, PC> for RecordingCommitter {
+        fn commit(
+            &self,
+            ck: &Ck,
+            p: &LabeledPolynomial<F, P>,
+            rng: Option<&mut dyn RngCore>,
+        ) -> std::result::Result<(LabeledCommitment<Cmt>, Rand), Error<PcError>> {
+            self.labels.borrow_mut().push(p.label().clone());
+            CommitterBackend::<F, PC>::commit(&LocalCommitter, ck, p, rng)
+        }
+
+        fn commit_many(
+            &self,
+            ck: &Ck,
+            ps: &[&LabeledPolynomial<F, P>],
+            rng: Option<&mut dyn RngCore>,
+        ) -> std::result::Result<Vec<(LabeledCommitment<Cmt>, Rand)>, Error<PcError>> {
+            let mut labels = self.labels.borrow_mut();
+            labels.extend(ps.iter().map(|p| p.label().clone()));
+            self.batches.borrow_mut().push(ps.len());
+            let mut cmts = CommitterBackend::<F, PC>::commit_many(&LocalCommitter, ck, ps, rng)?;
+            if self.drop_last {
+                cmts.pop();
+            }
+            Ok(cmts)
+        }
+
+        fn open(
+            &self,
+            ck: &Ck,
+            p: &LabeledPolynomial<F, P>,
+            p_c: &LabeledCommitment<Cmt>,
+            x: F,
+            p_r: &Rand,
+            rng: Option<&mut dyn RngCore>,
+        ) -> std::result::Result<PcPf, Error<PcError>> {
+            *self.opens.borrow_mut() += 1;
+            CommitterBackend::<F, PC>::open(&LocalCommitter, ck, p, p_c, x, p_r, rng)
+        }
+
+        fn open_combinations(
+            &self,
+            ck: &Ck,
+            lcs: &[LinearCombination<F>],
+            ps: &[&LabeledPolynomial<F, P>],
+            p_cs: &[&LabeledCommitment<Cmt>],
+            query_set: &QuerySet<F>,
+            opening_challenge: F,
+            p_rs: &[&Rand],
+            rng: Option<&mut dyn RngCore>,
+        ) -> std::result::Result<BatchLCProof<F, P, PC>, Error<PcError>> {
+            *self.opens.borrow_mut() += 1;
+            CommitterBackend::<F, PC>::open_combinations(
+                &LocalCommitter,
+                ck,
+                lcs,
+                ps,
+                p_cs,
+                query_set,
+                opening_challenge,
+                p_rs,
+                rng,
+            )
+        }
+    }
+
+    #[test]
+    fn plonk_committer_backend_test() {
+        let (circ, witness, public) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, &circ);
+        let local = Pl::prove(&pk, &circ, &witness, &mut ark_std::test_rng());
+        let backend = RecordingCommitter::default();
+        let rng = &mut ark_std::test_rng();
+        let mut prv = Prover::<F, PC>::new(&pk, rng);
+        prv.committer_backend(&backend);
+        let delegated = prv.prove(&circ, &witness);
+        // The gates quotient is committed with the P_j's, ahead of the public quotient.
+        assert_eq!(
+            backend.labels.borrow()[..5],
+            ["p_0", "p_1", "p_2", "gates_q", "pub_q"]
+        );
+        // Each round's commitments are computed in one call.
+        assert_eq!(*backend.batches.borrow(), vec![4, 1, 4]);
+        assert!(*backend.opens.borrow() > 0);
+        let mut local_bytes = Vec::new();
+        local.serialize(&mut local_bytes).unwrap();
+        let mut delegated_bytes = Vec::new();
+        delegated.serialize(&mut delegated_bytes).unwrap();
+        assert_eq!(local_bytes, delegated_bytes);
+        Pl::verify(&vk, &circ, delegated, &public);
+    }
+
+    #[test]
+    fn plonk_committer_backend_error_test() {
+        let (circ, _, _) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, _) = Pl::circuit_setup(&srs, &circ);
+        let backend = RecordingCommitter {
+            drop_last: true,
+            ..Default::default()
+        };
+        let rng = &mut ark_std::test_rng();
+        let mut prv = Prover::<F, PC>::new(&pk, rng);
+        prv.committer_backend(&backend);
+        let polys = (0..2)
+            .map(|i| {
+                let p = DensePolynomial::rand(3, &mut ark_std::test_rng());
+                ("p", prv.labeled(format!("p_{}", i), p, None))
+            })
+            .collect();
+        assert!(matches!(prv.queue_many(polys), Err(Error::Backend(_))));
+    }
+
+    #[test]
+    fn commit_many_test() {
+        let (circ, _, _) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, _) = Pl::circuit_setup_hiding(&srs, &circ);
+        let k = circ.domains.gates.size();
+        let ps: Vec<LabeledPolynomial<F, P>> = (0..3)
+            .map(|i| {
+                let p = DensePolynomial::rand(k - 1, &mut ark_std::test_rng());
+                LabeledPolynomial::new(format!("p_{}", i), p, Some(k - 1), Some(HIDING_BOUND))
+            })
+            .collect();
+        let refs: Vec<_> = ps.iter().collect();
+        let many = CommitterBackend::<F, PC>::commit_many(
+            &LocalCommitter,
+            &pk.pc_ck,
+            &refs,
+            Some(&mut ark_std::test_rng()),
+        )
+        .unwrap();
+        // The same commitments, and blinds, as committing one at a time
+        let rng = &mut ark_std::test_rng();
+        let each: Vec<_> = ps
+            .iter()
+            .map(|p| {
+                CommitterBackend::<F, PC>::commit(&LocalCommitter, &pk.pc_ck, p, Some(&mut *rng))
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(many.len(), 3);
+        for ((c, r), (c2, r2)) in many.iter().zip(&each) {
+            assert_eq!(c.label(), c2.label());
+            assert_eq!(c.commitment(), c2.commitment());
+            assert_eq!(r, r2);
+        }
+    }
+
+    #[test]
+    fn plonk_incremental_committer_test() {
+        let (circ, witness, public) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup_hiding(&srs, &circ);
+        let local = Pl::prove_hiding(&pk, &circ, &witness, &mut ark_std::test_rng());
+        let mut local_bytes = Vec::new();
+        local.serialize(&mut local_bytes).unwrap();
+        let committer = incremental::IncrementalCommitter::<E>::for_wires(&circ);
+        let n = circ.domains.gates.size();
+        for changed in vec![n, 0] {
+            let rng = &mut ark_std::test_rng();
+            let mut prv = Prover::<F, PC>::new(&pk, rng);
+            prv.hiding_commitments(HIDING_BOUND);
+            prv.committer_backend(&committer);
+            let pf = prv.prove(&circ, &witness);
+            assert_eq!(committer.changed("p_0"), Some(changed));
+            let mut bytes = Vec::new();
+            pf.serialize(&mut bytes).unwrap();
+            assert_eq!(bytes, local_bytes);
+            Pl::verify(&vk, &circ, pf, &public);
+        }
+    }
+
+    #[test]
+    fn incremental_commitment_update_test() {
+        let (circ, _, _) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, _) = Pl::circuit_setup_hiding(&srs, &circ);
+        let dom = circ.domains.gates;
+        let committer = incremental::IncrementalCommitter::<E>::new(dom, vec!["p".to_owned()]);
+        let commit = |backend: &dyn CommitterBackend<F, PC>, evals: &[F]| {
+            let p = DensePolynomial::from_coefficients_vec(dom.ifft(evals));
+            let p = LabeledPolynomial::new("p".to_owned(), p, Some(dom.size() - 1), Some(1));
+            let (cmt, _) = backend
+                .commit(&pk.pc_ck, &p, Some(&mut ark_std::test_rng()))
+                .unwrap();
+            *cmt.commitment()
+        };
+        let mut evals: Vec<F> = (0..dom.size() as u64).map(F::from).collect();
+        assert_eq!(commit(&committer, &evals), commit(&LocalCommitter, &evals));
+        evals[1] += F::from(1u64);
+        evals[3] = F::from(0u64);
+        assert_eq!(commit(&committer, &evals), commit(&LocalCommitter, &evals));
+        assert_eq!(committer.changed("p"), Some(2));
+        committer.clear();
+        assert_eq!(commit(&committer, &evals), commit(&LocalCommitter, &evals));
+        assert_eq!(committer.changed("p"), Some(dom.size()));
+    }
+
+    /// Computes in process, counting its FFTs and MSMs.
+    #[derive(Default)]
+    struct CountingBackend {
+        ffts: std::sync::atomic::AtomicUsize,
+        coset_ffts: std::sync::atomic::AtomicUsize,
+        msms: std::sync::atomic::AtomicUsize,
+    }
+
+    impl ArithmeticBackend<F> for CountingBackend {
+        fn fft_in_place(&self, domain: Radix2EvaluationDomain<F>, coeffs: &mut Vec<F>) {
+            self.ffts.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            CpuBackend.fft_in_place(domain, coeffs)
+        }
+
+        fn ifft_in_place(&self, domain: Radix2EvaluationDomain<F>, evals: &mut Vec<F>) {
+            self.ffts.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            CpuBackend.ifft_in_place(domain, evals)
+        }
+
+        fn coset_fft_in_place(&self, domain: Radix2EvaluationDomain<F>, coeffs: &mut Vec<F>) {
+            self.coset_ffts
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            CpuBackend.coset_fft_in_place(domain, coeffs)
+        }
+
+        fn coset_ifft_in_place(&self, domain: Radix2EvaluationDomain<F>, evals: &mut Vec<F>) {
+            self.coset_ffts
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            CpuBackend.coset_ifft_in_place(domain, evals)
+        }
+
+        fn msm<G: ark_ec::AffineCurve<ScalarField = F>>(
+            &self,
+            bases: &[G],
+            scalars: &[F],
+        ) -> G::Projective {
+            self.msms.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            CpuBackend.msm(bases, scalars)
+        }
+    }
+
+    #[test]
+    fn plonk_arithmetic_backend_test() {
+        let (circ, witness, public) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup_hiding(&srs, &circ);
+        let local = Pl::prove_hiding(&pk, &circ, &witness, &mut ark_std::test_rng());
+        let mut local_bytes = Vec::new();
+        local.serialize(&mut local_bytes).unwrap();
+        let backend = CountingBackend::default();
+        let committer = ArithmeticCommitter::new(CountingBackend::default());
+        let rng = &mut ark_std::test_rng();
+        let mut prv = Prover::<F, PC>::new(&pk, rng);
+        prv.hiding_commitments(HIDING_BOUND);
+        prv.arithmetic_backend(&backend);
+        prv.committer_backend(&committer);
+        let pf = prv.prove(&circ, &witness);
+        assert!(backend.ffts.into_inner() > 0);
+        assert!(backend.coset_ffts.into_inner() > 0);
+        assert!(
+            committer
+                .backend()
+                .msms
+                .load(std::sync::atomic::Ordering::SeqCst)
+                > 0
+        );
+        let mut bytes = Vec::new();
+        pf.serialize(&mut bytes).unwrap();
+        assert_eq!(bytes, local_bytes);
+        Pl::verify(&vk, &circ, pf, &public);
+    }
+
+    fn small_proof() -> (Vk, Instance<F>, SmallPf, HashMap<String, F>) {
+        let (circ, witness, public) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, &circ);
+        let pf = Pl::prove_small(&pk, &circ, &witness, &mut ark_std::test_rng());
+        (vk, circ, pf, public)
+    }
+
+    #[test]
+    fn plonk_small_proof_test() {
+        let (vk, v_circ, pf, public) = small_proof();
+        let (_, _, full_pf, _) = transcript_proof::<Blake2sTranscript>();
+        assert!(pf.serialized_size() < full_pf.serialized_size());
+        let mut bytes = Vec::new();
+        pf.serialize(&mut bytes).unwrap();
+        let pf = SmallPf::deserialize(&bytes[..]).unwrap();
+        Pl::verify_small(&vk, &v_circ, pf, &public);
+    }
+
+    #[test]
+    #[should_panic(expected = "quotient identity failed")]
+    fn plonk_small_proof_wrong_public_test() {
+        let (vk, v_circ, pf, mut public) = small_proof();
+        public.insert("out".to_owned(), F::from(1u64));
+        Pl::verify_small(&vk, &v_circ, pf, &public);
+    }
+
+    #[test]
+    fn plonk_small_proof_report_test() {
+        let (vk, v_circ, mut pf, public) = small_proof();
+        pf.q_opens.pop();
+        let report = Pl::verify_small_with_report(&vk, &v_circ, pf, &public).unwrap_err();
+        assert_eq!(
+            report.failures,
+            vec![Failure::QuotientChunks {
+                expected: 3,
+                commitments: 3,
+                openings: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn plonk_sub_verifiers_test() {
+        let (vk, v_circ, pf, public) = transcript_proof::<Blake2sTranscript>();
+        // A transcript that starts where the Plonk verifier's does
+        let transcript: Blake2sTranscript =
+            transcript_init(&vk.circuit_digest, None, &Session::default());
+        let ver = Verifier::<F, PC>::with_transcript(&vk, transcript);
+        let d = v_circ.domains.gate_degree();
+        let p = ver.recv_columns("p", pf.p_cmts, Some(d));
+        ver.verify_public(&p, &v_circ, pf.public, &public);
+        ver.verify_gates(&p, &v_circ, pf.gates);
+        ver.verify_wiring(&p, &v_circ, pf.wiring);
+        let _: F = ver.into_transcript().challenge(b"next");
+    }
+
+    #[test]
+    fn plonk_wide_layout_test() {
+        let (_, _, public) = squaring_instance();
+        let c = PlonkCircuit::<F>::new_squaring_circuit(4, Some(F::from(2u64)));
+        let circ = Instance::from_circuit_with_width(&c, 5);
+        let witness = Witness::from_circuit(&circ, &c);
+        let srs = Pl::universal_setup_with_width(4, 5, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, &circ);
+        let pf = Pl::prove(&pk, &circ, &witness, &mut ark_std::test_rng());
+        assert_eq!(pf.p_cmts.len(), 5);
+        Pl::verify(&vk, &circ, pf, &public);
+        let pf = Pl::prove_small(&pk, &circ, &witness, &mut ark_std::test_rng());
+        assert_eq!(pf.q_cmts.len(), 5);
+        Pl::verify_small(&vk, &circ, pf, &public);
+    }
+
+    /// A circuit of five gates, padded to eight by its layout
+    #[test]
+    fn plonk_unpadded_test() {
+        let c = PlonkCircuit::<F>::unpadded_squaring_circuit(5);
+        let inputs: Public = vec![("start".to_owned(), F::from(2u64))]
+            .into_iter()
+            .collect();
+        let assignment = assign(&c, &inputs).unwrap();
+        let c = c.with_assignment(assignment);
+        let circ = Instance::from_circuit(&c);
+        assert_eq!((circ.n_gates, circ.padded_gates()), (5, 8));
+        let witness = Witness::from_circuit(&circ, &c);
+        let out = (0..5).fold(F::from(2u64), |a, _| a * a);
+        let public: Public = vec![("out".to_owned(), out)].into_iter().collect();
+        let srs = Pl::universal_setup(8, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, &circ);
+        let pf = Pl::prove(&pk, &circ, &witness, &mut ark_std::test_rng());
+        Pl::verify(&vk, &circ, pf, &public);
+        let pf = Pl::prove_small(&pk, &circ, &witness, &mut ark_std::test_rng());
+        Pl::verify_small(&vk, &circ, pf, &public);
+    }
+
+    /// A copy constraint across columns binds: scaling a product gate's left input up and its
+    /// right input down keeps the gate, but breaks the wiring permutation's cycles.
+    #[test]
+    fn plonk_broken_copy_test() {
+        let (_, _, public) = squaring_instance();
+        let c = PlonkCircuit::<F>::new_squaring_circuit(4, Some(F::from(2u64)));
+        let circ = Instance::from_circuit_with_width(&c, 4);
+        let mut p = Witness::from_circuit(&circ, &c).polys(&circ);
+        let gates = circ.domains.gates;
+        let s = circ.s.evaluate_over_domain_by_ref(gates).evals;
+        let mut evals: Vec<_> = p
+            .iter()
+            .map(|p_j| p_j.evaluate_over_domain_by_ref(gates).evals)
+            .collect();
+        let g = (0..gates.size())
+            .find(|&g| s[g].is_zero() && !evals[0][g].is_zero())
+            .unwrap();
+        let two = F::from(2u64);
+        evals[0][g] *= two;
+        evals[1][g] *= two.inverse().unwrap();
+        for (p_j, e) in p.iter_mut().zip(evals) {
+            *p_j = Evaluations::from_vec_and_domain(e, gates).interpolate();
+        }
+        let srs = Pl::universal_setup_with_width(4, 4, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, &circ);
+        let pf = Pl::prove(&pk, &circ, &Witness::Polys(p), &mut ark_std::test_rng());
+        let report = Pl::verify_with_report(&vk, &circ, pf, &public, None).unwrap_err();
+        let identities: Vec<_> = report
+            .failures
+            .iter()
+            .map(|failure| match failure {
+                Failure::Identity { identity, .. } => *identity,
+                failure => panic!("Unexpected failure: {:?}", failure),
+            })
+            .collect();
+        // L_1 is still the fraction's running product, but it no longer ends at one
+        assert_eq!(identities, ["partial product", "total product"]);
+        assert_eq!(report.abort().code, AbortCode::ConstraintUnsatisfied);
+    }
+
+    /// The squaring circuit, in three steps rather than four: as many gates, wired differently.
+    fn other_squaring_layout() -> Instance<F> {
+        Instance::from_circuit(&PlonkCircuit::<F>::new_squaring_circuit(3, None))
+    }
+
+    #[test]
+    fn plonk_wrong_circuit_test() {
+        let (vk, v_circ, pf, public) = transcript_proof::<Blake2sTranscript>();
+        let other = other_squaring_layout();
+        assert_eq!(other.domains.gates, v_circ.domains.gates);
+        assert_eq!(vk.circuit_digest, v_circ.digest());
+        let report = Pl::verify_with_report(&vk, &other, pf, &public, None).unwrap_err();
+        assert_eq!(
+            report.failures,
+            vec![Failure::Circuit {
+                expected: v_circ.digest(),
+                found: other.digest(),
+            }]
+        );
+        assert_eq!(report.abort().code, AbortCode::SrsMismatch);
+    }
+
+    #[test]
+    fn plonk_prove_wrong_circuit_test() {
+        let (circ, witness, _) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, _) = Pl::circuit_setup(&srs, &circ);
+        let rng = &mut ark_std::test_rng();
+        let abort = match Pl::try_prove(&pk, &other_squaring_layout(), &witness, rng) {
+            Ok(_) => panic!("Proved with the wrong circuit"),
+            Err(abort) => abort,
+        };
+        assert_eq!(abort.code, AbortCode::SrsMismatch);
+        assert!(abort
+            .detail
+            .starts_with("Circuit layout differs from the prover key's"));
+    }
+
+    #[test]
+    fn validate_verifier_key_test() {
+        let (circ, _, _) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (_, vk) = Pl::circuit_setup(&srs, &circ);
+        let (ck, pc_vk) = trim_for_circuit::<F, PC>(&srs, &circ);
+        vk.validate::<F, PC>(&ck, &pc_vk, &circ).unwrap();
+
+        // An indexer that swaps commitments, keeping the digest
+        let mut tampered = vk.clone();
+        tampered.s_cmt = LabeledCommitment::new("s".into(), *tampered.w_cmts[0].commitment(), None);
+        assert_eq!(
+            tampered.validate::<F, PC>(&ck, &pc_vk, &circ),
+            Err(KeyError::Commitment("s".into()))
+        );
+        tampered = vk.clone();
+        tampered.w_cmts.swap(0, 1);
+        assert_eq!(
+            tampered.validate::<F, PC>(&ck, &pc_vk, &circ),
+            Err(KeyError::Commitment("w_0".into()))
+        );
+        tampered = vk.clone();
+        tampered.w_cmts.pop();
+        assert!(matches!(
+            tampered.validate::<F, PC>(&ck, &pc_vk, &circ),
+            Err(KeyError::Columns { .. })
+        ));
+
+        let other = other_squaring_layout();
+        assert_eq!(
+            vk.validate::<F, PC>(&ck, &pc_vk, &other),
+            Err(KeyError::Circuit {
+                expected: other.digest(),
+                found: circ.digest(),
+            })
+        );
+        // Keys from another SRS
+        let other_srs = Pl::universal_setup(4, &mut ChaChaRng::seed_from_u64(1));
+        let (_, other_vk) = Pl::circuit_setup(&other_srs, &circ);
+        assert_eq!(
+            other_vk.validate::<F, PC>(&ck, &pc_vk, &circ),
+            Err(KeyError::Commitment("w_0".into()))
+        );
+    }
+
+    #[test]
+    fn plonk_wrong_width_test() {
+        let (vk, v_circ, mut pf, public) = transcript_proof::<Blake2sTranscript>();
+        pf.p_cmts.pop();
+        let report = Pl::verify_with_report(&vk, &v_circ, pf, &public, None).unwrap_err();
+        assert_eq!(
+            report.failures,
+            vec![Failure::Columns {
+                what: "P commitments",
+                expected: 3,
+                found: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn plonk_setup_many_test() {
+        let instances: Vec<_> = [2usize, 4, 8]
+            .iter()
+            .map(|steps| {
+                let start = F::from(2u64);
+                let c = PlonkCircuit::<F>::new_squaring_circuit(*steps, Some(start));
+                let res = (0..*steps).fold(start, |a, _| a * a);
+                let public: Public = vec![("out".to_owned(), res)].into_iter().collect();
+                let circ = Instance::from_circuit(&c);
+                let witness = Witness::from_circuit(&circ, &c);
+                (circ, witness, public)
+            })
+            .collect();
+        let circs: Vec<_> = instances.iter().map(|(c, _, _)| c.clone()).collect();
+        let srs = Pl::universal_setup(8, &mut ark_std::test_rng());
+        let keys = Pl::circuit_setup_many(&srs, &circs);
+        for ((circ, witness, public), (pk, vk)) in instances.iter().zip(&keys) {
+            let pf = Pl::prove(pk, circ, witness, &mut ark_std::test_rng());
+            Pl::verify(vk, circ, pf, public);
+        }
+    }
+
+    /// `x^3 + 3x + 5 = out`, with `out` public
+    struct CubicCircuit {
+        x: Option<F>,
+    }
+
+    impl ConstraintSynthesizer<F> for CubicCircuit {
+        fn generate_constraints(
+            self,
+            cs: ark_relations::r1cs::ConstraintSystemRef<F>,
+        ) -> std::result::Result<(), SynthesisError> {
+            use ark_relations::{lc, r1cs::Variable};
+            let x_val = self.x;
+            let x2_val = x_val.map(|x| x * x);
+            let out_val = x_val.map(|x| x * x * x + x * F::from(3u64) + F::from(5u64));
+            let missing = || SynthesisError::AssignmentMissing;
+            let out = cs.new_input_variable(|| out_val.ok_or_else(missing))?;
+            let x = cs.new_witness_variable(|| x_val.ok_or_else(missing))?;
+            let x2 = cs.new_witness_variable(|| x2_val.ok_or_else(missing))?;
+            cs.enforce_constraint(lc!() + x, lc!() + x, lc!() + x2)?;
+            cs.enforce_constraint(
+                lc!() + x2 + (F::from(3u64), Variable::One),
+                lc!() + x,
+                lc!() + out - (F::from(5u64), Variable::One),
+            )?;
+            Ok(())
+        }
+    }
+
+    fn r1cs_proof() -> (Vk, Instance<F>, Pf, Vec<F>, relations::r1cs::R1csPlonk<F>) {
+        let setup = relations::r1cs::R1csPlonk::from_synthesizer(CubicCircuit { x: None }, false)
+            .unwrap();
+        let v_circ = Instance::from_circuit(&setup.circuit);
+        let srs = Pl::universal_setup(setup.circuit.n_gates(), &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, &v_circ);
+        let c = CubicCircuit {
+            x: Some(F::from(3u64)),
+        };
+        let (pf, inputs) = Pl::prove_synthesizer(&pk, c, &mut ark_std::test_rng()).unwrap();
+        (vk, v_circ, pf, inputs, setup)
+    }
+
+    #[test]
+    fn plonk_r1cs_test() {
+        let (vk, v_circ, pf, inputs, setup) = r1cs_proof();
+        assert_eq!(inputs, vec![F::from(41u64)]);
+        Pl::verify(&vk, &v_circ, pf, &setup.public_inputs(&inputs));
+    }
+
+    #[test]
+    #[should_panic]
+    fn plonk_r1cs_wrong_input_test() {
+        let (vk, v_circ, pf, _, setup) = r1cs_proof();
+        Pl::verify(&vk, &v_circ, pf, &setup.public_inputs(&[F::from(40u64)]));
+    }
+
+    /// Proofs of the squaring circuit, from several starting values.
+    fn aggregation_proofs() -> (Vk, Instance<F>, Vec<Pf>, Vec<Public>) {
+        let steps = 4;
+        let v_circ = Instance::from_circuit(&PlonkCircuit::<F>::new_squaring_circuit(steps, None));
+        let srs = Pl::universal_setup(steps, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, &v_circ);
+        let rng = &mut ark_std::test_rng();
+        let (pfs, publics) = (2..5u64)
+            .map(|start| {
+                let start = F::from(start);
+                let c = PlonkCircuit::<F>::new_squaring_circuit(steps, Some(start));
+                let res = (0..steps).fold(start, |a, _| a * a);
+                let public: HashMap<String, F> =
+                    vec![("out".to_owned(), res)].into_iter().collect();
+                let witness = Witness::from_circuit(&v_circ, &c);
+                (Pl::prove(&pk, &v_circ, &witness, rng), public)
+            })
+            .unzip();
+        (vk, v_circ, pfs, publics)
+    }
+
+    #[test]
+    fn plonk_aggregate_test() {
+        let (vk, v_circ, pfs, publics) = aggregation_proofs();
+        let agg = Pl::aggregate(&pfs);
+        assert_eq!(agg.openings.len(), pfs.len());
+        let mut bytes = Vec::new();
+        agg.serialize(&mut bytes).unwrap();
+        let agg = CanonicalDeserialize::deserialize(&bytes[..]).unwrap();
+        Pl::verify_aggregated(&vk, &v_circ, agg, &publics);
+    }
+
+    #[test]
+    #[should_panic(expected = "Aggregated verification failed")]
+    fn plonk_aggregate_bad_opening_test() {
+        let (vk, v_circ, pfs, publics) = aggregation_proofs();
+        let mut agg = Pl::aggregate(&pfs);
+        agg.openings[1].swap(0, 1);
+        Pl::verify_aggregated(&vk, &v_circ, agg, &publics);
+    }
+
+    #[test]
+    #[should_panic(expected = "Aggregated verification failed")]
+    fn plonk_aggregate_reordered_test() {
+        let (vk, v_circ, pfs, mut publics) = aggregation_proofs();
+        let mut agg = Pl::aggregate(&pfs);
+        agg.proofs.swap(0, 1);
+        publics.swap(0, 1);
+        Pl::verify_aggregated(&vk, &v_circ, agg, &publics);
+    }
+
+    #[test]
+    fn plonk_aggregate_malformed_test() {
+        let (vk, v_circ, pfs, publics) = aggregation_proofs();
+        let failures = |agg| {
+            Pl::verify_aggregated_with_report(&vk, &v_circ, agg, &publics)
+                .unwrap_err()
+                .failures
+        };
+        let mut agg = Pl::aggregate(&pfs);
+        agg.proofs[1].p_cmts[0].shifted_comm = None;
+        // The proof's other checks fail too: its transcript differs
+        assert!(matches!(
+            failures(agg)[..],
+            [Failure::DegreeBound { bound: 3, .. }, ..]
+        ));
+        let mut agg = Pl::aggregate(&pfs);
+        agg.proofs[1].gates.p_values.pop();
+        assert!(matches!(
+            failures(agg)[..],
+            [Failure::Columns { .. }, ..]
+        ));
+        let mut agg = Pl::aggregate(&pfs);
+        agg.openings[1].pop();
+        assert!(matches!(
+            failures(agg)[..],
+            [Failure::Aggregated { what: "openings", .. }]
+        ));
+        let mut agg = Pl::aggregate(&pfs);
+        agg.openings.pop();
+        assert!(matches!(
+            failures(agg)[..],
+            [Failure::Aggregated { what: "proofs' openings", .. }]
+        ));
+    }
+
+    #[test]
+    fn plonk_test() {
+        let steps = 4;
+        let start = F::from(2u64);
+        let c = PlonkCircuit::<F>::new_squaring_circuit(steps, Some(start));
+        let res = (0..steps).fold(start, |a, _| a * a);
+        let public: HashMap<String, F> = vec![("out".to_owned(), res)].into_iter().collect();
+        let circ = Instance::from_circuit(&c);
+        let witness = Witness::from_circuit(&circ, &c);
+
+        let setup_rng = &mut ark_std::test_rng();
+        let zk_rng = &mut ark_std::test_rng();
+
+        let srs = Pl::universal_setup(steps, setup_rng);
+        let (pk, vk) = Pl::circuit_setup(&srs, &circ);
+        let pf = Pl::prove(&pk, &circ, &witness, zk_rng);
+        Pl::verify(&vk, &circ, pf, &public);
+    }
+
+    type MF = mpc_algebra::honest_but_curious::MpcField<F>;
+    type ME = mpc_algebra::honest_but_curious::MpcPairingEngine<E>;
+    type MpcPC = ark_poly_commit::marlin::marlin_pc::MarlinKZG10<ME, DensePolynomial<MF>>;
+    type MpcPl = Plonk<MF, MpcPC>;
+
+    /// Prove the squaring circuit collaboratively, as each of `parties` parties simulated in
+    /// threads (see [MpcMultiNet::simulate]), with party 0 holding the input. With `prss`, each
+    /// party draws its masks from PRSS keys of its own. Produces what `after` makes of each
+    /// party's revealed proof, and of what the party exchanged to prove it (before revealing it).
+    pub(crate) fn simulated_proof<T: Send>(
+        parties: usize,
+        prss: bool,
+        after: impl Fn(Pf, mpc_net::Stats) -> T + Sync,
+    ) -> (Vk, Instance<F>, Vec<T>, Public) {
+        use mpc_algebra::Reveal;
+        let (circ, _, public) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, &circ);
+        // Each party reads its own copy, as if from a file
+        let mut pk_bytes = Vec::new();
+        pk.serialize(&mut pk_bytes).unwrap();
+        let results = MpcMultiNet::simulate(parties, |id| {
+            if prss {
+                mpc_algebra::prss::install(mpc_algebra::prss::Prss::setup(1));
+            }
+            let start = MF::from_add_shared(if id == 0 { F::from(2u64) } else { F::zero() });
+            let c = PlonkCircuit::<MF>::new_squaring_circuit(4, Some(start));
+            let circ = Instance::from_circuit(&c);
+            let witness = Witness::from_circuit(&circ, &c);
+            let pk = ProverKey::from_public(ProverKey::deserialize(&pk_bytes[..]).unwrap());
+            MpcMultiNet::reset_stats();
+            let pf = MpcPl::prove(&pk, &circ, &witness, &mut ark_std::test_rng());
+            let stats = MpcMultiNet::stats();
+            after(pf.reveal(), stats)
+        });
+        (vk, circ, results, public)
+    }
+
+    /// Each of two parties' transcript check, after starting its transcript in the session that
+    /// `nonce` gives it.
+    fn transcript_check(nonce: impl Fn(usize) -> u8 + Sync) -> Vec<Result<(), AbortCode>> {
+        use mpc_algebra::Reveal;
+        let (circ, _, _) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, _) = Pl::circuit_setup(&srs, &circ);
+        let mut pk_bytes = Vec::new();
+        pk.serialize(&mut pk_bytes).unwrap();
+        MpcMultiNet::simulate(2, |id| {
+            let pk = ProverKey::from_public(ProverKey::deserialize(&pk_bytes[..]).unwrap());
+            let zk_rng = &mut ark_std::test_rng();
+            let session = Session::new(vec![nonce(id)]);
+            let prv = Prover::<MF, MpcPC>::new_in_session(&pk, zk_rng, None, &session);
+            catch_abort(|| prv.check_transcript::<MpcMultiNet>()).map_err(|a| a.code)
+        })
+    }
+
+    #[test]
+    fn transcript_check_test() {
+        assert_eq!(transcript_check(|_| 0), vec![Ok(()); 2]);
+        assert_eq!(
+            transcript_check(|id| id as u8),
+            vec![Err(AbortCode::TranscriptDivergence); 2]
+        );
+    }
+
+    #[test]
+    fn plonk_simulated_mpc_test() {
+        let (vk, circ, pfs, public) = simulated_proof(3, true, |pf, _| pf);
+        for pf in pfs {
+            Pl::verify(&vk, &circ, pf, &public);
+        }
+    }
+
+    #[test]
+    fn mpc_prover_leaks_nothing() {
+        use mpc_algebra::taint::{set_taint_mode, TaintMode};
+        // A shared value made public outside a commitment or an opening would panic
+        set_taint_mode(TaintMode::Panic);
+        let (vk, circ, pfs, public) = simulated_proof(2, false, |pf, _| pf);
+        for pf in pfs {
+            Pl::verify(&vk, &circ, pf, &public);
+        }
+    }
+
+    /// `(a^2 + a)^4 * b`, public as `out`, where the king has `a` and `b` is shared.
+    fn mixed_circuit<G: Field>() -> PlonkCircuit<G> {
+        let mut c = PlonkCircuit::new(false);
+        let a = c.new_input("a".to_owned());
+        let b = c.new_input("b".to_owned());
+        let mut v = c.new_prod(a, a);
+        v = c.new_sum(v, a);
+        for _ in 0..2 {
+            v = c.new_prod(v, v);
+        }
+        let out = c.new_prod(v, b);
+        c.publicize_var(out, "out".to_owned());
+        c.pad_to_power_of_2();
+        c
+    }
+
+    #[test]
+    fn hybrid_mixed_wires_test() {
+        use mpc_algebra::Reveal;
+        let inputs = |name: &str| Some(F::from(if name == "a" { 3u64 } else { 5u64 }));
+        let v_c = mixed_circuit::<F>();
+        let values = assign(&v_c, &inputs).unwrap();
+        let public: Public = v_c
+            .pub_vars
+            .iter()
+            .map(|(v, name)| (name.clone(), values.0[*v as usize]))
+            .collect();
+        let circ = Instance::from_circuit(&v_c);
+        let srs = Pl::universal_setup(circ.domains.gates.size(), &mut ark_std::test_rng());
+        let (pk, vk) = Pl::circuit_setup(&srs, &circ);
+        let mut pk_bytes = Vec::new();
+        pk.serialize(&mut pk_bytes).unwrap();
+        let results = MpcMultiNet::simulate(3, |id| {
+            let c = mixed_circuit::<MF>();
+            let local_inputs = std::iter::once("a".to_owned()).collect();
+            // Only the king knows `a`; party 1 holds `b`
+            let a: HashMap<String, F> = std::iter::once(("a".to_owned(), F::from(3u64))).collect();
+            let king: Option<&dyn relations::structured::Witness<F>> =
+                if id == 0 { Some(&a) } else { None };
+            let b_share = if id == 1 { F::from(5u64) } else { F::zero() };
+            let b = |_: &str| Some(MF::from_add_shared(b_share));
+            let (assignment, local) =
+                assign_hybrid(&c, &local_inputs, king, &b, &mut ark_std::test_rng()).unwrap();
+            let c = c.with_assignment(assignment);
+            let circ = Instance::from_circuit(&c);
+            let witness = Witness::from_circuit(&circ, &c);
+            let rows = LocalRows::new(&circ, &local);
+            let pk = ProverKey::from_public(ProverKey::deserialize(&pk_bytes[..]).unwrap());
+            let shared_pf = MpcPl::prove(&pk, &circ, &witness, &mut ark_std::test_rng());
+            let zk_rng = &mut ark_std::test_rng();
+            let mut prv = Prover::<MF, MpcPC>::new(&pk, zk_rng);
+            prv.local_rows(&rows);
+            let pf = prv.prove(&circ, &witness);
+            (rows.rows.len(), pf.reveal(), shared_pf.reveal())
+        });
+        let n = circ.domains.gates.size();
+        for (local_rows, pf, shared_pf) in results {
+            // Some rows, but not all, are the king's alone
+            assert!(0 < local_rows && local_rows < n);
+            // L_1 is the same, however it was computed
+            assert_eq!(pf.wiring.l1_cmt, shared_pf.wiring.l1_cmt);
+            Pl::verify(&vk, &circ, pf, &public);
+        }
+    }
+
+    #[test]
+    fn simulated_commit_many_test() {
+        use mpc_algebra::Reveal;
+        let (circ, _, _) = squaring_instance();
+        let srs = Pl::universal_setup(4, &mut ark_std::test_rng());
+        let (pk, _) = Pl::circuit_setup(&srs, &circ);
+        let k = circ.domains.gates.size();
+        let polys: Vec<P> = (0..3)
+            .map(|_| DensePolynomial::rand(k - 1, &mut ark_std::test_rng()))
+            .collect();
+        // The last has a degree bound, so its shift is committed in the same batch
+        let bound = |i: usize| if i == 2 { Some(k - 1) } else { None };
+        let ps: Vec<LabeledPolynomial<F, P>> = polys
+            .iter()
+            .enumerate()
+            .map(|(i, p)| LabeledPolynomial::new(format!("p_{}", i), p.clone(), bound(i), None))
+            .collect();
+        let refs: Vec<_> = ps.iter().collect();
+        let local = CommitterBackend::<F, PC>::commit_many(&LocalCommitter, &pk.pc_ck, &refs, None)
+            .unwrap();
+        let mut pk_bytes = Vec::new();
+        pk.serialize(&mut pk_bytes).unwrap();
+        let results = MpcMultiNet::simulate(3, |id| {
+            let pk = ProverKey::from_public(ProverKey::deserialize(&pk_bytes[..]).unwrap());
+            // Party 0 holds the coefficients
+            let ps: Vec<LabeledPolynomial<MF, DensePolynomial<MF>>> = polys
+                .iter()
+                .enumerate()
+                .map(|(i, p)| {
+                    let coeffs = p
+                        .coeffs
+                        .iter()
+                        .map(|c| MF::from_add_shared(if id == 0 { *c } else { F::zero() }));
+                    let p = DensePolynomial::from_coefficients_vec(coeffs.collect());
+                    LabeledPolynomial::new(format!("p_{}", i), p, bound(i), None)
+                })
+                .collect();
+            let refs: Vec<_> = ps.iter().collect();
+            let cmts =
+                CommitterBackend::<MF, MpcPC>::commit_many(&LocalCommitter, &pk.pc_ck, &refs, None)
+                    .unwrap();
+            let cmts: Vec<_> = cmts.into_iter().map(|(c, _)| *c.commitment()).collect();
+            let cmts = defer::enqueue(cmts);
+            MpcMultiNet::reset_stats();
+            defer::flush();
+            let broadcasts = MpcMultiNet::stats().broadcasts;
+            (cmts.get().reveal(), broadcasts)
+        });
+        for (cmts, broadcasts) in results {
+            // One exchange of every party's shares of every commitment
+            assert_eq!(broadcasts, 1);
+            let expected: Vec<_> = local.iter().map(|(c, _)| *c.commitment()).collect();
+            assert_eq!(cmts, expected);
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, MpcWire, mpc_trait::Reveal)]
+    enum Wires<T> {
+        Pair(T, Vec<T>),
+        Named { x: Option<T> },
+        Empty,
+    }
+
+    #[test]
+    fn derive_mpc_wire_reveal_test() {
+        use mpc_algebra::Reveal;
+        let wires = vec![
+            Wires::Pair(1u64, vec![2, 3]),
+            Wires::Named { x: Some(4) },
+            Wires::Empty,
+        ];
+        for w in wires {
+            assert!(!w.is_shared());
+            assert_eq!(w.clone().reveal(), w);
+            assert_eq!(Wires::from_public(w.clone()), w);
+        }
+    }
 }
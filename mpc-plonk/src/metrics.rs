@@ -0,0 +1,262 @@
+//! Where a proof's time goes, phase by phase.
+//!
+//! With feature `metrics`, the [Prover](crate::Prover) records, for each [Phase] of its last
+//! proof: wall time, time in FFTs, commitments, and opening proofs, time waiting on the network,
+//! and the bytes and rounds exchanged (see [Prover::metrics](crate::Prover::metrics)). Without it,
+//! the recording compiles to nothing.
+//!
+//! The network figures are from [MpcMultiNet](mpc_net::MpcMultiNet)'s stats, so they are zero
+//! for a local proof, and count whatever else the process exchanges meanwhile.
+use ark_ff::FftField;
+use ark_poly::Radix2EvaluationDomain;
+#[cfg(feature = "metrics")]
+use mpc_net::{MpcMultiNet, MpcNet, Stats};
+#[cfg(feature = "metrics")]
+use std::cell::{Cell, RefCell};
+#[cfg(feature = "metrics")]
+use std::ops::AddAssign;
+#[cfg(feature = "metrics")]
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "metrics")]
+use std::time::{Duration, Instant};
+
+use crate::ArithmeticBackend;
+
+/// A phase of proving. A phase may be entered several times in a proof; its metrics sum over
+/// them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Phase {
+    /// Computing what depends only on the circuit (unless [warmed up](crate::Prover::warmup))
+    Warmup,
+    /// Committing to the wire columns, and revealing the commitments
+    Columns,
+    /// The public-input sub-proof: its quotient, commitment, and openings
+    Public,
+    /// The gate sub-proof
+    Gates,
+    /// The wiring sub-proof, with its unit product
+    Wiring,
+    /// Combining the quotients of a [SmallProof](crate::SmallProof), and opening everything
+    Quotient,
+    /// Making the opened values public
+    Publish,
+}
+
+/// What one [Phase] of a proof cost.
+///
+/// FFTs run on several threads (with feature `parallel`) count the time of each, so `fft` can
+/// exceed `wall`.
+#[cfg(feature = "metrics")]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PhaseMetrics {
+    pub wall: Duration,
+    pub fft: Duration,
+    pub commit: Duration,
+    pub open: Duration,
+    /// Time in network exchanges
+    pub wait: Duration,
+    pub bytes_sent: usize,
+    pub bytes_recv: usize,
+    pub rounds: usize,
+}
+
+#[cfg(feature = "metrics")]
+impl AddAssign<&PhaseMetrics> for PhaseMetrics {
+    fn add_assign(&mut self, other: &PhaseMetrics) {
+        self.wall += other.wall;
+        self.fft += other.fft;
+        self.commit += other.commit;
+        self.open += other.open;
+        self.wait += other.wait;
+        self.bytes_sent += other.bytes_sent;
+        self.bytes_recv += other.bytes_recv;
+        self.rounds += other.rounds;
+    }
+}
+
+/// What each [Phase] of a proof cost, in the order first entered.
+#[cfg(feature = "metrics")]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProverMetrics {
+    pub phases: Vec<(Phase, PhaseMetrics)>,
+}
+
+#[cfg(feature = "metrics")]
+impl ProverMetrics {
+    pub fn phase(&self, phase: Phase) -> Option<&PhaseMetrics> {
+        self.phases
+            .iter()
+            .find(|(p, _)| *p == phase)
+            .map(|(_, m)| m)
+    }
+    /// The whole proof's cost.
+    pub fn total(&self) -> PhaseMetrics {
+        let mut total = PhaseMetrics::default();
+        for (_, m) in &self.phases {
+            total += m;
+        }
+        total
+    }
+    fn add(&mut self, phase: Phase, m: &PhaseMetrics) {
+        match self.phases.iter_mut().find(|(p, _)| *p == phase) {
+            Some((_, total)) => *total += m,
+            None => self.phases.push((phase, m.clone())),
+        }
+    }
+}
+
+/// The phase being recorded: which, since when, and the network stats then.
+#[cfg(feature = "metrics")]
+struct Current {
+    phase: Phase,
+    start: Instant,
+    stats: Stats,
+}
+
+/// Records a prover's [ProverMetrics].
+#[cfg(feature = "metrics")]
+#[derive(Default)]
+pub(crate) struct Recorder {
+    current: RefCell<Option<Current>>,
+    metrics: RefCell<ProverMetrics>,
+    /// Nanoseconds in FFTs in the current phase, from any thread
+    fft: AtomicU64,
+    commit: Cell<Duration>,
+    open: Cell<Duration>,
+}
+
+#[cfg(feature = "metrics")]
+impl Recorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Forget the last proof's metrics.
+    pub fn clear(&self) {
+        self.finish();
+        self.metrics.take();
+    }
+    /// End the current phase, if any, and start `phase`.
+    pub fn enter(&self, phase: Phase) {
+        self.finish();
+        *self.current.borrow_mut() = Some(Current {
+            phase,
+            start: Instant::now(),
+            stats: MpcMultiNet::stats(),
+        });
+    }
+    /// End the current phase, if any.
+    pub fn finish(&self) {
+        let fft = Duration::from_nanos(self.fft.swap(0, Ordering::Relaxed));
+        let (commit, open) = (self.commit.take(), self.open.take());
+        if let Some(c) = self.current.take() {
+            let stats = MpcMultiNet::stats().since(&c.stats);
+            let m = PhaseMetrics {
+                wall: c.start.elapsed(),
+                fft,
+                commit,
+                open,
+                wait: stats.wait,
+                bytes_sent: stats.bytes_sent,
+                bytes_recv: stats.bytes_recv,
+                rounds: stats.rounds(),
+            };
+            self.metrics.borrow_mut().add(c.phase, &m);
+        }
+    }
+    pub fn metrics(&self) -> ProverMetrics {
+        self.metrics.borrow().clone()
+    }
+    pub fn commit<T>(&self, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let t = f();
+        self.commit.set(self.commit.get() + start.elapsed());
+        t
+    }
+    pub fn open<T>(&self, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let t = f();
+        self.open.set(self.open.get() + start.elapsed());
+        t
+    }
+    /// `inner`, counting its time in FFTs.
+    pub fn arithmetic<'a, F: FftField>(
+        &'a self,
+        inner: &'a dyn ArithmeticBackend<F>,
+    ) -> Metered<'a, F> {
+        Metered {
+            inner,
+            fft: &self.fft,
+        }
+    }
+}
+
+/// Computes as its inner backend does, counting the time (with feature `metrics`).
+pub(crate) struct Metered<'a, F: FftField> {
+    inner: &'a dyn ArithmeticBackend<F>,
+    #[cfg(feature = "metrics")]
+    fft: &'a AtomicU64,
+}
+
+impl<F: FftField> Metered<'_, F> {
+    #[cfg(feature = "metrics")]
+    fn timed(&self, f: impl FnOnce()) {
+        let start = Instant::now();
+        f();
+        let nanos = start.elapsed().as_nanos() as u64;
+        self.fft.fetch_add(nanos, Ordering::Relaxed);
+    }
+    #[cfg(not(feature = "metrics"))]
+    #[inline]
+    fn timed(&self, f: impl FnOnce()) {
+        f()
+    }
+}
+
+impl<F: FftField> ArithmeticBackend<F> for Metered<'_, F> {
+    fn fft_in_place(&self, domain: Radix2EvaluationDomain<F>, coeffs: &mut Vec<F>) {
+        self.timed(|| self.inner.fft_in_place(domain, coeffs))
+    }
+    fn ifft_in_place(&self, domain: Radix2EvaluationDomain<F>, evals: &mut Vec<F>) {
+        self.timed(|| self.inner.ifft_in_place(domain, evals))
+    }
+    fn coset_fft_in_place(&self, domain: Radix2EvaluationDomain<F>, coeffs: &mut Vec<F>) {
+        self.timed(|| self.inner.coset_fft_in_place(domain, coeffs))
+    }
+    fn coset_ifft_in_place(&self, domain: Radix2EvaluationDomain<F>, evals: &mut Vec<F>) {
+        self.timed(|| self.inner.coset_ifft_in_place(domain, evals))
+    }
+}
+
+/// Records nothing: feature `metrics` is off.
+#[cfg(not(feature = "metrics"))]
+#[derive(Default)]
+pub(crate) struct Recorder;
+
+#[cfg(not(feature = "metrics"))]
+impl Recorder {
+    #[inline]
+    pub fn new() -> Self {
+        Self
+    }
+    #[inline]
+    pub fn clear(&self) {}
+    #[inline]
+    pub fn enter(&self, _phase: Phase) {}
+    #[inline]
+    pub fn finish(&self) {}
+    #[inline]
+    pub fn commit<T>(&self, f: impl FnOnce() -> T) -> T {
+        f()
+    }
+    #[inline]
+    pub fn open<T>(&self, f: impl FnOnce() -> T) -> T {
+        f()
+    }
+    #[inline]
+    pub fn arithmetic<'a, F: FftField>(
+        &self,
+        inner: &'a dyn ArithmeticBackend<F>,
+    ) -> Metered<'a, F> {
+        Metered { inner }
+    }
+}
@@ -0,0 +1,153 @@
+//! Where a [Proof](crate::Proof)'s committed polynomials are opened.
+//!
+//! Each sub-proof opens its polynomials at a point drawn from the transcript (and at rotations of
+//! it, by the generator of the gate domain). An [OpeningPolicy] picks those points: one per
+//! sub-proof ([PerSubProof], the default), or one for the whole proof ([SinglePoint],
+//! [Rotated]). Sub-proofs that share a point make some of the same openings, which the prover
+//! makes only once; in MPC, each opening is a round.
+//!
+//! Prover and verifier must use the same policy. The policy isn't recorded in the proof.
+use ark_ff::Field;
+use std::collections::HashMap;
+
+use crate::transcript::Transcript;
+
+/// The sub-proofs of a [Proof](crate::Proof), in the order they commit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SubProof {
+    /// Agreement with the public inputs
+    Public,
+    /// Gate constraints
+    Gates,
+    /// Unit product of L_1, within [SubProof::Wiring]
+    UnitProduct,
+    /// Wiring permutation
+    Wiring,
+}
+
+impl SubProof {
+    pub const ALL: [SubProof; 4] = [
+        SubProof::Public,
+        SubProof::Gates,
+        SubProof::UnitProduct,
+        SubProof::Wiring,
+    ];
+}
+
+/// Where a sub-proof opens: `x * w^rotation`, for the challenge `x` labeled `challenge`, and the
+/// gate domain's generator `w`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OpeningPoint {
+    pub challenge: &'static str,
+    pub rotation: i64,
+}
+
+/// Decides where each sub-proof opens.
+///
+/// A challenge is drawn once every sub-proof opening at it has committed, so any policy is sound.
+pub trait OpeningPolicy {
+    fn point(&self, sub_proof: SubProof) -> OpeningPoint;
+}
+
+/// Each sub-proof opens at its own challenge, drawn as soon as it has committed.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PerSubProof;
+
+impl OpeningPolicy for PerSubProof {
+    fn point(&self, sub_proof: SubProof) -> OpeningPoint {
+        let challenge = match sub_proof {
+            SubProof::Public => "public_x",
+            SubProof::Gates => "gates_x",
+            SubProof::UnitProduct => "unit_product_r",
+            SubProof::Wiring => "wiring_x",
+        };
+        OpeningPoint {
+            challenge,
+            rotation: 0,
+        }
+    }
+}
+
+/// Every sub-proof opens at one challenge, drawn once everything is committed.
+///
+/// `P` is opened at that point once, rather than once per sub-proof.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SinglePoint;
+
+impl OpeningPolicy for SinglePoint {
+    fn point(&self, _sub_proof: SubProof) -> OpeningPoint {
+        OpeningPoint {
+            challenge: "opening_x",
+            rotation: 0,
+        }
+    }
+}
+
+/// Like [SinglePoint], but the unit product opens one rotation back, where its opening of L_1 is
+/// the wiring sub-proof's.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Rotated;
+
+impl OpeningPolicy for Rotated {
+    fn point(&self, sub_proof: SubProof) -> OpeningPoint {
+        OpeningPoint {
+            challenge: "opening_x",
+            rotation: if sub_proof == SubProof::UnitProduct {
+                -1
+            } else {
+                0
+            },
+        }
+    }
+}
+
+/// The opening points of one proof, drawn as a policy says.
+pub(crate) struct Points<'a, F: Field> {
+    policy: &'a dyn OpeningPolicy,
+    /// The gate domain's generator
+    w: F,
+    /// Sub-proofs that have yet to commit
+    pending: Vec<SubProof>,
+    drawn: HashMap<&'static str, F>,
+}
+
+impl<'a, F: Field> Points<'a, F> {
+    pub fn new(policy: &'a dyn OpeningPolicy, w: F) -> Self {
+        Self {
+            policy,
+            w,
+            pending: SubProof::ALL.to_vec(),
+            drawn: HashMap::new(),
+        }
+    }
+    /// Record that `sub_proof` has made its commitments, drawing its challenge from `transcript`
+    /// if no other sub-proof opening there still has to commit.
+    pub fn committed<T: Transcript>(&mut self, sub_proof: SubProof, transcript: &mut T) {
+        self.pending.retain(|s| *s != sub_proof);
+        let challenge = self.policy.point(sub_proof).challenge;
+        let policy = self.policy;
+        if !self
+            .pending
+            .iter()
+            .any(|s| policy.point(*s).challenge == challenge)
+        {
+            let x = transcript.challenge::<F>(challenge.as_bytes());
+            self.drawn.insert(challenge, x);
+        }
+    }
+    /// The label and value of `sub_proof`'s challenge, which must have been drawn.
+    pub fn challenge(&self, sub_proof: SubProof) -> (&'static str, F) {
+        let challenge = self.policy.point(sub_proof).challenge;
+        (challenge, self.drawn[challenge])
+    }
+    /// Where `sub_proof` opens.
+    pub fn get(&self, sub_proof: SubProof) -> F {
+        let rotation = self.policy.point(sub_proof).rotation;
+        let w = if rotation < 0 {
+            self.w.inverse().unwrap()
+        } else {
+            self.w
+        };
+        self.challenge(sub_proof).1 * w.pow([rotation.unsigned_abs()])
+    }
+}
@@ -0,0 +1,307 @@
+//! Verifying many proofs of one circuit concurrently, as a service that relays or aggregates the
+//! proofs of many committees must.
+//!
+//! A [VerifierPool] holds a [VerifierSnapshot]: a verifier key, with the layout of its circuit,
+//! checked against each other once. Every proof of a batch is verified against the snapshot that
+//! was current when the batch started, even if [VerifierPool::update] replaces it meanwhile, so a
+//! batch's results all hold under one key; [Verdicts::generation] says which.
+//!
+//! With feature `parallel`, proofs are verified on the pool's own rayon threads, which steal work
+//! from each other, so a slow proof holds up only its own thread. Without it, they are verified
+//! in turn.
+use ark_ff::FftField;
+use ark_poly::univariate::DensePolynomial;
+use ark_poly_commit::PolynomialCommitment;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::{Arc, RwLock};
+
+use crate::relations::flat::Instance;
+use crate::{
+    Blake2sTranscript, KeyError, Plonk, Proof, Transcript, VerificationReport, VerifierKey,
+    Versioned,
+};
+
+/// A proof, as read by [crate::read_versioned], with its public inputs.
+pub type Submission<F, PC> = (
+    Versioned<
+        Proof<
+            F,
+            <PC as PolynomialCommitment<F, DensePolynomial<F>>>::Commitment,
+            <PC as PolynomialCommitment<F, DensePolynomial<F>>>::Proof,
+        >,
+    >,
+    HashMap<String, F>,
+);
+
+/// A verifier key and its circuit's [Instance], as a [VerifierPool] shares them.
+pub struct VerifierSnapshot<F: FftField, PC: PolynomialCommitment<F, DensePolynomial<F>>> {
+    pub vk: VerifierKey<PC::Commitment, PC::VerifierKey>,
+    pub circ: Instance<F>,
+    /// How many snapshots the pool held before this one
+    pub generation: u64,
+}
+
+impl<F: FftField, PC: PolynomialCommitment<F, DensePolynomial<F>>> VerifierSnapshot<F, PC> {
+    /// Check that `vk` is for `circ`'s layout.
+    ///
+    /// This checks only the circuit digest and the column count; a key from an untrusted indexer
+    /// should also be [validated](VerifierKey::validate).
+    fn new(
+        vk: VerifierKey<PC::Commitment, PC::VerifierKey>,
+        circ: Instance<F>,
+        generation: u64,
+    ) -> Result<Self, KeyError> {
+        let digest = circ.digest();
+        if digest != vk.circuit_digest {
+            return Err(KeyError::Circuit {
+                expected: digest,
+                found: vk.circuit_digest,
+            });
+        }
+        if vk.w_cmts.len() != circ.domains.width {
+            return Err(KeyError::Columns {
+                expected: circ.domains.width,
+                found: vk.w_cmts.len(),
+            });
+        }
+        Ok(Self {
+            vk,
+            circ,
+            generation,
+        })
+    }
+}
+
+/// The results of verifying a batch of proofs, in the batch's order.
+pub struct Verdicts<F: FftField> {
+    /// The [VerifierSnapshot::generation] of the key that every proof was verified against
+    pub generation: u64,
+    pub results: Vec<Result<(), VerificationReport<F>>>,
+}
+
+impl<F: FftField> Verdicts<F> {
+    /// Whether every proof verified.
+    pub fn all_valid(&self) -> bool {
+        self.results.iter().all(Result::is_ok)
+    }
+}
+
+/// Verifies proofs of one circuit on several threads, against a key that can be replaced while
+/// they run.
+///
+/// See the [module docs](self).
+pub struct VerifierPool<
+    F: FftField,
+    PC: PolynomialCommitment<F, DensePolynomial<F>>,
+    T: Transcript = Blake2sTranscript,
+> {
+    current: RwLock<Arc<VerifierSnapshot<F, PC>>>,
+    #[cfg(feature = "parallel")]
+    threads: rayon::ThreadPool,
+    _transcript: PhantomData<T>,
+}
+
+impl<F, PC, T> VerifierPool<F, PC, T>
+where
+    F: FftField,
+    PC: PolynomialCommitment<F, DensePolynomial<F>>,
+    PC::Commitment: mpc_trait::MpcWire + Send + Sync,
+    PC::VerifierKey: Send + Sync,
+    PC::Proof: Send,
+    PC::Error: 'static,
+    T: Transcript,
+{
+    /// A pool verifying against `vk`, a key for `circ`, on `threads` threads (with feature
+    /// `parallel`; zero for one per core).
+    pub fn new(
+        vk: VerifierKey<PC::Commitment, PC::VerifierKey>,
+        circ: Instance<F>,
+        threads: usize,
+    ) -> Result<Self, KeyError> {
+        #[cfg(not(feature = "parallel"))]
+        let _ = threads;
+        Ok(Self {
+            current: RwLock::new(Arc::new(VerifierSnapshot::new(vk, circ, 0)?)),
+            #[cfg(feature = "parallel")]
+            threads: rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .expect("Could not start verifier threads"),
+            _transcript: PhantomData,
+        })
+    }
+
+    /// The current snapshot: the one that a batch started now is verified against.
+    pub fn snapshot(&self) -> Arc<VerifierSnapshot<F, PC>> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Verify later batches against `vk`, a key for `circ`, returning its generation. Batches
+    /// already started keep their snapshot.
+    pub fn update(
+        &self,
+        vk: VerifierKey<PC::Commitment, PC::VerifierKey>,
+        circ: Instance<F>,
+    ) -> Result<u64, KeyError> {
+        let mut current = self.current.write().unwrap();
+        let next = VerifierSnapshot::new(vk, circ, current.generation + 1)?;
+        *current = Arc::new(next);
+        Ok(current.generation)
+    }
+
+    /// Verify each proof, with its public inputs, reporting failed checks as
+    /// [Plonk::verify_versioned_with_report] does: a proof in a format version that isn't
+    /// verified is invalid, and the rest of the batch is still verified.
+    pub fn verify_batch(&self, proofs: Vec<Submission<F, PC>>) -> Verdicts<F> {
+        let snapshot = self.snapshot();
+        let verify = |(pf, public)| verify_one::<F, PC, T>(&snapshot, pf, &public);
+        #[cfg(feature = "parallel")]
+        let results = self
+            .threads
+            .install(|| proofs.into_par_iter().map(verify).collect());
+        #[cfg(not(feature = "parallel"))]
+        let results = proofs.into_iter().map(verify).collect();
+        Verdicts {
+            generation: snapshot.generation,
+            results,
+        }
+    }
+
+    /// Verify proofs as `proofs` yields them, passing each result, with the proof's index in
+    /// `proofs`, to `on_result` as soon as it is known (so, out of order). Returns the generation
+    /// of the snapshot that every proof was verified against.
+    pub fn verify_stream<I>(
+        &self,
+        proofs: I,
+        on_result: impl Fn(usize, Result<(), VerificationReport<F>>) + Sync,
+    ) -> u64
+    where
+        I: Iterator<Item = Submission<F, PC>> + Send,
+    {
+        let snapshot = self.snapshot();
+        let verify =
+            |(i, (pf, public))| on_result(i, verify_one::<F, PC, T>(&snapshot, pf, &public));
+        #[cfg(feature = "parallel")]
+        self.threads
+            .install(|| proofs.enumerate().par_bridge().for_each(verify));
+        #[cfg(not(feature = "parallel"))]
+        proofs.enumerate().for_each(verify);
+        snapshot.generation
+    }
+}
+
+fn verify_one<F, PC, T>(
+    snapshot: &VerifierSnapshot<F, PC>,
+    pf: Versioned<Proof<F, PC::Commitment, PC::Proof>>,
+    public: &HashMap<String, F>,
+) -> Result<(), VerificationReport<F>>
+where
+    F: FftField,
+    PC: PolynomialCommitment<F, DensePolynomial<F>>,
+    PC::Commitment: mpc_trait::MpcWire,
+    PC::Error: 'static,
+    T: Transcript,
+{
+    Plonk::<F, PC, T>::verify_versioned_with_report(&snapshot.vk, &snapshot.circ, pf, public, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relations::{flat::Witness, structured::PlonkCircuit};
+    use crate::{Failure, FORMAT_VERSION};
+    use ark_bls12_377::{Bls12_377 as E, Fr as F};
+    use ark_poly_commit::marlin_pc::MarlinKZG10;
+    use std::sync::Mutex;
+
+    type PC = MarlinKZG10<E, DensePolynomial<F>>;
+    type Pl = Plonk<F, PC>;
+    type Vk = VerifierKey<
+        <PC as PolynomialCommitment<F, DensePolynomial<F>>>::Commitment,
+        <PC as PolynomialCommitment<F, DensePolynomial<F>>>::VerifierKey,
+    >;
+
+    /// Proofs of `steps` squarings of 2..2+n, each with its public output, and the verifier key.
+    fn squaring_proofs(steps: usize, n: u64) -> (Vk, Instance<F>, Vec<Submission<F, PC>>) {
+        let rng = &mut ark_std::test_rng();
+        let v_circ = Instance::from_circuit(&PlonkCircuit::<F>::new_squaring_circuit(steps, None));
+        let srs = Pl::universal_setup(steps, rng);
+        let (pk, vk) = Pl::circuit_setup(&srs, &v_circ);
+        let proofs = (2..2 + n)
+            .map(|start| {
+                let start = F::from(start);
+                let c = PlonkCircuit::<F>::new_squaring_circuit(steps, Some(start));
+                let out = (0..steps).fold(start, |a, _| a * a);
+                let pf = Pl::prove(&pk, &v_circ, &Witness::from_circuit(&v_circ, &c), rng);
+                let pf = Versioned {
+                    version: FORMAT_VERSION,
+                    value: pf,
+                };
+                (pf, std::iter::once(("out".to_owned(), out)).collect())
+            })
+            .collect();
+        (vk, v_circ, proofs)
+    }
+
+    #[test]
+    fn verifier_pool_test() {
+        let (vk, circ, mut proofs) = squaring_proofs(4, 6);
+        // A wrong output
+        proofs[3].1.insert("out".to_owned(), F::from(5u64));
+        let pool = VerifierPool::<F, PC>::new(vk, circ, 3).unwrap();
+        let verdicts = pool.verify_batch(proofs.clone());
+        assert_eq!(verdicts.generation, 0);
+        let valid: Vec<bool> = verdicts.results.iter().map(Result::is_ok).collect();
+        assert_eq!(valid, vec![true, true, true, false, true, true]);
+
+        let streamed = Mutex::new(vec![None; proofs.len()]);
+        let generation = pool.verify_stream(proofs.into_iter(), |i, r| {
+            streamed.lock().unwrap()[i] = Some(r.is_ok());
+        });
+        assert_eq!(generation, 0);
+        let streamed: Vec<bool> = streamed
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(Option::unwrap)
+            .collect();
+        assert_eq!(streamed, valid);
+    }
+
+    #[test]
+    fn verifier_pool_version_test() {
+        let (vk, circ, mut proofs) = squaring_proofs(4, 3);
+        proofs[0].0.version = FORMAT_VERSION + 1;
+        proofs[1].0.version = 0;
+        let pool = VerifierPool::<F, PC>::new(vk, circ, 2).unwrap();
+        let verdicts = pool.verify_batch(proofs);
+        for (result, version) in verdicts.results[..2].iter().zip([FORMAT_VERSION + 1, 0]) {
+            let failures = &result.as_ref().unwrap_err().failures;
+            assert_eq!(failures, &[Failure::Version { version }]);
+        }
+        assert!(verdicts.results[2].is_ok());
+    }
+
+    #[test]
+    fn verifier_pool_update_test() {
+        let (vk4, circ4, proofs4) = squaring_proofs(4, 2);
+        let (vk8, circ8, proofs8) = squaring_proofs(8, 2);
+        assert!(matches!(
+            VerifierPool::<F, PC>::new(vk4.clone(), circ8.clone(), 2),
+            Err(KeyError::Circuit { .. })
+        ));
+        let pool = VerifierPool::<F, PC>::new(vk4, circ4, 2).unwrap();
+        // A batch keeps the snapshot it started with
+        let before = pool.snapshot();
+        assert_eq!(pool.update(vk8, circ8).unwrap(), 1);
+        assert_eq!(before.generation, 0);
+        let verdicts = pool.verify_batch(proofs8);
+        assert_eq!(verdicts.generation, 1);
+        assert!(verdicts.all_valid());
+        let verdicts = pool.verify_batch(proofs4);
+        assert!(verdicts.results.iter().all(Result::is_err));
+    }
+}
@@ -0,0 +1,392 @@
+//! Universal parameters from a Powers of Tau ceremony, rather than from [Plonk::universal_setup]
+//! (whose trapdoor is known to whoever ran it).
+//!
+//! Reads the `.ptau` files of snarkjs and the perpetual Powers of Tau ceremony: for BN254
+//! (`bn128`, in snarkjs) or BLS12-381 (`bls12381`). A file holds sections, each a type (`u32`),
+//! a length (`u64`) and its contents, all little-endian. We read:
+//!
+//! 1. the header: the size of a base field element in bytes, the base field's modulus, and the
+//!    power `p` of the ceremony;
+//! 2. `tau^i G1`, for `i < 2^{p+1} - 1`;
+//! 3. `tau^i G2`, for `i < 2^p`;
+//! 4. `alpha tau^i G1`, for `i < 2^p`.
+//!
+//! Points are affine, with coordinates in Montgomery form; the point at infinity is all zeros.
+//! No point we read may be infinity: tau and alpha are never zero.
+//! The powers of `alpha tau^i G1` serve as KZG's hiding powers `gamma beta^i G1`: like tau,
+//! alpha is the ceremony's secret.
+//!
+//! Every point is checked to be on its curve and in its prime-order subgroup, and the power to
+//! count its points in a `usize`. The ceremony's contributions are not checked: snarkjs's
+//! `powersoftau verify` does that.
+//!
+//! [Plonk::universal_setup]: crate::Plonk::universal_setup
+use ark_ec::{
+    models::{short_weierstrass_jacobian::GroupAffine, SWModelParameters},
+    PairingEngine,
+};
+use ark_ff::{BigInteger, Field, FpParameters, FromBytes, PrimeField, Zero};
+use ark_poly_commit::kzg10::UniversalParams;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+use thiserror::Error;
+
+use crate::sections::{hex, read_preamble, read_sections, read_u32};
+
+const MAGIC: &[u8; 4] = b"ptau";
+const HEADER: u32 = 1;
+const TAU_G1: u32 = 2;
+const TAU_G2: u32 = 3;
+const ALPHA_TAU_G1: u32 = 4;
+
+#[derive(Error, Debug)]
+pub enum PtauError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Not a .ptau file")]
+    NotPtau,
+    #[error("Unsupported .ptau version {0}")]
+    UnsupportedVersion(u32),
+    #[error("No section {0}, or it comes before the header")]
+    MissingSection(u32),
+    #[error("Parameters are for a different curve: base field modulus {found}, not {expected}")]
+    Curve { expected: String, found: String },
+    #[error("Parameters support degree {max}, not {degree}")]
+    TooSmall { degree: usize, max: usize },
+    #[error("Power {0} has too many points to count")]
+    Power(u32),
+    #[error("Point {index} of section {section} is infinity, or not in the curve's prime-order subgroup")]
+    BadPoint { section: u32, index: usize },
+}
+
+/// Universal parameters for [MarlinKZG10](ark_poly_commit::marlin::marlin_pc::MarlinKZG10)
+/// commitments of degree up to `max_degree`, from a `.ptau` file (see the [module
+/// docs](self)).
+pub fn load_ptau<E, P1, P2>(
+    path: impl AsRef<Path>,
+    max_degree: usize,
+) -> Result<UniversalParams<E>, PtauError>
+where
+    E: PairingEngine<G1Affine = GroupAffine<P1>, G2Affine = GroupAffine<P2>>,
+    P1: SWModelParameters<BaseField = E::Fq>,
+    P2: SWModelParameters<BaseField = E::Fqe>,
+    E::Fqe: Field<BasePrimeField = E::Fq>,
+{
+    read_ptau(BufReader::new(File::open(path)?), max_degree)
+}
+
+/// As [load_ptau], from `reader`.
+pub fn read_ptau<E, P1, P2>(
+    mut reader: impl Read,
+    max_degree: usize,
+) -> Result<UniversalParams<E>, PtauError>
+where
+    E: PairingEngine<G1Affine = GroupAffine<P1>, G2Affine = GroupAffine<P2>>,
+    P1: SWModelParameters<BaseField = E::Fq>,
+    P2: SWModelParameters<BaseField = E::Fqe>,
+    E::Fqe: Field<BasePrimeField = E::Fq>,
+{
+    match read_preamble(&mut reader, MAGIC)? {
+        None => return Err(PtauError::NotPtau),
+        Some(1) => {}
+        Some(version) => return Err(PtauError::UnsupportedVersion(version)),
+    }
+    let mut coords: Option<Coordinates<E::Fq>> = None;
+    // 2^p, for the ceremony's power p
+    let mut n = 0usize;
+    let (mut tau_g1, mut tau_g2, mut alpha_tau_g1) = (None, None, None);
+    read_sections(&mut reader, |section, contents| {
+        match (section, &coords) {
+            (HEADER, _) => {
+                let header = Coordinates::read(contents)?;
+                let power = read_u32(contents)?;
+                // 2^{p+1} - 1 powers of tau in G1, which we must count
+                n = 1usize
+                    .checked_shl(power)
+                    .filter(|n| n.checked_mul(2).is_some())
+                    .ok_or(PtauError::Power(power))?;
+                coords = Some(header);
+            }
+            (TAU_G1, Some(c)) => {
+                // Degrees up to 2^{p+1} - 2
+                let max = 2 * n - 2;
+                if max_degree > max {
+                    return Err(PtauError::TooSmall {
+                        degree: max_degree,
+                        max,
+                    });
+                }
+                tau_g1 =
+                    Some(c.read_points(contents, section, max_degree + 1, |c, r| {
+                        c.read_g1::<P1>(r)
+                    })?);
+            }
+            (TAU_G2, Some(c)) => {
+                tau_g2 = Some(c.read_points(contents, section, 2, |c, r| c.read_g2::<P2>(r))?);
+            }
+            (ALPHA_TAU_G1, Some(c)) => {
+                // As many as [UniversalParams::powers_of_gamma_g] has, if the file has them
+                let n = (max_degree + 2).min(n);
+                alpha_tau_g1 =
+                    Some(c.read_points(contents, section, n, |c, r| c.read_g1::<P1>(r))?);
+            }
+            _ => {}
+        }
+        Ok(())
+    })?;
+    let powers_of_g = tau_g1.ok_or(PtauError::MissingSection(TAU_G1))?;
+    let tau_g2 = tau_g2.ok_or(PtauError::MissingSection(TAU_G2))?;
+    let alpha_tau_g1 = alpha_tau_g1.ok_or(PtauError::MissingSection(ALPHA_TAU_G1))?;
+    let (h, beta_h) = (tau_g2[0], tau_g2[1]);
+    Ok(UniversalParams {
+        powers_of_g,
+        powers_of_gamma_g: alpha_tau_g1.into_iter().enumerate().collect(),
+        h,
+        beta_h,
+        neg_powers_of_h: BTreeMap::new(),
+        prepared_h: h.into(),
+        prepared_beta_h: beta_h.into(),
+    })
+}
+
+/// How a file's coordinates are written: in Montgomery form, over base field `F`.
+struct Coordinates<F: PrimeField> {
+    /// The inverse of the Montgomery constant
+    r_inv: F,
+}
+
+impl<F: PrimeField> Coordinates<F> {
+    /// Read the header's field description, checking that it is `F`'s.
+    fn read(reader: &mut impl Read) -> Result<Self, PtauError> {
+        let n8 = read_u32(reader)? as usize;
+        let mut modulus = vec![0u8; n8];
+        reader.read_exact(&mut modulus)?;
+        let expected = F::Params::MODULUS.to_bytes_le();
+        // Also checks that coordinates have as many bytes as F::BigInt
+        if modulus != expected {
+            return Err(PtauError::Curve {
+                expected: hex(&expected),
+                found: hex(&modulus),
+            });
+        }
+        let r = F::from(2u64).pow([8 * n8 as u64]);
+        Ok(Self {
+            r_inv: r.inverse().unwrap(),
+        })
+    }
+    fn read_fq(&self, reader: &mut impl Read) -> Result<F, PtauError> {
+        let repr = F::BigInt::read(reader)?;
+        // Coordinates are below the modulus, so this only fails on a corrupt file
+        let x = F::from_repr(repr)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Coordinate too large"))?;
+        Ok(x * self.r_inv)
+    }
+    fn read_g1<P: SWModelParameters<BaseField = F>>(
+        &self,
+        reader: &mut impl Read,
+    ) -> Result<Option<GroupAffine<P>>, PtauError> {
+        let (x, y) = (self.read_fq(reader)?, self.read_fq(reader)?);
+        Ok(point(x, y))
+    }
+    fn read_g2<P: SWModelParameters>(
+        &self,
+        reader: &mut impl Read,
+    ) -> Result<Option<GroupAffine<P>>, PtauError>
+    where
+        P::BaseField: Field<BasePrimeField = F>,
+    {
+        let mut fq2 = || -> Result<P::BaseField, PtauError> {
+            let c = [self.read_fq(reader)?, self.read_fq(reader)?];
+            Ok(P::BaseField::from_base_prime_field_elems(&c).unwrap())
+        };
+        let (x, y) = (fq2()?, fq2()?);
+        Ok(point(x, y))
+    }
+    /// The first `n` points of section `section`, each read by `read_point` (which gives `None`
+    /// for infinity, or a point off its curve or outside its subgroup).
+    fn read_points<P: SWModelParameters, R: Read>(
+        &self,
+        reader: &mut R,
+        section: u32,
+        n: usize,
+        read_point: impl Fn(&Self, &mut R) -> Result<Option<GroupAffine<P>>, PtauError>,
+    ) -> Result<Vec<GroupAffine<P>>, PtauError> {
+        (0..n)
+            .map(|index| read_point(self, reader)?.ok_or(PtauError::BadPoint { section, index }))
+            .collect()
+    }
+}
+
+/// The point `(x, y)`, if it's in the prime-order subgroup and not infinity (all zeros).
+fn point<P: SWModelParameters>(x: P::BaseField, y: P::BaseField) -> Option<GroupAffine<P>> {
+    let p = GroupAffine::new(x, y, false);
+    if !(x.is_zero() && y.is_zero())
+        && p.is_on_curve()
+        && p.is_in_correct_subgroup_assuming_on_curve()
+    {
+        Some(p)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relations::{
+        flat::{Instance, Witness},
+        structured::PlonkCircuit,
+    };
+    use crate::{index, trim_for_circuit, Plonk};
+    use ark_bn254::{Bn254, Fq, Fr};
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_ff::{One, ToBytes};
+    use ark_poly::univariate::DensePolynomial;
+    use ark_poly_commit::marlin::marlin_pc::MarlinKZG10;
+    use std::collections::HashMap;
+
+    type PC = MarlinKZG10<Bn254, DensePolynomial<Fr>>;
+    const POWER: u32 = 3;
+
+    fn write_fq(x: Fq, out: &mut Vec<u8>) {
+        let r = Fq::from(2u64).pow([256u64]);
+        (x * r).into_repr().write(&mut *out).unwrap();
+    }
+
+    fn section(kind: u32, contents: Vec<u8>, out: &mut Vec<u8>) {
+        out.extend(&kind.to_le_bytes());
+        out.extend(&(contents.len() as u64).to_le_bytes());
+        out.extend(contents);
+    }
+
+    /// A BN254 `.ptau` file of power [POWER], for `tau` and `alpha`.
+    fn ptau(tau: Fr, alpha: Fr) -> Vec<u8> {
+        let powers = |n: usize, scale: Fr| -> Vec<Fr> {
+            (0..n)
+                .scan(scale, |x, _| {
+                    let y = *x;
+                    *x *= tau;
+                    Some(y)
+                })
+                .collect()
+        };
+        let g1 = |xs: Vec<Fr>| {
+            let mut out = Vec::new();
+            for x in xs {
+                let p = <Bn254 as PairingEngine>::G1Affine::prime_subgroup_generator()
+                    .mul(x)
+                    .into_affine();
+                write_fq(p.x, &mut out);
+                write_fq(p.y, &mut out);
+            }
+            out
+        };
+        let mut out = b"ptau".to_vec();
+        out.extend(&1u32.to_le_bytes());
+        out.extend(&5u32.to_le_bytes());
+        let mut header = 32u32.to_le_bytes().to_vec();
+        header.extend(
+            Fq::characteristic()
+                .iter()
+                .flat_map(|l| l.to_le_bytes().to_vec()),
+        );
+        header.extend(&POWER.to_le_bytes());
+        header.extend(&POWER.to_le_bytes());
+        section(HEADER, header, &mut out);
+        let n = 1 << POWER;
+        section(TAU_G1, g1(powers(2 * n - 1, Fr::one())), &mut out);
+        let mut tau_g2 = Vec::new();
+        for x in powers(n, Fr::one()) {
+            let p = <Bn254 as PairingEngine>::G2Affine::prime_subgroup_generator()
+                .mul(x)
+                .into_affine();
+            for c in &[p.x.c0, p.x.c1, p.y.c0, p.y.c1] {
+                write_fq(*c, &mut tau_g2);
+            }
+        }
+        section(TAU_G2, tau_g2, &mut out);
+        section(ALPHA_TAU_G1, g1(powers(n, alpha)), &mut out);
+        // A section we don't read
+        section(7, vec![0; 10], &mut out);
+        out
+    }
+
+    #[test]
+    fn ptau_prove_test() {
+        let tau = Fr::from(7u64);
+        let file = ptau(tau, Fr::from(11u64));
+        let srs = read_ptau::<Bn254, _, _>(&file[..], 12).unwrap();
+        let g = srs.powers_of_g[0];
+        assert_eq!(srs.powers_of_g.len(), 13);
+        assert_eq!(srs.powers_of_g[3], g.mul(tau * tau * tau).into_affine());
+        assert_eq!(
+            srs.powers_of_gamma_g[&1],
+            g.mul(Fr::from(77u64)).into_affine()
+        );
+
+        let steps = 3;
+        let start = Fr::from(2u64);
+        let c = PlonkCircuit::<Fr>::new_squaring_circuit(steps, Some(start));
+        let circ = Instance::from_circuit(&c);
+        let witness = Witness::from_circuit(&circ, &c);
+        let (ck, vk) = trim_for_circuit::<Fr, PC>(&srs, &circ);
+        let (pk, vk) = index::<Fr, PC>(ck, vk, &circ);
+        let pf = Plonk::<Fr, PC>::prove(&pk, &circ, &witness, &mut ark_std::test_rng());
+        let out = (0..steps).fold(start, |a, _| a * a);
+        let public: HashMap<String, Fr> = vec![("out".to_owned(), out)].into_iter().collect();
+        Plonk::<Fr, PC>::verify(&vk, &circ, pf, &public);
+    }
+
+    #[test]
+    fn ptau_reject_test() {
+        let file = ptau(Fr::from(7u64), Fr::from(11u64));
+        assert!(matches!(
+            read_ptau::<Bn254, _, _>(&file[..], 15),
+            Err(PtauError::TooSmall {
+                degree: 15,
+                max: 14
+            })
+        ));
+        assert!(matches!(
+            read_ptau::<ark_bls12_381::Bls12_381, _, _>(&file[..], 4),
+            Err(PtauError::Curve { .. })
+        ));
+        assert!(matches!(
+            read_ptau::<Bn254, _, _>(&b"ptax"[..], 4),
+            Err(PtauError::NotPtau)
+        ));
+        // Move the second power of tau off the curve: it starts after the file's header (12
+        // bytes), the header section (12 + 44 bytes), the tau G1 section's heading (12 bytes),
+        // and the first power (64 bytes).
+        let mut bad = file.clone();
+        bad[12 + 12 + 44 + 12 + 64] ^= 1;
+        assert!(matches!(
+            read_ptau::<Bn254, _, _>(&bad[..], 4),
+            Err(PtauError::BadPoint {
+                section: TAU_G1,
+                index: 1
+            })
+        ));
+        // Make the second power of tau in G2 infinity: it starts after the tau G1 section (12
+        // bytes, and 15 powers of 64), its heading (12 bytes) and the first power (128 bytes).
+        let mut bad = file.clone();
+        let start = 12 + 12 + 44 + 12 + 15 * 64 + 12 + 128;
+        bad[start..start + 128].iter_mut().for_each(|b| *b = 0);
+        assert!(matches!(
+            read_ptau::<Bn254, _, _>(&bad[..], 4),
+            Err(PtauError::BadPoint {
+                section: TAU_G2,
+                index: 1
+            })
+        ));
+        // Claim a power whose points overflow: it follows the header's field description
+        let mut bad = file;
+        bad[12 + 12 + 4 + 32..][..4].copy_from_slice(&(usize::BITS - 1).to_le_bytes());
+        assert!(matches!(
+            read_ptau::<Bn254, _, _>(&bad[..], 4),
+            Err(PtauError::Power(p)) if p == usize::BITS - 1
+        ));
+    }
+}
@@ -0,0 +1,452 @@
+//! Circuits assembled from blocks of gates, say one block a party, with copy constraints
+//! between the blocks: see [compose].
+//!
+//! The wiring permutation of a layout (see [Instance::from_circuit]) follows its circuit's
+//! variables, so composing comes down to numbering: each block's variables are offset past the
+//! earlier blocks', and a linked input is renumbered to the variable it is linked to, which
+//! puts their wires in one cycle. Every party must compose the same blocks, in the same order,
+//! with the same links, to get the same circuit: the numbering depends on nothing else.
+//!
+//! ```
+//! use ark_bls12_377::Fr;
+//! use mpc_plonk::circuit;
+//! use mpc_plonk::relations::compose::compose;
+//! use mpc_plonk::relations::structured::PlonkCircuit;
+//! use std::collections::HashMap;
+//!
+//! let alice: PlonkCircuit<Fr> = circuit! { shared y = a * a; };
+//! let bob: PlonkCircuit<Fr> = circuit! { pub out = (y * b) + b; };
+//! let blocks = vec![("alice", alice), ("bob", bob)];
+//! let composed = compose(blocks, vec![("alice/y", "bob/y")]).unwrap();
+//!
+//! let a: HashMap<_, _> = vec![("a".to_owned(), Fr::from(3u64))].into_iter().collect();
+//! let b: HashMap<_, _> = vec![("b".to_owned(), Fr::from(2u64))].into_iter().collect();
+//! let values = composed.assign(&[&a, &b]).unwrap();
+//! let public = composed.circuit.eval_public_outputs(&values);
+//! assert_eq!(public["bob/out"], Fr::from(20u64));
+//! ```
+//!
+//! A circuit can also be built up a module at a time: [PlonkCircuit::embed] splices in another
+//! circuit, naming its variables the same way, and [PlonkCircuit::link] joins two of them.
+//!
+//! [Instance::from_circuit]: super::flat::Instance::from_circuit
+use ark_ff::Field;
+use std::collections::{HashMap, HashSet};
+use thiserror::Error;
+
+use super::structured::{assign, AssignError, Assignment, PlonkCircuit, Var, Witness};
+
+/// What separates a block's name from a variable's in the composed circuit: `block/name`.
+pub const BLOCK_SEPARATOR: &str = "/";
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ComposeError {
+    #[error("No block has a variable named {0:?}")]
+    UnknownVar(String),
+    #[error("{0:?} is linked to an earlier variable, so it must be an input")]
+    NotAnInput(String),
+    #[error("{0:?} and {1:?} are linked, so they can't both be public or shared outputs")]
+    Rebound(String, String),
+}
+
+/// A circuit composed of blocks, and where their variables ended up.
+pub struct Composition<F: Field> {
+    pub circuit: PlonkCircuit<F>,
+    /// Each block's name, and its variables' numbers in [Composition::circuit], in block order
+    pub blocks: Vec<(String, Vec<Var>)>,
+}
+
+/// Union-find over variables, each class's root its least variable.
+struct Classes(Vec<Var>);
+
+impl Classes {
+    fn root(&mut self, v: Var) -> Var {
+        let parent = self.0[v as usize];
+        if parent == v {
+            return v;
+        }
+        let root = self.root(parent);
+        self.0[v as usize] = root;
+        root
+    }
+
+    fn union(&mut self, a: Var, b: Var) {
+        let (a, b) = (self.root(a), self.root(b));
+        self.0[a.max(b) as usize] = a.min(b);
+    }
+}
+
+/// The circuit made of `blocks`, in order, by name, with the variables of each pair of `links`
+/// constrained equal.
+///
+/// Every input, public variable and shared output of a block is named `block/name` in the
+/// composed circuit (see [BLOCK_SEPARATOR]), and links name variables that way. Of two linked
+/// variables, the later must be an input: it is dropped, and its uses read the earlier one
+/// instead. So a block's output may feed any later block's input, and inputs may be linked to
+/// each other, in either order. At most one variable of a linked set may be public or a shared
+/// output.
+///
+/// Each block keeps its gates, padding included; the composed circuit is not padded again. If
+/// every block has values, so does the composed circuit, and a dropped input's value is simply
+/// dropped: under MPC, where shares can't be compared for free, a block that disagrees with the
+/// one it is linked to only shows up as a failed proof.
+pub fn compose<'n, F: Field>(
+    blocks: impl IntoIterator<Item = (&'n str, PlonkCircuit<F>)>,
+    links: impl IntoIterator<Item = (&'n str, &'n str)>,
+) -> Result<Composition<F>, ComposeError> {
+    let blocks: Vec<(&str, PlonkCircuit<F>)> = blocks.into_iter().collect();
+    let mut offsets: Vec<Var> = Vec::with_capacity(blocks.len());
+    let mut n_vars: Var = 0;
+    let mut names: HashMap<String, Var> = HashMap::new();
+    let mut seen = HashSet::new();
+    for (block, c) in &blocks {
+        assert!(
+            !block.contains(BLOCK_SEPARATOR),
+            "Block name {:?} contains {:?}",
+            block,
+            BLOCK_SEPARATOR
+        );
+        assert!(seen.insert(*block), "Two blocks are named {:?}", block);
+        offsets.push(n_vars);
+        for (v, name) in c.inputs.iter().chain(&c.pub_vars).chain(&c.shared_outputs) {
+            names.insert(format!("{}{}{}", block, BLOCK_SEPARATOR, name), n_vars + v);
+        }
+        n_vars += c.n_vars;
+    }
+    // Link, then check that every dropped variable is an input
+    let mut classes = Classes((0..n_vars).collect());
+    let mut linked = Vec::new();
+    for (a, b) in links {
+        let var = |name: &str| {
+            names
+                .get(name)
+                .copied()
+                .ok_or_else(|| ComposeError::UnknownVar(name.to_owned()))
+        };
+        let (va, vb) = (var(a)?, var(b)?);
+        classes.union(va, vb);
+        linked.push((a, va));
+        linked.push((b, vb));
+    }
+    // Each variable's block, and its number in it
+    let local = |v: Var| {
+        let k = offsets.partition_point(|o| *o <= v) - 1;
+        (k, v - offsets[k])
+    };
+    for (name, v) in &linked {
+        let (k, w) = local(*v);
+        if classes.root(*v) != *v && !blocks[k].1.inputs.contains_key(&w) {
+            return Err(ComposeError::NotAnInput(name.to_string()));
+        }
+    }
+    // Renumber: a class's root keeps its place, in order, and the others read it
+    let values = blocks.iter().all(|(_, c)| c.values.is_some());
+    assert!(
+        values || blocks.iter().all(|(_, c)| c.values.is_none()),
+        "Either every block or none must have values"
+    );
+    let mut circuit = PlonkCircuit::new(values);
+    let mut renamed: Vec<Var> = Vec::with_capacity(n_vars as usize);
+    for v in 0..n_vars {
+        let root = classes.root(v);
+        let new = if root == v {
+            let (k, w) = local(v);
+            let c = &blocks[k].1;
+            let new = circuit.new_var(|| c.values.as_ref().unwrap()[w as usize]);
+            if let Some(name) = c.inputs.get(&w) {
+                let name = format!("{}{}{}", blocks[k].0, BLOCK_SEPARATOR, name);
+                circuit.inputs.insert(new, name);
+            }
+            new
+        } else {
+            renamed[root as usize]
+        };
+        renamed.push(new);
+    }
+    // Then the gates and names, block by block
+    let mut bound: HashMap<Var, String> = HashMap::new();
+    for ((block, c), offset) in blocks.iter().zip(&offsets) {
+        let map = |v: &Var| renamed[(offset + v) as usize];
+        let gate = |(a, b, o): &(Var, Var, Var)| (map(a), map(b), map(o));
+        circuit.prods.extend(c.prods.iter().map(gate));
+        circuit.sums.extend(c.sums.iter().map(gate));
+        let outputs = c.pub_vars.iter().map(|v| (true, v));
+        for (public, (v, name)) in outputs.chain(c.shared_outputs.iter().map(|v| (false, v))) {
+            let name = format!("{}{}{}", block, BLOCK_SEPARATOR, name);
+            if let Some(other) = bound.insert(map(v), name.clone()) {
+                return Err(ComposeError::Rebound(other, name));
+            }
+            if public {
+                circuit.publicize_var(map(v), name);
+            } else {
+                circuit.share_output_var(map(v), name);
+            }
+        }
+    }
+    let blocks = blocks
+        .iter()
+        .zip(&offsets)
+        .map(|((block, c), offset)| {
+            let vars = (0..c.n_vars).map(|v| renamed[(offset + v) as usize]);
+            (block.to_string(), vars.collect())
+        })
+        .collect();
+    Ok(Composition { circuit, blocks })
+}
+
+impl<F: Field> PlonkCircuit<F> {
+    /// Splice `sub` into this circuit, after its own variables, and return the numbers of
+    /// `sub`'s variables here, in order.
+    ///
+    /// Every input, public variable and shared output of `sub` is named `prefix/name` here (see
+    /// [BLOCK_SEPARATOR]), so the same module may be embedded more than once under different
+    /// prefixes, and modules may nest. `sub` keeps its gates, padding included. Either both
+    /// circuits have values or neither does.
+    pub fn embed(&mut self, sub: &PlonkCircuit<F>, prefix: &str) -> Vec<Var> {
+        assert_eq!(
+            self.values.is_some(),
+            sub.values.is_some(),
+            "Either both circuits or neither must have values"
+        );
+        let offset = self.n_vars;
+        let name = |name: &String| format!("{}{}{}", prefix, BLOCK_SEPARATOR, name);
+        let gate = |(a, b, o): &(Var, Var, Var)| (offset + a, offset + b, offset + o);
+        self.n_vars += sub.n_vars;
+        if let (Some(values), Some(sub_values)) = (self.values.as_mut(), sub.values.as_ref()) {
+            values.extend_from_slice(sub_values);
+        }
+        self.prods.extend(sub.prods.iter().map(gate));
+        self.sums.extend(sub.sums.iter().map(gate));
+        self.inputs
+            .extend(sub.inputs.iter().map(|(v, n)| (offset + v, name(n))));
+        for (v, n) in &sub.pub_vars {
+            self.publicize_var(offset + v, name(n));
+        }
+        for (v, n) in &sub.shared_outputs {
+            self.share_output_var(offset + v, name(n));
+        }
+        (offset..self.n_vars).collect()
+    }
+
+    /// Constrain the variables named `a` and `b` (inputs, public variables or shared outputs)
+    /// equal, say to feed one embedded module's output to another's input.
+    ///
+    /// As in [compose], the later of the two must be an input: it is dropped, and its uses read
+    /// the earlier one instead, which puts their wires in one cycle. At most one of them may be
+    /// public or a shared output; if the dropped one is, the other takes its name. Every later
+    /// variable's number goes down by one, so numbers that [PlonkCircuit::embed] returned before
+    /// are stale; the dropped input's name is gone, so link to the other's. If the circuit has
+    /// values, the dropped input's value is dropped too, and nothing is recomputed: values that
+    /// disagree only show up as a failed proof.
+    pub fn link(&mut self, a: &str, b: &str) -> Result<(), ComposeError> {
+        let var = |name: &str| {
+            self.inputs
+                .iter()
+                .chain(&self.pub_vars)
+                .chain(&self.shared_outputs)
+                .find(|(_, n)| *n == name)
+                .map(|(v, _)| *v)
+                .ok_or_else(|| ComposeError::UnknownVar(name.to_owned()))
+        };
+        let (va, vb) = (var(a)?, var(b)?);
+        let ((keep, keep_name), (drop, drop_name)) = if va <= vb {
+            ((va, a), (vb, b))
+        } else {
+            ((vb, b), (va, a))
+        };
+        if keep == drop {
+            return Ok(());
+        }
+        if !self.inputs.contains_key(&drop) {
+            return Err(ComposeError::NotAnInput(drop_name.to_owned()));
+        }
+        let bound = |v: &Var| self.pub_vars.contains_key(v) || self.shared_outputs.contains_key(v);
+        if bound(&keep) && bound(&drop) {
+            return Err(ComposeError::Rebound(
+                keep_name.to_owned(),
+                drop_name.to_owned(),
+            ));
+        }
+        let map = |v: Var| match v {
+            v if v == drop => keep,
+            v if v > drop => v - 1,
+            v => v,
+        };
+        let gate = |(a, b, o): &(Var, Var, Var)| (map(*a), map(*b), map(*o));
+        let names = |vars: &HashMap<Var, String>| -> HashMap<Var, String> {
+            vars.iter().map(|(v, n)| (map(*v), n.clone())).collect()
+        };
+        self.inputs.remove(&drop);
+        self.inputs = names(&self.inputs);
+        self.pub_vars = names(&self.pub_vars);
+        self.shared_outputs = names(&self.shared_outputs);
+        self.prods = self.prods.iter().map(gate).collect();
+        self.sums = self.sums.iter().map(gate).collect();
+        if let Some(values) = self.values.as_mut() {
+            values.remove(drop as usize);
+        }
+        self.n_vars -= 1;
+        Ok(())
+    }
+}
+
+impl<F: Field> Composition<F> {
+    /// The composed circuit's [assign]ment, given a witness for each block, in block order:
+    /// `block/name` takes the value that `block`'s witness gives `name`. Under MPC, a witness
+    /// for another party's block gives shares, like any other.
+    pub fn assign(&self, witnesses: &[&dyn Witness<F>]) -> Result<Assignment<F>, AssignError> {
+        assert_eq!(witnesses.len(), self.blocks.len(), "One witness a block");
+        let witness = |name: &str| {
+            let (block, name) = name.split_once(BLOCK_SEPARATOR)?;
+            let k = self.blocks.iter().position(|(b, _)| b == block)?;
+            witnesses[k].input(name)
+        };
+        assign(&self.circuit, &witness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit;
+    use crate::relations::flat::Instance;
+    type F = ark_bls12_377::Fr;
+
+    fn inputs(values: &[(&str, u64)]) -> HashMap<String, F> {
+        values
+            .iter()
+            .map(|(n, v)| (n.to_string(), F::from(*v)))
+            .collect()
+    }
+
+    #[test]
+    fn composed() {
+        let alice: PlonkCircuit<F> = circuit! { shared y = a * a; };
+        let bob: PlonkCircuit<F> = circuit! { z = y * c; pub out = z + c; };
+        let carol: PlonkCircuit<F> = circuit! { pub sum = c + d; };
+        let links = vec![("alice/y", "bob/y"), ("carol/c", "bob/c")];
+        let composed = compose(
+            vec![("alice", alice), ("bob", bob), ("carol", carol)],
+            links,
+        );
+        let composed = composed.unwrap();
+        let c = &composed.circuit;
+        // Bob's `y` and Carol's `c` are gone
+        let mut names: Vec<&String> = c.inputs.values().collect();
+        names.sort_unstable();
+        assert_eq!(names, vec!["alice/a", "bob/c", "carol/d"]);
+        let (a, b, d) = (
+            inputs(&[("a", 3)]),
+            inputs(&[("c", 2)]),
+            inputs(&[("d", 5)]),
+        );
+        let values = composed.assign(&[&a, &b, &d]).unwrap();
+        let public = c.eval_public_outputs(&values);
+        assert_eq!(public["bob/out"], F::from(20u64));
+        assert_eq!(public["carol/sum"], F::from(7u64));
+        // Bob's `c` and Carol's are one wiring cycle
+        let (bob_c, carol_c) = (composed.blocks[1].1[1], composed.blocks[2].1[0]);
+        assert_eq!(bob_c, carol_c);
+        let inst = Instance::from_circuit(c);
+        assert_eq!(inst.vars_to_indices[&bob_c].len(), 3);
+        // With values, the same
+        let alice = PlonkCircuit::new_squaring_circuit(1, Some(F::from(3u64)));
+        let bob = PlonkCircuit::new_squaring_circuit(1, Some(F::from(9u64)));
+        let composed = compose(vec![("a", alice), ("b", bob)], vec![("a/out", "b/start")]);
+        let c = composed.unwrap().circuit;
+        let public = c.eval_public_outputs(&Assignment(c.values.clone().unwrap()));
+        assert_eq!(public["b/out"], F::from(81u64));
+    }
+
+    #[test]
+    fn embedded() {
+        let square: PlonkCircuit<F> = circuit! { shared y = x * x; };
+        let add: PlonkCircuit<F> = circuit! { pub out = a + b; };
+        let mut c = PlonkCircuit::new(false);
+        c.embed(&square, "left");
+        c.embed(&square, "right");
+        c.embed(&add, "sum");
+        c.link("left/y", "sum/a").unwrap();
+        c.link("sum/b", "right/y").unwrap();
+        let mut names: Vec<&String> = c.inputs.values().collect();
+        names.sort_unstable();
+        assert_eq!(names, vec!["left/x", "right/x"]);
+        let values = assign(&c, &inputs(&[("left/x", 3), ("right/x", 4)])).unwrap();
+        assert_eq!(c.eval_public_outputs(&values)["sum/out"], F::from(25u64));
+        assert_eq!(c.shared_outputs.len(), 2);
+        // Each square's output wire and the sum's operand wire are one cycle
+        let inst = Instance::from_circuit(&c);
+        assert!(c
+            .shared_outputs
+            .keys()
+            .all(|y| inst.vars_to_indices[y].len() == 2));
+        // With values, and nested
+        let mut inner = PlonkCircuit::new(true);
+        inner.embed(
+            &PlonkCircuit::new_squaring_circuit(1, Some(F::from(3u64))),
+            "a",
+        );
+        inner.embed(
+            &PlonkCircuit::new_squaring_circuit(1, Some(F::from(9u64))),
+            "b",
+        );
+        inner.link("a/out", "b/start").unwrap();
+        let mut outer = PlonkCircuit::new(true);
+        outer.embed(&inner, "outer");
+        let public = outer.eval_public_outputs(&Assignment(outer.values.clone().unwrap()));
+        assert_eq!(public["outer/b/out"], F::from(81u64));
+        assert_eq!(outer.n_vars, 3);
+    }
+
+    #[test]
+    fn bad_embedded_links() {
+        let alice: PlonkCircuit<F> = circuit! { pub y = a * a; };
+        let bob: PlonkCircuit<F> = circuit! { pub y; pub z = y * y; };
+        let mut c = PlonkCircuit::new(false);
+        c.embed(&alice, "alice");
+        c.embed(&bob, "bob");
+        assert_eq!(
+            c.link("alice/y", "bob/x"),
+            Err(ComposeError::UnknownVar("bob/x".to_owned()))
+        );
+        assert_eq!(
+            c.link("bob/z", "alice/y"),
+            Err(ComposeError::NotAnInput("bob/z".to_owned()))
+        );
+        assert_eq!(
+            c.link("alice/y", "bob/y"),
+            Err(ComposeError::Rebound(
+                "alice/y".to_owned(),
+                "bob/y".to_owned()
+            ))
+        );
+        // Nothing changed
+        assert_eq!(c.n_vars, alice.n_vars + bob.n_vars);
+    }
+
+    #[test]
+    fn bad_links() {
+        let blocks = || {
+            let alice: PlonkCircuit<F> = circuit! { pub y = a * a; };
+            let bob: PlonkCircuit<F> = circuit! { pub y; pub z = y * y; };
+            vec![("alice", alice), ("bob", bob)]
+        };
+        let err = |links| compose(blocks(), links).err().unwrap();
+        assert_eq!(
+            err(vec![("alice/y", "bob/x")]),
+            ComposeError::UnknownVar("bob/x".to_owned())
+        );
+        assert_eq!(
+            err(vec![("bob/z", "alice/y")]),
+            ComposeError::NotAnInput("bob/z".to_owned())
+        );
+        assert_eq!(
+            err(vec![("alice/a", "bob/z")]),
+            ComposeError::NotAnInput("bob/z".to_owned())
+        );
+        assert_eq!(
+            err(vec![("alice/y", "bob/y")]),
+            ComposeError::Rebound("alice/y".to_owned(), "bob/y".to_owned())
+        );
+    }
+}
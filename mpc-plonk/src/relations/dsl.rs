@@ -0,0 +1,191 @@
+//! A small language for writing [PlonkCircuit]s: see [circuit!](crate::circuit).
+use ark_ff::Field;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::{Add, Mul};
+
+use super::structured::PlonkCircuit;
+
+/// Builds a circuit, without values, out of named variables.
+///
+/// A name is bound by [CircuitBuilder::bind]; an unbound name is an input of the circuit (see
+/// [PlonkCircuit::new_input]) the first time it is used.
+pub struct CircuitBuilder<F: Field> {
+    circuit: RefCell<PlonkCircuit<F>>,
+    names: RefCell<HashMap<String, u32>>,
+}
+
+/// A variable of the circuit a [CircuitBuilder] is building. Adding or multiplying wires adds a
+/// gate.
+#[derive(Clone, Copy)]
+pub struct Wire<'a, F: Field> {
+    builder: &'a CircuitBuilder<F>,
+    var: u32,
+}
+
+impl<F: Field> CircuitBuilder<F> {
+    pub fn new() -> Self {
+        Self {
+            circuit: RefCell::new(PlonkCircuit::new(false)),
+            names: RefCell::new(HashMap::new()),
+        }
+    }
+    /// The variable bound to `name`, or a new input named `name`.
+    pub fn wire(&self, name: &str) -> Wire<'_, F> {
+        let mut names = self.names.borrow_mut();
+        let var = *names
+            .entry(name.to_owned())
+            .or_insert_with(|| self.circuit.borrow_mut().new_input(name.to_owned()));
+        Wire { builder: self, var }
+    }
+    /// Bind `name` to `wire`'s variable.
+    pub fn bind(&self, name: &str, wire: Wire<'_, F>) {
+        self.names.borrow_mut().insert(name.to_owned(), wire.var);
+    }
+    /// Make the variable bound to `name` public under `name`.
+    pub fn publicize(&self, name: &str) {
+        let var = self.wire(name).var;
+        self.circuit
+            .borrow_mut()
+            .publicize_var(var, name.to_owned());
+    }
+    /// Keep the variable bound to `name` secret, as an output named `name` (see
+    /// [PlonkCircuit::share_output_var]).
+    pub fn share_output(&self, name: &str) {
+        let var = self.wire(name).var;
+        self.circuit
+            .borrow_mut()
+            .share_output_var(var, name.to_owned());
+    }
+    /// The circuit, padded to a power-of-two number of gates.
+    pub fn finish(self) -> PlonkCircuit<F> {
+        let mut circuit = self.finish_unpadded();
+        circuit.pad_to_power_of_2();
+        circuit
+    }
+    /// The circuit, unpadded, to splice into another.
+    pub(crate) fn finish_unpadded(self) -> PlonkCircuit<F> {
+        self.circuit.into_inner()
+    }
+}
+
+impl<F: Field> Default for CircuitBuilder<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, F: Field> Add for Wire<'a, F> {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        let var = self
+            .builder
+            .circuit
+            .borrow_mut()
+            .new_sum(self.var, other.var);
+        Wire { var, ..self }
+    }
+}
+
+impl<'a, F: Field> Mul for Wire<'a, F> {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        let var = self
+            .builder
+            .circuit
+            .borrow_mut()
+            .new_prod(self.var, other.var);
+        Wire { var, ..self }
+    }
+}
+
+/// A [PlonkCircuit] without values, written as statements.
+///
+/// * `x = e;` binds `x` to expression `e`: names, `+`, `*`, and parentheses.
+/// * `pub x = e;` does the same, and makes `x` public under the name `x`.
+/// * `pub x;` makes `x` public.
+/// * `shared x = e;` and `shared x;` do the same, but keep `x` secret, as a shared output (see
+///   [PlonkCircuit::share_output_var]).
+///
+/// A name used before it is bound is an input of the circuit, under that name (see
+/// [PlonkCircuit::new_input]); [assign](super::structured::assign) computes the circuit's values
+/// from its inputs'. The circuit is padded to a power-of-two number of gates.
+///
+/// ```
+/// use ark_bls12_377::Fr;
+/// use mpc_plonk::circuit;
+/// use mpc_plonk::relations::structured::{assign, PlonkCircuit};
+///
+/// let c: PlonkCircuit<Fr> = circuit! {
+///     s = a + b;
+///     pub out = s * c;
+/// };
+/// assert_eq!(c.n_gates(), 2);
+/// assert_eq!(c.pub_vars.values().collect::<Vec<_>>(), vec!["out"]);
+///
+/// let witness = |name: &str| match name {
+///     "a" => Some(Fr::from(1u64)),
+///     "b" => Some(Fr::from(2u64)),
+///     "c" => Some(Fr::from(3u64)),
+///     _ => None,
+/// };
+/// let assignment = assign(&c, &witness).unwrap();
+/// let out = *c.pub_vars.keys().next().unwrap() as usize;
+/// assert_eq!(assignment.0[out], Fr::from(9u64));
+/// ```
+#[macro_export]
+macro_rules! circuit {
+    ($($body:tt)*) => {{
+        let builder = $crate::relations::dsl::CircuitBuilder::new();
+        $crate::__circuit!(@stmts builder $($body)*);
+        builder.finish()
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __circuit {
+    // Statements
+    (@stmts $b:ident) => {};
+    (@stmts $b:ident pub $name:ident ; $($rest:tt)*) => {
+        $b.publicize(stringify!($name));
+        $crate::__circuit!(@stmts $b $($rest)*);
+    };
+    (@stmts $b:ident pub $name:ident = $($rest:tt)*) => {
+        $crate::__circuit!(@stmt $b [$name] [$b.publicize(stringify!($name));] [] $($rest)*);
+    };
+    (@stmts $b:ident shared $name:ident ; $($rest:tt)*) => {
+        $b.share_output(stringify!($name));
+        $crate::__circuit!(@stmts $b $($rest)*);
+    };
+    (@stmts $b:ident shared $name:ident = $($rest:tt)*) => {
+        $crate::__circuit!(@stmt $b [$name] [$b.share_output(stringify!($name));] [] $($rest)*);
+    };
+    (@stmts $b:ident $name:ident = $($rest:tt)*) => {
+        $crate::__circuit!(@stmt $b [$name] [] [] $($rest)*);
+    };
+    // A statement's expression: everything up to its `;`
+    (@stmt $b:ident [$name:ident] [$($then:tt)*] [$($e:tt)*] ; $($rest:tt)*) => {
+        let wire = $crate::__circuit!(@expr $b [] $($e)*);
+        $b.bind(stringify!($name), wire);
+        $($then)*
+        $crate::__circuit!(@stmts $b $($rest)*);
+    };
+    (@stmt $b:ident [$name:ident] [$($then:tt)*] [$($e:tt)*] $t:tt $($rest:tt)*) => {
+        $crate::__circuit!(@stmt $b [$name] [$($then)*] [$($e)* $t] $($rest)*)
+    };
+    // Expressions, with names replaced by wires
+    (@expr $b:ident [$($out:tt)*]) => { $($out)* };
+    (@expr $b:ident [$($out:tt)*] ($($inner:tt)*) $($rest:tt)*) => {
+        $crate::__circuit!(@expr $b [$($out)* ($crate::__circuit!(@expr $b [] $($inner)*))] $($rest)*)
+    };
+    (@expr $b:ident [$($out:tt)*] + $($rest:tt)*) => {
+        $crate::__circuit!(@expr $b [$($out)* +] $($rest)*)
+    };
+    (@expr $b:ident [$($out:tt)*] * $($rest:tt)*) => {
+        $crate::__circuit!(@expr $b [$($out)* *] $($rest)*)
+    };
+    (@expr $b:ident [$($out:tt)*] $x:ident $($rest:tt)*) => {
+        $crate::__circuit!(@expr $b [$($out)* $b.wire(stringify!($x))] $($rest)*)
+    };
+}
@@ -0,0 +1,288 @@
+//! Expressions, to write small circuits inline: inputs and constants, combined with `+`, `-`,
+//! `*` and unary `-`, and flattened into gates by [PlonkCircuit::from_exprs].
+//!
+//! Unlike a [Wire](super::dsl::Wire), an [Expr] belongs to no circuit, so it may be built up
+//! anywhere, and cloned cheaply: a subexpression used twice (the same [Expr], cloned, not an
+//! equal one) is computed once.
+//!
+//! ```
+//! use ark_bls12_377::Fr;
+//! use mpc_plonk::relations::expr::Expr;
+//! use mpc_plonk::relations::structured::{assign, PlonkCircuit};
+//!
+//! let (x, y) = (Expr::input("x"), Expr::input("y"));
+//! let sum = &x + &y;
+//! let out = &sum * &sum - x * Expr::constant(Fr::from(3u64));
+//! let (c, constants) = PlonkCircuit::from_exprs(vec![("out", out)]);
+//!
+//! let mut inputs = constants;
+//! inputs.insert("x".to_owned(), Fr::from(2u64));
+//! inputs.insert("y".to_owned(), Fr::from(5u64));
+//! let values = assign(&c, &inputs).unwrap().0;
+//! let out = c.pub_vars.iter().find(|(_, name)| *name == "out").unwrap().0;
+//! assert_eq!(values[*out as usize], Fr::from(43u64));
+//! ```
+use ark_ff::Field;
+use std::collections::{HashMap, HashSet};
+use std::ops::{Add, Mul, Neg, Sub};
+use std::rc::Rc;
+
+use super::dsl::{CircuitBuilder, Wire};
+use super::r1cs::CONST_PREFIX;
+use super::structured::PlonkCircuit;
+
+/// An arithmetic expression over named inputs (see the [module docs](self)).
+#[derive(Clone, Debug)]
+pub struct Expr<F: Field>(Rc<Node<F>>);
+
+#[derive(Debug)]
+enum Node<F: Field> {
+    Input(String),
+    Constant(F),
+    Sum(Expr<F>, Expr<F>),
+    Product(Expr<F>, Expr<F>),
+}
+
+impl<F: Field> Expr<F> {
+    /// The circuit's input `name`.
+    pub fn input(name: &str) -> Self {
+        Self(Rc::new(Node::Input(name.to_owned())))
+    }
+
+    pub fn constant(c: F) -> Self {
+        Self(Rc::new(Node::Constant(c)))
+    }
+
+    /// The expression's value, given its inputs', or `None` if one is missing.
+    pub fn evaluate(&self, inputs: &HashMap<String, F>) -> Option<F> {
+        Some(match &*self.0 {
+            Node::Input(name) => *inputs.get(name)?,
+            Node::Constant(c) => *c,
+            Node::Sum(x, y) => x.evaluate(inputs)? + y.evaluate(inputs)?,
+            Node::Product(x, y) => x.evaluate(inputs)? * y.evaluate(inputs)?,
+        })
+    }
+
+    fn as_constant(&self) -> Option<F> {
+        match &*self.0 {
+            Node::Constant(c) => Some(*c),
+            _ => None,
+        }
+    }
+
+    /// `x + y`, or its value, if both are constants.
+    fn sum(x: Self, y: Self) -> Self {
+        match (x.as_constant(), y.as_constant()) {
+            (Some(a), Some(b)) => Self::constant(a + b),
+            _ => Self(Rc::new(Node::Sum(x, y))),
+        }
+    }
+
+    /// `x * y`, or its value, if both are constants.
+    fn product(x: Self, y: Self) -> Self {
+        match (x.as_constant(), y.as_constant()) {
+            (Some(a), Some(b)) => Self::constant(a * b),
+            _ => Self(Rc::new(Node::Product(x, y))),
+        }
+    }
+}
+
+impl<F: Field> Add for Expr<F> {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self::sum(self, other)
+    }
+}
+
+impl<F: Field> Sub for Expr<F> {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Self::sum(self, -other)
+    }
+}
+
+impl<F: Field> Mul for Expr<F> {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        Self::product(self, other)
+    }
+}
+
+impl<F: Field> Neg for Expr<F> {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self::product(Self::constant(-F::one()), self)
+    }
+}
+
+/// `op` on references too, by cloning
+macro_rules! by_ref {
+    ($op:ident, $method:ident) => {
+        impl<'a, F: Field> $op<&'a Expr<F>> for Expr<F> {
+            type Output = Expr<F>;
+            fn $method(self, other: &'a Expr<F>) -> Expr<F> {
+                self.$method(other.clone())
+            }
+        }
+
+        impl<'a, F: Field> $op<Expr<F>> for &'a Expr<F> {
+            type Output = Expr<F>;
+            fn $method(self, other: Expr<F>) -> Expr<F> {
+                self.clone().$method(other)
+            }
+        }
+
+        impl<'a, 'b, F: Field> $op<&'b Expr<F>> for &'a Expr<F> {
+            type Output = Expr<F>;
+            fn $method(self, other: &'b Expr<F>) -> Expr<F> {
+                self.clone().$method(other.clone())
+            }
+        }
+    };
+}
+
+by_ref!(Add, add);
+by_ref!(Sub, sub);
+by_ref!(Mul, mul);
+
+impl<F: Field> Neg for &Expr<F> {
+    type Output = Expr<F>;
+    fn neg(self) -> Expr<F> {
+        -self.clone()
+    }
+}
+
+/// Flattens expressions into a circuit, a gate a sum or product.
+struct Flattening<'a, F: Field> {
+    builder: &'a CircuitBuilder<F>,
+    /// Each subexpression's wire, by its address
+    wires: HashMap<*const Node<F>, Wire<'a, F>>,
+    /// Each constant's name
+    consts: HashMap<F, String>,
+    constants: HashMap<String, F>,
+}
+
+impl<'a, F: Field> Flattening<'a, F> {
+    fn constant(&mut self, c: F) -> Wire<'a, F> {
+        let n = self.consts.len();
+        let name = self
+            .consts
+            .entry(c)
+            .or_insert_with(|| format!("{}{}", CONST_PREFIX, n));
+        if !self.constants.contains_key(name) {
+            self.builder.publicize(name);
+            self.constants.insert(name.clone(), c);
+        }
+        self.builder.wire(name)
+    }
+
+    fn wire(&mut self, e: &Expr<F>) -> Wire<'a, F> {
+        if let Some(w) = self.wires.get(&Rc::as_ptr(&e.0)) {
+            return *w;
+        }
+        let w = match &*e.0 {
+            Node::Input(name) => self.builder.wire(name),
+            Node::Constant(c) => self.constant(*c),
+            Node::Sum(x, y) => self.wire(x) + self.wire(y),
+            Node::Product(x, y) => self.wire(x) * self.wire(y),
+        };
+        self.wires.insert(Rc::as_ptr(&e.0), w);
+        w
+    }
+}
+
+impl<F: Field> PlonkCircuit<F> {
+    /// The circuit that computes `outputs`, each public under its name, and the values of the
+    /// constants, which are public inputs named [CONST_PREFIX] and a number, in order of first
+    /// use. Inputs keep their names. The circuit is padded to a power-of-two number of gates.
+    ///
+    /// An output that is an input, a constant, or another output is copied (times one) into a
+    /// variable of its own, so that it may be public under its name.
+    pub fn from_exprs<'n>(
+        outputs: impl IntoIterator<Item = (&'n str, Expr<F>)>,
+    ) -> (Self, HashMap<String, F>) {
+        let builder = CircuitBuilder::new();
+        let mut flat = Flattening {
+            builder: &builder,
+            wires: HashMap::new(),
+            consts: HashMap::new(),
+            constants: HashMap::new(),
+        };
+        // Memoizing by address needs every expression alive until the end
+        let outputs: Vec<(&str, Expr<F>)> = outputs.into_iter().collect();
+        let mut public: HashSet<*const Node<F>> = HashSet::new();
+        for (name, e) in &outputs {
+            let mut w = flat.wire(e);
+            let leaf = matches!(&*e.0, Node::Input(_) | Node::Constant(_));
+            if leaf || !public.insert(Rc::as_ptr(&e.0)) {
+                w = w * flat.constant(F::one());
+            }
+            builder.bind(name, w);
+            builder.publicize(name);
+        }
+        let constants = flat.constants;
+        (builder.finish(), constants)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relations::structured::assign;
+    type F = ark_bls12_377::Fr;
+
+    /// The values of `c`'s public variables, by name, given `inputs`
+    fn public_values(c: &PlonkCircuit<F>, inputs: &HashMap<String, F>) -> HashMap<String, F> {
+        let values = assign(c, inputs).unwrap().0;
+        c.pub_vars
+            .iter()
+            .map(|(var, name)| (name.clone(), values[*var as usize]))
+            .collect()
+    }
+
+    #[test]
+    fn flattened() {
+        let (x, y) = (Expr::input("x"), Expr::input("y"));
+        let three = Expr::constant(F::from(3u64));
+        let sq = &x * &x;
+        let outputs = vec![
+            // x^2 once, though used twice
+            ("a", &sq * &sq + &sq),
+            ("b", &x - &y * &three),
+            ("c", -&y),
+            // Folded to a constant
+            ("d", &three * &three - Expr::constant(F::from(1u64))),
+            ("e", x.clone()),
+            ("f", sq.clone()),
+            ("g", sq.clone()),
+        ];
+        let inputs: HashMap<String, F> = vec![("x", 4u64), ("y", 5)]
+            .into_iter()
+            .map(|(n, v)| (n.to_owned(), F::from(v)))
+            .collect();
+        let expected: HashMap<String, F> = outputs
+            .iter()
+            .map(|(n, e)| (n.to_string(), e.evaluate(&inputs).unwrap()))
+            .collect();
+        let (c, constants) = PlonkCircuit::from_exprs(outputs);
+        // 3, -1, 8, and 1 for the copies
+        assert_eq!(constants.len(), 4);
+        let mut all = constants.clone();
+        all.extend(inputs);
+        let values = public_values(&c, &all);
+        for (name, value) in &values {
+            let expected = constants.get(name).or_else(|| expected.get(name)).unwrap();
+            assert_eq!(value, expected, "{}", name);
+        }
+        assert_eq!(values.len(), 7 + constants.len());
+        assert_eq!(values["a"], F::from(272u64));
+        assert_eq!(values["b"], -F::from(11u64));
+    }
+
+    #[test]
+    fn missing_input() {
+        let e = Expr::input("x") + Expr::<F>::input("y");
+        let inputs = vec![("x".to_owned(), F::from(1u64))].into_iter().collect();
+        assert_eq!(e.evaluate(&inputs), None);
+    }
+}
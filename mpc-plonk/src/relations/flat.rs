@@ -1,160 +1,232 @@
-use ark_ff::{FftField, FftParameters};
-use std::collections::HashMap;
+use ark_ff::{batch_inversion, FftField, Field};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_std::io::{Read, Write};
+use blake2::{Blake2s, Digest};
+use memmap2::Mmap;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Display, Formatter};
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::PathBuf;
+use std::sync::Arc;
 
 use ark_poly::{
-    domain::{EvaluationDomain, MixedRadixEvaluationDomain, Radix2EvaluationDomain},
+    domain::{EvaluationDomain, Radix2EvaluationDomain},
     evaluations::univariate::Evaluations,
-    univariate::DensePolynomial,
-    Polynomial,
-    UVPolynomial,
+    univariate::{DensePolynomial, SparsePolynomial},
+    Polynomial, UVPolynomial,
 };
 
 use std::iter;
 
-use crate::util::shift;
+use super::structured::{LocalAssignment, PlonkCircuit};
 
-use super::structured::PlonkCircuit;
+/// Wires that a gate reads: its two inputs and its output, in columns 0, 1, and 2.
+pub const GATE_WIDTH: usize = 3;
 
+/// A circuit's public data, as both the prover and the verifier have it: its layout (gates and
+/// wiring), domains, and public wires. The wire values are a [Witness], which only the prover
+/// has.
 #[derive(Clone)]
-pub struct CircuitLayout<F: FftField> {
-    /// Wiring permutation polynomial
-    pub w: DensePolynomial<F>,
+pub struct Instance<F: FftField> {
+    /// Wiring permutation polynomials, one per column: over the gate domain, column `j`'s maps
+    /// each of its wires to the point (see [WireId::point]) of the next wire in its cycle
+    pub w: Vec<DensePolynomial<F>>,
     /// Gate selection polynomial
     pub s: DensePolynomial<F>,
-    /// Map from variables to indices in the layout
-    pub vars_to_indices: HashMap<u32, Vec<usize>>,
-    /// Public variables
-    pub public_indices: HashMap<String, usize>,
-    /// Wire value polynomial
-    pub p: Option<DensePolynomial<F>>,
+    /// Map from variables to their wires
+    pub vars_to_indices: HashMap<u32, Vec<WireId>>,
+    /// Public variables, each at its first wire
+    pub public_indices: HashMap<String, WireId>,
+    /// Each public variable's gate point, and its barycentric weight: one over the product of the
+    /// point's differences from the other public points in its column
+    pub input_points: HashMap<String, (F, F)>,
     /// Domains over which the polynomials have meaning
     pub domains: Domains<F>,
+    /// The circuit's own gates. The gate domain (see [Instance::padded_gates]) may hold more:
+    /// the rest are padding, products of zeros with each wire wired only to itself.
+    pub n_gates: usize,
+    /// What every proof of this circuit would otherwise recompute, shared between clones
+    pub precomputed: Arc<Precomputed<F>>,
 }
 
-impl<F: FftField> CircuitLayout<F> {
+impl<F: FftField> Instance<F> {
     pub fn from_circuit(c: &PlonkCircuit<F>) -> Self {
-        let domains = Domains::from_circuit(c);
-        // Our layout is products followed by sums
+        Self::from_circuit_with_width(c, GATE_WIDTH)
+    }
+
+    /// The layout of `c` in `width` columns, at least [GATE_WIDTH]. The gates read the first
+    /// [GATE_WIDTH]; the others hold zeros, each wire wired only to itself.
+    pub fn from_circuit_with_width(c: &PlonkCircuit<F>, width: usize) -> Self {
+        let domains = Domains::from_circuit(c, width);
+        let n = domains.gates.size();
+        // Our layout is products followed by sums, then padding up to the domain's size
 
         // Start with gate selector polynomial
+        let mut gate_selector: Vec<F> = iter::repeat(F::zero())
+            .take(c.prods.len())
+            .chain(iter::repeat(F::one()).take(c.sums.len()))
+            .collect();
+        // Padding gates are products, of zeros
+        gate_selector.resize(n, F::zero());
         let gate_selector_evals = Evaluations::<F, Radix2EvaluationDomain<F>>::from_vec_and_domain(
-            iter::repeat(F::zero())
-                .take(c.prods.len())
-                .chain(iter::repeat(F::one()).take(c.sums.len()))
-                .collect(),
+            gate_selector,
             domains.gates.clone(),
         );
 
-        // Get powers of w for wire permuation poly
-        let n_wires = c.n_gates() * 3;
-        let wire_g = domains.wires.group_gen;
-        let wire_g_pows: Vec<F> = iter::successors(Some(F::one()), |f| Some(wire_g * f))
-            .take(n_wires)
+        // Each wire's point, by column and then gate, for the wiring permutation polys
+        let gate_g = domains.gates.group_gen;
+        let gate_g_pows: Vec<F> = iter::successors(Some(F::one()), |f| Some(gate_g * f))
+            .take(n)
             .collect();
-        // Manifest layout
-        let var_layout: Vec<u32> = c
-            .prods
-            .iter()
-            .chain(c.sums.iter())
-            .flat_map(|(in0, in1, out)| vec![*in0, *in1, *out])
+        let points: Vec<Vec<F>> = (0..width)
+            .map(|j| {
+                let k = domains.shift(j);
+                gate_g_pows.iter().map(|x| k * x).collect()
+            })
             .collect();
+        // Manifest layout
+        let var_layout = var_layout(c);
         // Assemble cycles
         let vars_to_indices = {
-            let mut vars_to_indices: HashMap<u32, Vec<usize>> =
+            let mut vars_to_indices: HashMap<u32, Vec<WireId>> =
                 (0..c.n_vars).map(|i| (i, Vec::new())).collect();
             for (i, v) in var_layout.iter().enumerate() {
-                vars_to_indices.get_mut(v).unwrap().push(i);
+                let wire = GateId::new(i / GATE_WIDTH).wire(i % GATE_WIDTH);
+                vars_to_indices.get_mut(v).unwrap().push(wire);
             }
             vars_to_indices
         };
-        // Write cycles into evaluations
-        let mut wire_evals = Evaluations::<F, MixedRadixEvaluationDomain<F>>::from_vec_and_domain(
-            vec![F::zero(); n_wires],
-            domains.wires.clone(),
-        );
-        for (_var, indices) in &vars_to_indices {
+        // Write cycles into evaluations; a wire in no cycle, like a padding gate's, maps to itself
+        let mut wire_evals = points.clone();
+        for indices in vars_to_indices.values() {
             for i in 0..indices.len() {
-                let i_next = (i + 1) % indices.len();
-                wire_evals.evals[indices[i]] = wire_g_pows[indices[i_next]];
+                let (cur, next) = (indices[i], indices[(i + 1) % indices.len()]);
+                wire_evals[cur.slot()][cur.gate().get()] = points[next.slot()][next.gate().get()];
             }
         }
-            #[cfg(debug_assertions)]
-            {
-                println!("Plonk W evals:");
-                let mut p = wire_evals.clone();
-                for (i, e) in p.evals.iter_mut().enumerate() {
-                    println!("{}: {}", i, e);
+        #[cfg(debug_assertions)]
+        {
+            println!("Plonk W evals:");
+            for (j, evals) in wire_evals.iter().enumerate() {
+                for (g, e) in evals.iter().enumerate() {
+                    println!("{} {}: {}", j, g, e);
                 }
             }
+        }
 
-        // Compute P polynomial if needed
-        let p = c.values.as_ref().map(|vals| {
-            let mut p_evals = Evaluations::<F, MixedRadixEvaluationDomain<F>>::from_vec_and_domain(
-                vec![F::zero(); n_wires],
-                domains.wires.clone(),
-            );
-            for (var, indices) in &vars_to_indices {
-                for i in indices {
-                    p_evals.evals[*i] = vals[*var as usize];
-                }
-            }
-            #[cfg(debug_assertions)]
-            {
-                println!("Plonk P evals:");
-                let mut p = p_evals.clone();
-                for (i, e) in p.evals.iter_mut().enumerate() {
-                    e.publicize();
-                    println!("{}: {}", i, e);
-                }
-            }
-            p_evals.interpolate()
-        });
-        let w = wire_evals.interpolate();
-            #[cfg(debug_assertions)]
-            {
-                println!("Plonk w coeffs:");
-                let mut p = w.clone();
-                for (i, e) in p.coeffs.iter_mut().enumerate() {
-                    println!("{}: {}", i, e);
-                }
-            }
-        CircuitLayout {
+        let w = wire_evals
+            .into_iter()
+            .map(|evals| Evaluations::from_vec_and_domain(evals, domains.gates).interpolate())
+            .collect();
+        let public_indices = c
+            .pub_vars
+            .iter()
+            .filter_map(|(v, name)| {
+                vars_to_indices
+                    .get(v)
+                    .and_then(|is| is.first().map(|i| (name.clone(), *i)))
+            })
+            .collect();
+        let input_points = input_points(&public_indices, &domains);
+        let inst = Instance {
             w,
             s: gate_selector_evals.interpolate(),
+            precomputed: Arc::new(Precomputed::new(&public_indices, &input_points, &domains)),
+            input_points,
             domains: domains.clone(),
-            p,
-            public_indices: c
-                .pub_vars
-                .iter()
-                .filter_map(|(v, name)| {
-                    vars_to_indices
-                        .get(v)
-                        .and_then(|is| is.first().map(|i| (name.clone(), *i)))
-                })
-                .collect(),
+            n_gates: c.n_gates(),
+            public_indices,
             vars_to_indices,
+        };
+        debug_assert!(inst.padding_is_inert());
+        inst
+    }
+
+    /// Gates in the gate domain: the circuit's own (see [Instance::n_gates]), and padding.
+    pub fn padded_gates(&self) -> usize {
+        self.domains.gates.size()
+    }
+
+    /// Whether the padding gates constrain nothing: each is a product (its selector is zero),
+    /// holds no variable, and has each of its wires wired only to itself. Otherwise the wiring
+    /// permutation could tie a variable to a padding wire, whose value the prover picks freely.
+    pub fn padding_is_inert(&self) -> bool {
+        let dom = self.domains.gates;
+        if self.n_gates > dom.size() {
+            return false;
         }
+        let padding = self.n_gates..dom.size();
+        let holds_var = self
+            .vars_to_indices
+            .values()
+            .flatten()
+            .chain(self.public_indices.values())
+            .any(|i| padding.contains(&i.gate().get()));
+        if holds_var {
+            return false;
+        }
+        let s_evals = self.s.evaluate_over_domain_by_ref(dom);
+        let x_evals = &self.precomputed.x_evals;
+        padding.clone().all(|g| s_evals.evals[g].is_zero())
+            && self.w.iter().enumerate().all(|(j, w_j)| {
+                let w_evals = w_j.evaluate_over_domain_by_ref(dom);
+                let shift = self.domains.shift(j);
+                padding
+                    .clone()
+                    .all(|g| w_evals.evals[g] == shift * x_evals.evals[g])
+            })
+    }
+
+    /// A hash of everything that the prover and verifier must lay out alike: the domains, the
+    /// gate selector and wiring permutation polynomials, and the public wires.
+    ///
+    /// Keys record the digest of their circuit, and proofs are bound to it through the
+    /// transcript, so a circuit laid out differently (say, with its gates in another order) fails
+    /// loudly rather than yielding proofs that don't verify.
+    pub fn digest(&self) -> CircuitDigest {
+        let mut bytes = Vec::new();
+        self.domains.serialize(&mut bytes).unwrap();
+        self.s.serialize(&mut bytes).unwrap();
+        self.w.serialize(&mut bytes).unwrap();
+        sorted(&self.public_indices).serialize(&mut bytes).unwrap();
+        CircuitDigest(Blake2s::digest(&bytes).into())
     }
 
     pub fn degree_bound(&self) -> usize {
-        self.domains.wires.size() * 2 - 1
+        self.domains.n_wires() - 1
+    }
+
+    /// The columns that hold public wires, in order
+    pub fn public_columns(&self) -> Vec<usize> {
+        self.precomputed.public.iter().map(|c| c.column).collect()
+    }
+
+    /// The column whose coset (see [Domains::shift]) holds `y`, and `y`'s point in the gate
+    /// domain.
+    fn locate(&self, y: F) -> (usize, F) {
+        (0..self.domains.width)
+            .find_map(|j| {
+                let x = y * self.domains.shift(j).inverse().unwrap();
+                let on_gates = self.domains.gates.evaluate_vanishing_polynomial(x) == F::zero();
+                on_gates.then(|| (j, x))
+            })
+            .unwrap_or_else(|| panic!("{} is no wire's point", y))
     }
 
     /// Check that no wire is in more than `d` connections
     ///
     /// Used in testing
     pub fn check_connection_degree(&self, d: usize) {
-        let n_wires = self.domains.wires.size();
-        let wire_g = self.domains.wires.group_gen;
-        let wire_g_pows: Vec<F> = iter::successors(Some(F::one()), |f| Some(wire_g * f))
-            .take(n_wires)
-            .collect();
         for (v, indices) in &self.vars_to_indices {
-            let start = wire_g_pows[indices[0]];
+            let start = indices[0].point(&self.domains);
             let mut cur = start;
             let mut cycle = false;
             for _ in 0..d {
-                cur = self.w.evaluate(&cur);
+                let (j, x) = self.locate(cur);
+                cur = self.w[j].evaluate(&x);
                 if cur == start {
                     cycle = true;
                     break;
@@ -169,136 +241,698 @@ impl<F: FftField> CircuitLayout<F> {
         }
     }
 
-    pub fn evaluate_over_gates(
+    /// The public variables in `column`, with their points and weights
+    pub(crate) fn column_inputs(&self, column: usize) -> impl Iterator<Item = (&String, &(F, F))> {
+        self.precomputed
+            .public_column(column)
+            .into_iter()
+            .flat_map(|c| c.inputs.iter().map(|(var, point)| (var, point)))
+    }
+
+    /// Returns the monic polynomial which vanishes at the input pins in `column`
+    pub fn vanishing_poly_on_inputs(&self, column: usize) -> DensePolynomial<F> {
+        match self.precomputed.public_column(column) {
+            Some(c) => c.vanishing.clone(),
+            None => poly_from_roots(&[]),
+        }
+    }
+
+    pub fn inputs_poly(&self, inputs: &HashMap<String, F>, column: usize) -> DensePolynomial<F> {
+        let points: Vec<(F, F)> = inputs
+            .iter()
+            .map(|(var, val)| (self.public_indices[var], *val))
+            .filter(|(i, _)| i.slot() == column)
+            .map(|(i, val)| (i.gate().point(&self.domains), val))
+            .collect();
+        assert!(points.len() > 0);
+        crate::util::interpolate(&points)
+    }
+
+    /// Evaluates [Instance::vanishing_poly_on_inputs] at `x`, in time linear in the number
+    /// of public inputs.
+    pub fn eval_vanishing_on_inputs(&self, column: usize, x: F) -> F {
+        self.column_inputs(column)
+            .map(|(_, (xi, _))| x - xi)
+            .product()
+    }
+
+    /// Evaluates [Instance::inputs_poly] at `x`, in time linear in the number of public
+    /// inputs, by barycentric interpolation.
+    ///
+    /// Panics unless `inputs` has every public input in `column`.
+    pub fn eval_inputs_poly(&self, inputs: &HashMap<String, F>, column: usize, x: F) -> F {
+        let terms: Vec<(F, F, F)> = self
+            .column_inputs(column)
+            .map(|(var, (xi, wi))| {
+                let yi = *inputs
+                    .get(var)
+                    .unwrap_or_else(|| panic!("Missing public input {}", var));
+                (*xi, *wi, yi)
+            })
+            .collect();
+        if let Some((_, _, yi)) = terms.iter().find(|(xi, _, _)| *xi == x) {
+            return *yi;
+        }
+        let mut dens: Vec<F> = terms.iter().map(|(xi, _, _)| x - xi).collect();
+        let z: F = dens.iter().product();
+        batch_inversion(&mut dens);
+        let sum: F = terms
+            .iter()
+            .zip(&dens)
+            .map(|((_, wi, yi), d)| *wi * yi * d)
+            .sum();
+        z * sum
+    }
+
+    /// The public check's constraints at `x`, combined with powers of `beta`: the sum over the
+    /// public columns (see [Instance::public_columns]) of `beta^i (P_j(x) - V_j(x))/Z_j(x)`,
+    /// as a numerator and a denominator, given each public column's `P_j(x)`.
+    ///
+    /// `V_j` and `Z_j` are [Instance::inputs_poly] and
+    /// [Instance::vanishing_poly_on_inputs], evaluated by their `eval_` counterparts.
+    pub fn eval_public_fraction(
         &self,
-        p: &DensePolynomial<F>,
-    ) -> Evaluations<F, Radix2EvaluationDomain<F>> {
-        let cs = p.coeffs.len();
-        if cs <= self.domains.gates.size() {
-            p.evaluate_over_domain_by_ref(self.domains.gates)
-        } else if cs <= self.domains.wires.size() {
-            Evaluations::from_vec_and_domain(
-                p.evaluate_over_domain_by_ref(self.domains.wires)
-                    .evals
-                    .into_iter()
-                    .enumerate()
-                    .filter(|(i, _)| i % 3 == 0)
-                    .map(|(_, v)| v)
-                    .collect(),
-                self.domains.gates,
-            )
-        } else {
-            panic!(
-                "Cannot evaluate polynomial with {} coefficients over gates domain",
-                cs
-            )
+        inputs: &HashMap<String, F>,
+        beta: F,
+        p_values: &[F],
+        x: F,
+    ) -> (F, F) {
+        let columns = &self.precomputed.public;
+        assert_eq!(p_values.len(), columns.len(), "One value per public column");
+        let mut power = F::one();
+        let mut fraction = (F::zero(), F::one());
+        for (j, p) in columns.iter().map(|c| c.column).zip(p_values) {
+            let z = self.eval_vanishing_on_inputs(j, x);
+            let c = *p - self.eval_inputs_poly(inputs, j, x);
+            fraction = (fraction.0 * z + power * c * fraction.1, fraction.1 * z);
+            power *= beta;
         }
+        fraction
     }
+}
 
-    fn check_inputs(&self, public_wires: &HashMap<String, F>) {
-        if let Some(p) = &self.p {
-            let wire_g = self.domains.wires.group_gen;
-            for (variable, value) in public_wires {
-                let idx = self
-                    .public_indices
-                    .get(variable)
-                    .unwrap_or_else(|| panic!("Missing public wire {:?}", variable));
-                assert_eq!(&p.evaluate(&wire_g.pow(&[*idx as u64])), value);
+/// A circuit's wire values, which only the prover has: the polynomials, or a file to read them
+/// from while proving. Keeping them out of the [Instance] keeps them from the verifier.
+#[derive(Clone)]
+pub enum Witness<F: FftField> {
+    /// Wire value polynomials, one per column, each over the gate domain
+    Polys(Vec<DensePolynomial<F>>),
+    /// Wire values in a file, read only when proving
+    File(WitnessFile),
+}
+
+impl<F: FftField> Witness<F> {
+    /// The wire values of `c`, laid out as `inst`, which must be `c`'s.
+    ///
+    /// Panics if `c` has no values.
+    pub fn from_circuit(inst: &Instance<F>, c: &PlonkCircuit<F>) -> Self {
+        let vals = c.values.as_ref().expect("Circuit has no values");
+        let n = inst.domains.gates.size();
+        let mut p_evals = vec![vec![F::zero(); n]; inst.domains.width];
+        for (var, indices) in &inst.vars_to_indices {
+            for i in indices {
+                p_evals[i.slot()][i.gate().get()] = vals[*var as usize];
+            }
+        }
+        #[cfg(debug_assertions)]
+        {
+            println!("Plonk P evals:");
+            for (j, evals) in p_evals.iter().enumerate() {
+                for (g, e) in evals.iter().enumerate() {
+                    let mut e = *e;
+                    e.publicize();
+                    println!("{} {}: {}", j, g, e);
+                }
+            }
+        }
+        Witness::Polys(
+            p_evals
+                .into_iter()
+                .map(|evals| {
+                    Evaluations::from_vec_and_domain(evals, inst.domains.gates).interpolate()
+                })
+                .collect(),
+        )
+    }
+
+    /// The wire values of `c`, which must not have values, read from `path`, only when proving.
+    ///
+    /// The file holds each variable's value, in order, all serialized in the same number of
+    /// bytes; see [WitnessFile::write_values].
+    pub fn from_file(c: &PlonkCircuit<F>, path: impl Into<PathBuf>) -> io::Result<Self> {
+        assert!(c.values.is_none(), "Circuit has values");
+        let path = path.into();
+        let len = std::fs::metadata(&path)?.len();
+        let n_vars = c.n_vars as u64;
+        if n_vars == 0 || len % n_vars != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} bytes can't hold {} equal-sized values", len, n_vars),
+            ));
+        }
+        let value_size = len / n_vars;
+        let offsets = var_layout(c)
+            .into_iter()
+            .map(|v| v as u64 * value_size)
+            .collect();
+        Ok(Witness::File(WitnessFile {
+            path,
+            offsets,
+            value_size: value_size as usize,
+        }))
+    }
+
+    /// The wire value polynomials, laid out as `inst`: [Witness::Polys], or else interpolated
+    /// from the file, a column at a time.
+    ///
+    /// Panics if the file can't be read.
+    pub fn polys(&self, inst: &Instance<F>) -> Vec<DensePolynomial<F>> {
+        match self {
+            Witness::Polys(p) => p.clone(),
+            Witness::File(file) => {
+                let n = inst.domains.gates.size();
+                (0..inst.domains.width)
+                    .map(|j| {
+                        let evals = if j < GATE_WIDTH {
+                            file.read_column(j, n).expect("failed to read witness file")
+                        } else {
+                            vec![F::zero(); n]
+                        };
+                        Evaluations::from_vec_and_domain(evals, inst.domains.gates).interpolate()
+                    })
+                    .collect()
             }
         }
     }
 
-    fn check_gates(&self) {
-        let wire_g = self.domains.wires.group_gen;
-        if let Some(p) = &self.p {
-            let p_x_evals = self.evaluate_over_gates(p);
-            let p_wx_evals = self.evaluate_over_gates(&shift(p.clone(), wire_g));
-            let p_wwx_evals = self.evaluate_over_gates(&shift(p.clone(), wire_g * wire_g));
-            let s_evals = self.evaluate_over_gates(&self.s);
-            let a = &s_evals * &(&p_x_evals + &p_wx_evals);
-            let b = &(&(&s_evals * &-F::one()) + &F::one()) * &(&p_x_evals * &p_wx_evals);
-            let c = &a + &b;
-            assert_eq!(c, p_wwx_evals);
+    fn check_inputs(
+        inst: &Instance<F>,
+        p: &[DensePolynomial<F>],
+        public_wires: &HashMap<String, F>,
+    ) {
+        for (variable, value) in public_wires {
+            let idx = inst
+                .public_indices
+                .get(variable)
+                .unwrap_or_else(|| panic!("Missing public wire {:?}", variable));
+            let x = idx.gate().point(&inst.domains);
+            assert_eq!(&p[idx.slot()].evaluate(&x), value);
         }
     }
 
-    fn check_wiring(&self) {
-        let n_wires = self.domains.wires.size();
-        let wire_g = self.domains.wires.group_gen;
-        let wire_g_pows: Vec<F> = iter::successors(Some(F::one()), |f| Some(wire_g * f))
-            .take(self.domains.wires.size())
+    fn check_gates(inst: &Instance<F>, p: &[DensePolynomial<F>]) {
+        let evals: Vec<_> = p[..GATE_WIDTH]
+            .iter()
+            .map(|p| p.evaluate_over_domain_by_ref(inst.domains.gates))
             .collect();
-        if let Some(p) = &self.p {
-            for i in 0..n_wires {
-                let x = wire_g_pows[i];
-                let p_of_x = p.evaluate(&x);
-                let p_of_w_of_x = p.evaluate(&self.w.evaluate(&x));
+        let s_evals = inst.s.evaluate_over_domain_by_ref(inst.domains.gates);
+        let a = &s_evals * &(&evals[0] + &evals[1]);
+        let b = &(&(&s_evals * &-F::one()) + &F::one()) * &(&evals[0] * &evals[1]);
+        let c = &a + &b;
+        assert_eq!(c, evals[2]);
+    }
+
+    fn check_wiring(inst: &Instance<F>, p: &[DensePolynomial<F>]) {
+        for (j, (p_j, w_j)) in p.iter().zip(&inst.w).enumerate() {
+            for g in 0..inst.domains.gates.size() {
+                let x = inst.domains.gates.element(g);
+                let p_of_x = p_j.evaluate(&x);
+                let (k, y) = inst.locate(w_j.evaluate(&x));
+                let p_of_w_of_x = p[k].evaluate(&y);
                 assert_eq!(
                     p_of_x, p_of_w_of_x,
-                    "p(x) != p(w(x)) for x = {}, pin {}\n{} vs \n{}",
-                    x, i, p_of_x, p_of_w_of_x
+                    "p(x) != p(w(x)) for x = {}, gate {} column {}\n{} vs \n{}",
+                    x, g, j, p_of_x, p_of_w_of_x
                 );
             }
         }
     }
 
-    /// Returns the monic polynomial which vanishes at the input pins
-    pub fn vanishing_poly_on_inputs(&self) -> DensePolynomial<F> {
-        let roots: Vec<F> = self
-            .public_indices
-            .iter()
-            .map(|(_, i)| self.domains.wires.element(*i))
-            .collect();
-        poly_from_roots(&roots)
+    /// Check that these values satisfy `inst`'s gates and wiring, and its public wires.
+    ///
+    /// Used in testing
+    pub fn check(&self, inst: &Instance<F>, public_wires: &HashMap<String, F>) {
+        let p = self.polys(inst);
+        Self::check_gates(inst, &p);
+        Self::check_wiring(inst, &p);
+        Self::check_inputs(inst, &p, public_wires);
     }
+}
 
-    pub fn inputs_poly(
-        &self,
-        inputs: &HashMap<String, F>,
-    ) -> DensePolynomial<F> {
-        assert!(inputs.len() > 0);
-        let points: Vec<(F, F)> = inputs
-            .iter()
-            .map(|(var, val)| {
-                let idx = self.public_indices[var];
-                let x = self.domains.wires.element(idx);
-                (x, *val)
-            })
+/// The rows of the gate domain whose every wire is local (see [LocalAssignment]), with the king's
+/// values on them: the prover computes their part of the wiring proof natively (see
+/// [crate::Prover::local_rows]). Rows with no wires, past the circuit's gates, are local too.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LocalRows<B> {
+    /// The rows, in order
+    pub rows: Vec<usize>,
+    /// Each column's values on the rows (zeros, but on the king)
+    pub values: Vec<Vec<B>>,
+}
+
+impl<B: Field> LocalRows<B> {
+    /// The local rows of `inst`, which must be laid out from the circuit `local` is for.
+    pub fn new<F: FftField>(inst: &Instance<F>, local: &LocalAssignment<B>) -> Self {
+        let width = inst.domains.width;
+        let mut rows: Vec<Option<Vec<B>>> =
+            vec![Some(vec![B::zero(); width]); inst.domains.gates.size()];
+        for (var, indices) in &inst.vars_to_indices {
+            for i in indices {
+                let row = &mut rows[i.gate().get()];
+                match (local.0[*var as usize], row.as_mut()) {
+                    (Some(value), Some(values)) => values[i.slot()] = value,
+                    _ => *row = None,
+                }
+            }
+        }
+        let (rows, row_values): (Vec<usize>, Vec<Vec<B>>) = rows
+            .into_iter()
+            .enumerate()
+            .filter_map(|(g, values)| Some((g, values?)))
+            .unzip();
+        let values = (0..width)
+            .map(|j| row_values.iter().map(|values| values[j]).collect())
             .collect();
-        crate::util::interpolate(&points)
+        Self { rows, values }
     }
+}
+
+/// A hash of a circuit's layout (see [Instance::digest]).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CircuitDigest(pub [u8; 32]);
+
+impl Display for CircuitDigest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|b| write!(f, "{:02x}", b))
+    }
+}
 
+impl CanonicalSerialize for CircuitDigest {
+    fn serialize<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        Ok(writer.write_all(&self.0)?)
+    }
+    fn serialized_size(&self) -> usize {
+        self.0.len()
+    }
+}
 
-    pub fn check(&self, public_wires: &HashMap<String, F>) {
-        self.check_gates();
-        self.check_wiring();
-        self.check_inputs(public_wires);
+impl CanonicalDeserialize for CircuitDigest {
+    fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        let mut bytes = [0u8; 32];
+        reader.read_exact(&mut bytes)?;
+        Ok(CircuitDigest(bytes))
     }
 }
 
-/// We assume a power-of-two number of gates.
-/// We use a 2^r*3-sized domain for wires and a 2^r-sized domain for gates.
-#[derive(Clone, Debug)]
+/// The gate domain is the smallest of power-of-two size holding the circuit's gates; layouts pad
+/// the rest (see [Instance::n_gates]).
+///
+/// Each column's polynomials are over the gate domain. In the wiring permutation, column `j`'s
+/// wires are at the gate domain's coset by [Domains::shift]`(j)`, so every wire has a point of
+/// its own.
+#[derive(Clone, Debug, CanonicalSerialize)]
 pub struct Domains<F: FftField> {
-    pub wires: MixedRadixEvaluationDomain<F>,
     pub gates: Radix2EvaluationDomain<F>,
+    /// Columns: wires per gate
+    pub width: usize,
 }
 
 impl<F: FftField> Domains<F> {
-    pub fn from_circuit(c: &PlonkCircuit<F>) -> Self {
-        assert_eq!(
-            F::FftParams::SMALL_SUBGROUP_BASE,
-            Some(3),
-            "We require the scalar field's multiplicative group to have a subgroup of order 3"
-        );
-        let n = c.n_gates();
-        let gates = Radix2EvaluationDomain::new(n).expect("gate domain");
-        let wires = MixedRadixEvaluationDomain::new(3 * n).expect("wire domain");
-        assert!(3 * gates.size() == wires.size());
-        let wire_g = wires.group_gen;
-        assert_eq!(wire_g * wire_g * wire_g, gates.group_gen);
-        Domains { gates, wires }
+    pub fn from_circuit(c: &PlonkCircuit<F>, width: usize) -> Self {
+        assert!(width >= GATE_WIDTH, "A gate has {} wires", GATE_WIDTH);
+        let gates = Radix2EvaluationDomain::new(c.n_gates()).expect("gate domain");
+        let domains = Domains { gates, width };
+        domains.quotient();
+        domains
+    }
+
+    /// Wires in all columns
+    pub fn n_wires(&self) -> usize {
+        self.width * self.gates.size()
+    }
+
+    /// The coset of column `column`'s wires: the field's multiplicative generator, to that power,
+    /// so that the columns' cosets are disjoint.
+    pub fn shift(&self, column: usize) -> F {
+        F::multiplicative_generator().pow([column as u64])
+    }
+
+    /// The degree bound of each `P_j`, and of the other polynomials over the gate domain: L_1,
+    /// its partial products, and the public and unit product quotients.
+    pub fn gate_degree(&self) -> usize {
+        self.gates.size() - 1
+    }
+
+    /// The degree bound of the gates quotient: for n gates, the gate constraint has degree
+    /// 3n - 3, and the gate domain's vanishing polynomial n.
+    pub fn gates_quotient_degree(&self) -> usize {
+        (2 * self.gates.size()).saturating_sub(3)
+    }
+
+    /// The degree bound of the wiring quotient: below the wires' (see [Domains::quotient]).
+    pub fn wiring_quotient_degree(&self) -> usize {
+        self.n_wires() - 1
+    }
+
+    /// Every degree bound that proofs over these domains enforce.
+    pub fn degree_bounds(&self) -> [usize; 3] {
+        [
+            self.gate_degree(),
+            self.gates_quotient_degree(),
+            self.wiring_quotient_degree(),
+        ]
+    }
+
+    /// The domain over (a coset of) which the wiring quotient is computed: room for a column
+    /// more than the wires, so larger than the degree of L_1 times the wiring denominator.
+    pub fn quotient(&self) -> Radix2EvaluationDomain<F> {
+        Radix2EvaluationDomain::new(self.n_wires() + self.gates.size()).expect("quotient domain")
+    }
+
+    /// Whether `gates` gates, in `width` columns, fit in the field's domains: the largest is the
+    /// wiring quotient's (see [Domains::quotient]), with a column more than the wires.
+    pub fn fit(gates: usize, width: usize) -> bool {
+        width
+            .checked_add(1)
+            .and_then(|columns| columns.checked_mul(gates))
+            .and_then(usize::checked_next_power_of_two)
+            .and_then(Radix2EvaluationDomain::<F>::new)
+            .is_some()
+    }
+}
+
+// Domains are read from untrusted bytes (in keys and layouts), so the gate domain is checked to
+// be the domain of its (power-of-two) size, and the columns to be enough for the gates and few enough for the
+// quotient domain.
+impl<F: FftField> CanonicalDeserialize for Domains<F> {
+    fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        let gates = Radix2EvaluationDomain::<F>::deserialize(&mut reader)?;
+        let width = usize::deserialize(&mut reader)?;
+        // A power of two, first, so that rebuilding the domain can't overflow
+        let consistent = gates.size().is_power_of_two()
+            && Radix2EvaluationDomain::new(gates.size()) == Some(gates)
+            && width >= GATE_WIDTH
+            && Self::fit(gates.size(), width);
+        if !consistent {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(Domains { gates, width })
+    }
+}
+
+/// A gate: an index into [Domains::gates]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GateId(usize);
+
+impl GateId {
+    pub fn new(i: usize) -> Self {
+        GateId(i)
+    }
+    /// The raw index
+    pub fn get(self) -> usize {
+        self.0
+    }
+    /// This gate's point, in the gate domain
+    pub fn point<F: FftField>(self, domains: &Domains<F>) -> F {
+        domains.gates.element(self.0)
+    }
+    /// This gate's wire in column `slot` (see [WireId::slot])
+    pub fn wire(self, slot: usize) -> WireId {
+        WireId { gate: self, slot }
+    }
+}
+
+/// A wire: a gate, and the column it's in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WireId {
+    gate: GateId,
+    slot: usize,
+}
+
+impl WireId {
+    /// The gate this wire belongs to
+    pub fn gate(self) -> GateId {
+        self.gate
+    }
+    /// Which of its gate's wires this is: 0 and 1 for the inputs, 2 for the output, and any
+    /// further for wires the gate doesn't read
+    pub fn slot(self) -> usize {
+        self.slot
+    }
+    /// This wire's point in the wiring permutation: its gate's, in its column's coset
+    pub fn point<F: FftField>(self, domains: &Domains<F>) -> F {
+        domains.shift(self.slot) * self.gate.point(domains)
+    }
+}
+
+impl CanonicalSerialize for WireId {
+    fn serialize<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        self.gate.0.serialize(&mut writer)?;
+        self.slot.serialize(&mut writer)
+    }
+    fn serialized_size(&self) -> usize {
+        self.gate.0.serialized_size() + self.slot.serialized_size()
+    }
+}
+
+impl CanonicalDeserialize for WireId {
+    fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        let gate = usize::deserialize(&mut reader)?;
+        let slot = usize::deserialize(&mut reader)?;
+        Ok(GateId::new(gate).wire(slot))
+    }
+}
+
+/// `m`, sorted, so that it always serializes the same way.
+fn sorted<K: Ord + Clone, V: Clone>(m: &HashMap<K, V>) -> BTreeMap<K, V> {
+    m.clone().into_iter().collect()
+}
+
+impl<F: FftField> CanonicalSerialize for Instance<F> {
+    fn serialize<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        self.w.serialize(&mut writer)?;
+        self.s.serialize(&mut writer)?;
+        sorted(&self.vars_to_indices).serialize(&mut writer)?;
+        sorted(&self.public_indices).serialize(&mut writer)?;
+        self.domains.serialize(&mut writer)
+    }
+    fn serialized_size(&self) -> usize {
+        self.w.serialized_size()
+            + self.s.serialized_size()
+            + sorted(&self.vars_to_indices).serialized_size()
+            + sorted(&self.public_indices).serialized_size()
+            + self.domains.serialized_size()
+    }
+}
+
+impl<F: FftField> CanonicalDeserialize for Instance<F> {
+    fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        let w = Vec::<DensePolynomial<F>>::deserialize(&mut reader)?;
+        let s = DensePolynomial::deserialize(&mut reader)?;
+        let vars_to_indices = BTreeMap::<u32, Vec<WireId>>::deserialize(&mut reader)?;
+        let public_indices = BTreeMap::<String, WireId>::deserialize(&mut reader)?;
+        let domains = Domains::<F>::deserialize(&mut reader)?;
+        let public_indices: HashMap<String, WireId> = public_indices.into_iter().collect();
+        let columns_ok = w.len() == domains.width;
+        let in_range = |i: &WireId| i.gate.0 < domains.gates.size() && i.slot < domains.width;
+        if !columns_ok
+            || !vars_to_indices
+                .values()
+                .flatten()
+                .chain(public_indices.values())
+                .all(in_range)
+        {
+            return Err(SerializationError::InvalidData);
+        }
+        // Every gate of the circuit's own holds its wires' variables; the rest are padding
+        let n_gates = vars_to_indices
+            .values()
+            .flatten()
+            .map(|i| i.gate.0 + 1)
+            .max()
+            .unwrap_or(0);
+        let input_points = input_points(&public_indices, &domains);
+        let inst = Self {
+            w,
+            s,
+            vars_to_indices: vars_to_indices.into_iter().collect(),
+            precomputed: Arc::new(Precomputed::new(&public_indices, &input_points, &domains)),
+            input_points,
+            public_indices,
+            domains,
+            n_gates,
+        };
+        if !inst.padding_is_inert() {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(inst)
+    }
+}
+
+/// What an [Instance] computes once, when laid out or read, for all of its proofs: per public
+/// column, its public wires and their vanishing polynomial; and tables over the domains.
+#[derive(Clone)]
+pub struct Precomputed<F: FftField> {
+    /// The public columns, in order
+    pub public: Vec<PublicColumn<F>>,
+    /// Vanishes on the gate domain
+    pub gates_vanishing: SparsePolynomial<F>,
+    /// X over the gate domain
+    pub x_evals: Evaluations<F, Radix2EvaluationDomain<F>>,
+    /// X over the quotient domain's coset (see [Domains::quotient])
+    pub x_coset_evals: Vec<F>,
+    /// Each column's coset shift (see [Domains::shift])
+    pub shifts: Vec<F>,
+}
+
+/// A column holding public wires.
+#[derive(Clone)]
+pub struct PublicColumn<F: FftField> {
+    pub column: usize,
+    /// Its public variables, by name, with their points and barycentric weights
+    pub inputs: Vec<(String, (F, F))>,
+    /// The monic polynomial vanishing at its public wires' points
+    pub vanishing: DensePolynomial<F>,
+}
+
+impl<F: FftField> Precomputed<F> {
+    fn new(
+        public_indices: &HashMap<String, WireId>,
+        input_points: &HashMap<String, (F, F)>,
+        domains: &Domains<F>,
+    ) -> Self {
+        let mut by_column = BTreeMap::<usize, Vec<_>>::new();
+        for (var, point) in sorted(input_points) {
+            let column = public_indices[&var].slot();
+            by_column.entry(column).or_default().push((var, point));
+        }
+        let public = by_column
+            .into_iter()
+            .map(|(column, inputs)| {
+                let roots: Vec<F> = inputs.iter().map(|(_, (x, _))| *x).collect();
+                PublicColumn {
+                    column,
+                    vanishing: poly_from_roots(&roots),
+                    inputs,
+                }
+            })
+            .collect();
+        let dom = domains.gates;
+        Precomputed {
+            public,
+            gates_vanishing: dom.vanishing_polynomial(),
+            x_evals: Evaluations::from_vec_and_domain(dom.elements().collect(), dom),
+            x_coset_evals: domains
+                .quotient()
+                .elements()
+                .map(|x| x * F::multiplicative_generator())
+                .collect(),
+            shifts: (0..domains.width).map(|j| domains.shift(j)).collect(),
+        }
     }
+
+    /// The public column `column`, if it is one.
+    fn public_column(&self, column: usize) -> Option<&PublicColumn<F>> {
+        self.public.iter().find(|c| c.column == column)
+    }
+}
+
+/// Wire values in a file, which is memory-mapped and read a column at a time (in chunks) while
+/// proving, so that only the column being interpolated is held in memory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WitnessFile {
+    pub path: PathBuf,
+    /// Offset in the file of each wire's value, gate by gate
+    pub offsets: Vec<u64>,
+    /// Bytes per value
+    pub value_size: usize,
+}
+
+/// Gates read per chunk (and per thread, with the `parallel` feature).
+const WITNESS_CHUNK: usize = 1 << 16;
+
+impl WitnessFile {
+    /// Write `values` (each variable's) to `path`, in plaintext, as [Witness::from_file] reads
+    /// them. Shares are never written out (serializing one panics), so witness files are for
+    /// proving locally.
+    pub fn write_values<F: CanonicalSerialize>(
+        values: &[F],
+        path: impl Into<PathBuf>,
+    ) -> Result<(), SerializationError> {
+        let mut w = BufWriter::new(File::create(path.into())?);
+        for v in values {
+            v.serialize(&mut w)?;
+        }
+        w.flush()?;
+        Ok(())
+    }
+
+    /// The values on wire `slot` of each gate, then zeros, up to `n` of them.
+    pub fn read_column<F: FftField>(
+        &self,
+        slot: usize,
+        n: usize,
+    ) -> Result<Vec<F>, SerializationError> {
+        assert!(slot < GATE_WIDTH);
+        let n_gates = self.offsets.len() / GATE_WIDTH;
+        assert!(n_gates <= n);
+        let file = File::open(&self.path)?;
+        // Safety: the file must not change while mapped.
+        let map = unsafe { Mmap::map(&file)? };
+        let mut values = vec![F::zero(); n];
+        ark_std::cfg_chunks_mut!(values[..n_gates], WITNESS_CHUNK)
+            .zip(ark_std::cfg_chunks!(
+                self.offsets,
+                GATE_WIDTH * WITNESS_CHUNK
+            ))
+            .try_for_each(|(values, offsets)| {
+                for (v, gate) in values.iter_mut().zip(offsets.chunks(GATE_WIDTH)) {
+                    let off = gate[slot] as usize;
+                    let bytes = map
+                        .get(off..off + self.value_size)
+                        .ok_or(SerializationError::InvalidData)?;
+                    *v = F::deserialize(bytes)?;
+                }
+                Ok::<_, SerializationError>(())
+            })?;
+        Ok(values)
+    }
+}
+
+/// The variable on each wire the gates read, gate by gate: products, then sums.
+fn var_layout<F: ark_ff::Field>(c: &PlonkCircuit<F>) -> Vec<u32> {
+    c.prods
+        .iter()
+        .chain(c.sums.iter())
+        .flat_map(|(in0, in1, out)| vec![*in0, *in1, *out])
+        .collect()
+}
+
+/// Each public variable's gate point, and its barycentric weight within its column.
+fn input_points<F: FftField>(
+    public_indices: &HashMap<String, WireId>,
+    domains: &Domains<F>,
+) -> HashMap<String, (F, F)> {
+    let points: Vec<(&String, usize, F)> = public_indices
+        .iter()
+        .map(|(var, i)| (var, i.slot(), i.gate().point(domains)))
+        .collect();
+    let mut weights: Vec<F> = points
+        .iter()
+        .map(|(_, j, xi)| {
+            points
+                .iter()
+                .filter(|(_, k, xk)| k == j && xk != xi)
+                .map(|(_, _, xk)| *xi - xk)
+                .product()
+        })
+        .collect();
+    batch_inversion(&mut weights);
+    points
+        .into_iter()
+        .zip(weights)
+        .map(|((var, _, xi), wi)| (var.clone(), (xi, wi)))
+        .collect()
 }
 
 fn poly_from_roots<F: FftField>(roots: &[F]) -> DensePolynomial<F> {
@@ -308,24 +942,23 @@ fn poly_from_roots<F: FftField>(roots: &[F]) -> DensePolynomial<F> {
     )
 }
 
-
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::relations::structured::assign;
     type F = ark_bls12_377::Fr;
 
     #[test]
     fn domains() {
         for steps in &[1, 15, 1024, 12341] {
             let c = PlonkCircuit::<F>::new_squaring_circuit(*steps, None);
-            let _d = Domains::from_circuit(&c);
+            let _d = Domains::from_circuit(&c, GATE_WIDTH);
         }
 
         // should still work, even with witness
         for steps in &[1, 15, 1024, 12341] {
             let c = PlonkCircuit::<F>::new_squaring_circuit(*steps, Some(F::from(0u64)));
-            let _d = Domains::from_circuit(&c);
+            let _d = Domains::from_circuit(&c, GATE_WIDTH);
         }
     }
 
@@ -333,20 +966,153 @@ mod tests {
     fn circuit_polys() {
         for steps in &[1, 3] {
             let c = PlonkCircuit::<F>::new_squaring_circuit(*steps, None);
-            let polys = CircuitLayout::from_circuit(&c);
+            let polys = Instance::from_circuit(&c);
             polys.check_connection_degree(3);
         }
     }
     #[test]
     fn circuit_check() {
         for steps in &[1, 3] {
-            let start = F::from(2u64);
-            let c = PlonkCircuit::<F>::new_squaring_circuit(*steps, Some(start));
-            let res = (0..*steps).fold(start, |a, _| a * a);
-            let public: HashMap<String, F> = vec![("out".to_owned(), res)].into_iter().collect();
-            let polys = CircuitLayout::from_circuit(&c);
-            polys.check_connection_degree(3);
-            polys.check(&public);
+            for width in &[GATE_WIDTH, 5] {
+                let start = F::from(2u64);
+                let c = PlonkCircuit::<F>::new_squaring_circuit(*steps, Some(start));
+                let res = (0..*steps).fold(start, |a, _| a * a);
+                let public: HashMap<String, F> =
+                    vec![("out".to_owned(), res)].into_iter().collect();
+                let polys = Instance::from_circuit_with_width(&c, *width);
+                assert_eq!(polys.w.len(), *width);
+                polys.check_connection_degree(3);
+                Witness::from_circuit(&polys, &c).check(&polys, &public);
+            }
+        }
+    }
+    #[test]
+    fn domains_deserialize_checked() {
+        let c = PlonkCircuit::<F>::new_squaring_circuit(3, None);
+        let d = Domains::from_circuit(&c, GATE_WIDTH);
+        let mut bytes = Vec::new();
+        d.serialize(&mut bytes).unwrap();
+        assert!(Domains::<F>::deserialize(&bytes[..]).is_ok());
+        // Too few columns for a gate, and too many for any domain
+        for width in &[GATE_WIDTH - 1, usize::MAX] {
+            let mut bad = Vec::new();
+            d.gates.serialize(&mut bad).unwrap();
+            width.serialize(&mut bad).unwrap();
+            assert!(Domains::<F>::deserialize(&bad[..]).is_err());
+        }
+        // A gate domain too large to rebuild
+        let mut bad = bytes.clone();
+        bad[..8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(Domains::<F>::deserialize(&bad[..]).is_err());
+    }
+    #[test]
+    fn wire_points() {
+        let c = PlonkCircuit::<F>::new_squaring_circuit(3, None);
+        let d = Domains::from_circuit(&c, 5);
+        let mut points = Vec::new();
+        for g in 0..d.gates.size() {
+            let gate = GateId::new(g);
+            for slot in 0..d.width {
+                let wire = gate.wire(slot);
+                assert_eq!(wire.gate(), gate);
+                assert_eq!(wire.slot(), slot);
+                points.push(wire.point(&d));
+            }
+            assert_eq!(gate.point(&d), gate.wire(0).point(&d));
+        }
+        // Every wire's point is its own
+        let n = points.len();
+        points.sort_unstable();
+        points.dedup();
+        assert_eq!(points.len(), n);
+    }
+    #[test]
+    fn inputs_poly_barycentric() {
+        let mut c = PlonkCircuit::<F>::new_squaring_circuit(3, Some(F::from(2u64)));
+        c.publicize_var(0, "in".to_owned());
+        let polys = Instance::from_circuit(&c);
+        let public: HashMap<String, F> = vec![
+            ("in".to_owned(), F::from(2u64)),
+            ("out".to_owned(), F::from(256u64)),
+        ]
+        .into_iter()
+        .collect();
+        for j in polys.public_columns() {
+            let v = polys.inputs_poly(&public, j);
+            let z = polys.vanishing_poly_on_inputs(j);
+            for x in &[F::from(7u64), F::from(12345u64)] {
+                assert_eq!(polys.eval_inputs_poly(&public, j, *x), v.evaluate(x));
+                assert_eq!(polys.eval_vanishing_on_inputs(j, *x), z.evaluate(x));
+            }
+        }
+        for (var, (x, _)) in &polys.input_points {
+            let j = polys.public_indices[var].slot();
+            assert_eq!(polys.eval_inputs_poly(&public, j, *x), public[var]);
+            assert_eq!(polys.eval_vanishing_on_inputs(j, *x), F::from(0u64));
+        }
+    }
+    #[test]
+    fn precomputed_survives_serialization() {
+        let mut c = PlonkCircuit::<F>::new_squaring_circuit(3, None);
+        c.publicize_var(0, "in".to_owned());
+        let inst = Instance::from_circuit(&c);
+        let pre = &inst.precomputed;
+        let mut columns: Vec<usize> = inst.public_indices.values().map(|i| i.slot()).collect();
+        columns.sort_unstable();
+        columns.dedup();
+        assert_eq!(inst.public_columns(), columns);
+        assert_eq!(
+            pre.public.iter().map(|c| c.inputs.len()).sum::<usize>(),
+            inst.public_indices.len()
+        );
+        for column in &pre.public {
+            for (_, (x, _)) in &column.inputs {
+                assert_eq!(column.vanishing.evaluate(x), F::from(0u64));
+            }
+        }
+        let x = F::from(7u64);
+        let z = inst.domains.gates.evaluate_vanishing_polynomial(x);
+        assert_eq!(pre.gates_vanishing.evaluate(&x), z);
+        assert_eq!(pre.shifts.len(), inst.domains.width);
+        assert_eq!(pre.x_coset_evals.len(), inst.domains.quotient().size());
+
+        let mut bytes = Vec::new();
+        inst.serialize(&mut bytes).unwrap();
+        let read = Instance::<F>::deserialize(&bytes[..]).unwrap();
+        assert_eq!(read.public_columns(), inst.public_columns());
+        for (a, b) in read.precomputed.public.iter().zip(&pre.public) {
+            assert_eq!((&a.inputs, &a.vanishing), (&b.inputs, &b.vanishing));
         }
+        assert_eq!(read.precomputed.x_evals.evals, pre.x_evals.evals);
+        assert_eq!(read.precomputed.x_coset_evals, pre.x_coset_evals);
+    }
+    #[test]
+    fn padding() {
+        let c = PlonkCircuit::<F>::unpadded_squaring_circuit(5);
+        let start = F::from(2u64);
+        let assignment = assign(&c, &|_: &str| Some(start)).unwrap();
+        let c = c.with_assignment(assignment);
+        let inst = Instance::from_circuit(&c);
+        assert_eq!((inst.n_gates, inst.padded_gates()), (5, 8));
+        assert!(inst.padding_is_inert());
+        let out = (0..5).fold(start, |a, _| a * a);
+        let public = vec![("out".to_owned(), out)].into_iter().collect();
+        Witness::from_circuit(&inst, &c).check(&inst, &public);
+
+        let bytes = |inst: &Instance<F>| {
+            let mut bytes = Vec::new();
+            inst.serialize(&mut bytes).unwrap();
+            bytes
+        };
+        let read = Instance::<F>::deserialize(&bytes(&inst)[..]).unwrap();
+        assert_eq!(read.n_gates, 5);
+        // A padding gate made a sum, or wired to a variable's wire, is rejected
+        let mut sum = inst.clone();
+        sum.s += &DensePolynomial::from_coefficients_vec(vec![F::from(1u64)]);
+        assert!(!sum.padding_is_inert());
+        assert!(Instance::<F>::deserialize(&bytes(&sum)[..]).is_err());
+        let mut wired = inst.clone();
+        wired.w = wired.w.iter().map(|_| wired.w[0].clone()).collect();
+        assert!(Instance::<F>::deserialize(&bytes(&wired)[..]).is_err());
     }
 }
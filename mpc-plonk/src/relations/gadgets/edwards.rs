@@ -0,0 +1,360 @@
+//! Points of a twisted Edwards curve embedded in the circuit's field, such as the one over
+//! Bls12-377's scalar field (see [Edwards::ed_on_bls12_377]): for Pedersen commitments, and
+//! for verifying signatures.
+//!
+//! Its addition, `(x1, y1) + (x2, y2) =`
+//!
+//! ```text
+//! ((x1 y2 + y1 x2) / (1 + d x1 x2 y1 y2), (y1 y2 - a x1 x2) / (1 - d x1 x2 y1 y2))
+//! ```
+//!
+//! is complete: its denominators are never zero, so it adds any points, doubles too, and the
+//! identity `(0, 1)` is no exception. No gate divides, so the prover gives each sum, and the
+//! circuit checks it against the numerators and denominators (see [Edwards::add_inputs] and
+//! [Edwards::scalar_mul_inputs], which compute sums in any field, shared ones too).
+//!
+//! ```
+//! use ark_bls12_377::Fr;
+//! use ark_ec::{AffineCurve, ProjectiveCurve};
+//! use ark_ed_on_bls12_377::EdwardsAffine;
+//! use mpc_plonk::relations::dsl::CircuitBuilder;
+//! use mpc_plonk::relations::gadgets::edwards::Edwards;
+//! use mpc_plonk::relations::gadgets::{bit_inputs, Gadgets};
+//! use mpc_plonk::relations::structured::assign;
+//!
+//! // `k G`, for a scalar `k` under 2^8
+//! let curve = Edwards::ed_on_bls12_377();
+//! let b = CircuitBuilder::<Fr>::new();
+//! let g = Gadgets::new(&b);
+//! let k = g.decompose_to_bits("k", b.wire("k"), 8);
+//! let base = curve.input(&g, "g");
+//! let kg = curve.scalar_mul(&g, "kg", &k, &base);
+//! b.bind("x", kg.x);
+//! b.bind("y", kg.y);
+//! b.publicize("x");
+//! b.publicize("y");
+//! let public = g.public_values::<Fr>();
+//! let c = b.finish();
+//!
+//! let generator = EdwardsAffine::prime_subgroup_generator();
+//! let point = (generator.x, generator.y);
+//! let bits: Vec<Fr> = (0..8).map(|j| Fr::from((200u64 >> j) & 1)).collect();
+//! let mut inputs = public.clone();
+//! inputs.extend(bit_inputs("k", 200, 8));
+//! inputs.insert("k".to_owned(), Fr::from(200u64));
+//! inputs.extend(curve.input_values("g", point));
+//! let ((x, y), witness) = curve.scalar_mul_inputs("kg", &bits, point);
+//! inputs.extend(witness);
+//! let values = assign(&c, &inputs).unwrap().0;
+//! let expected = generator.mul(200u64).into_affine();
+//! for (var, name) in &c.pub_vars {
+//!     let v = match name.as_str() {
+//!         "x" => x,
+//!         "y" => y,
+//!         name => public[name],
+//!     };
+//!     assert_eq!(values[*var as usize], v);
+//! }
+//! assert_eq!((x, y), (expected.x, expected.y));
+//! ```
+use ark_ec::TEModelParameters;
+use ark_ff::{BigInteger, Field, PrimeField};
+use num_bigint::BigUint;
+use std::collections::HashMap;
+
+use super::super::dsl::Wire;
+use super::Gadgets;
+
+/// A twisted Edwards curve `a x^2 + y^2 = 1 + d x^2 y^2`, by its coefficients in the field `F`.
+///
+/// For the curve to be complete, `a` must be a square and `d` not. In circuits, `F` is the
+/// circuit's (base) field; for a shared field's host computations, the coefficients are public
+/// values of it (see [Edwards::map]).
+#[derive(Clone, Copy, Debug)]
+pub struct Edwards<F> {
+    pub a: F,
+    pub d: F,
+}
+
+/// A point of an [Edwards] curve, in a circuit.
+#[derive(Clone, Copy)]
+pub struct EdwardsPoint<'a, F: Field> {
+    pub x: Wire<'a, F>,
+    pub y: Wire<'a, F>,
+}
+
+impl<F: Field> Edwards<F> {
+    /// The curve of `P`.
+    pub fn of<P: TEModelParameters<BaseField = F>>() -> Self {
+        Self {
+            a: P::COEFF_A,
+            d: P::COEFF_D,
+        }
+    }
+}
+
+impl Edwards<ark_bls12_377::Fr> {
+    /// The curve over Bls12-377's scalar field of [ark_ed_on_bls12_377].
+    pub fn ed_on_bls12_377() -> Self {
+        Self::of::<ark_ed_on_bls12_377::EdwardsParameters>()
+    }
+}
+
+impl<F: Copy> Edwards<F> {
+    /// The coefficients, mapped by `f`: say, to public values of a shared field.
+    pub fn map<G>(&self, f: impl Fn(F) -> G) -> Edwards<G> {
+        Edwards {
+            a: f(self.a),
+            d: f(self.d),
+        }
+    }
+}
+
+impl<C: PrimeField> Edwards<C> {
+    /// A point that the prover gives as the inputs `{name}_x` and `{name}_y`, checked to be on
+    /// the curve: nine gates.
+    pub fn input<'a, F: Field>(&self, gadgets: &Gadgets<'a, F>, name: &str) -> EdwardsPoint<'a, F> {
+        let b = gadgets.builder;
+        let p = EdwardsPoint {
+            x: b.wire(&format!("{}_x", name)),
+            y: b.wire(&format!("{}_y", name)),
+        };
+        let (x2, y2) = (p.x * p.x, p.y * p.y);
+        let lhs = self.constant(gadgets, self.a) * x2 + y2;
+        let rhs = gadgets.one() + self.constant(gadgets, self.d) * (x2 * y2);
+        gadgets.assert_equal(lhs, rhs);
+        p
+    }
+
+    /// `p + q`, which the prover gives as the inputs `{name}_x` and `{name}_y` (see
+    /// [Edwards::add_inputs]): nineteen gates. `p` and `q` must be on the curve, as inputs and
+    /// sums of them are.
+    pub fn add<'a, F: Field>(
+        &self,
+        gadgets: &Gadgets<'a, F>,
+        name: &str,
+        p: &EdwardsPoint<'a, F>,
+        q: &EdwardsPoint<'a, F>,
+    ) -> EdwardsPoint<'a, F> {
+        let b = gadgets.builder;
+        let (one, minus_one) = (gadgets.one(), gadgets.minus_one());
+        let sum = EdwardsPoint {
+            x: b.wire(&format!("{}_x", name)),
+            y: b.wire(&format!("{}_y", name)),
+        };
+        let (x1y2, y1x2) = (p.x * q.y, p.y * q.x);
+        // d x1 x2 y1 y2
+        let t = self.constant(gadgets, self.d) * (x1y2 * y1x2);
+        gadgets.assert_equal(sum.x * (one + t), x1y2 + y1x2);
+        let y_numerator = p.y * q.y + minus_one * (self.constant(gadgets, self.a) * (p.x * q.x));
+        gadgets.assert_equal(sum.y * (one + minus_one * t), y_numerator);
+        sum
+    }
+
+    /// `p` if `bit` is one, and `q` if it is zero (see [Gadgets::select]).
+    pub fn select<'a, F: Field>(
+        &self,
+        gadgets: &Gadgets<'a, F>,
+        bit: Wire<'a, F>,
+        p: &EdwardsPoint<'a, F>,
+        q: &EdwardsPoint<'a, F>,
+    ) -> EdwardsPoint<'a, F> {
+        EdwardsPoint {
+            x: gadgets.select(bit, p.x, q.x),
+            y: gadgets.select(bit, p.y, q.y),
+        }
+    }
+
+    /// `k p`, for the bits of `k`, least significant first (say, from
+    /// [Gadgets::decompose_to_bits]), by double-and-add from the most significant bit: a
+    /// doubling, an addition and a selection, 46 gates, a bit.
+    ///
+    /// The prover gives the doublings and additions as points `{name}_double_j` and
+    /// `{name}_add_j` (see [Edwards::scalar_mul_inputs]).
+    pub fn scalar_mul<'a, F: Field>(
+        &self,
+        gadgets: &Gadgets<'a, F>,
+        name: &str,
+        bits: &[Wire<'a, F>],
+        p: &EdwardsPoint<'a, F>,
+    ) -> EdwardsPoint<'a, F> {
+        let (last, rest) = bits.split_last().expect("A scalar has at least one bit");
+        // The top bit selects p or the identity, (0, 1)
+        let one = gadgets.one();
+        let top = EdwardsPoint {
+            x: *last * p.x,
+            y: gadgets.select(*last, p.y, one),
+        };
+        rest.iter().enumerate().rev().fold(top, |acc, (j, bit)| {
+            let double = self.add(gadgets, &format!("{}_double_{}", name, j), &acc, &acc);
+            let sum = self.add(gadgets, &format!("{}_add_{}", name, j), &double, p);
+            self.select(gadgets, *bit, &sum, &double)
+        })
+    }
+
+    /// Check that `p` and `q` are the same point.
+    pub fn assert_equal<'a, F: Field>(
+        &self,
+        gadgets: &Gadgets<'a, F>,
+        p: &EdwardsPoint<'a, F>,
+        q: &EdwardsPoint<'a, F>,
+    ) {
+        gadgets.assert_equal(p.x, q.x);
+        gadgets.assert_equal(p.y, q.y);
+    }
+
+    /// The coefficient `c`, as a public input.
+    fn constant<'a, F: Field>(&self, gadgets: &Gadgets<'a, F>, c: C) -> Wire<'a, F> {
+        gadgets.big_constant(&BigUint::from_bytes_le(&c.into_repr().to_bytes_le()))
+    }
+}
+
+impl<G: Field> Edwards<G> {
+    /// The inputs that [Edwards::input] reads for `p`.
+    pub fn input_values(&self, name: &str, p: (G, G)) -> HashMap<String, G> {
+        let mut inputs = HashMap::new();
+        inputs.insert(format!("{}_x", name), p.0);
+        inputs.insert(format!("{}_y", name), p.1);
+        inputs
+    }
+
+    /// `p + q`, in any field: with values, or shared.
+    pub fn sum(&self, p: (G, G), q: (G, G)) -> (G, G) {
+        let (x1y2, y1x2) = (p.0 * q.1, p.1 * q.0);
+        let t = self.d * x1y2 * y1x2;
+        let x = (x1y2 + y1x2) * (G::one() + t).inverse().expect("Incomplete addition");
+        let y = (p.1 * q.1 - self.a * p.0 * q.0)
+            * (G::one() - t).inverse().expect("Incomplete addition");
+        (x, y)
+    }
+
+    /// `p + q`, and the inputs that [Edwards::add] reads for it.
+    pub fn add_inputs(&self, name: &str, p: (G, G), q: (G, G)) -> ((G, G), HashMap<String, G>) {
+        let sum = self.sum(p, q);
+        (sum, self.input_values(name, sum))
+    }
+
+    /// `k p`, for the bits of `k` (which may be shared), and the inputs that
+    /// [Edwards::scalar_mul] reads for it.
+    pub fn scalar_mul_inputs(
+        &self,
+        name: &str,
+        bits: &[G],
+        p: (G, G),
+    ) -> ((G, G), HashMap<String, G>) {
+        let (last, rest) = bits.split_last().expect("A scalar has at least one bit");
+        let select =
+            |bit: G, p: (G, G), q: (G, G)| (q.0 + bit * (p.0 - q.0), q.1 + bit * (p.1 - q.1));
+        let mut acc = select(*last, p, (G::zero(), G::one()));
+        let mut inputs = HashMap::new();
+        for (j, bit) in rest.iter().enumerate().rev() {
+            let (double, double_inputs) =
+                self.add_inputs(&format!("{}_double_{}", name, j), acc, acc);
+            let (sum, sum_inputs) = self.add_inputs(&format!("{}_add_{}", name, j), double, p);
+            inputs.extend(double_inputs);
+            inputs.extend(sum_inputs);
+            acc = select(*bit, sum, double);
+        }
+        (acc, inputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::run;
+    use super::*;
+    use crate::relations::dsl::CircuitBuilder;
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_ed_on_bls12_377::{EdwardsAffine, EdwardsProjective};
+    use ark_ff::Zero;
+    use ark_std::{test_rng, UniformRand};
+    type F = ark_bls12_377::Fr;
+
+    fn coordinates(p: EdwardsAffine) -> (F, F) {
+        (p.x, p.y)
+    }
+
+    #[test]
+    fn add() {
+        let curve = Edwards::ed_on_bls12_377();
+        let b = CircuitBuilder::new();
+        let g = Gadgets::new(&b);
+        let (p, q) = (curve.input(&g, "p"), curve.input(&g, "q"));
+        let sum = curve.add(&g, "sum", &p, &q);
+        b.bind("x", sum.x);
+        b.bind("y", sum.y);
+        b.publicize("x");
+        b.publicize("y");
+        let public = g.public_values();
+        let c = b.finish();
+
+        let rng = &mut test_rng();
+        let random = EdwardsProjective::rand(rng).into_affine();
+        let other = EdwardsProjective::rand(rng).into_affine();
+        let identity = EdwardsAffine::zero();
+        for (p, q) in &[
+            (random, other),
+            (random, random),
+            (random, identity),
+            (identity, identity),
+            (random, -random),
+        ] {
+            let mut inputs = curve.input_values("p", coordinates(*p));
+            inputs.extend(curve.input_values("q", coordinates(*q)));
+            let (sum, sum_inputs) = curve.add_inputs("sum", coordinates(*p), coordinates(*q));
+            inputs.extend(sum_inputs.clone());
+            let (outputs, ok) = run(&c, &public, inputs.clone());
+            assert!(ok);
+            assert_eq!(sum, coordinates(*p + q));
+            assert_eq!((outputs["x"], outputs["y"]), sum);
+            // Another sum, and a point off the curve
+            inputs.extend(curve.input_values("sum", coordinates(*p)));
+            assert_eq!(!run(&c, &public, inputs).1, *q != identity);
+        }
+        let mut inputs = curve.input_values("p", (F::from(1u64), F::from(2u64)));
+        inputs.extend(curve.input_values("q", coordinates(identity)));
+        inputs.extend(curve.input_values("sum", (F::from(1u64), F::from(2u64))));
+        assert!(!run(&c, &public, inputs).1);
+    }
+
+    #[test]
+    fn scalar_mul() {
+        let curve = Edwards::ed_on_bls12_377();
+        let n = <ark_ed_on_bls12_377::Fr as PrimeField>::size_in_bits();
+        let b = CircuitBuilder::new();
+        let g = Gadgets::new(&b);
+        let k = g.bits("k", n);
+        let p = curve.input(&g, "p");
+        let kp = curve.scalar_mul(&g, "kp", &k, &p);
+        b.bind("x", kp.x);
+        b.bind("y", kp.y);
+        b.publicize("x");
+        b.publicize("y");
+        let public = g.public_values();
+        let c = b.finish();
+
+        let rng = &mut test_rng();
+        let p = EdwardsProjective::rand(rng).into_affine();
+        let scalars = [
+            ark_ed_on_bls12_377::Fr::from(0u64),
+            ark_ed_on_bls12_377::Fr::from(1u64),
+            ark_ed_on_bls12_377::Fr::from(6u64),
+            ark_ed_on_bls12_377::Fr::rand(rng),
+        ];
+        for k in &scalars {
+            let bits: Vec<F> = k.into_repr().to_bits_le()[..n]
+                .iter()
+                .map(|b| F::from(*b))
+                .collect();
+            let mut inputs: HashMap<String, F> =
+                (0..n).map(|j| (format!("k_bit_{}", j), bits[j])).collect();
+            inputs.extend(curve.input_values("p", coordinates(p)));
+            let (kp, kp_inputs) = curve.scalar_mul_inputs("kp", &bits, coordinates(p));
+            inputs.extend(kp_inputs);
+            let (outputs, ok) = run(&c, &public, inputs);
+            assert!(ok);
+            assert_eq!(kp, coordinates(p.mul(k.into_repr()).into_affine()));
+            assert_eq!((outputs["x"], outputs["y"]), kp);
+        }
+    }
+}
@@ -0,0 +1,292 @@
+//! Merkle membership: a sub-circuit that checks that a leaf is in a Merkle tree with a given
+//! root, for any hash gadget and arity, and the host-side tree that gives its inputs.
+//!
+//! The path is an input: at each level, all the children of the node on the path, and a
+//! one-hot selector of which child that node is. A path for shared leaves is shared too (see
+//! [MerkleTree::path_inputs]), so the parties can prove that a secret leaf, say a credential, is
+//! in a public tree, without revealing which it is.
+//!
+//! ```
+//! use ark_bls12_377::Fr;
+//! use mpc_plonk::relations::dsl::CircuitBuilder;
+//! use mpc_plonk::relations::gadgets::merkle::{Merkle, PoseidonHash};
+//! use mpc_plonk::relations::gadgets::Gadgets;
+//! use mpc_plonk::relations::structured::assign;
+//!
+//! // A leaf of a binary tree of depth 2
+//! let merkle = Merkle::new(PoseidonHash::default(), 2, 2);
+//! let b = CircuitBuilder::<Fr>::new();
+//! let g = Gadgets::new(&b);
+//! b.bind("root", merkle.path_root(&g, "path", b.wire("leaf")));
+//! b.publicize("root");
+//! let public = g.public_values::<Fr>();
+//! let c = b.finish();
+//!
+//! let leaves: Vec<Fr> = (10..14u64).map(Fr::from).collect();
+//! let tree = merkle.tree(&leaves);
+//! let mut inputs = public.clone();
+//! inputs.extend(tree.path_inputs("path", 2));
+//! inputs.insert("leaf".to_owned(), leaves[2]);
+//! let values = assign(&c, &inputs).unwrap().0;
+//! for (var, name) in &c.pub_vars {
+//!     let expected = public.get(name).copied().unwrap_or_else(|| tree.root());
+//!     assert_eq!(values[*var as usize], expected);
+//! }
+//! ```
+use ark_ff::{Field, PrimeField};
+use std::collections::HashMap;
+
+use super::super::dsl::Wire;
+use super::Gadgets;
+use crate::util::{poseidon_hash, PoseidonParameters};
+
+/// A hash of a node's children, both of values and in a circuit.
+pub trait MerkleHash<F: Field> {
+    /// The hash of `children`'s values, which may be shared.
+    fn hash(&self, children: &[F]) -> F;
+    /// The hash of `children`, in the circuit that `gadgets` add to. Its constants must be
+    /// among [Gadgets::public_values].
+    fn gadget<'a>(&self, gadgets: &Gadgets<'a, F>, children: &[Wire<'a, F>]) -> Wire<'a, F>;
+}
+
+/// The Poseidon hash: [poseidon_hash] of values, and [Gadgets::poseidon] in circuits.
+pub struct PoseidonHash<F> {
+    params: PoseidonParameters<F>,
+}
+
+impl<F> PoseidonHash<F> {
+    /// With `params`, which must be [PoseidonParameters::new]'s, for the circuit to agree: for a
+    /// shared field, its base field's, as public values (see [PoseidonParameters::map]).
+    pub fn new(params: PoseidonParameters<F>) -> Self {
+        Self { params }
+    }
+}
+
+impl<F: PrimeField> Default for PoseidonHash<F> {
+    fn default() -> Self {
+        Self::new(PoseidonParameters::new())
+    }
+}
+
+impl<F: PrimeField> MerkleHash<F> for PoseidonHash<F> {
+    fn hash(&self, children: &[F]) -> F {
+        poseidon_hash(&self.params, children)
+    }
+
+    fn gadget<'a>(&self, gadgets: &Gadgets<'a, F>, children: &[Wire<'a, F>]) -> Wire<'a, F> {
+        gadgets.poseidon(children)
+    }
+}
+
+/// Merkle trees of `depth` levels over `arity` children a node, hashed by `hash`.
+pub struct Merkle<H> {
+    pub hash: H,
+    pub arity: usize,
+    pub depth: usize,
+}
+
+impl<H> Merkle<H> {
+    pub fn new(hash: H, arity: usize, depth: usize) -> Self {
+        assert!(arity >= 2, "A node has at least two children");
+        assert!(
+            arity.checked_pow(depth as u32).is_some(),
+            "Too many leaves to address"
+        );
+        Self { hash, arity, depth }
+    }
+
+    /// The number of leaves: `arity^depth`.
+    pub fn capacity(&self) -> usize {
+        self.arity.pow(self.depth as u32)
+    }
+
+    /// The root of the tree that the path inputs named `name` and `leaf` are in: a hash, and
+    /// `5 arity + 2` more gates, a level.
+    ///
+    /// At level `l` (the leaves' is zero), the inputs are the children `{name}_{l}_child_j` of
+    /// the node on the path, and its selector bits `{name}_{l}_is_j`, of which it checks that
+    /// exactly one is one, and that the node is the child it selects. See
+    /// [MerkleTree::path_inputs].
+    pub fn path_root<'a, F: Field>(
+        &self,
+        gadgets: &Gadgets<'a, F>,
+        name: &str,
+        leaf: Wire<'a, F>,
+    ) -> Wire<'a, F>
+    where
+        H: MerkleHash<F>,
+    {
+        let b = gadgets.builder;
+        (0..self.depth).fold(leaf, |node, l| {
+            let input = |kind: &str, j: usize| b.wire(&format!("{}_{}_{}_{}", name, l, kind, j));
+            let children: Vec<_> = (0..self.arity).map(|j| input("child", j)).collect();
+            let selectors: Vec<_> = (0..self.arity).map(|j| input("is", j)).collect();
+            for s in &selectors {
+                gadgets.assert_bit(*s);
+            }
+            let sum = |terms: Vec<Wire<'a, F>>| terms.into_iter().reduce(|x, y| x + y).unwrap();
+            gadgets.assert_equal(sum(selectors.clone()), gadgets.one());
+            let selected = selectors.iter().zip(&children).map(|(s, c)| *s * *c);
+            gadgets.assert_equal(sum(selected.collect()), node);
+            self.hash.gadget(gadgets, &children)
+        })
+    }
+
+    /// Check that `leaf` is in the tree whose root is `root`, by the path inputs named `name`
+    /// (see [Merkle::path_root]).
+    pub fn assert_member<'a, F: Field>(
+        &self,
+        gadgets: &Gadgets<'a, F>,
+        name: &str,
+        leaf: Wire<'a, F>,
+        root: Wire<'a, F>,
+    ) where
+        H: MerkleHash<F>,
+    {
+        gadgets.assert_equal(self.path_root(gadgets, name, leaf), root);
+    }
+
+    /// The tree over `leaves`, which may be shared, padded with zeros to [Merkle::capacity].
+    pub fn tree<F: Field>(&self, leaves: &[F]) -> MerkleTree<F>
+    where
+        H: MerkleHash<F>,
+    {
+        let capacity = self.capacity();
+        assert!(
+            leaves.len() <= capacity,
+            "{} leaves, but at most {} fit",
+            leaves.len(),
+            capacity
+        );
+        // Zeros shared like the leaves: a tree is all shared, or all public
+        let zero = leaves.iter().map(|x| *x * F::zero()).sum();
+        let mut level = leaves.to_vec();
+        level.resize(capacity, zero);
+        let mut levels = vec![level];
+        for _ in 0..self.depth {
+            let below = levels.last().unwrap();
+            let level = below
+                .chunks(self.arity)
+                .map(|c| self.hash.hash(c))
+                .collect();
+            levels.push(level);
+        }
+        MerkleTree {
+            arity: self.arity,
+            levels,
+        }
+    }
+}
+
+/// A Merkle tree's nodes (see [Merkle::tree]).
+pub struct MerkleTree<F> {
+    arity: usize,
+    /// By level, from the leaves up to the root
+    levels: Vec<Vec<F>>,
+}
+
+impl<F: Field> MerkleTree<F> {
+    pub fn root(&self) -> F {
+        self.levels.last().unwrap()[0]
+    }
+
+    pub fn leaves(&self) -> &[F] {
+        &self.levels[0]
+    }
+
+    /// The inputs named `name` of the path from the leaf at `index` up to the root, that
+    /// [Merkle::path_root] reads.
+    ///
+    /// The parties, who pass `index`, must all know it; the verifier doesn't learn it. For a
+    /// shared tree the selectors are shared too, as every wire of the proof is.
+    pub fn path_inputs(&self, name: &str, index: usize) -> HashMap<String, F> {
+        assert!(
+            index < self.leaves().len(),
+            "No leaf {} of {}",
+            index,
+            self.leaves().len()
+        );
+        let zero = self.leaves()[index] * F::zero();
+        let mut inputs = HashMap::new();
+        let mut i = index;
+        for (l, level) in self.levels[..self.levels.len() - 1].iter().enumerate() {
+            let first = i - i % self.arity;
+            for j in 0..self.arity {
+                let selected = if first + j == i { F::one() } else { F::zero() };
+                inputs.insert(format!("{}_{}_child_{}", name, l, j), level[first + j]);
+                inputs.insert(format!("{}_{}_is_{}", name, l, j), zero + selected);
+            }
+            i /= self.arity;
+        }
+        inputs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::run;
+    use super::*;
+    use crate::relations::dsl::CircuitBuilder;
+    type F = ark_bls12_377::Fr;
+
+    #[test]
+    fn membership() {
+        for (arity, depth, n_leaves) in &[(2, 3, 5), (3, 2, 9), (4, 1, 2), (2, 0, 1)] {
+            let merkle = Merkle::new(PoseidonHash::default(), *arity, *depth);
+            let b = CircuitBuilder::new();
+            let g = Gadgets::new(&b);
+            merkle.assert_member(&g, "path", b.wire("leaf"), b.wire("root"));
+            b.publicize("root");
+            let mut public = g.public_values();
+            let c = b.finish();
+
+            let leaves: Vec<F> = (0..*n_leaves).map(|i| F::from(i as u64 + 100)).collect();
+            let tree = merkle.tree(&leaves);
+            public.insert("root".to_owned(), tree.root());
+            let inputs = |index: usize, leaf: F| {
+                let mut inputs = tree.path_inputs("path", index);
+                inputs.insert("leaf".to_owned(), leaf);
+                inputs.insert("root".to_owned(), tree.root());
+                inputs
+            };
+            for index in 0..merkle.capacity() {
+                let leaf = tree.leaves()[index];
+                assert!(run(&c, &public, inputs(index, leaf)).1, "leaf {}", index);
+            }
+            // A leaf that isn't in the tree, or is elsewhere than the path says
+            assert!(!run(&c, &public, inputs(0, F::from(7u64))).1);
+            if *depth > 0 {
+                assert!(!run(&c, &public, inputs(0, tree.leaves()[1])).1);
+            }
+        }
+    }
+
+    #[test]
+    fn selectors_are_one_hot() {
+        let merkle = Merkle::new(PoseidonHash::default(), 2, 1);
+        let b = CircuitBuilder::new();
+        let g = Gadgets::new(&b);
+        merkle.assert_member(&g, "path", b.wire("leaf"), b.wire("root"));
+        let public = g.public_values();
+        let c = b.finish();
+        let leaves = [F::from(3u64), F::from(5u64)];
+        let tree = merkle.tree(&leaves);
+        let tampered = |selectors: [u64; 2], leaf: F| {
+            let mut inputs = tree.path_inputs("path", 0);
+            inputs.insert("path_0_is_0".to_owned(), F::from(selectors[0]));
+            inputs.insert("path_0_is_1".to_owned(), F::from(selectors[1]));
+            inputs.insert("leaf".to_owned(), leaf);
+            inputs.insert("root".to_owned(), tree.root());
+            inputs
+        };
+        assert!(run(&c, &public, tampered([1, 0], leaves[0])).1);
+        // No child, both, or a blend of them
+        assert!(!run(&c, &public, tampered([0, 0], F::from(0u64))).1);
+        assert!(!run(&c, &public, tampered([1, 1], leaves[0] + leaves[1])).1);
+        let half = F::from(2u64).inverse().unwrap();
+        let mut inputs = tampered([0, 0], (leaves[0] + leaves[1]) * half);
+        inputs.insert("path_0_is_0".to_owned(), half);
+        inputs.insert("path_0_is_1".to_owned(), half);
+        assert!(!run(&c, &public, inputs).1);
+    }
+}
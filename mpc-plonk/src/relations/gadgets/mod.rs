@@ -0,0 +1,470 @@
+//! Gadgets for circuits written with a [CircuitBuilder]: bits, range checks, comparisons,
+//! selection, and the Poseidon hash. Merkle membership is in [merkle], arithmetic modulo a
+//! foreign prime in [nonnative], and an embedded curve's points in [edwards].
+//!
+//! Circuits have only sums and products, so a gadget's constants are public inputs, and its
+//! checks are public wires that must be zero: [Gadgets::public_values] gives both, for the
+//! verifier to add to the circuit's other public inputs. Bits, which no gate can compute, are
+//! inputs that the prover gives (see [bit_inputs]).
+//!
+//! ```
+//! use ark_bls12_377::Fr;
+//! use mpc_plonk::relations::dsl::CircuitBuilder;
+//! use mpc_plonk::relations::gadgets::{bit_inputs, Gadgets};
+//! use mpc_plonk::relations::structured::assign;
+//!
+//! // `bid` is under 2^8, and exceeds `reserve`'s bits
+//! let b = CircuitBuilder::<Fr>::new();
+//! let g = Gadgets::new(&b);
+//! let bid = g.decompose_to_bits("bid", b.wire("bid"), 8);
+//! let reserve = g.decompose_to_bits("reserve", b.wire("reserve"), 8);
+//! b.bind("wins", g.less_than(&reserve, &bid));
+//! b.publicize("wins");
+//! let public = g.public_values::<Fr>();
+//! let c = b.finish();
+//!
+//! let mut inputs = public.clone();
+//! inputs.extend(bit_inputs("bid", 200, 8));
+//! inputs.extend(bit_inputs("reserve", 150, 8));
+//! inputs.insert("bid".to_owned(), Fr::from(200u64));
+//! inputs.insert("reserve".to_owned(), Fr::from(150u64));
+//! let values = assign(&c, &inputs).unwrap().0;
+//! for (var, name) in &c.pub_vars {
+//!     let expected = public.get(name).copied().unwrap_or(Fr::from(1u64));
+//!     assert_eq!(values[*var as usize], expected);
+//! }
+//! ```
+use ark_ff::{Field, PrimeField};
+use num_bigint::BigUint;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use super::dsl::{CircuitBuilder, Wire};
+use crate::util::{
+    PoseidonParameters, POSEIDON_FULL_ROUNDS, POSEIDON_PARTIAL_ROUNDS, POSEIDON_RATE,
+    POSEIDON_WIDTH,
+};
+
+pub mod edwards;
+pub mod merkle;
+pub mod nonnative;
+
+/// Adds gadgets to the circuit that `builder` is building, and keeps track of their public
+/// inputs.
+pub struct Gadgets<'a, F: Field> {
+    builder: &'a CircuitBuilder<F>,
+    /// The gadgets' public wires, with their values: constants, and checks (zero)
+    public: RefCell<HashMap<String, Constant>>,
+    /// Checks so far, to name the next
+    checks: RefCell<usize>,
+}
+
+impl<'a, F: Field> Gadgets<'a, F> {
+    pub fn new(builder: &'a CircuitBuilder<F>) -> Self {
+        Self {
+            builder,
+            public: RefCell::new(HashMap::new()),
+            checks: RefCell::new(0),
+        }
+    }
+
+    /// The builder that the gadgets add to.
+    pub fn builder(&self) -> &'a CircuitBuilder<F> {
+        self.builder
+    }
+
+    /// The constant `value`, as the public input `gadget_{name}`.
+    fn constant(&self, name: &str, value: Constant) -> Wire<'a, F> {
+        let name = format!("gadget_{}", name);
+        let mut public = self.public.borrow_mut();
+        if !public.contains_key(&name) {
+            self.builder.publicize(&name);
+            public.insert(name.clone(), value);
+        }
+        self.builder.wire(&name)
+    }
+
+    /// The constant `v`, reduced into the field.
+    pub fn big_constant(&self, v: &BigUint) -> Wire<'a, F> {
+        self.constant(&format!("big_{:x}", v), Constant::Big(v.clone()))
+    }
+
+    pub fn one(&self) -> Wire<'a, F> {
+        self.constant("one", Constant::Int(1))
+    }
+
+    pub fn minus_one(&self) -> Wire<'a, F> {
+        self.constant("minus_one", Constant::Int(-1))
+    }
+
+    pub fn two(&self) -> Wire<'a, F> {
+        self.constant("two", Constant::Int(2))
+    }
+
+    pub fn minus_two(&self) -> Wire<'a, F> {
+        self.constant("minus_two", Constant::Int(-2))
+    }
+
+    /// The values of the public inputs that the gadgets added so far expect, by name.
+    pub fn public_values<G: PrimeField>(&self) -> HashMap<String, G> {
+        self.public_values_with(PoseidonParameters::new)
+    }
+
+    /// [Gadgets::public_values], with the Poseidon constants of `params`, if any are needed:
+    /// for a shared field, its base field's, as public values (see [PoseidonParameters::map]).
+    pub fn public_values_with<G: PrimeField>(
+        &self,
+        params: impl FnOnce() -> PoseidonParameters<G>,
+    ) -> HashMap<String, G> {
+        let public = self.public.borrow();
+        let poseidon = public
+            .values()
+            .any(|c| matches!(c, Constant::PoseidonRound(..) | Constant::PoseidonMds(..)))
+            .then(params);
+        let poseidon = || poseidon.as_ref().unwrap();
+        public
+            .iter()
+            .map(|(name, c)| {
+                let value = match c {
+                    Constant::Int(v) => {
+                        let abs = G::from(v.unsigned_abs());
+                        if *v < 0 {
+                            -abs
+                        } else {
+                            abs
+                        }
+                    }
+                    Constant::Big(v) => G::from_le_bytes_mod_order(&v.to_bytes_le()),
+                    Constant::PoseidonRound(r, i) => poseidon().round_constants[*r][*i],
+                    Constant::PoseidonMds(i, j) => poseidon().mds[*i][*j],
+                };
+                (name.clone(), value)
+            })
+            .collect()
+    }
+
+    /// Check that `x` is zero, as the public wire `gadget_check_i`.
+    pub fn assert_zero(&self, x: Wire<'a, F>) {
+        let i = self.checks.replace_with(|i| *i + 1);
+        let name = format!("gadget_check_{}", i);
+        self.builder.bind(&name, x);
+        self.builder.publicize(&name);
+        self.public.borrow_mut().insert(name, Constant::Int(0));
+    }
+
+    pub fn assert_equal(&self, x: Wire<'a, F>, y: Wire<'a, F>) {
+        self.assert_zero(x + self.minus_one() * y);
+    }
+
+    /// Check that `x` is 0 or 1: that `x (x - 1)` is zero.
+    pub fn assert_bit(&self, x: Wire<'a, F>) {
+        self.assert_zero(x * (x + self.minus_one()));
+    }
+
+    /// `x`'s `n` bits, least significant first, checked to be bits of `x`, which must so be
+    /// under `2^n`. The bits are the inputs `{name}_bit_j` (see [bit_inputs]).
+    ///
+    /// `2^n` must be under the field's characteristic, or the bits could be of `x` plus a
+    /// multiple of it.
+    pub fn decompose_to_bits(&self, name: &str, x: Wire<'a, F>, n: usize) -> Vec<Wire<'a, F>> {
+        assert!(n > 0, "A value has at least one bit");
+        assert!(
+            n < <F::BasePrimeField as PrimeField>::size_in_bits(),
+            "{} bits could wrap around the field",
+            n
+        );
+        let bits = self.bits(name, n);
+        self.assert_equal(self.from_bits(&bits), x);
+        bits
+    }
+
+    /// `n` inputs `{name}_bit_j`, checked to be bits (see [bit_inputs]).
+    pub fn bits(&self, name: &str, n: usize) -> Vec<Wire<'a, F>> {
+        let bits: Vec<_> = (0..n)
+            .map(|j| self.builder.wire(&format!("{}_bit_{}", name, j)))
+            .collect();
+        for bit in &bits {
+            self.assert_bit(*bit);
+        }
+        bits
+    }
+
+    /// Check that `x` is under `2^n_bits` (see [Gadgets::decompose_to_bits]).
+    pub fn range_check(&self, name: &str, x: Wire<'a, F>, n_bits: usize) {
+        self.decompose_to_bits(name, x, n_bits);
+    }
+
+    /// The value of `bits`, least significant first, by Horner's rule: two gates a bit.
+    pub fn from_bits(&self, bits: &[Wire<'a, F>]) -> Wire<'a, F> {
+        let (last, rest) = bits.split_last().expect("A value has at least one bit");
+        let two = self.two();
+        rest.iter().rev().fold(*last, |acc, bit| acc * two + *bit)
+    }
+
+    /// `x xor y` and `x and y`, for bits: four gates.
+    pub fn xor_and(&self, x: Wire<'a, F>, y: Wire<'a, F>) -> (Wire<'a, F>, Wire<'a, F>) {
+        let and = x * y;
+        (x + y + self.minus_two() * and, and)
+    }
+
+    /// The bits of `x + y`, for bits `x` and `y` of the same length, least significant first:
+    /// one more bit than theirs, from a ripple-carry adder of nine gates a bit.
+    pub fn add_bits(&self, x: &[Wire<'a, F>], y: &[Wire<'a, F>]) -> Vec<Wire<'a, F>> {
+        assert_eq!(x.len(), y.len(), "Operands of different lengths");
+        let (first, mut carry) = self.xor_and(x[0], y[0]);
+        let mut bits = vec![first];
+        for (x, y) in x.iter().zip(y).skip(1) {
+            let (half, and) = self.xor_and(*x, *y);
+            let (sum, half_and_carry) = self.xor_and(half, carry);
+            bits.push(sum);
+            // At most one of these is one
+            carry = and + half_and_carry;
+        }
+        bits.push(carry);
+        bits
+    }
+
+    /// Whether `x < y`, as a bit, for bits `x` and `y` of the same length (say, from
+    /// [Gadgets::decompose_to_bits]), least significant first: eight gates a bit.
+    ///
+    /// From the least significant bit up, the comparison so far is the higher bits' wherever
+    /// they differ: `lt = lt + (x_j xor y_j) (y_j - lt)`.
+    pub fn less_than(&self, x: &[Wire<'a, F>], y: &[Wire<'a, F>]) -> Wire<'a, F> {
+        assert_eq!(x.len(), y.len(), "Operands of different lengths");
+        let minus_one = self.minus_one();
+        // y_0 and not x_0
+        let mut lt = y[0] + minus_one * (x[0] * y[0]);
+        for (x, y) in x.iter().zip(y).skip(1) {
+            let (differ, _) = self.xor_and(*x, *y);
+            lt = lt + differ * (*y + minus_one * lt);
+        }
+        lt
+    }
+
+    /// `x` if `cond` is one, and `y` if it is zero: `y + cond (x - y)`, four gates. `cond` must
+    /// be a bit (see [Gadgets::assert_bit]).
+    pub fn select(&self, cond: Wire<'a, F>, x: Wire<'a, F>, y: Wire<'a, F>) -> Wire<'a, F> {
+        y + cond * (x + self.minus_one() * y)
+    }
+}
+
+impl<'a, F: PrimeField> Gadgets<'a, F> {
+    /// The Poseidon hash of `inputs`, as [crate::poseidon_hash] and [crate::PoseidonSponge]
+    /// compute it: a permutation every [POSEIDON_RATE] inputs, of about 1,700 gates. Its
+    /// constants are public inputs.
+    pub fn poseidon(&self, inputs: &[Wire<'a, F>]) -> Wire<'a, F> {
+        // The sponge's state starts at zero, which needs no wire
+        let mut state = [None; POSEIDON_WIDTH];
+        let mut pos = 0;
+        for x in inputs {
+            if pos == POSEIDON_RATE {
+                state = self.poseidon_permute(state).map(Some);
+                pos = 0;
+            }
+            state[pos] = Some(match state[pos] {
+                Some(s) => s + *x,
+                None => *x,
+            });
+            pos += 1;
+        }
+        self.poseidon_permute(state)[0]
+    }
+
+    /// The Poseidon permutation of `state`, whose `None`s are zeros.
+    fn poseidon_permute(
+        &self,
+        mut state: [Option<Wire<'a, F>>; POSEIDON_WIDTH],
+    ) -> [Wire<'a, F>; POSEIDON_WIDTH] {
+        let alpha = PoseidonParameters::<F>::sbox_exponent();
+        let half_full = POSEIDON_FULL_ROUNDS / 2;
+        let mds = |i: usize, j: usize| {
+            self.constant(
+                &format!("poseidon_mds_{}_{}", i, j),
+                Constant::PoseidonMds(i, j),
+            )
+        };
+        for r in 0..POSEIDON_FULL_ROUNDS + POSEIDON_PARTIAL_ROUNDS {
+            let mut added: [Wire<'a, F>; POSEIDON_WIDTH] = std::array::from_fn(|i| {
+                let name = format!("poseidon_rc_{}_{}", r, i);
+                let c = self.constant(&name, Constant::PoseidonRound(r, i));
+                state[i].map_or(c, |s| s + c)
+            });
+            let full = r < half_full || r >= half_full + POSEIDON_PARTIAL_ROUNDS;
+            let sboxes = if full { POSEIDON_WIDTH } else { 1 };
+            for s in &mut added[..sboxes] {
+                *s = self.pow(*s, alpha);
+            }
+            for (i, s) in state.iter_mut().enumerate() {
+                let row = added.iter().enumerate().map(|(j, a)| mds(i, j) * *a);
+                *s = row.reduce(|x, y| x + y);
+            }
+        }
+        state.map(Option::unwrap)
+    }
+
+    /// `x^e`, by square-and-multiply.
+    fn pow(&self, x: Wire<'a, F>, e: u64) -> Wire<'a, F> {
+        (0..63 - e.leading_zeros()).rev().fold(x, |acc, bit| {
+            let square = acc * acc;
+            if (e >> bit) & 1 == 1 {
+                square * x
+            } else {
+                square
+            }
+        })
+    }
+}
+
+/// The value of a public wire that a gadget added.
+#[derive(Clone)]
+enum Constant {
+    Int(i64),
+    /// An integer too large for `Int`, reduced into the field
+    Big(BigUint),
+    /// The round constant of [PoseidonParameters::round_constants], by round and element
+    PoseidonRound(usize, usize),
+    /// The entry of [PoseidonParameters::mds], by row and column
+    PoseidonMds(usize, usize),
+}
+
+/// The inputs that [Gadgets::decompose_to_bits] and [Gadgets::bits] read for `value`'s `n` bits under `name`.
+pub fn bit_inputs<F: Field>(name: &str, value: u64, n: usize) -> HashMap<String, F> {
+    assert!(
+        n >= 64 || value >> n == 0,
+        "{} has more than {} bits",
+        value,
+        n
+    );
+    (0..n)
+        .map(|j| {
+            let bit = if j < 64 { (value >> j) & 1 } else { 0 };
+            (format!("{}_bit_{}", name, j), F::from(bit))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relations::structured::{assign, PlonkCircuit};
+    type F = ark_bls12_377::Fr;
+
+    /// The values of `c`'s public wires, given `inputs`, and whether the gadgets' checks pass.
+    pub(super) fn run(
+        c: &PlonkCircuit<F>,
+        public: &HashMap<String, F>,
+        inputs: HashMap<String, F>,
+    ) -> (HashMap<String, F>, bool) {
+        let mut all = public.clone();
+        all.extend(inputs);
+        let values = assign(c, &all).unwrap().0;
+        let outputs: HashMap<String, F> = c
+            .pub_vars
+            .iter()
+            .map(|(v, name)| (name.clone(), values[*v as usize]))
+            .collect();
+        let ok = public.iter().all(|(name, v)| outputs[name] == *v);
+        (outputs, ok)
+    }
+
+    #[test]
+    fn bits() {
+        let b = CircuitBuilder::new();
+        let g = Gadgets::new(&b);
+        g.range_check("x", b.wire("x"), 4);
+        let public = g.public_values();
+        let c = b.finish();
+        let x = |v: u64, bits: u64| {
+            let mut inputs = bit_inputs("x", bits, 4);
+            inputs.insert("x".to_owned(), F::from(v));
+            inputs
+        };
+        assert!(run(&c, &public, x(13, 13)).1);
+        // Bits of another value, and a value too large for its bits
+        assert!(!run(&c, &public, x(13, 12)).1);
+        assert!(!run(&c, &public, x(16, 0)).1);
+        // A "bit" that isn't one
+        let mut inputs = x(13, 13);
+        inputs.insert("x_bit_0".to_owned(), F::from(3u64));
+        inputs.insert("x_bit_1".to_owned(), -F::from(1u64));
+        assert!(!run(&c, &public, inputs).1);
+    }
+
+    #[test]
+    fn compare_add_select() {
+        let b = CircuitBuilder::new();
+        let g = Gadgets::new(&b);
+        let x = g.decompose_to_bits("x", b.wire("x"), 5);
+        let y = g.decompose_to_bits("y", b.wire("y"), 5);
+        b.bind("lt", g.less_than(&x, &y));
+        b.bind("sum", g.from_bits(&g.add_bits(&x, &y)));
+        b.bind("max", g.select(b.wire("lt"), b.wire("y"), b.wire("x")));
+        for name in &["lt", "sum", "max"] {
+            b.publicize(name);
+        }
+        let public = g.public_values();
+        let c = b.finish();
+        for (x, y) in &[(0u64, 0u64), (3, 17), (17, 3), (31, 31), (30, 31), (31, 0)] {
+            let mut inputs = bit_inputs("x", *x, 5);
+            inputs.extend(bit_inputs("y", *y, 5));
+            inputs.insert("x".to_owned(), F::from(*x));
+            inputs.insert("y".to_owned(), F::from(*y));
+            let (outputs, ok) = run(&c, &public, inputs);
+            assert!(ok);
+            assert_eq!(outputs["lt"], F::from((x < y) as u64), "{} < {}", x, y);
+            assert_eq!(outputs["sum"], F::from(x + y));
+            assert_eq!(outputs["max"], F::from(*x.max(y)));
+        }
+    }
+
+    #[test]
+    fn poseidon() {
+        for n in 1..=3 {
+            let b = CircuitBuilder::new();
+            let g = Gadgets::new(&b);
+            let names: Vec<String> = (0..n).map(|i| format!("x_{}", i)).collect();
+            let inputs: Vec<_> = names.iter().map(|name| b.wire(name)).collect();
+            b.bind("hash", g.poseidon(&inputs));
+            b.publicize("hash");
+            let public = g.public_values();
+            let c = b.finish();
+            let values: Vec<F> = (0..n).map(|i| F::from(i as u64 + 7)).collect();
+            let (outputs, ok) = run(&c, &public, names.into_iter().zip(values.clone()).collect());
+            assert!(ok);
+            let mut sponge = crate::PoseidonSponge::new();
+            sponge.absorb_elems(&values);
+            assert_eq!(outputs["hash"], sponge.squeeze(), "{} inputs", n);
+        }
+    }
+
+    #[test]
+    fn optimized() {
+        let circuit = || {
+            let b = CircuitBuilder::new();
+            let g = Gadgets::new(&b);
+            b.bind("hash", g.poseidon(&[b.wire("x"), b.wire("y")]));
+            b.publicize("hash");
+            g.range_check("x", b.wire("x"), 8);
+            (g.public_values(), b.finish())
+        };
+        let (public, c) = circuit();
+        let (mut optimized_public, mut optimized) = circuit();
+        let opt = optimized.optimize(&optimized_public);
+        // Without constants, only padding and duplicates go
+        let unfolded = circuit().1.optimize(&HashMap::new());
+        assert!(opt.gates_after < unfolded.gates_after);
+        optimized_public.extend(opt.folded);
+        let inputs = |x: u64, bits: u64| {
+            let mut inputs = bit_inputs("x", bits, 8);
+            inputs.insert("x".to_owned(), F::from(x));
+            inputs.insert("y".to_owned(), F::from(5u64));
+            inputs
+        };
+        let (outputs, ok) = run(&c, &public, inputs(200, 200));
+        assert!(ok);
+        let (optimized_outputs, ok) = run(&optimized, &optimized_public, inputs(200, 200));
+        assert!(ok);
+        assert_eq!(optimized_outputs["hash"], outputs["hash"]);
+        assert!(!run(&optimized, &optimized_public, inputs(300, 44)).1);
+    }
+}
@@ -0,0 +1,574 @@
+//! Arithmetic modulo a foreign prime, such as secp256k1's, in circuits over another field.
+//!
+//! A foreign element is `n` limbs of `b` bits each, least significant first, that are together
+//! under the modulus `p` (see [ForeignField::input]). A sum or product is first computed as wide
+//! limbs, then reduced: the prover gives its quotient `q` and remainder `r` by `p`, and the
+//! circuit checks that `x = q p + r` as integers, one limb position at a time, with carries that
+//! the prover gives too. Every check is bounded away from the native modulus, so none can wrap
+//! around it.
+//!
+//! The prover computes its inputs in the clear, with [ForeignField::input_values],
+//! [ForeignField::add_inputs] and [ForeignField::mul_inputs]: in a collaborative proof, they are
+//! one party's (see [crate::relations::structured::assign_hybrid]).
+//!
+//! ```
+//! use ark_bls12_377::Fr;
+//! use mpc_plonk::relations::dsl::CircuitBuilder;
+//! use mpc_plonk::relations::gadgets::nonnative::ForeignField;
+//! use mpc_plonk::relations::gadgets::Gadgets;
+//! use mpc_plonk::relations::structured::assign;
+//! use num_bigint::BigUint;
+//!
+//! // x y = z, modulo secp256k1's base field's prime
+//! let field = ForeignField::secp256k1_base();
+//! let b = CircuitBuilder::<Fr>::new();
+//! let g = Gadgets::new(&b);
+//! let (x, y) = (field.input(&g, "x"), field.input(&g, "y"));
+//! let z = field.input(&g, "z");
+//! field.assert_equal(&g, &field.mul(&g, "xy", &x, &y), &z);
+//! let public = g.public_values::<Fr>();
+//! let c = b.finish();
+//!
+//! let (x, y) = (BigUint::from(3u64) << 250, field.modulus() - 1u64);
+//! let mut inputs = public.clone();
+//! inputs.extend(field.input_values("x", &x));
+//! inputs.extend(field.input_values("y", &y));
+//! let (z, xy) = field.mul_inputs("xy", &x, &y);
+//! inputs.extend(xy);
+//! inputs.extend(field.input_values("z", &z));
+//! let values = assign(&c, &inputs).unwrap().0;
+//! for (var, name) in &c.pub_vars {
+//!     assert_eq!(values[*var as usize], public[name]);
+//! }
+//! ```
+use ark_ff::{Field, PrimeField};
+use num_bigint::{BigInt, BigUint};
+use std::collections::HashMap;
+
+use super::super::dsl::Wire;
+use super::Gadgets;
+
+/// A prime field other than the circuit's: its modulus, and its elements' limbs.
+#[derive(Clone, Debug)]
+pub struct ForeignField {
+    modulus: BigUint,
+    /// Bits a limb
+    limb_bits: usize,
+    /// Limbs an element
+    n_limbs: usize,
+}
+
+/// An element of a [ForeignField], in a circuit: its limbs, least significant first.
+#[derive(Clone)]
+pub struct ForeignElement<'a, F: Field> {
+    pub limbs: Vec<Wire<'a, F>>,
+}
+
+/// The witness of a reduction, beyond its remainder (see [ForeignField::reduce]).
+struct Reduction {
+    /// Limbs of the quotient
+    q_limbs: usize,
+    /// Bits of each carry, at every limb position but the last. A carry may be negative: its
+    /// bits are of it plus half their range.
+    carry_bits: Vec<usize>,
+}
+
+impl ForeignField {
+    pub fn new(modulus: BigUint, limb_bits: usize) -> Self {
+        assert!(
+            modulus > BigUint::from(1u32),
+            "The modulus must be at least two"
+        );
+        assert!(limb_bits > 0, "A limb has at least one bit");
+        let n_limbs = (modulus.bits() as usize).div_ceil(limb_bits);
+        Self {
+            modulus,
+            limb_bits,
+            n_limbs,
+        }
+    }
+
+    /// secp256k1's base field, of its points' coordinates, in 64-bit limbs.
+    pub fn secp256k1_base() -> Self {
+        let p = "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F";
+        Self::new(BigUint::parse_bytes(p.as_bytes(), 16).unwrap(), 64)
+    }
+
+    /// secp256k1's scalar field, of ECDSA's scalars, in 64-bit limbs.
+    pub fn secp256k1_scalar() -> Self {
+        let n = "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141";
+        Self::new(BigUint::parse_bytes(n.as_bytes(), 16).unwrap(), 64)
+    }
+
+    pub fn modulus(&self) -> &BigUint {
+        &self.modulus
+    }
+
+    pub fn n_limbs(&self) -> usize {
+        self.n_limbs
+    }
+
+    /// An element that the prover gives: the bits `{name}_limb_i_bit_j` of its limbs, checked to
+    /// be under the modulus (see [ForeignField::input_values]).
+    pub fn input<'a, F: Field>(
+        &self,
+        gadgets: &Gadgets<'a, F>,
+        name: &str,
+    ) -> ForeignElement<'a, F> {
+        let (limbs, bits): (Vec<_>, Vec<_>) = (0..self.n_limbs)
+            .map(|i| {
+                let bits = gadgets.bits(&format!("{}_limb_{}", name, i), self.limb_bits);
+                (gadgets.from_bits(&bits), bits)
+            })
+            .unzip();
+        self.assert_below_modulus(gadgets, &bits.concat());
+        ForeignElement { limbs }
+    }
+
+    /// `a + b`, as the element `name` (see [ForeignField::reduce]).
+    pub fn add<'a, F: Field>(
+        &self,
+        gadgets: &Gadgets<'a, F>,
+        name: &str,
+        a: &ForeignElement<'a, F>,
+        b: &ForeignElement<'a, F>,
+    ) -> ForeignElement<'a, F> {
+        let wide: Vec<_> = a.limbs.iter().zip(&b.limbs).map(|(a, b)| *a + *b).collect();
+        self.reduce(gadgets, name, &wide, &self.sum_bounds())
+    }
+
+    /// `a b`, as the element `name` (see [ForeignField::reduce]): `n^2` products of limbs, and
+    /// the reduction.
+    pub fn mul<'a, F: Field>(
+        &self,
+        gadgets: &Gadgets<'a, F>,
+        name: &str,
+        a: &ForeignElement<'a, F>,
+        b: &ForeignElement<'a, F>,
+    ) -> ForeignElement<'a, F> {
+        let wide: Vec<_> = self
+            .convolution()
+            .map(|pairs| {
+                let products = pairs.map(|(i, j)| a.limbs[i] * b.limbs[j]);
+                products.reduce(|x, y| x + y).unwrap()
+            })
+            .collect();
+        self.reduce(gadgets, name, &wide, &self.product_bounds())
+    }
+
+    /// Check that `a` and `b` are the same element, limb by limb: both are under the modulus.
+    pub fn assert_equal<'a, F: Field>(
+        &self,
+        gadgets: &Gadgets<'a, F>,
+        a: &ForeignElement<'a, F>,
+        b: &ForeignElement<'a, F>,
+    ) {
+        for (a, b) in a.limbs.iter().zip(&b.limbs) {
+            gadgets.assert_equal(*a, *b);
+        }
+    }
+
+    /// The remainder of `x = sum_k wide_k 2^(b k)` by the modulus, as the element `name`, for
+    /// wide limbs that are integers from zero to `bounds`.
+    ///
+    /// The prover gives the remainder as an [ForeignField::input], the quotient's limbs as the
+    /// bits `{name}_q_limb_i_bit_j`, and the carries as the bits `{name}_carry_k_bit_j` (see
+    /// [ForeignField::reduce_inputs]). At each limb position `k`, the circuit checks that
+    /// `wide_k + carry_(k-1) = (q p)_k + r_k + 2^b carry_k`, where the last carry is zero.
+    ///
+    /// Panics if the bounds are so large that a check could wrap around the native field.
+    pub fn reduce<'a, F: Field>(
+        &self,
+        gadgets: &Gadgets<'a, F>,
+        name: &str,
+        wide: &[Wire<'a, F>],
+        bounds: &[BigUint],
+    ) -> ForeignElement<'a, F> {
+        assert_eq!(wide.len(), bounds.len(), "A bound a wide limb");
+        let reduction = self.reduction(bounds, native_bits::<F>());
+        let r = self.input(gadgets, name);
+        let q: Vec<_> = (0..reduction.q_limbs)
+            .map(|i| {
+                let bits = gadgets.bits(&format!("{}_q_limb_{}", name, i), self.limb_bits);
+                gadgets.from_bits(&bits)
+            })
+            .collect();
+        let p: Vec<_> = self
+            .limbs(&self.modulus, self.n_limbs)
+            .iter()
+            .map(|limb| gadgets.big_constant(limb))
+            .collect();
+        let minus_one = gadgets.minus_one();
+        let carries: Vec<_> = reduction
+            .carry_bits
+            .iter()
+            .enumerate()
+            .map(|(k, n)| {
+                let bits = gadgets.bits(&format!("{}_carry_{}", name, k), *n);
+                let offset = gadgets.big_constant(&(BigUint::from(1u32) << (n - 1)));
+                gadgets.from_bits(&bits) + minus_one * offset
+            })
+            .collect();
+        let shift = gadgets.big_constant(&(BigUint::from(1u32) << self.limb_bits));
+        let sum = |terms: Vec<Wire<'a, F>>| terms.into_iter().reduce(|x, y| x + y);
+        for k in 0..=carries.len() {
+            let mut lhs: Vec<_> = wide.get(k).copied().into_iter().collect();
+            lhs.extend(k.checked_sub(1).map(|k| carries[k]));
+            let mut rhs: Vec<_> = (0..q.len())
+                .filter(|i| k >= *i && k - i < p.len())
+                .map(|i| q[i] * p[k - i])
+                .collect();
+            rhs.extend(r.limbs.get(k).copied());
+            rhs.extend(carries.get(k).map(|c| shift * *c));
+            match (sum(lhs), sum(rhs)) {
+                (Some(x), Some(y)) => gadgets.assert_equal(x, y),
+                (Some(x), None) | (None, Some(x)) => gadgets.assert_zero(x),
+                (None, None) => {}
+            }
+        }
+        r
+    }
+
+    /// The inputs that [ForeignField::input] reads for `x`, which must be under the modulus.
+    pub fn input_values<G: Field>(&self, name: &str, x: &BigUint) -> HashMap<String, G> {
+        assert!(x < &self.modulus, "{} is not under the modulus", x);
+        self.limb_inputs(name, x, self.n_limbs)
+    }
+
+    /// `a + b`, and the inputs that [ForeignField::add] reads for it.
+    pub fn add_inputs<G: Field>(
+        &self,
+        name: &str,
+        a: &BigUint,
+        b: &BigUint,
+    ) -> (BigUint, HashMap<String, G>) {
+        let (a, b) = (self.limbs(a, self.n_limbs), self.limbs(b, self.n_limbs));
+        let wide: Vec<_> = a.iter().zip(&b).map(|(a, b)| a + b).collect();
+        self.reduce_inputs(name, &wide, &self.sum_bounds())
+    }
+
+    /// `a b`, and the inputs that [ForeignField::mul] reads for it.
+    pub fn mul_inputs<G: Field>(
+        &self,
+        name: &str,
+        a: &BigUint,
+        b: &BigUint,
+    ) -> (BigUint, HashMap<String, G>) {
+        let (a, b) = (self.limbs(a, self.n_limbs), self.limbs(b, self.n_limbs));
+        let wide: Vec<BigUint> = self
+            .convolution()
+            .map(|pairs| pairs.map(|(i, j)| &a[i] * &b[j]).sum())
+            .collect();
+        self.reduce_inputs(name, &wide, &self.product_bounds())
+    }
+
+    /// The remainder of `sum_k wide_k 2^(b k)` by the modulus, and the inputs that
+    /// [ForeignField::reduce] reads for it.
+    pub fn reduce_inputs<G: Field>(
+        &self,
+        name: &str,
+        wide: &[BigUint],
+        bounds: &[BigUint],
+    ) -> (BigUint, HashMap<String, G>) {
+        let x: BigUint = wide
+            .iter()
+            .enumerate()
+            .map(|(k, limb)| limb << (k * self.limb_bits))
+            .sum();
+        let (q, r) = (&x / &self.modulus, &x % &self.modulus);
+        let inputs = self.reduction_inputs(name, wide, bounds, &q, &r);
+        (r, inputs)
+    }
+
+    /// The inputs of [ForeignField::reduce], for the quotient `q` and remainder `r`.
+    fn reduction_inputs<G: Field>(
+        &self,
+        name: &str,
+        wide: &[BigUint],
+        bounds: &[BigUint],
+        q: &BigUint,
+        r: &BigUint,
+    ) -> HashMap<String, G> {
+        let reduction = self.reduction(bounds, native_bits::<G>());
+        let b = self.limb_bits;
+        let mut inputs = self.limb_inputs(name, r, self.n_limbs);
+        inputs.extend(self.limb_inputs(&format!("{}_q", name), q, reduction.q_limbs));
+        let p = self.limbs(&self.modulus, self.n_limbs);
+        let (q, r) = (
+            self.limbs(q, reduction.q_limbs),
+            self.limbs(r, self.n_limbs),
+        );
+        let mut carry = BigInt::from(0);
+        for (k, n) in reduction.carry_bits.iter().enumerate() {
+            let qp: BigUint = (0..q.len())
+                .filter(|i| k >= *i && k - i < p.len())
+                .map(|i| &q[i] * &p[k - i])
+                .sum();
+            let rhs = BigInt::from(qp + r.get(k).cloned().unwrap_or_default());
+            let lhs = BigInt::from(wide.get(k).cloned().unwrap_or_default()) + &carry;
+            let d = lhs - rhs;
+            carry = &d >> b;
+            debug_assert_eq!(&carry << b, d, "An inexact carry");
+            let value = (&carry + (BigInt::from(1) << (n - 1))).to_biguint();
+            let value = value.expect("A carry below its range");
+            inputs.extend(big_bit_inputs(&format!("{}_carry_{}", name, k), &value, *n));
+        }
+        inputs
+    }
+
+    /// Check that `bits`, least significant first, are of an integer under the modulus, as
+    /// [Gadgets::less_than] would against the modulus's bits, but with those as constants.
+    fn assert_below_modulus<'a, F: Field>(&self, gadgets: &Gadgets<'a, F>, bits: &[Wire<'a, F>]) {
+        let (one, minus_one) = (gadgets.one(), gadgets.minus_one());
+        // The comparison so far, or `None` while it is zero
+        let mut lt: Option<Wire<'a, F>> = None;
+        for (j, x) in bits.iter().enumerate() {
+            lt = if self.modulus.bit(j as u64) {
+                // lt + (1 - x_j) (1 - lt)
+                let not_x = one + minus_one * *x;
+                Some(lt.map_or(not_x, |lt| lt + not_x * (one + minus_one * lt)))
+            } else {
+                // lt - x_j lt
+                lt.map(|lt| lt + minus_one * (*x * lt))
+            };
+        }
+        gadgets.assert_equal(lt.expect("The modulus is positive"), one);
+    }
+
+    /// The quotient's limbs and the carries' bits of a reduction of wide limbs up to `bounds`,
+    /// in a native field of `native_bits` bits.
+    fn reduction(&self, bounds: &[BigUint], native_bits: usize) -> Reduction {
+        let b = self.limb_bits;
+        let x_max: BigUint = bounds
+            .iter()
+            .enumerate()
+            .map(|(k, bound)| bound << (k * b))
+            .sum();
+        let q_bits = (x_max / &self.modulus).bits() as usize;
+        let q_limbs = q_bits.div_ceil(b).max(1);
+        let positions = bounds.len().max(q_limbs + self.n_limbs - 1);
+        let limb_max = self.limb_max();
+        let p = self.limbs(&self.modulus, self.n_limbs);
+        let mut carry_bits = Vec::new();
+        let mut carry_max = BigUint::default();
+        for k in 0..positions {
+            // The most that either side of the check can be, without its outgoing carry
+            let qp: BigUint = (0..q_limbs)
+                .filter(|i| k >= *i && k - i < p.len())
+                .map(|i| &limb_max * &p[k - i])
+                .sum();
+            let r_max = if k < self.n_limbs {
+                limb_max.clone()
+            } else {
+                BigUint::default()
+            };
+            let x = bounds.get(k).cloned().unwrap_or_default();
+            let side_max = x.max(qp + r_max) + &carry_max;
+            carry_max = if k + 1 < positions {
+                let n = (&side_max >> b).bits() as usize + 1;
+                carry_bits.push(n);
+                BigUint::from(1u32) << (n - 1)
+            } else {
+                BigUint::default()
+            };
+            assert!(
+                ((side_max + (&carry_max << b)).bits() as usize) < native_bits,
+                "Limbs of {} bits are too wide for a native field of {} bits",
+                b,
+                native_bits
+            );
+        }
+        Reduction {
+            q_limbs,
+            carry_bits,
+        }
+    }
+
+    /// `x`'s first `n` limbs.
+    fn limbs(&self, x: &BigUint, n: usize) -> Vec<BigUint> {
+        let limb_max = self.limb_max();
+        (0..n)
+            .map(|i| (x >> (i * self.limb_bits)) & &limb_max)
+            .collect()
+    }
+
+    /// The inputs of the bits of `x`'s `n` limbs, `{name}_limb_i_bit_j`.
+    fn limb_inputs<G: Field>(&self, name: &str, x: &BigUint, n: usize) -> HashMap<String, G> {
+        assert!(
+            x.bits() as usize <= n * self.limb_bits,
+            "{} has more than {} limbs",
+            x,
+            n
+        );
+        let limbs = self.limbs(x, n);
+        let limb_bits =
+            |(i, limb)| big_bit_inputs(&format!("{}_limb_{}", name, i), limb, self.limb_bits);
+        limbs.iter().enumerate().flat_map(limb_bits).collect()
+    }
+
+    fn limb_max(&self) -> BigUint {
+        (BigUint::from(1u32) << self.limb_bits) - 1u32
+    }
+
+    fn sum_bounds(&self) -> Vec<BigUint> {
+        vec![self.limb_max() * 2u32; self.n_limbs]
+    }
+
+    /// The pairs of limbs whose products make up each wide limb of a product.
+    fn convolution(&self) -> impl Iterator<Item = impl Iterator<Item = (usize, usize)>> {
+        let n = self.n_limbs;
+        (0..2 * n - 1)
+            .map(move |k| (k.saturating_sub(n - 1)..=k.min(n - 1)).map(move |i| (i, k - i)))
+    }
+
+    fn product_bounds(&self) -> Vec<BigUint> {
+        let square = self.limb_max() * self.limb_max();
+        self.convolution()
+            .map(|pairs| &square * pairs.count())
+            .collect()
+    }
+}
+
+/// The inputs that [Gadgets::bits] reads for `value`'s `n` bits under `name`.
+fn big_bit_inputs<G: Field>(name: &str, value: &BigUint, n: usize) -> HashMap<String, G> {
+    assert!(
+        value.bits() as usize <= n,
+        "{} has more than {} bits",
+        value,
+        n
+    );
+    (0..n)
+        .map(|j| {
+            let bit = value.bit(j as u64) as u64;
+            (format!("{}_bit_{}", name, j), G::from(bit))
+        })
+        .collect()
+}
+
+fn native_bits<F: Field>() -> usize {
+    <F::BasePrimeField as PrimeField>::size_in_bits()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::run;
+    use super::*;
+    use crate::relations::dsl::CircuitBuilder;
+    use crate::relations::structured::PlonkCircuit;
+    use ark_ff::BigInteger;
+    use ark_std::rand::Rng;
+    use ark_std::test_rng;
+    type F = ark_bls12_377::Fr;
+
+    /// A circuit of `x + y` and `x y`, public as the limbs `sum_i` and `product_i`.
+    fn circuit(field: &ForeignField) -> (PlonkCircuit<F>, HashMap<String, F>) {
+        let b = CircuitBuilder::new();
+        let g = Gadgets::new(&b);
+        let (x, y) = (field.input(&g, "x"), field.input(&g, "y"));
+        let sum = field.add(&g, "sum", &x, &y);
+        let product = field.mul(&g, "product", &x, &y);
+        for (name, z) in &[("sum", sum), ("product", product)] {
+            for (i, limb) in z.limbs.iter().enumerate() {
+                let name = format!("{}_{}", name, i);
+                b.bind(&name, *limb);
+                b.publicize(&name);
+            }
+        }
+        let public = g.public_values();
+        (b.finish(), public)
+    }
+
+    fn inputs(field: &ForeignField, x: &BigUint, y: &BigUint) -> HashMap<String, F> {
+        let mut inputs = field.input_values("x", x);
+        inputs.extend(field.input_values("y", y));
+        inputs.extend(field.add_inputs("sum", x, y).1);
+        inputs.extend(field.mul_inputs("product", x, y).1);
+        inputs
+    }
+
+    /// `z`'s value, from its limbs among `outputs`.
+    fn value(field: &ForeignField, outputs: &HashMap<String, F>, z: &str) -> BigUint {
+        (0..field.n_limbs())
+            .rev()
+            .fold(BigUint::default(), |acc, i| {
+                let limb = BigUint::from_bytes_le(
+                    &outputs[&format!("{}_{}", z, i)].into_repr().to_bytes_le(),
+                );
+                (acc << field.limb_bits) + limb
+            })
+    }
+
+    #[test]
+    fn matches_bigint() {
+        let rng = &mut test_rng();
+        let small = BigUint::from((1u64 << 61) - 1);
+        for field in &[
+            ForeignField::secp256k1_base(),
+            ForeignField::secp256k1_scalar(),
+            ForeignField::new(small, 16),
+        ] {
+            let (c, public) = circuit(field);
+            let p = field.modulus();
+            let mut cases = vec![
+                (BigUint::default(), BigUint::default()),
+                (p - 1u32, p - 1u32),
+                (p - 1u32, BigUint::from(1u32)),
+            ];
+            for _ in 0..4 {
+                let mut random = || BigUint::from_bytes_le(&rng.gen::<[u8; 32]>()) % p;
+                cases.push((random(), random()));
+            }
+            for (x, y) in &cases {
+                let (outputs, ok) = run(&c, &public, inputs(field, x, y));
+                assert!(ok, "{} and {}", x, y);
+                assert_eq!(value(field, &outputs, "sum"), (x + y) % p);
+                assert_eq!(value(field, &outputs, "product"), (x * y) % p);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_other_results() {
+        let field = ForeignField::secp256k1_base();
+        let (c, public) = circuit(&field);
+        let p = field.modulus();
+        // x y = 1 (mod p)
+        let (x, y) = (p - 1u32, p - 1u32);
+        assert!(run(&c, &public, inputs(&field, &x, &y)).1);
+        // Another element under p
+        let mut tampered = inputs(&field, &x, &y);
+        tampered.extend(field.input_values::<F>("product", &BigUint::from(2u32)));
+        assert!(!run(&c, &public, tampered).1);
+        // 1 + p, with one less in the quotient: the same integer, but not reduced
+        let (xy, bounds): (Vec<_>, _) = {
+            let (x, y) = (field.limbs(&x, 4), field.limbs(&y, 4));
+            let wide = field.convolution();
+            (
+                wide.map(|pairs| pairs.map(|(i, j)| &x[i] * &y[j]).sum())
+                    .collect(),
+                field.product_bounds(),
+            )
+        };
+        let (q, r) = ((&x * &y) / p - 1u32, p + 1u32);
+        let mut tampered = inputs(&field, &x, &y);
+        tampered.extend(field.reduction_inputs::<F>("product", &xy, &bounds, &q, &r));
+        assert!(!run(&c, &public, tampered).1);
+    }
+
+    #[test]
+    fn inputs_are_reduced() {
+        let field = ForeignField::secp256k1_scalar();
+        let b = CircuitBuilder::new();
+        let g = Gadgets::new(&b);
+        field.input(&g, "x");
+        let public = g.public_values();
+        let c = b.finish();
+        let p = field.modulus();
+        let x = |x: BigUint| field.limb_inputs("x", &x, field.n_limbs());
+        assert!(run(&c, &public, x(BigUint::default())).1);
+        assert!(run(&c, &public, x(p - 1u32)).1);
+        assert!(!run(&c, &public, x(p.clone())).1);
+        assert!(!run(&c, &public, x(p + 1u32)).1);
+        assert!(!run(&c, &public, x((BigUint::from(1u32) << 256) - 1u32)).1);
+    }
+}
@@ -0,0 +1,399 @@
+//! Noir programs, from their ACIR (the Abstract Circuit Intermediate Representation that Noir's
+//! compiler emits): a list of opcodes over numbered witnesses, which we lower to Plonk gates so
+//! that mpc-plonk may prove a Noir program collaboratively.
+//!
+//! We take the opcodes that are gates, as [Opcode]s: an `AssertZero` is an [Expression], a
+//! polynomial of degree two in the witnesses that must be zero, and a `RANGE` black box
+//! function checks that a witness has at most some number of bits. A program's other opcodes
+//! (its Brillig calls, memory operations, and other black box functions) have no gates here,
+//! so a program must not use them. This module does not read ACIR's serialization: callers
+//! convert from the `acir` crate's `Circuit`, whose fields these types share the names of,
+//! and pass the witness values that ACVM solves for.
+//!
+//! Witness `i` is the input `w_{i}`; the program's public parameters and return values are
+//! public under those names. Coefficients are the gadgets' constants, and each `AssertZero` a
+//! gadget check (see [Gadgets::public_values]).
+//!
+//! ```
+//! use ark_bls12_377::Fr;
+//! use mpc_plonk::relations::import::acir::{Acir, Expression, Opcode};
+//! use mpc_plonk::relations::structured::assign;
+//! use std::collections::BTreeMap;
+//!
+//! // w_0 * w_1 = w_2, with w_0 under 2^8, and w_2 returned
+//! let acir = Acir {
+//!     current_witness_index: 2,
+//!     opcodes: vec![
+//!         Opcode::AssertZero(Expression {
+//!             mul_terms: vec![(Fr::from(1u64), 0, 1)],
+//!             linear_combinations: vec![(-Fr::from(1u64), 2)],
+//!             q_c: Fr::from(0u64),
+//!         }),
+//!         Opcode::Range { witness: 0, num_bits: 8 },
+//!     ],
+//!     public_parameters: Default::default(),
+//!     return_values: vec![2].into_iter().collect(),
+//! };
+//! let witness: BTreeMap<u32, Fr> = vec![(0, 6u64), (1, 7), (2, 42)]
+//!     .into_iter()
+//!     .map(|(i, v)| (i, Fr::from(v)))
+//!     .collect();
+//! assert!(acir.is_satisfied(&witness));
+//!
+//! let (c, public) = acir.circuit();
+//! let mut inputs = public.clone();
+//! inputs.extend(acir.input_values(&witness));
+//! let values = assign(&c, &inputs).unwrap().0;
+//! for (var, name) in &c.pub_vars {
+//!     let expected = public.get(name).copied().unwrap_or(Fr::from(42u64));
+//!     assert_eq!(values[*var as usize], expected);
+//! }
+//! ```
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_std::rand::Rng;
+use mpc_algebra::Reveal;
+use num_bigint::BigUint;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use super::super::dsl::{CircuitBuilder, Wire};
+use super::super::gadgets::Gadgets;
+use super::super::structured::PlonkCircuit;
+
+/// A polynomial of degree two in the witnesses, which an [Opcode::AssertZero] checks is zero.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Expression<F> {
+    /// Terms `c * w_a * w_b`, as `(c, a, b)`
+    pub mul_terms: Vec<(F, u32, u32)>,
+    /// Terms `c * w_a`, as `(c, a)`
+    pub linear_combinations: Vec<(F, u32)>,
+    /// The constant term
+    pub q_c: F,
+}
+
+impl<F: Field> Expression<F> {
+    /// The expression's value, given each witness it reads.
+    pub fn evaluate(&self, witness: &BTreeMap<u32, F>) -> F {
+        let w = |i: &u32| witness_value(witness, *i);
+        self.mul_terms
+            .iter()
+            .map(|(c, a, b)| *c * w(a) * w(b))
+            .chain(self.linear_combinations.iter().map(|(c, a)| *c * w(a)))
+            .fold(self.q_c, |acc, t| acc + t)
+    }
+}
+
+/// The ACIR opcodes that are gates (see the [module docs](self)).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Opcode<F> {
+    /// The expression is zero
+    AssertZero(Expression<F>),
+    /// Witness `witness` is under `2^num_bits`: a `RANGE` black box function call
+    Range { witness: u32, num_bits: u32 },
+}
+
+/// A Noir program's circuit, as its ACIR opcodes (see the [module docs](self)).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Acir<F> {
+    /// The largest witness index
+    pub current_witness_index: u32,
+    pub opcodes: Vec<Opcode<F>>,
+    /// The witnesses that are public inputs
+    pub public_parameters: BTreeSet<u32>,
+    /// The witnesses that are public outputs
+    pub return_values: BTreeSet<u32>,
+}
+
+impl<F: PrimeField> Acir<F> {
+    /// The witnesses that the opcodes read, or that are public, in order.
+    pub fn witnesses(&self) -> BTreeSet<u32> {
+        let mut witnesses: BTreeSet<u32> = self
+            .public_parameters
+            .union(&self.return_values)
+            .copied()
+            .collect();
+        for opcode in &self.opcodes {
+            match opcode {
+                Opcode::AssertZero(e) => {
+                    for (_, a, b) in &e.mul_terms {
+                        witnesses.insert(*a);
+                        witnesses.insert(*b);
+                    }
+                    witnesses.extend(e.linear_combinations.iter().map(|(_, a)| *a));
+                }
+                Opcode::Range { witness, .. } => {
+                    witnesses.insert(*witness);
+                }
+            }
+        }
+        if let Some(last) = witnesses.iter().next_back() {
+            assert!(
+                *last <= self.current_witness_index,
+                "Witness {} is past the current witness index, {}",
+                last,
+                self.current_witness_index
+            );
+        }
+        witnesses
+    }
+
+    /// The circuit that `gadgets` add to, over any field whose characteristic is `F`'s: each
+    /// witness is the input `w_{i}`, and each range check's bits, for opcode `k`, the inputs
+    /// `range_{k}_bit_j` (see [Gadgets::bits]). Returns each witness's wire.
+    ///
+    /// A product term is a gate, and a term's coefficient another unless it is one; summing
+    /// the terms is a gate a term. A range check of `n` bits is about `4n` gates, and one of
+    /// as many bits as the field's elements none.
+    pub fn build<'a, G: Field>(&self, gadgets: &Gadgets<'a, G>) -> BTreeMap<u32, Wire<'a, G>> {
+        let b = gadgets.builder();
+        let wires: BTreeMap<u32, Wire<'a, G>> = self
+            .witnesses()
+            .into_iter()
+            .map(|i| (i, b.wire(&witness_name(i))))
+            .collect();
+        for i in self.public_parameters.union(&self.return_values) {
+            b.publicize(&witness_name(*i));
+        }
+        let term = |c: &F, w: Wire<'a, G>| {
+            if c.is_one() {
+                w
+            } else {
+                gadgets.big_constant(&to_biguint(c)) * w
+            }
+        };
+        for (k, opcode) in self.opcodes.iter().enumerate() {
+            match opcode {
+                Opcode::AssertZero(e) => {
+                    let mut terms: Vec<Wire<'a, G>> = e
+                        .mul_terms
+                        .iter()
+                        .filter(|(c, _, _)| !c.is_zero())
+                        .map(|(c, a, b)| term(c, wires[a] * wires[b]))
+                        .chain(
+                            e.linear_combinations
+                                .iter()
+                                .filter(|(c, _)| !c.is_zero())
+                                .map(|(c, a)| term(c, wires[a])),
+                        )
+                        .collect();
+                    if !e.q_c.is_zero() {
+                        terms.push(gadgets.big_constant(&to_biguint(&e.q_c)));
+                    }
+                    // An empty expression is zero already
+                    if let Some(sum) = terms.into_iter().reduce(|x, y| x + y) {
+                        gadgets.assert_zero(sum);
+                    }
+                }
+                Opcode::Range { witness, num_bits } => {
+                    let w = wires[witness];
+                    match *num_bits as usize {
+                        0 => gadgets.assert_zero(w),
+                        n if n < F::size_in_bits() => {
+                            gadgets.range_check(&format!("range_{}", k), w, n)
+                        }
+                        // Every element is in range
+                        _ => {}
+                    }
+                }
+            }
+        }
+        wires
+    }
+
+    /// The circuit alone (see [Acir::build]), and the values of the gadgets' public inputs
+    /// (see [Gadgets::public_values]).
+    pub fn circuit(&self) -> (PlonkCircuit<F>, HashMap<String, F>) {
+        let b = CircuitBuilder::new();
+        let g = Gadgets::new(&b);
+        self.build(&g);
+        let public = g.public_values();
+        (b.finish(), public)
+    }
+
+    /// Whether `witness` satisfies every opcode.
+    pub fn is_satisfied(&self, witness: &BTreeMap<u32, F>) -> bool {
+        self.opcodes.iter().all(|opcode| match opcode {
+            Opcode::AssertZero(e) => e.evaluate(witness).is_zero(),
+            Opcode::Range {
+                witness: i,
+                num_bits,
+            } => {
+                let bits = witness_value(witness, *i).into_repr().to_bits_le();
+                bits.iter().skip(*num_bits as usize).all(|bit| !bit)
+            }
+        })
+    }
+
+    /// The inputs that [Acir::build] reads, given each witness's value.
+    pub fn input_values(&self, witness: &BTreeMap<u32, F>) -> HashMap<String, F> {
+        self.inputs(Some(witness)).into_iter().collect()
+    }
+
+    /// The inputs that [Acir::build] reads, in an order that depends only on the opcodes, with
+    /// `witness`'s values, or zeros.
+    fn inputs(&self, witness: Option<&BTreeMap<u32, F>>) -> Vec<(String, F)> {
+        let value = |i: u32| witness.map_or(F::zero(), |w| witness_value(w, i));
+        let mut inputs: Vec<(String, F)> = self
+            .witnesses()
+            .into_iter()
+            .map(|i| (witness_name(i), value(i)))
+            .collect();
+        for (k, opcode) in self.opcodes.iter().enumerate() {
+            if let Opcode::Range { witness, num_bits } = opcode {
+                let n = *num_bits as usize;
+                if n == 0 || n >= F::size_in_bits() {
+                    continue;
+                }
+                let bits = value(*witness).into_repr().to_bits_le();
+                inputs.extend((0..n).map(|j| {
+                    let bit = if bits[j] { F::one() } else { F::zero() };
+                    (format!("range_{}_bit_{}", k, j), bit)
+                }));
+            }
+        }
+        inputs
+    }
+
+    /// The inputs that [Acir::build] reads, shared by the king (see
+    /// [Reveal::king_share_batch]), which passes `witness`: say, what ACVM solved. The other
+    /// parties' `witness` is ignored, so they may pass `None`.
+    pub fn share_inputs<S: Field + Reveal<Base = F>, R: Rng>(
+        &self,
+        witness: Option<&BTreeMap<u32, F>>,
+        rng: &mut R,
+    ) -> HashMap<String, S> {
+        let (names, values): (Vec<String>, Vec<F>) = self.inputs(witness).into_iter().unzip();
+        names
+            .into_iter()
+            .zip(S::king_share_batch(values, rng))
+            .collect()
+    }
+}
+
+fn witness_name(i: u32) -> String {
+    format!("w_{}", i)
+}
+
+fn witness_value<F: Copy>(witness: &BTreeMap<u32, F>, i: u32) -> F {
+    *witness
+        .get(&i)
+        .unwrap_or_else(|| panic!("No value for witness {}", i))
+}
+
+fn to_biguint<F: PrimeField>(x: &F) -> BigUint {
+    BigUint::from_bytes_le(&x.into_repr().to_bytes_le())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relations::structured::assign;
+    type F = ark_bls12_377::Fr;
+
+    fn f(x: i64) -> F {
+        let abs = F::from(x.unsigned_abs());
+        if x < 0 {
+            -abs
+        } else {
+            abs
+        }
+    }
+
+    /// `w_0 * w_1 = w_2`, `w_0` under `2^8`, and `2 w_0 + w_1 + 3 = w_3`, for a public `w_1`
+    /// and a returned `w_3`; and an empty expression, and checks of zero and of every bit
+    fn program() -> Acir<F> {
+        let assert_zero = |mul_terms, linear_combinations, q_c| {
+            Opcode::AssertZero(Expression {
+                mul_terms,
+                linear_combinations,
+                q_c,
+            })
+        };
+        Acir {
+            current_witness_index: 4,
+            opcodes: vec![
+                assert_zero(vec![(f(1), 0, 1)], vec![(f(-1), 2)], f(0)),
+                Opcode::Range {
+                    witness: 0,
+                    num_bits: 8,
+                },
+                assert_zero(vec![], vec![(f(2), 0), (f(1), 1), (f(-1), 3)], f(3)),
+                assert_zero(vec![(f(0), 0, 0)], vec![], f(0)),
+                Opcode::Range {
+                    witness: 4,
+                    num_bits: 0,
+                },
+                Opcode::Range {
+                    witness: 1,
+                    num_bits: 253,
+                },
+            ],
+            public_parameters: vec![1].into_iter().collect(),
+            return_values: vec![3].into_iter().collect(),
+        }
+    }
+
+    fn witness(values: &[i64]) -> BTreeMap<u32, F> {
+        (0..).zip(values.iter().map(|v| f(*v))).collect()
+    }
+
+    /// Whether the lowered circuit's public outputs are as expected: the gadgets' constants,
+    /// zero checks, and `w_1` and `w_3`
+    fn lowered_holds(acir: &Acir<F>, witness: &BTreeMap<u32, F>) -> bool {
+        let (c, public) = acir.circuit();
+        let mut inputs = public.clone();
+        inputs.extend(acir.input_values(witness));
+        let values = assign(&c, &inputs).unwrap().0;
+        c.pub_vars.iter().all(|(var, name)| {
+            let expected = match name.as_str() {
+                "w_1" => witness[&1],
+                "w_3" => witness[&3],
+                _ => public[name],
+            };
+            values[*var as usize] == expected
+        })
+    }
+
+    #[test]
+    fn lowered() {
+        let acir = program();
+        let good = witness(&[5, 7, 35, 20, 0]);
+        assert!(acir.is_satisfied(&good));
+        assert!(lowered_holds(&acir, &good));
+        // Out of range, but every product and sum right
+        let wide = witness(&[300, 7, 2100, 610, 0]);
+        assert!(!acir.is_satisfied(&wide));
+        assert!(!lowered_holds(&acir, &wide));
+        let wrong = witness(&[5, 7, 36, 20, 0]);
+        assert!(!acir.is_satisfied(&wrong));
+        assert!(!lowered_holds(&acir, &wrong));
+        let nonzero = witness(&[5, 7, 35, 20, 1]);
+        assert!(!acir.is_satisfied(&nonzero));
+        assert!(!lowered_holds(&acir, &nonzero));
+    }
+
+    #[test]
+    fn inputs() {
+        let acir = program();
+        let inputs = acir.input_values(&witness(&[5, 7, 35, 20, 0]));
+        // Five witnesses, and the range check's eight bits: the others have none
+        assert_eq!(inputs.len(), 13);
+        let bits: Vec<F> = (0..8)
+            .map(|j| inputs[&format!("range_1_bit_{}", j)])
+            .collect();
+        assert_eq!(
+            bits,
+            [1, 0, 1, 0, 0, 0, 0, 0]
+                .iter()
+                .map(|b| f(*b))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "past the current witness index")]
+    fn witness_past_index() {
+        let mut acir = program();
+        acir.current_witness_index = 3;
+        acir.circuit();
+    }
+}
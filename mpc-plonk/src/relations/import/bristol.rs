@@ -0,0 +1,527 @@
+//! Boolean circuits in Bristol Fashion, the format that many MPC and garbled-circuit toolchains
+//! emit, re-proven as Plonk circuits over bits.
+//!
+//! A file is a header, then one gate a line:
+//!
+//! 1. the number of gates, and of wires;
+//! 2. the number of input values, then each one's width in bits;
+//! 3. the same for the output values;
+//! 4. each gate: its number of input and output wires, the wires, and its kind: `XOR`, `AND`,
+//!    `INV`, `EQW` (a copy), `EQ` (whose "input" is a constant bit), or `MAND` (`k` ANDs at
+//!    once: `2k k a_1 .. a_k b_1 .. b_k c_1 .. c_k MAND`).
+//!
+//! The inputs are the first wires, value by value, and the outputs the last. Each wire is set
+//! once, by an input or a gate, before any gate reads it, which [Bristol::parse] checks.
+//!
+//! In the circuit, each input value's bits are inputs, checked to be bits (see
+//! [Gadgets::bits]), and each gate is arithmetic on bits: `a + b - 2ab`, `ab`, or `1 - a`.
+//!
+//! ```
+//! use ark_bls12_377::Fr;
+//! use mpc_plonk::relations::import::bristol::Bristol;
+//! use mpc_plonk::relations::structured::assign;
+//!
+//! // A two-bit adder, modulo four
+//! let adder: Bristol = "4 8\n2 2 2\n1 2\n\n\
+//!     2 1 0 2 6 XOR\n2 1 0 2 4 AND\n2 1 1 3 5 XOR\n2 1 5 4 7 XOR\n"
+//!     .parse()
+//!     .unwrap();
+//! let (c, public) = adder.circuit::<Fr>();
+//! let bits = |x: u64| (0..2).map(|j| Fr::from((x >> j) & 1)).collect::<Vec<_>>();
+//! let mut inputs = public.clone();
+//! inputs.extend(adder.input_values("in", &[bits(3), bits(2)]));
+//! let values = assign(&c, &inputs).unwrap().0;
+//! for (var, name) in &c.pub_vars {
+//!     // 3 + 2 = 1, modulo four
+//!     let expected = match name.as_str() {
+//!         "out_0_bit_0" => Fr::from(1u64),
+//!         "out_0_bit_1" => Fr::from(0u64),
+//!         _ => public[name],
+//!     };
+//!     assert_eq!(values[*var as usize], expected);
+//! }
+//! ```
+use ark_ff::{Field, PrimeField};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+use std::str::FromStr;
+use thiserror::Error;
+
+use super::super::dsl::{CircuitBuilder, Wire};
+use super::super::gadgets::Gadgets;
+use super::super::structured::PlonkCircuit;
+
+#[derive(Error, Debug)]
+pub enum BristolError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Line {line}: {message}")]
+    Syntax { line: usize, message: String },
+    #[error("Line {line}: unknown gate {kind:?}")]
+    UnknownGate { line: usize, kind: String },
+    #[error("Line {line}: wire {wire} is not below the {n_wires} wires")]
+    NoWire {
+        line: usize,
+        wire: usize,
+        n_wires: usize,
+    },
+    #[error("Line {line}: wire {wire} is read before it is set")]
+    Unset { line: usize, wire: usize },
+    #[error("Line {line}: wire {wire} is already set")]
+    SetTwice { line: usize, wire: usize },
+    #[error("{found} gates, but the header says {expected}")]
+    GateCount { expected: usize, found: usize },
+    #[error("Output wire {0} is never set")]
+    UnsetOutput(usize),
+}
+
+/// A gate of a [Bristol] circuit, on wires: its inputs, then its output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Gate {
+    Xor(usize, usize, usize),
+    And(usize, usize, usize),
+    Inv(usize, usize),
+    /// A copy
+    Eqw(usize, usize),
+    /// A constant bit
+    Eq(bool, usize),
+}
+
+/// A boolean circuit in Bristol Fashion (see the [module docs](self)).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bristol {
+    pub n_wires: usize,
+    /// The width of each input value, in bits
+    pub inputs: Vec<usize>,
+    /// The width of each output value, in bits
+    pub outputs: Vec<usize>,
+    /// In order; a `MAND` is its `AND`s
+    pub gates: Vec<Gate>,
+}
+
+impl Bristol {
+    /// Parse and check the circuit in `text`.
+    pub fn parse(text: &str) -> Result<Self, BristolError> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(i, l)| (i + 1, l.split_whitespace().collect::<Vec<_>>()))
+            .filter(|(_, tokens)| !tokens.is_empty());
+        let mut header = || {
+            lines.next().ok_or(BristolError::Syntax {
+                line: text.lines().count(),
+                message: "Incomplete header".to_owned(),
+            })
+        };
+        let (line, counts) = header()?;
+        let counts = numbers(line, &counts)?;
+        if counts.len() != 2 {
+            return Err(syntax(line, "Expected the numbers of gates and wires"));
+        }
+        let (n_gates, n_wires) = (counts[0], counts[1]);
+        let mut widths = || {
+            let (line, tokens) = header()?;
+            let values = numbers(line, &tokens)?;
+            if values.is_empty() || values.len() != values[0] + 1 {
+                return Err(syntax(
+                    line,
+                    "Expected a count of values, then their widths",
+                ));
+            }
+            Ok((line, values[1..].to_vec()))
+        };
+        let (_, inputs) = widths()?;
+        let (line, outputs) = widths()?;
+        let (n_in, n_out): (usize, usize) = (inputs.iter().sum(), outputs.iter().sum());
+        if n_in + n_out > n_wires {
+            return Err(syntax(line, "More input and output wires than wires"));
+        }
+
+        let mut set = vec![false; n_wires];
+        set[..n_in].iter_mut().for_each(|s| *s = true);
+        let mut gates = Vec::with_capacity(n_gates);
+        let mut n_lines = 0;
+        for (line, tokens) in lines {
+            n_lines += 1;
+            let (kind, args) = tokens.split_last().unwrap();
+            let args = numbers(line, args)?;
+            if args.len() < 2 || args.len() != 2 + args[0] + args[1] {
+                return Err(syntax(line, "Expected wire counts, then the wires"));
+            }
+            let (ins, outs) = args[2..].split_at(args[0]);
+            let arity = |n_in: usize, n_out: usize| {
+                if (ins.len(), outs.len()) == (n_in, n_out) {
+                    Ok(())
+                } else {
+                    Err(syntax(
+                        line,
+                        &format!("{} takes {} wires to {}", kind, n_in, n_out),
+                    ))
+                }
+            };
+            let read = |w: usize| match set.get(w) {
+                None => Err(BristolError::NoWire {
+                    line,
+                    wire: w,
+                    n_wires,
+                }),
+                Some(false) => Err(BristolError::Unset { line, wire: w }),
+                Some(true) => Ok(w),
+            };
+            let new: Vec<Gate> = match *kind {
+                "XOR" | "AND" => {
+                    arity(2, 1)?;
+                    let (a, b) = (read(ins[0])?, read(ins[1])?);
+                    if *kind == "XOR" {
+                        vec![Gate::Xor(a, b, outs[0])]
+                    } else {
+                        vec![Gate::And(a, b, outs[0])]
+                    }
+                }
+                "INV" => {
+                    arity(1, 1)?;
+                    vec![Gate::Inv(read(ins[0])?, outs[0])]
+                }
+                "EQW" => {
+                    arity(1, 1)?;
+                    vec![Gate::Eqw(read(ins[0])?, outs[0])]
+                }
+                "EQ" => {
+                    arity(1, 1)?;
+                    match ins[0] {
+                        0 | 1 => vec![Gate::Eq(ins[0] == 1, outs[0])],
+                        _ => return Err(syntax(line, "EQ sets a wire to 0 or 1")),
+                    }
+                }
+                "MAND" => {
+                    let k = outs.len();
+                    arity(2 * k, k)?;
+                    let ands =
+                        (0..k).map(|i| Ok(Gate::And(read(ins[i])?, read(ins[k + i])?, outs[i])));
+                    ands.collect::<Result<_, BristolError>>()?
+                }
+                _ => {
+                    return Err(BristolError::UnknownGate {
+                        line,
+                        kind: kind.to_string(),
+                    })
+                }
+            };
+            for w in outs {
+                match set.get_mut(*w) {
+                    None => {
+                        return Err(BristolError::NoWire {
+                            line,
+                            wire: *w,
+                            n_wires,
+                        })
+                    }
+                    Some(true) => return Err(BristolError::SetTwice { line, wire: *w }),
+                    Some(s) => *s = true,
+                }
+            }
+            gates.extend(new);
+        }
+        if n_lines != n_gates {
+            return Err(BristolError::GateCount {
+                expected: n_gates,
+                found: n_lines,
+            });
+        }
+        if let Some(w) = (n_wires - n_out..n_wires).find(|w| !set[*w]) {
+            return Err(BristolError::UnsetOutput(w));
+        }
+        Ok(Self {
+            n_wires,
+            inputs,
+            outputs,
+            gates,
+        })
+    }
+
+    /// Parse and check the circuit that `reader` reads.
+    pub fn read(mut reader: impl Read) -> Result<Self, BristolError> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        Self::parse(&text)
+    }
+
+    /// Parse and check the circuit in the file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, BristolError> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+
+    /// The output values' bits, in wire order, of the circuit on `inputs`: each input value's
+    /// bits, in wire order.
+    pub fn evaluate(&self, inputs: &[Vec<bool>]) -> Vec<Vec<bool>> {
+        self.check_widths(inputs);
+        let mut wires = vec![false; self.n_wires];
+        let mut i = 0;
+        for value in inputs {
+            wires[i..i + value.len()].copy_from_slice(value);
+            i += value.len();
+        }
+        for gate in &self.gates {
+            match *gate {
+                Gate::Xor(a, b, c) => wires[c] = wires[a] ^ wires[b],
+                Gate::And(a, b, c) => wires[c] = wires[a] & wires[b],
+                Gate::Inv(a, c) => wires[c] = !wires[a],
+                Gate::Eqw(a, c) => wires[c] = wires[a],
+                Gate::Eq(bit, c) => wires[c] = bit,
+            }
+        }
+        self.split_outputs(&wires)
+    }
+
+    /// The circuit that `gadgets` add to: each input value `i`'s bits are the inputs
+    /// `{name}_{i}_bit_j` (see [Gadgets::bits]). Returns each output value's bits, in wire
+    /// order.
+    ///
+    /// An `XOR` is four gates, an `AND` one, and an `INV` two. Every output wire is a variable
+    /// of its own, even one that a copy or a constant sets, so that each may be public.
+    pub fn build<'a, F: Field>(
+        &self,
+        gadgets: &Gadgets<'a, F>,
+        name: &str,
+    ) -> Vec<Vec<Wire<'a, F>>> {
+        let mut wires: Vec<Option<Wire<'a, F>>> = vec![None; self.n_wires];
+        let mut i = 0;
+        for (v, width) in self.inputs.iter().enumerate() {
+            for bit in gadgets.bits(&format!("{}_{}", name, v), *width) {
+                wires[i] = Some(bit);
+                i += 1;
+            }
+        }
+        let one = gadgets.one();
+        for gate in &self.gates {
+            // Parsing checked that each wire is set before it is read
+            let w = |i: usize| wires[i].unwrap();
+            let (out, wire) = match *gate {
+                Gate::Xor(a, b, c) => (c, gadgets.xor_and(w(a), w(b)).0),
+                Gate::And(a, b, c) => (c, w(a) * w(b)),
+                Gate::Inv(a, c) => (c, one + gadgets.minus_one() * w(a)),
+                Gate::Eqw(a, c) => (c, w(a) * one),
+                Gate::Eq(true, c) => (c, one * one),
+                Gate::Eq(false, c) => (c, one + gadgets.minus_one()),
+            };
+            wires[out] = Some(wire);
+        }
+        self.split_outputs(&wires)
+            .into_iter()
+            .map(|bits| bits.into_iter().map(Option::unwrap).collect())
+            .collect()
+    }
+
+    /// The circuit alone, with each output value `i`'s bits public as `out_{i}_bit_j`, and the
+    /// input bits named `in_{i}_bit_j` (see [Bristol::build]), and the values of the gadgets'
+    /// public inputs (see [Gadgets::public_values]).
+    pub fn circuit<F: PrimeField>(&self) -> (PlonkCircuit<F>, HashMap<String, F>) {
+        let b = CircuitBuilder::new();
+        let g = Gadgets::new(&b);
+        for (i, bits) in self.build(&g, "in").into_iter().enumerate() {
+            for (j, bit) in bits.into_iter().enumerate() {
+                let name = format!("out_{}_bit_{}", i, j);
+                b.bind(&name, bit);
+                b.publicize(&name);
+            }
+        }
+        let public = g.public_values();
+        (b.finish(), public)
+    }
+
+    /// The inputs that [Bristol::build] reads, under `name`, for the input values' bits:
+    /// zeros and ones, which may be shared.
+    pub fn input_values<F: Field>(&self, name: &str, inputs: &[Vec<F>]) -> HashMap<String, F> {
+        self.check_widths(inputs);
+        inputs
+            .iter()
+            .enumerate()
+            .flat_map(|(i, bits)| {
+                bits.iter()
+                    .enumerate()
+                    .map(move |(j, bit)| (format!("{}_{}_bit_{}", name, i, j), *bit))
+            })
+            .collect()
+    }
+
+    fn check_widths<T>(&self, inputs: &[Vec<T>]) {
+        let widths: Vec<usize> = inputs.iter().map(Vec::len).collect();
+        assert_eq!(widths, self.inputs, "Inputs of the wrong widths");
+    }
+
+    /// The output values' bits, from the last of `wires`.
+    fn split_outputs<T: Clone>(&self, wires: &[T]) -> Vec<Vec<T>> {
+        let mut i = wires.len() - self.outputs.iter().sum::<usize>();
+        self.outputs
+            .iter()
+            .map(|width| {
+                i += width;
+                wires[i - width..i].to_vec()
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Bristol {
+    type Err = BristolError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+fn syntax(line: usize, message: &str) -> BristolError {
+    BristolError::Syntax {
+        line,
+        message: message.to_owned(),
+    }
+}
+
+fn numbers(line: usize, tokens: &[&str]) -> Result<Vec<usize>, BristolError> {
+    tokens
+        .iter()
+        .map(|t| {
+            t.parse()
+                .map_err(|_| syntax(line, &format!("{:?} is not a number", t)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relations::structured::assign;
+    type F = ark_bls12_377::Fr;
+
+    const ADDER: &str = "4 8
+2 2 2
+1 2
+
+2 1 0 2 6 XOR
+2 1 0 2 4 AND
+2 1 1 3 5 XOR
+2 1 5 4 7 XOR
+";
+
+    /// Every other kind of gate: `!x_0`, `x_0 and x_1` (from a `MAND`, whose other `AND` a copy
+    /// outputs), and the constants one and zero
+    const GATES: &str = "5 8
+1 2
+1 5
+
+1 1 0 3 INV
+4 2 0 1 1 0 4 2 MAND
+1 1 2 5 EQW
+1 1 1 6 EQ
+1 1 0 7 EQ
+";
+
+    /// The circuit's public outputs on `inputs`, by [Bristol::evaluate]'s value and bit, and
+    /// whether its checks pass.
+    fn run(bristol: &Bristol, inputs: &[Vec<u64>]) -> (Vec<Vec<F>>, bool) {
+        let (c, public) = bristol.circuit::<F>();
+        let bits: Vec<Vec<F>> = inputs
+            .iter()
+            .map(|v| v.iter().map(|b| F::from(*b)).collect())
+            .collect();
+        let mut witness = public.clone();
+        witness.extend(bristol.input_values("in", &bits));
+        let values = assign(&c, &witness).unwrap().0;
+        let named: HashMap<&String, F> = c
+            .pub_vars
+            .iter()
+            .map(|(v, name)| (name, values[*v as usize]))
+            .collect();
+        let ok = public.iter().all(|(name, v)| named[name] == *v);
+        let outputs = bristol
+            .outputs
+            .iter()
+            .enumerate()
+            .map(|(i, width)| {
+                (0..*width)
+                    .map(|j| named[&format!("out_{}_bit_{}", i, j)])
+                    .collect()
+            })
+            .collect();
+        (outputs, ok)
+    }
+
+    fn bits(x: u64, n: usize) -> Vec<bool> {
+        (0..n).map(|j| (x >> j) & 1 == 1).collect()
+    }
+
+    #[test]
+    fn matches_evaluation() {
+        for (text, n_inputs) in &[(ADDER, 2), (GATES, 1)] {
+            let bristol: Bristol = text.parse().unwrap();
+            for x in 0..1u64 << (2 * n_inputs) {
+                let inputs: Vec<Vec<bool>> =
+                    (0..*n_inputs).map(|i| bits(x >> (2 * i), 2)).collect();
+                let expected = bristol.evaluate(&inputs);
+                let as_u64 = |v: &Vec<Vec<bool>>| -> Vec<Vec<u64>> {
+                    v.iter()
+                        .map(|b| b.iter().map(|b| *b as u64).collect())
+                        .collect()
+                };
+                let (outputs, ok) = run(&bristol, &as_u64(&inputs));
+                assert!(ok);
+                let expected: Vec<Vec<F>> = as_u64(&expected)
+                    .into_iter()
+                    .map(|v| v.into_iter().map(F::from).collect())
+                    .collect();
+                assert_eq!(outputs, expected, "{:?}", inputs);
+            }
+        }
+        let adder: Bristol = ADDER.parse().unwrap();
+        assert_eq!(adder.evaluate(&[bits(3, 2), bits(2, 2)]), vec![bits(1, 2)]);
+        let gates: Bristol = GATES.parse().unwrap();
+        assert_eq!(
+            gates.evaluate(&[bits(3, 2)]),
+            vec![vec![false, true, true, true, false]]
+        );
+        // An input that isn't a bit
+        assert!(!run(&adder, &[vec![2, 0], vec![0, 0]]).1);
+    }
+
+    #[test]
+    fn malformed() {
+        let parse = |text: &str| text.parse::<Bristol>().unwrap_err();
+        let header = "1 4\n1 2\n1 1\n\n";
+        let gate = |g: &str| parse(&format!("{}{}\n", header, g));
+        assert!(matches!(
+            gate("2 1 0 1 3 NAND"),
+            BristolError::UnknownGate { line: 5, .. }
+        ));
+        assert!(matches!(
+            gate("2 1 0 2 3 AND"),
+            BristolError::Unset { wire: 2, .. }
+        ));
+        assert!(matches!(
+            gate("2 1 0 1 1 AND"),
+            BristolError::SetTwice { wire: 1, .. }
+        ));
+        assert!(matches!(
+            gate("2 1 0 1 4 AND"),
+            BristolError::NoWire { wire: 4, .. }
+        ));
+        assert!(matches!(
+            gate("2 1 0 1 2 AND"),
+            BristolError::UnsetOutput(3)
+        ));
+        assert!(matches!(
+            gate("1 1 0 3 AND"),
+            BristolError::Syntax { line: 5, .. }
+        ));
+        assert!(matches!(gate("1 1 2 3 EQ"), BristolError::Syntax { .. }));
+        assert!(matches!(gate("2 1 0 x 3 XOR"), BristolError::Syntax { .. }));
+        assert!(matches!(
+            parse(&format!("{}2 1 0 1 3 XOR\n2 1 0 1 2 XOR\n", header)),
+            BristolError::GateCount {
+                expected: 1,
+                found: 2
+            }
+        ));
+        assert!(matches!(parse("1 4\n1 2\n"), BristolError::Syntax { .. }));
+    }
+}
@@ -0,0 +1,449 @@
+//! Circom circuits: the constraints of a `.r1cs` file, lowered to Plonk as any R1CS circuit is
+//! (see [R1csPlonk]), and the witness of a `.wtns` file, which the king may share.
+//!
+//! Both files are sections, each a type (`u32`) and a length (`u64`) then its contents, after
+//! a magic number, a version and a count of sections, all little-endian. Field elements are
+//! `n8` bytes, little-endian, not in Montgomery form. We read:
+//!
+//! * from a `.r1cs`, the header: `n8`, the field's modulus, and counts of wires, public outputs,
+//!   public inputs, private inputs, labels and constraints; then the constraints, each three
+//!   linear combinations `A`, `B`, `C` (for `A * B = C`), each a count of terms, then each
+//!   term's wire (`u32`) and coefficient;
+//! * from a `.wtns`, the header: `n8`, the modulus, and the number of values; then the values,
+//!   one a wire.
+//!
+//! Wire 0 is the constant 1; the public outputs, then the public inputs, follow it. These are
+//! the R1CS instance, so output or input `i`, counting from wire 1, is the public wire
+//! `input_i` (see [INPUT_PREFIX]).
+//!
+//! [INPUT_PREFIX]: super::super::r1cs::INPUT_PREFIX
+use ark_ff::{BigInteger, Field, FpParameters, FromBytes, PrimeField, Zero};
+use ark_relations::r1cs::{ConstraintMatrices, Matrix};
+use ark_std::rand::Rng;
+use mpc_algebra::Reveal;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+use thiserror::Error;
+
+use super::super::r1cs::R1csPlonk;
+use crate::sections::{self, hex, read_sections, read_u32, read_u64};
+
+const R1CS_MAGIC: &[u8; 4] = b"r1cs";
+const WTNS_MAGIC: &[u8; 4] = b"wtns";
+const HEADER: u32 = 1;
+const CONSTRAINTS: u32 = 2;
+const WITNESS: u32 = 2;
+
+#[derive(Error, Debug)]
+pub enum CircomError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Not a .{0} file")]
+    WrongMagic(&'static str),
+    #[error("Unsupported .{0} version {1}")]
+    UnsupportedVersion(&'static str, u32),
+    #[error("No section {0}, or it comes before the header")]
+    MissingSection(u32),
+    #[error("Circuit is over a different field: modulus {found}, not {expected}")]
+    Field { expected: String, found: String },
+    #[error("Constraint {constraint} reads wire {wire}, of {n_wires}")]
+    NoWire {
+        constraint: usize,
+        wire: usize,
+        n_wires: usize,
+    },
+    #[error("Inconsistent header: {0}")]
+    Header(&'static str),
+}
+
+/// The constraints of a Circom circuit (see the [module docs](self)).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Circom<F> {
+    /// Wires, counting the constant 1
+    pub n_wires: usize,
+    pub n_public_outputs: usize,
+    pub n_public_inputs: usize,
+    pub n_private_inputs: usize,
+    /// Each constraint's `A`, `B` and `C`, as terms of coefficients and wires
+    pub constraints: Vec<[Vec<(F, usize)>; 3]>,
+}
+
+impl<F: PrimeField> Circom<F> {
+    /// Read the constraints of a `.r1cs` file from `reader`, checking that they are over `F`.
+    pub fn read_r1cs(mut reader: impl Read) -> Result<Self, CircomError> {
+        read_preamble(&mut reader, R1CS_MAGIC, "r1cs", 1)?;
+        let mut circom: Option<Self> = None;
+        let mut n_constraints = 0;
+        let mut has_constraints = false;
+        // Sections we don't read, like the labels, are skipped
+        read_sections(&mut reader, |section, contents| {
+            match (section, &mut circom) {
+                (HEADER, _) => {
+                    read_field::<F>(contents)?;
+                    let mut count = || read_u32(&mut *contents).map(|n| n as usize);
+                    let (n_wires, n_public_outputs, n_public_inputs, n_private_inputs) =
+                        (count()?, count()?, count()?, count()?);
+                    let _n_labels = read_u64(contents)?;
+                    n_constraints = read_u32(contents)? as usize;
+                    if 1 + n_public_outputs + n_public_inputs + n_private_inputs > n_wires {
+                        return Err(CircomError::Header("more inputs and outputs than wires"));
+                    }
+                    circom = Some(Self {
+                        n_wires,
+                        n_public_outputs,
+                        n_public_inputs,
+                        n_private_inputs,
+                        constraints: Vec::new(),
+                    });
+                }
+                (CONSTRAINTS, Some(c)) => {
+                    for constraint in 0..n_constraints {
+                        let mut lc = || -> Result<Vec<(F, usize)>, CircomError> {
+                            let n_terms = read_u32(&mut *contents)?;
+                            (0..n_terms)
+                                .map(|_| {
+                                    let wire = read_u32(&mut *contents)? as usize;
+                                    if wire >= c.n_wires {
+                                        return Err(CircomError::NoWire {
+                                            constraint,
+                                            wire,
+                                            n_wires: c.n_wires,
+                                        });
+                                    }
+                                    Ok((read_element(&mut *contents)?, wire))
+                                })
+                                .collect()
+                        };
+                        let abc = [lc()?, lc()?, lc()?];
+                        c.constraints.push(abc);
+                    }
+                    has_constraints = true;
+                }
+                _ => {}
+            }
+            Ok(())
+        })?;
+        match circom {
+            Some(c) if has_constraints => Ok(c),
+            Some(_) => Err(CircomError::MissingSection(CONSTRAINTS)),
+            None => Err(CircomError::MissingSection(HEADER)),
+        }
+    }
+
+    /// Read the constraints of the `.r1cs` file at `path` (see [Circom::read_r1cs]).
+    pub fn load_r1cs(path: impl AsRef<Path>) -> Result<Self, CircomError> {
+        Self::read_r1cs(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<F: Copy> Circom<F> {
+    /// These constraints, with `f` of each coefficient: say, [Reveal::from_public], for an MPC
+    /// field.
+    pub fn map<G>(&self, f: impl Fn(F) -> G) -> Circom<G> {
+        Circom {
+            n_wires: self.n_wires,
+            n_public_outputs: self.n_public_outputs,
+            n_public_inputs: self.n_public_inputs,
+            n_private_inputs: self.n_private_inputs,
+            constraints: self
+                .constraints
+                .iter()
+                .map(|abc| {
+                    let lc =
+                        |terms: &Vec<(F, usize)>| terms.iter().map(|(c, w)| (f(*c), *w)).collect();
+                    [lc(&abc[0]), lc(&abc[1]), lc(&abc[2])]
+                })
+                .collect(),
+        }
+    }
+
+    /// The R1CS instance's size: the constant 1, the public outputs, and the public inputs.
+    pub fn n_instance(&self) -> usize {
+        1 + self.n_public_outputs + self.n_public_inputs
+    }
+}
+
+impl<F: Field> Circom<F> {
+    /// The constraints, as arkworks matrices: the instance (see [Circom::n_instance]), then the
+    /// witness, is Circom's wire order.
+    pub fn matrices(&self) -> ConstraintMatrices<F> {
+        let matrix =
+            |i: usize| -> Matrix<F> { self.constraints.iter().map(|abc| abc[i].clone()).collect() };
+        let (a, b, c) = (matrix(0), matrix(1), matrix(2));
+        let non_zero = |m: &Matrix<F>| m.iter().map(Vec::len).sum();
+        ConstraintMatrices {
+            num_instance_variables: self.n_instance(),
+            num_witness_variables: self.n_wires - self.n_instance(),
+            num_constraints: self.constraints.len(),
+            a_num_non_zero: non_zero(&a),
+            b_num_non_zero: non_zero(&b),
+            c_num_non_zero: non_zero(&c),
+            a,
+            b,
+            c,
+        }
+    }
+
+    /// The constraints lowered to Plonk, with `witness`, every wire's value, if the circuit has
+    /// values: say, [read_wtns]'s, or under MPC, [Circom::share_witness]'s.
+    pub fn lower(&self, witness: Option<&[F]>) -> R1csPlonk<F> {
+        if let Some(w) = witness {
+            assert_eq!(w.len(), self.n_wires, "A witness has a value a wire");
+        }
+        let assignment = witness.map(|w| w.split_at(self.n_instance()));
+        R1csPlonk::from_matrices(&self.matrices(), assignment)
+    }
+
+    /// Whether `witness`, every wire's value, satisfies the constraints.
+    pub fn is_satisfied(&self, witness: &[F]) -> bool {
+        let lc = |terms: &[(F, usize)]| terms.iter().map(|(c, w)| *c * witness[*w]).sum::<F>();
+        witness.len() == self.n_wires
+            && witness[0].is_one()
+            && self
+                .constraints
+                .iter()
+                .all(|[a, b, c]| lc(a) * lc(b) == lc(c))
+    }
+}
+
+impl<F: Field + Reveal> Circom<F> {
+    /// Every wire's value, shared by the king (see [Reveal::king_share_batch]), which passes
+    /// `witness`: say, [read_wtns]'s. The other parties' `witness` is ignored, so they may pass
+    /// `None`.
+    pub fn share_witness<R: Rng>(&self, witness: Option<&[F::Base]>, rng: &mut R) -> Vec<F>
+    where
+        F::Base: Field,
+    {
+        let values = match witness {
+            Some(w) => {
+                assert_eq!(w.len(), self.n_wires, "A witness has a value a wire");
+                w.to_vec()
+            }
+            None => vec![F::Base::zero(); self.n_wires],
+        };
+        F::king_share_batch(values, rng)
+    }
+}
+
+/// Read a `.wtns` file's values from `reader`, checking that they are in `F`.
+pub fn read_wtns<F: PrimeField>(mut reader: impl Read) -> Result<Vec<F>, CircomError> {
+    read_preamble(&mut reader, WTNS_MAGIC, "wtns", 2)?;
+    let mut n_values = None;
+    let mut values = None;
+    read_sections(&mut reader, |section, contents| {
+        match (section, n_values) {
+            (HEADER, _) => {
+                read_field::<F>(contents)?;
+                n_values = Some(read_u32(contents)? as usize);
+            }
+            (WITNESS, Some(n)) => {
+                values = Some(
+                    (0..n)
+                        .map(|_| read_element(&mut *contents))
+                        .collect::<Result<Vec<F>, _>>()?,
+                );
+            }
+            _ => {}
+        }
+        Ok::<_, CircomError>(())
+    })?;
+    values.ok_or(CircomError::MissingSection(WITNESS))
+}
+
+/// Read the `.wtns` file at `path` (see [read_wtns]).
+pub fn load_wtns<F: PrimeField>(path: impl AsRef<Path>) -> Result<Vec<F>, CircomError> {
+    read_wtns(BufReader::new(File::open(path)?))
+}
+
+fn read_preamble(
+    reader: &mut impl Read,
+    magic: &[u8; 4],
+    kind: &'static str,
+    version: u32,
+) -> Result<(), CircomError> {
+    match sections::read_preamble(reader, magic)? {
+        None => Err(CircomError::WrongMagic(kind)),
+        Some(v) if v == version => Ok(()),
+        Some(v) => Err(CircomError::UnsupportedVersion(kind, v)),
+    }
+}
+
+/// Read a header's field description, checking that it is `F`'s.
+fn read_field<F: PrimeField>(reader: &mut impl Read) -> Result<(), CircomError> {
+    let n8 = read_u32(reader)? as usize;
+    let mut modulus = vec![0u8; n8];
+    reader.read_exact(&mut modulus)?;
+    let expected = F::Params::MODULUS.to_bytes_le();
+    // Also checks that elements have as many bytes as F::BigInt
+    if modulus != expected {
+        return Err(CircomError::Field {
+            expected: hex(&expected),
+            found: hex(&modulus),
+        });
+    }
+    Ok(())
+}
+
+fn read_element<F: PrimeField>(reader: &mut impl Read) -> Result<F, CircomError> {
+    let repr = F::BigInt::read(reader)?;
+    // Elements are below the modulus, so this only fails on a corrupt file
+    Ok(F::from_repr(repr)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Element too large"))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relations::flat::{Instance, Witness};
+    type F = ark_bls12_377::Fr;
+
+    /// `out = x y + 3`, for a public input `x` and a private `y`: the wires are 1, `out`, `x`,
+    /// `y`, and `x y`
+    fn circuit() -> Circom<F> {
+        let one = F::from(1u64);
+        Circom {
+            n_wires: 5,
+            n_public_outputs: 1,
+            n_public_inputs: 1,
+            n_private_inputs: 1,
+            constraints: vec![
+                [vec![(one, 2)], vec![(one, 3)], vec![(one, 4)]],
+                [
+                    vec![(one, 4), (F::from(3u64), 0)],
+                    vec![(one, 0)],
+                    vec![(one, 1)],
+                ],
+            ],
+        }
+    }
+
+    fn file(magic: &[u8], version: u32, sections: &[(u32, Vec<u8>)]) -> Vec<u8> {
+        let mut bytes = magic.to_vec();
+        bytes.extend(version.to_le_bytes());
+        bytes.extend((sections.len() as u32).to_le_bytes());
+        for (kind, contents) in sections {
+            bytes.extend(kind.to_le_bytes());
+            bytes.extend((contents.len() as u64).to_le_bytes());
+            bytes.extend(contents);
+        }
+        bytes
+    }
+
+    fn field(modulus: &[u8]) -> Vec<u8> {
+        let mut bytes = (modulus.len() as u32).to_le_bytes().to_vec();
+        bytes.extend(modulus);
+        bytes
+    }
+
+    fn element(x: F) -> Vec<u8> {
+        x.into_repr().to_bytes_le()
+    }
+
+    fn r1cs_sections(c: &Circom<F>, modulus: &[u8]) -> Vec<(u32, Vec<u8>)> {
+        let mut header = field(modulus);
+        for n in &[
+            c.n_wires,
+            c.n_public_outputs,
+            c.n_public_inputs,
+            c.n_private_inputs,
+        ] {
+            header.extend((*n as u32).to_le_bytes());
+        }
+        header.extend((c.n_wires as u64).to_le_bytes());
+        header.extend((c.constraints.len() as u32).to_le_bytes());
+        let mut constraints = Vec::new();
+        for lc in c.constraints.iter().flatten() {
+            constraints.extend((lc.len() as u32).to_le_bytes());
+            for (coeff, wire) in lc {
+                constraints.extend((*wire as u32).to_le_bytes());
+                constraints.extend(element(*coeff));
+            }
+        }
+        let labels = (0..c.n_wires as u64).flat_map(u64::to_le_bytes).collect();
+        vec![(HEADER, header), (CONSTRAINTS, constraints), (3, labels)]
+    }
+
+    fn modulus() -> Vec<u8> {
+        <F as PrimeField>::Params::MODULUS.to_bytes_le()
+    }
+
+    fn wtns(values: &[F]) -> Vec<u8> {
+        let mut header = field(&modulus());
+        header.extend((values.len() as u32).to_le_bytes());
+        let values = values.iter().flat_map(|x| element(*x)).collect();
+        file(WTNS_MAGIC, 2, &[(HEADER, header), (WITNESS, values)])
+    }
+
+    #[test]
+    fn lowered() {
+        let c = circuit();
+        let r1cs = file(R1CS_MAGIC, 1, &r1cs_sections(&c, &modulus()));
+        assert_eq!(Circom::<F>::read_r1cs(&r1cs[..]).unwrap(), c);
+        let witness: Vec<F> = [1, 23, 4, 5, 20]
+            .iter()
+            .map(|x| F::from(*x as u64))
+            .collect();
+        let read: Vec<F> = read_wtns(&wtns(&witness)[..]).unwrap();
+        assert_eq!(read, witness);
+        assert!(c.is_satisfied(&witness));
+        let mut wrong = witness.clone();
+        wrong[1] = F::from(24u64);
+        assert!(!c.is_satisfied(&wrong));
+
+        let lowered = c.lower(Some(&read));
+        let v_lowered = c.lower(None);
+        assert_eq!(lowered.inputs, Some(vec![F::from(23u64), F::from(4u64)]));
+        let inst = Instance::from_circuit(&v_lowered.circuit);
+        let public = v_lowered.public_inputs(&[F::from(23u64), F::from(4u64)]);
+        Witness::from_circuit(&inst, &lowered.circuit).check(&inst, &public);
+    }
+
+    #[test]
+    fn malformed() {
+        let c = circuit();
+        let sections = r1cs_sections(&c, &modulus());
+        let read = |bytes: Vec<u8>| Circom::<F>::read_r1cs(&bytes[..]).unwrap_err();
+        assert!(matches!(
+            read(file(WTNS_MAGIC, 1, &sections)),
+            CircomError::WrongMagic("r1cs")
+        ));
+        assert!(matches!(
+            read(file(R1CS_MAGIC, 2, &sections)),
+            CircomError::UnsupportedVersion("r1cs", 2)
+        ));
+        let mut other = modulus();
+        other[0] ^= 2;
+        assert!(matches!(
+            read(file(R1CS_MAGIC, 1, &r1cs_sections(&c, &other))),
+            CircomError::Field { .. }
+        ));
+        // Constraints before the header
+        let swapped = [sections[1].clone(), sections[0].clone()];
+        assert!(matches!(
+            read(file(R1CS_MAGIC, 1, &swapped)),
+            CircomError::MissingSection(CONSTRAINTS)
+        ));
+        let mut outside = c.clone();
+        outside.constraints[1][2] = vec![(F::from(1u64), 5)];
+        assert!(matches!(
+            read(file(R1CS_MAGIC, 1, &r1cs_sections(&outside, &modulus()))),
+            CircomError::NoWire {
+                constraint: 1,
+                wire: 5,
+                ..
+            }
+        ));
+        // A truncated file
+        let bytes = file(R1CS_MAGIC, 1, &sections);
+        assert!(matches!(
+            read(bytes[..bytes.len() - 10].to_vec()),
+            CircomError::Io(_)
+        ));
+        let bytes = wtns(&[F::from(1u64)]);
+        assert!(matches!(
+            read_wtns::<ark_bls12_381::Fr>(&bytes[..]).unwrap_err(),
+            CircomError::Field { .. }
+        ));
+    }
+}
@@ -0,0 +1,7 @@
+//! Circuits from other toolchains, as [PlonkCircuit](super::structured::PlonkCircuit)s: boolean
+//! circuits in [bristol] fashion, [circom]'s R1CS circuits and witnesses, and Noir programs'
+//! [acir] opcodes.
+
+pub mod acir;
+pub mod bristol;
+pub mod circom;
@@ -4,4 +4,11 @@
 ///
 
 pub mod structured;
+pub mod compose;
+pub mod dsl;
+pub mod expr;
+pub mod gadgets;
+pub mod import;
+pub mod optimize;
 pub mod flat;
+pub mod r1cs;
@@ -0,0 +1,234 @@
+//! A pass that shrinks a [PlonkCircuit] before it is laid out: it folds gates on constants,
+//! merges duplicate gates, and drops gates whose outputs nothing uses. Fewer gates mean a
+//! smaller gate domain, so smaller FFTs, and, under MPC, fewer multiplications to communicate.
+//!
+//! ```
+//! use ark_bls12_377::Fr;
+//! use mpc_plonk::circuit;
+//! use mpc_plonk::relations::structured::{assign, PlonkCircuit};
+//! use std::collections::HashMap;
+//!
+//! let mut c: PlonkCircuit<Fr> = circuit! {
+//!     pub two;
+//!     four = two * two;
+//!     unused = x * x;
+//!     pub out = (x * four) + (four * x);
+//! };
+//! let constants: HashMap<_, _> = vec![("two".to_owned(), Fr::from(2u64))].into_iter().collect();
+//! let opt = c.optimize(&constants);
+//! // `two * two` is folded, `x * x` dropped, `four * x` merged with `x * four`, and the padding
+//! // dropped too
+//! assert_eq!((opt.gates_before, opt.gates_after), (8, 2));
+//!
+//! let mut inputs = constants;
+//! inputs.extend(opt.folded);
+//! inputs.insert("x".to_owned(), Fr::from(3u64));
+//! let values = assign(&c, &inputs).unwrap().0;
+//! let out = c.pub_vars.iter().find(|(_, name)| *name == "out").unwrap().0;
+//! assert_eq!(values[*out as usize], Fr::from(24u64));
+//! ```
+use ark_ff::Field;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display, Formatter};
+
+use super::structured::{gate_map, PlonkCircuit, Var};
+
+/// Name prefix of the public inputs that hold constants the optimizer folded.
+pub const FOLDED_PREFIX: &str = "folded_";
+
+/// What [PlonkCircuit::optimize] did.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Optimization<F> {
+    pub gates_before: usize,
+    pub gates_after: usize,
+    /// The constants that replaced gates, as public inputs, by name: both the prover's witness
+    /// and the verifier's public inputs must include them
+    pub folded: HashMap<String, F>,
+}
+
+impl<F> Optimization<F> {
+    /// The gates the pass removed.
+    pub fn saved(&self) -> usize {
+        self.gates_before - self.gates_after
+    }
+}
+
+impl<F> Display for Optimization<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} gates to {} ({} saved, {} constants folded)",
+            self.gates_before,
+            self.gates_after,
+            self.saved(),
+            self.folded.len()
+        )
+    }
+}
+
+/// What the optimizer knows of a variable.
+#[derive(Clone, Copy)]
+enum Known<F> {
+    /// Nothing: it is kept, as it is
+    Var,
+    /// It has a constant value
+    Constant(F),
+    /// It equals an earlier variable
+    Alias(Var),
+}
+
+impl<F: Field> PlonkCircuit<F> {
+    /// Optimize this circuit, in place, given the values of its constant inputs: public inputs
+    /// whose values are fixed, such as [Gadgets::public_values]. Other names in `constants` are
+    /// ignored.
+    ///
+    /// * A gate on constants becomes a constant, as do `x * 0` and `0 * x`; `x * 1` and `x + 0`
+    ///   become `x`.
+    /// * A gate with the same operands as an earlier one, in either order, becomes that gate.
+    /// * A gate whose output neither a later gate uses nor is public or a shared output is
+    ///   dropped, as is an input that nothing uses. So is padding: layouts pad on their own.
+    ///
+    /// Public variables and shared outputs keep their gates. A constant that a remaining gate
+    /// still uses becomes a public input, named [FOLDED_PREFIX] and a number, whose value
+    /// [Optimization::folded] gives. Variables keep their order, but not their numbers, and the
+    /// values, if the circuit has any, follow them.
+    ///
+    /// Every variable must be an input, or the output of a gate on earlier variables, as
+    /// [assign] requires. The pass is deterministic, so the prover and verifier, optimizing the
+    /// same circuit with the same constants, get the same circuit.
+    ///
+    /// [Gadgets::public_values]: super::gadgets::Gadgets::public_values
+    /// [assign]: super::structured::assign
+    pub fn optimize(&mut self, constants: &HashMap<String, F>) -> Optimization<F> {
+        let gates_before = self.n_gates();
+        let gates = gate_map(self);
+        let roots: HashSet<Var> = self
+            .pub_vars
+            .keys()
+            .chain(self.shared_outputs.keys())
+            .copied()
+            .collect();
+        // Forward: fold, simplify, and merge, gates on the variables they resolve to
+        let mut known: Vec<Known<F>> = Vec::with_capacity(self.n_vars as usize);
+        let mut seen: HashMap<(bool, Var, Var), Var> = HashMap::new();
+        let mut kept: HashMap<Var, (bool, Var, Var)> = HashMap::new();
+        for v in 0..self.n_vars {
+            let k = match (self.inputs.get(&v), gates.get(&v)) {
+                (Some(name), _) if self.pub_vars.contains_key(&v) => constants
+                    .get(name)
+                    .map_or(Known::Var, |c| Known::Constant(*c)),
+                (Some(_), _) | (None, None) => Known::Var,
+                (None, Some((is_prod, a, b))) => {
+                    assert!(
+                        *a < v && *b < v,
+                        "Variable {} is the output of a gate on later variables",
+                        v
+                    );
+                    let resolve = |x: Var| match known[x as usize] {
+                        Known::Alias(y) => y,
+                        _ => x,
+                    };
+                    let (a, b) = (resolve(*a), resolve(*b));
+                    let (a, b) = (a.min(b), a.max(b));
+                    let simplified = match (known[a as usize], known[b as usize]) {
+                        _ if roots.contains(&v) => None,
+                        (Known::Constant(x), Known::Constant(y)) => {
+                            Some(Known::Constant(if *is_prod { x * y } else { x + y }))
+                        }
+                        (Known::Constant(c), _) | (_, Known::Constant(c)) => {
+                            let other = if matches!(known[a as usize], Known::Constant(_)) {
+                                b
+                            } else {
+                                a
+                            };
+                            match (*is_prod, c.is_zero(), c.is_one()) {
+                                (true, true, _) => Some(Known::Constant(F::zero())),
+                                (true, _, true) | (false, true, _) => Some(Known::Alias(other)),
+                                _ => None,
+                            }
+                        }
+                        _ => None,
+                    };
+                    match simplified {
+                        Some(k) => k,
+                        None if roots.contains(&v) => {
+                            seen.entry((*is_prod, a, b)).or_insert(v);
+                            kept.insert(v, (*is_prod, a, b));
+                            Known::Var
+                        }
+                        None => match seen.get(&(*is_prod, a, b)) {
+                            Some(w) => Known::Alias(*w),
+                            None => {
+                                seen.insert((*is_prod, a, b), v);
+                                kept.insert(v, (*is_prod, a, b));
+                                Known::Var
+                            }
+                        },
+                    }
+                }
+            };
+            known.push(k);
+        }
+        // Backward: keep what the public variables and shared outputs depend on
+        let mut live = vec![false; self.n_vars as usize];
+        for v in &roots {
+            live[*v as usize] = true;
+        }
+        for v in (0..self.n_vars).rev() {
+            if let (true, Some((_, a, b))) = (live[v as usize], kept.get(&v)) {
+                live[*a as usize] = true;
+                live[*b as usize] = true;
+            }
+        }
+        // Renumber the live variables, in order
+        let mut circuit = PlonkCircuit::new(self.values.is_some());
+        let mut renamed: HashMap<Var, Var> = HashMap::new();
+        let mut folded = HashMap::new();
+        for v in (0..self.n_vars).filter(|v| live[*v as usize]) {
+            let value = || self.values.as_ref().unwrap()[v as usize];
+            let new = match (kept.get(&v), known[v as usize], self.inputs.get(&v)) {
+                (Some((is_prod, a, b)), _, _) => {
+                    let (a, b) = (renamed[a], renamed[b]);
+                    if *is_prod {
+                        circuit.new_prod(a, b)
+                    } else {
+                        circuit.new_sum(a, b)
+                    }
+                }
+                (None, Known::Constant(c), None) => {
+                    let name = format!("{}{}", FOLDED_PREFIX, folded.len());
+                    assert!(
+                        !self.inputs.values().any(|n| *n == name),
+                        "The circuit already has an input named {:?}",
+                        name
+                    );
+                    folded.insert(name.clone(), c);
+                    let new = circuit.new_var(value);
+                    circuit.inputs.insert(new, name.clone());
+                    circuit.publicize_var(new, name);
+                    new
+                }
+                (None, _, input) => {
+                    let new = circuit.new_var(value);
+                    if let Some(name) = input {
+                        circuit.inputs.insert(new, name.clone());
+                    }
+                    new
+                }
+            };
+            renamed.insert(v, new);
+        }
+        for (v, name) in &self.pub_vars {
+            circuit.pub_vars.insert(renamed[v], name.clone());
+        }
+        for (v, name) in &self.shared_outputs {
+            circuit.shared_outputs.insert(renamed[v], name.clone());
+        }
+        *self = circuit;
+        Optimization {
+            gates_before,
+            gates_after: self.n_gates(),
+            folded,
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! Plonk circuits from arkworks R1CS circuits.
+//!
+//! Any [ConstraintSynthesizer] can be lowered to a [PlonkCircuit]. Run over an MPC field, the
+//! synthesizer generates the witness inside the MPC, and the result can be proved
+//! collaboratively like any other circuit.
+use ark_ff::Field;
+use ark_relations::r1cs::{
+    ConstraintMatrices, ConstraintSynthesizer, ConstraintSystem, SynthesisError, SynthesisMode,
+};
+use std::collections::HashMap;
+
+use super::structured::PlonkCircuit;
+
+/// Name prefix of the public wires holding R1CS instance variables (other than the constant 1).
+pub const INPUT_PREFIX: &str = "input_";
+/// Name prefix of the public wires holding the constants that the R1CS circuit uses.
+pub const CONST_PREFIX: &str = "const_";
+
+type Var = u32;
+
+/// A [PlonkCircuit] lowered from an R1CS circuit.
+///
+/// Plonk has no constant gates, so the constants of the R1CS circuit (including its
+/// coefficients) are public wires, named `const_i` in order of first use. R1CS instance variable
+/// `i` (counting from 0, not counting the constant 1) is the public wire `input_i`.
+///
+/// The constants depend only on the circuit's shape, so the verifier should take them from a
+/// circuit synthesized without values, not from the prover.
+pub struct R1csPlonk<F: Field> {
+    pub circuit: PlonkCircuit<F>,
+    /// Values of the `const_i` wires
+    pub constants: HashMap<String, F>,
+    /// The R1CS instance, not counting the constant 1 (if the circuit has values)
+    pub inputs: Option<Vec<F>>,
+}
+
+struct Lowering<F: Field> {
+    circuit: PlonkCircuit<F>,
+    consts: HashMap<F, Var>,
+    constants: HashMap<String, F>,
+    instance: Vec<Var>,
+    witness: Vec<Var>,
+}
+
+impl<F: Field> Lowering<F> {
+    fn constant(&mut self, c: F) -> Var {
+        if let Some(v) = self.consts.get(&c) {
+            return *v;
+        }
+        let name = format!("{}{}", CONST_PREFIX, self.consts.len());
+        let v = self.circuit.new_pub_var(|| c, name.clone());
+        self.consts.insert(c, v);
+        self.constants.insert(name, c);
+        v
+    }
+    fn term(&mut self, coeff: F, index: usize) -> Var {
+        if index == 0 {
+            // A multiple of the constant 1
+            return self.constant(coeff);
+        }
+        let n_instance = self.instance.len();
+        let v = if index < n_instance {
+            self.instance[index]
+        } else {
+            self.witness[index - n_instance]
+        };
+        if coeff.is_one() {
+            v
+        } else {
+            let c = self.constant(coeff);
+            self.circuit.new_prod(c, v)
+        }
+    }
+    fn lc(&mut self, terms: &[(F, usize)]) -> Var {
+        let mut vars = terms
+            .iter()
+            .map(|(c, i)| self.term(*c, *i))
+            .collect::<Vec<_>>();
+        match vars.pop() {
+            Some(last) => vars
+                .into_iter()
+                .fold(last, |acc, v| self.circuit.new_sum(acc, v)),
+            None => self.constant(F::zero()),
+        }
+    }
+}
+
+impl<F: Field> R1csPlonk<F> {
+    /// Synthesize `c` and lower it to Plonk.
+    ///
+    /// With `with_values`, the witness is generated, and the circuit has values.
+    pub fn from_synthesizer<C: ConstraintSynthesizer<F>>(
+        c: C,
+        with_values: bool,
+    ) -> Result<Self, SynthesisError> {
+        let cs = ConstraintSystem::new_ref();
+        cs.set_mode(if with_values {
+            SynthesisMode::Prove {
+                construct_matrices: true,
+            }
+        } else {
+            SynthesisMode::Setup
+        });
+        c.generate_constraints(cs.clone())?;
+        cs.finalize();
+        let matrices = cs.to_matrices().ok_or(SynthesisError::MissingCS)?;
+        let cs = cs.borrow().ok_or(SynthesisError::MissingCS)?;
+        let assignment = if with_values {
+            Some((&cs.instance_assignment[..], &cs.witness_assignment[..]))
+        } else {
+            None
+        };
+        Ok(Self::from_matrices(&matrices, assignment))
+    }
+
+    /// Lower R1CS `matrices`, with `assignment` (instance, then witness) if the circuit has
+    /// values.
+    pub fn from_matrices(
+        matrices: &ConstraintMatrices<F>,
+        assignment: Option<(&[F], &[F])>,
+    ) -> Self {
+        let mut l = Lowering {
+            circuit: PlonkCircuit::new(assignment.is_some()),
+            consts: HashMap::new(),
+            constants: HashMap::new(),
+            instance: Vec::new(),
+            witness: Vec::new(),
+        };
+        // The constant 1 is instance variable 0
+        let one = l.constant(F::one());
+        l.instance.push(one);
+        for i in 1..matrices.num_instance_variables {
+            let v = l.circuit.new_pub_var(
+                || assignment.unwrap().0[i],
+                format!("{}{}", INPUT_PREFIX, i - 1),
+            );
+            l.instance.push(v);
+        }
+        for i in 0..matrices.num_witness_variables {
+            let v = l.circuit.new_var(|| assignment.unwrap().1[i]);
+            l.witness.push(v);
+        }
+        for ((a, b), c) in matrices.a.iter().zip(&matrices.b).zip(&matrices.c) {
+            let gate = (l.lc(a), l.lc(b), l.lc(c));
+            l.circuit.prods.push(gate);
+        }
+        l.circuit.pad_to_power_of_2();
+        Self {
+            circuit: l.circuit,
+            constants: l.constants,
+            inputs: assignment.map(|(instance, _)| instance[1..].to_vec()),
+        }
+    }
+
+    /// The verifier's public inputs: our constants, and `inputs` as the R1CS instance.
+    pub fn public_inputs(&self, inputs: &[F]) -> HashMap<String, F> {
+        let mut public = self.constants.clone();
+        for (i, x) in inputs.iter().enumerate() {
+            public.insert(format!("{}{}", INPUT_PREFIX, i), *x);
+        }
+        public
+    }
+}
@@ -1,5 +1,16 @@
-use ark_ff::Field;
-use std::collections::HashMap;
+use ark_ff::{FftField, Field, PrimeField, Zero};
+use ark_std::rand::Rng;
+use mpc_algebra::{defer, Reveal};
+use std::collections::{HashMap, HashSet};
+use thiserror::Error;
+
+use super::dsl::CircuitBuilder;
+use super::flat::{Domains, GATE_WIDTH};
+use super::gadgets::Gadgets;
+use crate::util::PoseidonParameters;
+
+/// Name prefix of the public wires that link the chunks of a split circuit.
+pub const LINK_PREFIX: &str = "link_";
 
 pub struct PlonkCircuit<F: Field> {
     pub n_vars: u32,
@@ -7,9 +18,259 @@ pub struct PlonkCircuit<F: Field> {
     pub prods: Vec<(Var, Var, Var)>,
     pub sums: Vec<(Var, Var, Var)>,
     pub values: Option<Vec<F>>,
+    /// Input variables, whose values a [Witness] supplies by name (see [assign])
+    pub inputs: HashMap<Var, String>,
+    /// Outputs that stay secret, by name (see [PlonkCircuit::share_output_var])
+    pub shared_outputs: HashMap<Var, String>,
+}
+
+pub(super) type Var = u32;
+
+/// Where the values of a circuit's inputs (see [PlonkCircuit::new_input]) come from: a map from
+/// input names to values, or a closure.
+pub trait Witness<F> {
+    /// The value of the input named `name`, if there is one.
+    fn input(&self, name: &str) -> Option<F>;
+}
+
+impl<F: Clone> Witness<F> for HashMap<String, F> {
+    fn input(&self, name: &str) -> Option<F> {
+        self.get(name).cloned()
+    }
+}
+
+impl<F, W: Fn(&str) -> Option<F>> Witness<F> for W {
+    fn input(&self, name: &str) -> Option<F> {
+        self(name)
+    }
+}
+
+/// A value for every variable of a circuit, in variable order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Assignment<F>(pub Vec<F>);
+
+/// The native value of every local variable (see [assign_hybrid]), in variable order, and `None`
+/// for every shared one. Only the king's values are the variables'; the others' are zeros.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LocalAssignment<B>(pub Vec<Option<B>>);
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum AssignError {
+    #[error("No value for input {0:?}")]
+    MissingInput(String),
+    #[error("Variable {0} is neither an input nor the output of a gate on earlier variables")]
+    Unassignable(Var),
+}
+
+/// Compute the value of every variable of `circuit` from the values that `witness` gives its
+/// inputs.
+///
+/// The circuit may have been built without values, as the verifier builds it: every variable
+/// must be an input, or the output of a gate on earlier variables, as
+/// [PlonkCircuit::new_input], [PlonkCircuit::new_sum] and [PlonkCircuit::new_prod] make them.
+pub fn assign<F: Field>(
+    circuit: &PlonkCircuit<F>,
+    witness: &impl Witness<F>,
+) -> Result<Assignment<F>, AssignError> {
+    let gates = gate_map(circuit);
+    let mut values: Vec<F> = Vec::with_capacity(circuit.n_vars as usize);
+    for v in 0..circuit.n_vars {
+        let value = match (circuit.inputs.get(&v), gates.get(&v)) {
+            (Some(name), _) => witness
+                .input(name)
+                .ok_or_else(|| AssignError::MissingInput(name.clone()))?,
+            (None, Some((is_prod, a, b))) if *a < v && *b < v => {
+                let (a, b) = (values[*a as usize], values[*b as usize]);
+                if *is_prod {
+                    a * b
+                } else {
+                    a + b
+                }
+            }
+            _ => return Err(AssignError::Unassignable(v)),
+        };
+        values.push(value);
+    }
+    Ok(Assignment(values))
+}
+
+/// Like [assign], but for a witness that is mostly known to one party, the king, in the clear.
+///
+/// The inputs named in `local_inputs` are the king's: it passes their values as `local`. The
+/// other parties' values are ignored, so they may pass `None`. All parties must agree on
+/// `local_inputs`. Every other input comes from `shared`, as in [assign].
+///
+/// A variable is *local* if it is a local input, or a gate on local variables. The king computes
+/// local variables natively, in `F::Base`, at no cost to the others. Then, at the boundary, it
+/// secret-shares all of them in one round (see [Reveal::king_share_batch]), since every wire of
+/// the proof is shared. Only gates that touch a shared variable use MPC arithmetic.
+///
+/// The native values are returned too, so that the prover can compute on them natively as well
+/// (see [super::flat::LocalRows]).
+pub fn assign_hybrid<F: Field + Reveal, R: Rng>(
+    circuit: &PlonkCircuit<F>,
+    local_inputs: &HashSet<String>,
+    local: Option<&dyn Witness<F::Base>>,
+    shared: &impl Witness<F>,
+    rng: &mut R,
+) -> Result<(Assignment<F>, LocalAssignment<F::Base>), AssignError>
+where
+    F::Base: Field,
+{
+    let gates = gate_map(circuit);
+    // The native value of each local variable, in variable order
+    let mut native: Vec<Option<F::Base>> = Vec::with_capacity(circuit.n_vars as usize);
+    for v in 0..circuit.n_vars {
+        let value = match (circuit.inputs.get(&v), gates.get(&v)) {
+            (Some(name), _) if local_inputs.contains(name) => Some(match local {
+                Some(witness) => witness
+                    .input(name)
+                    .ok_or_else(|| AssignError::MissingInput(name.clone()))?,
+                None => F::Base::zero(),
+            }),
+            (Some(_), _) => None,
+            (None, Some((is_prod, a, b))) if *a < v && *b < v => {
+                match (native[*a as usize], native[*b as usize]) {
+                    (Some(a), Some(b)) if *is_prod => Some(a * b),
+                    (Some(a), Some(b)) => Some(a + b),
+                    _ => None,
+                }
+            }
+            _ => return Err(AssignError::Unassignable(v)),
+        };
+        native.push(value);
+    }
+    let mut promoted =
+        F::king_share_batch(native.iter().flatten().cloned().collect(), rng).into_iter();
+    let mut values: Vec<F> = Vec::with_capacity(circuit.n_vars as usize);
+    for (v, native) in (0..circuit.n_vars).zip(&native) {
+        let value = match (native, circuit.inputs.get(&v), gates.get(&v)) {
+            (Some(_), _, _) => promoted.next().unwrap(),
+            (None, Some(name), _) => shared
+                .input(name)
+                .ok_or_else(|| AssignError::MissingInput(name.clone()))?,
+            (None, None, Some((is_prod, a, b))) => {
+                let (a, b) = (values[*a as usize], values[*b as usize]);
+                if *is_prod {
+                    a * b
+                } else {
+                    a + b
+                }
+            }
+            (None, None, None) => unreachable!("variable {} was checked above", v),
+        };
+        values.push(value);
+    }
+    Ok((Assignment(values), LocalAssignment(native)))
+}
+
+/// The gate that outputs each variable: whether it is a product, and its operands.
+pub(super) fn gate_map<F: Field>(circuit: &PlonkCircuit<F>) -> HashMap<Var, (bool, Var, Var)> {
+    let mut gates = HashMap::new();
+    for (a, b, o) in &circuit.prods {
+        gates.insert(*o, (true, *a, *b));
+    }
+    for (a, b, o) in &circuit.sums {
+        gates.insert(*o, (false, *a, *b));
+    }
+    gates
 }
 
-type Var = u32;
+/// A circuit split into chunks that each fit in an evaluation domain.
+///
+/// A variable used by more than one chunk is a *link*. Its value stays secret: each chunk that
+/// uses it makes public, as `link_i`, a commitment to it, the Poseidon hash of the value and a
+/// random blind that all those chunks share. The verifier checks that the chunks agree on the
+/// commitments, so, short of a Poseidon collision, on the values.
+pub struct ChunkedCircuit<F: Field> {
+    pub chunks: Vec<PlonkCircuit<F>>,
+    /// Link commitments, in link order (if the circuit has values)
+    pub links: Option<Vec<F>>,
+    /// The commitments' Poseidon constants, public inputs of the chunks, for the verifier to add
+    /// to the circuit's
+    pub public: HashMap<String, F>,
+}
+
+/// A circuit that commits to `m` values, `value_k`: `commitment_k`, public, is the Poseidon hash
+/// of `value_k` and its blind, `blind_k`. The Poseidon constants are public inputs, returned with
+/// `params`' values.
+fn link_commitments<F: PrimeField>(
+    m: usize,
+    params: &PoseidonParameters<F>,
+) -> (PlonkCircuit<F>, HashMap<String, F>) {
+    let b = CircuitBuilder::new();
+    let g = Gadgets::new(&b);
+    for k in 0..m {
+        let name = format!("commitment_{}", k);
+        let opening = [
+            b.wire(&format!("value_{}", k)),
+            b.wire(&format!("blind_{}", k)),
+        ];
+        b.bind(&name, g.poseidon(&opening));
+        b.publicize(&name);
+    }
+    let constants = g.public_values_with(|| params.clone());
+    (b.finish_unpadded(), constants)
+}
+
+/// Where to cut `gates` into chunks: the end of each, as far along as its gates, with
+/// `commitment_gates` more for each of its links, are at most `max_gates`. A link is a variable
+/// of the chunk, other than a `public` one, that another chunk's gates use too.
+fn chunk_ends(
+    gates: &[(bool, (Var, Var, Var))],
+    public: &HashMap<Var, String>,
+    max_gates: usize,
+    commitment_gates: usize,
+) -> Vec<usize> {
+    // The first and last gate of each variable that may be a link
+    let mut spans: HashMap<Var, (usize, usize)> = HashMap::new();
+    for (i, (_, (a, b, o))) in gates.iter().enumerate() {
+        for v in [a, b, o] {
+            if !public.contains_key(v) {
+                spans.entry(*v).or_insert((i, i)).1 = i;
+            }
+        }
+    }
+    let mut ends = Vec::new();
+    let mut start = 0;
+    while start < gates.len() {
+        // The chunk's variables so far, and how many of them are links: used before the chunk,
+        // or after its gates so far
+        let mut seen: HashSet<Var> = HashSet::new();
+        let mut links = 0usize;
+        let mut end = None;
+        for (i, (_, (a, b, o))) in gates.iter().enumerate().skip(start).take(max_gates) {
+            let mut vars = vec![*a, *b, *o];
+            vars.sort_unstable();
+            vars.dedup();
+            for v in vars {
+                let (first, last) = match spans.get(&v) {
+                    Some(span) => *span,
+                    None => continue,
+                };
+                if seen.insert(v) {
+                    if first < start || last > i {
+                        links += 1;
+                    }
+                } else if first >= start && last == i {
+                    links -= 1;
+                }
+            }
+            if i + 1 - start + links * commitment_gates <= max_gates {
+                end = Some(i + 1);
+            }
+        }
+        let end = end.unwrap_or_else(|| {
+            panic!(
+                "Gate {}, with commitments to its links, takes more than {} gates",
+                start, max_gates
+            )
+        });
+        ends.push(end);
+        start = end;
+    }
+    ends
+}
 
 impl<F: Field> PlonkCircuit<F> {
     pub fn new(values: bool) -> Self {
@@ -19,14 +280,49 @@ impl<F: Field> PlonkCircuit<F> {
             prods: Vec::new(),
             sums: Vec::new(),
             values: if values { Some(Vec::new()) } else { None },
+            inputs: HashMap::new(),
+            shared_outputs: HashMap::new(),
         }
     }
+    /// This circuit, with the values of `assignment` (see [assign]).
+    pub fn with_assignment(mut self, assignment: Assignment<F>) -> Self {
+        assert_eq!(
+            assignment.0.len(),
+            self.n_vars as usize,
+            "Assignment is for a different circuit"
+        );
+        self.values = Some(assignment.0);
+        self
+    }
     pub fn new_var(&mut self, value: impl FnOnce() -> F) -> Var {
         self.n_vars += 1;
         self.values.as_mut().map(|v| v.push(value()));
         self.n_vars - 1
     }
+    /// An input variable, whose value a [Witness] supplies under `name` (see [assign]).
+    ///
+    /// Only for circuits built without values.
+    pub fn new_input(&mut self, name: String) -> Var {
+        assert!(
+            self.values.is_none(),
+            "Inputs are assigned later, so the circuit can't have values yet"
+        );
+        self.n_vars += 1;
+        self.inputs.insert(self.n_vars - 1, name);
+        self.n_vars - 1
+    }
+    /// A public input variable, named `name` both as an input and to the verifier.
+    pub fn new_pub_input(&mut self, name: String) -> Var {
+        let v = self.new_input(name.clone());
+        self.publicize_var(v, name);
+        v
+    }
     pub fn publicize_var(&mut self, v: Var, name: String) {
+        assert!(
+            !self.shared_outputs.contains_key(&v),
+            "Variable {} is a shared output, so it can't be public",
+            v
+        );
         if let Some(old_name) = self.pub_vars.insert(v, name) {
             panic!(
                 "Variable {} was already public as {:?}, but is now being bound to {:?}",
@@ -34,6 +330,60 @@ impl<F: Field> PlonkCircuit<F> {
             );
         }
     }
+    /// Keep `v` secret, as an output named `name`, rather than making it public.
+    ///
+    /// The verifier never sees a shared output: it is a wire like any other, proven consistent
+    /// with the circuit. The prover reads its value with [PlonkCircuit::shared_output_values];
+    /// under MPC, that is each party's share, for a follow-on protocol, or to be revealed (see
+    /// [mpc_algebra::Reveal]) once the outputs may be opened.
+    pub fn share_output_var(&mut self, v: Var, name: String) {
+        assert!(
+            !self.pub_vars.contains_key(&v),
+            "Variable {} is public, so it can't be a shared output",
+            v
+        );
+        if let Some(old_name) = self.shared_outputs.insert(v, name) {
+            panic!(
+                "Variable {} was already a shared output as {:?}, but is now being bound to {:?}",
+                v, old_name, self.shared_outputs[&v]
+            );
+        }
+    }
+    /// The value of each shared output (see [PlonkCircuit::share_output_var]), by name, if the
+    /// circuit has values.
+    pub fn shared_output_values(&self) -> Option<HashMap<String, F>> {
+        self.values.as_ref().map(|vals| {
+            self.shared_outputs
+                .iter()
+                .map(|(v, name)| (name.clone(), vals[*v as usize]))
+                .collect()
+        })
+    }
+    /// The value of each public variable, by name, in `assignment` (say, [assign]'s): what the
+    /// verifier checks the proof against. Under MPC, the values stay shared; see
+    /// [PlonkCircuit::reveal_public_outputs].
+    pub fn eval_public_outputs(&self, assignment: &Assignment<F>) -> HashMap<String, F> {
+        self.pub_vars
+            .iter()
+            .map(|(v, name)| (name.clone(), assignment.0[*v as usize]))
+            .collect()
+    }
+    /// [PlonkCircuit::eval_public_outputs], opened: in MPC, every party learns the public
+    /// variables' values, in one round, and no other variable's. Every party must call this
+    /// together, with the same circuit. The values are public, so
+    /// [Reveal::unwrap_as_public] takes them without another round; a map's order differs
+    /// from party to party, so revealing them in it would not agree.
+    pub fn reveal_public_outputs(&self, assignment: &Assignment<F>) -> HashMap<String, F> {
+        // In variable order, for the parties to open the same wires together
+        let mut vars: Vec<(&Var, &String)> = self.pub_vars.iter().collect();
+        vars.sort_unstable();
+        let values: Vec<F> = vars.iter().map(|(v, _)| assignment.0[**v as usize]).collect();
+        let values = defer::enqueue(values).get();
+        vars.into_iter()
+            .map(|(_, name)| name.clone())
+            .zip(values)
+            .collect()
+    }
     pub fn new_sum(&mut self, a: Var, b: Var) -> Var {
         self.values.as_mut().map(|v| {
             let o = v[a as usize] + v[b as usize];
@@ -60,6 +410,9 @@ impl<F: Field> PlonkCircuit<F> {
     pub fn n_gates(&self) -> usize {
         self.prods.len() + self.sums.len()
     }
+    /// Pads with sums of the last variable, up to a power-of-two number of gates. Layouts pad
+    /// on their own (see [super::flat::Instance::n_gates]), so this is only needed to keep a
+    /// circuit's layout, and digest, as they were.
     pub fn pad_to_power_of_2(&mut self) {
         let n = self.n_gates().next_power_of_two();
         assert!(self.n_vars > 0, "Cannot pad an empty circuit!");
@@ -69,14 +422,231 @@ impl<F: Field> PlonkCircuit<F> {
         }
         assert!(self.n_gates().is_power_of_two());
     }
-    pub fn new_squaring_circuit(steps: usize, start: Option<F>) -> Self {
-        let mut self_ = PlonkCircuit::new(start.is_some());
-        let mut v = self_.new_var(|| start.unwrap());
+    /// A circuit that squares its input `start` `steps` times, with the result public as
+    /// `out`, and no values (see [assign]).
+    pub fn squaring_circuit(steps: usize) -> Self {
+        let mut self_ = Self::unpadded_squaring_circuit(steps);
+        self_.pad_to_power_of_2();
+        self_
+    }
+    /// [PlonkCircuit::squaring_circuit], with a gate per step and no more: its layout pads it
+    /// (see [super::flat::Instance::n_gates]).
+    pub fn unpadded_squaring_circuit(steps: usize) -> Self {
+        let mut self_ = PlonkCircuit::new(false);
+        let mut v = self_.new_input("start".to_owned());
         for _ in 0..steps {
             v = self_.new_prod(v, v);
         }
-        self_.pad_to_power_of_2();
         self_.publicize_var(v, "out".to_owned());
         self_
     }
+    pub fn new_squaring_circuit(steps: usize, start: Option<F>) -> Self {
+        let self_ = Self::squaring_circuit(steps);
+        match start {
+            Some(start) => {
+                let assignment = assign(&self_, &|_: &str| Some(start)).unwrap();
+                self_.with_assignment(assignment)
+            }
+            None => self_,
+        }
+    }
+}
+
+impl<F: FftField> PlonkCircuit<F> {
+    /// The most gates that fit in our evaluation domains, given the field's two-adicity: a
+    /// [GATE_WIDTH]-column layout has [GATE_WIDTH] times as many wires as gates, and its wiring
+    /// quotient is computed over a coset with room for a column more (see [Domains::fit]).
+    ///
+    /// [GATE_WIDTH]: super::flat::GATE_WIDTH
+    /// [Domains::fit]: super::flat::Domains::fit
+    pub fn max_gates() -> usize {
+        (0..usize::BITS)
+            .map(|k| 1usize << k)
+            .take_while(|n| Domains::<F>::fit(*n, GATE_WIDTH))
+            .last()
+            .expect("The field's domains fit no gates")
+    }
+}
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Split into chunks of at most `max_gates` gates (before padding), commitments to their
+    /// links included (see [ChunkedCircuit]).
+    ///
+    /// `params` are the Poseidon parameters of the commitments (see
+    /// [PoseidonParameters::map]), and `rng` draws their blinds, if the circuit has values:
+    /// under MPC, as shares.
+    pub fn split<R: Rng>(
+        &self,
+        max_gates: usize,
+        params: &PoseidonParameters<F>,
+        rng: &mut R,
+    ) -> ChunkedCircuit<F> {
+        assert!(max_gates.is_power_of_two(), "Chunks must have a power-of-two size");
+        let gates: Vec<(bool, (Var, Var, Var))> = self
+            .prods
+            .iter()
+            .map(|g| (true, *g))
+            .chain(self.sums.iter().map(|g| (false, *g)))
+            .collect();
+        let (commitment, constants) = link_commitments(1, params);
+        let ends = chunk_ends(&gates, &self.pub_vars, max_gates, commitment.n_gates());
+        let chunk_gates: Vec<_> = ends
+            .iter()
+            .scan(0, |start, end| {
+                let chunk = &gates[*start..*end];
+                *start = *end;
+                Some(chunk)
+            })
+            .collect();
+        // Which chunks each variable appears in
+        let mut var_chunks: HashMap<Var, Vec<usize>> = HashMap::new();
+        for (j, gs) in chunk_gates.iter().enumerate() {
+            for (_, (a, b, c)) in gs.iter() {
+                for v in &[*a, *b, *c] {
+                    let cs = var_chunks.entry(*v).or_default();
+                    if cs.last() != Some(&j) {
+                        cs.push(j);
+                    }
+                }
+            }
+        }
+        // Public variables are already pinned down by the verifier's inputs
+        let mut links: Vec<Var> = var_chunks
+            .iter()
+            .filter(|(v, cs)| cs.len() > 1 && !self.pub_vars.contains_key(v))
+            .map(|(v, _)| *v)
+            .collect();
+        links.sort_unstable();
+        let blinds: Option<Vec<F>> = self
+            .values
+            .as_ref()
+            .map(|_| links.iter().map(|_| F::rand(rng)).collect());
+        let mut commitments: Vec<Option<F>> = vec![None; links.len()];
+        let chunks = chunk_gates
+            .into_iter()
+            .map(|gs| {
+                let mut c = PlonkCircuit::new(self.values.is_some());
+                let mut local: HashMap<Var, Var> = HashMap::new();
+                for (is_prod, (a, b, o)) in gs {
+                    let mut map = |v: Var| {
+                        *local.entry(v).or_insert_with(|| {
+                            c.new_var(|| self.values.as_ref().unwrap()[v as usize])
+                        })
+                    };
+                    let gate = (map(*a), map(*b), map(*o));
+                    if *is_prod {
+                        c.prods.push(gate);
+                    } else {
+                        c.sums.push(gate);
+                    }
+                }
+                for (v, name) in &self.pub_vars {
+                    if let Some(l) = local.get(v) {
+                        c.publicize_var(*l, name.clone());
+                    }
+                }
+                let used: Vec<(usize, Var)> = links
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, v)| local.get(v).map(|l| (i, *l)))
+                    .collect();
+                if !used.is_empty() {
+                    let values = c.commit_links(&used, params, blinds.as_deref());
+                    for ((i, _), value) in used.iter().zip(values.into_iter().flatten()) {
+                        commitments[*i] = Some(value);
+                    }
+                }
+                c.pad_to_power_of_2();
+                c
+            })
+            .collect();
+        ChunkedCircuit {
+            chunks,
+            links: self
+                .values
+                .as_ref()
+                .map(|_| commitments.into_iter().map(Option::unwrap).collect()),
+            public: if links.is_empty() {
+                HashMap::new()
+            } else {
+                constants
+            },
+        }
+    }
+    /// Split into as few chunks as fit in our evaluation domains (see
+    /// [PlonkCircuit::split]).
+    ///
+    /// Circuits that already fit become a single chunk, with no links.
+    pub fn split_to_fit<R: Rng>(
+        &self,
+        params: &PoseidonParameters<F>,
+        rng: &mut R,
+    ) -> ChunkedCircuit<F> {
+        self.split(Self::max_gates(), params, rng)
+    }
+    /// Make public, as `link_i`, a commitment to each of `links`' variables, by its link `i`,
+    /// with the link's blind (see [link_commitments]). Public variables named like the
+    /// commitments' constants are reused. Returns the commitments' values, if the circuit has
+    /// values.
+    fn commit_links(
+        &mut self,
+        links: &[(usize, Var)],
+        params: &PoseidonParameters<F>,
+        blinds: Option<&[F]>,
+    ) -> Option<Vec<F>> {
+        let (module, constants) = link_commitments(links.len(), params);
+        let module_values = self.values.as_ref().map(|values| {
+            let mut inputs = constants;
+            for (k, (i, v)) in links.iter().enumerate() {
+                inputs.insert(format!("value_{}", k), values[*v as usize]);
+                inputs.insert(format!("blind_{}", k), blinds.unwrap()[*i]);
+            }
+            assign(&module, &inputs)
+                .expect("Commitments are assignable")
+                .0
+        });
+        let named: HashMap<&String, Var> = self.pub_vars.iter().map(|(v, n)| (n, *v)).collect();
+        let module_inputs: HashMap<&String, Var> =
+            module.inputs.iter().map(|(v, n)| (n, *v)).collect();
+        let mut map: Vec<Option<Var>> = vec![None; module.n_vars as usize];
+        for (k, (_, v)) in links.iter().enumerate() {
+            map[module_inputs[&format!("value_{}", k)] as usize] = Some(*v);
+        }
+        for (name, v) in &module_inputs {
+            if let Some(here) = named.get(name) {
+                map[*v as usize] = Some(*here);
+            }
+        }
+        let mut fresh = Vec::new();
+        for (mv, here) in map.iter_mut().enumerate() {
+            if here.is_none() {
+                let value = || module_values.as_ref().unwrap()[mv];
+                *here = Some(self.new_var(value));
+                fresh.push(mv as Var);
+            }
+        }
+        let map: Vec<Var> = map.into_iter().map(Option::unwrap).collect();
+        let gate =
+            |(a, b, o): &(Var, Var, Var)| (map[*a as usize], map[*b as usize], map[*o as usize]);
+        self.prods.extend(module.prods.iter().map(gate));
+        self.sums.extend(module.sums.iter().map(gate));
+        for mv in fresh {
+            if let Some(name) = module.pub_vars.get(&mv) {
+                let name = match name.strip_prefix("commitment_") {
+                    Some(k) => format!("{}{}", LINK_PREFIX, links[k.parse::<usize>().unwrap()].0),
+                    None => name.clone(),
+                };
+                self.publicize_var(map[mv as usize], name);
+            }
+        }
+        module_values.map(|values| {
+            (0..links.len())
+                .map(|k| {
+                    let name = format!("commitment_{}", k);
+                    let (v, _) = module.pub_vars.iter().find(|(_, n)| **n == name).unwrap();
+                    values[*v as usize]
+                })
+                .collect()
+        })
+    }
 }
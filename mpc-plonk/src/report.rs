@@ -0,0 +1,155 @@
+//! Why a proof failed verification (see [crate::Plonk::verify_with_report]).
+use mpc_net::{Abort, AbortCode};
+use std::fmt::{self, Display, Formatter};
+use thiserror::Error;
+
+use crate::relations::flat::CircuitDigest;
+
+/// One check that a proof failed.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum Failure<F: Display> {
+    /// A polynomial identity did not hold at the challenge point(s).
+    #[error(
+        "{identity} identity failed: {left} not equal to {right} (at {})",
+        show_challenges(.challenges)
+    )]
+    Identity {
+        /// Which identity: public, partial product, total product, wiring, or quotient (in a
+        /// [crate::SmallProof])
+        identity: &'static str,
+        /// Its left side, evaluated
+        left: F,
+        /// Its right side, evaluated
+        right: F,
+        /// The challenges it was evaluated with, by transcript label
+        challenges: Vec<(&'static str, F)>,
+    },
+    /// An opening of a commitment did not verify.
+    #[error("Verification failed: {commitment} at {point}")]
+    Opening {
+        /// The commitment's label (or the linear combination's, for a [crate::GateProof])
+        commitment: String,
+        /// The point it was opened at
+        point: F,
+        /// The claimed value
+        value: F,
+    },
+    /// A batch of openings did not verify (see [crate::Verifier::batch_openings]): at least one
+    /// of them is wrong.
+    #[error("Verification failed: a batch of {count} openings")]
+    BatchedOpenings { count: usize },
+    /// A commitment lacked what its degree bound is enforced with (for KZG, a shifted
+    /// commitment).
+    #[error("Commitment {commitment} does not enforce its degree bound {bound}")]
+    DegreeBound {
+        /// The commitment's label
+        commitment: String,
+        bound: usize,
+    },
+    /// The circuit's layout differs from the one the verifier key is for (see
+    /// [crate::relations::flat::Instance::digest]).
+    #[error("Circuit layout differs from the verifier key's: digest {found}, not {expected}")]
+    Circuit {
+        expected: CircuitDigest,
+        found: CircuitDigest,
+    },
+    /// The proof was made with a different beacon round than the verifier's.
+    #[error("Proof uses a different beacon round: {found:?}, not {expected:?}")]
+    BeaconRound {
+        expected: Option<u64>,
+        found: Option<u64>,
+    },
+    /// A [crate::SmallProof] had the wrong number of quotient chunk commitments or openings.
+    #[error(
+        "Wrong number of quotient chunks: {commitments} commitments and {openings} openings, not \
+         {expected}"
+    )]
+    QuotientChunks {
+        expected: usize,
+        commitments: usize,
+        openings: usize,
+    },
+    /// A public input of the circuit was not given to the verifier.
+    #[error("Missing public input {name}")]
+    MissingInput { name: String },
+    /// The proof is in a format version that this verifier doesn't verify (see
+    /// [crate::format]).
+    #[error("Unsupported proof format version {version}")]
+    Version { version: u8 },
+    /// A proof had the wrong number of some per-column commitment, opening, or value.
+    #[error("Wrong number of {what}: {found}, not {expected}")]
+    Columns {
+        /// What was miscounted
+        what: &'static str,
+        expected: usize,
+        found: usize,
+    },
+    /// An [crate::AggregatedProof] had the wrong number of proofs (for the public inputs given),
+    /// or of openings.
+    #[error("Wrong number of aggregated {what}: {found}, not {expected}")]
+    Aggregated {
+        /// What was miscounted
+        what: &'static str,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl<F: Display> Failure<F> {
+    /// Why a collaborative proof that fails this check was computed wrongly, if the parties were
+    /// honest (see [AbortCode]).
+    pub fn abort_code(&self) -> AbortCode {
+        match self {
+            // The proof was computed as specified, so its witness is wrong
+            Failure::Identity { .. } => AbortCode::ConstraintUnsatisfied,
+            // The prover's keys (or circuit) differ from the verifier's
+            Failure::Opening { .. }
+            | Failure::BatchedOpenings { .. }
+            | Failure::DegreeBound { .. }
+            | Failure::Circuit { .. }
+            | Failure::QuotientChunks { .. }
+            | Failure::Columns { .. }
+            | Failure::Aggregated { .. }
+            | Failure::MissingInput { .. } => AbortCode::SrsMismatch,
+            Failure::BeaconRound { .. } => AbortCode::TranscriptDivergence,
+            Failure::Version { .. } => AbortCode::ConfigMismatch,
+        }
+    }
+}
+
+fn show_challenges<F: Display>(challenges: &[(&'static str, F)]) -> String {
+    challenges
+        .iter()
+        .map(|(label, x)| format!("{} = {}", label, x))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Every check that a proof failed, in the order the verifier made them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerificationReport<F: Display> {
+    pub failures: Vec<Failure<F>>,
+}
+
+impl<F: Display> Display for VerificationReport<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Proof failed {} check(s)", self.failures.len())?;
+        for failure in &self.failures {
+            write!(f, "\n  {}", failure)?;
+        }
+        Ok(())
+    }
+}
+
+impl<F: Display + fmt::Debug> std::error::Error for VerificationReport<F> {}
+
+impl<F: Display> VerificationReport<F> {
+    /// An abort with the first failure's [Failure::abort_code], detailing every failure.
+    pub fn abort(&self) -> Abort {
+        let code = self
+            .failures
+            .first()
+            .map_or(AbortCode::ConstraintUnsatisfied, Failure::abort_code);
+        Abort::new(code, self.to_string())
+    }
+}
@@ -1,41 +1,25 @@
-use ark_ec::PairingEngine;
-use ark_poly_commit::marlin_pc;
+use ark_ec::{AffineCurve, PairingEngine};
+use ark_poly_commit::{ipa_pc, marlin_pc};
 use mpc_algebra::*;
 
+use crate::relations::flat::CircuitDigest;
 use crate::*;
 
-impl<C: Reveal, O: Reveal> Reveal for GateProof<C, O> {
-    type Base = GateProof<C::Base, O::Base>;
-    struct_reveal_impl!(GateProof<C, O>, GateProof;
-        (C, q_cmt), (O, s_open), (O, q_open), (O, p_open), (O, p_w_open), (O, p_w2_open));
-}
-
-impl<C: Reveal, O: Reveal> Reveal for PublicProof<C, O> {
-    type Base = PublicProof<C::Base, O::Base>;
-    struct_reveal_impl!(PublicProof<C, O>, PublicProof;
-        (C, q_cmt), (O, q_open), (O, p_open));
-}
-
-impl<C: Reveal, O: Reveal> Reveal for ProductProof<C, O> {
-    type Base = ProductProof<C::Base, O::Base>;
-    struct_reveal_impl!(ProductProof<C, O>, ProductProof;
-        (C, q_cmt), (C, t_cmt), (O, t_wk_open), (O, t_r_open), (O, t_wr_open), (O, f_wr_open), (O, q_r_open));
-}
-
-impl<C: Reveal, O: Reveal> Reveal for WiringProof<C, O> {
-    type Base = WiringProof<C::Base, O::Base>;
-    struct_reveal_impl!(WiringProof<C, O>, WiringProof;
-        (C, l1_cmt), (ProductProof<C, O>, l1_prod_pf), (C, l2_q_cmt), (O, p_x_open), (O, w_x_open), (O, l1_x_open), (O, l2_q_x_open));
-}
-
-impl<F: Reveal, C: Reveal, O: Reveal> Reveal for Proof<F, C, O> {
-    type Base = Proof<F::Base, C::Base, O::Base>;
-    struct_reveal_impl!(Proof<F, PC>, Proof;
-        (C, p_cmt),
-        (WiringProof<C, (F, O)>, wiring),
-        (GateProof<C, (F, O)>, gates),
-        (PublicProof<C, (F, O)>, public)
-    );
+// The same for every party.
+impl Reveal for CircuitDigest {
+    type Base = CircuitDigest;
+    fn reveal(self) -> Self::Base {
+        self
+    }
+    fn from_public(b: Self::Base) -> Self {
+        b
+    }
+    fn from_add_shared(b: Self::Base) -> Self {
+        b
+    }
+    fn unwrap_as_public(self) -> Self::Base {
+        self
+    }
 }
 
 impl<E: PairingEngine, S: PairingShare<E>> Reveal
@@ -50,5 +34,17 @@ impl<E: PairingEngine, S: PairingShare<E>> Reveal
         marlin_pc::Commitment<E>,
         marlin_pc::CommitterKey<E>,
     >;
-    struct_reveal_simp_impl!(ProverKey; w, s, w_cmt, s_cmt, pc_ck);
+    struct_reveal_simp_impl!(ProverKey; w, s, w_cmts, s_cmt, circuit_digest, pc_ck);
+}
+
+impl<C: AffineGroup, S: CurveShare<C>> Reveal
+    for ProverKey<
+        <MpcCurveAffine<C, S> as AffineCurve>::ScalarField,
+        ipa_pc::Commitment<MpcCurveAffine<C, S>>,
+        ipa_pc::CommitterKey<MpcCurveAffine<C, S>>,
+    >
+{
+    type Base =
+        ProverKey<<C as AffineCurve>::ScalarField, ipa_pc::Commitment<C>, ipa_pc::CommitterKey<C>>;
+    struct_reveal_simp_impl!(ProverKey; w, s, w_cmts, s_cmt, circuit_digest, pc_ck);
 }
@@ -0,0 +1,84 @@
+//! Which of a prover's commitments are revealed together.
+//!
+//! In MPC, revealing commitments is a round of communication (see [mpc_algebra::defer]). A
+//! commitment can be revealed once the challenges its polynomial depends on are drawn, and must
+//! be before the first challenge drawn after it is absorbed into the transcript. Commitments
+//! revealed in the same round are still absorbed in transcript order, so the proof is the same.
+//!
+//! [RoundSchedule::rounds] reveals each commitment in the earliest round it can be, which takes
+//! as few rounds as possible: a commitment depending on a challenge can't be revealed in a round
+//! before one that is absorbed ahead of the challenge, so each commitment needs at least as many
+//! rounds as the longest such chain ending with it, and gets exactly that many.
+use std::collections::HashMap;
+
+/// A step of a prover's transcript.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Step {
+    /// A commitment absorbed, to a polynomial computed from challenges `after`
+    Commit {
+        label: String,
+        after: Vec<&'static str>,
+    },
+    /// A challenge drawn
+    Challenge(&'static str),
+}
+
+/// A prover's commitments and the challenges they depend on, in transcript order, from which
+/// their reveals are planned.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RoundSchedule {
+    steps: Vec<Step>,
+}
+
+impl RoundSchedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Absorb the commitment labeled `label`, to a polynomial computed from the challenges
+    /// `after`.
+    pub fn commit(&mut self, label: impl Into<String>, after: &[&'static str]) {
+        self.steps.push(Step::Commit {
+            label: label.into(),
+            after: after.to_vec(),
+        });
+    }
+    /// Draw the challenge labeled `label`.
+    pub fn challenge(&mut self, label: &'static str) {
+        self.steps.push(Step::Challenge(label));
+    }
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+    /// The labels of the commitments revealed in each round, each as early as it can be (see
+    /// the [module docs](self)), in transcript order.
+    pub fn rounds(&self) -> Vec<Vec<String>> {
+        let mut rounds: Vec<Vec<String>> = Vec::new();
+        // Rounds needed to reveal every commitment absorbed so far
+        let mut absorbed = 0;
+        // Rounds needed before each challenge is drawn
+        let mut drawn: HashMap<&'static str, usize> = HashMap::new();
+        for step in &self.steps {
+            match step {
+                Step::Challenge(label) => {
+                    drawn.insert(label, absorbed);
+                }
+                Step::Commit { label, after } => {
+                    let round = after
+                        .iter()
+                        .map(|c| match drawn.get(c) {
+                            Some(r) => *r,
+                            None => panic!("{} depends on challenge {}, not yet drawn", label, c),
+                        })
+                        .max()
+                        .unwrap_or(0);
+                    absorbed = absorbed.max(round + 1);
+                    if rounds.len() == round {
+                        rounds.push(Vec::new());
+                    }
+                    rounds[round].push(label.clone());
+                }
+            }
+        }
+        rounds
+    }
+}
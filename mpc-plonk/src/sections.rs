@@ -0,0 +1,52 @@
+//! The binary files of snarkjs and Circom (`.ptau`, `.r1cs`, `.wtns`): a magic number, a
+//! version and a count of sections, then the sections, each a type (`u32`), a length (`u64`)
+//! and its contents, all little-endian.
+use std::io::{self, Read};
+
+/// Read a file's magic number and version: the version, if the magic number is `magic`.
+pub(crate) fn read_preamble(reader: &mut impl Read, magic: &[u8; 4]) -> io::Result<Option<u32>> {
+    let mut found = [0u8; 4];
+    reader.read_exact(&mut found)?;
+    if &found != magic {
+        return Ok(None);
+    }
+    read_u32(reader).map(Some)
+}
+
+/// Read the count of sections after the preamble, then each section with `read`, given its
+/// type and its contents. Whatever `read` leaves of a section is skipped, but must be there.
+pub(crate) fn read_sections<R: Read, E: From<io::Error>>(
+    mut reader: R,
+    mut read: impl FnMut(u32, &mut io::Take<&mut R>) -> Result<(), E>,
+) -> Result<(), E> {
+    let n_sections = read_u32(&mut reader)?;
+    for _ in 0..n_sections {
+        let section = read_u32(&mut reader)?;
+        let len = read_u64(&mut reader)?;
+        let mut contents = (&mut reader).take(len);
+        read(section, &mut contents)?;
+        io::copy(&mut contents, &mut io::sink())?;
+        if contents.limit() != 0 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+pub(crate) fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Little-endian `bytes`, as a hexadecimal number.
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    let digits: String = bytes.iter().rev().map(|b| format!("{:02x}", b)).collect();
+    format!("0x{}", digits)
+}
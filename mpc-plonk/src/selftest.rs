@@ -0,0 +1,144 @@
+//! A tiny end-to-end collaborative proof, as a health check for a deployment.
+//!
+//! Every party runs [selftest] at once (after initializing [mpc_net::MpcMultiNet]). Together,
+//! they setup, share a witness, prove, and reveal the proof, which each party then verifies on its
+//! own. If this works, the network, the sharing scheme, and the prover all work.
+use ark_ec::PairingEngine;
+use ark_poly::univariate::DensePolynomial;
+use ark_poly_commit::marlin_pc::MarlinKZG10;
+use ark_std::test_rng;
+use mpc_algebra::{BlindingRng, MpcPairingEngine, PairingShare, Reveal};
+use mpc_net::Abort;
+
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::time::{Duration, Instant};
+
+use crate::relations::{
+    flat::{Instance, Witness},
+    structured::{assign_hybrid, PlonkCircuit},
+};
+use crate::{Plonk, ProverKey};
+
+/// Squarings in the self-test circuit.
+pub const SELFTEST_STEPS: usize = 4;
+
+type KzgPlonk<E> =
+    Plonk<<E as PairingEngine>::Fr, MarlinKZG10<E, DensePolynomial<<E as PairingEngine>::Fr>>>;
+
+/// The outcome of a [selftest].
+#[derive(Clone, Debug)]
+pub struct SelftestReport {
+    /// How long each phase took, in order.
+    pub phases: Vec<(&'static str, Duration)>,
+    /// Why the revealed proof did not verify, if it did not (see [crate::Failure::abort_code]).
+    pub failure: Option<Abort>,
+}
+
+impl SelftestReport {
+    /// Whether the revealed proof verified.
+    pub fn verified(&self) -> bool {
+        self.failure.is_none()
+    }
+    /// Total time across phases.
+    pub fn total(&self) -> Duration {
+        self.phases.iter().map(|(_, d)| *d).sum()
+    }
+}
+
+impl Display for SelftestReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (name, d) in &self.phases {
+            writeln!(f, "  {:<8} {:>10.3?}", name, d)?;
+        }
+        writeln!(f, "  {:<8} {:>10.3?}", "total", self.total())?;
+        write!(
+            f,
+            "Selftest {}",
+            if self.verified() { "passed" } else { "FAILED" }
+        )?;
+        if let Some(abort) = &self.failure {
+            write!(f, "\n{}", abort)?;
+        }
+        Ok(())
+    }
+}
+
+/// Times one phase, recording it in `phases`.
+fn phase<T>(
+    phases: &mut Vec<(&'static str, Duration)>,
+    name: &'static str,
+    f: impl FnOnce() -> T,
+) -> T {
+    let start = Instant::now();
+    let t = f();
+    phases.push((name, start.elapsed()));
+    t
+}
+
+/// Collaboratively prove (and then verify) a tiny squaring circuit, timing each phase.
+///
+/// All parties must call this together. It panics if the network fails, but a proof that does not
+/// verify is reported, not raised.
+pub fn selftest<E: PairingEngine, S: PairingShare<E>>() -> SelftestReport {
+    let mut phases = Vec::new();
+
+    let v_circ = Instance::from_circuit(&PlonkCircuit::<E::Fr>::new_squaring_circuit(
+        SELFTEST_STEPS,
+        None,
+    ));
+    let (pk, vk) = phase(&mut phases, "setup", || {
+        let srs =
+            KzgPlonk::<E>::universal_setup(SELFTEST_STEPS.next_power_of_two(), &mut test_rng());
+        KzgPlonk::<E>::circuit_setup_hiding(&srs, &v_circ)
+    });
+
+    let (circ, witness, public) = phase(&mut phases, "share", || {
+        // The king knows the whole witness, so it squares natively and shares every wire at once.
+        let local_inputs = std::iter::once("start".to_owned()).collect();
+        let start: HashMap<String, E::Fr> =
+            std::iter::once(("start".to_owned(), E::Fr::from(3u64))).collect();
+        let c = PlonkCircuit::<<MpcPairingEngine<E, S> as PairingEngine>::Fr>::squaring_circuit(
+            SELFTEST_STEPS,
+        );
+        let no_shared_inputs = |_: &str| None;
+        // The prover still multiplies shares on every row, so that the self-test covers it
+        let (assignment, _) = assign_hybrid(
+            &c,
+            &local_inputs,
+            Some(&start),
+            &no_shared_inputs,
+            &mut test_rng(),
+        )
+        .unwrap();
+        let public: HashMap<String, E::Fr> = c
+            .pub_vars
+            .iter()
+            .map(|(v, name)| (name.clone(), assignment.0[*v as usize].reveal()))
+            .collect();
+        let c = c.with_assignment(assignment);
+        let circ = Instance::from_circuit(&c);
+        let witness = Witness::from_circuit(&circ, &c);
+        (circ, witness, public)
+    });
+
+    let mpc_pf = phase(&mut phases, "prove", || {
+        let mpc_pk = ProverKey::from_public(pk);
+        KzgPlonk::<MpcPairingEngine<E, S>>::prove_hiding(
+            &mpc_pk,
+            &circ,
+            &witness,
+            &mut BlindingRng::new(),
+        )
+    });
+
+    let pf = phase(&mut phases, "reveal", || mpc_pf.reveal());
+
+    let failure = phase(&mut phases, "verify", || {
+        KzgPlonk::<E>::verify_with_report(&vk, &v_circ, pf, &public, None)
+            .err()
+            .map(|report| report.abort())
+    });
+
+    SelftestReport { phases, failure }
+}
@@ -0,0 +1,203 @@
+//! Fiat-Shamir transcripts.
+//!
+//! Everything absorbed into a transcript, and every challenge squeezed from it, carries a label, so
+//! that no two messages (or challenges) of the protocol can be confused for one another.
+use ark_ff::{PrimeField, PubUniformRand, ToBytes};
+use ark_std::rand::{RngCore, SeedableRng};
+use blake2::Blake2s;
+use digest::Digest;
+use rand_chacha::ChaChaRng;
+
+use crate::util::{FiatShamirRng, PoseidonSponge};
+
+/// A Fiat-Shamir transcript: absorbs labeled messages, and squeezes labeled challenges.
+///
+/// Every party (and the verifier) must absorb the same messages, in the same order, to agree on
+/// the challenges.
+pub trait Transcript {
+    /// Whether proofs' transcripts begin with the format version and session (see
+    /// [crate::transcript_init]). Only those of earlier format versions ([LegacyTranscript] and
+    /// [V6Transcript]) do not.
+    const BINDS_SESSION: bool = true;
+
+    /// A fresh transcript for the protocol named `label`.
+    fn new(label: &'static [u8]) -> Self;
+
+    /// Absorb `bytes`, labeled `label`.
+    fn absorb_bytes(&mut self, label: &'static [u8], bytes: &[u8]);
+
+    /// Fill `dest` with challenge bytes, labeled `label`.
+    fn challenge_bytes(&mut self, label: &'static [u8], dest: &mut [u8]);
+
+    /// Absorb `t` (e.g., a commitment or a field element), labeled `label`.
+    fn absorb<T: ToBytes>(&mut self, label: &'static [u8], t: &T) {
+        let bytes = ark_ff::to_bytes![t].expect("failed serialization");
+        self.absorb_bytes(label, &bytes);
+    }
+
+    /// A public challenge (e.g., a field element), labeled `label`.
+    fn challenge<T: PubUniformRand>(&mut self, label: &'static [u8]) -> T {
+        let mut seed = [0u8; 32];
+        self.challenge_bytes(label, &mut seed);
+        T::pub_rand(&mut ChaChaRng::from_seed(seed))
+    }
+}
+
+/// A transcript built from hash chaining with Blake2s.
+pub type Blake2sTranscript = FiatShamirRng<Blake2s>;
+
+/// A transcript built from hash chaining with SHA3-256.
+pub type Sha3Transcript = FiatShamirRng<sha3::Sha3_256>;
+
+/// A transcript built from a Poseidon sponge over `F`, so that a verifier can be expressed in a
+/// circuit over `F`.
+pub type PoseidonTranscript<F> = PoseidonSponge<F>;
+
+/// `len(label) || label || len(msg) || msg`, so that no two (label, msg) pairs collide.
+fn frame(label: &[u8], msg: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(16 + label.len() + msg.len());
+    bytes.extend_from_slice(&(label.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(label);
+    bytes.extend_from_slice(&(msg.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(msg);
+    bytes
+}
+
+impl<D: Digest> Transcript for FiatShamirRng<D> {
+    fn new(label: &'static [u8]) -> Self {
+        Self::from_seed(&frame(b"protocol", label))
+    }
+
+    fn absorb_bytes(&mut self, label: &'static [u8], bytes: &[u8]) {
+        FiatShamirRng::absorb(self, &frame(label, bytes));
+    }
+
+    fn challenge_bytes(&mut self, label: &'static [u8], dest: &mut [u8]) {
+        FiatShamirRng::absorb(self, &frame(b"challenge", label));
+        self.fill_bytes(dest);
+    }
+}
+
+// Labels and messages are each packed into elements, with a length prefix.
+impl<F: PrimeField> Transcript for PoseidonSponge<F> {
+    fn new(label: &'static [u8]) -> Self {
+        let mut sponge = PoseidonSponge::new();
+        sponge.absorb_packed(label);
+        sponge
+    }
+
+    fn absorb_bytes(&mut self, label: &'static [u8], bytes: &[u8]) {
+        self.absorb_packed(label);
+        self.absorb_packed(bytes);
+    }
+
+    fn challenge_bytes(&mut self, label: &'static [u8], dest: &mut [u8]) {
+        self.absorb_packed(label);
+        self.squeeze_packed(dest);
+    }
+}
+
+/// The transcript of format version 1 proofs (see [crate::format]): Blake2s hash chaining that
+/// ignores labels, with challenges drawn straight from the hash-seeded stream. It begins with
+/// neither the format version nor a session, so version 1 proofs keep their challenges.
+#[cfg(feature = "compat")]
+pub struct LegacyTranscript(FiatShamirRng<Blake2s>);
+
+#[cfg(feature = "compat")]
+impl Transcript for LegacyTranscript {
+    const BINDS_SESSION: bool = false;
+
+    fn new(_label: &'static [u8]) -> Self {
+        Self(FiatShamirRng::from_seed(&0u64))
+    }
+
+    fn absorb_bytes(&mut self, _label: &'static [u8], bytes: &[u8]) {
+        self.0.absorb(&bytes.to_vec());
+    }
+
+    fn challenge_bytes(&mut self, _label: &'static [u8], dest: &mut [u8]) {
+        self.0.fill_bytes(dest);
+    }
+
+    fn challenge<T: PubUniformRand>(&mut self, _label: &'static [u8]) -> T {
+        self.0.gen()
+    }
+}
+
+/// The transcript of format version 6 proofs (see [crate::format]): `T`, beginning with neither
+/// the format version nor a session, which version 7 added.
+#[cfg(feature = "compat")]
+pub struct V6Transcript<T>(T);
+
+#[cfg(feature = "compat")]
+impl<T: Transcript> Transcript for V6Transcript<T> {
+    const BINDS_SESSION: bool = false;
+
+    fn new(label: &'static [u8]) -> Self {
+        Self(T::new(label))
+    }
+
+    fn absorb_bytes(&mut self, label: &'static [u8], bytes: &[u8]) {
+        self.0.absorb_bytes(label, bytes);
+    }
+
+    fn challenge_bytes(&mut self, label: &'static [u8], dest: &mut [u8]) {
+        self.0.challenge_bytes(label, dest);
+    }
+
+    fn challenge<C: PubUniformRand>(&mut self, label: &'static [u8]) -> C {
+        self.0.challenge(label)
+    }
+}
+
+impl Transcript for merlin::Transcript {
+    fn new(label: &'static [u8]) -> Self {
+        merlin::Transcript::new(label)
+    }
+
+    fn absorb_bytes(&mut self, label: &'static [u8], bytes: &[u8]) {
+        self.append_message(label, bytes);
+    }
+
+    fn challenge_bytes(&mut self, label: &'static [u8], dest: &mut [u8]) {
+        merlin::Transcript::challenge_bytes(self, label, dest);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type F = ark_bls12_377::Fr;
+
+    fn separates_labels<T: Transcript>() {
+        let challenge = |absorb_label, challenge_label| {
+            let mut t = T::new(b"test");
+            t.absorb(absorb_label, &F::from(7u64));
+            t.challenge::<F>(challenge_label)
+        };
+        assert_eq!(challenge(b"a", b"x"), challenge(b"a", b"x"));
+        assert_ne!(challenge(b"a", b"x"), challenge(b"b", b"x"));
+        assert_ne!(challenge(b"a", b"x"), challenge(b"a", b"y"));
+    }
+
+    #[test]
+    fn blake2s_separates_labels() {
+        separates_labels::<Blake2sTranscript>();
+    }
+
+    #[test]
+    fn sha3_separates_labels() {
+        separates_labels::<Sha3Transcript>();
+    }
+
+    #[test]
+    fn poseidon_separates_labels() {
+        separates_labels::<PoseidonTranscript<F>>();
+    }
+
+    #[test]
+    fn merlin_separates_labels() {
+        separates_labels::<merlin::Transcript>();
+    }
+}
@@ -1,20 +1,113 @@
 ///! Extra algebra utils
-use ark_ff::{FftField, FromBytes, ToBytes, PubUniformRand};
+use ark_ff::{
+    batch_inversion, BigInteger, FftField, Field, FpParameters, FromBytes, PrimeField,
+    PubUniformRand, ToBytes,
+};
 use ark_poly::univariate::DensePolynomial;
-use ark_poly::UVPolynomial;
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain, UVPolynomial};
 use ark_std::marker::PhantomData;
 use ark_std::rand::{RngCore, SeedableRng};
 use digest::{generic_array::GenericArray, Digest};
 use rand_chacha::ChaChaRng;
 
-/// Computes f(a*X) from a and f(X)
-pub fn shift<F: FftField>(mut f: DensePolynomial<F>, a: F) -> DensePolynomial<F> {
-    let mut s = F::one();
-    for c in &mut f.coeffs {
-        *c *= s;
-        s *= a;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A table of field elements, held either in memory or in a file.
+///
+/// A file holds the elements in plaintext, serialized as [CanonicalSerialize::serialize] does.
+pub enum Table<F> {
+    Memory(Vec<F>),
+    Disk(Spilled, usize),
+}
+
+/// A spilled table's file, deleted on drop.
+pub struct Spilled(PathBuf);
+
+impl Drop for Spilled {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+static SPILLED: AtomicUsize = AtomicUsize::new(0);
+
+impl<F: CanonicalSerialize + CanonicalDeserialize> Table<F> {
+    /// Hold `elems` in a new file in `dir`, if given, and in memory otherwise.
+    pub fn new(elems: Vec<F>, dir: Option<&Path>) -> Self {
+        match dir {
+            None => Table::Memory(elems),
+            Some(dir) => {
+                let name = format!(
+                    "mpc-plonk-{}-{}.table",
+                    std::process::id(),
+                    SPILLED.fetch_add(1, Ordering::Relaxed)
+                );
+                let file = Spilled(dir.join(name));
+                let mut w = BufWriter::new(File::create(&file.0).expect("failed to spill table"));
+                for e in &elems {
+                    e.serialize(&mut w).expect("failed to spill table");
+                }
+                Table::Disk(file, elems.len())
+            }
+        }
+    }
+
+    /// The elements, read back (and the file deleted) if spilled.
+    pub fn load(self) -> Vec<F> {
+        match self {
+            Table::Memory(elems) => elems,
+            Table::Disk(file, len) => {
+                let mut r = BufReader::new(File::open(&file.0).expect("failed to read table"));
+                (0..len)
+                    .map(|_| F::deserialize(&mut r).expect("failed to read table"))
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Computes `(a(), b())`, on two threads with the `parallel` feature.
+pub fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA + Send,
+    B: FnOnce() -> RB + Send,
+    RA: Send,
+    RB: Send,
+{
+    #[cfg(feature = "parallel")]
+    return rayon::join(a, b);
+    #[cfg(not(feature = "parallel"))]
+    (a(), b())
+}
+
+/// Divides `evals`, values over the coset of `quotient` (shifted by the multiplicative
+/// generator), by the vanishing polynomial of `dom`, a subgroup of `quotient`, there.
+///
+/// That polynomial takes only |quotient|/|dom| values on the coset, so only those are inverted.
+pub fn divide_by_vanishing_on_coset<F: FftField>(
+    evals: &mut [F],
+    dom: Radix2EvaluationDomain<F>,
+    quotient: Radix2EvaluationDomain<F>,
+) {
+    let n = dom.size();
+    let ratio = quotient.size() / n;
+    let step = quotient.element(n);
+    let mut x = F::multiplicative_generator().pow(&[n as u64]);
+    let mut inverses: Vec<F> = (0..ratio)
+        .map(|_| {
+            let z = x - F::one();
+            x *= step;
+            z
+        })
+        .collect();
+    batch_inversion(&mut inverses);
+    for (i, e) in evals.iter_mut().enumerate() {
+        *e *= inverses[i % ratio];
     }
-    f
 }
 
 pub fn interpolate<F: FftField>(points: &[(F, F)]) -> DensePolynomial<F> {
@@ -107,6 +200,230 @@ impl<D: Digest> FiatShamirRng<D> {
     }
 }
 
+/// Width of the [PoseidonSponge] state.
+pub const POSEIDON_WIDTH: usize = 3;
+/// Elements absorbed (or squeezed) per [PoseidonSponge] permutation.
+pub const POSEIDON_RATE: usize = 2;
+pub const POSEIDON_FULL_ROUNDS: usize = 8;
+/// A little more than the 128-bit security estimates ask for, for any of our S-box exponents.
+pub const POSEIDON_PARTIAL_ROUNDS: usize = 60;
+
+/// The Poseidon permutation's parameters, in the field `F`.
+///
+/// They are those of the field they are made for (see [PoseidonParameters::new]). A shared field
+/// uses its base field's, as public values (see [PoseidonParameters::map]).
+#[derive(Clone)]
+pub struct PoseidonParameters<F> {
+    /// The S-box is `x^alpha`
+    pub alpha: u64,
+    pub round_constants: Vec<[F; POSEIDON_WIDTH]>,
+    pub mds: [[F; POSEIDON_WIDTH]; POSEIDON_WIDTH],
+}
+
+impl<F: PrimeField> PoseidonParameters<F> {
+    /// The round constants are pseudorandom, from a fixed seed. The MDS matrix is the Cauchy
+    /// matrix `1 / (i + (WIDTH + j))`.
+    pub fn new() -> Self {
+        let rng = &mut ChaChaRng::from_seed(*b"mpc-plonk poseidon round consts!");
+        let round_constants = (0..POSEIDON_FULL_ROUNDS + POSEIDON_PARTIAL_ROUNDS)
+            .map(|_| [F::rand(rng), F::rand(rng), F::rand(rng)])
+            .collect();
+        let mut mds = [[F::zero(); POSEIDON_WIDTH]; POSEIDON_WIDTH];
+        for (i, row) in mds.iter_mut().enumerate() {
+            for (j, m) in row.iter_mut().enumerate() {
+                *m = F::from((i + POSEIDON_WIDTH + j) as u64).inverse().unwrap();
+            }
+        }
+        Self {
+            alpha: Self::sbox_exponent(),
+            round_constants,
+            mds,
+        }
+    }
+
+    /// The least `alpha` for which `x^alpha` permutes `F`.
+    pub fn sbox_exponent() -> u64 {
+        // From the modulus, not [ark_ff::Field::characteristic], which shared fields lack
+        let modulus = <F::Params as FpParameters>::MODULUS;
+        let p_minus_1_mod = |a: u64| {
+            let p_mod = modulus
+                .as_ref()
+                .iter()
+                .rev()
+                .fold(0u128, |r, limb| ((r << 64) + *limb as u128) % a as u128);
+            (p_mod + a as u128 - 1) % a as u128
+        };
+        [3u64, 5, 7, 11, 13, 17, 19, 23]
+            .iter()
+            .cloned()
+            .find(|a| p_minus_1_mod(*a) != 0)
+            .expect("no small S-box exponent for this field")
+    }
+}
+
+impl<F: PrimeField> Default for PoseidonParameters<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Copy> PoseidonParameters<F> {
+    /// The same parameters in another field: say, [mpc_algebra::Reveal::from_public], to hash
+    /// shared values.
+    pub fn map<G: Copy>(&self, f: impl Fn(F) -> G) -> PoseidonParameters<G> {
+        PoseidonParameters {
+            alpha: self.alpha,
+            round_constants: self
+                .round_constants
+                .iter()
+                .map(|c| [f(c[0]), f(c[1]), f(c[2])])
+                .collect(),
+            mds: [
+                [f(self.mds[0][0]), f(self.mds[0][1]), f(self.mds[0][2])],
+                [f(self.mds[1][0]), f(self.mds[1][1]), f(self.mds[1][2])],
+                [f(self.mds[2][0]), f(self.mds[2][1]), f(self.mds[2][2])],
+            ],
+        }
+    }
+}
+
+/// Raise each of `xs` to the power `alpha`, by square-and-multiply, a layer of products at a
+/// time: under MPC, each layer is one batch of Beaver multiplications (see
+/// [ark_ff::Field::batch_product_in_place]).
+///
+/// Under MPC, `xs` must be all shared or all public.
+fn batch_pow<F: Field>(xs: &mut [F], alpha: u64) {
+    let base = xs.to_vec();
+    for bit in (0..63 - alpha.leading_zeros()).rev() {
+        let squares = xs.to_vec();
+        F::batch_product_in_place(xs, &squares);
+        if (alpha >> bit) & 1 == 1 {
+            F::batch_product_in_place(xs, &base);
+        }
+    }
+}
+
+/// The Poseidon permutation of `state`, in any field: one with values, or shared.
+pub fn poseidon_permute<F: Field>(params: &PoseidonParameters<F>, state: &mut [F; POSEIDON_WIDTH]) {
+    let half_full = POSEIDON_FULL_ROUNDS / 2;
+    for (r, constants) in params.round_constants.iter().enumerate() {
+        for (s, c) in state.iter_mut().zip(constants) {
+            *s += c;
+        }
+        if r < half_full || r >= half_full + POSEIDON_PARTIAL_ROUNDS {
+            batch_pow(&mut state[..], params.alpha);
+        } else {
+            batch_pow(&mut state[..1], params.alpha);
+        }
+        let mut next = [F::zero(); POSEIDON_WIDTH];
+        for (n, row) in next.iter_mut().zip(&params.mds) {
+            for (m, s) in row.iter().zip(state.iter()) {
+                *n += *m * s;
+            }
+        }
+        *state = next;
+    }
+}
+
+/// The hash of `inputs`: [PoseidonSponge::absorb_elems] then [PoseidonSponge::squeeze], in any
+/// field.
+///
+/// To hash shared inputs, pass `params` mapped to public values (see [PoseidonParameters::map]):
+/// only the S-boxes' products of shared values need communication.
+pub fn poseidon_hash<F: Field>(params: &PoseidonParameters<F>, inputs: &[F]) -> F {
+    // Some shared zero, if any input is shared: added to every element of the state, it makes
+    // all of them shared, so that the S-boxes can multiply them in batches
+    let zero: F = inputs.iter().map(|x| *x * F::zero()).sum();
+    let mut state = [zero; POSEIDON_WIDTH];
+    let mut pos = 0;
+    for x in inputs {
+        if pos == POSEIDON_RATE {
+            poseidon_permute(params, &mut state);
+            pos = 0;
+        }
+        state[pos] += x;
+        pos += 1;
+    }
+    poseidon_permute(params, &mut state);
+    state[0]
+}
+
+/// A duplex sponge over the Poseidon permutation, on the field `F`.
+///
+/// Unlike [FiatShamirRng], the hash here is cheap to express in a circuit over `F` (see
+/// [crate::relations::gadgets::Gadgets::poseidon]).
+#[derive(Clone)]
+pub struct PoseidonSponge<F: PrimeField> {
+    state: [F; POSEIDON_WIDTH],
+    /// Where the next absorbed element goes.
+    pos: usize,
+    params: PoseidonParameters<F>,
+}
+
+impl<F: PrimeField> PoseidonSponge<F> {
+    /// A sponge with the empty state, and the parameters of [PoseidonParameters::new].
+    pub fn new() -> Self {
+        Self {
+            state: [F::zero(); POSEIDON_WIDTH],
+            pos: 0,
+            params: PoseidonParameters::new(),
+        }
+    }
+
+    fn permute(&mut self) {
+        poseidon_permute(&self.params, &mut self.state);
+    }
+
+    /// Absorb `elems`.
+    pub fn absorb_elems(&mut self, elems: &[F]) {
+        for e in elems {
+            if self.pos == POSEIDON_RATE {
+                self.permute();
+                self.pos = 0;
+            }
+            self.state[self.pos] += e;
+            self.pos += 1;
+        }
+    }
+
+    /// Squeeze one element, which depends on everything absorbed so far.
+    pub fn squeeze(&mut self) -> F {
+        self.permute();
+        self.pos = 0;
+        self.state[0]
+    }
+
+    /// Bytes that fit in one element, without reduction.
+    pub fn bytes_per_elem() -> usize {
+        (F::size_in_bits() - 1) / 8
+    }
+
+    /// Absorb `bytes`, packed into elements (and prefixed with their length).
+    pub fn absorb_packed(&mut self, bytes: &[u8]) {
+        let mut elems = vec![F::from(bytes.len() as u64)];
+        elems.extend(
+            bytes
+                .chunks(Self::bytes_per_elem())
+                .map(F::from_le_bytes_mod_order),
+        );
+        self.absorb_elems(&elems);
+    }
+
+    /// Fill `dest` with the low bytes of squeezed elements.
+    pub fn squeeze_packed(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(Self::bytes_per_elem()) {
+            let bytes = self.squeeze().into_repr().to_bytes_le();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+}
+
+impl<F: PrimeField> Default for PoseidonSponge<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -123,6 +440,37 @@ mod tests {
         assert!(p.degree() <= pts.len());
     }
 
+    #[test]
+    fn poseidon_sbox_permutes() {
+        // 3, 5 and 7 divide p - 1 for BLS12-377's scalar field, but not for BLS12-381's.
+        assert_eq!(PoseidonParameters::<F>::sbox_exponent(), 11);
+        assert_eq!(PoseidonParameters::<ark_bls12_381::Fr>::sbox_exponent(), 5);
+    }
+
+    #[test]
+    fn poseidon_sponge_absorbs() {
+        let squeeze = |elems: &[F]| {
+            let mut s = PoseidonSponge::<F>::new();
+            s.absorb_elems(elems);
+            s.squeeze()
+        };
+        let (a, b, c) = (F::from(1u64), F::from(2u64), F::from(3u64));
+        assert_eq!(squeeze(&[a, b, c]), squeeze(&[a, b, c]));
+        assert_ne!(squeeze(&[a, b, c]), squeeze(&[a, b]));
+        assert_ne!(squeeze(&[a, b, c]), squeeze(&[a, c, b]));
+    }
+
+    #[test]
+    fn poseidon_hash_is_the_sponge() {
+        let params = PoseidonParameters::<F>::new();
+        for n in 0..5 {
+            let elems: Vec<F> = (0..n).map(|i| F::from(i as u64 + 1)).collect();
+            let mut s = PoseidonSponge::<F>::new();
+            s.absorb_elems(&elems);
+            assert_eq!(poseidon_hash(&params, &elems), s.squeeze());
+        }
+    }
+
     #[test]
     fn interp_test_1() {
         interp_test(&[(F::from(0u64), F::from(1u64))]);
@@ -0,0 +1,471 @@
+//! Benchmark collaborative Plonk across circuit sizes, with every party on this machine.
+//!
+//! `bench run --parties 3 --alg spdz --min-log-gates 10 --max-log-gates 14` starts the parties
+//! over loopback, once per size, by running this binary again as each `party`. Each size gives
+//! one row, as CSV (the default) or JSON lines, with the king's measurements:
+//!
+//! * `share`: secret-sharing the witness (the king knows it all, see
+//!   [mpc_plonk::relations::structured::assign_hybrid]);
+//! * `fft`, `commit`, `open`: time in the prover's FFTs, commitments, and opening proofs,
+//!   measured by wrapping its arithmetic and committer backends;
+//! * `prove`: all of proving, including the above, and `reveal`: revealing the proof;
+//! * `rounds`, `bytes_sent`, `bytes_recv`: communication over sharing, proving, and revealing.
+//!
+//! Comparing rows across commits shows where a regression is: in local arithmetic, or in the
+//! network. Build with `--release`: debug builds check and print much more.
+use ark_bls12_377::Bls12_377 as E;
+use ark_ec::PairingEngine;
+use ark_ff::FftField;
+use ark_poly::{univariate::DensePolynomial, Radix2EvaluationDomain};
+use ark_poly_commit::{
+    marlin_pc::MarlinKZG10, BatchLCProof, LabeledCommitment, LabeledPolynomial, LinearCombination,
+    PolynomialCommitment, QuerySet,
+};
+use ark_std::rand::RngCore;
+use ark_std::test_rng;
+use clap::arg_enum;
+use mpc_algebra::{BlindingRng, MpcPairingEngine, PairingShare, Reveal};
+use mpc_net::{Abort, MpcMultiNet, MpcNet, MpcNetError};
+use mpc_plonk::relations::{
+    flat::{self, Instance},
+    structured::{assign_hybrid, PlonkCircuit, Witness},
+};
+use mpc_plonk::{
+    ArithmeticBackend, CommitterBackend, CpuBackend, Error, LocalCommitter, Plonk, Prover,
+    ProverKey,
+};
+use structopt::StructOpt;
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+type Fr = <E as PairingEngine>::Fr;
+type KzgPlonk<E> =
+    Plonk<<E as PairingEngine>::Fr, MarlinKZG10<E, DensePolynomial<<E as PairingEngine>::Fr>>>;
+
+arg_enum! {
+    #[derive(PartialEq, Debug, Clone, Copy)]
+    pub enum MpcAlg {
+        Spdz,
+        Hbc,
+        Gsz,
+        Shamir,
+    }
+}
+
+arg_enum! {
+    #[derive(PartialEq, Debug, Clone, Copy)]
+    pub enum Format {
+        Csv,
+        Json,
+    }
+}
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "bench",
+    about = "Benchmark collaborative Plonk across circuit sizes"
+)]
+enum Opt {
+    /// Run every party locally, for each size, and print a row per size.
+    Run {
+        /// Number of parties
+        #[structopt(long, default_value = "3")]
+        parties: usize,
+        /// Sharing scheme
+        #[structopt(long, default_value = "spdz")]
+        alg: MpcAlg,
+        /// Smallest circuit, as log2 of its gates
+        #[structopt(long, default_value = "10")]
+        min_log_gates: u32,
+        /// Largest circuit, as log2 of its gates (up to 22)
+        #[structopt(long, default_value = "14")]
+        max_log_gates: u32,
+        #[structopt(long, default_value = "csv")]
+        format: Format,
+        /// The first loopback port; each run uses the next `parties` ports
+        #[structopt(long, default_value = "9000")]
+        base_port: u16,
+    },
+    /// Be one party of a run (started by `run`).
+    Party {
+        #[structopt(long, parse(from_os_str))]
+        hosts: PathBuf,
+        #[structopt(long)]
+        party: usize,
+        #[structopt(long)]
+        alg: MpcAlg,
+        #[structopt(long)]
+        log_gates: u32,
+        #[structopt(long)]
+        format: Format,
+    },
+}
+
+/// One benchmark run's measurements, at the king.
+struct Row {
+    parties: usize,
+    alg: MpcAlg,
+    log_gates: u32,
+    gates: usize,
+    share: Duration,
+    fft: Duration,
+    commit: Duration,
+    open: Duration,
+    prove: Duration,
+    reveal: Duration,
+    rounds: usize,
+    bytes_sent: usize,
+    bytes_recv: usize,
+}
+
+const CSV_HEADER: &str = "parties,alg,log_gates,gates,share_s,fft_s,commit_s,open_s,prove_s,\
+                          reveal_s,rounds,bytes_sent,bytes_recv";
+
+impl Row {
+    fn csv(&self) -> String {
+        format!(
+            "{},{},{},{},{:.6},{:.6},{:.6},{:.6},{:.6},{:.6},{},{},{}",
+            self.parties,
+            self.alg,
+            self.log_gates,
+            self.gates,
+            self.share.as_secs_f64(),
+            self.fft.as_secs_f64(),
+            self.commit.as_secs_f64(),
+            self.open.as_secs_f64(),
+            self.prove.as_secs_f64(),
+            self.reveal.as_secs_f64(),
+            self.rounds,
+            self.bytes_sent,
+            self.bytes_recv
+        )
+    }
+    fn json(&self) -> String {
+        format!(
+            "{{\"parties\":{},\"alg\":\"{}\",\"log_gates\":{},\"gates\":{},\"share_s\":{:.6},\
+             \"fft_s\":{:.6},\"commit_s\":{:.6},\"open_s\":{:.6},\"prove_s\":{:.6},\
+             \"reveal_s\":{:.6},\"rounds\":{},\"bytes_sent\":{},\"bytes_recv\":{}}}",
+            self.parties,
+            self.alg,
+            self.log_gates,
+            self.gates,
+            self.share.as_secs_f64(),
+            self.fft.as_secs_f64(),
+            self.commit.as_secs_f64(),
+            self.open.as_secs_f64(),
+            self.prove.as_secs_f64(),
+            self.reveal.as_secs_f64(),
+            self.rounds,
+            self.bytes_sent,
+            self.bytes_recv
+        )
+    }
+}
+
+/// Nanoseconds spent in each of the prover's backends.
+#[derive(Default)]
+struct BackendTimes {
+    fft: AtomicU64,
+    commit: AtomicU64,
+    open: AtomicU64,
+}
+
+fn timed<T>(total: &AtomicU64, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let t = f();
+    total.fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+    t
+}
+
+fn duration(total: &AtomicU64) -> Duration {
+    Duration::from_nanos(total.load(Ordering::Relaxed))
+}
+
+/// Computes as [CpuBackend] and [LocalCommitter] do, timing each call.
+struct Timed<'a>(&'a BackendTimes);
+
+impl<F: FftField> ArithmeticBackend<F> for Timed<'_> {
+    fn fft_in_place(&self, domain: Radix2EvaluationDomain<F>, coeffs: &mut Vec<F>) {
+        timed(&self.0.fft, || CpuBackend.fft_in_place(domain, coeffs))
+    }
+    fn ifft_in_place(&self, domain: Radix2EvaluationDomain<F>, evals: &mut Vec<F>) {
+        timed(&self.0.fft, || CpuBackend.ifft_in_place(domain, evals))
+    }
+    fn coset_fft_in_place(&self, domain: Radix2EvaluationDomain<F>, coeffs: &mut Vec<F>) {
+        timed(&self.0.fft, || {
+            CpuBackend.coset_fft_in_place(domain, coeffs)
+        })
+    }
+    fn coset_ifft_in_place(&self, domain: Radix2EvaluationDomain<F>, evals: &mut Vec<F>) {
+        timed(&self.0.fft, || {
+            CpuBackend.coset_ifft_in_place(domain, evals)
+        })
+    }
+}
+
+impl<F: FftField, PC: PolynomialCommitment<F, DensePolynomial<F>>> CommitterBackend<F, PC>
+    for Timed<'_>
+where
+    PC::Error: 'static,
+{
+    fn commit(
+        &self,
+        ck: &PC::CommitterKey,
+        p: &LabeledPolynomial<F, DensePolynomial<F>>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<(LabeledCommitment<PC::Commitment>, PC::Randomness), Error<PC::Error>> {
+        timed(&self.0.commit, || {
+            CommitterBackend::<F, PC>::commit(&LocalCommitter, ck, p, rng)
+        })
+    }
+    fn commit_many(
+        &self,
+        ck: &PC::CommitterKey,
+        ps: &[&LabeledPolynomial<F, DensePolynomial<F>>],
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<Vec<(LabeledCommitment<PC::Commitment>, PC::Randomness)>, Error<PC::Error>> {
+        timed(&self.0.commit, || {
+            CommitterBackend::<F, PC>::commit_many(&LocalCommitter, ck, ps, rng)
+        })
+    }
+    fn open(
+        &self,
+        ck: &PC::CommitterKey,
+        p: &LabeledPolynomial<F, DensePolynomial<F>>,
+        p_c: &LabeledCommitment<PC::Commitment>,
+        x: F,
+        p_r: &PC::Randomness,
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<PC::Proof, Error<PC::Error>> {
+        timed(&self.0.open, || {
+            CommitterBackend::<F, PC>::open(&LocalCommitter, ck, p, p_c, x, p_r, rng)
+        })
+    }
+    fn open_combinations(
+        &self,
+        ck: &PC::CommitterKey,
+        lcs: &[LinearCombination<F>],
+        ps: &[&LabeledPolynomial<F, DensePolynomial<F>>],
+        p_cs: &[&LabeledCommitment<PC::Commitment>],
+        query_set: &QuerySet<F>,
+        opening_challenge: F,
+        p_rs: &[&PC::Randomness],
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<BatchLCProof<F, DensePolynomial<F>, PC>, Error<PC::Error>> {
+        timed(&self.0.open, || {
+            CommitterBackend::<F, PC>::open_combinations(
+                &LocalCommitter,
+                ck,
+                lcs,
+                ps,
+                p_cs,
+                query_set,
+                opening_challenge,
+                p_rs,
+                rng,
+            )
+        })
+    }
+}
+
+/// Collaboratively prove `2^log_gates` squarings and verify the proof, as one party.
+fn party<S: PairingShare<E>>(alg: MpcAlg, log_gates: u32) -> Row {
+    type MpcE<S> = MpcPairingEngine<E, S>;
+    type MFr<S> = <MpcE<S> as PairingEngine>::Fr;
+    type MpcPc<S> = MarlinKZG10<MpcE<S>, DensePolynomial<MFr<S>>>;
+
+    let steps = 1 << log_gates;
+    let v_circ = Instance::from_circuit(&PlonkCircuit::<Fr>::new_squaring_circuit(steps, None));
+    let srs = KzgPlonk::<E>::universal_setup(steps, &mut test_rng());
+    let (pk, vk) = KzgPlonk::<E>::circuit_setup(&srs, &v_circ);
+    let mpc_pk = ProverKey::from_public(pk);
+
+    MpcMultiNet::reset_stats();
+    let start = Instant::now();
+    let c = PlonkCircuit::<MFr<S>>::squaring_circuit(steps);
+    let local_inputs = std::iter::once("start".to_owned()).collect();
+    let king_inputs: HashMap<String, Fr> =
+        std::iter::once(("start".to_owned(), Fr::from(3u64))).collect();
+    let local: Option<&dyn Witness<Fr>> = if MpcMultiNet::am_king() {
+        Some(&king_inputs)
+    } else {
+        None
+    };
+    let no_shared_inputs = |_: &str| None;
+    let (assignment, _) =
+        assign_hybrid(&c, &local_inputs, local, &no_shared_inputs, &mut test_rng()).unwrap();
+    let public: HashMap<String, Fr> = c
+        .pub_vars
+        .iter()
+        .map(|(v, name)| (name.clone(), assignment.0[*v as usize].reveal()))
+        .collect();
+    let c = c.with_assignment(assignment);
+    let circ = Instance::from_circuit(&c);
+    let witness = flat::Witness::from_circuit(&circ, &c);
+    let share = start.elapsed();
+
+    let times = BackendTimes::default();
+    let backends = Timed(&times);
+    let start = Instant::now();
+    let zk_rng = &mut BlindingRng::new();
+    let mut prv = Prover::<MFr<S>, MpcPc<S>>::new(&mpc_pk, zk_rng);
+    prv.committer_backend(&backends);
+    prv.arithmetic_backend(&backends);
+    let mpc_pf = prv.prove(&circ, &witness);
+    let prove = start.elapsed();
+    let start = Instant::now();
+    let pf = mpc_pf.reveal();
+    let reveal = start.elapsed();
+    let stats = MpcMultiNet::stats();
+
+    if let Err(report) = KzgPlonk::<E>::verify_with_report(&vk, &v_circ, pf, &public, None) {
+        report.abort().raise();
+    }
+    Row {
+        parties: MpcMultiNet::n_parties(),
+        alg,
+        log_gates,
+        gates: v_circ.domains.gates.size as usize,
+        share,
+        fft: duration(&times.fft),
+        commit: duration(&times.commit),
+        open: duration(&times.open),
+        prove,
+        reveal,
+        rounds: stats.rounds(),
+        bytes_sent: stats.bytes_sent,
+        bytes_recv: stats.bytes_recv,
+    }
+}
+
+/// Run every party of one benchmark, and return the king's row.
+fn run_once(hosts: &Path, parties: usize, alg: MpcAlg, log_gates: u32, format: Format) -> String {
+    let exe = std::env::current_exe().unwrap();
+    let children: Vec<_> = (0..parties)
+        .map(|i| {
+            Command::new(&exe)
+                .arg("party")
+                .arg("--hosts")
+                .arg(hosts)
+                .args(["--party", &i.to_string()])
+                .args(["--alg", &alg.to_string()])
+                .args(["--log-gates", &log_gates.to_string()])
+                .args(["--format", &format.to_string()])
+                .stdout(if i == 0 {
+                    Stdio::piped()
+                } else {
+                    Stdio::null()
+                })
+                .spawn()
+                .unwrap_or_else(|e| panic!("{}: {}", exe.display(), e))
+        })
+        .collect();
+    let mut king_output = String::new();
+    for (i, child) in children.into_iter().enumerate() {
+        let output = child.wait_with_output().unwrap();
+        if !output.status.success() {
+            eprintln!(
+                "party {} failed ({}) at 2^{} gates",
+                i, output.status, log_gates
+            );
+            std::process::exit(output.status.code().unwrap_or(1));
+        }
+        if i == 0 {
+            king_output = String::from_utf8(output.stdout).unwrap();
+        }
+    }
+    // The row is the king's last line; debug builds and timers print before it
+    king_output
+        .lines()
+        .last()
+        .unwrap_or_else(|| panic!("The king printed no row at 2^{} gates", log_gates))
+        .to_owned()
+}
+
+fn main() {
+    let opt = Opt::from_args();
+    env_logger::init();
+    match opt {
+        Opt::Run {
+            parties,
+            alg,
+            min_log_gates,
+            max_log_gates,
+            format,
+            base_port,
+        } => {
+            assert!(
+                parties >= 2,
+                "A collaborative proof has at least two parties"
+            );
+            assert!(
+                min_log_gates <= max_log_gates && max_log_gates <= 22,
+                "Sizes must be in 2^0..2^22 gates"
+            );
+            let hosts =
+                std::env::temp_dir().join(format!("mpc-bench-{}.hosts", std::process::id()));
+            if format == Format::Csv {
+                println!("{}", CSV_HEADER);
+            }
+            for (run, log_gates) in (min_log_gates..=max_log_gates).enumerate() {
+                // Fresh ports for each run, so no listener waits on the last run's sockets
+                let first = base_port as usize + run * parties;
+                let addrs: Vec<String> = (first..first + parties)
+                    .map(|port| format!("127.0.0.1:{}", port))
+                    .collect();
+                std::fs::write(&hosts, addrs.join("\n") + "\n").unwrap();
+                println!("{}", run_once(&hosts, parties, alg, log_gates, format));
+                std::io::stdout().flush().unwrap();
+            }
+            std::fs::remove_file(&hosts).unwrap();
+        }
+        Opt::Party {
+            hosts,
+            party: i,
+            alg,
+            log_gates,
+            format,
+        } => {
+            // Network failures and other aborts are reported below, not as panics.
+            let default_hook = std::panic::take_hook();
+            std::panic::set_hook(Box::new(move |info| {
+                let payload = info.payload();
+                if !payload.is::<MpcNetError>() && !payload.is::<Abort>() {
+                    default_hook(info)
+                }
+            }));
+            MpcMultiNet::init_from_file(hosts.to_str().unwrap(), i);
+            let row = mpc_net::catch_abort(|| match alg {
+                MpcAlg::Spdz => {
+                    party::<mpc_algebra::share::spdz::SpdzPairingShare<E>>(alg, log_gates)
+                }
+                MpcAlg::Hbc => {
+                    party::<mpc_algebra::share::add::AdditivePairingShare<E>>(alg, log_gates)
+                }
+                MpcAlg::Gsz => {
+                    party::<mpc_algebra::share::gsz20::GszPairingShare<E>>(alg, log_gates)
+                }
+                MpcAlg::Shamir => {
+                    party::<mpc_algebra::share::shamir::ShamirPairingShare<E>>(alg, log_gates)
+                }
+            })
+            .unwrap_or_else(|e| {
+                eprintln!("Aborting: {}", e);
+                MpcMultiNet::deinit();
+                std::process::exit(e.code.exit_code());
+            });
+            MpcMultiNet::deinit();
+            if i == 0 {
+                match format {
+                    Format::Csv => println!("{}", row.csv()),
+                    Format::Json => println!("{}", row.json()),
+                }
+            }
+        }
+    }
+}
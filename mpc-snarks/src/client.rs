@@ -4,7 +4,7 @@ use log::debug;
 
 use ark_bls12_377::Fr;
 use ark_ec::{group::Group, AffineCurve, PairingEngine, ProjectiveCurve};
-use ark_ff::Field;
+use ark_ff::{Field, PrimeField};
 use ark_poly::domain::radix2::Radix2EvaluationDomain;
 use ark_poly::univariate::DensePolynomial;
 use ark_poly::{EvaluationDomain, Polynomial, UVPolynomial};
@@ -21,6 +21,14 @@ use mpc_algebra::malicious_majority as mm;
 use mpc_algebra::*;
 use mpc_trait::MpcWire;
 use mpc_net::{MpcNet, MpcMultiNet};
+use mpc_plonk::relations::{
+    dsl::CircuitBuilder,
+    gadgets::edwards::Edwards,
+    gadgets::merkle::{Merkle, PoseidonHash},
+    gadgets::Gadgets,
+    structured::assign,
+};
+use mpc_plonk::{poseidon_hash, PoseidonParameters};
 
 use clap::arg_enum;
 use merlin::Transcript;
@@ -58,7 +66,12 @@ arg_enum! {
         KzgZkBatch,
         PcTwoCom,
         Plonk,
+        PlonkIpa,
         PolyDiv,
+        IpaSetup,
+        Poseidon,
+        MerklePath,
+        ScalarMul,
     }
 }
 
@@ -125,6 +138,8 @@ impl Opt {
             Computation::Marlin
             | Computation::Groth16
             | Computation::Plonk
+            | Computation::PlonkIpa
+            | Computation::IpaSetup
             | Computation::Kzg
             | Computation::KzgZk
             | Computation::Msm
@@ -203,6 +218,36 @@ impl Computation {
             Computation::Plonk => {
                 plonk::local_test_prove_and_verify(1);
                 plonk::mpc_test_prove_and_verify(1);
+                plonk::mpc_r1cs_test_prove_and_verify(1);
+                vec![]
+            }
+            Computation::PlonkIpa => {
+                plonk::mpc_ipa_test_prove_and_verify(1);
+                vec![]
+            }
+            Computation::IpaSetup => {
+                let pp = IpaPc::joint_setup(7);
+                // Everyone derived the same generators...
+                let mut bytes = Vec::new();
+                (pp.comm_key.clone(), pp.h, pp.s).serialize(&mut bytes).unwrap();
+                let all_bytes = MpcMultiNet::broadcast_bytes(&bytes);
+                assert!(all_bytes.iter().all(|b| b == &bytes));
+                // ...and they work.
+                let rng = &mut ark_std::test_rng();
+                let (ck, vk) = IpaPc::trim(&pp, 7, 1, None).unwrap();
+                let poly = P::from_coefficients_vec(inputs.iter().map(|i| i.reveal()).collect());
+                let x = Fr::from(2u32);
+                let polys = vec![ark_poly_commit::LabeledPolynomial::new(
+                    "a".into(),
+                    poly.clone(),
+                    None,
+                    Some(1),
+                )];
+                let (commits, rands) = IpaPc::commit(&ck, &polys, Some(rng)).unwrap();
+                let chal = Fr::from(2u32);
+                let pf = IpaPc::open(&ck, &polys, &commits, &x, chal, &rands, Some(rng)).unwrap();
+                let values = vec![poly.evaluate(&x)];
+                assert!(IpaPc::check(&vk, &commits, &x, values, &pf, chal, Some(rng)).unwrap());
                 vec![]
             }
             Computation::MarlinPc => {
@@ -664,7 +709,7 @@ impl Computation {
         }
         outputs
     }
-    fn run_field<F: ComField>(&self, mut inputs: Vec<F>) -> Vec<F> {
+    fn run_field<F: ComField + PrimeField + Reveal<Base = Fr>>(&self, mut inputs: Vec<F>) -> Vec<F> {
         let outputs = match self {
             Computation::Fft => {
                 let d = Radix2EvaluationDomain::<F>::new(inputs.len()).unwrap();
@@ -684,6 +729,90 @@ impl Computation {
             Computation::Sum => {
                 vec![inputs.into_iter().fold(F::from(0u32), std::ops::Add::add)]
             }
+            Computation::Poseidon => {
+                // Hash the shared inputs, and check the hash against the opened inputs'
+                let params = PoseidonParameters::<F::Base>::new();
+                let hash = poseidon_hash(&params.map(F::from_public), &inputs);
+                let opened: Vec<F::Base> = inputs.into_iter().map(Reveal::reveal).collect();
+                assert_eq!(hash.reveal(), poseidon_hash(&params, &opened));
+                vec![hash]
+            }
+            Computation::MerklePath => {
+                // Compute the root of a binary tree over the shared inputs in a circuit, from the
+                // first input's shared path, and check it and the checks against the opened ones
+                let params = PoseidonParameters::<F::Base>::new();
+                let depth = inputs.len().next_power_of_two().trailing_zeros() as usize;
+                let merkle = Merkle::new(PoseidonHash::new(params.map(F::from_public)), 2, depth);
+                let b = CircuitBuilder::<F>::new();
+                let g = Gadgets::new(&b);
+                b.bind("root", merkle.path_root(&g, "path", b.wire("leaf")));
+                b.publicize("root");
+                let public = g.public_values::<F::Base>();
+                let c = b.finish();
+                let tree = merkle.tree(&inputs);
+                let mut witness: std::collections::HashMap<String, F> = public
+                    .iter()
+                    .map(|(name, v)| (name.clone(), F::from_public(*v)))
+                    .collect();
+                witness.extend(tree.path_inputs("path", 0));
+                witness.insert("leaf".to_owned(), inputs[0]);
+                let assignment = assign(&c, &witness).unwrap();
+                let opened: Vec<F::Base> = inputs.into_iter().map(Reveal::reveal).collect();
+                let plain = Merkle::new(PoseidonHash::new(params), 2, depth).tree(&opened);
+                for (name, value) in c.reveal_public_outputs(&assignment) {
+                    let expected = public.get(&name).copied().unwrap_or_else(|| plain.root());
+                    assert_eq!(value.unwrap_as_public(), expected, "{}", name);
+                }
+                vec![tree.root()]
+            }
+            Computation::ScalarMul => {
+                // Multiply the generator of the curve over Fr by the scalar whose bits, least
+                // significant first, are the inputs, in a circuit, from shared doublings and
+                // additions, and check the product against the opened bits'
+                let curve = Edwards::ed_on_bls12_377();
+                let b = CircuitBuilder::<F>::new();
+                let g = Gadgets::new(&b);
+                let bits = g.bits("k", inputs.len());
+                let base = curve.input(&g, "g");
+                let kg = curve.scalar_mul(&g, "kg", &bits, &base);
+                b.bind("x", kg.x);
+                b.bind("y", kg.y);
+                b.publicize("x");
+                b.publicize("y");
+                let public = g.public_values::<Fr>();
+                let c = b.finish();
+                let shared_curve = curve.map(F::from_public);
+                let generator = ark_ed_on_bls12_377::EdwardsAffine::prime_subgroup_generator();
+                let point = (F::from_public(generator.x), F::from_public(generator.y));
+                let mut witness: std::collections::HashMap<String, F> = public
+                    .iter()
+                    .map(|(name, v)| (name.clone(), F::from_public(*v)))
+                    .collect();
+                witness.extend(
+                    inputs
+                        .iter()
+                        .enumerate()
+                        .map(|(j, bit)| (format!("k_bit_{}", j), *bit)),
+                );
+                witness.extend(shared_curve.input_values("g", point));
+                let (product, product_inputs) = shared_curve.scalar_mul_inputs("kg", &inputs, point);
+                witness.extend(product_inputs);
+                let assignment = assign(&c, &witness).unwrap();
+                let k = inputs
+                    .into_iter()
+                    .rev()
+                    .fold(0u64, |k, bit| 2 * k + (bit.reveal() == Fr::from(1u64)) as u64);
+                let expected = AffineCurve::mul(&generator, k).into_affine();
+                for (name, value) in c.reveal_public_outputs(&assignment) {
+                    let v = match name.as_str() {
+                        "x" => expected.x,
+                        "y" => expected.y,
+                        name => public[name],
+                    };
+                    assert_eq!(value.unwrap_as_public(), v, "{}", name);
+                }
+                vec![product.0, product.1]
+            }
             Computation::Product => {
                 assert_eq!(inputs.len(), 2);
                 let product = inputs[0] * inputs[1];
@@ -861,6 +990,7 @@ trait Pc = ark_poly_commit::PolynomialCommitment<Fr, DensePolynomial<Fr>>;
 trait MPc = ark_poly_commit::PolynomialCommitment<MFr, DensePolynomial<MFr>>;
 type MarlinPc = marlin_pc::MarlinKZG10<E, P>;
 type MarlinMPc = marlin_pc::MarlinKZG10<ME, MP>;
+type IpaPc = ark_poly_commit::ipa_pc::InnerProductArgPC<ark_bls12_377::G1Affine, blake2::Blake2s, P>;
 
 fn main() -> () {
     let opt = Opt::from_args();
@@ -0,0 +1,411 @@
+//! One interface to the collaborative proof systems: Groth16, Marlin, and Plonk.
+//!
+//! Each proves an R1CS circuit (a [ConstraintSynthesizer]) from a witness shared among the
+//! parties, under pairing `E` and sharing scheme `S`, and reveals the proof, which anyone can
+//! verify. So an application (or a benchmark) written against [CollaborativeSnark] can switch
+//! proof systems with a type parameter, and (with [CollaborativeSnark::prove_in]) security models
+//! with another.
+//!
+//! As elsewhere in MPC, a party that gives up unwinds (see [mpc_net::Abort]) rather than
+//! returning an error.
+use ark_ec::PairingEngine;
+use ark_poly::univariate::DensePolynomial;
+use ark_poly_commit::marlin::marlin_pc::MarlinKZG10;
+use ark_relations::r1cs::{ConstraintSynthesizer, SynthesisError};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::rand::RngCore;
+use blake2::Blake2s;
+use mpc_algebra::{BlindingRng, MpcPairingEngine, MpcSecurityModel, PairingShare, Reveal};
+use mpc_plonk::relations::{flat::Instance, r1cs::R1csPlonk};
+use std::fmt;
+
+use crate::sealed::{self, ReceiverPublicKey, SealedProof};
+
+/// The field the parties' witness is shared in.
+pub type MpcFr<E, S> = <MpcPairingEngine<E, S> as PairingEngine>::Fr;
+
+/// Why setup, indexing, or proving failed.
+#[derive(Debug)]
+pub enum Error {
+    /// The circuit could not be synthesized
+    Synthesis(SynthesisError),
+    /// The proof system failed (say, the parameters are too small for the circuit)
+    Snark(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Synthesis(e) => write!(f, "Synthesis failed: {}", e),
+            Error::Snark(e) => write!(f, "Proof system failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<SynthesisError> for Error {
+    fn from(e: SynthesisError) -> Self {
+        Error::Synthesis(e)
+    }
+}
+
+/// A proof system whose proofs the parties compute together.
+///
+/// Public inputs are the R1CS instance, not counting the constant 1, in order of allocation.
+pub trait CollaborativeSnark<E: PairingEngine> {
+    /// A short name, for reports.
+    const NAME: &'static str;
+    /// What [CollaborativeSnark::setup] generates: a universal SRS, or (for Groth16) the
+    /// circuit's own parameters.
+    type Params;
+    type ProvingKey;
+    type VerifyingKey;
+    type Proof: CanonicalSerialize + CanonicalDeserialize;
+
+    /// Parameters large enough for `circuit` (which needs no values).
+    fn setup<C: ConstraintSynthesizer<E::Fr>, R: RngCore>(
+        circuit: C,
+        rng: &mut R,
+    ) -> Result<Self::Params, Error>;
+
+    /// Keys for `circuit` (which needs no values), from `params`.
+    fn index<C: ConstraintSynthesizer<E::Fr>>(
+        params: &Self::Params,
+        circuit: C,
+    ) -> Result<(Self::ProvingKey, Self::VerifyingKey), Error>;
+
+    /// Prove, collaboratively, that `circuit`'s shared values satisfy it, and reveal the proof.
+    ///
+    /// Every party calls this, with the same key. In MPC, each party's `zk_rng` should be private
+    /// to it.
+    fn prove<S: PairingShare<E>, C: ConstraintSynthesizer<MpcFr<E, S>>, R: RngCore>(
+        pk: &Self::ProvingKey,
+        circuit: C,
+        zk_rng: &mut R,
+    ) -> Result<Self::Proof, Error>;
+
+    /// Prove, as [CollaborativeSnark::prove] does, over the sharing of security model `M`.
+    fn prove_in<
+        M: MpcSecurityModel,
+        C: ConstraintSynthesizer<MpcFr<E, M::Share<E>>>,
+        R: RngCore,
+    >(
+        pk: &Self::ProvingKey,
+        circuit: C,
+        zk_rng: &mut R,
+    ) -> Result<Self::Proof, Error> {
+        Self::prove::<M::Share<E>, C, R>(pk, circuit, zk_rng)
+    }
+
+    /// Whether `proof` shows that some witness satisfies the circuit, with `public_inputs`.
+    fn verify(
+        vk: &Self::VerifyingKey,
+        public_inputs: &[E::Fr],
+        proof: &Self::Proof,
+    ) -> Result<bool, Error>;
+}
+
+/// Groth16, with its prover run in MPC (see [crate::groth]).
+pub struct Groth16;
+
+impl<E: PairingEngine> CollaborativeSnark<E> for Groth16 {
+    const NAME: &'static str = "groth16";
+    type Params = ark_groth16::ProvingKey<E>;
+    type ProvingKey = ark_groth16::ProvingKey<E>;
+    type VerifyingKey = ark_groth16::PreparedVerifyingKey<E>;
+    type Proof = ark_groth16::Proof<E>;
+
+    fn setup<C: ConstraintSynthesizer<E::Fr>, R: RngCore>(
+        circuit: C,
+        rng: &mut R,
+    ) -> Result<Self::Params, Error> {
+        Ok(ark_groth16::generate_random_parameters::<E, _, _>(
+            circuit, rng,
+        )?)
+    }
+
+    /// The parameters are already the circuit's keys, so `circuit` is not needed.
+    fn index<C: ConstraintSynthesizer<E::Fr>>(
+        params: &Self::Params,
+        _circuit: C,
+    ) -> Result<(Self::ProvingKey, Self::VerifyingKey), Error> {
+        Ok((
+            params.clone(),
+            ark_groth16::prepare_verifying_key(&params.vk),
+        ))
+    }
+
+    fn prove<S: PairingShare<E>, C: ConstraintSynthesizer<MpcFr<E, S>>, R: RngCore>(
+        pk: &Self::ProvingKey,
+        circuit: C,
+        zk_rng: &mut R,
+    ) -> Result<Self::Proof, Error> {
+        let mpc_pk = ark_groth16::ProvingKey::from_public(pk.clone());
+        let pf = crate::groth::prover::create_random_proof::<MpcPairingEngine<E, S>, _, _>(
+            circuit, &mpc_pk, zk_rng,
+        )?;
+        Ok(pf.reveal())
+    }
+
+    fn verify(
+        vk: &Self::VerifyingKey,
+        public_inputs: &[E::Fr],
+        proof: &Self::Proof,
+    ) -> Result<bool, Error> {
+        Ok(ark_groth16::verify_proof(vk, proof, public_inputs)?)
+    }
+}
+
+impl Groth16 {
+    /// Prove, collaboratively, as [CollaborativeSnark::prove] does, but reveal the proof sealed to
+    /// `to` (see [crate::sealed]), so that only its receiver learns it.
+    ///
+    /// `zk_rng` draws both the proof's randomness and the sealing's: a party that knew either
+    /// could unseal the proof, so each party's must be its own.
+    pub fn prove_sealed<
+        E: PairingEngine,
+        S: PairingShare<E>,
+        C: ConstraintSynthesizer<MpcFr<E, S>>,
+    >(
+        pk: &ark_groth16::ProvingKey<E>,
+        circuit: C,
+        to: &ReceiverPublicKey<E>,
+        zk_rng: &mut BlindingRng,
+    ) -> Result<SealedProof<E>, Error> {
+        let mpc_pk = ark_groth16::ProvingKey::from_public(pk.clone());
+        let pf = crate::groth::prover::create_random_proof::<MpcPairingEngine<E, S>, _, _>(
+            circuit, &mpc_pk, zk_rng,
+        )?;
+        Ok(sealed::seal_proof(pf, to, zk_rng))
+    }
+}
+
+type KzgMarlin<F, E> = ark_marlin::Marlin<F, MarlinKZG10<E, DensePolynomial<F>>, Blake2s>;
+
+/// Marlin, with the KZG commitments of [MarlinKZG10].
+pub struct Marlin;
+
+fn marlin_error<E: fmt::Debug>(e: E) -> Error {
+    Error::Snark(format!("{:?}", e))
+}
+
+impl<E: PairingEngine> CollaborativeSnark<E> for Marlin {
+    const NAME: &'static str = "marlin";
+    type Params = ark_marlin::UniversalSRS<E::Fr, MarlinKZG10<E, DensePolynomial<E::Fr>>>;
+    type ProvingKey = ark_marlin::IndexProverKey<E::Fr, MarlinKZG10<E, DensePolynomial<E::Fr>>>;
+    type VerifyingKey = ark_marlin::IndexVerifierKey<E::Fr, MarlinKZG10<E, DensePolynomial<E::Fr>>>;
+    type Proof = ark_marlin::Proof<E::Fr, MarlinKZG10<E, DensePolynomial<E::Fr>>>;
+
+    /// An SRS for exactly `circuit`'s size.
+    fn setup<C: ConstraintSynthesizer<E::Fr>, R: RngCore>(
+        circuit: C,
+        rng: &mut R,
+    ) -> Result<Self::Params, Error> {
+        let index = ark_marlin::ahp::AHPForR1CS::index(circuit).map_err(marlin_error)?;
+        let info = index.index_info;
+        KzgMarlin::<E::Fr, E>::universal_setup(
+            info.num_constraints,
+            info.num_variables,
+            info.num_non_zero,
+            rng,
+        )
+        .map_err(marlin_error)
+    }
+
+    fn index<C: ConstraintSynthesizer<E::Fr>>(
+        params: &Self::Params,
+        circuit: C,
+    ) -> Result<(Self::ProvingKey, Self::VerifyingKey), Error> {
+        KzgMarlin::<E::Fr, E>::index(params, circuit).map_err(marlin_error)
+    }
+
+    fn prove<S: PairingShare<E>, C: ConstraintSynthesizer<MpcFr<E, S>>, R: RngCore>(
+        pk: &Self::ProvingKey,
+        circuit: C,
+        zk_rng: &mut R,
+    ) -> Result<Self::Proof, Error> {
+        let mpc_pk = ark_marlin::IndexProverKey::from_public(pk.clone());
+        let pf = KzgMarlin::<MpcFr<E, S>, MpcPairingEngine<E, S>>::prove(&mpc_pk, circuit, zk_rng)
+            .map_err(marlin_error)?;
+        Ok(pf.reveal())
+    }
+
+    /// Batches the pairing checks with fresh randomness.
+    fn verify(
+        vk: &Self::VerifyingKey,
+        public_inputs: &[E::Fr],
+        proof: &Self::Proof,
+    ) -> Result<bool, Error> {
+        KzgMarlin::<E::Fr, E>::verify(vk, public_inputs, proof, &mut rand::thread_rng())
+            .map_err(marlin_error)
+    }
+}
+
+type KzgPlonk<F, E> = mpc_plonk::Plonk<F, MarlinKZG10<E, DensePolynomial<F>>>;
+
+/// Plonk, proving the circuit as lowered by [R1csPlonk].
+pub struct Plonk;
+
+/// A Plonk verifier's key, with the lowered circuit it checks proofs against.
+pub struct PlonkVerifyingKey<E: PairingEngine> {
+    pub vk: mpc_plonk::VerifierKey<
+        ark_poly_commit::marlin_pc::Commitment<E>,
+        ark_poly_commit::marlin_pc::VerifierKey<E>,
+    >,
+    /// The lowered circuit, without values
+    pub r1cs: R1csPlonk<E::Fr>,
+    pub circ: Instance<E::Fr>,
+}
+
+impl<E: PairingEngine> CollaborativeSnark<E> for Plonk {
+    const NAME: &'static str = "plonk";
+    type Params = ark_poly_commit::kzg10::UniversalParams<E>;
+    type ProvingKey = mpc_plonk::ProverKey<
+        E::Fr,
+        ark_poly_commit::marlin_pc::Commitment<E>,
+        ark_poly_commit::marlin_pc::CommitterKey<E>,
+    >;
+    type VerifyingKey = PlonkVerifyingKey<E>;
+    type Proof = mpc_plonk::Proof<
+        E::Fr,
+        ark_poly_commit::marlin_pc::Commitment<E>,
+        ark_poly_commit::kzg10::Proof<E>,
+    >;
+
+    /// An SRS for `circuit`'s number of gates, once lowered.
+    fn setup<C: ConstraintSynthesizer<E::Fr>, R: RngCore>(
+        circuit: C,
+        rng: &mut R,
+    ) -> Result<Self::Params, Error> {
+        let r1cs = R1csPlonk::from_synthesizer(circuit, false)?;
+        Ok(KzgPlonk::<E::Fr, E>::universal_setup(
+            r1cs.circuit.n_gates(),
+            rng,
+        ))
+    }
+
+    fn index<C: ConstraintSynthesizer<E::Fr>>(
+        params: &Self::Params,
+        circuit: C,
+    ) -> Result<(Self::ProvingKey, Self::VerifyingKey), Error> {
+        let r1cs = R1csPlonk::from_synthesizer(circuit, false)?;
+        let circ = Instance::from_circuit(&r1cs.circuit);
+        let (pk, vk) = KzgPlonk::<E::Fr, E>::circuit_setup(params, &circ);
+        Ok((pk, PlonkVerifyingKey { vk, r1cs, circ }))
+    }
+
+    fn prove<S: PairingShare<E>, C: ConstraintSynthesizer<MpcFr<E, S>>, R: RngCore>(
+        pk: &Self::ProvingKey,
+        circuit: C,
+        zk_rng: &mut R,
+    ) -> Result<Self::Proof, Error> {
+        let mpc_pk = Reveal::from_public(pk.clone());
+        let (pf, _inputs) = KzgPlonk::<MpcFr<E, S>, MpcPairingEngine<E, S>>::prove_synthesizer(
+            &mpc_pk, circuit, zk_rng,
+        )?;
+        Ok(pf.reveal())
+    }
+
+    fn verify(
+        vk: &Self::VerifyingKey,
+        public_inputs: &[E::Fr],
+        proof: &Self::Proof,
+    ) -> Result<bool, Error> {
+        let public = vk.r1cs.public_inputs(public_inputs);
+        let report = KzgPlonk::<E::Fr, E>::verify_with_report(
+            &vk.vk,
+            &vk.circ,
+            proof.clone(),
+            &public,
+            None,
+        );
+        Ok(report.is_ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_377::{Bls12_377 as E, Fr};
+    use ark_ff::{Field, UniformRand};
+    use ark_relations::lc;
+    use ark_relations::r1cs::{ConstraintSystemRef, Variable};
+    use ark_std::test_rng;
+    use mpc_algebra::share::add::AdditivePairingShare;
+    use mpc_net::MpcMultiNet;
+
+    type S = AdditivePairingShare<E>;
+
+    /// A chain of squarings, whose last value is public.
+    struct Squarings<F> {
+        chain: Vec<Option<F>>,
+    }
+
+    impl<F: Field> ConstraintSynthesizer<F> for Squarings<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let n = self.chain.len();
+            let vars = self
+                .chain
+                .iter()
+                .enumerate()
+                .map(|(i, v)| {
+                    let v = || v.ok_or(SynthesisError::AssignmentMissing);
+                    if i + 1 == n {
+                        cs.new_input_variable(v)
+                    } else {
+                        cs.new_witness_variable(v)
+                    }
+                })
+                .collect::<Result<Vec<Variable>, _>>()?;
+            for w in vars.windows(2) {
+                cs.enforce_constraint(lc!() + w[0], lc!() + w[0], lc!() + w[1])?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Prove 4 squarings under `Sn` as each of two parties, through [CollaborativeSnark], and
+    /// check that every party's proof verifies, for the chain's end and not another value.
+    ///
+    /// Keys hold `Rc`s, so each party sets up its own (the same, from the same seed).
+    fn prove_squarings<Sn: CollaborativeSnark<E>>() {
+        let chain: Vec<Fr> =
+            std::iter::successors(Some(Fr::rand(&mut test_rng())), |a| Some(a.square()))
+                .take(5)
+                .collect();
+        let end = *chain.last().unwrap();
+        let verified = MpcMultiNet::simulate(2, |_| {
+            let no_data = || Squarings::<Fr> {
+                chain: vec![None; chain.len()],
+            };
+            let params = Sn::setup(no_data(), &mut test_rng()).unwrap();
+            let (pk, vk) = Sn::index(&params, no_data()).unwrap();
+            let shares = MpcFr::<E, S>::king_share_batch(chain.clone(), &mut test_rng());
+            let circuit = Squarings {
+                chain: shares.into_iter().map(Some).collect(),
+            };
+            let pf = Sn::prove::<S, _, _>(&pk, circuit, &mut BlindingRng::new()).unwrap();
+            (
+                Sn::verify(&vk, &[end], &pf).unwrap(),
+                Sn::verify(&vk, &[end + Fr::from(1u64)], &pf).unwrap(),
+            )
+        });
+        assert_eq!(verified, vec![(true, false); 2]);
+    }
+
+    #[test]
+    fn groth16_prove() {
+        prove_squarings::<Groth16>();
+    }
+
+    #[test]
+    fn marlin_prove() {
+        prove_squarings::<Marlin>();
+    }
+
+    #[test]
+    fn plonk_prove() {
+        prove_squarings::<Plonk>();
+    }
+}
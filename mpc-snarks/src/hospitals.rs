@@ -0,0 +1,296 @@
+//! Two hospitals prove, together, that their combined count of some outcome exceeds a public
+//! threshold, without revealing either count.
+//!
+//! Each hospital runs this binary as one party, with its own records: one count per line (say,
+//! cases per ward). Hospital A is party 0, the king; hospital B is party 1.
+//!
+//! ```bash
+//! hospitals --hosts data/2 --party 0 --records data/hospitals/a.txt --threshold 200 &
+//! hospitals --hosts data/2 --party 1 --records data/hospitals/b.txt --threshold 200
+//! ```
+//!
+//! The flow is the template for a collaborative Plonk proof:
+//!
+//! 1. Both parties build the same circuit (see [threshold_circuit]) and keys, without values.
+//! 2. Each secret-shares its own records, and its subtotal's bits: A as the king (see
+//!    [assign_hybrid]), B by adding its values to its shares.
+//! 3. The circuit adds the subtotals in binary, in MPC, and compares the total with the threshold.
+//! 4. The parties prove, reveal the proof, and each verifies it.
+//!
+//! The only values revealed are the proof's public wires: the threshold, some constants, and
+//! whether the total exceeds the threshold.
+use ark_bls12_377::Bls12_377 as E;
+use ark_ec::PairingEngine;
+use ark_ff::{Field, One, Zero};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly_commit::marlin_pc::MarlinKZG10;
+use ark_std::test_rng;
+use clap::arg_enum;
+use mpc_algebra::{BlindingRng, MpcPairingEngine, PairingShare, Reveal};
+use mpc_net::{Abort, MpcMultiNet, MpcNet, MpcNetError};
+use mpc_plonk::relations::{
+    dsl::{CircuitBuilder, Wire},
+    flat::{self, Instance},
+    gadgets::{bit_inputs, Gadgets},
+    structured::{assign_hybrid, PlonkCircuit, Witness},
+};
+use mpc_plonk::{Plonk, ProverKey};
+use structopt::StructOpt;
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+type Fr = <E as PairingEngine>::Fr;
+type KzgPlonk<E> =
+    Plonk<<E as PairingEngine>::Fr, MarlinKZG10<E, DensePolynomial<<E as PairingEngine>::Fr>>>;
+
+/// Bits in each hospital's subtotal: each must be under `2^BITS`.
+const BITS: usize = 16;
+
+arg_enum! {
+    #[derive(PartialEq, Debug, Clone, Copy)]
+    pub enum MpcAlg {
+        Spdz,
+        Hbc,
+    }
+}
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "hospitals",
+    about = "Two hospitals prove that their combined count exceeds a threshold"
+)]
+struct Opt {
+    #[structopt(long, parse(from_os_str))]
+    hosts: PathBuf,
+    /// 0 for hospital A, 1 for hospital B
+    #[structopt(long)]
+    party: usize,
+    /// This hospital's records: one count per line
+    #[structopt(long, parse(from_os_str))]
+    records: PathBuf,
+    /// The public threshold that the combined count must exceed
+    #[structopt(long)]
+    threshold: u64,
+    /// Records per hospital, at most; both hospitals must pass the same
+    #[structopt(long, default_value = "16")]
+    max_records: usize,
+    /// Sharing scheme (an additive one: each hospital adds its values to its shares)
+    #[structopt(long, default_value = "spdz")]
+    alg: MpcAlg,
+}
+
+/// The hospitals' names, by party.
+const HOSPITALS: [&str; 2] = ["a", "b"];
+
+/// The circuit both hospitals prove: that the sum of their records exceeds the threshold, with
+/// the public values its gadgets expect (see [Gadgets::public_values]).
+///
+/// Its inputs are each hospital's records (`a_rec_i`, `b_rec_i`) and its subtotal's bits
+/// (`a_bit_j`, `b_bit_j`), and, publicly, the bits `c_bit_j` of `2^(BITS + 1) - threshold - 1`
+/// (see [public_inputs]). It checks each hospital's bits against its subtotal, adds the
+/// subtotals in binary, and adds the result to the `c_bit`s. The last carry, public as
+/// `exceeds`, is one exactly when the total exceeds the threshold.
+fn threshold_circuit<F: Field>(max_records: usize) -> (PlonkCircuit<F>, HashMap<String, Fr>) {
+    let b = CircuitBuilder::new();
+    let g = Gadgets::new(&b);
+    let subtotal_bits: Vec<Vec<Wire<'_, F>>> = HOSPITALS
+        .iter()
+        .map(|h| {
+            let total = (0..max_records)
+                .map(|i| b.wire(&format!("{}_rec_{}", h, i)))
+                .reduce(|x, y| x + y)
+                .expect("A hospital has records");
+            g.decompose_to_bits(h, total, BITS)
+        })
+        .collect();
+    let sum = g.add_bits(&subtotal_bits[0], &subtotal_bits[1]);
+    let c_bits: Vec<_> = (0..=BITS)
+        .map(|j| {
+            let name = format!("c_bit_{}", j);
+            b.publicize(&name);
+            b.wire(&name)
+        })
+        .collect();
+    let exceeds = *g.add_bits(&sum, &c_bits).last().unwrap();
+    b.bind("exceeds", exceeds);
+    b.publicize("exceeds");
+    let public = g.public_values();
+    (b.finish(), public)
+}
+
+/// Every public wire of [threshold_circuit], but `exceeds`: its gadgets', and the bits of
+/// `2^(BITS + 1) - threshold - 1`.
+fn public_inputs(threshold: u64, gadgets: HashMap<String, Fr>) -> HashMap<String, Fr> {
+    assert!(
+        threshold < 1 << (BITS + 1),
+        "The threshold must be under 2^{}",
+        BITS + 1
+    );
+    let complement = (1u64 << (BITS + 1)) - threshold - 1;
+    let mut public = gadgets;
+    public.extend(bit_inputs("c", complement, BITS + 1));
+    public
+}
+
+/// Hospital `h`'s inputs to [threshold_circuit]: its records, padded with zeros to
+/// `max_records`, and its subtotal's bits.
+fn hospital_inputs(h: &str, records: &[u64], max_records: usize) -> HashMap<String, Fr> {
+    assert!(
+        records.len() <= max_records,
+        "{} records, but at most {} fit",
+        records.len(),
+        max_records
+    );
+    let subtotal: u64 = records.iter().sum();
+    assert!(
+        subtotal < 1 << BITS,
+        "The records' total, {}, must be under 2^{}",
+        subtotal,
+        BITS
+    );
+    let padded = records.iter().chain(std::iter::repeat(&0));
+    let mut inputs: HashMap<String, Fr> = padded
+        .take(max_records)
+        .enumerate()
+        .map(|(i, r)| (format!("{}_rec_{}", h, i), Fr::from(*r)))
+        .collect();
+    inputs.extend(bit_inputs(h, subtotal, BITS));
+    inputs
+}
+
+/// One count per line; blank lines are skipped.
+fn read_records(path: &Path) -> Vec<u64> {
+    let text = std::fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("Could not read {}: {}", path.display(), e));
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            line.parse()
+                .unwrap_or_else(|e| panic!("Bad record {:?} in {}: {}", line, path.display(), e))
+        })
+        .collect()
+}
+
+/// Prove, with the other hospital, that the combined count exceeds `threshold`, and verify the
+/// proof. Returns whether it does.
+fn prove_threshold<S: PairingShare<E>>(
+    records: HashMap<String, Fr>,
+    threshold: u64,
+    max_records: usize,
+) -> bool {
+    type MpcE<S> = MpcPairingEngine<E, S>;
+    type MFr<S> = <MpcE<S> as PairingEngine>::Fr;
+
+    // Setup: both parties derive the same keys from a fixed seed. A real deployment would use
+    // the SRS of a setup ceremony (see [mpc_plonk::ptau]).
+    let (v_c, gadgets) = threshold_circuit::<Fr>(max_records);
+    let v_circ = Instance::from_circuit(&v_c);
+    let srs = KzgPlonk::<E>::universal_setup(v_c.n_gates(), &mut test_rng());
+    let (pk, vk) = KzgPlonk::<E>::circuit_setup_hiding(&srs, &v_circ);
+
+    // Sharing: A's inputs, as the king's; B's, by adding them to B's shares of zero; and the
+    // public inputs, as public values.
+    let (c, _) = threshold_circuit::<MFr<S>>(max_records);
+    let local_inputs: HashSet<String> = c
+        .inputs
+        .values()
+        .filter(|name| name.starts_with("a_"))
+        .cloned()
+        .collect();
+    let local: Option<&dyn Witness<Fr>> = if MpcMultiNet::am_king() {
+        Some(&records)
+    } else {
+        None
+    };
+    let public_values = public_inputs(threshold, gadgets);
+    let shared = |name: &str| -> Option<MFr<S>> {
+        if let Some(v) = public_values.get(name) {
+            return Some(MFr::<S>::from_public(*v));
+        }
+        let mine = if name.starts_with("b_") && MpcMultiNet::party_id() == 1 {
+            *records.get(name)?
+        } else {
+            Fr::zero()
+        };
+        Some(MFr::<S>::from_add_shared(mine))
+    };
+    let (assignment, _) = assign_hybrid(&c, &local_inputs, local, &shared, &mut test_rng())
+        .unwrap_or_else(|e| panic!("Could not share the inputs: {}", e));
+    let exceeds_var = *c
+        .pub_vars
+        .iter()
+        .find(|(_, name)| *name == "exceeds")
+        .unwrap()
+        .0;
+    let exceeds = assignment.0[exceeds_var as usize].reveal();
+    let c = c.with_assignment(assignment);
+    let circ = Instance::from_circuit(&c);
+    let witness = flat::Witness::from_circuit(&circ, &c);
+
+    // Proving, and revealing the proof
+    let mpc_pk = ProverKey::from_public(pk);
+    let mpc_pf = KzgPlonk::<MpcE<S>>::try_prove(&mpc_pk, &circ, &witness, &mut BlindingRng::new())
+        .unwrap_or_else(|abort| abort.raise());
+    let pf = mpc_pf.reveal();
+
+    // Verifying, as anyone could, from the public inputs and the claimed result
+    let mut public = public_values;
+    public.insert("exceeds".to_owned(), exceeds);
+    if let Err(report) = KzgPlonk::<E>::verify_with_report(&vk, &v_circ, pf, &public, None) {
+        report.abort().raise();
+    }
+    exceeds.is_one()
+}
+
+fn main() {
+    let opt = Opt::from_args();
+    env_logger::init();
+    assert!(opt.party < 2, "There are two hospitals: parties 0 and 1");
+    let h = HOSPITALS[opt.party];
+    let records = hospital_inputs(h, &read_records(&opt.records), opt.max_records);
+    // Network failures and other aborts are reported below, not as panics.
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let payload = info.payload();
+        if !payload.is::<MpcNetError>() && !payload.is::<Abort>() {
+            default_hook(info)
+        }
+    }));
+    MpcMultiNet::init_from_file(opt.hosts.to_str().unwrap(), opt.party);
+    assert_eq!(
+        MpcMultiNet::n_parties(),
+        2,
+        "The hosts file must list two parties"
+    );
+    let (threshold, max_records) = (opt.threshold, opt.max_records);
+    let exceeds = mpc_net::catch_abort(|| match opt.alg {
+        MpcAlg::Spdz => prove_threshold::<mpc_algebra::share::spdz::SpdzPairingShare<E>>(
+            records,
+            threshold,
+            max_records,
+        ),
+        MpcAlg::Hbc => prove_threshold::<mpc_algebra::share::add::AdditivePairingShare<E>>(
+            records,
+            threshold,
+            max_records,
+        ),
+    })
+    .unwrap_or_else(|e| {
+        eprintln!("Aborting: {}", e);
+        MpcMultiNet::deinit();
+        std::process::exit(e.code.exit_code());
+    });
+    MpcMultiNet::deinit();
+    println!(
+        "Hospital {}: the combined count {} {}; the proof verifies",
+        h.to_uppercase(),
+        if exceeds {
+            "exceeds"
+        } else {
+            "does not exceed"
+        },
+        threshold
+    );
+}
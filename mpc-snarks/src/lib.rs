@@ -1,3 +1,13 @@
+pub mod collaborative;
+pub use collaborative::CollaborativeSnark;
+pub mod sealed;
+
+/// The Groth16 prover, over MPC fields.
+pub mod groth {
+    pub mod prover;
+    pub mod r1cs_to_qap;
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
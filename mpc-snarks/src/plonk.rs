@@ -1,10 +1,12 @@
+use super::silly::MySillyCircuit;
 use ark_ff::UniformRand;
 use ark_poly::univariate::DensePolynomial;
+use ark_poly_commit::ipa_pc::InnerProductArgPC;
 use ark_poly_commit::marlin_pc::MarlinKZG10;
 use ark_std::{end_timer, start_timer, test_rng};
 use mpc_algebra::honest_but_curious::*;
 use mpc_plonk::*;
-use mpc_algebra::Reveal;
+use mpc_algebra::{BlindingRng, Reveal};
 use std::collections::HashMap;
 
 type F = ark_bls12_377::Fr;
@@ -17,40 +19,35 @@ type LocalPlonk = mpc_plonk::Plonk<F, LocalMarlinKZG10>;
 type MpcPlonk = mpc_plonk::Plonk<MF, MpcMarlinKZG10>;
 
 pub fn local_test_prove_and_verify(n_iters: usize) {
-    use relations::{flat::*, structured::*};
+    use relations::{flat::*, structured::PlonkCircuit};
     let steps = n_iters;
     let start = F::from(2u64);
     let c = PlonkCircuit::<F>::new_squaring_circuit(steps, Some(start));
     let res = (0..steps).fold(start, |a, _| a * a);
     let public: HashMap<String, F> = vec![("out".to_owned(), res)].into_iter().collect();
-    let circ = CircuitLayout::from_circuit(&c);
+    let circ = Instance::from_circuit(&c);
+    let witness = Witness::from_circuit(&circ, &c);
 
     let setup_rng = &mut test_rng();
     let zk_rng = &mut test_rng();
 
-    let v_circ = {
-        let mut t = circ.clone();
-        t.p = None;
-        t
-    };
-
     let srs = LocalPlonk::universal_setup(steps.next_power_of_two(), setup_rng);
-    let (pk, vk) = LocalPlonk::circuit_setup(&srs, &v_circ);
-    let pf = LocalPlonk::prove(&pk, &circ, zk_rng);
-    LocalPlonk::verify(&vk, &v_circ, pf, &public);
+    let (pk, vk) = LocalPlonk::circuit_setup(&srs, &circ);
+    let pf = LocalPlonk::prove(&pk, &circ, &witness, zk_rng);
+    LocalPlonk::verify(&vk, &circ, pf, &public);
 }
 
 pub fn mpc_test_prove_and_verify(n_iters: usize) {
-    use relations::{flat::*, structured::*};
+    use relations::{flat::*, structured::PlonkCircuit};
     let steps = n_iters;
 
     // empty circuit
     let v_c = PlonkCircuit::<F>::new_squaring_circuit(steps, None);
-    let v_circ = CircuitLayout::from_circuit(&v_c);
+    let v_circ = Instance::from_circuit(&v_c);
     // setup
     let setup_rng = &mut test_rng();
     let srs = LocalPlonk::universal_setup(steps.next_power_of_two(), setup_rng);
-    let (pk, vk) = LocalPlonk::circuit_setup(&srs, &v_circ);
+    let (pk, vk) = LocalPlonk::circuit_setup_hiding(&srs, &v_circ);
 
     // data circuit
     let data_rng = &mut test_rng();
@@ -60,12 +57,89 @@ pub fn mpc_test_prove_and_verify(n_iters: usize) {
         .into_iter()
         .collect();
     let c = PlonkCircuit::<MF>::new_squaring_circuit(steps, Some(start));
-    let circ = CircuitLayout::from_circuit(&c);
+    let circ = Instance::from_circuit(&c);
+    let witness = Witness::from_circuit(&circ, &c);
 
     let t = start_timer!(|| "timed section");
     let mpc_pk = ProverKey::from_public(pk);
-    let mpc_pf = MpcPlonk::prove(&mpc_pk, &circ, &mut test_rng());
+    let mpc_pf = MpcPlonk::prove_hiding(&mpc_pk, &circ, &witness, &mut BlindingRng::new());
     let pf = mpc_pf.reveal();
     end_timer!(t);
     LocalPlonk::verify(&vk, &v_circ, pf, &public);
+
+    let t = start_timer!(|| "timed section (small proof)");
+    let mpc_pf = MpcPlonk::prove_small(&mpc_pk, &circ, &witness, &mut test_rng());
+    let pf = mpc_pf.reveal();
+    end_timer!(t);
+    LocalPlonk::verify_small(&vk, &v_circ, pf, &public);
+}
+
+/// Like [mpc_test_prove_and_verify], but for an R1CS circuit, whose witness is generated in MPC.
+pub fn mpc_r1cs_test_prove_and_verify(n_iters: usize) {
+    use relations::{flat::*, r1cs::*};
+
+    // empty circuit
+    let v_c = R1csPlonk::<F>::from_synthesizer(MySillyCircuit { a: None, b: None }, false).unwrap();
+    let v_circ = Instance::from_circuit(&v_c.circuit);
+    // setup
+    let setup_rng = &mut test_rng();
+    let srs = LocalPlonk::universal_setup(v_c.circuit.n_gates(), setup_rng);
+    let (pk, vk) = LocalPlonk::circuit_setup(&srs, &v_circ);
+    let mpc_pk = ProverKey::from_public(pk);
+
+    let data_rng = &mut test_rng();
+    for _ in 0..n_iters {
+        let a = MF::rand(data_rng);
+        let b = MF::rand(data_rng);
+        let c = MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        };
+        let (mpc_pf, inputs) = MpcPlonk::prove_synthesizer(&mpc_pk, c, &mut test_rng()).unwrap();
+        let pf = mpc_pf.reveal();
+        let inputs: Vec<F> = inputs.reveal();
+        assert_eq!(inputs, vec![(a * b).reveal()]);
+        LocalPlonk::verify(&vk, &v_circ, pf, &v_c.public_inputs(&inputs));
+    }
+}
+
+type IpaF = ark_pallas::Fr;
+type IpaG = ark_pallas::Affine;
+type MpcIpaG = MpcCurveAffine<IpaG>;
+type MpcIpaF = MpcField<IpaF>;
+type LocalIpaPC = InnerProductArgPC<IpaG, blake2::Blake2s, DensePolynomial<IpaF>>;
+type MpcIpaPC = InnerProductArgPC<MpcIpaG, blake2::Blake2s, DensePolynomial<MpcIpaF>>;
+type LocalIpaPlonk = mpc_plonk::Plonk<IpaF, LocalIpaPC>;
+type MpcIpaPlonk = mpc_plonk::Plonk<MpcIpaF, MpcIpaPC>;
+
+/// Like [mpc_test_prove_and_verify], but over Pallas with the inner-product argument.
+pub fn mpc_ipa_test_prove_and_verify(n_iters: usize) {
+    use relations::{flat::*, structured::PlonkCircuit};
+    let steps = n_iters;
+
+    // empty circuit
+    let v_c = PlonkCircuit::<IpaF>::new_squaring_circuit(steps, None);
+    let v_circ = Instance::from_circuit(&v_c);
+    // setup
+    let setup_rng = &mut test_rng();
+    let srs = LocalIpaPlonk::universal_setup(steps.next_power_of_two(), setup_rng);
+    let (pk, vk) = LocalIpaPlonk::circuit_setup(&srs, &v_circ);
+
+    // data circuit
+    let data_rng = &mut test_rng();
+    let start = MpcIpaF::rand(data_rng);
+    let res = (0..steps).fold(start, |a, _| a * a);
+    let public: HashMap<String, IpaF> = vec![("out".to_owned(), res.reveal())]
+        .into_iter()
+        .collect();
+    let c = PlonkCircuit::<MpcIpaF>::new_squaring_circuit(steps, Some(start));
+    let circ = Instance::from_circuit(&c);
+    let witness = Witness::from_circuit(&circ, &c);
+
+    let t = start_timer!(|| "timed section");
+    let mpc_pk = ProverKey::from_public(pk);
+    let mpc_pf = MpcIpaPlonk::prove(&mpc_pk, &circ, &witness, &mut test_rng());
+    let pf = mpc_pf.reveal();
+    end_timer!(t);
+    LocalIpaPlonk::verify(&vk, &v_circ, pf, &public);
 }
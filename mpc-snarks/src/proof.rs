@@ -3,6 +3,7 @@
 use ark_ec::PairingEngine;
 use ark_ff::{Field, UniformRand};
 use ark_groth16;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_relations::{
     lc,
     r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError, Variable},
@@ -12,11 +13,17 @@ use ark_std::{end_timer, start_timer};
 use blake2::Blake2s;
 use clap::arg_enum;
 use log::debug;
-use mpc_algebra::{channel, MpcPairingEngine, PairingShare, Reveal};
-use mpc_net::{MpcMultiNet, MpcNet, MpcTwoNet};
+use mpc_algebra::{
+    channel, preprocessing, prss, BlindingRng, MpcG1Affine, MpcPairingEngine, PairingShare, Reveal,
+};
+use mpc_net::multi::Timeouts;
+use mpc_net::{Abort, AbortCode, KingRotation, MpcMultiNet, MpcNet, MpcNetError, MpcTwoNet};
+use mpc_snarks::collaborative::{CollaborativeSnark, Groth16, MpcFr};
+use mpc_snarks::sealed::{ReceiverKey, ReceiverPublicKey, SealedProof};
 use structopt::StructOpt;
 
 use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
 mod groth;
 mod marlin;
@@ -24,12 +31,52 @@ mod silly;
 
 const TIMED_SECTION_LABEL: &str = "timed section";
 
+/// Give up if a revealed proof did not verify. Had a party deviated, a MAC check would have
+/// failed first (with SPDZ), so the witness must be wrong.
+fn check_verified(verified: bool) {
+    if !verified {
+        Abort::new(AbortCode::ConstraintUnsatisfied, "proof did not verify").raise();
+    }
+}
+
 trait SnarkBench {
     fn local<E: PairingEngine>(n: usize, timer_label: &str);
     fn ark_local<E: PairingEngine>(_n: usize, _timer_label: &str) {
         unimplemented!("ark benchmark for {}", std::any::type_name::<Self>())
     }
-    fn mpc<E: PairingEngine, S: PairingShare<E>>(n: usize, timer_label: &str);
+    /// With `msm_tables`, precompute fixed-base tables for commitments, if the proof system has
+    /// a committer key to precompute (see [mpc_algebra::msm::precompute]).
+    fn mpc<E: PairingEngine, S: PairingShare<E>>(n: usize, timer_label: &str, msm_tables: bool);
+}
+
+/// What a collaborative proof cost this party.
+#[derive(Clone, Debug)]
+struct Measurement {
+    prove_time: Duration,
+    stats: mpc_net::Stats,
+    /// Size of the revealed, serialized proof
+    proof_bytes: usize,
+}
+
+impl Measurement {
+    /// Messages sent or received in lockstep: broadcasts, and exchanges with the king.
+    fn rounds(&self) -> usize {
+        self.stats.rounds()
+    }
+}
+
+/// Time `prove`, which proves collaboratively and returns the revealed proof, serialized, and
+/// count its communication.
+fn measure(prove: impl FnOnce() -> Vec<u8>) -> (Measurement, Vec<u8>) {
+    MpcMultiNet::reset_stats();
+    let start = Instant::now();
+    let proof = channel::without_cheating(prove);
+    let measurement = Measurement {
+        prove_time: start.elapsed(),
+        stats: MpcMultiNet::stats(),
+        proof_bytes: proof.len(),
+    };
+    (measurement, proof)
 }
 
 mod squarings {
@@ -109,7 +156,11 @@ mod squarings {
                 assert!(verify_proof(&pvk, &proof, &public_inputs).unwrap());
             }
 
-            fn mpc<E: PairingEngine, S: PairingShare<E>>(n: usize, timer_label: &str) {
+            fn mpc<E: PairingEngine, S: PairingShare<E>>(
+                n: usize,
+                timer_label: &str,
+                _msm_tables: bool,
+            ) {
                 let rng = &mut test_rng();
                 let circ_no_data = RepeatedSquaringCircuit::without_data(n);
 
@@ -128,17 +179,20 @@ mod squarings {
                 end_timer!(computation_timer);
                 MpcMultiNet::reset_stats();
                 let timer = start_timer!(|| timer_label);
-                let proof = channel::without_cheating(|| {
+                let proof_bytes = channel::without_cheating(|| {
                     let pf = create_random_proof::<MpcPairingEngine<E, S>, _, _>(circ_data, &mpc_params, rng)
                         .unwrap();
                     let reveal_timer = start_timer!(|| "reveal");
-                    let pf = pf.reveal();
+                    let mut bytes = Vec::new();
+                    pf.serialize_revealed(&mut bytes).unwrap();
                     end_timer!(reveal_timer);
-                    pf
+                    bytes
                 });
                 end_timer!(timer);
 
-                assert!(verify_proof(&pvk, &proof, &public_inputs).unwrap());
+                // Any Groth16 verifier can read the proof.
+                let proof = ark_groth16::Proof::<E>::deserialize(&proof_bytes[..]).unwrap();
+                check_verified(verify_proof(&pvk, &proof, &public_inputs).unwrap());
             }
         }
     }
@@ -173,7 +227,11 @@ mod squarings {
                 assert!(KzgMarlin::<E::Fr, E>::verify(&vk, &public_inputs, &proof, rng).unwrap());
             }
 
-            fn mpc<E: PairingEngine, S: PairingShare<E>>(n: usize, timer_label: &str) {
+            fn mpc<E: PairingEngine, S: PairingShare<E>>(
+                n: usize,
+                timer_label: &str,
+                _msm_tables: bool,
+            ) {
                 let rng = &mut test_rng();
                 let circ_no_data = RepeatedSquaringCircuit::without_data(n);
 
@@ -203,7 +261,9 @@ mod squarings {
                     .reveal()
                 });
                 end_timer!(timer);
-                assert!(KzgMarlin::<E::Fr, E>::verify(&vk, &public_inputs, &proof, rng).unwrap());
+                check_verified(
+                    KzgMarlin::<E::Fr, E>::verify(&vk, &public_inputs, &proof, rng).unwrap(),
+                );
             }
         }
     }
@@ -211,9 +271,10 @@ mod squarings {
     pub mod plonk {
         use super::*;
         use ark_poly::univariate::DensePolynomial;
-        use ark_poly_commit::marlin::marlin_pc::MarlinKZG10;
+        use ark_poly_commit::marlin::marlin_pc::{CommitterKey, MarlinKZG10};
         use mpc_algebra::Reveal;
-        use mpc_plonk::relations::flat::CircuitLayout;
+        use mpc_plonk::relations::flat::{Instance, Witness};
+        use mpc_plonk::relations::r1cs::R1csPlonk;
         use mpc_plonk::relations::structured::PlonkCircuit;
         use mpc_plonk::*;
 
@@ -227,6 +288,8 @@ mod squarings {
                 prods: (0..(n_vars - 1)).map(|i| (i, i, i + 1)).collect(),
                 sums: Vec::new(),
                 values: c.chain.into_iter().collect(),
+                inputs: Default::default(),
+                shared_outputs: Default::default(),
             };
             this.pad_to_power_of_2();
             this
@@ -239,12 +302,12 @@ mod squarings {
             fn local<E: PairingEngine>(n: usize, timer_label: &str) {
                 let rng = &mut test_rng();
                 let circ_no_data = plonk_squaring_circuit(RepeatedSquaringCircuit::without_data(n));
-                let circ_no_data = CircuitLayout::from_circuit(&circ_no_data);
+                let circ_no_data = Instance::from_circuit(&circ_no_data);
 
                 let a = E::Fr::rand(rng);
                 let circ_data = RepeatedSquaringCircuit::from_start(a, n);
                 let plonk_circ_data = plonk_squaring_circuit(circ_data.clone());
-                let plonk_circ_data = CircuitLayout::from_circuit(&plonk_circ_data);
+                let witness = Witness::from_circuit(&circ_no_data, &plonk_circ_data);
                 let public_inputs =
                     std::iter::once(("out".to_owned(), circ_data.chain.last().unwrap().unwrap()))
                         .collect();
@@ -254,15 +317,19 @@ mod squarings {
                     MarlinPcPlonk::<E::Fr, E>::universal_setup(n.next_power_of_two(), setup_rng);
                 let (pk, vk) = MarlinPcPlonk::<E::Fr, E>::circuit_setup(&srs, &circ_no_data);
                 let timer = start_timer!(|| timer_label);
-                let pf = MarlinPcPlonk::<E::Fr, E>::prove(&pk, &plonk_circ_data, zk_rng);
+                let pf = MarlinPcPlonk::<E::Fr, E>::prove(&pk, &circ_no_data, &witness, zk_rng);
                 end_timer!(timer);
                 MarlinPcPlonk::<E::Fr, E>::verify(&vk, &circ_no_data, pf, &public_inputs);
             }
 
-            fn mpc<E: PairingEngine, S: PairingShare<E>>(n: usize, timer_label: &str) {
+            fn mpc<E: PairingEngine, S: PairingShare<E>>(
+                n: usize,
+                timer_label: &str,
+                msm_tables: bool,
+            ) {
                 let rng = &mut test_rng();
                 let circ_no_data = plonk_squaring_circuit(RepeatedSquaringCircuit::without_data(n));
-                let circ_no_data = CircuitLayout::from_circuit(&circ_no_data);
+                let circ_no_data = Instance::from_circuit(&circ_no_data);
 
                 let a = E::Fr::rand(rng);
                 let circ_data = mpc_squaring_circuit::<
@@ -270,7 +337,8 @@ mod squarings {
                     <MpcPairingEngine<E, S> as PairingEngine>::Fr,
                 >(a, n);
                 let plonk_circ_data = plonk_squaring_circuit(circ_data.clone());
-                let plonk_circ_data = CircuitLayout::from_circuit(&plonk_circ_data);
+                let circ = Instance::from_circuit(&plonk_circ_data);
+                let witness = Witness::from_circuit(&circ, &plonk_circ_data);
                 let public_inputs = std::iter::once((
                     "out".to_owned(),
                     circ_data.chain.last().unwrap().unwrap().reveal(),
@@ -281,14 +349,21 @@ mod squarings {
                 let srs =
                     MarlinPcPlonk::<E::Fr, E>::universal_setup(n.next_power_of_two(), setup_rng);
                 let (pk, vk) = MarlinPcPlonk::<E::Fr, E>::circuit_setup(&srs, &circ_no_data);
-                let mpc_pk = Reveal::from_public(pk);
+                let mpc_pk: ProverKey<_, _, CommitterKey<MpcPairingEngine<E, S>>> =
+                    Reveal::from_public(pk);
+                if msm_tables {
+                    let timer = start_timer!(|| "MSM tables");
+                    MpcG1Affine::<E, S>::precompute_msm(&mpc_pk.pc_ck.powers);
+                    end_timer!(timer);
+                }
                 MpcMultiNet::reset_stats();
                 let t = start_timer!(|| timer_label);
                 let pf = channel::without_cheating(|| {
                     let pf = MarlinPcPlonk::<
                         <MpcPairingEngine<E, S> as PairingEngine>::Fr,
                         MpcPairingEngine<E, S>,
-                    >::prove(&mpc_pk, &plonk_circ_data, zk_rng);
+                    >::try_prove(&mpc_pk, &circ, &witness, zk_rng)
+                    .unwrap_or_else(|abort| abort.raise());
 
                     let reveal_timer = start_timer!(|| "reveal");
                     let pf = pf.reveal();
@@ -296,11 +371,74 @@ mod squarings {
                     pf
                 });
                 end_timer!(t);
-                MarlinPcPlonk::<E::Fr, E>::verify(&vk, &circ_no_data, pf, &public_inputs);
+                if let Err(report) = MarlinPcPlonk::<E::Fr, E>::verify_with_report(
+                    &vk,
+                    &circ_no_data,
+                    pf,
+                    &public_inputs,
+                    None,
+                ) {
+                    report.abort().raise();
+                }
+                mpc_algebra::msm::clear_precomputed();
             }
         }
     }
 
+    /// Keys for `n` squarings under `Sn`, and the start of the chain, the same in every process.
+    fn instance<Sn: CollaborativeSnark<E>, E: PairingEngine>(
+        n: usize,
+    ) -> (Sn::ProvingKey, Sn::VerifyingKey, E::Fr) {
+        let rng = &mut test_rng();
+        let params = Sn::setup(RepeatedSquaringCircuit::without_data(n), rng).unwrap();
+        let (pk, vk) = Sn::index(&params, RepeatedSquaringCircuit::without_data(n)).unwrap();
+        (pk, vk, E::Fr::rand(rng))
+    }
+
+    /// Prove `n` squarings collaboratively with Groth16, and reveal the proof sealed to `to`.
+    pub fn prove_sealed<E: PairingEngine, S: PairingShare<E>>(
+        n: usize,
+        to: &ReceiverPublicKey<E>,
+    ) -> SealedProof<E> {
+        let (pk, _vk, start) = instance::<Groth16, E>(n);
+        let circ_data = mpc_squaring_circuit::<E::Fr, MpcFr<E, S>>(start, n);
+        let zk_rng = &mut BlindingRng::new();
+        channel::without_cheating(|| {
+            Groth16::prove_sealed::<E, S, _>(&pk, circ_data, to, zk_rng)
+        })
+        .unwrap()
+    }
+
+    /// Whether `proof` shows `n` squarings, as [prove_sealed] proves them.
+    pub fn verify_groth16<E: PairingEngine>(n: usize, proof: &ark_groth16::Proof<E>) -> bool {
+        let (_pk, vk, start) = instance::<Groth16, E>(n);
+        let end = (0..n).fold(start, |a, _| a.square());
+        Groth16::verify(&vk, &[end], proof).unwrap()
+    }
+
+    /// Setup for `n` squarings under `Sn`, then prove them collaboratively and reveal the proof
+    /// (measured), and verify it.
+    ///
+    /// Every proof system proves the same R1CS circuit (Plonk lowers it with
+    /// [mpc_plonk::relations::r1cs::R1csPlonk]), from the same shared witness.
+    pub fn measure<Sn: CollaborativeSnark<E>, E: PairingEngine, S: PairingShare<E>>(
+        n: usize,
+    ) -> Measurement {
+        let (pk, vk, start) = instance::<Sn, E>(n);
+        let circ_data = mpc_squaring_circuit::<E::Fr, MpcFr<E, S>>(start, n);
+        let public_inputs = vec![circ_data.chain.last().unwrap().unwrap().reveal()];
+        let zk_rng = &mut test_rng();
+        let (measurement, proof_bytes) = super::measure(|| {
+            let pf = Sn::prove::<S, _, _>(&pk, circ_data, zk_rng).unwrap();
+            let mut bytes = Vec::new();
+            pf.serialize(&mut bytes).unwrap();
+            bytes
+        });
+        let proof = Sn::Proof::deserialize(&proof_bytes[..]).unwrap();
+        check_verified(Sn::verify(&vk, &public_inputs, &proof).unwrap());
+        measurement
+    }
+
     fn mpc_squaring_circuit<Fr: Field, MFr: Field + Reveal<Base = Fr>>(
         start: Fr,
         squarings: usize,
@@ -357,16 +495,236 @@ struct ShareInfo {
     /// Use spdz?
     #[structopt(long)]
     alg: MpcAlg,
+
+    /// Our file of preprocessed triples (see the `triples` binary). Without one, triples are
+    /// generated inline.
+    #[structopt(long, parse(from_os_str))]
+    triples: Option<PathBuf>,
+
+    /// Our TLS private key. If given, the hosts file must list each party's certificate.
+    #[cfg(feature = "tls")]
+    #[structopt(long, parse(from_os_str))]
+    tls_key: Option<PathBuf>,
+
+    /// Give up on a party that is silent for this many milliseconds. Without a timeout, we only
+    /// notice parties that crash.
+    #[structopt(long)]
+    peer_timeout_ms: Option<u64>,
+
+    /// With a timeout, how often (in milliseconds) to tell the other parties that we're alive.
+    #[structopt(long, default_value = "1000")]
+    heartbeat_ms: u64,
+
+    /// Reconnect to a party whose connection breaks, and carry on, rather than abort. Every party
+    /// must pass this, or none. Not with TLS.
+    #[structopt(long)]
+    resume: bool,
+
+    /// Precompute fixed-base tables for committing over the SRS (Plonk only). Building them
+    /// costs a few MSMs, outside the timed proof, and makes each commitment cheaper.
+    #[structopt(long)]
+    msm_tables: bool,
+
+    /// Make each party king in turn, rather than always party 0, so that the reveals of Shamir
+    /// and GSZ shares load every party alike. Every party must pass this, or none.
+    #[structopt(long)]
+    rotate_king: bool,
+
+    /// Agree on PRSS keys once connected, and expand random masks and blinds from them locally,
+    /// rather than take them from preprocessing (see `mpc_algebra::prss`). Not for SPDZ or GSZ
+    /// shares, which can't be expanded from keys.
+    #[structopt(long)]
+    prss: bool,
 }
 
 impl ShareInfo {
-    fn setup(&self) {
+    fn setup<E: PairingEngine>(&self) {
+        MpcMultiNet::set_field_and_curve(
+            std::any::type_name::<E::Fr>(),
+            std::any::type_name::<E>(),
+        );
+        if let Some(ms) = self.peer_timeout_ms {
+            MpcMultiNet::set_timeouts(Timeouts {
+                peer: std::time::Duration::from_millis(ms),
+                heartbeat: std::time::Duration::from_millis(self.heartbeat_ms),
+            });
+        }
+        MpcMultiNet::set_resumable(self.resume);
+        MpcMultiNet::set_king_rotation(if self.rotate_king {
+            KingRotation::RoundRobin
+        } else {
+            KingRotation::Fixed
+        });
+        self.init();
+        if self.prss {
+            let threshold = match self.alg {
+                MpcAlg::Shamir => mpc_algebra::share::shamir::threshold(),
+                MpcAlg::Hbc => MpcMultiNet::n_parties() - 1,
+                alg => panic!("PRSS can't expand {:?} shares", alg),
+            };
+            prss::install(prss::Prss::setup(threshold));
+        }
+    }
+    fn init(&self) {
+        #[cfg(feature = "tls")]
+        if let Some(key) = &self.tls_key {
+            return MpcMultiNet::init_from_file_with_tls(
+                self.hosts.to_str().unwrap(),
+                self.party as usize,
+                key.to_str().unwrap(),
+            );
+        }
         MpcMultiNet::init_from_file(self.hosts.to_str().unwrap(), self.party as usize)
     }
     fn teardown(&self) {
+        prss::uninstall();
         debug!("Stats: {:#?}", MpcMultiNet::stats());
         MpcMultiNet::deinit();
     }
+    fn mpc<E: PairingEngine, S: PairingShare<E>, B: SnarkBench>(
+        &self,
+        computation_size: usize,
+        timed_label: &str,
+    ) {
+        if let Some(path) = &self.triples {
+            preprocessing::install_triple_file::<E::Fr, S::FrShare>(path);
+        }
+        B::mpc::<E, S>(computation_size, timed_label, self.msm_tables);
+        if let Some(src) = preprocessing::uninstall_triple_source::<S::FrShare>() {
+            debug!("Unused preprocessing: {:?}", src.remaining());
+        }
+    }
+    fn selftest<E: PairingEngine, S: PairingShare<E>>(&self) -> Option<Abort> {
+        if let Some(path) = &self.triples {
+            preprocessing::install_triple_file::<E::Fr, S::FrShare>(path);
+        }
+        let report = mpc_plonk::selftest::selftest::<E, S>();
+        println!("{}", report);
+        preprocessing::uninstall_triple_source::<S::FrShare>();
+        report.failure
+    }
+    fn compare<E: PairingEngine, S: PairingShare<E>>(&self, computation_size: usize) {
+        use mpc_snarks::collaborative::{Marlin, Plonk};
+        if let Some(path) = &self.triples {
+            preprocessing::install_triple_file::<E::Fr, S::FrShare>(path);
+        }
+        let n = computation_size;
+        let rows = vec![
+            (
+                <Groth16 as CollaborativeSnark<E>>::NAME,
+                squarings::measure::<Groth16, E, S>(n),
+            ),
+            (
+                <Marlin as CollaborativeSnark<E>>::NAME,
+                squarings::measure::<Marlin, E, S>(n),
+            ),
+            (
+                <Plonk as CollaborativeSnark<E>>::NAME,
+                squarings::measure::<Plonk, E, S>(n),
+            ),
+        ];
+        if let Some(src) = preprocessing::uninstall_triple_source::<S::FrShare>() {
+            debug!("Unused preprocessing: {:?}", src.remaining());
+        }
+        println!(
+            "{:<8} {:>12} {:>8} {:>12} {:>12} {:>10}",
+            "system", "prove", "rounds", "sent (B)", "recv (B)", "proof (B)"
+        );
+        for (name, m) in rows {
+            println!(
+                "{:<8} {:>12.3?} {:>8} {:>12} {:>12} {:>10}",
+                name,
+                m.prove_time,
+                m.rounds(),
+                m.stats.bytes_sent,
+                m.stats.bytes_recv,
+                m.proof_bytes
+            );
+        }
+    }
+    /// Prove with Groth16, sealed to the receiver in `to`, and have the king write the sealed
+    /// proof to `out`.
+    fn seal<E: PairingEngine, S: PairingShare<E>>(
+        &self,
+        computation_size: usize,
+        to: &PathBuf,
+        out: &PathBuf,
+    ) {
+        if let Some(path) = &self.triples {
+            preprocessing::install_triple_file::<E::Fr, S::FrShare>(path);
+        }
+        let to = ReceiverPublicKey::<E>::deserialize(std::fs::File::open(to).unwrap()).unwrap();
+        assert!(to.is_consistent(), "Inconsistent receiver key");
+        let sealed = squarings::prove_sealed::<E, S>(computation_size, &to);
+        if let Some(src) = preprocessing::uninstall_triple_source::<S::FrShare>() {
+            debug!("Unused preprocessing: {:?}", src.remaining());
+        }
+        if MpcMultiNet::am_king() {
+            sealed
+                .serialize(std::fs::File::create(out).unwrap())
+                .unwrap();
+        }
+    }
+    fn run_seal<E: PairingEngine>(
+        &self,
+        computation: Computation,
+        computation_size: usize,
+        to: &PathBuf,
+        out: &PathBuf,
+    ) {
+        match computation {
+            Computation::Squaring => match self.alg {
+                MpcAlg::Spdz => self.seal::<E, mpc_algebra::share::spdz::SpdzPairingShare<E>>(
+                    computation_size,
+                    to,
+                    out,
+                ),
+                MpcAlg::Hbc => self.seal::<E, mpc_algebra::share::add::AdditivePairingShare<E>>(
+                    computation_size,
+                    to,
+                    out,
+                ),
+                MpcAlg::Gsz => self.seal::<E, mpc_algebra::share::gsz20::GszPairingShare<E>>(
+                    computation_size,
+                    to,
+                    out,
+                ),
+                MpcAlg::Shamir => self
+                    .seal::<E, mpc_algebra::share::shamir::ShamirPairingShare<E>>(
+                        computation_size,
+                        to,
+                        out,
+                    ),
+            },
+        }
+    }
+    fn run_compare<E: PairingEngine>(&self, computation: Computation, computation_size: usize) {
+        match computation {
+            Computation::Squaring => match self.alg {
+                MpcAlg::Spdz => self
+                    .compare::<E, mpc_algebra::share::spdz::SpdzPairingShare<E>>(computation_size),
+                MpcAlg::Hbc => self.compare::<E, mpc_algebra::share::add::AdditivePairingShare<E>>(
+                    computation_size,
+                ),
+                MpcAlg::Gsz => self
+                    .compare::<E, mpc_algebra::share::gsz20::GszPairingShare<E>>(computation_size),
+                MpcAlg::Shamir => self
+                    .compare::<E, mpc_algebra::share::shamir::ShamirPairingShare<E>>(
+                        computation_size,
+                    ),
+            },
+        }
+    }
+    fn run_selftest<E: PairingEngine>(&self) -> Option<Abort> {
+        match self.alg {
+            MpcAlg::Spdz => self.selftest::<E, mpc_algebra::share::spdz::SpdzPairingShare<E>>(),
+            MpcAlg::Hbc => self.selftest::<E, mpc_algebra::share::add::AdditivePairingShare<E>>(),
+            MpcAlg::Gsz => self.selftest::<E, mpc_algebra::share::gsz20::GszPairingShare<E>>(),
+            MpcAlg::Shamir => {
+                self.selftest::<E, mpc_algebra::share::shamir::ShamirPairingShare<E>>()
+            }
+        }
+    }
     fn run<E: PairingEngine, B: SnarkBench>(
         &self,
         computation: Computation,
@@ -376,18 +734,23 @@ impl ShareInfo {
     ) {
         match computation {
             Computation::Squaring => match self.alg {
-                MpcAlg::Spdz => B::mpc::<E, mpc_algebra::share::spdz::SpdzPairingShare<E>>(
+                MpcAlg::Spdz => self.mpc::<E, mpc_algebra::share::spdz::SpdzPairingShare<E>, B>(
                     computation_size,
                     timed_label,
                 ),
-                MpcAlg::Hbc => B::mpc::<E, mpc_algebra::share::add::AdditivePairingShare<E>>(
+                MpcAlg::Hbc => self.mpc::<E, mpc_algebra::share::add::AdditivePairingShare<E>, B>(
                     computation_size,
                     timed_label,
                 ),
-                MpcAlg::Gsz => B::mpc::<E, mpc_algebra::share::gsz20::GszPairingShare<E>>(
+                MpcAlg::Gsz => self.mpc::<E, mpc_algebra::share::gsz20::GszPairingShare<E>, B>(
                     computation_size,
                     timed_label,
                 ),
+                MpcAlg::Shamir => self
+                    .mpc::<E, mpc_algebra::share::shamir::ShamirPairingShare<E>, B>(
+                        computation_size,
+                        timed_label,
+                    ),
             },
         }
     }
@@ -399,6 +762,7 @@ arg_enum! {
         Spdz,
         Hbc,
         Gsz,
+        Shamir,
     }
 }
 
@@ -426,18 +790,69 @@ enum FieldOpt {
     },
     Local,
     ArkLocal,
+    /// Collaboratively prove and verify a tiny Plonk circuit (whatever `-p` is), reporting how
+    /// long each phase took. If the proof does not verify, exits with the cause's code (see the
+    /// `Exit codes` section of the Readme).
+    Selftest {
+        #[structopt(flatten)]
+        party_info: ShareInfo,
+    },
+    /// Collaboratively prove the same circuit with every proof system (whatever `-p` is), over the
+    /// same network, and report each one's prove time, rounds, communication, and proof size.
+    Compare {
+        #[structopt(flatten)]
+        party_info: ShareInfo,
+    },
+    /// Generate a receiver's key pair, for `sealed`.
+    Keygen {
+        /// Where to write the secret key
+        #[structopt(long, parse(from_os_str))]
+        key: PathBuf,
+        /// Where to write the public key
+        #[structopt(long, parse(from_os_str))]
+        public_key: PathBuf,
+    },
+    /// Collaboratively prove the circuit with Groth16 (whatever `-p` is), revealing the proof only
+    /// encrypted to a receiver. The king writes it out.
+    Sealed {
+        #[structopt(flatten)]
+        party_info: ShareInfo,
+        /// The receiver's public key (see `keygen`)
+        #[structopt(long, parse(from_os_str))]
+        to: PathBuf,
+        /// Where to write the sealed proof
+        #[structopt(long, parse(from_os_str))]
+        out: PathBuf,
+    },
+    /// Open a sealed proof, as its receiver, and verify it.
+    Unseal {
+        /// The receiver's secret key
+        #[structopt(long, parse(from_os_str))]
+        key: PathBuf,
+        #[structopt(long, parse(from_os_str))]
+        sealed: PathBuf,
+        /// Where to write the proof, as any Groth16 verifier reads it
+        #[structopt(long, parse(from_os_str))]
+        out: PathBuf,
+    },
 }
 
 impl FieldOpt {
-    fn setup(&self) {
+    fn setup<E: PairingEngine>(&self) {
         match self {
-            FieldOpt::Mpc { party_info, .. } => party_info.setup(),
+            FieldOpt::Mpc { party_info, .. }
+            | FieldOpt::Selftest { party_info }
+            | FieldOpt::Compare { party_info }
+            | FieldOpt::Sealed { party_info, .. } => party_info.setup::<E>(),
             _ => {}
         }
     }
     fn teardown(&self) {
         match self {
-            FieldOpt::Mpc { party_info, .. } => party_info.teardown(),
+            FieldOpt::Mpc { party_info, .. }
+            | FieldOpt::Selftest { party_info }
+            | FieldOpt::Compare { party_info }
+            | FieldOpt::Sealed { party_info, .. } => party_info.teardown(),
             _ => {}
         }
         println!("Stats: {:#?}", MpcMultiNet::stats());
@@ -449,18 +864,69 @@ impl FieldOpt {
         b: B,
         timed_label: &str,
     ) {
-        self.setup();
+        mpc_net::catch_abort(|| self.setup::<E>()).unwrap_or_else(|e| abort(e));
         match self {
-            FieldOpt::Mpc { party_info, .. } => {
+            FieldOpt::Mpc { party_info, .. } => mpc_net::catch_abort(|| {
                 party_info.run::<E, B>(computation, computation_size, b, timed_label)
+            })
+            .unwrap_or_else(|e| abort(e)),
+            FieldOpt::Compare { party_info } => {
+                mpc_net::catch_abort(|| party_info.run_compare::<E>(computation, computation_size))
+                    .unwrap_or_else(|e| abort(e))
+            }
+            FieldOpt::Sealed {
+                party_info,
+                to,
+                out,
+            } => mpc_net::catch_abort(|| {
+                party_info.run_seal::<E>(computation, computation_size, to, out)
+            })
+            .unwrap_or_else(|e| abort(e)),
+            FieldOpt::Keygen { key, public_key } => {
+                let sk = ReceiverKey::<E>::rand(&mut rand::thread_rng());
+                sk.public_key()
+                    .serialize(std::fs::File::create(public_key).unwrap())
+                    .unwrap();
+                sk.serialize(std::fs::File::create(key).unwrap()).unwrap();
+            }
+            FieldOpt::Unseal { key, sealed, out } => {
+                let sk = ReceiverKey::<E>::deserialize(std::fs::File::open(key).unwrap()).unwrap();
+                let sealed =
+                    SealedProof::<E>::deserialize(std::fs::File::open(sealed).unwrap()).unwrap();
+                let proof = sk.open(&sealed);
+                mpc_net::catch_abort(|| {
+                    check_verified(squarings::verify_groth16::<E>(computation_size, &proof))
+                })
+                .unwrap_or_else(|e| abort(e));
+                proof
+                    .serialize(std::fs::File::create(out).unwrap())
+                    .unwrap();
+                println!("Proof verified");
             }
             FieldOpt::Local => B::local::<E>(computation_size, timed_label),
             FieldOpt::ArkLocal => B::ark_local::<E>(computation_size, timed_label),
+            FieldOpt::Selftest { party_info } => {
+                let failure = mpc_net::catch_abort(|| party_info.run_selftest::<E>())
+                    .unwrap_or_else(|e| abort(e));
+                self.teardown();
+                if let Some(failure) = failure {
+                    std::process::exit(failure.code.exit_code());
+                }
+                return;
+            }
         }
         self.teardown();
     }
 }
 
+/// Give up, without waiting on the other parties, exiting with the cause's
+/// [AbortCode::exit_code].
+fn abort(e: Abort) -> ! {
+    eprintln!("Aborting: {}", e);
+    MpcMultiNet::deinit();
+    std::process::exit(e.code.exit_code());
+}
+
 #[derive(Debug, StructOpt)]
 #[structopt(name = "proof", about = "Standard and MPC proofs")]
 struct Opt {
@@ -485,6 +951,14 @@ impl Opt {}
 fn main() {
     let opt = Opt::from_args();
     env_logger::init();
+    // Network failures and other aborts are reported by `abort`, not as panics.
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let payload = info.payload();
+        if !payload.is::<MpcNetError>() && !payload.is::<Abort>() {
+            default_hook(info)
+        }
+    }));
     match opt.proof_system {
         ProofSystem::Groth16 => opt.field.run::<ark_bls12_377::Bls12_377, _>(
             opt.computation,
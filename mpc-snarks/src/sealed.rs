@@ -0,0 +1,179 @@
+//! Proofs sealed to a designated receiver.
+//!
+//! Sometimes only the client that commissioned a collaborative proof should learn it. Then the
+//! parties encrypt the proof to the client's [ReceiverPublicKey] while it is still shared, and
+//! reveal only the ciphertext: no party (nor any coalition that could not already open shares)
+//! sees the proof.
+//!
+//! Each group element of the proof is encrypted with ElGamal, under fresh shared randomness:
+//! `(k * G, p + k * pk)`, where `pk = sk * G`. The receiver recovers `p` with `sk`. Each party
+//! contributes to `k` from its own [BlindingRng]: a party that knew `k` would open `p` itself.
+//!
+//! Only Groth16 proofs are sealed. Marlin and Plonk reveal their commitments and evaluations
+//! during proving (each round's challenges are derived from them), so the parties see most of
+//! those proofs anyway.
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{UniformRand, Zero};
+use ark_groth16::Proof;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_std::io::{Read, Write};
+use ark_std::rand::Rng;
+use mpc_algebra::{BlindingRng, MpcPairingEngine, PairingShare, Reveal};
+
+/// A receiver's secret key.
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ReceiverKey<E: PairingEngine> {
+    sk: E::Fr,
+}
+
+/// A receiver's public key: its secret key times each group's generator.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ReceiverPublicKey<E: PairingEngine> {
+    pub g1: E::G1Affine,
+    pub g2: E::G2Affine,
+}
+
+/// An ElGamal encryption of a group element.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Ciphertext<G: AffineCurve> {
+    /// `k * G`
+    pub ephemeral: G,
+    /// `p + k * pk`
+    pub masked: G,
+}
+
+/// A Groth16 proof, each of whose elements is encrypted to a receiver.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct SealedProof<E: PairingEngine> {
+    pub a: Ciphertext<E::G1Affine>,
+    pub b: Ciphertext<E::G2Affine>,
+    pub c: Ciphertext<E::G1Affine>,
+}
+
+impl<E: PairingEngine> ReceiverKey<E> {
+    pub fn rand<R: Rng>(rng: &mut R) -> Self {
+        Self {
+            sk: E::Fr::rand(rng),
+        }
+    }
+
+    pub fn public_key(&self) -> ReceiverPublicKey<E> {
+        ReceiverPublicKey {
+            g1: E::G1Affine::prime_subgroup_generator()
+                .scalar_mul(self.sk)
+                .into_affine(),
+            g2: E::G2Affine::prime_subgroup_generator()
+                .scalar_mul(self.sk)
+                .into_affine(),
+        }
+    }
+
+    /// Decrypt `sealed`. The proof should then be verified: a proof sealed to another key decrypts
+    /// to garbage.
+    pub fn open(&self, sealed: &SealedProof<E>) -> Proof<E> {
+        Proof {
+            a: self.open_point(&sealed.a),
+            b: self.open_point(&sealed.b),
+            c: self.open_point(&sealed.c),
+        }
+    }
+
+    fn open_point<G: AffineCurve<ScalarField = E::Fr>>(&self, ct: &Ciphertext<G>) -> G {
+        (ct.masked.into_projective() - ct.ephemeral.scalar_mul(self.sk)).into_affine()
+    }
+}
+
+impl<E: PairingEngine> ReceiverPublicKey<E> {
+    /// Whether both elements carry the same secret key, so that every element of a proof sealed
+    /// to this key can be opened.
+    pub fn is_consistent(&self) -> bool {
+        !self.g1.is_zero()
+            && E::pairing(self.g1, E::G2Affine::prime_subgroup_generator())
+                == E::pairing(E::G1Affine::prime_subgroup_generator(), self.g2)
+    }
+}
+
+/// Encrypt shared `p` to public key `pk` (in `p`'s group), and reveal only the ciphertext.
+fn seal_point<G>(p: G, pk: G::Base, rng: &mut BlindingRng) -> Ciphertext<G::Base>
+where
+    G: AffineCurve + Reveal,
+    G::Base: AffineCurve,
+{
+    let k = G::ScalarField::rand(rng);
+    let ephemeral = G::prime_subgroup_generator().scalar_mul(k);
+    let pad = G::from_public(pk).scalar_mul(k);
+    Ciphertext {
+        ephemeral: ephemeral.into_affine().reveal(),
+        masked: pad.add_mixed(&p).into_affine().reveal(),
+    }
+}
+
+/// Encrypt shared proof `pf` to `to`, and reveal only the ciphertext.
+///
+/// Every party calls this, with its own `rng`.
+pub fn seal_proof<E: PairingEngine, S: PairingShare<E>>(
+    pf: Proof<MpcPairingEngine<E, S>>,
+    to: &ReceiverPublicKey<E>,
+    rng: &mut BlindingRng,
+) -> SealedProof<E> {
+    SealedProof {
+        a: seal_point(pf.a, to.g1, rng),
+        b: seal_point(pf.b, to.g2, rng),
+        c: seal_point(pf.c, to.g1, rng),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_377::{Bls12_377 as E, G1Projective, G2Projective};
+    use ark_std::test_rng;
+    use mpc_algebra::share::add::AdditivePairingShare;
+    use mpc_net::MpcMultiNet;
+
+    type ME = MpcPairingEngine<E, AdditivePairingShare<E>>;
+
+    fn proof() -> Proof<E> {
+        let rng = &mut test_rng();
+        Proof {
+            a: G1Projective::rand(rng).into_affine(),
+            b: G2Projective::rand(rng).into_affine(),
+            c: G1Projective::rand(rng).into_affine(),
+        }
+    }
+
+    /// `pf`, shared by the king among three parties and sealed to `to`: each party's ciphertext.
+    fn seal(pf: &Proof<E>, to: &ReceiverPublicKey<E>) -> Vec<SealedProof<E>> {
+        MpcMultiNet::simulate(3, |_| {
+            let rng = &mut test_rng();
+            let shared = Proof::<ME> {
+                a: Reveal::king_share(pf.a, rng),
+                b: Reveal::king_share(pf.b, rng),
+                c: Reveal::king_share(pf.c, rng),
+            };
+            seal_proof(shared, to, &mut BlindingRng::new())
+        })
+    }
+
+    #[test]
+    fn seal_open() {
+        let key = ReceiverKey::<E>::rand(&mut test_rng());
+        assert!(key.public_key().is_consistent());
+        let pf = proof();
+        let sealed = seal(&pf, &key.public_key());
+        assert!(sealed.iter().all(|s| s == &sealed[0]));
+        assert_eq!(key.open(&sealed[0]), pf);
+        assert_ne!(sealed[0].a.masked, pf.a);
+        // Fresh randomness each time
+        assert_ne!(seal(&pf, &key.public_key())[0], sealed[0]);
+    }
+
+    #[test]
+    fn open_with_wrong_key() {
+        let rng = &mut test_rng();
+        let (key, other) = (ReceiverKey::<E>::rand(rng), ReceiverKey::<E>::rand(rng));
+        let pf = proof();
+        let sealed = seal(&pf, &key.public_key());
+        assert_ne!(other.open(&sealed[0]), pf);
+    }
+}
@@ -0,0 +1,81 @@
+//! The offline phase: deal Beaver triples to every party, ahead of an MPC proof.
+//!
+//! Writes `<out-dir>/triples_<i>` for each party `i`. Pass party `i` its file with `proof ... mpc
+//! --triples <out-dir>/triples_<i>`.
+use ark_bls12_377::Fr;
+use clap::arg_enum;
+use log::debug;
+use mpc_algebra::bits::STAT_SEC;
+use mpc_algebra::preprocessing::{deal_eda_bits, deal_triples};
+use mpc_algebra::share::{add::AdditiveFieldShare, field::FieldShare, spdz::SpdzFieldShare};
+use structopt::StructOpt;
+
+use std::path::PathBuf;
+
+arg_enum! {
+    #[derive(PartialEq, Debug, Clone, Copy)]
+    pub enum MpcAlg {
+        Spdz,
+        Hbc,
+    }
+}
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "triples", about = "Preprocess Beaver triples for MPC proofs")]
+struct Opt {
+    /// Number of parties
+    #[structopt(long)]
+    parties: usize,
+
+    /// Number of triples
+    #[structopt(long)]
+    triples: usize,
+
+    /// Number of inverse pairs
+    #[structopt(long, default_value = "0")]
+    inv_pairs: usize,
+
+    /// Number of edaBits, for bit decomposition and comparison of shared values
+    #[structopt(long, default_value = "0")]
+    eda_bits: usize,
+
+    /// Bits in each edaBit: enough to decompose values of this many bits, less `STAT_SEC` (40)
+    #[structopt(long, default_value = "104")]
+    eda_bit_len: usize,
+
+    /// Sharing scheme the proof will use
+    #[structopt(long)]
+    alg: MpcAlg,
+
+    /// Directory to write the triple files to
+    #[structopt(long, parse(from_os_str))]
+    out_dir: PathBuf,
+}
+
+impl Opt {
+    fn deal<S: FieldShare<Fr>>(&self) {
+        let rng = &mut rand::thread_rng();
+        let mut files = deal_triples::<Fr, S, _>(self.parties, self.triples, self.inv_pairs, rng);
+        assert!(
+            self.eda_bits == 0 || self.eda_bit_len > STAT_SEC,
+            "edaBits must be longer than STAT_SEC"
+        );
+        deal_eda_bits::<Fr, S, _>(&mut files, self.eda_bits, self.eda_bit_len, rng);
+        std::fs::create_dir_all(&self.out_dir)
+            .unwrap_or_else(|e| panic!("{}: {}", self.out_dir.display(), e));
+        for (i, f) in files.iter().enumerate() {
+            let path = self.out_dir.join(format!("triples_{}", i));
+            debug!("Writing {}", path.display());
+            f.write_to(&path);
+        }
+    }
+}
+
+fn main() {
+    let opt = Opt::from_args();
+    env_logger::init();
+    match opt.alg {
+        MpcAlg::Spdz => opt.deal::<SpdzFieldShare<Fr>>(),
+        MpcAlg::Hbc => opt.deal::<AdditiveFieldShare<Fr>>(),
+    }
+}
@@ -0,0 +1,158 @@
+//! Collaborative Plonk proofs of a squaring circuit, with the same prover code under each security
+//! model (see [mpc_algebra::model]).
+//!
+//! The parties are processes: each test spawns this binary once per party, running just that
+//! test, with the party's id and the hosts file in the environment.
+use ark_ff::{Field, UniformRand};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_relations::{lc, r1cs::Variable};
+use ark_std::test_rng;
+use mpc_algebra::{channel, MpcSecurityModel, Reveal};
+use mpc_net::{catch_abort, AbortCode, MpcMultiNet, MpcNet};
+use mpc_snarks::collaborative::{CollaborativeSnark, MpcFr, Plonk};
+use std::net::TcpListener;
+use std::process::Command;
+
+type E = ark_bls12_377::Bls12_377;
+type Fr = ark_bls12_377::Fr;
+
+const N_PARTIES: usize = 2;
+const SQUARINGS: usize = 8;
+const PARTY: &str = "SECURITY_MODELS_PARTY";
+const HOSTS: &str = "SECURITY_MODELS_HOSTS";
+
+/// A chain of squarings, whose last value is public.
+struct Squarings<F> {
+    chain: Vec<Option<F>>,
+}
+
+impl<F: Field> ConstraintSynthesizer<F> for Squarings<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let n = self.chain.len();
+        let vars = self
+            .chain
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                let v = || v.ok_or(SynthesisError::AssignmentMissing);
+                if i + 1 == n {
+                    cs.new_input_variable(v)
+                } else {
+                    cs.new_witness_variable(v)
+                }
+            })
+            .collect::<Result<Vec<Variable>, _>>()?;
+        for w in vars.windows(2) {
+            cs.enforce_constraint(lc!() + w[0], lc!() + w[0], lc!() + w[1])?;
+        }
+        Ok(())
+    }
+}
+
+/// Run the test `name` as every party, each in a process of its own, and check that each proved.
+fn as_parties(name: &str) {
+    let dir = std::env::temp_dir().join(format!("security-models-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let hosts = dir.join("hosts");
+    let ports: Vec<_> = (0..N_PARTIES)
+        .map(|_| TcpListener::bind("127.0.0.1:0").unwrap())
+        .map(|l| l.local_addr().unwrap().to_string())
+        .collect();
+    std::fs::write(&hosts, ports.join("\n")).unwrap();
+    let parties: Vec<_> = (0..N_PARTIES)
+        .map(|id| {
+            Command::new(std::env::current_exe().unwrap())
+                .args(&[name, "--exact"])
+                .env(PARTY, id.to_string())
+                .env(HOSTS, &hosts)
+                .spawn()
+                .unwrap()
+        })
+        .collect();
+    for (id, mut p) in parties.into_iter().enumerate() {
+        assert!(p.wait().unwrap().success(), "party {} failed", id);
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// As one party, prove the squarings under model `M`, and verify the proof.
+///
+/// If `deviate`, the king adds one to its share of the first value, as no other party does: the
+/// proof must abort instead.
+fn prove<M: MpcSecurityModel>(party: usize, hosts: &str, deviate: bool) {
+    MpcMultiNet::init_from_file(hosts, party);
+    let rng = &mut test_rng();
+    let params = Plonk::setup(
+        Squarings::<Fr> {
+            chain: vec![None; SQUARINGS + 1],
+        },
+        rng,
+    )
+    .unwrap();
+    let (pk, vk) = <Plonk as CollaborativeSnark<E>>::index(
+        &params,
+        Squarings::<Fr> {
+            chain: vec![None; SQUARINGS + 1],
+        },
+    )
+    .unwrap();
+    let chain: Vec<Fr> = std::iter::successors(Some(Fr::rand(rng)), |a| Some(a.square()))
+        .take(SQUARINGS + 1)
+        .collect();
+    let end = *chain.last().unwrap();
+    let mut shared = MpcFr::<E, M::Share<E>>::king_share_batch(chain, rng);
+    if deviate && MpcMultiNet::am_king() {
+        shared[0] += MpcFr::<E, M::Share<E>>::from(1u64);
+    }
+    let circuit = Squarings {
+        chain: shared.into_iter().map(Some).collect(),
+    };
+    if deviate {
+        let aborted = catch_abort(|| {
+            channel::without_cheating(|| Plonk::prove_in::<M, _, _>(&pk, circuit, rng))
+        });
+        assert_eq!(
+            aborted.err().map(|a| a.code),
+            Some(AbortCode::MacCheckFailed)
+        );
+        MpcMultiNet::deinit();
+        return;
+    }
+    let proof =
+        channel::without_cheating(|| Plonk::prove_in::<M, _, _>(&pk, circuit, rng)).unwrap();
+    assert!(
+        Plonk::verify(&vk, &[end], &proof).unwrap(),
+        "{} proof",
+        M::NAME
+    );
+    MpcMultiNet::deinit();
+}
+
+fn run<M: MpcSecurityModel>(name: &str, deviate: bool) {
+    match std::env::var(PARTY) {
+        Ok(party) => prove::<M>(
+            party.parse().unwrap(),
+            &std::env::var(HOSTS).unwrap(),
+            deviate,
+        ),
+        Err(_) => as_parties(name),
+    }
+}
+
+#[cfg(feature = "semi-honest")]
+#[test]
+fn semi_honest() {
+    run::<mpc_algebra::model::SemiHonest>("semi_honest", false);
+}
+
+#[cfg(feature = "malicious")]
+#[test]
+fn malicious() {
+    run::<mpc_algebra::model::Malicious>("malicious", false);
+}
+
+#[cfg(feature = "malicious")]
+#[test]
+fn malicious_deviation_aborts() {
+    run::<mpc_algebra::model::Malicious>("malicious_deviation_aborts", true);
+}
@@ -0,0 +1,188 @@
+//! Derives for `MpcWire` (from `mpc-trait`) and `Reveal` (from `mpc-algebra`).
+//!
+//! Both work field by field, on structs (with named or unnamed fields) and enums, so each field's
+//! type must implement the derived trait. Every type parameter gets a bound on the trait, and, for
+//! `Reveal`, is replaced by its `Base` in `Self::Base`: a `Proof<F, C>` reveals to a
+//! `Proof<F::Base, C::Base>`.
+#![warn(unused, future_incompatible, nonstandard_style, rust_2018_idioms)]
+#![forbid(unsafe_code)]
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    ext::IdentExt, parse_macro_input, parse_quote, Data, DeriveInput, Fields, GenericParam,
+    Generics, Ident, Member, Path,
+};
+
+#[proc_macro_derive(MpcWire)]
+pub fn derive_mpc_wire(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    proc_macro::TokenStream::from(impl_mpc_wire(&ast))
+}
+
+#[proc_macro_derive(Reveal)]
+pub fn derive_reveal(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    proc_macro::TokenStream::from(impl_reveal(&ast))
+}
+
+/// A constructor of the type: the struct itself, or one enum variant.
+struct Constructor {
+    path: Path,
+    /// Each field, with the name it is bound to in patterns
+    fields: Vec<(Member, Ident)>,
+}
+
+impl Constructor {
+    fn new(path: Path, fields: &Fields) -> Self {
+        let fields = fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| match &f.ident {
+                Some(ident) => (
+                    Member::Named(ident.clone()),
+                    format_ident!("f_{}", ident.unraw()),
+                ),
+                None => (Member::Unnamed(i.into()), format_ident!("f_{}", i)),
+            })
+            .collect();
+        Self { path, fields }
+    }
+
+    /// A pattern binding every field.
+    fn pattern(&self) -> TokenStream {
+        let path = &self.path;
+        let (members, bindings): (Vec<_>, Vec<_>) = self.fields.iter().cloned().unzip();
+        quote! { #path { #(#members: #bindings),* } }
+    }
+
+    /// An expression building this constructor from `f` applied to each binding.
+    fn build(&self, f: impl Fn(&Ident) -> TokenStream) -> TokenStream {
+        let path = &self.path;
+        let members = self.fields.iter().map(|(m, _)| m);
+        let values = self.fields.iter().map(|(_, b)| f(b));
+        quote! { #path { #(#members: #values),* } }
+    }
+
+    fn bindings(&self) -> impl Iterator<Item = &Ident> {
+        self.fields.iter().map(|(_, b)| b)
+    }
+}
+
+fn constructors(ast: &DeriveInput) -> Vec<Constructor> {
+    let name = &ast.ident;
+    match &ast.data {
+        Data::Struct(s) => vec![Constructor::new(parse_quote!(#name), &s.fields)],
+        Data::Enum(e) => e
+            .variants
+            .iter()
+            .map(|v| {
+                let variant = &v.ident;
+                Constructor::new(parse_quote!(#name::#variant), &v.fields)
+            })
+            .collect(),
+        Data::Union(_) => panic!("Unions are not supported"),
+    }
+}
+
+/// `generics`, with every type parameter bounded by `bound`.
+fn bounded(generics: &Generics, bound: Path) -> Generics {
+    let mut generics = generics.clone();
+    let params: Vec<Ident> = generics.type_params().map(|p| p.ident.clone()).collect();
+    let where_clause = generics.make_where_clause();
+    for p in params {
+        where_clause.predicates.push(parse_quote!(#p: #bound));
+    }
+    generics
+}
+
+fn impl_mpc_wire(ast: &DeriveInput) -> TokenStream {
+    let name = &ast.ident;
+    let generics = bounded(&ast.generics, parse_quote!(::mpc_trait::MpcWire));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let cons = constructors(ast);
+
+    let publicize_arms = cons.iter().map(|c| {
+        let pattern = c.pattern();
+        let bindings = c.bindings();
+        quote! { #pattern => { #(::mpc_trait::MpcWire::publicize(#bindings);)* } }
+    });
+    let is_shared_arms = cons.iter().map(|c| {
+        let pattern = c.pattern();
+        let bindings = c.bindings();
+        quote! { #pattern => false #(|| ::mpc_trait::MpcWire::is_shared(#bindings))* }
+    });
+
+    quote! {
+        impl #impl_generics ::mpc_trait::MpcWire for #name #ty_generics #where_clause {
+            fn publicize(&mut self) {
+                match self {
+                    #(#publicize_arms)*
+                }
+            }
+            fn is_shared(&self) -> bool {
+                match self {
+                    #(#is_shared_arms,)*
+                }
+            }
+        }
+    }
+}
+
+fn impl_reveal(ast: &DeriveInput) -> TokenStream {
+    let name = &ast.ident;
+    let generics = bounded(&ast.generics, parse_quote!(::mpc_algebra::Reveal));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let base_params = ast.generics.params.iter().map(|p| match p {
+        GenericParam::Type(t) => {
+            let ident = &t.ident;
+            quote! { <#ident as ::mpc_algebra::Reveal>::Base }
+        }
+        GenericParam::Lifetime(l) => {
+            let lifetime = &l.lifetime;
+            quote! { #lifetime }
+        }
+        GenericParam::Const(c) => {
+            let ident = &c.ident;
+            quote! { #ident }
+        }
+    });
+    let cons = constructors(ast);
+
+    // `match scrutinee { C { .. } => C { field: method(binding), .. }, .. }`
+    let map = |scrutinee: TokenStream, method: TokenStream| {
+        let arms = cons.iter().map(|c| {
+            let pattern = c.pattern();
+            let value = c.build(|b| quote! { ::mpc_algebra::Reveal::#method(#b) });
+            quote! { #pattern => #value }
+        });
+        quote! {
+            match #scrutinee {
+                #(#arms,)*
+            }
+        }
+    };
+    let reveal = map(quote!(self), quote!(reveal));
+    let from_public = map(quote!(other), quote!(from_public));
+    let from_add_shared = map(quote!(other), quote!(from_add_shared));
+    let unwrap_as_public = map(quote!(self), quote!(unwrap_as_public));
+
+    quote! {
+        impl #impl_generics ::mpc_algebra::Reveal for #name #ty_generics #where_clause {
+            type Base = #name<#(#base_params),*>;
+
+            fn reveal(self) -> Self::Base {
+                #reveal
+            }
+            fn from_public(other: Self::Base) -> Self {
+                #from_public
+            }
+            fn from_add_shared(other: Self::Base) -> Self {
+                #from_add_shared
+            }
+            fn unwrap_as_public(self) -> Self::Base {
+                #unwrap_as_public
+            }
+        }
+    }
+}
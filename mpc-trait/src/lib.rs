@@ -2,6 +2,10 @@
 
 use std::borrow::Cow;
 
+/// `#[derive(MpcWire)]`, and `#[derive(Reveal)]` for `mpc_algebra::Reveal`.
+#[cfg(feature = "derive")]
+pub use mpc_trait_derive::*;
+
 pub trait MpcWire: Clone {
     type Public = Self;
     fn publicize(&mut self) {}
@@ -22,6 +26,7 @@ pub trait MpcWire: Clone {
 
 
 impl<T> MpcWire for std::marker::PhantomData<T> {}
+impl MpcWire for u64 {}
 
 impl<A: MpcWire, B: MpcWire> MpcWire for (A, B) {
     struct_mpc_wire_impl!((A, B); (A, 0), (B, 1));
@@ -122,7 +122,7 @@ impl<G: AffineCurve> PCCommitment for Commitment<G> {
     }
 
     fn has_degree_bound(&self) -> bool {
-        false
+        self.shifted_comm.is_some()
     }
 
     fn size_in_bytes(&self) -> usize {
@@ -3,8 +3,8 @@ use crate::{BatchLCProof, Error, Evaluations, QuerySet, UVPolynomial};
 use crate::{LabeledCommitment, LabeledPolynomial, LinearCombination};
 use crate::{PCCommitterKey, PCRandomness, PCUniversalParams, PolynomialCommitment};
 
-use ark_ec::{msm::VariableBaseMSM, AffineCurve, ProjectiveCurve};
-use ark_ff::{to_bytes, Field, One, PrimeField, UniformRand, Zero};
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{to_bytes, Field, One, UniformRand, Zero};
 use ark_std::rand::RngCore;
 use ark_std::{convert::TryInto, format, marker::PhantomData, vec};
 
@@ -15,6 +15,9 @@ pub use data_structures::*;
 use rayon::prelude::*;
 
 use digest::Digest;
+use mpc_algebra::channel::MpcSerNet;
+use mpc_net::MpcMultiNet;
+use mpc_trait::MpcWire;
 
 /// A polynomial commitment scheme based on the hardness of the
 /// discrete logarithm problem in prime-order groups.
@@ -41,17 +44,15 @@ impl<G: AffineCurve, D: Digest, P: UVPolynomial<G::ScalarField>> InnerProductArg
 
     /// Create a Pedersen commitment to `scalars` using the commitment key `comm_key`.
     /// Optionally, randomize the commitment using `hiding_generator` and `randomizer`.
+    ///
+    /// The scalars may be shared (the key never is).
     fn cm_commit(
         comm_key: &[G],
         scalars: &[G::ScalarField],
         hiding_generator: Option<G>,
         randomizer: Option<G::ScalarField>,
     ) -> G::Projective {
-        let scalars_bigint = ark_std::cfg_iter!(scalars)
-            .map(|s| s.into_repr())
-            .collect::<Vec<_>>();
-
-        let mut comm = VariableBaseMSM::multi_scalar_mul(comm_key, &scalars_bigint);
+        let mut comm = G::multi_scalar_mul(comm_key, scalars);
 
         if randomizer.is_some() {
             assert!(hiding_generator.is_some());
@@ -72,7 +73,10 @@ impl<G: AffineCurve, D: Digest, P: UVPolynomial<G::ScalarField>> InnerProductArg
             i += 1;
         }
 
-        challenge.unwrap()
+        // A hash output is public, even if the field wraps it as a share.
+        let mut challenge = challenge.unwrap();
+        challenge.publicize();
+        challenge
     }
 
     #[inline]
@@ -213,12 +217,20 @@ impl<G: AffineCurve, D: Digest, P: UVPolynomial<G::ScalarField>> InnerProductArg
             P::zero()
         } else {
             let mut shifted_polynomial_coeffs =
-                vec![G::ScalarField::zero(); ck.supported_degree() - degree_bound];
+                vec![Self::zero_like(p.coeffs()); ck.supported_degree() - degree_bound];
             shifted_polynomial_coeffs.extend_from_slice(&p.coeffs());
             P::from_coefficients_vec(shifted_polynomial_coeffs)
         }
     }
 
+    /// A zero that is shared iff `coeffs` are, for padding.
+    ///
+    /// Parties may hold shared polynomials of different lengths, so public padding would leave
+    /// them disagreeing about which coefficients are shared.
+    fn zero_like(coeffs: &[G::ScalarField]) -> G::ScalarField {
+        coeffs.first().map_or(G::ScalarField::zero(), |c| *c - c)
+    }
+
     fn combine_shifted_rand(
         combined_rand: Option<G::ScalarField>,
         new_rand: Option<G::ScalarField>,
@@ -280,15 +292,16 @@ impl<G: AffineCurve, D: Digest, P: UVPolynomial<G::ScalarField>> InnerProductArg
         return commitments;
     }
 
-    fn sample_generators(num_generators: usize) -> Vec<G> {
+    /// Hash `seed` to `num_generators` group elements, with no known relations between them.
+    fn sample_generators_from_seed(seed: &[u8], num_generators: usize) -> Vec<G> {
         let generators: Vec<_> = ark_std::cfg_into_iter!(0..num_generators)
             .map(|i| {
                 let i = i as u64;
-                let mut hash = D::digest(&to_bytes![&Self::PROTOCOL_NAME, i].unwrap());
+                let mut hash = D::digest(&to_bytes![&seed, i].unwrap());
                 let mut g = G::from_random_bytes(&hash);
                 let mut j = 0u64;
                 while g.is_none() {
-                    hash = D::digest(&to_bytes![&Self::PROTOCOL_NAME, i, j].unwrap());
+                    hash = D::digest(&to_bytes![&seed, i, j].unwrap());
                     g = G::from_random_bytes(&hash);
                     j += 1;
                 }
@@ -301,6 +314,38 @@ impl<G: AffineCurve, D: Digest, P: UVPolynomial<G::ScalarField>> InnerProductArg
     }
 }
 
+impl<G: AffineCurve, D: Digest, P: UVPolynomial<G::ScalarField>> InnerProductArgPC<G, D, P> {
+    /// Derive the parameters by hashing `seed` to curve points.
+    ///
+    /// [PolynomialCommitment::setup] uses the fixed seed [Self::PROTOCOL_NAME].
+    pub fn setup_from_seed(max_degree: usize, seed: &[u8]) -> UniversalParams<G> {
+        // Ensure that max_degree + 1 is a power of 2
+        let max_degree = (max_degree + 1).next_power_of_two() - 1;
+
+        let setup_time = start_timer!(|| format!("Sampling {} generators", max_degree + 3));
+        let mut generators = Self::sample_generators_from_seed(seed, max_degree + 3);
+        end_timer!(setup_time);
+
+        let h = generators.pop().unwrap();
+        let s = generators.pop().unwrap();
+
+        UniversalParams {
+            comm_key: generators,
+            h,
+            s,
+        }
+    }
+
+    /// Jointly derive the parameters, from a seed that the MPC parties sample together.
+    ///
+    /// Since no party controls the seed, no party can know a relation among the generators
+    /// (including the hiding generator), even if the hash-to-curve is only heuristically random.
+    pub fn joint_setup(max_degree: usize) -> UniversalParams<G> {
+        let seed = [Self::PROTOCOL_NAME, &MpcMultiNet::joint_seed()].concat();
+        Self::setup_from_seed(max_degree, &seed)
+    }
+}
+
 impl<G, D, P> PolynomialCommitment<G::ScalarField, P> for InnerProductArgPC<G, D, P>
 where
     G: AffineCurve,
@@ -323,23 +368,7 @@ where
         _: Option<usize>,
         _rng: &mut R,
     ) -> Result<Self::UniversalParams, Self::Error> {
-        // Ensure that max_degree + 1 is a power of 2
-        let max_degree = (max_degree + 1).next_power_of_two() - 1;
-
-        let setup_time = start_timer!(|| format!("Sampling {} generators", max_degree + 3));
-        let mut generators = Self::sample_generators(max_degree + 3);
-        end_timer!(setup_time);
-
-        let h = generators.pop().unwrap();
-        let s = generators.pop().unwrap();
-
-        let pp = UniversalParams {
-            comm_key: generators,
-            h,
-            s,
-        };
-
-        Ok(pp)
+        Ok(Self::setup_from_seed(max_degree, Self::PROTOCOL_NAME))
     }
 
     fn trim(
@@ -539,7 +568,9 @@ where
 
         end_timer!(combine_time);
 
-        let combined_v = combined_polynomial.evaluate(point);
+        // The verifier learns the combined evaluation (and so does the transcript).
+        let mut combined_v = combined_polynomial.evaluate(point);
+        combined_v.publicize();
 
         // Pad the coefficients to the appropriate vector size
         let d = ck.supported_degree();
@@ -569,7 +600,9 @@ where
                 hiding_commitment_proj,
             ]);
             hiding_commitment = Some(batch.pop().unwrap());
+            hiding_commitment.publicize();
             combined_commitment = batch.pop().unwrap();
+            combined_commitment.publicize();
 
             let hiding_challenge = Self::compute_random_oracle_challenge(
                 &ark_ff::to_bytes![
@@ -598,6 +631,7 @@ where
             start_timer!(|| format!("Generating proof for degree {} combined polynomial", d + 1));
 
         combined_commitment = combined_commitment_proj.into_affine();
+        combined_commitment.publicize();
 
         // ith challenge
         let mut round_challenge = Self::compute_random_oracle_challenge(
@@ -609,8 +643,9 @@ where
         // Pads the coefficients with zeroes to get the number of coeff to be d+1
         let mut coeffs = combined_polynomial.coeffs().to_vec();
         if coeffs.len() < d + 1 {
+            let zero = Self::zero_like(&coeffs);
             for _ in coeffs.len()..(d + 1) {
-                coeffs.push(G::ScalarField::zero());
+                coeffs.push(zero);
             }
         }
         let mut coeffs = coeffs.as_mut_slice();
@@ -650,7 +685,8 @@ where
             let r = Self::cm_commit(key_r, coeffs_l, None, None)
                 + &h_prime.scalar_mul(Self::inner_product(coeffs_l, z_r));
 
-            let lr = G::Projective::batch_normalization_into_affine(&[l, r]);
+            let mut lr = G::Projective::batch_normalization_into_affine(&[l, r]);
+            lr.publicize();
             l_vec.push(lr[0]);
             r_vec.push(lr[1]);
 
@@ -1060,6 +1096,20 @@ mod tests {
             .expect("test failed for ed_on_bls12_381-blake2s");
     }
 
+    #[test]
+    fn seeded_setup_test() {
+        use crate::PolynomialCommitment;
+        let rng = &mut ark_std::test_rng();
+        let pp = PC_JJB2S::setup(7, None, rng).unwrap();
+        let same = PC_JJB2S::setup_from_seed(7, PC_JJB2S::PROTOCOL_NAME);
+        assert_eq!(pp.comm_key, same.comm_key);
+        assert_eq!((pp.h, pp.s), (same.h, same.s));
+        let other = PC_JJB2S::setup_from_seed(7, b"another seed");
+        assert_eq!(pp.comm_key.len(), other.comm_key.len());
+        assert!(pp.comm_key.iter().zip(&other.comm_key).all(|(a, b)| a != b));
+        assert_ne!(pp.s, other.s);
+    }
+
     #[test]
     fn constant_poly_test() {
         use crate::tests::*;
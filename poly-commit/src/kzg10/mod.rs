@@ -5,7 +5,7 @@
 //! proposed by Kate, Zaverucha, and Goldberg ([KZG11](http://cacr.uwaterloo.ca/techreports/2010/cacr2010-10.pdf)).
 //! This construction achieves extractability in the algebraic group model (AGM).
 
-use crate::{BTreeMap, Error, LabeledPolynomial, PCRandomness, ToString};
+use crate::{BTreeMap, Error, LabeledPolynomial, PCRandomness, ToString, Vec};
 use ark_ec::msm::FixedBaseMSM;
 use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
 use ark_ff::{One, PrimeField, UniformRand, Zero};
@@ -192,6 +192,58 @@ where
         Ok((Commitment(commitment.into()), randomness))
     }
 
+    /// Commit to each of `polynomials`, under its own powers and hiding bound, as
+    /// [KZG10::commit] does (drawing the same randomness, in order), but with a single
+    /// [AffineCurve::batch_multi_scalar_mul] for all of them.
+    pub fn batch_commit(
+        polynomials: &[(&Powers<E>, &P, Option<usize>)],
+        mut rng: Option<&mut dyn RngCore>,
+    ) -> Result<Vec<(Commitment<E>, Randomness<E::Fr, P>)>, Error> {
+        let commit_time =
+            start_timer!(|| format!("Committing to {} polynomials", polynomials.len()));
+
+        let mut randomness = Vec::with_capacity(polynomials.len());
+        for (powers, polynomial, hiding_bound) in polynomials {
+            Self::check_degree_is_too_large(polynomial.degree(), powers.size())?;
+            let mut r = Randomness::<E::Fr, P>::empty();
+            if let Some(hiding_degree) = hiding_bound {
+                let rng = rng.as_mut().ok_or(Error::MissingRng)?;
+                r = Randomness::rand(*hiding_degree, false, None, rng);
+                Self::check_hiding_bound(
+                    r.blinding_polynomial.degree(),
+                    powers.powers_of_gamma_g.len(),
+                )?;
+            }
+            randomness.push(r);
+        }
+
+        let msm_time = start_timer!(|| "MSM to compute commitments to plaintext and random polys");
+        let msms: Vec<(&[E::G1Affine], &[E::Fr])> = polynomials
+            .iter()
+            .zip(&randomness)
+            .flat_map(|((powers, polynomial, _), r)| {
+                vec![
+                    (&powers.powers_of_g[..], polynomial.coeffs()),
+                    (
+                        &powers.powers_of_gamma_g[..],
+                        r.blinding_polynomial.coeffs(),
+                    ),
+                ]
+            })
+            .collect();
+        let products = <E::G1Affine as AffineCurve>::batch_multi_scalar_mul(&msms);
+        end_timer!(msm_time);
+
+        let commitments = products.chunks(2).map(|c| {
+            let mut commitment = c[0];
+            commitment.add_assign_mixed(&c[1].into_affine());
+            Commitment(commitment.into())
+        });
+        let commitments = commitments.zip(randomness).collect();
+        end_timer!(commit_time);
+        Ok(commitments)
+    }
+
     /// Compute witness polynomial.
     ///
     /// The witness polynomial w(x) the quotient of the division (p(x) - p(z)) / (x - z)
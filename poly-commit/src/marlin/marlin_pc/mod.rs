@@ -43,8 +43,11 @@ pub(crate) fn shift_polynomial<E: PairingEngine, P: UVPolynomial<E::Fr>>(
             .expect("Polynomial requires degree bounds, but `ck` does not support any");
         let largest_enforced_degree_bound = enforced_degree_bounds.last().unwrap();
 
+        // A zero that is shared iff the coefficients are (as in the IPA's shift), so that under
+        // MPC, the shifted witness's MSM does not mix shared and public scalars.
+        let zero = p.coeffs()[0] - &p.coeffs()[0];
         let mut shifted_polynomial_coeffs =
-            vec![E::Fr::zero(); largest_enforced_degree_bound - degree_bound];
+            vec![zero; largest_enforced_degree_bound - degree_bound];
         shifted_polynomial_coeffs.extend_from_slice(&p.coeffs());
         P::from_coefficients_vec(shifted_polynomial_coeffs)
     }
@@ -186,56 +189,59 @@ where
         let rng = &mut crate::optional_rng::OptionalRng(rng);
         let commit_time = start_timer!(|| "Committing to polynomials");
 
-        let mut commitments = Vec::new();
-        let mut randomness = Vec::new();
-
-        for p in polynomials {
-            let label = p.label();
-            let degree_bound = p.degree_bound();
-            let hiding_bound = p.hiding_bound();
-            let polynomial: &P = p.polynomial();
-
-            let enforced_degree_bounds: Option<&[usize]> = ck
-                .enforced_degree_bounds
-                .as_ref()
-                .map(|bounds| bounds.as_slice());
+        let enforced_degree_bounds: Option<&[usize]> = ck
+            .enforced_degree_bounds
+            .as_ref()
+            .map(|bounds| bounds.as_slice());
+        let polynomials: Vec<_> = polynomials.into_iter().collect();
+        let mut shifted_powers = Vec::with_capacity(polynomials.len());
+        for p in &polynomials {
             kzg10::KZG10::<E, P>::check_degrees_and_bounds(
                 ck.supported_degree(),
                 ck.max_degree,
                 enforced_degree_bounds,
-                &p,
+                p,
             )?;
+            shifted_powers.push(match p.degree_bound() {
+                Some(degree_bound) => Some(
+                    ck.shifted_powers(degree_bound)
+                        .ok_or(Error::UnsupportedDegreeBound(degree_bound))?,
+                ),
+                None => None,
+            });
+        }
 
-            let commit_time = start_timer!(|| format!(
-                "Polynomial {} of degree {}, degree bound {:?}, and hiding bound {:?}",
-                label,
-                polynomial.degree(),
-                degree_bound,
-                hiding_bound,
-            ));
+        // Each polynomial, then its shift under its degree bound (if any), in one batch
+        let powers = ck.powers();
+        let mut to_commit = Vec::new();
+        for (p, shifted_powers) in polynomials.iter().zip(&shifted_powers) {
+            to_commit.push((&powers, p.polynomial(), p.hiding_bound()));
+            if let Some(shifted_powers) = shifted_powers {
+                to_commit.push((shifted_powers, p.polynomial(), p.hiding_bound()));
+            }
+        }
+        let mut committed = kzg10::KZG10::batch_commit(&to_commit, Some(rng))?.into_iter();
 
-            let (comm, rand) =
-                kzg10::KZG10::commit(&ck.powers(), polynomial, hiding_bound, Some(rng))?;
-            let (shifted_comm, shifted_rand) = if let Some(degree_bound) = degree_bound {
-                let shifted_powers = ck
-                    .shifted_powers(degree_bound)
-                    .ok_or(Error::UnsupportedDegreeBound(degree_bound))?;
-                let (shifted_comm, shifted_rand) =
-                    kzg10::KZG10::commit(&shifted_powers, &polynomial, hiding_bound, Some(rng))?;
-                (Some(shifted_comm), Some(shifted_rand))
-            } else {
-                (None, None)
+        let mut commitments = Vec::with_capacity(polynomials.len());
+        let mut randomness = Vec::with_capacity(polynomials.len());
+        for p in polynomials {
+            let (comm, rand) = committed.next().unwrap();
+            let (shifted_comm, shifted_rand) = match p.degree_bound() {
+                Some(_) => {
+                    let (shifted_comm, shifted_rand) = committed.next().unwrap();
+                    (Some(shifted_comm), Some(shifted_rand))
+                }
+                None => (None, None),
             };
 
             let comm = Commitment { comm, shifted_comm };
             let rand = Randomness { rand, shifted_rand };
             commitments.push(LabeledCommitment::new(
-                label.to_string(),
+                p.label().to_string(),
                 comm,
-                degree_bound,
+                p.degree_bound(),
             ));
             randomness.push(rand);
-            end_timer!(commit_time);
         }
         end_timer!(commit_time);
         Ok((commitments, randomness))
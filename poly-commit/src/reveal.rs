@@ -7,7 +7,7 @@ use mpc_algebra::*;
 
 use std::rc::Rc;
 
-use crate::{kzg10, marlin_pc, BatchLCProof, LabeledCommitment, LabeledPolynomial, PCCommitment};
+use crate::{ipa_pc, kzg10, marlin_pc, BatchLCProof, LabeledCommitment, LabeledPolynomial, PCCommitment};
 use marlin_pc::*;
 
 impl<E: PairingEngine, S: PairingShare<E>> Reveal for Commitment<MpcPairingEngine<E, S>> {
@@ -105,6 +105,31 @@ impl<E: PairingEngine, S: PairingShare<E>> Reveal for CommitterKey<MpcPairingEng
     struct_reveal_simp_impl!(CommitterKey; powers, shifted_powers, powers_of_gamma_g, enforced_degree_bounds, max_degree);
 }
 
+impl<C: AffineGroup, S: CurveShare<C>> Reveal for ipa_pc::Commitment<MpcCurveAffine<C, S>> {
+    type Base = ipa_pc::Commitment<C>;
+    struct_reveal_simp_impl!(ipa_pc::Commitment; comm, shifted_comm);
+}
+
+impl<C: AffineGroup, S: CurveShare<C>> Reveal for ipa_pc::Randomness<MpcCurveAffine<C, S>> {
+    type Base = ipa_pc::Randomness<C>;
+    struct_reveal_simp_impl!(ipa_pc::Randomness; rand, shifted_rand);
+}
+
+impl<C: AffineGroup, S: CurveShare<C>> Reveal for ipa_pc::Proof<MpcCurveAffine<C, S>> {
+    type Base = ipa_pc::Proof<C>;
+    struct_reveal_simp_impl!(ipa_pc::Proof; l_vec, r_vec, final_comm_key, c, hiding_comm, rand);
+}
+
+impl<C: AffineGroup, S: CurveShare<C>> Reveal for ipa_pc::CommitterKey<MpcCurveAffine<C, S>> {
+    type Base = ipa_pc::CommitterKey<C>;
+    struct_reveal_simp_impl!(ipa_pc::CommitterKey; comm_key, h, s, max_degree);
+}
+
+impl<C: AffineGroup, S: CurveShare<C>> Reveal for ipa_pc::UniversalParams<MpcCurveAffine<C, S>> {
+    type Base = ipa_pc::UniversalParams<C>;
+    struct_reveal_simp_impl!(ipa_pc::UniversalParams; comm_key, h, s);
+}
+
 impl<C: PCCommitment + Reveal> Reveal for LabeledCommitment<C>
 where
     C::Base: PCCommitment,